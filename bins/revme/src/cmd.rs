@@ -2,6 +2,7 @@ pub mod bytecode;
 pub mod eofvalidation;
 pub mod evmrunner;
 pub mod statetest;
+pub mod trace_diff;
 
 use structopt::{clap::AppSettings, StructOpt};
 
@@ -19,6 +20,8 @@ pub enum MainCmd {
     Evm(evmrunner::Cmd),
     #[structopt(alias = "bc", about = "Prints the opcodes of an hex Bytecodes.")]
     Bytecode(bytecode::Cmd),
+    #[structopt(about = "Compares two EIP-3155 traces and reports their first divergence")]
+    TraceDiff(trace_diff::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +30,8 @@ pub enum Error {
     Statetest(#[from] statetest::Error),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    TraceDiff(#[from] trace_diff::Error),
     #[error("Eof validation failed: {:?}/{total_tests}", total_tests-failed_test)]
     EofValidation {
         failed_test: usize,
@@ -46,6 +51,7 @@ impl MainCmd {
                 cmd.run();
                 Ok(())
             }
+            Self::TraceDiff(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
@@ -121,3 +121,15 @@ pub fn run_test(path: &Path) -> Result<(), Error> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eest_eof_validation_vectors() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/eof_suite/eest/eof_tests/osaka");
+        run_test(&path).unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+pub mod diff;
 pub mod merkle_trie;
 pub mod models;
 mod runner;
@@ -5,7 +6,7 @@ pub mod utils;
 
 pub use runner::TestError as Error;
 
-use runner::{find_all_json_tests, run, TestError};
+use runner::{find_all_json_tests, run, RunOptions, TestError, TestFilter};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -18,9 +19,13 @@ pub struct Cmd {
     /// Folders will be searched recursively for files with the extension `.json`.
     #[structopt(required = true)]
     path: Vec<PathBuf>,
-    /// Run tests in a single thread.
+    /// Run tests in a single thread. Same as `--jobs 1`.
     #[structopt(short = "s", long)]
     single_thread: bool,
+    /// Number of threads used to run test files in parallel.
+    /// Defaults to the available parallelism.
+    #[structopt(short = "j", long)]
+    jobs: Option<usize>,
     /// Output results in JSON format.
     /// It will stop second run of evm on failure.
     #[structopt(long)]
@@ -31,6 +36,15 @@ pub struct Cmd {
     json_outcome: bool,
     #[structopt(long, alias = "no-fail-fast")]
     keep_going: bool,
+    /// Rerun the first failing test with an EIP-3155 tracer and dump the state before and after.
+    #[structopt(long)]
+    trace_on_fail: bool,
+    /// Run only the given forks, e.g. `--fork Cancun --fork Prague`. Case insensitive.
+    #[structopt(long = "fork")]
+    forks: Vec<String>,
+    /// Run only tests whose name matches the glob pattern. Supports `*` and `?`.
+    #[structopt(long = "test")]
+    test_name: Option<String>,
 }
 
 impl Cmd {
@@ -39,14 +53,26 @@ impl Cmd {
         for path in &self.path {
             println!("\nRunning tests in {}...", path.display());
             let test_files = find_all_json_tests(path);
-            run(
-                test_files,
-                self.single_thread,
-                self.json,
-                self.json_outcome,
-                self.keep_going,
-            )?
+            run(test_files, &self.run_options())?
         }
         Ok(())
     }
+
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            jobs: if self.single_thread {
+                Some(1)
+            } else {
+                self.jobs
+            },
+            trace: self.json,
+            print_outcome: self.json_outcome,
+            keep_going: self.keep_going,
+            trace_on_fail: self.trace_on_fail,
+            filter: TestFilter {
+                forks: self.forks.clone(),
+                name: self.test_name.clone(),
+            },
+        }
+    }
 }
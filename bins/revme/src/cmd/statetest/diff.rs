@@ -0,0 +1,248 @@
+use super::models::AccountInfo;
+use revm::{
+    db::PlainAccount,
+    primitives::{keccak256, Address, HashMap, B256, KECCAK_EMPTY, U256},
+};
+use std::{collections::BTreeMap, fmt};
+
+/// Difference between the expected post state and the state produced by execution.
+///
+/// Displayed as one indented line per difference, each preceded by a newline.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StateDiff(pub Vec<AccountDiff>);
+
+/// Difference of a single account.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AccountDiff {
+    /// Account is expected but does not exist.
+    Missing(Address),
+    /// Account exists but is not expected.
+    Unexpected(Address),
+    Balance {
+        address: Address,
+        got: U256,
+        expected: U256,
+    },
+    Nonce {
+        address: Address,
+        got: u64,
+        expected: u64,
+    },
+    /// Code hashes differ.
+    Code {
+        address: Address,
+        got: B256,
+        expected: B256,
+    },
+    Storage {
+        address: Address,
+        slot: U256,
+        got: U256,
+        expected: U256,
+    },
+}
+
+impl StateDiff {
+    /// Compares the accounts after execution with the expected post state.
+    ///
+    /// Zero storage values are treated as absent. Differences are ordered by address and slot.
+    pub fn new<'a>(
+        got: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+        expected: &HashMap<Address, AccountInfo>,
+    ) -> Self {
+        let got: BTreeMap<_, _> = got.into_iter().collect();
+        let expected: BTreeMap<_, _> = expected.iter().map(|(a, info)| (*a, info)).collect();
+
+        let mut diffs = Vec::new();
+        for (&address, &expected) in &expected {
+            let Some(got) = got.get(&address) else {
+                diffs.push(AccountDiff::Missing(address));
+                continue;
+            };
+            if got.info.balance != expected.balance {
+                diffs.push(AccountDiff::Balance {
+                    address,
+                    got: got.info.balance,
+                    expected: expected.balance,
+                });
+            }
+            if got.info.nonce != expected.nonce {
+                diffs.push(AccountDiff::Nonce {
+                    address,
+                    got: got.info.nonce,
+                    expected: expected.nonce,
+                });
+            }
+            let expected_code_hash = if expected.code.is_empty() {
+                KECCAK_EMPTY
+            } else {
+                keccak256(&expected.code)
+            };
+            if got.info.code_hash != expected_code_hash {
+                diffs.push(AccountDiff::Code {
+                    address,
+                    got: got.info.code_hash,
+                    expected: expected_code_hash,
+                });
+            }
+
+            let got_storage = non_zero_slots(&got.storage);
+            let expected_storage = non_zero_slots(&expected.storage);
+            let mut slots: Vec<_> = got_storage.keys().chain(expected_storage.keys()).collect();
+            slots.sort_unstable();
+            slots.dedup();
+            for &slot in slots {
+                let got = got_storage.get(&slot).copied().unwrap_or_default();
+                let expected = expected_storage.get(&slot).copied().unwrap_or_default();
+                if got != expected {
+                    diffs.push(AccountDiff::Storage {
+                        address,
+                        slot,
+                        got,
+                        expected,
+                    });
+                }
+            }
+        }
+        for address in got.keys() {
+            if !expected.contains_key(address) {
+                diffs.push(AccountDiff::Unexpected(*address));
+            }
+        }
+        diffs.sort_by_key(AccountDiff::address);
+        Self(diffs)
+    }
+
+    /// Returns `true` if the states are equal.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl AccountDiff {
+    /// Returns the address of the account.
+    pub fn address(&self) -> Address {
+        match *self {
+            Self::Missing(address)
+            | Self::Unexpected(address)
+            | Self::Balance { address, .. }
+            | Self::Nonce { address, .. }
+            | Self::Code { address, .. }
+            | Self::Storage { address, .. } => address,
+        }
+    }
+}
+
+fn non_zero_slots(storage: &HashMap<U256, U256>) -> BTreeMap<U256, U256> {
+    storage
+        .iter()
+        .filter(|(_, value)| !value.is_zero())
+        .map(|(slot, value)| (*slot, *value))
+        .collect()
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in &self.0 {
+            write!(f, "\n  {diff}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AccountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(address) => write!(f, "{address}: missing, expected to exist"),
+            Self::Unexpected(address) => write!(f, "{address}: exists, expected to be missing"),
+            Self::Balance {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: balance: got {got}, expected {expected}"),
+            Self::Nonce {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: nonce: got {got}, expected {expected}"),
+            Self::Code {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: code hash: got {got}, expected {expected}"),
+            Self::Storage {
+                address,
+                slot,
+                got,
+                expected,
+            } => write!(
+                f,
+                "{address}: slot {slot:#x}: got {got:#x}, expected {expected:#x}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, Bytes};
+
+    #[test]
+    fn diff_names_divergent_fields() {
+        let a = address!("1000000000000000000000000000000000000000");
+        let b = address!("2000000000000000000000000000000000000000");
+        let c = address!("3000000000000000000000000000000000000000");
+
+        let mut account = PlainAccount::new_empty_with_storage(HashMap::from_iter([
+            (U256::from(1), U256::from(1)),
+            (U256::from(2), U256::ZERO),
+        ]));
+        account.info.balance = U256::from(10);
+        account.info.nonce = 1;
+
+        let expected = HashMap::from_iter([
+            (
+                a,
+                AccountInfo {
+                    balance: U256::from(10),
+                    code: Bytes::new(),
+                    nonce: 1,
+                    storage: HashMap::from_iter([(U256::from(1), U256::from(2))]),
+                },
+            ),
+            (
+                b,
+                AccountInfo {
+                    balance: U256::ZERO,
+                    code: Bytes::new(),
+                    nonce: 0,
+                    storage: HashMap::default(),
+                },
+            ),
+        ]);
+
+        let diff = StateDiff::new([(a, &account), (c, &account)], &expected);
+        assert_eq!(
+            diff.0,
+            [
+                AccountDiff::Storage {
+                    address: a,
+                    slot: U256::from(1),
+                    got: U256::from(1),
+                    expected: U256::from(2),
+                },
+                AccountDiff::Missing(b),
+                AccountDiff::Unexpected(c),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            format!(
+                "\n  {a}: slot 0x1: got 0x1, expected 0x2\
+                 \n  {b}: missing, expected to exist\
+                 \n  {c}: exists, expected to be missing"
+            )
+        );
+    }
+}
@@ -47,8 +47,6 @@ pub struct Test {
     pub logs: B256,
 
     /// Output state.
-    ///
-    /// Note: Not used.
     #[serde(default)]
     state: HashMap<Address, AccountInfo>,
 
@@ -57,6 +55,15 @@ pub struct Test {
 }
 
 impl Test {
+    /// Returns the expected state after execution, empty if the test does not provide it.
+    pub fn expected_post_state(&self) -> &HashMap<Address, AccountInfo> {
+        if self.post_state.is_empty() {
+            &self.state
+        } else {
+            &self.post_state
+        }
+    }
+
     pub fn eip7702_authorization_list(
         &self,
     ) -> Result<Option<AuthorizationList>, alloy_rlp::Error> {
@@ -1,7 +1,8 @@
 use super::{
+    diff::StateDiff,
     merkle_trie::{log_rlp_hash, state_merkle_trie_root},
     models::{SpecName, Test, TestSuite},
-    utils::recover_address,
+    utils::{glob_match, recover_address},
 };
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use revm::{
@@ -40,8 +41,13 @@ pub struct TestError {
 pub enum TestErrorKind {
     #[error("logs root mismatch: got {got}, expected {expected}")]
     LogsRootMismatch { got: B256, expected: B256 },
-    #[error("state root mismatch: got {got}, expected {expected}")]
-    StateRootMismatch { got: B256, expected: B256 },
+    #[error("state root mismatch: got {got}, expected {expected}{diff}")]
+    StateRootMismatch {
+        got: B256,
+        expected: B256,
+        /// Difference from the expected post state, empty if the test does not provide it.
+        diff: StateDiff,
+    },
     #[error("unknown private key: {0:?}")]
     UnknownPrivateKey(B256),
     #[error("unexpected exception: got {got_exception:?}, expected {expected_exception:?}")]
@@ -60,6 +66,48 @@ pub enum TestErrorKind {
     Panic,
 }
 
+/// Selects which tests are run.
+#[derive(Clone, Debug, Default)]
+pub struct TestFilter {
+    /// Fork names to run, case insensitive. All forks are run if empty.
+    pub forks: Vec<String>,
+    /// Glob pattern the test name has to match.
+    pub name: Option<String>,
+}
+
+impl TestFilter {
+    fn matches_fork(&self, spec_name: &SpecName) -> bool {
+        let spec_name = format!("{spec_name:?}");
+        self.forks.is_empty()
+            || self
+                .forks
+                .iter()
+                .any(|fork| fork.eq_ignore_ascii_case(&spec_name))
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|pattern| glob_match(pattern, name))
+    }
+}
+
+/// Options of the statetest [`run`].
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// Number of threads, defaults to the available parallelism.
+    pub jobs: Option<usize>,
+    /// Trace every test, implies `print_outcome`.
+    pub trace: bool,
+    /// Print the outcome of every test in JSON format, implies a single thread.
+    pub print_outcome: bool,
+    /// Keep running after a failure.
+    pub keep_going: bool,
+    /// Rerun the first failing test with an EIP-3155 tracer.
+    pub trace_on_fail: bool,
+    pub filter: TestFilter,
+}
+
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     if path.is_file() {
         vec![path.to_path_buf()]
@@ -224,6 +272,10 @@ fn check_evm_execution<EXT>(
         let kind = TestErrorKind::StateRootMismatch {
             got: state_root,
             expected: test.hash,
+            diff: StateDiff::new(
+                evm.context.evm.db.cache.trie_account(),
+                test.expected_post_state(),
+            ),
         };
         print_json_output(Some(kind.to_string()));
         return Err(TestError {
@@ -242,6 +294,8 @@ pub fn execute_test_suite(
     elapsed: &Arc<Mutex<Duration>>,
     trace: bool,
     print_json_outcome: bool,
+    trace_on_fail: bool,
+    filter: &TestFilter,
 ) -> Result<(), TestError> {
     if skip_test(path) {
         return Ok(());
@@ -254,6 +308,10 @@ pub fn execute_test_suite(
     })?;
 
     for (name, unit) in suite.0 {
+        if !filter.matches_name(&name) {
+            continue;
+        }
+
         // Create database and insert cache
         let mut cache_state = revm::CacheState::new(false);
         for (address, info) in unit.pre {
@@ -307,7 +365,7 @@ pub fn execute_test_suite(
             // Constantinople was immediately extended by Petersburg.
             // There isn't any production Constantinople transaction
             // so we don't support it and skip right to Petersburg.
-            if spec_name == SpecName::Constantinople {
+            if spec_name == SpecName::Constantinople || !filter.matches_fork(&spec_name) {
                 continue;
             }
 
@@ -439,6 +497,15 @@ pub fn execute_test_suite(
                     return Err(e);
                 }
 
+                let path = path.display();
+                if !trace_on_fail {
+                    println!("\nExecution result: {exec_result:#?}");
+                    println!("\nExpected exception: {:?}", test.expect_exception);
+                    println!("\nSpecification: {spec_id:?}");
+                    println!("\nTest name: {name:?} (index: {index}, path: {path}) failed:\n{e}");
+                    return Err(e);
+                }
+
                 // re build to run with tracing
                 let mut cache = cache_state.clone();
                 cache.set_state_clear_flag(SpecId::enabled(
@@ -450,7 +517,6 @@ pub fn execute_test_suite(
                     .with_bundle_update()
                     .build();
 
-                let path = path.display();
                 println!("\nTraces:");
                 let mut evm = Evm::builder()
                     .with_spec_id(spec_id)
@@ -476,20 +542,22 @@ pub fn execute_test_suite(
     Ok(())
 }
 
-pub fn run(
-    test_files: Vec<PathBuf>,
-    mut single_thread: bool,
-    trace: bool,
-    mut print_outcome: bool,
-    keep_going: bool,
-) -> Result<(), TestError> {
+pub fn run(test_files: Vec<PathBuf>, options: &RunOptions) -> Result<(), TestError> {
+    let RunOptions {
+        mut jobs,
+        trace,
+        mut print_outcome,
+        keep_going,
+        trace_on_fail,
+        ref filter,
+    } = *options;
     // trace implies print_outcome
     if trace {
         print_outcome = true;
     }
     // print_outcome or trace implies single_thread
     if print_outcome {
-        single_thread = true;
+        jobs = Some(1);
     }
     let n_files = test_files.len();
 
@@ -501,17 +569,16 @@ pub fn run(
     let queue = Arc::new(Mutex::new((0usize, test_files)));
     let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
 
-    let num_threads = match (single_thread, std::thread::available_parallelism()) {
-        (true, _) | (false, Err(_)) => 1,
-        (false, Ok(n)) => n.get(),
-    };
-    let num_threads = num_threads.min(n_files);
+    let num_threads =
+        jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let num_threads = num_threads.clamp(1, n_files.max(1));
     let mut handles = Vec::with_capacity(num_threads);
     for i in 0..num_threads {
         let queue = queue.clone();
         let n_errors = n_errors.clone();
         let console_bar = console_bar.clone();
         let elapsed = elapsed.clone();
+        let filter = filter.clone();
 
         let thread = std::thread::Builder::new().name(format!("runner-{i}"));
 
@@ -530,7 +597,14 @@ pub fn run(
                 (prev_idx, test_path)
             };
 
-            let result = execute_test_suite(&test_path, &elapsed, trace, print_outcome);
+            let result = execute_test_suite(
+                &test_path,
+                &elapsed,
+                trace,
+                print_outcome,
+                trace_on_fail,
+                &filter,
+            );
 
             // Increment after the test is done.
             console_bar.inc(1);
@@ -585,3 +659,61 @@ pub fn run(
         Err(thread_errors.swap_remove(0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::statetest::diff::AccountDiff;
+    use revm::primitives::{address, U256};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(name)
+    }
+
+    #[test]
+    fn corrupted_fixture_names_divergent_slot() {
+        let path = fixture("corrupted_slot.json");
+        let elapsed = Arc::default();
+        let err = execute_test_suite(&path, &elapsed, false, false, false, &TestFilter::default())
+            .unwrap_err();
+
+        let TestErrorKind::StateRootMismatch { diff, .. } = &err.kind else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(
+            diff.0,
+            [AccountDiff::Storage {
+                address: address!("0000000000000000000000000000000000001000"),
+                slot: U256::ZERO,
+                got: U256::from(1),
+                expected: U256::from(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn filters_skip_tests() {
+        let path = fixture("corrupted_slot.json");
+        let elapsed = Arc::default();
+        for filter in [
+            TestFilter {
+                forks: vec!["prague".into()],
+                name: None,
+            },
+            TestFilter {
+                forks: vec![],
+                name: Some("other_*".into()),
+            },
+        ] {
+            execute_test_suite(&path, &elapsed, false, false, false, &filter).unwrap();
+        }
+
+        let filter = TestFilter {
+            forks: vec!["cancun".into()],
+            name: Some("corrupted_*".into()),
+        };
+        assert!(execute_test_suite(&path, &elapsed, false, false, false, &filter).is_err());
+    }
+}
@@ -8,6 +8,38 @@ pub fn recover_address(private_key: &[u8]) -> Option<Address> {
     Some(Address::from_raw_public_key(&public_key.as_bytes()[1..]))
 }
 
+/// Matches `text` against a glob `pattern`, where `*` matches any sequence of characters and
+/// `?` matches any single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern and the text position it was tried at.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => {
+                let Some((star_p, star_t)) = star else {
+                    return false;
+                };
+                // Let the last `*` match one more character.
+                p = star_p + 1;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,4 +54,21 @@ mod tests {
             ))
         )
     }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "add11"));
+        assert!(glob_match("add*", "add11"));
+        assert!(glob_match("add1?", "add11"));
+        assert!(glob_match(
+            "*sstore*",
+            "tests/cancun/test_sstore[fork_Cancun]"
+        ));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("a*b", "abab"));
+        assert!(!glob_match("add1?", "add1"));
+        assert!(!glob_match("add", "add11"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+    }
 }
@@ -0,0 +1,479 @@
+//! Comparison of two EIP-3155 traces, for example of revm and geth.
+//!
+//! A trace is a JSON object per line, a step per executed instruction and a summary at the end.
+//! [`diff_traces`] reports the first entry where two traces structurally diverge.
+//!
+//! Numbers can be JSON numbers, decimal or hex strings. Optional fields (`depth`, `refund`,
+//! `memSize`, `memory` and `returnData`) are only compared when both traces have them. `gasCost`
+//! is not compared, clients account for the gas forwarded to calls differently.
+
+use revm::{interpreter::OpCode, primitives::U256};
+use serde_json::{Map, Value};
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+/// Trace diff command.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// First EIP-3155 trace, in JSON lines.
+    a: PathBuf,
+    /// Second EIP-3155 trace, in JSON lines.
+    b: PathBuf,
+    /// Number of entries printed before the divergence.
+    #[structopt(long, default_value = "5")]
+    context: usize,
+}
+
+impl Cmd {
+    /// Run trace diff command.
+    pub fn run(&self) -> Result<(), Error> {
+        let a = BufReader::new(File::open(&self.a)?);
+        let b = BufReader::new(File::open(&self.b)?);
+        match diff_traces(a, b, self.context)? {
+            Some(divergence) => {
+                println!("{divergence}");
+                Err(Error::Diverged)
+            }
+            None => {
+                println!("Traces are identical");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("line {line} of trace {trace}: {source}")]
+    Json {
+        trace: &'static str,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("line {line} of trace {trace}: invalid or missing field `{field}`")]
+    InvalidField {
+        trace: &'static str,
+        line: usize,
+        field: &'static str,
+    },
+    #[error("traces diverge")]
+    Diverged,
+}
+
+/// First difference between two traces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the diverging entry, steps and summary included.
+    pub index: usize,
+    /// Line and content of the diverging entry of the first trace, `None` if it ended.
+    pub a: Option<(usize, String)>,
+    /// Line and content of the diverging entry of the second trace, `None` if it ended.
+    pub b: Option<(usize, String)>,
+    /// Fields that differ, empty if a trace ended.
+    pub fields: Vec<FieldDiff>,
+    /// Entries of the first trace before the divergence.
+    pub context: Vec<String>,
+}
+
+/// Field that differs between two entries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Name of the field, with the index of the stack item for the stack.
+    pub field: String,
+    /// Value in the first trace.
+    pub a: String,
+    /// Value in the second trace.
+    pub b: String,
+}
+
+impl FieldDiff {
+    fn new(field: impl Into<String>, a: impl fmt::Display, b: impl fmt::Display) -> Self {
+        Self {
+            field: field.into(),
+            a: a.to_string(),
+            b: b.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = |entry: &Option<(usize, String)>| match entry {
+            Some((line, _)) => format!("line {line}"),
+            None => "end".to_string(),
+        };
+        writeln!(
+            f,
+            "Traces diverge at entry {} ({} of a, {} of b)",
+            self.index,
+            line(&self.a),
+            line(&self.b)
+        )?;
+        for diff in &self.fields {
+            writeln!(f, "  {}: {} != {}", diff.field, diff.a, diff.b)?;
+        }
+        if !self.context.is_empty() {
+            writeln!(f, "Context:")?;
+            for line in &self.context {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        let entry = |entry: &Option<(usize, String)>| match entry {
+            Some((_, entry)) => entry.clone(),
+            None => "<end of trace>".to_string(),
+        };
+        writeln!(f, "a: {}", entry(&self.a))?;
+        write!(f, "b: {}", entry(&self.b))
+    }
+}
+
+/// Compares two EIP-3155 traces in JSON lines and returns their first divergence.
+///
+/// `context` entries before the divergence are included in it.
+pub fn diff_traces(
+    a: impl BufRead,
+    b: impl BufRead,
+    context: usize,
+) -> Result<Option<Divergence>, Error> {
+    let mut a = TraceReader::new(a, "a");
+    let mut b = TraceReader::new(b, "b");
+    let mut previous = VecDeque::with_capacity(context);
+    let mut index = 0;
+    loop {
+        let (entry_a, entry_b) = (a.next()?, b.next()?);
+        let fields = match (&entry_a, &entry_b) {
+            (None, None) => return Ok(None),
+            (Some((_, _, entry_a)), Some((_, _, entry_b))) => entry_a.diff(entry_b),
+            _ => Vec::new(),
+        };
+        if entry_a.is_none() || entry_b.is_none() || !fields.is_empty() {
+            return Ok(Some(Divergence {
+                index,
+                a: entry_a.map(|(line, raw, _)| (line, raw)),
+                b: entry_b.map(|(line, raw, _)| (line, raw)),
+                fields,
+                context: previous.into(),
+            }));
+        }
+
+        if context > 0 {
+            if previous.len() == context {
+                previous.pop_front();
+            }
+            previous.push_back(entry_a.unwrap().1);
+        }
+        index += 1;
+    }
+}
+
+/// Entry of a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Entry {
+    Step(Step),
+    Summary(Summary),
+}
+
+/// Executed instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    pc: u64,
+    op: u8,
+    gas: u64,
+    stack: Vec<U256>,
+    depth: Option<u64>,
+    refund: Option<u64>,
+    mem_size: Option<u64>,
+    memory: Option<String>,
+    return_data: Option<String>,
+}
+
+/// Summary at the end of a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Summary {
+    output: Option<String>,
+    gas_used: Option<u64>,
+    pass: Option<bool>,
+    state_root: Option<String>,
+}
+
+impl Entry {
+    /// Parses an entry, a step if it has a program counter.
+    fn parse(object: &Map<String, Value>) -> Result<Self, &'static str> {
+        if !object.contains_key("pc") {
+            return Ok(Self::Summary(Summary {
+                output: optional(object, "output", hex)?,
+                gas_used: optional(object, "gasUsed", number)?,
+                pass: optional(object, "pass", Value::as_bool)?,
+                state_root: optional(object, "stateRoot", hex)?,
+            }));
+        }
+
+        let stack = object
+            .get("stack")
+            .and_then(Value::as_array)
+            .ok_or("stack")?
+            .iter()
+            .map(word)
+            .collect::<Option<_>>()
+            .ok_or("stack")?;
+        Ok(Self::Step(Step {
+            pc: required(object, "pc", number)?,
+            op: required(object, "op", number)?
+                .try_into()
+                .map_err(|_| "op")?,
+            gas: required(object, "gas", number)?,
+            stack,
+            depth: optional(object, "depth", number)?,
+            refund: optional(object, "refund", number)?,
+            mem_size: optional(object, "memSize", number)?,
+            memory: optional(object, "memory", hex)?,
+            return_data: optional(object, "returnData", hex)?,
+        }))
+    }
+
+    /// Returns the fields that differ from `other`.
+    fn diff(&self, other: &Self) -> Vec<FieldDiff> {
+        let mut diffs = Vec::new();
+        match (self, other) {
+            (Self::Step(a), Self::Step(b)) => {
+                if a.pc != b.pc {
+                    diffs.push(FieldDiff::new("pc", a.pc, b.pc));
+                }
+                if a.op != b.op {
+                    let op = |op: u8| format!("{} ({op:#04x})", OpCode::name_by_op(op));
+                    diffs.push(FieldDiff::new("op", op(a.op), op(b.op)));
+                }
+                if a.gas != b.gas {
+                    diffs.push(FieldDiff::new("gas", a.gas, b.gas));
+                }
+                if a.stack.len() != b.stack.len() {
+                    diffs.push(FieldDiff::new("stack length", a.stack.len(), b.stack.len()));
+                }
+                // First differing item of the common part, from the bottom of the stack.
+                if let Some((i, (a, b))) = a
+                    .stack
+                    .iter()
+                    .zip(&b.stack)
+                    .enumerate()
+                    .find(|(_, (a, b))| a != b)
+                {
+                    diffs.push(FieldDiff::new(
+                        format!("stack[{i}]"),
+                        hex_word(a),
+                        hex_word(b),
+                    ));
+                }
+                diff_optional(&mut diffs, "depth", &a.depth, &b.depth);
+                diff_optional(&mut diffs, "refund", &a.refund, &b.refund);
+                diff_optional(&mut diffs, "memSize", &a.mem_size, &b.mem_size);
+                diff_optional(&mut diffs, "memory", &a.memory, &b.memory);
+                diff_optional(&mut diffs, "returnData", &a.return_data, &b.return_data);
+            }
+            (Self::Summary(a), Self::Summary(b)) => {
+                diff_optional(&mut diffs, "output", &a.output, &b.output);
+                diff_optional(&mut diffs, "gasUsed", &a.gas_used, &b.gas_used);
+                diff_optional(&mut diffs, "pass", &a.pass, &b.pass);
+                diff_optional(&mut diffs, "stateRoot", &a.state_root, &b.state_root);
+            }
+            (a, b) => diffs.push(FieldDiff::new("entry", a.kind(), b.kind())),
+        }
+        diffs
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Step(_) => "step",
+            Self::Summary(_) => "summary",
+        }
+    }
+}
+
+/// Pushes a diff of an optional field if both entries have it and it differs.
+fn diff_optional<T: PartialEq + fmt::Display>(
+    diffs: &mut Vec<FieldDiff>,
+    field: &'static str,
+    a: &Option<T>,
+    b: &Option<T>,
+) {
+    if let (Some(a), Some(b)) = (a, b) {
+        if a != b {
+            diffs.push(FieldDiff::new(field, a, b));
+        }
+    }
+}
+
+fn required<T>(
+    object: &Map<String, Value>,
+    field: &'static str,
+    parse: impl Fn(&Value) -> Option<T>,
+) -> Result<T, &'static str> {
+    optional(object, field, parse)?.ok_or(field)
+}
+
+/// Parses a field, `None` if it is missing or null.
+fn optional<T>(
+    object: &Map<String, Value>,
+    field: &'static str,
+    parse: impl Fn(&Value) -> Option<T>,
+) -> Result<Option<T>, &'static str> {
+    match object.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => parse(value).map(Some).ok_or(field),
+    }
+}
+
+/// Parses a JSON number, or a decimal or `0x` prefixed hex string.
+fn number(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Parses a hex string into a word, an empty string is zero.
+fn word(value: &Value) -> Option<U256> {
+    let hex = value.as_str()?;
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.is_empty() {
+        return Some(U256::ZERO);
+    }
+    U256::from_str_radix(hex, 16).ok()
+}
+
+/// Normalizes hex data, a string or an array of strings, to lowercase without prefix.
+fn hex(value: &Value) -> Option<String> {
+    let strip = |hex: &str| hex.strip_prefix("0x").unwrap_or(hex).to_ascii_lowercase();
+    match value {
+        Value::String(string) => Some(strip(string)),
+        Value::Array(words) => words.iter().map(|word| word.as_str().map(strip)).collect(),
+        _ => None,
+    }
+}
+
+fn hex_word(word: &U256) -> String {
+    format!("{word:#x}")
+}
+
+/// Reads the entries of a trace, skipping blank lines.
+struct TraceReader<R> {
+    lines: io::Lines<R>,
+    line: usize,
+    trace: &'static str,
+}
+
+impl<R: BufRead> TraceReader<R> {
+    fn new(reader: R, trace: &'static str) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+            trace,
+        }
+    }
+
+    /// Returns the line number, content and entry of the next entry.
+    fn next(&mut self) -> Result<Option<(usize, String, Entry)>, Error> {
+        for raw in self.lines.by_ref() {
+            let raw = raw?;
+            self.line += 1;
+            if raw.trim().is_empty() {
+                continue;
+            }
+            let object: Map<String, Value> =
+                serde_json::from_str(&raw).map_err(|source| Error::Json {
+                    trace: self.trace,
+                    line: self.line,
+                    source,
+                })?;
+            let entry = Entry::parse(&object).map_err(|field| Error::InvalidField {
+                trace: self.trace,
+                line: self.line,
+                field,
+            })?;
+            return Ok(Some((self.line, raw, entry)));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVM: &str = include_str!("../../testdata/trace_diff/revm.jsonl");
+    const GETH: &str = include_str!("../../testdata/trace_diff/geth.jsonl");
+    const GETH_DIVERGED: &str = include_str!("../../testdata/trace_diff/geth_diverged.jsonl");
+
+    fn diff(a: &str, b: &str, context: usize) -> Option<Divergence> {
+        diff_traces(a.as_bytes(), b.as_bytes(), context).unwrap()
+    }
+
+    #[test]
+    fn equivalent_traces() {
+        // Geth omits the memory and return data and formats numbers differently.
+        assert_eq!(diff(REVM, GETH, 5), None);
+        assert_eq!(diff(GETH, REVM, 5), None);
+    }
+
+    #[test]
+    fn seeded_divergence() {
+        let divergence = diff(REVM, GETH_DIVERGED, 2).unwrap();
+        assert_eq!(divergence.index, 3);
+        assert_eq!(divergence.a.as_ref().unwrap().0, 4);
+        assert_eq!(divergence.b.as_ref().unwrap().0, 4);
+        assert_eq!(
+            divergence.fields,
+            vec![
+                FieldDiff::new("gas", 78991, 78990),
+                FieldDiff::new("stack[0]", "0x3", "0x4"),
+            ]
+        );
+        let lines: Vec<&str> = REVM.lines().collect();
+        assert_eq!(divergence.context, [lines[1], lines[2]]);
+
+        let report = divergence.to_string();
+        assert!(report.starts_with(
+            "Traces diverge at entry 3 (line 4 of a, line 4 of b)\n  gas: 78991 != 78990\n"
+        ));
+        assert!(report.ends_with(&format!("b: {}", GETH_DIVERGED.lines().nth(3).unwrap())));
+    }
+
+    #[test]
+    fn truncated_trace() {
+        let truncated: String = GETH
+            .lines()
+            .take(2)
+            .map(|line| line.to_string() + "\n")
+            .collect();
+        let divergence = diff(REVM, &truncated, 0).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.b, None);
+        assert!(divergence.fields.is_empty());
+        assert!(divergence.context.is_empty());
+        assert!(divergence.to_string().ends_with("b: <end of trace>"));
+    }
+
+    #[test]
+    fn invalid_entry() {
+        let err = diff_traces(REVM.as_bytes(), "\n{\"pc\":0}\n".as_bytes(), 0).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidField {
+                trace: "b",
+                line: 2,
+                field: "stack"
+            }
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    // The C test program is compiled for the target of the crate.
+    println!(
+        "cargo:rustc-env=REVM_CAPI_TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+}
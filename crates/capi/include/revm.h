@@ -0,0 +1,161 @@
+/*
+ * C API of revm, see `crates/capi/src/lib.rs`.
+ *
+ * Ownership:
+ * - A RevmEvm created by revm_new is owned by the caller and freed with revm_free.
+ * - A RevmResult created by revm_transact is owned by the caller and freed with
+ *   revm_result_free. It does not borrow the RevmEvm, which can be freed first.
+ * - Pointers returned by the accessors of a RevmResult, and a RevmStateDiffIter over it,
+ *   borrow the result and are valid until it is freed.
+ * - Input buffers are copied, they are only borrowed for the duration of the call.
+ *
+ * Addresses are 20 bytes, 256-bit words (balances, storage slots and values, gas prices) are
+ * 32 bytes big-endian.
+ */
+
+#ifndef REVM_H
+#define REVM_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Error code returned by the functions of the C API. */
+typedef enum RevmError {
+    REVM_OK = 0,
+    /* A required pointer argument is null. */
+    REVM_ERROR_NULL_POINTER = 1,
+    /* The spec id is unknown. */
+    REVM_ERROR_INVALID_SPEC = 2,
+    /* The transaction is invalid, it was not executed. */
+    REVM_ERROR_INVALID_TRANSACTION = 3,
+    /* The block is invalid, the transaction was not executed. */
+    REVM_ERROR_INVALID_HEADER = 4,
+    /* Any other error, the transaction was not executed. */
+    REVM_ERROR_OTHER = 5,
+} RevmError;
+
+/* Status of an executed transaction. */
+typedef enum RevmExecutionStatus {
+    REVM_STATUS_SUCCESS = 0,
+    REVM_STATUS_REVERT = 1,
+    /* The transaction halted with an exceptional error. */
+    REVM_STATUS_HALT = 2,
+} RevmExecutionStatus;
+
+/* Kind of a RevmStateChange. */
+typedef enum RevmStateChangeKind {
+    /* The balance or nonce of an account, or its code, changed. */
+    REVM_STATE_CHANGE_ACCOUNT = 0,
+    /* A storage slot of an account changed. */
+    REVM_STATE_CHANGE_STORAGE = 1,
+} RevmStateChangeKind;
+
+/* Transaction environment, see revm_set_tx. */
+typedef struct RevmTxEnv {
+    uint64_t gas_limit;
+    /* Only checked if has_nonce is set. */
+    uint64_t nonce;
+    /* Calldata, or initcode of a create transaction. May be NULL if data_len is zero. */
+    const uint8_t *data;
+    size_t data_len;
+    uint8_t caller[20];
+    /* Ignored if is_create is set. */
+    uint8_t to[20];
+    uint8_t gas_price[32];
+    uint8_t value[32];
+    bool is_create;
+    bool has_nonce;
+} RevmTxEnv;
+
+/* Block environment, see revm_set_block. */
+typedef struct RevmBlockEnv {
+    uint64_t number;
+    uint64_t timestamp;
+    uint64_t gas_limit;
+    uint8_t coinbase[20];
+    uint8_t basefee[32];
+    uint8_t prevrandao[32];
+} RevmBlockEnv;
+
+/* A change of the state, returned by revm_state_diff_next. */
+typedef struct RevmStateChange {
+    RevmStateChangeKind kind;
+    uint8_t address[20];
+    /* New balance and nonce of a REVM_STATE_CHANGE_ACCOUNT change. */
+    uint8_t balance[32];
+    uint64_t nonce;
+    /* Slot and new value of a REVM_STATE_CHANGE_STORAGE change. */
+    uint8_t slot[32];
+    uint8_t value[32];
+} RevmStateChange;
+
+/* EVM over an in-memory database. */
+typedef struct RevmEvm RevmEvm;
+
+/* Result of a transaction. */
+typedef struct RevmResult RevmResult;
+
+/*
+ * Iterator over the state changes of a RevmResult. Accounts are ordered by address, each
+ * followed by its storage changes ordered by slot. The fields are private.
+ */
+typedef struct RevmStateDiffIter {
+    const RevmResult *result;
+    size_t next;
+} RevmStateDiffIter;
+
+/* Creates an EVM with an empty database and the spec spec_id, the SpecId of revm. */
+RevmError revm_new(uint8_t spec_id, RevmEvm **out);
+
+/* Frees an EVM created by revm_new. Does nothing if evm is NULL. */
+void revm_free(RevmEvm *evm);
+
+/* Sets the balance, nonce and code of an account, its storage is kept. */
+RevmError revm_set_account(RevmEvm *evm, const uint8_t address[20], const uint8_t balance[32],
+                           uint64_t nonce, const uint8_t *code, size_t code_len);
+
+/* Sets a storage slot of an account. */
+RevmError revm_set_storage(RevmEvm *evm, const uint8_t address[20], const uint8_t slot[32],
+                           const uint8_t value[32]);
+
+/* Sets the transaction environment, the calldata is copied. */
+RevmError revm_set_tx(RevmEvm *evm, const RevmTxEnv *tx);
+
+/* Sets the block environment. */
+RevmError revm_set_block(RevmEvm *evm, const RevmBlockEnv *block);
+
+/*
+ * Executes the transaction and commits its changes to the database.
+ *
+ * On success the result is written to out, it must be freed with revm_result_free. On error
+ * nothing is written and the database is unchanged.
+ */
+RevmError revm_transact(RevmEvm *evm, RevmResult **out);
+
+/* Frees a result created by revm_transact. Does nothing if result is NULL. */
+void revm_result_free(RevmResult *result);
+
+RevmExecutionStatus revm_result_status(const RevmResult *result);
+
+/* Gas used by the transaction, after refunds. */
+uint64_t revm_result_gas_used(const RevmResult *result);
+
+/* Output of the transaction, borrowed from result. Empty if the transaction halted. */
+const uint8_t *revm_result_output(const RevmResult *result, size_t *len);
+
+/* Returns an iterator over the state changes of result, borrowing it. */
+RevmStateDiffIter revm_state_diff_iter(const RevmResult *result);
+
+/* Writes the next state change to out and returns true, or returns false at the end. */
+bool revm_state_diff_next(RevmStateDiffIter *iter, RevmStateChange *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* REVM_H */
@@ -0,0 +1,485 @@
+//! C API of revm.
+//!
+//! A small `extern "C"` surface to execute transactions from non-Rust clients, declared in
+//! `include/revm.h`. An [`RevmEvm`] executes transactions over an in-memory database populated
+//! with [`revm_set_account`] and [`revm_set_storage`].
+//!
+//! # Ownership
+//!
+//! - An [`RevmEvm`] created by [`revm_new`] is owned by the caller and freed with [`revm_free`].
+//! - An [`RevmResult`] created by [`revm_transact`] is owned by the caller and freed with
+//!   [`revm_result_free`]. It does not borrow the [`RevmEvm`], which can be freed first.
+//! - Pointers returned by the accessors of an [`RevmResult`], and a [`RevmStateDiffIter`] over
+//!   it, borrow the result and are valid until it is freed.
+//! - Input buffers are copied, they are only borrowed for the duration of the call.
+//!
+//! Addresses are 20 bytes, 256-bit words (balances, storage slots and values, gas prices) are 32
+//! bytes big-endian.
+
+use core::{ptr, slice};
+use revm::{
+    primitives::{
+        AccountInfo, Address, Bytecode, Bytes, EVMError, EvmState, ExecutionResult, ResultAndState,
+        SpecId, TxKind, B256, U256,
+    },
+    DatabaseCommit, Evm, InMemoryDB,
+};
+
+/// Error code returned by the functions of the C API.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevmError {
+    /// No error.
+    Ok = 0,
+    /// A required pointer argument is null.
+    NullPointer = 1,
+    /// The spec id is unknown.
+    InvalidSpec = 2,
+    /// The transaction is invalid, it was not executed.
+    InvalidTransaction = 3,
+    /// The block is invalid, the transaction was not executed.
+    InvalidHeader = 4,
+    /// Any other error, the transaction was not executed.
+    Other = 5,
+}
+
+impl<E> From<EVMError<E>> for RevmError {
+    #[allow(deprecated)]
+    fn from(error: EVMError<E>) -> Self {
+        match error {
+            EVMError::Transaction(_) => Self::InvalidTransaction,
+            EVMError::Header(_) => Self::InvalidHeader,
+            EVMError::Database(_)
+            | EVMError::Custom(_)
+            | EVMError::Precompile(_)
+            | EVMError::Arithmetic(_) => Self::Other,
+        }
+    }
+}
+
+/// Status of an executed transaction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevmExecutionStatus {
+    /// The transaction succeeded.
+    Success = 0,
+    /// The transaction reverted.
+    Revert = 1,
+    /// The transaction halted with an exceptional error.
+    Halt = 2,
+}
+
+/// Kind of a [`RevmStateChange`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RevmStateChangeKind {
+    /// The balance or nonce of an account, or its code, changed.
+    #[default]
+    Account = 0,
+    /// A storage slot of an account changed.
+    Storage = 1,
+}
+
+/// Transaction environment, see [`revm_set_tx`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RevmTxEnv {
+    /// Gas limit of the transaction.
+    pub gas_limit: u64,
+    /// Nonce of the transaction, only checked if `has_nonce` is set.
+    pub nonce: u64,
+    /// Calldata, or initcode of a create transaction. May be null if `data_len` is zero.
+    pub data: *const u8,
+    /// Length of `data`.
+    pub data_len: usize,
+    /// Caller of the transaction.
+    pub caller: [u8; 20],
+    /// Target of a call transaction, ignored if `is_create` is set.
+    pub to: [u8; 20],
+    /// Gas price of the transaction.
+    pub gas_price: [u8; 32],
+    /// Value transferred by the transaction.
+    pub value: [u8; 32],
+    /// Whether the transaction creates a contract.
+    pub is_create: bool,
+    /// Whether `nonce` is checked against the nonce of the caller.
+    pub has_nonce: bool,
+}
+
+/// Block environment, see [`revm_set_block`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RevmBlockEnv {
+    /// Number of the block.
+    pub number: u64,
+    /// Timestamp of the block.
+    pub timestamp: u64,
+    /// Gas limit of the block.
+    pub gas_limit: u64,
+    /// Beneficiary of the block.
+    pub coinbase: [u8; 20],
+    /// Base fee of the block.
+    pub basefee: [u8; 32],
+    /// Randomness of the block, `PREVRANDAO`.
+    pub prevrandao: [u8; 32],
+}
+
+/// A change of the state, returned by [`revm_state_diff_next`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RevmStateChange {
+    /// Whether an account or a storage slot changed.
+    pub kind: RevmStateChangeKind,
+    /// Address of the account.
+    pub address: [u8; 20],
+    /// New balance of an [`RevmStateChangeKind::Account`] change.
+    pub balance: [u8; 32],
+    /// New nonce of an [`RevmStateChangeKind::Account`] change.
+    pub nonce: u64,
+    /// Slot of a [`RevmStateChangeKind::Storage`] change.
+    pub slot: [u8; 32],
+    /// New value of a [`RevmStateChangeKind::Storage`] change.
+    pub value: [u8; 32],
+}
+
+/// EVM over an in-memory database, created by [`revm_new`].
+#[derive(Debug)]
+pub struct RevmEvm {
+    evm: Evm<'static, (), InMemoryDB>,
+}
+
+/// Result of a transaction, created by [`revm_transact`].
+#[derive(Debug)]
+pub struct RevmResult {
+    status: RevmExecutionStatus,
+    gas_used: u64,
+    output: Bytes,
+    changes: Vec<RevmStateChange>,
+}
+
+/// Iterator over the state changes of an [`RevmResult`], created by [`revm_state_diff_iter`].
+///
+/// Accounts are ordered by address, each followed by its storage changes ordered by slot.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RevmStateDiffIter {
+    result: *const RevmResult,
+    next: usize,
+}
+
+/// Creates an EVM with an empty database and the spec `spec_id`, the `SpecId` of revm.
+///
+/// On success the EVM is written to `out`, it must be freed with [`revm_free`].
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn revm_new(spec_id: u8, out: *mut *mut RevmEvm) -> RevmError {
+    if out.is_null() {
+        return RevmError::NullPointer;
+    }
+    let Some(spec_id) = SpecId::try_from_u8(spec_id) else {
+        return RevmError::InvalidSpec;
+    };
+    let evm = Evm::builder()
+        .with_db(InMemoryDB::default())
+        .with_spec_id(spec_id)
+        .build();
+    *out = Box::into_raw(Box::new(RevmEvm { evm }));
+    RevmError::Ok
+}
+
+/// Frees an EVM created by [`revm_new`]. Does nothing if `evm` is null.
+///
+/// # Safety
+///
+/// `evm` must be null or created by [`revm_new`] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn revm_free(evm: *mut RevmEvm) {
+    if !evm.is_null() {
+        drop(Box::from_raw(evm));
+    }
+}
+
+/// Sets the balance, nonce and code of an account, its storage is kept.
+///
+/// # Safety
+///
+/// `evm` must be null or a live EVM, `address` and `balance` null or valid for reads of 20 and
+/// 32 bytes, and `code` valid for reads of `code_len` bytes unless `code_len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_account(
+    evm: *mut RevmEvm,
+    address: *const [u8; 20],
+    balance: *const [u8; 32],
+    nonce: u64,
+    code: *const u8,
+    code_len: usize,
+) -> RevmError {
+    let (Some(evm), Some(address), Some(balance)) =
+        (evm.as_mut(), address.as_ref(), balance.as_ref())
+    else {
+        return RevmError::NullPointer;
+    };
+    let Some(code) = bytes(code, code_len) else {
+        return RevmError::NullPointer;
+    };
+    let mut info = if code.is_empty() {
+        AccountInfo::default()
+    } else {
+        AccountInfo::from_bytecode(Bytecode::new_raw(Bytes::copy_from_slice(code)))
+    };
+    info.balance = U256::from_be_bytes(*balance);
+    info.nonce = nonce;
+    evm.evm
+        .db_mut()
+        .insert_account_info(Address::from(*address), info);
+    RevmError::Ok
+}
+
+/// Sets a storage slot of an account.
+///
+/// # Safety
+///
+/// `evm` must be null or a live EVM, `address` null or valid for reads of 20 bytes, `slot` and
+/// `value` null or valid for reads of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_storage(
+    evm: *mut RevmEvm,
+    address: *const [u8; 20],
+    slot: *const [u8; 32],
+    value: *const [u8; 32],
+) -> RevmError {
+    let (Some(evm), Some(address), Some(slot), Some(value)) = (
+        evm.as_mut(),
+        address.as_ref(),
+        slot.as_ref(),
+        value.as_ref(),
+    ) else {
+        return RevmError::NullPointer;
+    };
+    match evm.evm.db_mut().insert_account_storage(
+        Address::from(*address),
+        U256::from_be_bytes(*slot),
+        U256::from_be_bytes(*value),
+    ) {
+        Ok(()) => RevmError::Ok,
+        Err(_) => RevmError::Other,
+    }
+}
+
+/// Sets the transaction environment, the calldata is copied.
+///
+/// # Safety
+///
+/// `evm` must be null or a live EVM and `tx` null or valid for reads, with `tx->data` valid for
+/// reads of `tx->data_len` bytes unless `tx->data_len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_tx(evm: *mut RevmEvm, tx: *const RevmTxEnv) -> RevmError {
+    let (Some(evm), Some(tx)) = (evm.as_mut(), tx.as_ref()) else {
+        return RevmError::NullPointer;
+    };
+    let Some(data) = bytes(tx.data, tx.data_len) else {
+        return RevmError::NullPointer;
+    };
+    let env = evm.evm.tx_mut();
+    env.caller = Address::from(tx.caller);
+    env.transact_to = if tx.is_create {
+        TxKind::Create
+    } else {
+        TxKind::Call(Address::from(tx.to))
+    };
+    env.gas_limit = tx.gas_limit;
+    env.gas_price = U256::from_be_bytes(tx.gas_price);
+    env.value = U256::from_be_bytes(tx.value);
+    env.nonce = tx.has_nonce.then_some(tx.nonce);
+    env.data = Bytes::copy_from_slice(data);
+    RevmError::Ok
+}
+
+/// Sets the block environment.
+///
+/// # Safety
+///
+/// `evm` must be null or a live EVM and `block` null or valid for reads.
+#[no_mangle]
+pub unsafe extern "C" fn revm_set_block(
+    evm: *mut RevmEvm,
+    block: *const RevmBlockEnv,
+) -> RevmError {
+    let (Some(evm), Some(block)) = (evm.as_mut(), block.as_ref()) else {
+        return RevmError::NullPointer;
+    };
+    let env = evm.evm.block_mut();
+    env.number = U256::from(block.number);
+    env.timestamp = U256::from(block.timestamp);
+    env.gas_limit = U256::from(block.gas_limit);
+    env.coinbase = Address::from(block.coinbase);
+    env.basefee = U256::from_be_bytes(block.basefee);
+    env.prevrandao = Some(B256::from(block.prevrandao));
+    RevmError::Ok
+}
+
+/// Executes the transaction and commits its changes to the database.
+///
+/// On success the result is written to `out`, it must be freed with [`revm_result_free`]. On
+/// error nothing is written and the database is unchanged.
+///
+/// # Safety
+///
+/// `evm` must be null or a live EVM and `out` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn revm_transact(evm: *mut RevmEvm, out: *mut *mut RevmResult) -> RevmError {
+    let Some(evm) = evm.as_mut() else {
+        return RevmError::NullPointer;
+    };
+    if out.is_null() {
+        return RevmError::NullPointer;
+    }
+    let ResultAndState { result, state } = match evm.evm.transact() {
+        Ok(result_and_state) => result_and_state,
+        Err(error) => return error.into(),
+    };
+    let changes = state_changes(&state);
+    evm.evm.db_mut().commit(state);
+
+    let (status, gas_used, output) = match result {
+        ExecutionResult::Success {
+            gas_used, output, ..
+        } => (RevmExecutionStatus::Success, gas_used, output.into_data()),
+        ExecutionResult::Revert { gas_used, output } => {
+            (RevmExecutionStatus::Revert, gas_used, output)
+        }
+        ExecutionResult::Halt { gas_used, .. } => {
+            (RevmExecutionStatus::Halt, gas_used, Bytes::new())
+        }
+    };
+    *out = Box::into_raw(Box::new(RevmResult {
+        status,
+        gas_used,
+        output,
+        changes,
+    }));
+    RevmError::Ok
+}
+
+/// Frees a result created by [`revm_transact`]. Does nothing if `result` is null.
+///
+/// # Safety
+///
+/// `result` must be null or created by [`revm_transact`] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_free(result: *mut RevmResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Returns the status of the transaction.
+///
+/// # Safety
+///
+/// `result` must be a live result.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_status(result: *const RevmResult) -> RevmExecutionStatus {
+    (*result).status
+}
+
+/// Returns the gas used by the transaction, after refunds.
+///
+/// # Safety
+///
+/// `result` must be a live result.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_gas_used(result: *const RevmResult) -> u64 {
+    (*result).gas_used
+}
+
+/// Returns the output of the transaction and writes its length to `len`.
+///
+/// The output is borrowed from `result`, it is empty if the transaction halted.
+///
+/// # Safety
+///
+/// `result` must be a live result and `len` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn revm_result_output(
+    result: *const RevmResult,
+    len: *mut usize,
+) -> *const u8 {
+    let output = &(*result).output;
+    *len = output.len();
+    output.as_ptr()
+}
+
+/// Returns an iterator over the state changes of the transaction.
+///
+/// The iterator borrows `result` and must not be used after it is freed.
+#[no_mangle]
+pub extern "C" fn revm_state_diff_iter(result: *const RevmResult) -> RevmStateDiffIter {
+    RevmStateDiffIter { result, next: 0 }
+}
+
+/// Writes the next state change to `out` and returns `true`, or returns `false` at the end.
+///
+/// # Safety
+///
+/// `iter` must be valid for reads and writes, its result must be live and `out` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn revm_state_diff_next(
+    iter: *mut RevmStateDiffIter,
+    out: *mut RevmStateChange,
+) -> bool {
+    let iter = &mut *iter;
+    let result = &*iter.result;
+    let Some(change) = result.changes.get(iter.next) else {
+        return false;
+    };
+    iter.next += 1;
+    ptr::write(out, *change);
+    true
+}
+
+/// Returns the buffer `data` of `len` bytes, `None` if it is null and not empty.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Flattens the touched accounts of `state` and their changed storage slots.
+fn state_changes(state: &EvmState) -> Vec<RevmStateChange> {
+    let mut accounts: Vec<_> = state
+        .iter()
+        .filter(|(_, account)| account.is_touched())
+        .collect();
+    accounts.sort_unstable_by_key(|(address, _)| **address);
+
+    let mut changes = Vec::new();
+    for (address, account) in accounts {
+        changes.push(RevmStateChange {
+            kind: RevmStateChangeKind::Account,
+            address: address.into_array(),
+            balance: account.info.balance.to_be_bytes(),
+            nonce: account.info.nonce,
+            ..Default::default()
+        });
+
+        let mut slots: Vec<_> = account.changed_storage_slots().collect();
+        slots.sort_unstable_by_key(|(slot, _)| **slot);
+        changes.extend(slots.into_iter().map(|(slot, value)| RevmStateChange {
+            kind: RevmStateChangeKind::Storage,
+            address: address.into_array(),
+            slot: slot.to_be_bytes(),
+            value: value.present_value.to_be_bytes(),
+            ..Default::default()
+        }));
+    }
+    changes
+}
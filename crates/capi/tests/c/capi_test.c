@@ -0,0 +1,121 @@
+/*
+ * Executes transactions through the C API, see `tests/c_program.rs`.
+ *
+ * Exits with zero on success and prints the first failed check otherwise.
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "revm.h"
+
+#define CHECK(cond)                                                            \
+    do {                                                                       \
+        if (!(cond)) {                                                         \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                          \
+        }                                                                      \
+    } while (0)
+
+#define ITERATIONS 100
+
+/* SSTORE(0, 42), MSTORE(0, 42), RETURN(0, 32). */
+static const uint8_t CODE[] = {
+    0x60, 0x2a, 0x60, 0x00, 0x55,
+    0x60, 0x2a, 0x60, 0x00, 0x52,
+    0x60, 0x20, 0x60, 0x00, 0xf3,
+};
+
+static void address(uint8_t out[20], uint16_t last) {
+    memset(out, 0, 20);
+    out[18] = (uint8_t)(last >> 8);
+    out[19] = (uint8_t)last;
+}
+
+static void word(uint8_t out[32], uint64_t value) {
+    memset(out, 0, 32);
+    for (int i = 0; i < 8; i++) {
+        out[31 - i] = (uint8_t)(value >> (8 * i));
+    }
+}
+
+int main(void) {
+    uint8_t caller[20], contract[20], balance[32], zero[32];
+    address(caller, 1);
+    /* Outside of the precompile range. */
+    address(contract, 0x1000);
+    word(balance, 1000000000);
+    word(zero, 0);
+
+    RevmEvm *evm = NULL;
+    CHECK(revm_new(200, &evm) == REVM_ERROR_INVALID_SPEC);
+    CHECK(evm == NULL);
+    /* SpecId::CANCUN */
+    CHECK(revm_new(17, &evm) == REVM_OK);
+    CHECK(evm != NULL);
+
+    CHECK(revm_set_account(evm, caller, balance, 0, NULL, 0) == REVM_OK);
+    CHECK(revm_set_account(evm, contract, zero, 1, CODE, sizeof(CODE)) == REVM_OK);
+    CHECK(revm_set_storage(evm, contract, zero, zero) == REVM_OK);
+    CHECK(revm_set_account(NULL, caller, balance, 0, NULL, 0) == REVM_ERROR_NULL_POINTER);
+
+    RevmBlockEnv block = {0};
+    block.number = 1;
+    block.timestamp = 12;
+    block.gas_limit = 30000000;
+    CHECK(revm_set_block(evm, &block) == REVM_OK);
+
+    RevmTxEnv tx = {0};
+    tx.gas_limit = 100000;
+    memcpy(tx.caller, caller, 20);
+    memcpy(tx.to, contract, 20);
+    word(tx.gas_price, 1);
+    CHECK(revm_set_tx(evm, &tx) == REVM_OK);
+
+    for (uint64_t i = 0; i < ITERATIONS; i++) {
+        /* The nonce of the caller is committed after every transaction. */
+        tx.has_nonce = true;
+        tx.nonce = i;
+        CHECK(revm_set_tx(evm, &tx) == REVM_OK);
+
+        RevmResult *result = NULL;
+        CHECK(revm_transact(evm, &result) == REVM_OK);
+        CHECK(revm_result_status(result) == REVM_STATUS_SUCCESS);
+        CHECK(revm_result_gas_used(result) > 21000);
+
+        size_t len = 0;
+        const uint8_t *output = revm_result_output(result, &len);
+        CHECK(len == 32);
+        CHECK(output[31] == 42);
+
+        /* The first transaction sets the slot, later ones leave it unchanged. */
+        int caller_changes = 0, storage_changes = 0;
+        RevmStateChange change;
+        RevmStateDiffIter iter = revm_state_diff_iter(result);
+        while (revm_state_diff_next(&iter, &change)) {
+            if (change.kind == REVM_STATE_CHANGE_ACCOUNT && memcmp(change.address, caller, 20) == 0) {
+                CHECK(change.nonce == i + 1);
+                caller_changes++;
+            }
+            if (change.kind == REVM_STATE_CHANGE_STORAGE) {
+                CHECK(memcmp(change.address, contract, 20) == 0);
+                CHECK(change.value[31] == 42);
+                storage_changes++;
+            }
+        }
+        CHECK(caller_changes == 1);
+        CHECK(storage_changes == (i == 0 ? 1 : 0));
+
+        revm_result_free(result);
+    }
+
+    /* A nonce too low is rejected and nothing is written. */
+    tx.nonce = 0;
+    CHECK(revm_set_tx(evm, &tx) == REVM_OK);
+    RevmResult *result = NULL;
+    CHECK(revm_transact(evm, &result) == REVM_ERROR_INVALID_TRANSACTION);
+    CHECK(result == NULL);
+
+    revm_free(evm);
+    return 0;
+}
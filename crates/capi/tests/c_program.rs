@@ -0,0 +1,47 @@
+//! Compiles `tests/c/capi_test.c` against `include/revm.h` and the static library and runs it.
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Tests are in `target/<profile>/deps`, next to which the static library is built.
+    let profile_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .to_path_buf();
+    let static_lib = profile_dir.join("librevm_capi.a");
+    assert!(static_lib.exists(), "{} is not built", static_lib.display());
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let program = out_dir.join("capi_test");
+
+    let compiler = cc::Build::new()
+        .target(env!("REVM_CAPI_TARGET"))
+        .host(env!("REVM_CAPI_TARGET"))
+        .opt_level(0)
+        .out_dir(&out_dir)
+        .cargo_metadata(false)
+        .warnings_into_errors(true)
+        .get_compiler();
+    let status = compiler
+        .to_command()
+        .arg(manifest_dir.join("tests/c/capi_test.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(&static_lib)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .unwrap();
+    assert!(status.success(), "compiling the C test program failed");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
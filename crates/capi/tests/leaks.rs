@@ -0,0 +1,134 @@
+//! Checks that repeated calls of the C API free everything they allocate.
+
+use revm_capi::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    ptr,
+    sync::atomic::{AtomicIsize, Ordering},
+};
+
+/// Allocator counting the bytes currently allocated.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size() as isize, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const CALLER: [u8; 20] = [1; 20];
+const CONTRACT: [u8; 20] = [2; 20];
+
+/// SSTORE(0, CALLDATALOAD(0)), RETURN(0, 32).
+const CODE: [u8; 11] = [
+    0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x60, 0x20, 0x60, 0x00, 0xf3,
+];
+
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Creates an EVM, executes a few transactions, iterates their state changes and frees
+/// everything.
+unsafe fn run() {
+    let mut evm = ptr::null_mut();
+    assert_eq!(revm_new(17, &mut evm), RevmError::Ok);
+    let balance = word(1_000_000_000);
+    assert_eq!(
+        revm_set_account(evm, &CALLER, &balance, 0, ptr::null(), 0),
+        RevmError::Ok
+    );
+    assert_eq!(
+        revm_set_account(evm, &CONTRACT, &word(0), 1, CODE.as_ptr(), CODE.len()),
+        RevmError::Ok
+    );
+    assert_eq!(
+        revm_set_storage(evm, &CONTRACT, &word(1), &word(1)),
+        RevmError::Ok
+    );
+
+    for i in 0..4 {
+        let data = word(i + 1);
+        let tx = RevmTxEnv {
+            gas_limit: 100_000,
+            nonce: 0,
+            data: data.as_ptr(),
+            data_len: data.len(),
+            caller: CALLER,
+            to: CONTRACT,
+            gas_price: word(1),
+            value: word(0),
+            is_create: false,
+            has_nonce: false,
+        };
+        assert_eq!(revm_set_tx(evm, &tx), RevmError::Ok);
+
+        let mut result = ptr::null_mut();
+        assert_eq!(revm_transact(evm, &mut result), RevmError::Ok);
+        assert_eq!(revm_result_status(result), RevmExecutionStatus::Success);
+        let mut len = 0;
+        revm_result_output(result, &mut len);
+        assert_eq!(len, 32);
+
+        let mut iter = revm_state_diff_iter(result);
+        let mut change = RevmStateChange::default();
+        let mut storage_changes = 0;
+        while revm_state_diff_next(&mut iter, &mut change) {
+            if change.kind == RevmStateChangeKind::Storage {
+                assert_eq!(change.value, data);
+                storage_changes += 1;
+            }
+        }
+        assert_eq!(storage_changes, 1);
+        revm_result_free(result);
+    }
+
+    // Errors don't leak either.
+    let mut result = ptr::null_mut();
+    let tx = RevmTxEnv {
+        gas_limit: 100_000,
+        nonce: 0,
+        data: ptr::null(),
+        data_len: 0,
+        caller: CALLER,
+        to: CONTRACT,
+        gas_price: word(1),
+        value: word(0),
+        is_create: false,
+        has_nonce: true,
+    };
+    assert_eq!(revm_set_tx(evm, &tx), RevmError::Ok);
+    assert_eq!(
+        revm_transact(evm, &mut result),
+        RevmError::InvalidTransaction
+    );
+    assert!(result.is_null());
+
+    revm_free(evm);
+}
+
+#[test]
+fn repeated_calls_do_not_leak() {
+    unsafe {
+        // Lazily initialized statics, like the precompiles, are allocated once.
+        run();
+        let allocated = ALLOCATED.load(Ordering::SeqCst);
+        for _ in 0..100 {
+            run();
+        }
+        assert_eq!(ALLOCATED.load(Ordering::SeqCst), allocated);
+    }
+}
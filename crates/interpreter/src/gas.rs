@@ -2,20 +2,37 @@
 
 mod calc;
 mod constants;
+mod pricing;
 
 pub use calc::*;
 pub use constants::*;
+pub use pricing::{reprice, GasComponent, GasPricingOverride};
 
 /// Represents the state of gas during execution.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gas {
     /// The initial gas limit. This is constant throughout execution.
     limit: u64,
     /// The remaining gas.
     remaining: u64,
-    /// Refunded gas. This is used only at the end of execution.
+    /// Refunded gas accumulated during execution. This is used only at the end of execution.
     refunded: i64,
+    /// Max refund applied at the end of execution, set by [`Gas::set_final_refund`].
+    #[cfg_attr(feature = "serde", serde(default = "no_refund_cap"))]
+    refund_cap: u64,
+}
+
+#[cfg(feature = "serde")]
+const fn no_refund_cap() -> u64 {
+    u64::MAX
+}
+
+impl Default for Gas {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0)
+    }
 }
 
 impl Gas {
@@ -26,6 +43,7 @@ impl Gas {
             limit,
             remaining: limit,
             refunded: 0,
+            refund_cap: u64::MAX,
         }
     }
 
@@ -36,6 +54,7 @@ impl Gas {
             limit,
             remaining: 0,
             refunded: 0,
+            refund_cap: u64::MAX,
         }
     }
 
@@ -54,22 +73,34 @@ impl Gas {
         0
     }
 
-    /// Returns the total amount of gas that was refunded.
+    /// Returns the refund accumulated during execution, before it is capped by
+    /// [`Self::set_final_refund`].
     #[inline]
     pub const fn refunded(&self) -> i64 {
         self.refunded
     }
 
+    /// Returns the refund applied at the end of execution, the accumulated refund capped by
+    /// [`Self::set_final_refund`]. Equal to [`Self::refunded`] until then.
+    #[inline]
+    pub const fn final_refund(&self) -> i64 {
+        if self.refund_cap == u64::MAX || (self.refunded as u64) < self.refund_cap {
+            self.refunded
+        } else {
+            self.refund_cap as i64
+        }
+    }
+
     /// Returns the total amount of gas spent.
     #[inline]
     pub const fn spent(&self) -> u64 {
         self.limit - self.remaining
     }
 
-    /// Returns the total amount of gas spent, minus the refunded gas.
+    /// Returns the total amount of gas spent, minus the [final refund](Self::final_refund).
     #[inline]
     pub const fn spent_sub_refunded(&self) -> u64 {
-        self.spent().saturating_sub(self.refunded as u64)
+        self.spent().saturating_sub(self.final_refund() as u64)
     }
 
     /// Returns the amount of gas remaining.
@@ -104,15 +135,37 @@ impl Gas {
         self.refunded += refund;
     }
 
-    /// Set a refund value for final refund.
+    /// Caps the final refund, the accumulated refund is kept.
     ///
     /// Max refund value is limited to Nth part (depending of fork) of gas spend.
     ///
     /// Related to EIP-3529: Reduction in refunds
     #[inline]
     pub fn set_final_refund(&mut self, is_london: bool) {
-        let max_refund_quotient = if is_london { 5 } else { 2 };
-        self.refunded = (self.refunded() as u64).min(self.spent() / max_refund_quotient) as i64;
+        self.set_final_refund_with_quotient(if is_london {
+            MAX_REFUND_QUOTIENT_LONDON
+        } else {
+            MAX_REFUND_QUOTIENT_FRONTIER
+        });
+    }
+
+    /// Caps the final refund to `1 / quotient` of gas spend, the accumulated refund is kept.
+    ///
+    /// A quotient of zero disables refunds.
+    #[inline]
+    pub fn set_final_refund_with_quotient(&mut self, quotient: u64) {
+        self.refund_cap = self.spent().checked_div(quotient).unwrap_or_default();
+    }
+
+    /// Returns the final refund, the recorded refund limited to `1 / quotient` of gas spend.
+    ///
+    /// A quotient of zero disables refunds.
+    #[inline]
+    pub fn final_refund_with_quotient(&self, quotient: u64) -> i64 {
+        match self.spent().checked_div(quotient) {
+            Some(max_refund) => (self.refunded() as u64).min(max_refund) as i64,
+            None => 0,
+        }
     }
 
     /// Set a refund value. This overrides the current refund value.
@@ -3,7 +3,7 @@ use revm_primitives::eip7702;
 use super::constants::*;
 use crate::{
     num_words,
-    primitives::{AccessListItem, SpecId, U256},
+    primitives::{AccessListItem, CalldataStats, SpecId, U256},
     AccountLoad, Eip7702CodeLoad, SStoreResult, SelfDestructResult, StateLoad,
 };
 
@@ -17,6 +17,17 @@ macro_rules! tri {
     };
 }
 
+/// Returns the max refund quotient of the spec, refunds are limited to `1 / quotient` of gas
+/// spend.
+#[inline]
+pub const fn max_refund_quotient(spec_id: SpecId) -> u64 {
+    if spec_id.is_enabled_in(SpecId::LONDON) {
+        MAX_REFUND_QUOTIENT_LONDON
+    } else {
+        MAX_REFUND_QUOTIENT_FRONTIER
+    }
+}
+
 /// `SSTORE` opcode refund calculation.
 #[allow(clippy::collapsible_else_if)]
 #[inline]
@@ -187,6 +198,45 @@ pub const fn sload_cost(spec_id: SpecId, is_cold: bool) -> u64 {
     }
 }
 
+/// `BALANCE` opcode cost calculation.
+#[inline]
+pub const fn balance_cost(spec_id: SpecId, is_cold: bool) -> u64 {
+    if spec_id.is_enabled_in(SpecId::BERLIN) {
+        warm_cold_cost(is_cold)
+    } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+        // EIP-1884: Repricing for trie-size-dependent opcodes
+        700
+    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
+        400
+    } else {
+        20
+    }
+}
+
+/// `EXTCODESIZE` opcode cost calculation.
+#[inline]
+pub const fn extcodesize_cost(spec_id: SpecId, is_cold: bool) -> u64 {
+    if spec_id.is_enabled_in(SpecId::BERLIN) {
+        warm_cold_cost(is_cold)
+    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
+        700
+    } else {
+        20
+    }
+}
+
+/// `EXTCODEHASH` opcode cost calculation.
+#[inline]
+pub const fn extcodehash_cost(spec_id: SpecId, is_cold: bool) -> u64 {
+    if spec_id.is_enabled_in(SpecId::BERLIN) {
+        warm_cold_cost(is_cold)
+    } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+        700
+    } else {
+        400
+    }
+}
+
 /// `SSTORE` opcode cost calculation.
 #[inline]
 pub fn sstore_cost(spec_id: SpecId, vals: &SStoreResult, gas: u64, is_cold: bool) -> Option<u64> {
@@ -194,7 +244,35 @@ pub fn sstore_cost(spec_id: SpecId, vals: &SStoreResult, gas: u64, is_cold: bool
     if spec_id.is_enabled_in(SpecId::ISTANBUL) && gas <= CALL_STIPEND {
         return None;
     }
+    Some(sstore_gas(spec_id, vals, is_cold))
+}
 
+/// Returns the cost and refund of an `SSTORE` of `new` to a slot with the `original` value of the
+/// transaction and the `current` value.
+///
+/// The EIP-1706 check of the remaining gas done by [`sstore_cost`] is not included. The refund
+/// can be negative when an earlier refund of the slot is reverted.
+#[inline]
+pub fn sstore_cost_and_refund(
+    spec_id: SpecId,
+    original: U256,
+    current: U256,
+    new: U256,
+    is_cold: bool,
+) -> (u64, i64) {
+    let vals = SStoreResult {
+        original_value: original,
+        present_value: current,
+        new_value: new,
+    };
+    (
+        sstore_gas(spec_id, &vals, is_cold),
+        sstore_refund(spec_id, &vals),
+    )
+}
+
+#[inline]
+fn sstore_gas(spec_id: SpecId, vals: &SStoreResult, is_cold: bool) -> u64 {
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         // Berlin specification logic
         let mut gas_cost = istanbul_sstore_cost::<WARM_STORAGE_READ_COST, WARM_SSTORE_RESET>(vals);
@@ -202,15 +280,13 @@ pub fn sstore_cost(spec_id: SpecId, vals: &SStoreResult, gas: u64, is_cold: bool
         if is_cold {
             gas_cost += COLD_SLOAD_COST;
         }
-        Some(gas_cost)
+        gas_cost
     } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
         // Istanbul logic
-        Some(istanbul_sstore_cost::<INSTANBUL_SLOAD_GAS, SSTORE_RESET>(
-            vals,
-        ))
+        istanbul_sstore_cost::<INSTANBUL_SLOAD_GAS, SSTORE_RESET>(vals)
     } else {
         // Frontier logic
-        Some(frontier_sstore_cost(vals))
+        frontier_sstore_cost(vals)
     }
 }
 
@@ -325,6 +401,41 @@ pub const fn call_cost(spec_id: SpecId, transfers_value: bool, account_load: Acc
     gas
 }
 
+/// Calculates the gas forwarded by a call instruction, returns the gas limit of the new frame and
+/// the gas charged to the caller for it.
+///
+/// `remaining` is the gas left to the caller after paying for the call itself, see [`call_cost`],
+/// and `requested` the gas operand of the instruction.
+///
+/// Since TANGERINE (EIP-150) at most 63/64 of the remaining gas is forwarded. Before, the
+/// requested gas is forwarded as is and the call runs out of gas if it is above `remaining`.
+///
+/// [`crate::OpCode::CALL`] and [`crate::OpCode::CALLCODE`] transferring value add the
+/// [`CALL_STIPEND`] to the gas limit, free of charge for the caller.
+#[inline]
+pub const fn calc_call_gas(
+    spec_id: SpecId,
+    remaining: u64,
+    requested: u64,
+    has_value: bool,
+    is_call_or_callcode: bool,
+) -> (u64, u64) {
+    let mut gas_cost = requested;
+    if spec_id.is_enabled_in(SpecId::TANGERINE) {
+        let max_forwarded = remaining - remaining / 64;
+        if max_forwarded < gas_cost {
+            gas_cost = max_forwarded;
+        }
+    }
+
+    let gas_limit = if has_value && is_call_or_callcode {
+        gas_cost.saturating_add(CALL_STIPEND)
+    } else {
+        gas_cost
+    };
+    (gas_limit, gas_cost)
+}
+
 /// Berlin warm and cold storage access cost for account access.
 #[inline]
 pub const fn warm_cold_cost(is_cold: bool) -> u64 {
@@ -335,6 +446,58 @@ pub const fn warm_cold_cost(is_cold: bool) -> u64 {
     }
 }
 
+/// Returns the part of an account access cost that is charged because the account is cold.
+#[inline]
+pub const fn cold_account_access_surcharge(spec_id: SpecId, is_cold: bool) -> u64 {
+    if is_cold && spec_id.is_enabled_in(SpecId::BERLIN) {
+        COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST
+    } else {
+        0
+    }
+}
+
+/// Returns the part of the [`call_cost`] that is charged because the account, or its delegate, is
+/// cold.
+#[inline]
+pub const fn call_cold_surcharge(spec_id: SpecId, account_load: &AccountLoad) -> u64 {
+    let load = &account_load.load;
+    let mut gas = cold_account_access_surcharge(spec_id, load.state_load.is_cold);
+    if let Some(is_cold) = load.is_delegate_account_cold {
+        gas += cold_account_access_surcharge(spec_id, is_cold);
+    }
+    gas
+}
+
+/// Returns the part of an `SLOAD` cost that is charged because the slot is cold.
+#[inline]
+pub const fn cold_sload_surcharge(spec_id: SpecId, is_cold: bool) -> u64 {
+    if is_cold && spec_id.is_enabled_in(SpecId::BERLIN) {
+        COLD_SLOAD_COST - WARM_STORAGE_READ_COST
+    } else {
+        0
+    }
+}
+
+/// Returns the part of an `SSTORE` cost that is charged because the slot is cold.
+#[inline]
+pub const fn cold_sstore_surcharge(spec_id: SpecId, is_cold: bool) -> u64 {
+    if is_cold && spec_id.is_enabled_in(SpecId::BERLIN) {
+        COLD_SLOAD_COST
+    } else {
+        0
+    }
+}
+
+/// Returns the part of a `SELFDESTRUCT` cost that is charged because the target is cold.
+#[inline]
+pub const fn cold_selfdestruct_surcharge(spec_id: SpecId, is_cold: bool) -> u64 {
+    if is_cold && spec_id.is_enabled_in(SpecId::BERLIN) {
+        COLD_ACCOUNT_ACCESS_COST
+    } else {
+        0
+    }
+}
+
 /// Berlin warm and cold storage access cost for account access.
 ///
 /// If delegation is Some, add additional cost for delegation account load.
@@ -362,7 +525,8 @@ pub const fn memory_gas(num_words: u64) -> u64 {
 }
 
 /// Init and floor gas from transaction
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitialAndFloorGas {
     /// Initial gas for transaction.
     pub initial_gas: u64,
@@ -371,40 +535,66 @@ pub struct InitialAndFloorGas {
     pub floor_gas: u64,
 }
 
-/// Initial gas that is deducted for transaction to be included.
-/// Initial gas contains initial stipend gas, gas for access list and input data.
-///
-/// # Returns
+/// Itemized intrinsic gas of a transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntrinsicGasComponents {
+    /// Base stipend, 21000 for calls and 53000 for creates since Homestead.
+    pub base: u64,
+    /// Gas for zero bytes of the input.
+    pub calldata_zero_bytes: u64,
+    /// Gas for non-zero bytes of the input, cheaper since Istanbul (EIP-2028).
+    pub calldata_non_zero_bytes: u64,
+    /// Gas for access list accounts, since Berlin (EIP-2930).
+    pub access_list_accounts: u64,
+    /// Gas for access list storage keys, since Berlin (EIP-2930).
+    pub access_list_slots: u64,
+    /// Gas for init code words of creates, since Shanghai (EIP-3860).
+    pub initcode_words: u64,
+    /// Gas for authorization list items, since Prague (EIP-7702).
+    pub authorization_list: u64,
+}
+
+impl IntrinsicGasComponents {
+    /// Returns the sum of all components.
+    #[inline]
+    pub const fn total(&self) -> u64 {
+        self.base
+            + self.calldata_zero_bytes
+            + self.calldata_non_zero_bytes
+            + self.access_list_accounts
+            + self.access_list_slots
+            + self.initcode_words
+            + self.authorization_list
+    }
+}
+
+/// Itemized initial gas that is deducted for transaction to be included.
 ///
-/// - Intrinsic gas
-/// - Number of tokens in calldata
-pub fn calculate_initial_tx_gas(
+/// See [`calculate_initial_tx_gas`] for the total.
+pub fn calculate_initial_tx_gas_components(
     spec_id: SpecId,
     input: &[u8],
     is_create: bool,
     access_list: &[AccessListItem],
     authorization_list_num: u64,
-) -> InitialAndFloorGas {
-    let mut gas = InitialAndFloorGas::default();
+) -> IntrinsicGasComponents {
+    let mut gas = IntrinsicGasComponents::default();
 
-    let tokens_in_calldata = get_tokens_in_calldata(input, spec_id.is_enabled_in(SpecId::ISTANBUL));
-    gas.initial_gas += tokens_in_calldata * STANDARD_TOKEN_COST;
+    let stats = CalldataStats::new(input);
+    gas.calldata_zero_bytes = stats.zero_bytes * STANDARD_TOKEN_COST;
+    gas.calldata_non_zero_bytes = (stats.tokens(spec_id) - stats.zero_bytes) * STANDARD_TOKEN_COST;
 
     // get number of access list account and storages.
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         let accessed_slots: usize = access_list.iter().map(|item| item.storage_keys.len()).sum();
-        gas.initial_gas += access_list.len() as u64 * ACCESS_LIST_ADDRESS;
-        gas.initial_gas += accessed_slots as u64 * ACCESS_LIST_STORAGE_KEY;
+        gas.access_list_accounts = access_list.len() as u64 * ACCESS_LIST_ADDRESS;
+        gas.access_list_slots = accessed_slots as u64 * ACCESS_LIST_STORAGE_KEY;
     }
 
     // base stipend
-    gas.initial_gas += if is_create {
-        if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
-            // EIP-2: Homestead Hard-fork Changes
-            53000
-        } else {
-            21000
-        }
+    gas.base = if is_create && spec_id.is_enabled_in(SpecId::HOMESTEAD) {
+        // EIP-2: Homestead Hard-fork Changes
+        53000
     } else {
         21000
     };
@@ -412,15 +602,47 @@ pub fn calculate_initial_tx_gas(
     // EIP-3860: Limit and meter initcode
     // Init code stipend for bytecode analysis
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        gas.initial_gas += initcode_cost(input.len() as u64)
+        gas.initcode_words = initcode_cost(input.len() as u64);
     }
 
     // EIP-7702
     if spec_id.is_enabled_in(SpecId::PRAGUE) {
-        gas.initial_gas += authorization_list_num * eip7702::PER_EMPTY_ACCOUNT_COST;
+        gas.authorization_list = authorization_list_num * eip7702::PER_EMPTY_ACCOUNT_COST;
+    }
+
+    gas
+}
+
+/// Initial gas that is deducted for transaction to be included.
+/// Initial gas contains initial stipend gas, gas for access list and input data.
+///
+/// # Returns
+///
+/// - Intrinsic gas
+/// - Floor gas of EIP-7623, zero before Prague
+pub fn calculate_initial_tx_gas(
+    spec_id: SpecId,
+    input: &[u8],
+    is_create: bool,
+    access_list: &[AccessListItem],
+    authorization_list_num: u64,
+) -> InitialAndFloorGas {
+    let components = calculate_initial_tx_gas_components(
+        spec_id,
+        input,
+        is_create,
+        access_list,
+        authorization_list_num,
+    );
+
+    let mut gas = InitialAndFloorGas {
+        initial_gas: components.total(),
+        floor_gas: 0,
+    };
 
-        // Calculate gas floor for EIP-7623
-        gas.floor_gas = calc_tx_floor_cost(tokens_in_calldata);
+    // Calculate gas floor for EIP-7623
+    if spec_id.is_enabled_in(SpecId::PRAGUE) {
+        gas.floor_gas = calc_tx_floor_cost(get_tokens_in_calldata(input, true));
     }
 
     gas
@@ -429,15 +651,13 @@ pub fn calculate_initial_tx_gas(
 /// Retrieve the total number of tokens in calldata.
 #[inline]
 pub fn get_tokens_in_calldata(input: &[u8], is_istanbul: bool) -> u64 {
-    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
-    let non_zero_data_len = input.len() as u64 - zero_data_len;
-    let non_zero_data_multiplier = if is_istanbul {
-        // EIP-2028: Transaction data gas cost reduction
-        NON_ZERO_BYTE_MULTIPLIER_ISTANBUL
+    // EIP-2028: Transaction data gas cost reduction
+    let spec_id = if is_istanbul {
+        SpecId::ISTANBUL
     } else {
-        NON_ZERO_BYTE_MULTIPLIER
+        SpecId::FRONTIER
     };
-    zero_data_len + non_zero_data_len * non_zero_data_multiplier
+    CalldataStats::new(input).tokens(spec_id)
 }
 
 /// Calculate the transaction cost floor as specified in EIP-7623.
@@ -445,3 +665,198 @@ pub fn get_tokens_in_calldata(input: &[u8], is_istanbul: bool) -> u64 {
 pub fn calc_tx_floor_cost(tokens_in_calldata: u64) -> u64 {
     tokens_in_calldata * TOTAL_COST_FLOOR_PER_TOKEN + 21_000
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_gas_forwards_63_of_64() {
+        // 64_000 remaining retains 1_000.
+        for spec_id in [SpecId::TANGERINE, SpecId::CANCUN, SpecId::PRAGUE] {
+            let calc = |requested, has_value, is_call_or_callcode| {
+                calc_call_gas(spec_id, 64_000, requested, has_value, is_call_or_callcode)
+            };
+            // requested gas above the available gas is capped.
+            assert_eq!(calc(100_000, false, true), (63_000, 63_000));
+            assert_eq!(calc(u64::MAX, false, true), (63_000, 63_000));
+            assert_eq!(calc(63_000, false, true), (63_000, 63_000));
+            assert_eq!(calc(63_001, false, true), (63_000, 63_000));
+            assert_eq!(calc(62_999, false, true), (62_999, 62_999));
+            // the stipend is added on top of the capped gas for free.
+            assert_eq!(calc(100_000, true, true), (63_000 + CALL_STIPEND, 63_000));
+            // zero requested with value only forwards the stipend.
+            assert_eq!(calc(0, true, true), (CALL_STIPEND, 0));
+            assert_eq!(calc(0, false, true), (0, 0));
+            // DELEGATECALL and STATICCALL never receive the stipend.
+            assert_eq!(calc(0, true, false), (0, 0));
+            assert_eq!(calc(1_000, true, false), (1_000, 1_000));
+        }
+
+        // all remaining gas is retained below 64.
+        assert_eq!(
+            calc_call_gas(SpecId::CANCUN, 63, 100, false, true),
+            (63, 63)
+        );
+        assert_eq!(
+            calc_call_gas(SpecId::CANCUN, 64, 100, false, true),
+            (63, 63)
+        );
+        assert_eq!(
+            calc_call_gas(SpecId::CANCUN, 0, 100, true, true),
+            (CALL_STIPEND, 0)
+        );
+    }
+
+    #[test]
+    fn call_gas_before_tangerine() {
+        for spec_id in [SpecId::FRONTIER, SpecId::HOMESTEAD, SpecId::DAO_FORK] {
+            // requested gas is forwarded as is, even above the remaining gas.
+            assert_eq!(
+                calc_call_gas(spec_id, 64_000, 100_000, false, true),
+                (100_000, 100_000)
+            );
+            assert_eq!(
+                calc_call_gas(spec_id, 64_000, 64_000, true, true),
+                (64_000 + CALL_STIPEND, 64_000)
+            );
+            assert_eq!(
+                calc_call_gas(spec_id, 64_000, 0, true, true),
+                (CALL_STIPEND, 0)
+            );
+            assert_eq!(
+                calc_call_gas(spec_id, 64_000, u64::MAX, true, true),
+                (u64::MAX, u64::MAX)
+            );
+        }
+    }
+
+    /// Executes `PUSH1 new, PUSH1 0, SSTORE` for each of `values` on a warm slot with the
+    /// `original` value and returns the gas used, pushes included, and the refund.
+    fn sstore_sequence(spec_id: SpecId, original: u64, values: &[u64]) -> (u64, i64) {
+        let (mut gas, mut refund) = (0, 0);
+        let mut current = original;
+        for &new in values {
+            let (cost, sstore_refund) = sstore_cost_and_refund(
+                spec_id,
+                U256::from(original),
+                U256::from(current),
+                U256::from(new),
+                false,
+            );
+            gas += 2 * VERYLOW + cost;
+            refund += sstore_refund;
+            current = new;
+        }
+        (gas, refund)
+    }
+
+    /// Original value, stored values, and used gas and refund with Istanbul, Berlin and London.
+    ///
+    /// Istanbul values are from EIP-2200, London values from EIP-3529.
+    #[allow(clippy::type_complexity)]
+    const SSTORE_CASES: [(u64, &[u64], [(u64, i64); 3]); 17] = [
+        (0, &[0, 0], [(1612, 0), (212, 0), (212, 0)]),
+        (0, &[0, 1], [(20812, 0), (20112, 0), (20112, 0)]),
+        (0, &[1, 0], [(20812, 19200), (20112, 19900), (20112, 19900)]),
+        (0, &[1, 2], [(20812, 0), (20112, 0), (20112, 0)]),
+        (0, &[1, 1], [(20812, 0), (20112, 0), (20112, 0)]),
+        (1, &[0, 0], [(5812, 15000), (3012, 15000), (3012, 4800)]),
+        (1, &[0, 1], [(5812, 4200), (3012, 2800), (3012, 2800)]),
+        (1, &[0, 2], [(5812, 0), (3012, 0), (3012, 0)]),
+        (1, &[2, 0], [(5812, 15000), (3012, 15000), (3012, 4800)]),
+        (1, &[2, 3], [(5812, 0), (3012, 0), (3012, 0)]),
+        (1, &[2, 1], [(5812, 4200), (3012, 2800), (3012, 2800)]),
+        (1, &[2, 2], [(5812, 0), (3012, 0), (3012, 0)]),
+        (1, &[1, 0], [(5812, 15000), (3012, 15000), (3012, 4800)]),
+        (1, &[1, 2], [(5812, 0), (3012, 0), (3012, 0)]),
+        (1, &[1, 1], [(1612, 0), (212, 0), (212, 0)]),
+        (
+            0,
+            &[1, 0, 1],
+            [(40818, 19200), (40118, 19900), (40118, 19900)],
+        ),
+        (1, &[0, 1, 0], [(10818, 19200), (5918, 17800), (5918, 7600)]),
+    ];
+
+    #[test]
+    fn sstore_net_gas_metering() {
+        for (original, values, expected) in SSTORE_CASES {
+            for (spec_id, expected) in [SpecId::ISTANBUL, SpecId::BERLIN, SpecId::LONDON]
+                .into_iter()
+                .zip(expected)
+            {
+                assert_eq!(
+                    sstore_sequence(spec_id, original, values),
+                    expected,
+                    "{spec_id:?}: original {original}, values {values:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sstore_cold_slot() {
+        let (zero, one) = (U256::ZERO, U256::from(1));
+        for spec_id in [SpecId::BERLIN, SpecId::LONDON, SpecId::CANCUN] {
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, zero, zero, one, true).0,
+                SSTORE_SET + COLD_SLOAD_COST
+            );
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, one, one, one, true).0,
+                WARM_STORAGE_READ_COST + COLD_SLOAD_COST
+            );
+        }
+        // Slots are always warm before Berlin.
+        assert_eq!(
+            sstore_cost_and_refund(SpecId::ISTANBUL, zero, zero, one, true).0,
+            SSTORE_SET
+        );
+    }
+
+    #[test]
+    fn sstore_before_istanbul() {
+        let (zero, one, two) = (U256::ZERO, U256::from(1), U256::from(2));
+        for spec_id in [SpecId::FRONTIER, SpecId::PETERSBURG] {
+            // Only the current and new values matter.
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, one, zero, one, false),
+                (SSTORE_SET, 0)
+            );
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, zero, one, two, false),
+                (SSTORE_RESET, 0)
+            );
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, zero, one, zero, false),
+                (SSTORE_RESET, REFUND_SSTORE_CLEARS)
+            );
+            assert_eq!(
+                sstore_cost_and_refund(spec_id, zero, zero, zero, false),
+                (SSTORE_RESET, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn sstore_cost_checks_call_stipend() {
+        let vals = SStoreResult {
+            original_value: U256::ZERO,
+            present_value: U256::ZERO,
+            new_value: U256::from(1),
+        };
+        assert_eq!(
+            sstore_cost(SpecId::ISTANBUL, &vals, CALL_STIPEND, false),
+            None
+        );
+        assert_eq!(
+            sstore_cost(SpecId::ISTANBUL, &vals, CALL_STIPEND + 1, false),
+            Some(SSTORE_SET)
+        );
+        assert_eq!(
+            sstore_cost(SpecId::PETERSBURG, &vals, CALL_STIPEND, false),
+            Some(SSTORE_SET)
+        );
+    }
+}
@@ -25,7 +25,7 @@ pub const KECCAK256: u64 = 30;
 pub const KECCAK256WORD: u64 = 6;
 pub const COPY: u64 = 3;
 pub const BLOCKHASH: u64 = 20;
-pub const CODEDEPOSIT: u64 = 200;
+pub const CODEDEPOSIT: u64 = crate::primitives::CODE_DEPOSIT_GAS_PER_BYTE;
 
 /// EIP-1884: Repricing for trie-size-dependent opcodes
 pub const INSTANBUL_SLOAD_GAS: u64 = 800;
@@ -33,6 +33,11 @@ pub const SSTORE_SET: u64 = 20000;
 pub const SSTORE_RESET: u64 = 5000;
 pub const REFUND_SSTORE_CLEARS: i64 = 15000;
 
+/// Max refund quotient before London, refunds are limited to half of gas spend.
+pub const MAX_REFUND_QUOTIENT_FRONTIER: u64 = 2;
+/// EIP-3529: Reduction in refunds, refunds are limited to a fifth of gas spend.
+pub const MAX_REFUND_QUOTIENT_LONDON: u64 = 5;
+
 /// The standard cost of calldata token.
 pub const STANDARD_TOKEN_COST: u64 = 4;
 /// The cost of a non-zero byte in calldata.
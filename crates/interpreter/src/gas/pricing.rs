@@ -0,0 +1,160 @@
+use super::Gas;
+use crate::{opcode::static_gas, primitives::SpecId};
+use std::{vec, vec::Vec};
+
+/// A component of the dynamic gas cost of an instruction that [`GasPricingOverride`] scales.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GasComponent {
+    /// Memory expansion cost.
+    MemoryExpansion,
+    /// Part of an account or storage slot access cost charged because it is cold.
+    ColdAccess,
+    /// `SSTORE` cost, without the cold access part.
+    StorageWrite,
+}
+
+/// Gas pricing of a chain that reprices opcodes without changing their behavior.
+///
+/// Static gas overrides replace the cost reported by [`static_gas`] and only apply to opcodes
+/// that have a static cost at the spec. Multipliers are in percent of the cost of the spec, the
+/// default of `100` keeps it unchanged.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasPricingOverride {
+    /// Static gas overrides, indexed by opcode.
+    static_gas: Vec<Option<u16>>,
+    /// Multiplier of memory expansion costs, in percent.
+    pub memory_expansion_percent: u64,
+    /// Multiplier of the cold part of account and storage slot access costs, in percent.
+    pub cold_access_percent: u64,
+    /// Multiplier of `SSTORE` costs without their cold part, in percent.
+    pub storage_write_percent: u64,
+}
+
+impl Default for GasPricingOverride {
+    fn default() -> Self {
+        Self {
+            static_gas: vec![None; 256],
+            memory_expansion_percent: 100,
+            cold_access_percent: 100,
+            storage_write_percent: 100,
+        }
+    }
+}
+
+impl GasPricingOverride {
+    /// Creates a pricing that keeps every cost of the spec.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the static gas of the opcode.
+    pub fn with_static_gas(mut self, opcode: u8, gas: u16) -> Self {
+        self.static_gas[opcode as usize] = Some(gas);
+        self
+    }
+
+    /// Sets the memory expansion multiplier, in percent.
+    pub fn with_memory_expansion_percent(mut self, percent: u64) -> Self {
+        self.memory_expansion_percent = percent;
+        self
+    }
+
+    /// Sets the cold access multiplier, in percent.
+    pub fn with_cold_access_percent(mut self, percent: u64) -> Self {
+        self.cold_access_percent = percent;
+        self
+    }
+
+    /// Sets the storage write multiplier, in percent.
+    pub fn with_storage_write_percent(mut self, percent: u64) -> Self {
+        self.storage_write_percent = percent;
+        self
+    }
+
+    /// Returns the static gas of the opcode at the given spec, see [`static_gas`].
+    pub fn static_gas(&self, opcode: u8, spec_id: SpecId) -> Option<u16> {
+        let gas = static_gas(opcode, spec_id)?;
+        Some(self.static_gas[opcode as usize].unwrap_or(gas))
+    }
+
+    /// Returns the static gas of every opcode at the given spec, indexed by opcode.
+    ///
+    /// Same as [`static_gas_table`](crate::opcode::static_gas_table) with the overrides applied.
+    pub fn static_gas_table(&self, spec_id: SpecId) -> [Option<u16>; 256] {
+        core::array::from_fn(|opcode| self.static_gas(opcode as u8, spec_id))
+    }
+
+    /// Returns the cost of the component priced by this override.
+    #[inline]
+    pub fn price(&self, component: GasComponent, cost: u64) -> u64 {
+        let percent = match component {
+            GasComponent::MemoryExpansion => self.memory_expansion_percent,
+            GasComponent::ColdAccess => self.cold_access_percent,
+            GasComponent::StorageWrite => self.storage_write_percent,
+        };
+        let priced = cost as u128 * percent as u128 / 100;
+        priced.try_into().unwrap_or(u64::MAX)
+    }
+}
+
+/// Charges `priced` instead of the already charged `cost`.
+///
+/// Returns `false` if there is not enough gas left.
+#[inline]
+pub fn reprice(gas: &mut Gas, cost: u64, priced: u64) -> bool {
+    if priced >= cost {
+        gas.record_cost(priced - cost)
+    } else {
+        gas.erase_cost(cost - priced);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{ADD, MLOAD, SLOAD};
+
+    #[test]
+    fn static_gas_overrides() {
+        let pricing = GasPricingOverride::new()
+            .with_static_gas(ADD, 5)
+            .with_static_gas(SLOAD, 10)
+            .with_static_gas(MLOAD, 1);
+        assert_eq!(pricing.static_gas(ADD, SpecId::CANCUN), Some(5));
+        // Only opcodes with a static cost at the spec are overridden.
+        assert_eq!(pricing.static_gas(SLOAD, SpecId::ISTANBUL), Some(10));
+        assert_eq!(pricing.static_gas(SLOAD, SpecId::CANCUN), None);
+        assert_eq!(pricing.static_gas(MLOAD, SpecId::CANCUN), None);
+
+        let table = pricing.static_gas_table(SpecId::CANCUN);
+        assert_eq!(table[ADD as usize], Some(5));
+        assert_eq!(
+            table[0x02],
+            crate::opcode::static_gas_table(SpecId::CANCUN)[0x02]
+        );
+    }
+
+    #[test]
+    fn price_components() {
+        let pricing = GasPricingOverride::new()
+            .with_memory_expansion_percent(50)
+            .with_storage_write_percent(250);
+        assert_eq!(pricing.price(GasComponent::MemoryExpansion, 9), 4);
+        assert_eq!(pricing.price(GasComponent::ColdAccess, 2500), 2500);
+        assert_eq!(pricing.price(GasComponent::StorageWrite, 20_000), 50_000);
+        assert_eq!(
+            pricing.price(GasComponent::StorageWrite, u64::MAX),
+            u64::MAX
+        );
+
+        let mut gas = Gas::new(100);
+        assert!(gas.record_cost(10));
+        assert!(reprice(&mut gas, 10, 4));
+        assert_eq!(gas.spent(), 4);
+        assert!(reprice(&mut gas, 4, 20));
+        assert_eq!(gas.spent(), 20);
+        assert!(!reprice(&mut gas, 0, 200));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::primitives::{Address, Bytes, Env, Log, B256, U256};
 use core::ops::{Deref, DerefMut};
+use std::vec::Vec;
 
 mod dummy;
 pub use dummy::DummyHost;
@@ -16,6 +17,12 @@ pub trait Host {
     fn load_account_delegated(&mut self, address: Address) -> Option<AccountLoad>;
 
     /// Get the block hash of the given block `number`.
+    ///
+    /// The hash is only known for the
+    /// [`BLOCK_HASH_HISTORY`](crate::primitives::BLOCK_HASH_HISTORY) most recent complete blocks,
+    /// any other block, including the current and future ones, must return a zero hash. Returns
+    /// `None` if the lookup failed, which halts execution with
+    /// [`FatalExternalError`](crate::InstructionResult::FatalExternalError).
     fn block_hash(&mut self, number: u64) -> Option<B256>;
 
     /// Get balance of `address` and if the account is cold.
@@ -40,6 +47,35 @@ pub trait Host {
         value: U256,
     ) -> Option<StateLoad<SStoreResult>>;
 
+    /// Get storage values of `address` at each of the `indices`, in order.
+    ///
+    /// Warm/cold accounting is the same as calling [`Host::sload`] for each index, so an index
+    /// that appears twice is cold at most once. Returns `None` if any of the loads fails.
+    ///
+    /// Hosts backed by remote state can override it to fetch all slots in one request.
+    #[inline]
+    fn sload_many(&mut self, address: Address, indices: &[U256]) -> Option<Vec<StateLoad<U256>>> {
+        indices
+            .iter()
+            .map(|index| self.sload(address, *index))
+            .collect()
+    }
+
+    /// Set storage values of `address` for each of the `(index, value)` entries, in order.
+    ///
+    /// Same as calling [`Host::sstore`] for each entry. Returns `None` if any of the stores fails.
+    #[inline]
+    fn sstore_many(
+        &mut self,
+        address: Address,
+        entries: &[(U256, U256)],
+    ) -> Option<Vec<StateLoad<SStoreResult>>> {
+        entries
+            .iter()
+            .map(|(index, value)| self.sstore(address, *index, *value))
+            .collect()
+    }
+
     /// Get the transient storage value of `address` at `index`.
     fn tload(&mut self, address: Address, index: U256) -> U256;
 
@@ -251,6 +287,35 @@ pub struct SelfDestructResult {
     pub had_value: bool,
     pub target_exists: bool,
     pub previously_destroyed: bool,
+    /// What happened to the account and its balance.
+    pub outcome: SelfDestructOutcome,
+}
+
+/// Effect of a selfdestruct on the account.
+///
+/// Since Cancun ([EIP-6780](https://eips.ethereum.org/EIPS/eip-6780)) only accounts created in
+/// the same transaction are deleted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelfDestructOutcome {
+    /// Account is deleted at the end of the transaction and its balance is transferred to the
+    /// target.
+    #[default]
+    Deleted,
+    /// Account is deleted at the end of the transaction and the target is the account itself,
+    /// so its balance is burned.
+    Burned,
+    /// Account is not deleted, its code and storage are kept and only its balance is
+    /// transferred to the target. If the target is the account itself, nothing changes.
+    Swept,
+}
+
+impl SelfDestructOutcome {
+    /// Returns `true` if the account is deleted at the end of the transaction.
+    #[inline]
+    pub const fn is_deleted(&self) -> bool {
+        matches!(self, Self::Deleted | Self::Burned)
+    }
 }
 
 #[cfg(test)]
@@ -264,4 +329,32 @@ mod tests {
         assert_host::<DummyHost>();
         assert_host::<dyn Host>();
     }
+
+    #[test]
+    fn dummy_host_batched_storage_access_matches_scalar() {
+        let address = Address::ZERO;
+        let mut scalar = DummyHost::default();
+        scalar.storage.insert(U256::from(2), U256::from(20));
+        let mut batched = scalar.clone();
+
+        let indices = [1, 2, 1].map(U256::from);
+        let expected: Vec<_> = indices
+            .iter()
+            .map(|index| scalar.sload(address, *index).unwrap())
+            .collect();
+        let loads = batched.sload_many(address, &indices).unwrap();
+        assert_eq!(loads, expected);
+        assert_eq!(
+            loads.iter().map(|load| load.is_cold).collect::<Vec<_>>(),
+            [true, false, false]
+        );
+
+        let entries = [(1, 10), (3, 30), (3, 31)].map(|(i, v)| (U256::from(i), U256::from(v)));
+        let expected: Vec<_> = entries
+            .iter()
+            .map(|(index, value)| scalar.sstore(address, *index, *value).unwrap())
+            .collect();
+        assert_eq!(batched.sstore_many(address, &entries), Some(expected));
+        assert_eq!(batched, scalar);
+    }
 }
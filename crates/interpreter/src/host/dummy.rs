@@ -1,5 +1,8 @@
 use crate::{
-    primitives::{hash_map::Entry, Address, Bytes, Env, HashMap, Log, B256, KECCAK_EMPTY, U256},
+    primitives::{
+        hash_map::Entry, Address, Bytes, Env, HashMap, Log, B256, BLOCK_HASH_HISTORY, KECCAK_EMPTY,
+        U256,
+    },
     Host, SStoreResult, SelfDestructResult,
 };
 use std::vec::Vec;
@@ -13,6 +16,9 @@ pub struct DummyHost {
     pub storage: HashMap<U256, U256>,
     pub transient_storage: HashMap<U256, U256>,
     pub log: Vec<Log>,
+    /// Block hashes by number, missing blocks and blocks out of the `BLOCKHASH` window have a
+    /// zero hash.
+    pub block_hashes: HashMap<u64, B256>,
 }
 
 impl DummyHost {
@@ -31,6 +37,30 @@ impl DummyHost {
         self.storage.clear();
         self.log.clear();
     }
+
+    #[inline]
+    fn load(&mut self, index: U256) -> StateLoad<U256> {
+        match self.storage.entry(index) {
+            Entry::Occupied(entry) => StateLoad::new(*entry.get(), false),
+            Entry::Vacant(entry) => {
+                entry.insert(U256::ZERO);
+                StateLoad::new(U256::ZERO, true)
+            }
+        }
+    }
+
+    #[inline]
+    fn store(&mut self, index: U256, value: U256) -> StateLoad<SStoreResult> {
+        let present = self.storage.insert(index, value);
+        StateLoad {
+            data: SStoreResult {
+                original_value: U256::ZERO,
+                present_value: present.unwrap_or(U256::ZERO),
+                new_value: value,
+            },
+            is_cold: present.is_none(),
+        }
+    }
 }
 
 impl Host for DummyHost {
@@ -50,8 +80,14 @@ impl Host for DummyHost {
     }
 
     #[inline]
-    fn block_hash(&mut self, _number: u64) -> Option<B256> {
-        Some(B256::ZERO)
+    fn block_hash(&mut self, number: u64) -> Option<B256> {
+        let block_number = crate::as_u64_saturated!(self.env.block.number);
+        match block_number.checked_sub(number) {
+            Some(diff) if diff > 0 && diff <= BLOCK_HASH_HISTORY => {
+                Some(self.block_hashes.get(&number).copied().unwrap_or_default())
+            }
+            _ => Some(B256::ZERO),
+        }
     }
 
     #[inline]
@@ -71,13 +107,7 @@ impl Host for DummyHost {
 
     #[inline]
     fn sload(&mut self, _address: Address, index: U256) -> Option<StateLoad<U256>> {
-        match self.storage.entry(index) {
-            Entry::Occupied(entry) => Some(StateLoad::new(*entry.get(), false)),
-            Entry::Vacant(entry) => {
-                entry.insert(U256::ZERO);
-                Some(StateLoad::new(U256::ZERO, true))
-            }
-        }
+        Some(self.load(index))
     }
 
     #[inline]
@@ -87,15 +117,26 @@ impl Host for DummyHost {
         index: U256,
         value: U256,
     ) -> Option<StateLoad<SStoreResult>> {
-        let present = self.storage.insert(index, value);
-        Some(StateLoad {
-            data: SStoreResult {
-                original_value: U256::ZERO,
-                present_value: present.unwrap_or(U256::ZERO),
-                new_value: value,
-            },
-            is_cold: present.is_none(),
-        })
+        Some(self.store(index, value))
+    }
+
+    #[inline]
+    fn sload_many(&mut self, _address: Address, indices: &[U256]) -> Option<Vec<StateLoad<U256>>> {
+        Some(indices.iter().map(|index| self.load(*index)).collect())
+    }
+
+    #[inline]
+    fn sstore_many(
+        &mut self,
+        _address: Address,
+        entries: &[(U256, U256)],
+    ) -> Option<Vec<StateLoad<SStoreResult>>> {
+        Some(
+            entries
+                .iter()
+                .map(|(index, value)| self.store(*index, *value))
+                .collect(),
+        )
     }
 
     #[inline]
@@ -1,4 +1,5 @@
 use crate::primitives::{HaltReason, OutOfGasError, SuccessReason};
+use core::fmt;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
@@ -92,6 +93,13 @@ pub enum InstructionResult {
     EofAuxDataTooSmall,
     /// `EXT*CALL` target address needs to be padded with 0s.
     InvalidEXTCALLTarget,
+    /// Execution was cancelled through the cancellation token of the interpreter.
+    Cancelled,
+    /// Output exceeds the
+    /// [configured return data limit](crate::primitives::CfgEnv::max_return_data_size).
+    ReturnDataSizeLimit,
+    /// Called code starts with the EOF magic but is not a valid EOF container.
+    InvalidEOFCode,
 }
 
 impl From<SuccessReason> for InstructionResult {
@@ -137,8 +145,11 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::EofAuxDataTooSmall => Self::EofAuxDataTooSmall,
             HaltReason::EOFFunctionStackOverflow => Self::EOFFunctionStackOverflow,
             HaltReason::InvalidEXTCALLTarget => Self::InvalidEXTCALLTarget,
+            HaltReason::Cancelled => Self::Cancelled,
+            HaltReason::ReturnDataSizeLimit => Self::ReturnDataSizeLimit,
+            HaltReason::InvalidEOFCode => Self::InvalidEOFCode,
             #[cfg(feature = "optimism")]
-            HaltReason::FailedDeposit => Self::FatalExternalError,
+            HaltReason::FailedDeposit(_) => Self::FatalExternalError,
         }
     }
 }
@@ -197,6 +208,9 @@ macro_rules! return_error {
             | InstructionResult::EofAuxDataTooSmall
             | InstructionResult::EofAuxDataOverflow
             | InstructionResult::InvalidEXTCALLTarget
+            | InstructionResult::Cancelled
+            | InstructionResult::ReturnDataSizeLimit
+            | InstructionResult::InvalidEOFCode
     };
 }
 
@@ -322,10 +336,12 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::OverflowPayment => Self::Halt(HaltReason::OverflowPayment), // Check for first call is done separately.
             InstructionResult::PrecompileError => Self::Halt(HaltReason::PrecompileError),
             InstructionResult::NonceOverflow => Self::Halt(HaltReason::NonceOverflow),
-            InstructionResult::CreateContractSizeLimit
-            | InstructionResult::CreateContractStartingWithEF => {
+            InstructionResult::CreateContractSizeLimit => {
                 Self::Halt(HaltReason::CreateContractSizeLimit)
             }
+            InstructionResult::CreateContractStartingWithEF => {
+                Self::Halt(HaltReason::CreateContractStartingWithEF)
+            }
             InstructionResult::CreateInitCodeSizeLimit => {
                 Self::Halt(HaltReason::CreateInitCodeSizeLimit)
             }
@@ -340,6 +356,9 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::EofAuxDataOverflow => Self::Halt(HaltReason::EofAuxDataOverflow),
             InstructionResult::EofAuxDataTooSmall => Self::Halt(HaltReason::EofAuxDataTooSmall),
             InstructionResult::InvalidEXTCALLTarget => Self::Halt(HaltReason::InvalidEXTCALLTarget),
+            InstructionResult::Cancelled => Self::Halt(HaltReason::Cancelled),
+            InstructionResult::ReturnDataSizeLimit => Self::Halt(HaltReason::ReturnDataSizeLimit),
+            InstructionResult::InvalidEOFCode => Self::Halt(HaltReason::InvalidEOFCode),
             InstructionResult::InvalidExtDelegateCallTarget => {
                 Self::Internal(InternalResult::InvalidExtDelegateCallTarget)
             }
@@ -347,9 +366,37 @@ impl From<InstructionResult> for SuccessOrHalt {
     }
 }
 
+/// Error returned when converting an [`InstructionResult`] that is not a halt into a
+/// [`HaltReason`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NotHaltError(pub InstructionResult);
+
+impl fmt::Display for NotHaltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instruction result {:?} is not a halt", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotHaltError {}
+
+impl TryFrom<InstructionResult> for HaltReason {
+    type Error = NotHaltError;
+
+    fn try_from(result: InstructionResult) -> Result<Self, Self::Error> {
+        SuccessOrHalt::from(result)
+            .to_halt()
+            .ok_or(NotHaltError(result))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::InstructionResult;
+    use super::{NotHaltError, SuccessOrHalt};
+    use crate::{
+        primitives::{HaltReason, OutOfGasError},
+        InstructionResult,
+    };
 
     #[test]
     fn all_results_are_covered() {
@@ -411,6 +458,9 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::Cancelled,
+            InstructionResult::ReturnDataSizeLimit,
+            InstructionResult::InvalidEOFCode,
         ];
 
         for result in error_results {
@@ -419,4 +469,133 @@ mod tests {
             assert!(result.is_error());
         }
     }
+
+    /// Every [`InstructionResult`] variant.
+    const ALL: [InstructionResult; 43] = [
+        InstructionResult::Continue,
+        InstructionResult::Stop,
+        InstructionResult::Return,
+        InstructionResult::SelfDestruct,
+        InstructionResult::ReturnContract,
+        InstructionResult::Revert,
+        InstructionResult::CallTooDeep,
+        InstructionResult::OutOfFunds,
+        InstructionResult::CreateInitCodeStartingEF00,
+        InstructionResult::InvalidEOFInitCode,
+        InstructionResult::InvalidExtDelegateCallTarget,
+        InstructionResult::CallOrCreate,
+        InstructionResult::OutOfGas,
+        InstructionResult::MemoryOOG,
+        InstructionResult::MemoryLimitOOG,
+        InstructionResult::PrecompileOOG,
+        InstructionResult::InvalidOperandOOG,
+        InstructionResult::OpcodeNotFound,
+        InstructionResult::CallNotAllowedInsideStatic,
+        InstructionResult::StateChangeDuringStaticCall,
+        InstructionResult::InvalidFEOpcode,
+        InstructionResult::InvalidJump,
+        InstructionResult::NotActivated,
+        InstructionResult::StackUnderflow,
+        InstructionResult::StackOverflow,
+        InstructionResult::OutOfOffset,
+        InstructionResult::CreateCollision,
+        InstructionResult::OverflowPayment,
+        InstructionResult::PrecompileError,
+        InstructionResult::NonceOverflow,
+        InstructionResult::CreateContractSizeLimit,
+        InstructionResult::CreateContractStartingWithEF,
+        InstructionResult::CreateInitCodeSizeLimit,
+        InstructionResult::FatalExternalError,
+        InstructionResult::ReturnContractInNotInitEOF,
+        InstructionResult::EOFOpcodeDisabledInLegacy,
+        InstructionResult::EOFFunctionStackOverflow,
+        InstructionResult::EofAuxDataOverflow,
+        InstructionResult::EofAuxDataTooSmall,
+        InstructionResult::InvalidEXTCALLTarget,
+        InstructionResult::Cancelled,
+        InstructionResult::ReturnDataSizeLimit,
+        InstructionResult::InvalidEOFCode,
+    ];
+
+    #[test]
+    fn all_variants_categorized() {
+        // Variants are numbered sequentially within each category, the list must not skip any.
+        for pair in ALL.windows(2) {
+            let (prev, next) = (pair[0] as u8, pair[1] as u8);
+            assert!(
+                next == prev + 1 || matches!(next, 0x10 | 0x20 | 0x50),
+                "missing variant after {:?}",
+                pair[0]
+            );
+        }
+
+        for result in ALL {
+            let categories = [
+                result.is_ok(),
+                result.is_revert(),
+                result.is_error(),
+                result == InstructionResult::CallOrCreate,
+            ];
+            assert_eq!(
+                categories.iter().filter(|c| **c).count(),
+                1,
+                "{result:?} is in {categories:?}"
+            );
+
+            let outcome = SuccessOrHalt::from(result);
+            match HaltReason::try_from(result) {
+                Ok(reason) => {
+                    assert_eq!(outcome, SuccessOrHalt::Halt(reason));
+                    assert!(result.is_error() || result.is_revert(), "{result:?}");
+                    // Halts map back to an instruction result with the same halt reason.
+                    assert_eq!(
+                        HaltReason::try_from(InstructionResult::from(reason)),
+                        Ok(reason)
+                    );
+                }
+                Err(err) => {
+                    assert_eq!(err, NotHaltError(result));
+                    assert!(!outcome.is_halt(), "{result:?}");
+                }
+            }
+            if result.is_ok() && result != InstructionResult::Continue {
+                assert!(outcome.is_success(), "{result:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn halt_reasons_are_not_coerced() {
+        let cases = [
+            (
+                InstructionResult::CreateContractStartingWithEF,
+                HaltReason::CreateContractStartingWithEF,
+            ),
+            (
+                InstructionResult::CreateContractSizeLimit,
+                HaltReason::CreateContractSizeLimit,
+            ),
+            (
+                InstructionResult::MemoryLimitOOG,
+                HaltReason::OutOfGas(OutOfGasError::MemoryLimit),
+            ),
+            (
+                InstructionResult::InvalidEXTCALLTarget,
+                HaltReason::InvalidEXTCALLTarget,
+            ),
+        ];
+        for (result, reason) in cases {
+            assert_eq!(HaltReason::try_from(result), Ok(reason));
+            assert_eq!(InstructionResult::from(reason), result);
+        }
+
+        assert_eq!(
+            HaltReason::try_from(InstructionResult::Return),
+            Err(NotHaltError(InstructionResult::Return))
+        );
+        assert_eq!(
+            HaltReason::try_from(InstructionResult::FatalExternalError),
+            Err(NotHaltError(InstructionResult::FatalExternalError))
+        );
+    }
 }
@@ -7,6 +7,8 @@ pub mod bitwise;
 pub mod contract;
 pub mod control;
 pub mod data;
+#[cfg(feature = "experimental-opcodes")]
+pub mod experimental;
 pub mod host;
 pub mod host_env;
 pub mod i256;
@@ -6,16 +6,17 @@ use crate::{
     gas::{self, cost_per_word, EOF_CREATE_GAS, KECCAK256WORD, MIN_CALLEE_GAS},
     interpreter::Interpreter,
     primitives::{
-        eof::EofHeader, keccak256, Address, BerlinSpec, Bytes, Eof, Spec, SpecId::*, B256, U256,
+        create2_address, eof::EofHeader, Address, BerlinSpec, Bytes, Eof, Spec, SpecId::*, B256,
+        U256,
     },
     CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInputs, Host,
-    InstructionResult, InterpreterAction, InterpreterResult, MAX_INITCODE_SIZE,
+    InstructionResult, InterpreterAction, InterpreterResult,
 };
 use core::cmp::max;
 use std::boxed::Box;
 
 /// EOF Create instruction
-pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     require_eof!(interpreter);
     require_non_staticcall!(interpreter);
     gas!(interpreter, EOF_CREATE_GAS);
@@ -59,10 +60,11 @@ pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H)
         cost_per_word(sub_container.len() as u64, KECCAK256WORD)
     );
 
-    let created_address = interpreter
-        .contract
-        .target_address
-        .create2(salt.to_be_bytes(), keccak256(sub_container));
+    let created_address = create2_address(
+        interpreter.contract.target_address,
+        salt,
+        host.env().cfg.keccak.hash(&sub_container),
+    );
 
     let gas_limit = interpreter.gas().remaining_63_of_64_parts();
     gas!(interpreter, gas_limit);
@@ -140,6 +142,7 @@ pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &
             output,
             gas: interpreter.gas,
             result,
+            halt_context: None,
         },
     };
 }
@@ -171,8 +174,10 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
         return None;
     };
     // account_load.is_empty will be accounted if there is transfer value.
+    let cold_cost = gas::call_cold_surcharge(BerlinSpec::SPEC_ID, &account_load);
     let call_cost = gas::call_cost(BerlinSpec::SPEC_ID, transfers_value, account_load);
     gas!(interpreter, call_cost, None);
+    reprice!(interpreter, ColdAccess, cold_cost, None);
 
     // 7. Calculate the gas available to callee as caller’s
     // remaining gas reduced by max(ceil(gas/64), MIN_RETAINED_GAS) (MIN_RETAINED_GAS is 5000).
@@ -343,14 +348,7 @@ pub fn create<const IS_CREATE2: bool, H: Host + ?Sized, SPEC: Spec>(
     if len != 0 {
         // EIP-3860: Limit and meter initcode
         if SPEC::enabled(SHANGHAI) {
-            // Limit is set as double of max contract bytecode size
-            let max_initcode_size = host
-                .env()
-                .cfg
-                .limit_contract_code_size
-                .map(|limit| limit.saturating_mul(2))
-                .unwrap_or(MAX_INITCODE_SIZE);
-            if len > max_initcode_size {
+            if len > host.env().cfg.max_initcode_size() {
                 interpreter.instruction_result = InstructionResult::CreateInitCodeSizeLimit;
                 return;
             }
@@ -416,19 +414,16 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    let Some(mut gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, account_load, has_transfer, local_gas_limit)
-    else {
+    let Some(gas_limit) = calc_call_gas::<SPEC>(
+        interpreter,
+        account_load,
+        has_transfer,
+        true,
+        local_gas_limit,
+    ) else {
         return;
     };
 
-    gas!(interpreter, gas_limit);
-
-    // add call stipend if there is value to be transferred.
-    if has_transfer {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
-    }
-
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
         inputs: Box::new(CallInputs {
@@ -464,19 +459,12 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
     };
     // set is_empty to false as we are not creating this account.
     load.is_empty = false;
-    let Some(mut gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, load, !value.is_zero(), local_gas_limit)
+    let Some(gas_limit) =
+        calc_call_gas::<SPEC>(interpreter, load, !value.is_zero(), true, local_gas_limit)
     else {
         return;
     };
 
-    gas!(interpreter, gas_limit);
-
-    // add call stipend if there is value to be transferred.
-    if !value.is_zero() {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
-    }
-
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
         inputs: Box::new(CallInputs {
@@ -512,12 +500,11 @@ pub fn delegate_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter
     };
     // set is_empty to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas::<SPEC>(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas::<SPEC>(interpreter, load, false, false, local_gas_limit)
+    else {
         return;
     };
 
-    gas!(interpreter, gas_limit);
-
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
         inputs: Box::new(CallInputs {
@@ -553,10 +540,10 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     };
     // set is_empty to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas::<SPEC>(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas::<SPEC>(interpreter, load, false, false, local_gas_limit)
+    else {
         return;
     };
-    gas!(interpreter, gas_limit);
 
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
@@ -575,3 +562,257 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     };
     interpreter.instruction_result = InstructionResult::CallOrCreate;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        opcode::{
+            make_instruction_table, CALL, CALLCODE, DELEGATECALL, EXTCALL, EXTSTATICCALL,
+            STATICCALL,
+        },
+        primitives::{address, spec_to_generic, Bytecode, PragueSpec, SpecId},
+        CallOutcome, DummyHost, Gas, SharedMemory,
+    };
+
+    /// Interpreter executing a single EOF `opcode` with `gas_limit` gas.
+    fn eof_interpreter(opcode: u8, gas_limit: u64) -> Interpreter {
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(Bytes::from([opcode])));
+        interp.is_eof = true;
+        interp.gas = Gas::new(gas_limit);
+        interp
+    }
+
+    /// Pushes EXTSTATICCALL operands calling `target` without input.
+    fn push_extstaticcall_operands(interp: &mut Interpreter, target: U256) {
+        interp.stack.push(U256::ZERO).unwrap();
+        interp.stack.push(U256::ZERO).unwrap();
+        interp.stack.push(target).unwrap();
+    }
+
+    #[test]
+    fn extcall_status_codes() {
+        let cases = [
+            (InstructionResult::Stop, 0, 1),
+            (InstructionResult::Revert, 1, 0),
+            // Light failures are reported the same way as a revert.
+            (InstructionResult::OutOfFunds, 1, 0),
+            (InstructionResult::CallTooDeep, 1, 0),
+            (InstructionResult::OutOfGas, 2, 0),
+            (InstructionResult::InvalidFEOpcode, 2, 0),
+        ];
+        for (result, eof_status, legacy_status) in cases {
+            for (is_eof, status) in [(true, eof_status), (false, legacy_status)] {
+                let mut interp = eof_interpreter(EXTCALL, 10_000);
+                interp.is_eof = is_eof;
+                let outcome = CallOutcome::new(
+                    InterpreterResult::new(result, Bytes::new(), Gas::new(0)),
+                    0..0,
+                );
+                interp.insert_call_outcome(&mut SharedMemory::new(), outcome);
+                assert_eq!(
+                    interp.stack.pop(),
+                    Ok(U256::from(status)),
+                    "{result:?} is_eof: {is_eof}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn extcall_retained_gas_boundary() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+        // Warm account access cost.
+        let access_cost = 100;
+        // 5000 is retained, leaving exactly MIN_CALLEE_GAS to the callee.
+        let boundary = access_cost + 5000 + MIN_CALLEE_GAS;
+
+        let mut interp = eof_interpreter(EXTSTATICCALL, boundary);
+        push_extstaticcall_operands(&mut interp, U256::from(1));
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::CallOrCreate);
+        let InterpreterAction::Call { inputs } = &interp.next_action else {
+            panic!("expected a call, got {:?}", interp.next_action);
+        };
+        assert_eq!(inputs.gas_limit, MIN_CALLEE_GAS);
+        assert!(inputs.is_static);
+
+        // One gas less is a light failure, the call is not made and 1 is pushed.
+        let mut interp = eof_interpreter(EXTSTATICCALL, boundary - 1);
+        push_extstaticcall_operands(&mut interp, U256::from(1));
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert_eq!(interp.next_action, InterpreterAction::None);
+        assert_eq!(interp.stack.pop(), Ok(U256::from(1)));
+        assert_eq!(interp.gas.remaining(), boundary - 1 - access_cost);
+    }
+
+    #[test]
+    fn extcall_rejects_dirty_address() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp = eof_interpreter(EXTSTATICCALL, 100_000);
+        push_extstaticcall_operands(&mut interp, U256::from(1) << 160);
+        interp.step(&table, &mut host);
+        assert_eq!(
+            interp.instruction_result,
+            InstructionResult::InvalidEXTCALLTarget
+        );
+    }
+
+    #[test]
+    fn extcall_value_in_static_context() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp = eof_interpreter(EXTCALL, 100_000);
+        interp.is_static = true;
+        // value, input size, input offset, target.
+        interp.stack.push(U256::from(1)).unwrap();
+        push_extstaticcall_operands(&mut interp, U256::from(1));
+        interp.step(&table, &mut host);
+        assert_eq!(
+            interp.instruction_result,
+            InstructionResult::CallNotAllowedInsideStatic
+        );
+    }
+
+    #[test]
+    fn eof_calls_disabled_in_legacy() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp = eof_interpreter(EXTSTATICCALL, 100_000);
+        interp.is_eof = false;
+        push_extstaticcall_operands(&mut interp, U256::from(1));
+        interp.step(&table, &mut host);
+        assert_eq!(
+            interp.instruction_result,
+            InstructionResult::EOFOpcodeDisabledInLegacy
+        );
+    }
+
+    const CALLER: Address = address!("00000000000000000000000000000000000000ca");
+    const TARGET: Address = address!("0000000000000000000000000000000000000ba0");
+    const CALLEE: Address = address!("0000000000000000000000000000000000000dea");
+
+    /// Every spec, from Frontier to the latest.
+    fn all_specs() -> impl Iterator<Item = SpecId> {
+        (0..=u8::MAX).filter_map(SpecId::n)
+    }
+
+    /// Legacy interpreter of `TARGET` called by `CALLER` with a value of 5, executing a single
+    /// CALL-family `opcode` to `CALLEE` with the given `gas`, `value` and input size.
+    fn call_interpreter(opcode: u8, gas: u64, value: Option<U256>, in_len: U256) -> Interpreter {
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw(Bytes::from([opcode])));
+        interp.gas = Gas::new(1_000_000);
+        interp.contract.caller = CALLER;
+        interp.contract.target_address = TARGET;
+        interp.contract.call_value = U256::from(5);
+        // out len, out offset, in len, in offset.
+        for operand in [U256::ZERO, U256::ZERO, in_len, U256::ZERO] {
+            interp.stack.push(operand).unwrap();
+        }
+        if let Some(value) = value {
+            interp.stack.push(value).unwrap();
+        }
+        interp
+            .stack
+            .push(U256::from_be_bytes(CALLEE.into_word().0))
+            .unwrap();
+        interp.stack.push(U256::from(gas)).unwrap();
+        interp
+    }
+
+    /// Executes the instruction with the instruction table of `spec`.
+    fn step(spec: SpecId, interp: &mut Interpreter) {
+        let mut host = DummyHost::default();
+        spec_to_generic!(
+            spec,
+            interp.step(&make_instruction_table::<DummyHost, SPEC>(), &mut host)
+        );
+    }
+
+    fn call_inputs(interp: &Interpreter) -> &CallInputs {
+        assert_eq!(interp.instruction_result, InstructionResult::CallOrCreate);
+        let InterpreterAction::Call { inputs } = &interp.next_action else {
+            panic!("expected a call, got {:?}", interp.next_action);
+        };
+        inputs
+    }
+
+    #[test]
+    fn call_value_in_static_context() {
+        for spec in all_specs() {
+            // The static check comes before memory expansion, which would run out of gas.
+            let mut interp = call_interpreter(CALL, 0, Some(U256::from(1)), U256::from(1) << 40);
+            interp.is_static = true;
+            step(spec, &mut interp);
+            assert_eq!(
+                interp.instruction_result,
+                InstructionResult::CallNotAllowedInsideStatic,
+                "{spec:?}"
+            );
+            assert_eq!(interp.gas.remaining(), 1_000_000, "{spec:?}");
+
+            let mut interp = call_interpreter(CALL, 0, Some(U256::ZERO), U256::ZERO);
+            interp.is_static = true;
+            step(spec, &mut interp);
+            assert!(call_inputs(&interp).is_static, "{spec:?}");
+
+            // CALLCODE transfers value to itself and is allowed in a static context.
+            let mut interp = call_interpreter(CALLCODE, 0, Some(U256::from(1)), U256::ZERO);
+            interp.is_static = true;
+            step(spec, &mut interp);
+            let inputs = call_inputs(&interp);
+            assert!(inputs.is_static, "{spec:?}");
+            assert_eq!(inputs.scheme, CallScheme::CallCode);
+            assert_eq!(inputs.value, CallValue::Transfer(U256::from(1)));
+            assert_eq!(inputs.target_address, TARGET);
+            assert_eq!(inputs.caller, TARGET);
+            assert_eq!(inputs.bytecode_address, CALLEE);
+        }
+    }
+
+    #[test]
+    fn call_stipend() {
+        for spec in all_specs() {
+            for opcode in [CALL, CALLCODE] {
+                for (value, gas_limit) in [(U256::ZERO, 0), (U256::from(1), gas::CALL_STIPEND)] {
+                    let mut interp = call_interpreter(opcode, 0, Some(value), U256::ZERO);
+                    step(spec, &mut interp);
+                    assert_eq!(call_inputs(&interp).gas_limit, gas_limit, "{spec:?}");
+                }
+            }
+            // Calls without value never get the stipend.
+            if spec.is_enabled_in(SpecId::BYZANTIUM) {
+                let mut interp = call_interpreter(STATICCALL, 0, None, U256::ZERO);
+                step(spec, &mut interp);
+                assert_eq!(call_inputs(&interp).gas_limit, 0, "{spec:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn delegatecall_context() {
+        for spec in all_specs() {
+            let mut interp = call_interpreter(DELEGATECALL, 1_000, None, U256::ZERO);
+            interp.is_static = true;
+            step(spec, &mut interp);
+            if !spec.is_enabled_in(SpecId::HOMESTEAD) {
+                assert_eq!(interp.instruction_result, InstructionResult::NotActivated);
+                continue;
+            }
+            // The callee runs in the context of the current frame.
+            let inputs = call_inputs(&interp);
+            assert_eq!(inputs.caller, CALLER, "{spec:?}");
+            assert_eq!(inputs.target_address, TARGET, "{spec:?}");
+            assert_eq!(inputs.bytecode_address, CALLEE, "{spec:?}");
+            assert_eq!(inputs.value, CallValue::Apparent(U256::from(5)), "{spec:?}");
+            assert_eq!(inputs.gas_limit, 1_000, "{spec:?}");
+            assert!(inputs.is_static, "{spec:?}");
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use crate::{
     gas,
     interpreter::Interpreter,
-    primitives::{Bytes, Spec, SpecId::*, U256},
+    primitives::{Bytes, Spec, U256},
     AccountLoad,
 };
-use core::{cmp::min, ops::Range};
+use core::ops::Range;
 
 #[inline]
 pub fn get_memory_input_and_out_ranges(
@@ -42,26 +42,30 @@ pub fn resize_memory(
     Some(offset..offset + len)
 }
 
+/// Charges the cost of a call instruction and the gas it forwards, see [`gas::calc_call_gas`].
+///
+/// Returns the gas limit of the new frame, including the call stipend.
 #[inline]
 pub fn calc_call_gas<SPEC: Spec>(
     interpreter: &mut Interpreter,
     account_load: AccountLoad,
     has_transfer: bool,
+    is_call_or_callcode: bool,
     local_gas_limit: u64,
 ) -> Option<u64> {
+    let cold_cost = gas::call_cold_surcharge(SPEC::SPEC_ID, &account_load);
     let call_cost = gas::call_cost(SPEC::SPEC_ID, has_transfer, account_load);
     gas!(interpreter, call_cost, None);
+    reprice!(interpreter, ColdAccess, cold_cost, None);
 
-    // EIP-150: Gas cost changes for IO-heavy operations
-    let gas_limit = if SPEC::enabled(TANGERINE) {
-        // take l64 part of gas_limit
-        min(
-            interpreter.gas().remaining_63_of_64_parts(),
-            local_gas_limit,
-        )
-    } else {
-        local_gas_limit
-    };
+    let (gas_limit, gas_cost) = gas::calc_call_gas(
+        SPEC::SPEC_ID,
+        interpreter.gas().remaining(),
+        local_gas_limit,
+        has_transfer,
+        is_call_or_callcode,
+    );
+    gas!(interpreter, gas_cost, None);
 
     Some(gas_limit)
 }
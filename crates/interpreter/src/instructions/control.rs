@@ -100,7 +100,9 @@ pub fn callf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 
     // Check max stack height for target code section.
     // safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize
+        > interpreter.stack.limit()
+    {
         interpreter.instruction_result = InstructionResult::StackOverflow;
         return;
     }
@@ -138,7 +140,9 @@ pub fn jumpf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 
     // Check max stack height for target code section.
     // safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize
+        > interpreter.stack.limit()
+    {
         interpreter.instruction_result = InstructionResult::StackOverflow;
         return;
     }
@@ -173,6 +177,7 @@ fn return_inner(interpreter: &mut Interpreter, instruction_result: InstructionRe
             output,
             gas: interpreter.gas,
             result: instruction_result,
+            halt_context: None,
         },
     };
 }
@@ -438,4 +443,36 @@ mod test {
         // stack overflow
         assert_eq!(interp.instruction_result, InstructionResult::StackOverflow);
     }
+
+    #[test]
+    fn callf_jumpf_configured_stack_limit() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        for opcode in [CALLF, JUMPF] {
+            let bytes1 = Bytes::from([opcode, 0x00, 0x01]);
+            let bytes2 = Bytes::from([STOP]);
+            let setup = |max_stack_size| {
+                let mut interp = eof_setup_with_types(
+                    bytes1.clone(),
+                    bytes2.clone(),
+                    TypesSection::new(0, 0, max_stack_size),
+                );
+                interp.stack.set_limit(16);
+                interp.stack.push(U256::ZERO).unwrap();
+                interp
+            };
+
+            // The target section fits in the lowered limit.
+            let mut interp = setup(15);
+            interp.step(&table, &mut host);
+            assert_eq!(interp.instruction_result, InstructionResult::Continue);
+            assert_eq!(interp.function_stack.current_code_idx, 1);
+
+            // One more item overflows the lowered limit.
+            let mut interp = setup(16);
+            interp.step(&table, &mut host);
+            assert_eq!(interp.instruction_result, InstructionResult::StackOverflow);
+        }
+    }
 }
@@ -0,0 +1,8 @@
+//! Experimental instructions of proposed EIPs, enabled by the `experimental-opcodes` feature.
+//!
+//! **Unstable:** these instructions are only meant for prototyping. Their opcodes, semantics and
+//! gas costs follow drafts and may change or be removed in any release. They are only active in
+//! [`SpecId::LATEST`](crate::primitives::SpecId::LATEST).
+
+pub mod clz;
+pub mod setcode;
@@ -0,0 +1,60 @@
+//! `CLZ`, count leading zeros, as proposed by EIP-7939.
+
+use crate::{
+    gas,
+    primitives::{Spec, U256},
+    Host, Interpreter,
+};
+
+/// Gas cost of `CLZ`.
+pub const CLZ_GAS: u64 = gas::LOW;
+
+/// Replaces the topmost value with the number of its leading zero bits, `256` for zero.
+pub fn clz<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
+    check!(interpreter, LATEST);
+    gas!(interpreter, CLZ_GAS);
+    pop_top!(interpreter, op1);
+    *op1 = U256::from(op1.leading_zeros());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{CancunSpec, Env, LatestSpec},
+        Contract, DummyHost, InstructionResult,
+    };
+
+    #[test]
+    fn leading_zeros() {
+        let mut host = DummyHost::new(Env::default());
+        for (value, expected) in [
+            (U256::ZERO, 256),
+            (U256::from(1), 255),
+            (U256::from(0xff), 248),
+            (U256::from(1) << 255, 0),
+            (U256::MAX, 0),
+            (U256::from(1) << 128, 127),
+        ] {
+            let mut interpreter = Interpreter::new(Contract::default(), u64::MAX, false);
+            push!(interpreter, value);
+            clz::<DummyHost, LatestSpec>(&mut interpreter, &mut host);
+            pop!(interpreter, res);
+            assert_eq!(res, U256::from(expected));
+            assert_eq!(interpreter.gas.spent(), CLZ_GAS);
+        }
+    }
+
+    #[test]
+    fn only_latest_spec() {
+        let mut host = DummyHost::new(Env::default());
+        let mut interpreter = Interpreter::new(Contract::default(), u64::MAX, false);
+        push!(interpreter, U256::from(1));
+        clz::<DummyHost, CancunSpec>(&mut interpreter, &mut host);
+        assert_eq!(
+            interpreter.instruction_result,
+            InstructionResult::NotActivated
+        );
+        assert_eq!(interpreter.gas.spent(), 0);
+    }
+}
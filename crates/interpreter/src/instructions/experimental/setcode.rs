@@ -0,0 +1,76 @@
+//! `SETCODE` placeholder, in the style of EIP-6913.
+//!
+//! Replacing the code of the executing account needs host support that does not exist yet. The
+//! instruction validates its context and operands and charges its gas like the proposal, then
+//! halts with [`InstructionResult::NotActivated`].
+
+use crate::{gas, primitives::Spec, Host, InstructionResult, Interpreter};
+
+/// Gas charged by `SETCODE` before memory expansion, provisional.
+pub const SETCODE_BASE_GAS: u64 = gas::CREATE;
+
+/// Pops the offset and size of the new code in memory, then halts, see the [module
+/// documentation](self).
+pub fn setcode<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+    check!(interpreter, LATEST);
+    require_non_staticcall!(interpreter);
+    gas!(interpreter, SETCODE_BASE_GAS);
+    pop!(interpreter, offset, len);
+    let len = as_usize_or_fail!(interpreter, len);
+    if len > host.env().cfg.max_code_size() {
+        interpreter.instruction_result = InstructionResult::CreateContractSizeLimit;
+        return;
+    }
+    if len != 0 {
+        let offset = as_usize_or_fail!(interpreter, offset);
+        resize_memory!(interpreter, offset, len);
+    }
+    interpreter.instruction_result = InstructionResult::NotActivated;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{Env, LatestSpec, U256},
+        Contract, DummyHost,
+    };
+
+    fn run(offset: u64, len: u64, is_static: bool) -> Interpreter {
+        let mut host = DummyHost::new(Env::default());
+        let mut interpreter = Interpreter::new(Contract::default(), 100_000, is_static);
+        interpreter.stack.push(U256::from(len)).unwrap();
+        interpreter.stack.push(U256::from(offset)).unwrap();
+        setcode::<DummyHost, LatestSpec>(&mut interpreter, &mut host);
+        interpreter
+    }
+
+    #[test]
+    fn charges_gas_and_halts() {
+        let interpreter = run(0, 0, false);
+        assert_eq!(
+            interpreter.instruction_result,
+            InstructionResult::NotActivated
+        );
+        assert_eq!(interpreter.gas.spent(), SETCODE_BASE_GAS);
+
+        // Memory holding the code is expanded.
+        let interpreter = run(0, 64, false);
+        assert_eq!(
+            interpreter.gas.spent(),
+            SETCODE_BASE_GAS + gas::memory_gas(2)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_context() {
+        assert_eq!(
+            run(0, 0, true).instruction_result,
+            InstructionResult::StateChangeDuringStaticCall
+        );
+        assert_eq!(
+            run(0, 0x6001, false).instruction_result,
+            InstructionResult::CreateContractSizeLimit
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    gas::{self, warm_cold_cost},
+    gas,
     interpreter::Interpreter,
     primitives::{Bytes, Log, LogData, Spec, SpecId::*, B256, U256},
     Host, InstructionResult,
@@ -15,16 +15,12 @@ pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
     };
     gas!(
         interpreter,
-        if SPEC::enabled(BERLIN) {
-            warm_cold_cost(balance.is_cold)
-        } else if SPEC::enabled(ISTANBUL) {
-            // EIP-1884: Repricing for trie-size-dependent opcodes
-            700
-        } else if SPEC::enabled(TANGERINE) {
-            400
-        } else {
-            20
-        }
+        gas::balance_cost(SPEC::SPEC_ID, balance.is_cold)
+    );
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_account_access_surcharge(SPEC::SPEC_ID, balance.is_cold)
     );
     push!(interpreter, balance.data);
 }
@@ -46,13 +42,15 @@ pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code.is_cold));
-    } else if SPEC::enabled(TANGERINE) {
-        gas!(interpreter, 700);
-    } else {
-        gas!(interpreter, 20);
-    }
+    gas!(
+        interpreter,
+        gas::extcodesize_cost(SPEC::SPEC_ID, code.is_cold)
+    );
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_account_access_surcharge(SPEC::SPEC_ID, code.is_cold)
+    );
 
     push!(interpreter, U256::from(code.len()));
 }
@@ -65,13 +63,15 @@ pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code_hash.is_cold))
-    } else if SPEC::enabled(ISTANBUL) {
-        gas!(interpreter, 700);
-    } else {
-        gas!(interpreter, 400);
-    }
+    gas!(
+        interpreter,
+        gas::extcodehash_cost(SPEC::SPEC_ID, code_hash.is_cold)
+    );
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_account_access_surcharge(SPEC::SPEC_ID, code_hash.is_cold)
+    );
     push_b256!(interpreter, *code_hash);
 }
 
@@ -89,12 +89,15 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         interpreter,
         gas::extcodecopy_cost(SPEC::SPEC_ID, len as u64, code.is_cold)
     );
-    if len == 0 {
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_account_access_surcharge(SPEC::SPEC_ID, code.is_cold)
+    );
+    let Some(memory_offset) = super::system::copy_resize(interpreter, memory_offset, len) else {
         return;
-    }
-    let memory_offset = as_usize_or_fail!(interpreter, memory_offset);
+    };
     let code_offset = min(as_usize_saturated!(code_offset), code.len());
-    resize_memory!(interpreter, memory_offset, len);
 
     // Note: this can't panic because we resized memory to fit.
     interpreter
@@ -102,6 +105,12 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         .set_data(memory_offset, code_offset, len, &code);
 }
 
+/// Pushes the hash of one of the `BLOCK_HASH_HISTORY` most recent complete blocks, or zero for
+/// any other block, including the current and future ones.
+///
+/// The window is checked by the host, see [`Host::block_hash`]. It is the same in every spec,
+/// EIP-2935 serves older hashes from the state of the history contract from Prague, but doesn't
+/// change `BLOCKHASH`.
 pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BLOCKHASH);
     pop_top!(interpreter, number);
@@ -121,6 +130,11 @@ pub fn sload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
         return;
     };
     gas!(interpreter, gas::sload_cost(SPEC::SPEC_ID, value.is_cold));
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_sload_surcharge(SPEC::SPEC_ID, value.is_cold)
+    );
     *index = value.data;
 }
 
@@ -132,19 +146,24 @@ pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
-    gas_or_fail!(interpreter, {
-        let remaining_gas = interpreter.gas.remaining();
-        gas::sstore_cost(
-            SPEC::SPEC_ID,
-            &state_load.data,
-            remaining_gas,
-            state_load.is_cold,
-        )
-    });
-    refund!(
-        interpreter,
-        gas::sstore_refund(SPEC::SPEC_ID, &state_load.data)
+    // EIP-1706 Disable SSTORE with gasleft lower than call stipend
+    if SPEC::enabled(ISTANBUL) && interpreter.gas.remaining() <= gas::CALL_STIPEND {
+        interpreter.instruction_result = InstructionResult::OutOfGas;
+        return;
+    }
+    let vals = &state_load.data;
+    let (cost, refund) = gas::sstore_cost_and_refund(
+        SPEC::SPEC_ID,
+        vals.original_value,
+        vals.present_value,
+        vals.new_value,
+        state_load.is_cold,
     );
+    gas!(interpreter, cost);
+    let cold_cost = gas::cold_sstore_surcharge(SPEC::SPEC_ID, state_load.is_cold);
+    reprice!(interpreter, StorageWrite, cost - cold_cost);
+    reprice!(interpreter, ColdAccess, cold_cost);
+    refund!(interpreter, refund);
 }
 
 /// EIP-1153: Transient storage opcodes
@@ -216,7 +235,77 @@ pub fn selfdestruct<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     if !SPEC::enabled(LONDON) && !res.previously_destroyed {
         refund!(interpreter, gas::SELFDESTRUCT)
     }
+    let is_cold = res.is_cold;
     gas!(interpreter, gas::selfdestruct_cost(SPEC::SPEC_ID, res));
+    reprice!(
+        interpreter,
+        ColdAccess,
+        gas::cold_selfdestruct_surcharge(SPEC::SPEC_ID, is_cold)
+    );
 
     interpreter.instruction_result = InstructionResult::SelfDestruct;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{Bytecode, CancunSpec, PragueSpec},
+        DummyHost, Gas,
+    };
+
+    const BLOCK_NUMBER: u64 = 1_000;
+
+    fn hash(number: u64) -> B256 {
+        B256::from(U256::from(number + 1).to_be_bytes::<32>())
+    }
+
+    /// Host at [`BLOCK_NUMBER`] knowing the hashes of all blocks, including the current one,
+    /// and checking the window of `BLOCKHASH`.
+    fn host() -> DummyHost {
+        let mut host = DummyHost::default();
+        host.env.block.number = U256::from(BLOCK_NUMBER);
+        host.block_hashes = (0..=BLOCK_NUMBER).map(|n| (n, hash(n))).collect();
+        host
+    }
+
+    fn run_blockhash<SPEC: Spec>(host: &mut DummyHost, number: U256) -> B256 {
+        let mut interp = Interpreter::new_bytecode(Bytecode::default());
+        interp.gas = Gas::new(gas::BLOCKHASH);
+        interp.stack.push(number).unwrap();
+        blockhash::<DummyHost, SPEC>(&mut interp, host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        B256::from(interp.stack.pop().unwrap().to_be_bytes::<32>())
+    }
+
+    #[test]
+    fn blockhash_window() {
+        let cases = [
+            // Current block.
+            (U256::from(BLOCK_NUMBER), B256::ZERO),
+            (U256::from(BLOCK_NUMBER - 1), hash(BLOCK_NUMBER - 1)),
+            (U256::from(BLOCK_NUMBER - 256), hash(BLOCK_NUMBER - 256)),
+            (U256::from(BLOCK_NUMBER - 257), B256::ZERO),
+            // Future blocks.
+            (U256::from(BLOCK_NUMBER + 1), B256::ZERO),
+            (U256::MAX, B256::ZERO),
+        ];
+        for (number, expected) in cases {
+            // EIP-2935 doesn't change `BLOCKHASH`.
+            assert_eq!(run_blockhash::<CancunSpec>(&mut host(), number), expected);
+            assert_eq!(run_blockhash::<PragueSpec>(&mut host(), number), expected);
+        }
+    }
+
+    #[test]
+    fn blockhash_genesis() {
+        let mut host = host();
+        host.env.block.number = U256::from(1);
+        assert_eq!(run_blockhash::<CancunSpec>(&mut host, U256::ZERO), hash(0));
+        host.env.block.number = U256::ZERO;
+        assert_eq!(
+            run_blockhash::<CancunSpec>(&mut host, U256::ZERO),
+            B256::ZERO
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     gas,
-    primitives::{Spec, SpecId::*, U256},
+    primitives::{Spec, U256},
     Host, Interpreter,
 };
 
@@ -28,11 +28,15 @@ pub fn block_number<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut
 
 pub fn difficulty<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BASE);
-    if SPEC::enabled(MERGE) {
-        push_b256!(interpreter, host.env().block.prevrandao.unwrap());
-    } else {
-        push!(interpreter, host.env().block.difficulty);
-    }
+    // A missing `prevrandao` is rejected by `Env::validate_block_env`, a block environment
+    // that skipped validation pushes zero.
+    push_b256!(
+        interpreter,
+        host.env()
+            .block
+            .randomness(SPEC::SPEC_ID)
+            .unwrap_or_default()
+    );
 }
 
 pub fn gaslimit<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
@@ -61,6 +61,26 @@ macro_rules! gas {
     };
 }
 
+/// Charges the cost of a gas component priced by the
+/// [`GasPricingOverride`](crate::gas::GasPricingOverride) of the interpreter instead of the
+/// already charged `cost` of the spec. Does nothing if there is no override.
+#[macro_export]
+macro_rules! reprice {
+    ($interp:expr, $component:ident, $cost:expr) => {
+        $crate::reprice!($interp, $component, $cost, ())
+    };
+    ($interp:expr, $component:ident, $cost:expr, $ret:expr) => {
+        if let Some(pricing) = &$interp.gas_pricing {
+            let cost = $cost;
+            let priced = pricing.price($crate::gas::GasComponent::$component, cost);
+            if !$crate::gas::reprice(&mut $interp.gas, cost, priced) {
+                $interp.instruction_result = $crate::InstructionResult::OutOfGas;
+                return $ret;
+            }
+        }
+    };
+}
+
 /// Records a `gas` refund.
 #[macro_export]
 macro_rules! refund {
@@ -100,10 +120,11 @@ macro_rules! resize_memory {
             }
 
             // Note: we can't use `Interpreter` directly here because of potential double-borrows.
-            if !$crate::interpreter::resize_memory(
+            if !$crate::interpreter::resize_memory_priced(
                 &mut $interp.shared_memory,
                 &mut $interp.gas,
                 new_size,
+                $interp.gas_pricing.as_deref(),
             ) {
                 $interp.instruction_result = $crate::InstructionResult::MemoryOOG;
                 return $ret;
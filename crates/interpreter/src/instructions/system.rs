@@ -1,20 +1,33 @@
 use crate::{
     gas,
+    interpreter::num_words,
     primitives::{Spec, B256, KECCAK_EMPTY, U256},
     Host, InstructionResult, Interpreter,
 };
 use core::ptr;
 
-pub fn keccak256<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn keccak256<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     pop_top!(interpreter, offset, len_ptr);
     let len = as_usize_or_fail!(interpreter, len_ptr);
-    gas_or_fail!(interpreter, gas::keccak256_cost(len as u64));
+    if len <= 64 {
+        // Fast path for one or two words, e.g. mapping slots, the cost can't overflow.
+        gas!(
+            interpreter,
+            gas::KECCAK256 + gas::KECCAK256WORD * num_words(len as u64)
+        );
+    } else {
+        gas_or_fail!(interpreter, gas::keccak256_cost(len as u64));
+    }
     let hash = if len == 0 {
         KECCAK_EMPTY
     } else {
         let from = as_usize_or_fail!(interpreter, offset);
         resize_memory!(interpreter, from, len);
-        crate::primitives::keccak256(interpreter.shared_memory.slice(from, len))
+        // Hashed in place, without copying the input out of memory.
+        host.env()
+            .cfg
+            .keccak
+            .hash(interpreter.shared_memory.slice(from, len))
     };
     *len_ptr = hash.into();
 }
@@ -36,16 +49,32 @@ pub fn codesize<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H)
     push!(interpreter, U256::from(interpreter.contract.bytecode.len()));
 }
 
+/// Resizes memory to fit a copy of `len` bytes to `memory_offset` and returns the offset.
+///
+/// Returns `None` if `len` is zero, in which case memory is not resized, or if the instruction
+/// failed.
+#[inline]
+pub(crate) fn copy_resize(
+    interpreter: &mut Interpreter,
+    memory_offset: U256,
+    len: usize,
+) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let memory_offset = as_usize_or_fail_ret!(interpreter, memory_offset, None);
+    resize_memory!(interpreter, memory_offset, len, None);
+    Some(memory_offset)
+}
+
 pub fn codecopy<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     pop!(interpreter, memory_offset, code_offset, len);
     let len = as_usize_or_fail!(interpreter, len);
     gas_or_fail!(interpreter, gas::verylowcopy_cost(len as u64));
-    if len == 0 {
+    let Some(memory_offset) = copy_resize(interpreter, memory_offset, len) else {
         return;
-    }
-    let memory_offset = as_usize_or_fail!(interpreter, memory_offset);
+    };
     let code_offset = as_usize_saturated!(code_offset);
-    resize_memory!(interpreter, memory_offset, len);
 
     // Inform the optimizer that the bytecode cannot be EOF to remove a bounds check.
     assume!(!interpreter.contract.bytecode.is_eof());
@@ -95,12 +124,10 @@ pub fn calldatacopy<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut
     pop!(interpreter, memory_offset, data_offset, len);
     let len = as_usize_or_fail!(interpreter, len);
     gas_or_fail!(interpreter, gas::verylowcopy_cost(len as u64));
-    if len == 0 {
+    let Some(memory_offset) = copy_resize(interpreter, memory_offset, len) else {
         return;
-    }
-    let memory_offset = as_usize_or_fail!(interpreter, memory_offset);
+    };
     let data_offset = as_usize_saturated!(data_offset);
-    resize_memory!(interpreter, memory_offset, len);
 
     // Note: this can't panic because we resized memory to fit.
     interpreter.shared_memory.set_data(
@@ -140,13 +167,9 @@ pub fn returndatacopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interprete
     }
 
     // if len is zero memory is not resized.
-    if len == 0 {
+    let Some(memory_offset) = copy_resize(interpreter, memory_offset, len) else {
         return;
-    }
-
-    // resize memory
-    let memory_offset = as_usize_or_fail!(interpreter, memory_offset);
-    resize_memory!(interpreter, memory_offset, len);
+    };
 
     // Note: this can't panic because we resized memory to fit.
     interpreter.shared_memory.set_data(
@@ -181,17 +204,42 @@ pub fn returndataload<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &m
 
 pub fn gas<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::BASE);
-    push!(interpreter, U256::from(interpreter.gas.remaining()));
+    push!(
+        interpreter,
+        U256::from(interpreter.gas_remaining_observable())
+    );
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
-        opcode::{make_instruction_table, RETURNDATACOPY, RETURNDATALOAD},
+        opcode::{
+            make_instruction_table, CALLDATACOPY, GAS, MCOPY, RETURNDATACOPY, RETURNDATALOAD,
+        },
         primitives::{bytes, Bytecode, PragueSpec},
         DummyHost, Gas, InstructionResult,
     };
+    use std::vec::Vec;
+
+    #[test]
+    fn gas_ignores_refunds() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw([GAS, GAS].into()));
+        interp.gas = Gas::new(10000);
+        interp.step(&table, &mut host);
+        assert_eq!(interp.stack.data(), &vec![U256::from(9998)]);
+
+        interp.gas.record_refund(4800);
+        interp.step(&table, &mut host);
+        assert_eq!(interp.gas_remaining_observable(), 9996);
+        assert_eq!(
+            interp.stack.data(),
+            &vec![U256::from(9998), U256::from(9996)]
+        );
+    }
 
     #[test]
     fn returndataload() {
@@ -338,4 +386,74 @@ mod test {
         assert_eq!(interp.instruction_result, InstructionResult::Continue);
         assert_eq!(&interp.shared_memory.slice(0, 32), &[0u8; 32]);
     }
+
+    #[test]
+    fn copy_out_of_bounds_source() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp =
+            Interpreter::new_bytecode(Bytecode::LegacyRaw([CALLDATACOPY, CALLDATACOPY].into()));
+        interp.gas = Gas::new(10000);
+        interp.contract.input = bytes!("0102030405");
+        interp.shared_memory.resize(96);
+        interp.shared_memory.slice_mut(0, 96).fill(0xff);
+
+        // Partially out of bounds, the rest is zeroed.
+        interp.stack.push(U256::from(8)).unwrap();
+        interp.stack.push(U256::from(3)).unwrap();
+        interp.stack.push(U256::from(1)).unwrap();
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert_eq!(
+            interp.shared_memory.slice(0, 10),
+            &[0xff, 4, 5, 0, 0, 0, 0, 0, 0, 0xff]
+        );
+
+        // Saturated offset, memory is expanded and zeroed.
+        interp.stack.push(U256::from(64)).unwrap();
+        interp.stack.push(U256::MAX).unwrap();
+        interp.stack.push(U256::from(64)).unwrap();
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert_eq!(interp.shared_memory.len(), 128);
+        assert_eq!(interp.shared_memory.slice(64, 64), &[0; 64]);
+    }
+
+    #[test]
+    fn mcopy_overlapping() {
+        let table = make_instruction_table::<_, PragueSpec>();
+        let mut host = DummyHost::default();
+
+        let mut interp = Interpreter::new_bytecode(Bytecode::LegacyRaw([MCOPY, MCOPY].into()));
+        interp.gas = Gas::new(10000);
+        interp.shared_memory.resize(64);
+        for i in 0..64 {
+            interp.shared_memory.slice_mut(i, 1)[0] = i as u8;
+        }
+
+        // Forward overlap, the source is read before being overwritten.
+        interp.stack.push(U256::from(40)).unwrap();
+        interp.stack.push(U256::from(0)).unwrap();
+        interp.stack.push(U256::from(8)).unwrap();
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert_eq!(
+            interp.shared_memory.slice(8, 40),
+            &(0..40).collect::<Vec<u8>>()[..]
+        );
+
+        // Backward overlap past the end of memory expands it.
+        interp.stack.push(U256::from(64)).unwrap();
+        interp.stack.push(U256::from(32)).unwrap();
+        interp.stack.push(U256::from(16)).unwrap();
+        interp.step(&table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert_eq!(interp.shared_memory.len(), 96);
+        assert_eq!(
+            interp.shared_memory.slice(16, 16),
+            &(24..40).collect::<Vec<u8>>()[..]
+        );
+        assert_eq!(interp.shared_memory.slice(48, 32), &[0; 32]);
+    }
 }
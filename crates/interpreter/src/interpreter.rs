@@ -1,21 +1,24 @@
 pub mod analysis;
+mod cancellation;
 mod contract;
 #[cfg(feature = "serde")]
 pub mod serde;
 mod shared_memory;
 mod stack;
 
+pub use cancellation::{Cancellation, DEFAULT_CANCELLATION_STRIDE};
 pub use contract::Contract;
 pub use shared_memory::{num_words, SharedMemory, EMPTY_SHARED_MEMORY};
 pub use stack::{Stack, STACK_LIMIT};
 
 use crate::{
-    gas, primitives::Bytes, push, push_b256, return_ok, return_revert, CallOutcome, CreateOutcome,
-    FunctionStack, Gas, Host, InstructionResult, InterpreterAction,
+    gas::{self, GasComponent, GasPricingOverride},
+    primitives::Bytes,
+    push, push_b256, return_ok, return_revert, CallOutcome, CreateOutcome, FunctionStack, Gas,
+    Host, InstructionResult, InterpreterAction,
 };
 use core::cmp::min;
-use revm_primitives::{Bytecode, Eof, U256};
-use std::borrow::ToOwned;
+use revm_primitives::{Bytecode, Eof, HaltContext, U256};
 use std::sync::Arc;
 
 /// EVM bytecode interpreter.
@@ -60,6 +63,13 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: InterpreterAction,
+    /// Gas pricing of the chain, if it reprices the dynamic costs of the spec.
+    ///
+    /// Consulted when memory is expanded, when accounts and storage slots are accessed cold and
+    /// when storage is written. Static gas overrides are applied by the instruction table.
+    pub gas_pricing: Option<Arc<GasPricingOverride>>,
+    /// Cancellation checked while running, see [`Cancellation`].
+    pub cancellation: Option<Cancellation>,
 }
 
 impl Default for Interpreter {
@@ -90,7 +100,52 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: InterpreterAction::None,
+            gas_pricing: None,
+            cancellation: None,
+        }
+    }
+
+    /// Resets the interpreter to run `contract` from the start.
+    ///
+    /// Same as replacing it with [`Interpreter::new`], but the stack buffer is reused instead of
+    /// allocating a new one.
+    pub fn reset(&mut self, contract: Contract, gas_limit: u64, is_static: bool) {
+        if !contract.bytecode.is_execution_ready() {
+            panic!("Contract is not execution ready {:?}", contract.bytecode);
         }
+        self.is_eof = contract.bytecode.is_eof();
+        self.bytecode = contract.bytecode.bytecode().clone();
+        self.instruction_pointer = self.bytecode.as_ptr();
+        self.contract = contract;
+        self.gas = Gas::new(gas_limit);
+        self.instruction_result = InstructionResult::Continue;
+        self.function_stack = FunctionStack::default();
+        self.is_static = is_static;
+        self.is_eof_init = false;
+        self.return_data_buffer = Bytes::new();
+        self.shared_memory = EMPTY_SHARED_MEMORY;
+        self.stack.data_mut().clear();
+        self.next_action = InterpreterAction::None;
+        self.gas_pricing = None;
+        self.cancellation = None;
+    }
+
+    /// Drops the inputs, the bytecode and the output of the interpreter, keeping the stack
+    /// buffer.
+    ///
+    /// The interpreter can't run until it is [reset](Interpreter::reset).
+    pub fn clear(&mut self) {
+        self.contract.input = Bytes::new();
+        self.contract.bytecode = Bytecode::LegacyRaw(Bytes::new());
+        self.bytecode = Bytes::new();
+        self.instruction_pointer = self.bytecode.as_ptr();
+        self.return_data_buffer = Bytes::new();
+        self.shared_memory = EMPTY_SHARED_MEMORY;
+        self.stack.data_mut().clear();
+        self.function_stack = FunctionStack::default();
+        self.next_action = InterpreterAction::None;
+        self.gas_pricing = None;
+        self.cancellation = None;
     }
 
     /// Set is_eof_init to true, this is used to enable `RETURNCONTRACT` opcode.
@@ -160,13 +215,13 @@ impl Interpreter {
     /// - Modifies the stack by pushing values depending on the `InstructionResult`.
     /// - Updates gas costs and records refunds in the interpreter's `gas` field.
     /// - May alter `instruction_result` in case of external errors.
-    pub fn insert_create_outcome(&mut self, create_outcome: CreateOutcome) {
+    pub fn insert_create_outcome(&mut self, mut create_outcome: CreateOutcome) {
         self.instruction_result = InstructionResult::Continue;
 
-        let instruction_result = create_outcome.instruction_result();
+        let instruction_result = *create_outcome.instruction_result();
         self.return_data_buffer = if instruction_result.is_revert() {
             // Save data to return data buffer if the create reverted
-            create_outcome.output().to_owned()
+            core::mem::take(&mut create_outcome.result.output)
         } else {
             // Otherwise clear it
             Bytes::new()
@@ -192,13 +247,13 @@ impl Interpreter {
         }
     }
 
-    pub fn insert_eofcreate_outcome(&mut self, create_outcome: CreateOutcome) {
+    pub fn insert_eofcreate_outcome(&mut self, mut create_outcome: CreateOutcome) {
         self.instruction_result = InstructionResult::Continue;
-        let instruction_result = create_outcome.instruction_result();
+        let instruction_result = *create_outcome.instruction_result();
 
-        self.return_data_buffer = if *instruction_result == InstructionResult::Revert {
+        self.return_data_buffer = if instruction_result == InstructionResult::Revert {
             // Save data to return data buffer if the create reverted
-            create_outcome.output().to_owned()
+            core::mem::take(&mut create_outcome.result.output)
         } else {
             // Otherwise clear it. Note that RETURN opcode should abort.
             Bytes::new()
@@ -241,8 +296,8 @@ impl Interpreter {
     ///
     /// # Behavior
     ///
-    /// The function first copies the output data from the call outcome to the virtual machine's
-    /// return data buffer. It then checks the instruction result from the call outcome:
+    /// The function first moves the output data from the call outcome to the virtual machine's
+    /// return data buffer, sharing it without a copy. It then checks the instruction result from the call outcome:
     ///
     /// - `return_ok!()`: Processes successful execution, refunds gas, and updates shared memory.
     /// - `return_revert!()`: Handles a revert by only updating the gas usage and shared memory.
@@ -323,6 +378,17 @@ impl Interpreter {
         &self.gas
     }
 
+    /// Returns the gas left as observed by the `GAS` instruction.
+    ///
+    /// This is the gas remaining in the frame. It never includes refunds: they are only recorded
+    /// during execution and applied once the transaction ends, by handlers that may also rewrite
+    /// the gas afterwards, such as the Optimism `last_frame_return` of deposit transactions.
+    #[inline]
+    pub fn gas_remaining_observable(&self) -> u64 {
+        debug_assert!(self.gas.remaining() <= self.gas.limit());
+        self.gas.remaining()
+    }
+
     /// Returns a reference to the interpreter's stack.
     #[inline]
     pub fn stack(&self) -> &Stack {
@@ -381,30 +447,114 @@ impl Interpreter {
         self.next_action = InterpreterAction::None;
         self.shared_memory = shared_memory;
         // main loop
-        while self.instruction_result == InstructionResult::Continue {
-            self.step(instruction_table, host);
+        if let Some(cancellation) = self.cancellation.take() {
+            self.run_cancellable(&cancellation, instruction_table, host);
+            self.cancellation = Some(cancellation);
+        } else {
+            while self.instruction_result == InstructionResult::Continue {
+                self.step(instruction_table, host);
+            }
         }
 
+        self.take_next_action(host)
+    }
+
+    /// Executes a single instruction, for running interpreters in lockstep. [`Self::run`] should
+    /// be used otherwise.
+    ///
+    /// Returns [`InterpreterAction::None`] while the interpreter continues, and the action
+    /// [`Self::run`] would have returned once it stops. The shared memory must be set before the
+    /// first instruction, and the interpreter must not be stepped after it stopped.
+    pub fn step_instruction<FN, H: Host + ?Sized>(
+        &mut self,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        assert_eq!(
+            self.instruction_result,
+            InstructionResult::Continue,
+            "interpreter stepped after it stopped"
+        );
+        self.step(instruction_table, host);
+        if self.instruction_result == InstructionResult::Continue {
+            return InterpreterAction::None;
+        }
+        self.take_next_action(host)
+    }
+
+    /// Takes the action of a stopped interpreter.
+    fn take_next_action<H: Host + ?Sized>(&mut self, host: &H) -> InterpreterAction {
         // Return next action if it is some.
         if self.next_action.is_some() {
             return core::mem::take(&mut self.next_action);
         }
         // If not, return action without output as it is a halt.
+        let halt_context = (self.instruction_result.is_error()
+            && host.env().cfg.capture_halt_context)
+            .then(|| self.halt_context());
         InterpreterAction::Return {
             result: InterpreterResult {
                 result: self.instruction_result,
                 // return empty bytecode
                 output: Bytes::new(),
                 gas: self.gas,
+                halt_context,
             },
         }
     }
 
+    /// Main loop checking the cancellation token every [`Cancellation::stride`] instructions.
+    fn run_cancellable<FN, H: Host + ?Sized>(
+        &mut self,
+        cancellation: &Cancellation,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        // The token is checked before the first instruction, so a frame resumed after a
+        // cancelled call halts right away.
+        let mut remaining = 0;
+        while self.instruction_result == InstructionResult::Continue {
+            if remaining == 0 {
+                if cancellation.is_cancelled() {
+                    self.instruction_result = InstructionResult::Cancelled;
+                    break;
+                }
+                remaining = cancellation.stride();
+            }
+            remaining -= 1;
+            self.step(instruction_table, host);
+        }
+    }
+
+    /// Returns the location of the last executed instruction.
+    ///
+    /// Used to report where execution halted, as the instruction pointer has already been
+    /// advanced past the halting opcode.
+    #[inline]
+    fn halt_context(&self) -> HaltContext {
+        let pc = self.program_counter().saturating_sub(1);
+        HaltContext {
+            address: self.contract.target_address,
+            pc,
+            opcode: self.bytecode.get(pc).copied().unwrap_or_default(),
+        }
+    }
+
     /// Resize the memory to the new size. Returns whether the gas was enough to resize the memory.
     #[inline]
     #[must_use]
     pub fn resize_memory(&mut self, new_size: usize) -> bool {
-        resize_memory(&mut self.shared_memory, &mut self.gas, new_size)
+        resize_memory_priced(
+            &mut self.shared_memory,
+            &mut self.gas,
+            new_size,
+            self.gas_pricing.as_deref(),
+        )
     }
 }
 
@@ -418,6 +568,9 @@ pub struct InterpreterResult {
     pub output: Bytes,
     /// The gas usage information.
     pub gas: Gas,
+    /// Where the interpreter halted, if the result is an error and
+    /// [`CfgEnv::capture_halt_context`](crate::primitives::CfgEnv::capture_halt_context) is set.
+    pub halt_context: Option<HaltContext>,
 }
 
 impl InterpreterResult {
@@ -427,6 +580,7 @@ impl InterpreterResult {
             result,
             output,
             gas,
+            halt_context: None,
         }
     }
 
@@ -454,10 +608,24 @@ impl InterpreterResult {
 #[cold]
 #[must_use]
 pub fn resize_memory(memory: &mut SharedMemory, gas: &mut Gas, new_size: usize) -> bool {
+    resize_memory_priced(memory, gas, new_size, None)
+}
+
+/// Same as [`resize_memory`], with the expansion cost priced by the override if there is one.
+#[inline]
+pub fn resize_memory_priced(
+    memory: &mut SharedMemory,
+    gas: &mut Gas,
+    new_size: usize,
+    pricing: Option<&GasPricingOverride>,
+) -> bool {
     let new_words = num_words(new_size as u64);
     let new_cost = gas::memory_gas(new_words);
     let current_cost = memory.current_expansion_cost();
-    let cost = new_cost - current_cost;
+    let mut cost = new_cost - current_cost;
+    if let Some(pricing) = pricing {
+        cost = pricing.price(GasComponent::MemoryExpansion, cost);
+    }
     let success = gas.record_cost(cost);
     if success {
         memory.resize((new_words as usize) * 32);
@@ -485,4 +653,45 @@ mod tests {
             &crate::opcode::make_instruction_table::<dyn Host, CancunSpec>();
         let _ = interp.run(EMPTY_SHARED_MEMORY, table, host);
     }
+
+    #[test]
+    fn reset_reuses_stack() {
+        let mut interp = Interpreter::new(Contract::default(), 100, true);
+        interp.stack.push(U256::from(1)).unwrap();
+        assert!(interp.gas.record_cost(10));
+        interp.instruction_result = InstructionResult::Stop;
+        let stack_ptr = interp.stack.data().as_ptr();
+
+        interp.reset(Contract::default(), 200, false);
+        assert!(interp.stack.is_empty());
+        assert_eq!(interp.stack.data().as_ptr(), stack_ptr);
+        assert_eq!(interp.gas.limit(), 200);
+        assert_eq!(interp.gas.remaining(), 200);
+        assert_eq!(interp.instruction_result, InstructionResult::Continue);
+        assert!(!interp.is_static);
+        assert_eq!(interp.instruction_pointer, interp.bytecode.as_ptr());
+    }
+
+    #[test]
+    fn clear_drops_inputs() {
+        let contract = Contract {
+            input: Bytes::from_static(&[1, 2, 3]),
+            ..Default::default()
+        };
+        let mut interp = Interpreter::new(contract, 100, false);
+        interp.stack.push(U256::from(1)).unwrap();
+        interp.return_data_buffer = Bytes::from_static(&[4]);
+        let stack_ptr = interp.stack.data().as_ptr();
+
+        interp.clear();
+        assert!(interp.contract.input.is_empty());
+        assert!(interp.contract.bytecode.is_empty());
+        assert!(interp.bytecode.is_empty());
+        assert!(interp.return_data_buffer.is_empty());
+        assert!(interp.stack.is_empty());
+
+        interp.reset(Contract::default(), 200, false);
+        assert_eq!(interp.stack.data().as_ptr(), stack_ptr);
+        assert_eq!(interp.instruction_pointer, interp.bytecode.as_ptr());
+    }
 }
@@ -21,6 +21,16 @@ use std::{borrow::Cow, fmt, sync::Arc, vec, vec::Vec};
 /// If the bytecode is already analyzed, it is returned as-is.
 #[inline]
 pub fn to_analysed(bytecode: Bytecode) -> Bytecode {
+    to_analysed_with_jump_table(bytecode, None)
+}
+
+/// Perform bytecode analysis, reusing `jump_table` if it is given.
+///
+/// `jump_table` must be the jump table of the same bytecode, as returned by
+/// [`Bytecode::legacy_jump_table`] of an earlier analysis. Only legacy raw bytecode is analyzed,
+/// other bytecode is returned as-is.
+#[inline]
+pub fn to_analysed_with_jump_table(bytecode: Bytecode, jump_table: Option<JumpTable>) -> Bytecode {
     let (bytes, len) = match bytecode {
         Bytecode::LegacyRaw(bytecode) => {
             let len = bytecode.len();
@@ -31,7 +41,7 @@ pub fn to_analysed(bytecode: Bytecode) -> Bytecode {
         }
         n => return n,
     };
-    let jump_table = analyze(bytes.as_ref());
+    let jump_table = jump_table.unwrap_or_else(|| analyze(bytes.as_ref()));
 
     Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(bytes, len, jump_table))
 }
@@ -84,14 +94,36 @@ pub fn validate_raw_eof_inner(
     Ok(eof)
 }
 
-/// Fully validates an [`Eof`] container.
-///
-/// Only place where validation happen is in Creating Transaction.
-/// Because of that we are assuming CodeType is ReturnContract.
+/// Fully validates an [`Eof`] container, including all its subcontainers.
 ///
-/// Note: If needed we can make a flag that would assume ReturnContract CodeType.
-pub fn validate_eof(eof: &Eof) -> Result<(), EofError> {
-    validate_eof_inner(eof, Some(CodeType::ReturnContract))
+/// The `kind` specifies how the top level container is going to be used. Creation
+/// transactions and EOFCREATE validate initcode with [`ValidationKind::Initcode`].
+pub fn validate_eof(eof: &Eof, kind: ValidationKind) -> Result<(), EofError> {
+    validate_eof_inner(eof, kind.code_type())
+}
+
+/// Kind of the top level container that is validated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ValidationKind {
+    /// Container is initcode, it can only terminate with RETURNCONTRACT.
+    #[default]
+    Initcode,
+    /// Container is deployed runtime code, it can only terminate with RETURN or STOP.
+    Runtime,
+    /// Container can be either initcode or runtime code.
+    Any,
+}
+
+impl ValidationKind {
+    /// Returns the [`CodeType`] expected from the first code section, if any.
+    #[inline]
+    pub const fn code_type(self) -> Option<CodeType> {
+        match self {
+            Self::Initcode => Some(CodeType::ReturnContract),
+            Self::Runtime => Some(CodeType::ReturnOrStop),
+            Self::Any => None,
+        }
+    }
 }
 
 #[inline]
@@ -931,4 +963,18 @@ mod test {
             ))
         );
     }
+
+    #[test]
+    fn validation_kind() {
+        // Single code section with STOP.
+        let eof = Eof::decode(hex!("ef00010100040200010001040000000080000000").into()).unwrap();
+        assert_eq!(validate_eof(&eof, ValidationKind::Runtime), Ok(()));
+        assert_eq!(validate_eof(&eof, ValidationKind::Any), Ok(()));
+        assert_eq!(
+            validate_eof(&eof, ValidationKind::Initcode),
+            Err(EofError::Validation(
+                EofValidationError::SubContainerCalledInTwoModes
+            ))
+        );
+    }
 }
@@ -0,0 +1,45 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Number of instructions executed between two checks of the cancellation token by default.
+pub const DEFAULT_CANCELLATION_STRIDE: u32 = 1024;
+
+/// Token checked by the interpreter to stop execution from another thread.
+///
+/// The interpreter checks the token when it starts or resumes running, and then every
+/// `stride` instructions. Once the token is set, the frame halts with
+/// [`InstructionResult::Cancelled`](crate::InstructionResult::Cancelled) and so does every
+/// parent frame when it resumes.
+#[derive(Clone, Debug)]
+pub struct Cancellation {
+    token: Arc<AtomicBool>,
+    stride: u32,
+}
+
+impl Cancellation {
+    /// Creates a cancellation checking the token every `stride` instructions.
+    ///
+    /// A stride of zero is treated as one.
+    pub fn new(token: Arc<AtomicBool>, stride: u32) -> Self {
+        Self {
+            token,
+            stride: stride.max(1),
+        }
+    }
+
+    /// Returns the token.
+    pub fn token(&self) -> &Arc<AtomicBool> {
+        &self.token
+    }
+
+    /// Returns the number of instructions executed between two checks of the token.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Returns true if the token is set.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.token.load(Ordering::Relaxed)
+    }
+}
@@ -108,6 +108,9 @@ impl<'de> Deserialize<'de> for Interpreter {
             return_data_buffer,
             is_static,
             next_action,
+            // Installed again by the EVM before the interpreter runs.
+            gas_pricing: None,
+            cancellation: None,
         })
     }
 }
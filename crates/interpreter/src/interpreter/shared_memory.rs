@@ -254,32 +254,28 @@ impl SharedMemory {
     /// Set memory from data. Our memory offset+len is expected to be correct but we
     /// are doing bound checks on data/data_offeset/len and zeroing parts that is not copied.
     ///
+    /// The memory region is bounds checked once, the part of it that is out of `data` is zeroed
+    /// in place.
+    ///
     /// # Panics
     ///
     /// Panics if memory is out of bounds.
     #[inline]
     #[cfg_attr(debug_assertions, track_caller)]
     pub fn set_data(&mut self, memory_offset: usize, data_offset: usize, len: usize, data: &[u8]) {
-        if data_offset >= data.len() {
-            // nullify all memory slots
-            self.slice_mut(memory_offset, len).fill(0);
-            return;
-        }
-        let data_end = min(data_offset + len, data.len());
-        let data_len = data_end - data_offset;
-        debug_assert!(data_offset < data.len() && data_end <= data.len());
-        let data = unsafe { data.get_unchecked(data_offset..data_end) };
-        self.slice_mut(memory_offset, data_len)
-            .copy_from_slice(data);
-
-        // nullify rest of memory slots
-        // SAFETY: Memory is assumed to be valid, and it is commented where this assumption is made.
-        self.slice_mut(memory_offset + data_len, len - data_len)
-            .fill(0);
+        let memory = self.slice_mut(memory_offset, len);
+        let data = data.get(data_offset..).unwrap_or_default();
+        let data_len = min(len, data.len());
+        let (copied, zeroed) = memory.split_at_mut(data_len);
+        copied.copy_from_slice(&data[..data_len]);
+        zeroed.fill(0);
     }
 
     /// Copies elements from one part of the memory to another part of itself.
     ///
+    /// Overlapping regions are copied as if through an intermediate buffer, see
+    /// [`slice::copy_within`].
+    ///
     /// # Panics
     ///
     /// Panics on out of bounds.
@@ -404,4 +400,67 @@ mod tests {
         assert_eq!(shared_memory.len(), 64);
         assert_eq!(shared_memory.buffer.get(0..64), Some(&[0_u8; 64] as &[u8]));
     }
+
+    /// Memory of a new context filled with its byte offsets, after a dirty previous context.
+    fn filled_memory(len: usize) -> SharedMemory {
+        let mut shared_memory = SharedMemory::new();
+        shared_memory.new_context();
+        shared_memory.resize(64);
+        shared_memory.slice_mut(0, 64).fill(0xff);
+        shared_memory.new_context();
+        shared_memory.resize(len);
+        for (i, byte) in shared_memory.context_memory_mut().iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        shared_memory
+    }
+
+    #[test]
+    fn set_data_matches_bytewise_copy() {
+        let data: Vec<u8> = (100..164).collect();
+        let lens = [1, 31, 32, 63, 64, 65, 96];
+        let data_offsets = [0, 1, 31, 32, 63, 64, 65, 1000, usize::MAX];
+        for len in lens {
+            for data_offset in data_offsets {
+                for memory_offset in [0, 1, 33] {
+                    let mut shared_memory = filled_memory(256);
+                    shared_memory.set_data(memory_offset, data_offset, len, &data);
+
+                    let mut expected = filled_memory(256);
+                    for i in 0..len {
+                        let byte = data_offset
+                            .checked_add(i)
+                            .and_then(|offset| data.get(offset))
+                            .copied()
+                            .unwrap_or_default();
+                        expected.slice_mut(memory_offset + i, 1)[0] = byte;
+                    }
+                    assert_eq!(
+                        shared_memory.context_memory(),
+                        expected.context_memory(),
+                        "len {len}, data offset {data_offset}, memory offset {memory_offset}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copy_matches_buffered_copy() {
+        for len in [1, 31, 32, 33, 100] {
+            for (dst, src) in [(0, 0), (0, 1), (1, 0), (0, 32), (32, 0), (17, 50), (50, 17)] {
+                let mut shared_memory = filled_memory(256);
+                shared_memory.copy(dst, src, len);
+
+                let mut expected = filled_memory(256);
+                let source = expected.slice(src, len).to_vec();
+                expected.set(dst, &source);
+                assert_eq!(
+                    shared_memory.context_memory(),
+                    expected.context_memory(),
+                    "len {len}, dst {dst}, src {src}"
+                );
+            }
+        }
+    }
 }
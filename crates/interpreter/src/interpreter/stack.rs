@@ -8,12 +8,14 @@ use std::vec::Vec;
 /// EVM interpreter stack limit.
 pub const STACK_LIMIT: usize = 1024;
 
-/// EVM stack with [STACK_LIMIT] capacity of words.
+/// EVM stack with a capacity of [STACK_LIMIT] words, or of the limit it is created with.
 #[derive(Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Stack {
     /// The underlying data of the stack.
     data: Vec<U256>,
+    /// Maximum number of words on the stack.
+    limit: usize,
 }
 
 impl fmt::Display for Stack {
@@ -40,12 +42,39 @@ impl Stack {
     /// Instantiate a new stack with the [default stack limit][STACK_LIMIT].
     #[inline]
     pub fn new() -> Self {
+        Self::with_capacity_limit(STACK_LIMIT)
+    }
+
+    /// Instantiate a new stack holding at most `limit` words.
+    ///
+    /// Only meant for non-standard VMs, Ethereum limits the stack to [STACK_LIMIT] words.
+    #[inline]
+    pub fn with_capacity_limit(limit: usize) -> Self {
         Self {
-            // SAFETY: expansion functions assume that capacity is `STACK_LIMIT`.
-            data: Vec::with_capacity(STACK_LIMIT),
+            // SAFETY: expansion functions assume that capacity is at least `limit`.
+            data: Vec::with_capacity(limit),
+            limit,
         }
     }
 
+    /// Returns the maximum number of words on the stack.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Sets the maximum number of words on the stack, reserving the capacity for them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stack holds more than `limit` words.
+    #[inline]
+    pub fn set_limit(&mut self, limit: usize) {
+        assert!(self.data.len() <= limit, "stack exceeds the new limit");
+        self.data.reserve_exact(limit - self.data.len());
+        self.limit = limit;
+    }
+
     /// Returns the length of the stack in words.
     #[inline]
     pub fn len(&self) -> usize {
@@ -201,8 +230,8 @@ impl Stack {
     #[inline]
     pub fn push(&mut self, value: U256) -> Result<(), InstructionResult> {
         // Allows the compiler to optimize out the `Vec::push` capacity check.
-        assume!(self.data.capacity() == STACK_LIMIT);
-        if self.data.len() == STACK_LIMIT {
+        assume!(self.data.capacity() >= self.limit);
+        if self.data.len() == self.limit {
             return Err(InstructionResult::StackOverflow);
         }
         self.data.push(value);
@@ -233,7 +262,7 @@ impl Stack {
         let len = self.data.len();
         if len < n {
             Err(InstructionResult::StackUnderflow)
-        } else if len + 1 > STACK_LIMIT {
+        } else if len + 1 > self.limit {
             Err(InstructionResult::StackOverflow)
         } else {
             // SAFETY: check for out of bounds is done above and it makes this safe to do.
@@ -295,7 +324,7 @@ impl Stack {
 
         let n_words = (slice.len() + 31) / 32;
         let new_len = self.data.len() + n_words;
-        if new_len > STACK_LIMIT {
+        if new_len > self.limit {
             return Err(InstructionResult::StackOverflow);
         }
 
@@ -371,16 +400,27 @@ impl<'de> serde::Deserialize<'de> for Stack {
     where
         D: serde::Deserializer<'de>,
     {
-        let mut data = Vec::<U256>::deserialize(deserializer)?;
-        if data.len() > STACK_LIMIT {
+        #[derive(serde::Deserialize)]
+        struct StackSerde {
+            data: Vec<U256>,
+            #[serde(default = "default_limit")]
+            limit: usize,
+        }
+
+        fn default_limit() -> usize {
+            STACK_LIMIT
+        }
+
+        let StackSerde { mut data, limit } = StackSerde::deserialize(deserializer)?;
+        if data.len() > limit {
             return Err(serde::de::Error::custom(std::format!(
                 "stack size exceeds limit: {} > {}",
                 data.len(),
-                STACK_LIMIT
+                limit
             )));
         }
-        data.reserve(STACK_LIMIT - data.len());
-        Ok(Self { data })
+        data.reserve_exact(limit - data.len());
+        Ok(Self { data, limit })
     }
 }
 
@@ -444,4 +484,36 @@ mod tests {
             assert_eq!(stack.data, [U256::ZERO, U256::ZERO, U256::from(n)]);
         });
     }
+
+    #[test]
+    fn configured_limit() {
+        let mut stack = Stack::with_capacity_limit(16);
+        for i in 0..16 {
+            stack.push(U256::from(i)).unwrap();
+        }
+        assert_eq!(
+            stack.push(U256::ZERO),
+            Err(InstructionResult::StackOverflow)
+        );
+        assert_eq!(stack.dup(1), Err(InstructionResult::StackOverflow));
+        assert_eq!(stack.len(), 16);
+        assert!(std::format!("{stack:?}").contains("limit: 16"));
+
+        stack.data_mut().truncate(15);
+        assert_eq!(
+            stack.push_slice(&[1; 64]),
+            Err(InstructionResult::StackOverflow)
+        );
+        stack.push_slice(&[1; 32]).unwrap();
+
+        // Raising the limit reserves the capacity of the new limit.
+        stack.set_limit(STACK_LIMIT * 2);
+        for _ in 16..STACK_LIMIT * 2 {
+            stack.push(U256::ZERO).unwrap();
+        }
+        assert_eq!(
+            stack.push(U256::ZERO),
+            Err(InstructionResult::StackOverflow)
+        );
+    }
 }
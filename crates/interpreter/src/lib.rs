@@ -30,12 +30,13 @@ pub mod opcode;
 pub use function_stack::{FunctionReturnFrame, FunctionStack};
 pub use gas::Gas;
 pub use host::{
-    AccountLoad, DummyHost, Eip7702CodeLoad, Host, SStoreResult, SelfDestructResult, StateLoad,
+    AccountLoad, DummyHost, Eip7702CodeLoad, Host, SStoreResult, SelfDestructOutcome,
+    SelfDestructResult, StateLoad,
 };
 pub use instruction_result::*;
 pub use interpreter::{
-    analysis, num_words, Contract, Interpreter, InterpreterResult, SharedMemory, Stack,
-    EMPTY_SHARED_MEMORY, STACK_LIMIT,
+    analysis, num_words, Cancellation, Contract, Interpreter, InterpreterResult, SharedMemory,
+    Stack, DEFAULT_CANCELLATION_STRIDE, EMPTY_SHARED_MEMORY, STACK_LIMIT,
 };
 pub use interpreter_action::{
     CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, CreateScheme,
@@ -2,6 +2,14 @@
 
 pub mod eof_printer;
 
+#[cfg(feature = "experimental-opcodes")]
+mod experimental;
+#[cfg(feature = "experimental-opcodes")]
+pub use experimental::{CLZ, SETCODE};
+
+mod spec;
+pub use spec::{static_gas, static_gas_table, OpCodeSpecInfo};
+
 mod tables;
 pub use tables::{
     make_boxed_instruction_table, make_instruction_table, update_boxed_instruction,
@@ -70,7 +78,10 @@ impl OpCode {
     #[inline]
     #[cfg(feature = "parse")]
     pub fn parse(s: &str) -> Option<Self> {
-        NAME_TO_OPCODE.get(s).copied()
+        let opcode = NAME_TO_OPCODE.get(s).copied();
+        #[cfg(feature = "experimental-opcodes")]
+        let opcode = opcode.or_else(|| experimental::parse(s));
+        opcode
     }
 
     /// Returns true if the opcode is a jump destination.
@@ -409,6 +420,8 @@ macro_rules! opcodes {
                 map[$val] = Some(info);
             )*
             let _ = prev;
+            #[cfg(feature = "experimental-opcodes")]
+            let map = experimental::insert_infos(map);
             map
         };
 
@@ -420,6 +433,9 @@ macro_rules! opcodes {
         pub const fn instruction<H: Host + ?Sized, SPEC: Spec>(opcode: u8) -> Instruction<H> {
             match opcode {
                 $($name => $f,)*
+                #[cfg(feature = "experimental-opcodes")]
+                _ => experimental::instruction::<H, SPEC>(opcode),
+                #[cfg(not(feature = "experimental-opcodes"))]
                 _ => control::unknown,
             }
         }
@@ -462,7 +478,7 @@ opcodes! {
     0x1B => SHL    => bitwise::shl::<H, SPEC> => stack_io(2, 1);
     0x1C => SHR    => bitwise::shr::<H, SPEC> => stack_io(2, 1);
     0x1D => SAR    => bitwise::sar::<H, SPEC> => stack_io(2, 1);
-    // 0x1E
+    // 0x1E: CLZ with the `experimental-opcodes` feature
     // 0x1F
     0x20 => KECCAK256 => system::keccak256    => stack_io(2, 1);
     // 0x21
@@ -689,7 +705,7 @@ opcodes! {
     0xF9 => EXTDELEGATECALL => contract::extdelegatecall::<H, SPEC>  => stack_io(3, 1);
     0xFA => STATICCALL      => contract::static_call::<H, SPEC>      => stack_io(6, 1), not_eof;
     0xFB => EXTSTATICCALL   => contract::extstaticcall               => stack_io(3, 1);
-    // 0xFC
+    // 0xFC: SETCODE with the `experimental-opcodes` feature
     0xFD => REVERT       => control::revert::<H, SPEC>    => stack_io(2, 0), terminating;
     0xFE => INVALID      => control::invalid              => stack_io(0, 0), terminating;
     0xFF => SELFDESTRUCT => host::selfdestruct::<H, SPEC> => stack_io(1, 0), not_eof, terminating;
@@ -792,10 +808,41 @@ mod tests {
                 eof_opcode_num += 1;
             }
         }
+        // CLZ and the legacy only SETCODE.
+        #[cfg(feature = "experimental-opcodes")]
+        let (opcode_num, eof_opcode_num) = (opcode_num - 2, eof_opcode_num - 1);
         assert_eq!(opcode_num, 168);
         assert_eq!(eof_opcode_num, 152);
     }
 
+    #[test]
+    #[cfg(feature = "experimental-opcodes")]
+    fn experimental_opcodes() {
+        use crate::primitives::SpecId;
+
+        assert_eq!(std::format!("{}", OpCode::CLZ), "CLZ");
+        assert_eq!(OpCode::new(SETCODE), Some(OpCode::SETCODE));
+        assert_eq!(OpCode::CLZ.input_output(), (1, 1));
+        assert!(OpCode::SETCODE.info().is_disabled_in_eof());
+        #[cfg(feature = "parse")]
+        assert_eq!(OpCode::parse("SETCODE"), Some(OpCode::SETCODE));
+
+        assert_eq!(OpCode::CLZ.introduced_in(), SpecId::LATEST);
+        assert_eq!(static_gas(CLZ, SpecId::PRAGUE), None);
+        assert_eq!(static_gas(CLZ, SpecId::LATEST), Some(5));
+        assert_eq!(static_gas(SETCODE, SpecId::LATEST), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "experimental-opcodes"))]
+    fn experimental_opcodes_absent() {
+        assert_eq!(OpCode::new(0x1E), None);
+        assert_eq!(OpCode::new(0xFC), None);
+        assert_eq!(OpCode::name_by_op(0x1E), "Unknown");
+        #[cfg(feature = "parse")]
+        assert_eq!(OpCode::parse("CLZ"), None);
+    }
+
     #[test]
     fn test_terminating_opcodes() {
         let terminating = [
@@ -0,0 +1,77 @@
+//! Opcodes of the [experimental instructions](crate::instructions::experimental).
+//!
+//! **Unstable:** only available with the `experimental-opcodes` feature, and subject to change.
+
+use super::{not_eof, stack_io, Instruction, OpCode, OpCodeInfo};
+use crate::{
+    instructions::{control, experimental},
+    primitives::{Spec, SpecId},
+    Host,
+};
+
+/// The `0x1E` ("CLZ") opcode, experimental.
+pub const CLZ: u8 = 0x1E;
+/// The `0xFC` ("SETCODE") opcode, experimental.
+pub const SETCODE: u8 = 0xFC;
+
+impl OpCode {
+    /// The `0x1E` ("CLZ") opcode, experimental.
+    pub const CLZ: Self = Self(CLZ);
+    /// The `0xFC` ("SETCODE") opcode, experimental.
+    pub const SETCODE: Self = Self(SETCODE);
+}
+
+/// Experimental opcodes with their info.
+const OPCODES: [(u8, OpCodeInfo); 2] = [
+    (CLZ, stack_io(OpCodeInfo::new("CLZ"), 1, 1)),
+    (SETCODE, not_eof(stack_io(OpCodeInfo::new("SETCODE"), 2, 0))),
+];
+
+/// Adds the experimental opcodes to the opcode info table.
+pub(super) const fn insert_infos(mut map: [Option<OpCodeInfo>; 256]) -> [Option<OpCodeInfo>; 256] {
+    let mut i = 0;
+    while i < OPCODES.len() {
+        let (opcode, info) = OPCODES[i];
+        assert!(
+            map[opcode as usize].is_none(),
+            "experimental opcode is already assigned"
+        );
+        map[opcode as usize] = Some(info);
+        i += 1;
+    }
+    map
+}
+
+/// Returns the experimental instruction of the opcode, or the unknown instruction.
+pub(super) const fn instruction<H: Host + ?Sized, SPEC: Spec>(opcode: u8) -> Instruction<H> {
+    match opcode {
+        CLZ => experimental::clz::clz::<H, SPEC>,
+        SETCODE => experimental::setcode::setcode::<H, SPEC>,
+        _ => control::unknown,
+    }
+}
+
+/// Parses the name of an experimental opcode.
+#[cfg(feature = "parse")]
+pub(super) fn parse(name: &str) -> Option<OpCode> {
+    OPCODES
+        .iter()
+        .find(|(_, info)| info.name() == name)
+        .map(|&(opcode, _)| OpCode(opcode))
+}
+
+/// Returns `true` if the opcode is experimental.
+pub(super) const fn is_experimental(opcode: u8) -> bool {
+    matches!(opcode, CLZ | SETCODE)
+}
+
+/// Returns the static gas cost of the experimental opcode.
+pub(super) const fn static_gas(opcode: u8) -> Option<u64> {
+    match opcode {
+        CLZ => Some(experimental::clz::CLZ_GAS),
+        _ => None,
+    }
+}
+
+/// First spec in which the experimental opcodes are enabled.
+pub(super) const INTRODUCED_IN: SpecId = SpecId::LATEST;
@@ -0,0 +1,252 @@
+//! Spec dependent opcode information.
+
+use super::*;
+use crate::{gas, primitives::SpecId};
+
+/// Information about an opcode at a given [`SpecId`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpCodeSpecInfo {
+    /// Number of stack inputs.
+    pub inputs: u8,
+    /// Number of stack outputs.
+    pub outputs: u8,
+    /// Whether the opcode is enabled.
+    ///
+    /// EOF only opcodes are reported as enabled starting from [`SpecId::OSAKA`], they are still
+    /// rejected in legacy bytecode.
+    pub enabled: bool,
+    /// Static gas cost, see [`static_gas`].
+    pub static_gas: Option<u16>,
+}
+
+impl OpCode {
+    /// Returns the first spec in which the opcode is enabled.
+    #[inline]
+    pub const fn introduced_in(self) -> SpecId {
+        introduced_in(self.get())
+    }
+
+    /// Returns the opcode information at the given spec.
+    #[inline]
+    pub const fn info_for_spec(self, spec_id: SpecId) -> OpCodeSpecInfo {
+        OpCodeSpecInfo {
+            inputs: self.inputs(),
+            outputs: self.outputs(),
+            enabled: spec_id.is_enabled_in(self.introduced_in()),
+            static_gas: static_gas(self.get(), spec_id),
+        }
+    }
+}
+
+/// Returns the first spec in which the opcode is enabled.
+///
+/// Unknown opcodes are reported as [`SpecId::LATEST`].
+const fn introduced_in(opcode: u8) -> SpecId {
+    if OPCODE_INFO_JUMPTABLE[opcode as usize].is_none() {
+        return SpecId::LATEST;
+    }
+    #[cfg(feature = "experimental-opcodes")]
+    if experimental::is_experimental(opcode) {
+        return experimental::INTRODUCED_IN;
+    }
+    match opcode {
+        // EIP-7: DELEGATECALL
+        DELEGATECALL => SpecId::HOMESTEAD,
+        // EIP-140: REVERT, EIP-211: RETURNDATASIZE and RETURNDATACOPY, EIP-214: STATICCALL
+        REVERT | RETURNDATASIZE | RETURNDATACOPY | STATICCALL => SpecId::BYZANTIUM,
+        // EIP-145: Bitwise shifting, EIP-1052: EXTCODEHASH
+        SHL | SHR | SAR | EXTCODEHASH => SpecId::CONSTANTINOPLE,
+        // EIP-1014: Skinny CREATE2
+        CREATE2 => SpecId::PETERSBURG,
+        // EIP-1344: CHAINID, EIP-1884: SELFBALANCE
+        CHAINID | SELFBALANCE => SpecId::ISTANBUL,
+        // EIP-3198: BASEFEE
+        BASEFEE => SpecId::LONDON,
+        // EIP-3855: PUSH0
+        PUSH0 => SpecId::SHANGHAI,
+        // EIP-1153: Transient storage, EIP-4844: BLOBHASH, EIP-5656: MCOPY, EIP-7516: BLOBBASEFEE
+        TLOAD | TSTORE | BLOBHASH | MCOPY | BLOBBASEFEE => SpecId::CANCUN,
+        // EOF
+        RJUMP | RJUMPI | RJUMPV | CALLF | RETF | JUMPF | DUPN | SWAPN | EXCHANGE | DATALOAD
+        | DATALOADN | DATASIZE | DATACOPY | EOFCREATE | RETURNCONTRACT | RETURNDATALOAD
+        | EXTCALL | EXTDELEGATECALL | EXTSTATICCALL => SpecId::OSAKA,
+        _ => SpecId::FRONTIER,
+    }
+}
+
+/// Returns the static gas cost of the opcode at the given spec.
+///
+/// The static cost is the gas charged by the instruction regardless of its operands and of the
+/// state it accesses. Returns `None` if the opcode is unknown or disabled at the given spec, or if
+/// its cost is dynamic (memory expansion, copy size, cold access, etc.).
+pub const fn static_gas(opcode: u8, spec_id: SpecId) -> Option<u16> {
+    if !spec_id.is_enabled_in(introduced_in(opcode)) {
+        return None;
+    }
+    #[cfg(feature = "experimental-opcodes")]
+    if experimental::is_experimental(opcode) {
+        return match experimental::static_gas(opcode) {
+            Some(gas) => Some(gas as u16),
+            None => None,
+        };
+    }
+    let gas = match opcode {
+        STOP => gas::ZERO,
+
+        ADD | SUB | LT | GT | SLT | SGT | EQ | ISZERO | AND | OR | XOR | NOT | BYTE | SHL | SHR
+        | SAR | CALLDATALOAD | BLOBHASH | RETURNDATALOAD | DATALOADN | DUPN | SWAPN | EXCHANGE => {
+            gas::VERYLOW
+        }
+        PUSH1..=PUSH32 | DUP1..=DUP16 | SWAP1..=SWAP16 => gas::VERYLOW,
+
+        MUL | DIV | SDIV | MOD | SMOD | SIGNEXTEND | SELFBALANCE | CALLF | JUMPF => gas::LOW,
+        ADDMOD | MULMOD | JUMP => gas::MID,
+        JUMPI => gas::HIGH,
+        JUMPDEST => gas::JUMPDEST,
+
+        ADDRESS | ORIGIN | CALLER | CALLVALUE | CALLDATASIZE | CODESIZE | GASPRICE
+        | RETURNDATASIZE | COINBASE | TIMESTAMP | NUMBER | DIFFICULTY | GASLIMIT | CHAINID
+        | BASEFEE | BLOBBASEFEE | POP | PC | MSIZE | GAS | PUSH0 | RJUMP | DATASIZE => gas::BASE,
+
+        RJUMPI | RJUMPV => gas::CONDITION_JUMP_GAS,
+        RETF => gas::RETF_GAS,
+        DATALOAD => gas::DATA_LOAD_GAS,
+        BLOCKHASH => gas::BLOCKHASH,
+        TLOAD | TSTORE => gas::WARM_STORAGE_READ_COST,
+
+        // EIP-2929 made state access depend on whether the account or slot is warm.
+        SLOAD if !spec_id.is_enabled_in(SpecId::BERLIN) => gas::sload_cost(spec_id, false),
+        BALANCE if !spec_id.is_enabled_in(SpecId::BERLIN) => gas::balance_cost(spec_id, false),
+        EXTCODESIZE if !spec_id.is_enabled_in(SpecId::BERLIN) => {
+            gas::extcodesize_cost(spec_id, false)
+        }
+        EXTCODEHASH if !spec_id.is_enabled_in(SpecId::BERLIN) => {
+            gas::extcodehash_cost(spec_id, false)
+        }
+
+        _ => return None,
+    };
+    Some(gas as u16)
+}
+
+/// Returns the static gas cost of every opcode at the given spec, indexed by opcode.
+///
+/// See [`static_gas`] for details.
+pub const fn static_gas_table(spec_id: SpecId) -> [Option<u16>; 256] {
+    let mut table = [None; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = static_gas(i as u8, spec_id);
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{Bytecode, Bytes, Env, U256},
+        DummyHost, Gas, InstructionResult, Interpreter,
+    };
+
+    #[test]
+    fn known_values() {
+        assert_eq!(static_gas(ADD, SpecId::FRONTIER), Some(3));
+        assert_eq!(static_gas(JUMPDEST, SpecId::CANCUN), Some(1));
+
+        // EIP-150 and EIP-1884 repriced SLOAD, EIP-2929 made it depend on slot warmth.
+        assert_eq!(static_gas(SLOAD, SpecId::FRONTIER), Some(50));
+        assert_eq!(static_gas(SLOAD, SpecId::TANGERINE), Some(200));
+        assert_eq!(static_gas(SLOAD, SpecId::ISTANBUL), Some(800));
+        assert_eq!(static_gas(SLOAD, SpecId::BERLIN), None);
+        assert_eq!(static_gas(BALANCE, SpecId::ISTANBUL), Some(700));
+        assert_eq!(static_gas(BALANCE, SpecId::BERLIN), None);
+
+        assert_eq!(static_gas(PUSH0, SpecId::MERGE), None);
+        assert_eq!(static_gas(PUSH0, SpecId::SHANGHAI), Some(2));
+        assert!(!OpCode::PUSH0.info_for_spec(SpecId::MERGE).enabled);
+        assert!(OpCode::PUSH0.info_for_spec(SpecId::SHANGHAI).enabled);
+
+        assert_eq!(static_gas(TLOAD, SpecId::SHANGHAI), None);
+        assert_eq!(
+            OpCode::TLOAD.info_for_spec(SpecId::CANCUN),
+            OpCodeSpecInfo {
+                inputs: 1,
+                outputs: 1,
+                enabled: true,
+                static_gas: Some(100),
+            }
+        );
+
+        // Dynamically priced opcodes.
+        assert_eq!(static_gas(EXP, SpecId::CANCUN), None);
+        assert_eq!(static_gas(MLOAD, SpecId::CANCUN), None);
+        assert_eq!(static_gas(SSTORE, SpecId::CANCUN), None);
+        assert_eq!(static_gas(CALL, SpecId::CANCUN), None);
+
+        // EOF opcodes.
+        assert!(!OpCode::RJUMP.info_for_spec(SpecId::PRAGUE).enabled);
+        assert_eq!(static_gas(RJUMPI, SpecId::OSAKA), Some(4));
+
+        // Unknown opcodes.
+        assert_eq!(static_gas(0x0C, SpecId::LATEST), None);
+        assert_eq!(static_gas_table(SpecId::LATEST)[0x0C], None);
+    }
+
+    #[test]
+    fn table_matches_instructions() {
+        // Executes every legacy opcode on a fresh interpreter and checks that the charged gas and
+        // activation match the reported values.
+        for spec_id in [
+            SpecId::FRONTIER,
+            SpecId::HOMESTEAD,
+            SpecId::TANGERINE,
+            SpecId::SPURIOUS_DRAGON,
+            SpecId::BYZANTIUM,
+            SpecId::PETERSBURG,
+            SpecId::ISTANBUL,
+            SpecId::BERLIN,
+            SpecId::LONDON,
+            SpecId::MERGE,
+            SpecId::SHANGHAI,
+            SpecId::CANCUN,
+            SpecId::PRAGUE,
+        ] {
+            let table = static_gas_table(spec_id);
+            let instructions = crate::primitives::spec_to_generic!(
+                spec_id,
+                make_instruction_table::<DummyHost, SPEC>()
+            );
+            for opcode in 0..=255u8 {
+                let Some(op) = OpCode::new(opcode) else {
+                    continue;
+                };
+                let info = op.info_for_spec(spec_id);
+                assert_eq!(info.static_gas, table[opcode as usize]);
+
+                let mut code = [0u8; 34];
+                code[0] = opcode;
+                let mut interp =
+                    Interpreter::new_bytecode(Bytecode::new_legacy(Bytes::from(code.to_vec())));
+                interp.gas = Gas::new(100_000);
+                for _ in 0..info.inputs {
+                    interp.stack.push(U256::ZERO).unwrap();
+                }
+                interp.step(&instructions, &mut DummyHost::new(Env::default()));
+
+                // RETURNCONTRACT checks for initcode before checking for EOF.
+                let disabled = matches!(
+                    interp.instruction_result,
+                    InstructionResult::NotActivated
+                        | InstructionResult::EOFOpcodeDisabledInLegacy
+                        | InstructionResult::ReturnContractInNotInitEOF
+                );
+                assert_eq!(info.enabled, !disabled, "{op} at {spec_id:?}");
+                if let Some(gas) = info.static_gas {
+                    assert_eq!(interp.gas.spent(), gas as u64, "{op} at {spec_id:?}");
+                }
+            }
+        }
+    }
+}
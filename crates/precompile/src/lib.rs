@@ -62,6 +62,7 @@ impl Precompiles {
             PrecompileSpecId::BERLIN => Self::berlin(),
             PrecompileSpecId::CANCUN => Self::cancun(),
             PrecompileSpecId::PRAGUE => Self::prague(),
+            PrecompileSpecId::OSAKA => Self::osaka(),
             PrecompileSpecId::LATEST => Self::latest(),
         }
     }
@@ -179,9 +180,22 @@ impl Precompiles {
         })
     }
 
+    /// Returns precompiles for Osaka spec.
+    pub fn osaka() -> &'static Self {
+        static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
+        INSTANCE.get_or_init(|| {
+            let mut precompiles = Self::prague().clone();
+            precompiles.extend([
+                // EIP-7883: ModExp Gas Cost Increase.
+                modexp::OSAKA,
+            ]);
+            Box::new(precompiles)
+        })
+    }
+
     /// Returns the precompiles for the latest spec.
     pub fn latest() -> &'static Self {
-        Self::prague()
+        Self::osaka()
     }
 
     /// Returns an iterator over the precompiles addresses.
@@ -238,6 +252,57 @@ impl Precompiles {
         self.addresses.extend(items.iter().map(|p| *p.address()));
         self.inner.extend(items.into_iter().map(Into::into));
     }
+
+    /// Removes the precompile at the given address, returning it if it was present.
+    #[inline]
+    pub fn remove(&mut self, address: &Address) -> Option<Precompile> {
+        self.addresses.remove(address);
+        self.inner.remove(address)
+    }
+
+    /// Returns the precompiles added, removed and changed in this set relative to `other`.
+    ///
+    /// A precompile is changed if its implementation differs, see [`Precompile::ptr_eq`].
+    pub fn difference(&self, other: &Self) -> PrecompileDiff {
+        let mut diff = PrecompileDiff::default();
+        for (address, precompile) in &self.inner {
+            match other.inner.get(address) {
+                None => diff.added.push(*address),
+                Some(previous) if !precompile.ptr_eq(previous) => diff.changed.push(*address),
+                Some(_) => {}
+            }
+        }
+        diff.removed.extend(
+            other
+                .addresses()
+                .filter(|address| !self.contains(address))
+                .copied(),
+        );
+        diff.added.sort_unstable();
+        diff.removed.sort_unstable();
+        diff.changed.sort_unstable();
+        diff
+    }
+}
+
+/// Difference between two sets of precompiles, see [`Precompiles::difference`].
+///
+/// Addresses are sorted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PrecompileDiff {
+    /// Addresses only in the newer set.
+    pub added: Vec<Address>,
+    /// Addresses only in the older set.
+    pub removed: Vec<Address>,
+    /// Addresses in both sets with a different implementation.
+    pub changed: Vec<Address>,
+}
+
+impl PrecompileDiff {
+    /// Returns `true` if the sets have the same precompiles.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -277,6 +342,7 @@ pub enum PrecompileSpecId {
     BERLIN,
     CANCUN,
     PRAGUE,
+    OSAKA,
     LATEST,
 }
 
@@ -292,7 +358,8 @@ impl PrecompileSpecId {
             ISTANBUL | MUIR_GLACIER => Self::ISTANBUL,
             BERLIN | LONDON | ARROW_GLACIER | GRAY_GLACIER | MERGE | SHANGHAI => Self::BERLIN,
             CANCUN => Self::CANCUN,
-            PRAGUE | OSAKA => Self::PRAGUE,
+            PRAGUE => Self::PRAGUE,
+            OSAKA => Self::OSAKA,
             LATEST => Self::LATEST,
             #[cfg(feature = "optimism")]
             BEDROCK | REGOLITH | CANYON => Self::BERLIN,
@@ -313,3 +380,74 @@ pub const fn u64_to_address(x: u64) -> Address {
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7],
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm_primitives::{Env, SpecId};
+
+    #[test]
+    fn osaka_modexp_repricing() {
+        assert_eq!(
+            PrecompileSpecId::from_spec_id(SpecId::PRAGUE),
+            PrecompileSpecId::PRAGUE
+        );
+        assert_eq!(
+            PrecompileSpecId::from_spec_id(SpecId::OSAKA),
+            PrecompileSpecId::OSAKA
+        );
+
+        let env = Env::default();
+        let modexp_gas = |spec: SpecId| {
+            Precompiles::new(PrecompileSpecId::from_spec_id(spec))
+                .get(modexp::OSAKA.address())
+                .unwrap()
+                .call_ref(&Bytes::new(), 100_000, &env)
+                .unwrap()
+                .gas_used
+        };
+        assert_eq!(modexp_gas(SpecId::PRAGUE), 200);
+        assert_eq!(modexp_gas(SpecId::OSAKA), 500);
+        assert_eq!(modexp_gas(SpecId::LATEST), 500);
+    }
+
+    #[test]
+    fn difference_between_specs() {
+        assert!(Precompiles::cancun()
+            .difference(Precompiles::cancun())
+            .is_empty());
+
+        let diff = Precompiles::berlin().difference(Precompiles::istanbul());
+        assert_eq!(
+            diff,
+            PrecompileDiff {
+                changed: vec![*modexp::BERLIN.address()],
+                ..Default::default()
+            }
+        );
+
+        let diff = Precompiles::osaka().difference(Precompiles::prague());
+        assert_eq!(
+            diff,
+            PrecompileDiff {
+                changed: vec![*modexp::OSAKA.address()],
+                ..Default::default()
+            }
+        );
+        assert_eq!(Precompiles::prague().difference(Precompiles::osaka()), diff);
+
+        #[cfg(feature = "blst")]
+        {
+            let diff = Precompiles::prague().difference(Precompiles::cancun());
+            assert_eq!(
+                diff.added,
+                (0x0b..=0x11).map(u64_to_address).collect::<Vec<_>>()
+            );
+            assert!(diff.removed.is_empty() && diff.changed.is_empty());
+
+            let reverse = Precompiles::cancun().difference(Precompiles::prague());
+            assert_eq!(reverse.removed, diff.added);
+            assert!(reverse.added.is_empty() && reverse.changed.is_empty());
+        }
+    }
+}
@@ -15,6 +15,9 @@ pub const BYZANTIUM: PrecompileWithAddress = PrecompileWithAddress(
 pub const BERLIN: PrecompileWithAddress =
     PrecompileWithAddress(crate::u64_to_address(5), Precompile::Standard(berlin_run));
 
+pub const OSAKA: PrecompileWithAddress =
+    PrecompileWithAddress(crate::u64_to_address(5), Precompile::Standard(osaka_run));
+
 /// See: <https://eips.ethereum.org/EIPS/eip-198>
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000005>
 pub fn byzantium_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
@@ -29,7 +32,19 @@ pub fn berlin_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     })
 }
 
+/// See: <https://eips.ethereum.org/EIPS/eip-7883>
+pub fn osaka_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    run_inner(input, gas_limit, 500, |a, b, c, d| {
+        osaka_gas_calc(a, b, c, d)
+    })
+}
+
 pub fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
+    iteration_count(exp_length, exp_highp, 8)
+}
+
+/// Iteration count where every exponent byte past the first 32 counts `multiplier` times.
+fn iteration_count(exp_length: u64, exp_highp: &U256, multiplier: u64) -> u64 {
     let mut iteration_count: u64 = 0;
 
     if exp_length <= 32 && exp_highp.is_zero() {
@@ -37,7 +52,7 @@ pub fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
     } else if exp_length <= 32 {
         iteration_count = exp_highp.bit_len() as u64 - 1;
     } else if exp_length > 32 {
-        iteration_count = (8u64.saturating_mul(exp_length - 32))
+        iteration_count = (multiplier.saturating_mul(exp_length - 32))
             .saturating_add(max(1, exp_highp.bit_len() as u64) - 1);
     }
 
@@ -165,6 +180,24 @@ pub fn berlin_gas_calc(
     max(200, gas.saturating_to())
 }
 
+// Calculate gas cost according to EIP 7883:
+// https://eips.ethereum.org/EIPS/eip-7883
+pub fn osaka_gas_calc(base_length: u64, exp_length: u64, mod_length: u64, exp_highp: &U256) -> u64 {
+    fn calculate_multiplication_complexity(base_length: u64, mod_length: u64) -> U256 {
+        let max_length = max(base_length, mod_length);
+        if max_length <= 32 {
+            return U256::from(16);
+        }
+        let words = U256::from(max_length.div_ceil(8));
+        U256::from(2) * words * words
+    }
+
+    let multiplication_complexity = calculate_multiplication_complexity(base_length, mod_length);
+    let iteration_count = iteration_count(exp_length, exp_highp, 16);
+    let gas = multiplication_complexity * U256::from(iteration_count);
+    max(500, gas.saturating_to())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,6 +379,11 @@ mod tests {
         21_845, 5_461, 5_461, 87_381,
     ];
 
+    const OSAKA_GAS: [u64; 19] = [
+        453_596, 4_080, 4_080, 4_080, 500, 500, 2_048, 512, 512, 8_192, 2_048, 2_048, 32_768,
+        8_192, 8_192, 131_072, 32_768, 32_768, 524_288,
+    ];
+
     #[test]
     fn test_byzantium_modexp_gas() {
         for (test, &test_gas) in TESTS.iter().zip(BYZANTIUM_GAS.iter()) {
@@ -376,6 +414,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_osaka_modexp_gas() {
+        for (test, &test_gas) in TESTS.iter().zip(OSAKA_GAS.iter()) {
+            let input = hex::decode(test.input).unwrap().into();
+            let res = osaka_run(&input, 100_000_000).unwrap();
+            let expected = hex::decode(test.expected).unwrap();
+            assert_eq!(
+                res.gas_used, test_gas,
+                "used gas not matching for test: {}",
+                test.name
+            );
+            assert_eq!(res.bytes, expected, "test:{}", test.name);
+        }
+    }
+
+    #[test]
+    fn test_osaka_modexp_empty_input() {
+        let res = osaka_run(&Bytes::new(), 100_000).unwrap();
+        assert_eq!(res.gas_used, 500);
+        assert!(res.bytes.is_empty());
+        assert!(osaka_run(&Bytes::new(), 499).is_err());
+    }
+
     #[test]
     fn test_berlin_modexp_empty_input() {
         let res = berlin_run(&Bytes::new(), 100_000).unwrap();
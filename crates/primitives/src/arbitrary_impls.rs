@@ -0,0 +1,178 @@
+//! [`Arbitrary`] implementations used for fuzzing.
+//!
+//! Values are biased toward ones that pass validation, so that fuzzing reaches execution instead
+//! of being rejected upfront: gas limits are bounded, chain IDs and fees are small and legacy
+//! bytecode has complete `PUSH` data and frequent `JUMPDEST`s.
+
+use crate::{
+    BlobExcessGasAndPrice, BlockEnv, Bytecode, Bytes, CfgEnv, Env, TxEnv, TxKind, B256, U256,
+    VERSIONED_HASH_VERSION_KZG,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::vec::Vec;
+
+/// Maximum generated transaction and block gas limit.
+const MAX_GAS_LIMIT: u64 = 30_000_000;
+
+/// Maximum generated bytecode length.
+const MAX_CODE_LEN: usize = 1024;
+
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH32: u8 = 0x7f;
+
+impl<'a> Arbitrary<'a> for Env {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            cfg: u.arbitrary()?,
+            block: u.arbitrary()?,
+            tx: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for CfgEnv {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut cfg = CfgEnv::default().with_chain_id(u.int_in_range(1..=3)?);
+        if u.ratio(1, 8)? {
+            cfg.limit_contract_code_size = Some(u.int_in_range(0..=0x10000)?);
+        }
+        Ok(cfg)
+    }
+}
+
+impl<'a> Arbitrary<'a> for BlockEnv {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            number: U256::from(u.arbitrary::<u64>()?),
+            coinbase: u.arbitrary()?,
+            timestamp: U256::from(u.arbitrary::<u64>()?),
+            gas_limit: U256::from(u.int_in_range(0..=MAX_GAS_LIMIT)?),
+            basefee: U256::from(u.int_in_range(0..=1_000u64)?),
+            difficulty: u.arbitrary()?,
+            prevrandao: Some(u.arbitrary()?),
+            blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(
+                u.int_in_range(0..=10_000_000)?,
+                u.arbitrary()?,
+            )),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for TxEnv {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let max_fee_per_blob_gas = if u.ratio(1, 8)? {
+            Some(U256::from(u.int_in_range(0..=1_000u64)?))
+        } else {
+            None
+        };
+        let blob_hashes = if max_fee_per_blob_gas.is_some() {
+            let count = u.int_in_range(0..=7)?;
+            let mut hashes = Vec::with_capacity(count);
+            for _ in 0..count {
+                let mut hash: B256 = u.arbitrary()?;
+                if !u.ratio(1, 16)? {
+                    hash[0] = VERSIONED_HASH_VERSION_KZG;
+                }
+                hashes.push(hash);
+            }
+            hashes
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            caller: u.arbitrary()?,
+            gas_limit: u.int_in_range(0..=MAX_GAS_LIMIT)?,
+            gas_price: U256::from(u.int_in_range(0..=2_000u64)?),
+            gas_priority_fee: if u.arbitrary()? {
+                Some(U256::from(u.int_in_range(0..=2_000u64)?))
+            } else {
+                None
+            },
+            transact_to: if u.ratio(1, 4)? {
+                TxKind::Create
+            } else {
+                TxKind::Call(u.arbitrary()?)
+            },
+            value: U256::from(u.arbitrary::<u64>()?),
+            data: u.arbitrary()?,
+            chain_id: if u.arbitrary()? {
+                Some(u.int_in_range(1..=3)?)
+            } else {
+                None
+            },
+            nonce: None,
+            access_list: u.arbitrary()?,
+            blob_hashes,
+            max_fee_per_blob_gas,
+            authorization_list: None,
+            #[cfg(feature = "secp256k1-recover")]
+            signature: None,
+            #[cfg(feature = "optimism")]
+            optimism: Default::default(),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Bytecode {
+    /// Generates legacy bytecode from arbitrary opcodes, each `PUSH` followed by its complete
+    /// immediate data.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let len = u.int_in_range(0..=MAX_CODE_LEN)?;
+        let mut code = Vec::with_capacity(len + 32);
+        while code.len() < len && !u.is_empty() {
+            if u.ratio(1, 16)? {
+                code.push(JUMPDEST);
+                continue;
+            }
+            let opcode: u8 = u.arbitrary()?;
+            code.push(opcode);
+            if (PUSH1..=PUSH32).contains(&opcode) {
+                for _ in 0..=opcode - PUSH1 {
+                    code.push(u.arbitrary()?);
+                }
+            }
+        }
+        Ok(Bytecode::new_legacy(Bytes::from(code)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytecode_push_data_is_complete() {
+        let data: Vec<u8> = (0..4096u32)
+            .map(|i| i.wrapping_mul(2654435761) as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let code = Bytecode::arbitrary(&mut u).unwrap().original_bytes();
+            let mut i = 0;
+            while i < code.len() {
+                if (PUSH1..=PUSH32).contains(&code[i]) {
+                    i += (code[i] - PUSH1 + 1) as usize;
+                }
+                i += 1;
+            }
+            assert_eq!(i, code.len());
+        }
+    }
+
+    #[test]
+    fn env_is_bounded() {
+        let data: Vec<u8> = (0..4096u32)
+            .map(|i| i.wrapping_mul(2246822519) as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let env = Env::arbitrary(&mut u).unwrap();
+            assert!(env.tx.gas_limit <= MAX_GAS_LIMIT);
+            assert!(env.block.gas_limit <= U256::from(MAX_GAS_LIMIT));
+            assert!((1..=3).contains(&env.cfg.chain_id));
+            assert!(env.tx.max_fee_per_blob_gas.is_some() || env.tx.blob_hashes.is_empty());
+        }
+    }
+}
@@ -76,10 +76,20 @@ impl Bytecode {
     }
 
     /// Returns true if bytecode is EIP-7702.
+    #[inline]
     pub const fn is_eip7702(&self) -> bool {
         matches!(self, Self::Eip7702(_))
     }
 
+    /// Returns the delegated address if bytecode is EIP-7702.
+    #[inline]
+    pub const fn eip7702_address(&self) -> Option<Address> {
+        match self {
+            Self::Eip7702(eip7702) => Some(eip7702.delegated_address),
+            _ => None,
+        }
+    }
+
     /// Creates a new legacy [`Bytecode`].
     #[inline]
     pub fn new_legacy(raw: Bytes) -> Self {
@@ -249,9 +259,36 @@ impl fmt::Display for BytecodeDecodeError {
 
 #[cfg(test)]
 mod tests {
-    use super::{Bytecode, Eof};
+    use super::{Bytecode, BytecodeDecodeError, Eof};
+    use crate::{bytes, eip7702::Eip7702DecodeError, Address};
     use std::sync::Arc;
 
+    #[test]
+    fn eip7702_bytecode() {
+        let address = Address::new([0x01; 20]);
+        let bytecode = Bytecode::new_eip7702(address);
+        assert!(bytecode.is_eip7702());
+        assert_eq!(bytecode.eip7702_address(), Some(address));
+        assert_eq!(
+            Bytecode::new_raw_checked(bytecode.original_bytes()),
+            Ok(bytecode)
+        );
+        assert_eq!(Bytecode::new_legacy(bytes!("00")).eip7702_address(), None);
+
+        assert_eq!(
+            Bytecode::new_raw_checked(bytes!("ef0100deadbeef")),
+            Err(BytecodeDecodeError::Eip7702(
+                Eip7702DecodeError::InvalidLength
+            ))
+        );
+        assert_eq!(
+            Bytecode::new_raw_checked(bytes!("ef01010101010101010101010101010101010101010101")),
+            Err(BytecodeDecodeError::Eip7702(
+                Eip7702DecodeError::UnsupportedVersion
+            ))
+        );
+    }
+
     #[test]
     fn eof_arc_clone() {
         let eof = Arc::new(Eof::default());
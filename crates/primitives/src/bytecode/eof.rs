@@ -79,6 +79,23 @@ impl Eof {
         &self.body.data_section
     }
 
+    /// Returns the code sections.
+    pub fn code_sections(&self) -> &[Bytes] {
+        &self.body.code_section
+    }
+
+    /// Returns the types sections, one for each code section.
+    pub fn types_sections(&self) -> &[TypesSection] {
+        &self.body.types_section
+    }
+
+    /// Returns the raw container sections.
+    ///
+    /// Containers are not decoded, use [`Eof::decode`] on them to access their sections.
+    pub fn container_sections(&self) -> &[Bytes] {
+        &self.body.container_section
+    }
+
     /// Slow encode EOF bytes.
     pub fn encode_slow(&self) -> Bytes {
         let mut buffer: Vec<u8> = Vec::with_capacity(self.size());
@@ -234,6 +251,35 @@ mod test {
         }
     }
 
+    #[test]
+    fn section_accessors() {
+        let bytes = bytes!("ef000101000402000100010400020000800000fe0102");
+        let eof = Eof::decode(bytes).unwrap();
+        assert_eq!(eof.code_sections(), &[bytes!("fe")]);
+        assert_eq!(eof.types_sections(), &[TypesSection::new(0, 0x80, 0)]);
+        assert!(eof.container_sections().is_empty());
+        assert_eq!(eof.data(), &[0x01, 0x02]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let test_cases = [
+            (bytes!("ef01"), EofDecodeError::InvalidEOFMagicNumber),
+            (bytes!("ef0002"), EofDecodeError::InvalidEOFVersion),
+            (
+                bytes!("ef000101000402000100010400000000800000"),
+                EofDecodeError::MissingBodyWithoutData,
+            ),
+            (
+                bytes!("ef000101000802000100010400000000800000fe"),
+                EofDecodeError::MismatchCodeAndTypesSize,
+            ),
+        ];
+        for (bytes, error) in test_cases {
+            assert_eq!(Eof::decode(bytes), Err(error));
+        }
+    }
+
     #[test]
     fn data_slice() {
         let bytes = bytes!("ef000101000402000100010400000000800000fe");
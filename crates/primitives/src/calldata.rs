@@ -0,0 +1,205 @@
+//! Calldata statistics used in fee estimation: the intrinsic gas and the
+//! [EIP-7623](https://eips.ethereum.org/EIPS/eip-7623) floor charged for calldata, and the FastLZ
+//! compressed size used by the Optimism L1 data fee.
+
+use crate::SpecId;
+
+/// Tokens of a zero byte of calldata.
+pub const ZERO_BYTE_TOKENS: u64 = 1;
+
+/// Tokens of a non-zero byte of calldata before Istanbul, 68 gas at 4 gas per token.
+pub const NON_ZERO_BYTE_TOKENS: u64 = 17;
+
+/// Tokens of a non-zero byte of calldata from Istanbul, 16 gas at 4 gas per token.
+///
+/// EIP-2028: Transaction data gas cost reduction
+pub const NON_ZERO_BYTE_TOKENS_ISTANBUL: u64 = 4;
+
+/// Number of zero and non-zero bytes of calldata.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CalldataStats {
+    /// Number of zero bytes.
+    pub zero_bytes: u64,
+    /// Number of non-zero bytes.
+    pub non_zero_bytes: u64,
+}
+
+impl CalldataStats {
+    /// Counts the zero and non-zero bytes of the data.
+    #[inline]
+    pub fn new(data: &[u8]) -> Self {
+        let zero_bytes = data.iter().filter(|byte| **byte == 0).count() as u64;
+        Self {
+            zero_bytes,
+            non_zero_bytes: data.len() as u64 - zero_bytes,
+        }
+    }
+
+    /// Returns the number of tokens of the data at the spec.
+    ///
+    /// A zero byte is one token, a non-zero byte is 17 tokens before Istanbul and 4 tokens from
+    /// it. Intrinsic gas charges 4 gas per token, and the EIP-7623 floor 10 gas per token.
+    #[inline]
+    pub fn tokens(&self, spec: SpecId) -> u64 {
+        let non_zero_byte_tokens = if spec.is_enabled_in(SpecId::ISTANBUL) {
+            NON_ZERO_BYTE_TOKENS_ISTANBUL
+        } else {
+            NON_ZERO_BYTE_TOKENS
+        };
+        self.zero_bytes * ZERO_BYTE_TOKENS + self.non_zero_bytes * non_zero_byte_tokens
+    }
+}
+
+/// Returns the number of tokens of the calldata at the spec, see [`CalldataStats::tokens`].
+#[inline]
+pub fn calldata_token_count(data: &[u8], spec: SpecId) -> u64 {
+    CalldataStats::new(data).tokens(spec)
+}
+
+/// Returns the length of the data after compression through FastLZ, based on
+/// <https://github.com/Vectorized/solady/blob/5315d937d79b335c668896d7533ac603adac5315/js/solady.js>
+/// The u32s match op-geth's Go port:
+/// <https://github.com/ethereum-optimism/op-geth/blob/647c346e2bef36219cc7b47d76b1cb87e7ca29e4/core/types/rollup_cost.go#L411>
+pub fn flz_compress_len(input: &[u8]) -> u32 {
+    let mut idx: u32 = 2;
+
+    let idx_limit: u32 = if input.len() < 13 {
+        0
+    } else {
+        input.len() as u32 - 13
+    };
+
+    let mut anchor = 0;
+
+    let mut size = 0;
+
+    let mut htab = [0; 8192];
+
+    while idx < idx_limit {
+        let mut r: u32;
+        let mut distance: u32;
+
+        loop {
+            let seq = u24(input, idx);
+            let hash = hash(seq);
+            r = htab[hash as usize];
+            htab[hash as usize] = idx;
+            distance = idx - r;
+            if idx >= idx_limit {
+                break;
+            }
+            idx += 1;
+            if distance < 8192 && seq == u24(input, r) {
+                break;
+            }
+        }
+
+        if idx >= idx_limit {
+            break;
+        }
+
+        idx -= 1;
+
+        if idx > anchor {
+            size = literals(idx - anchor, size);
+        }
+
+        let len = cmp(input, r + 3, idx + 3, idx_limit + 9);
+        size = flz_match(len, size);
+
+        idx = set_next_hash(&mut htab, input, idx + len);
+        idx = set_next_hash(&mut htab, input, idx);
+        anchor = idx;
+    }
+
+    literals(input.len() as u32 - anchor, size)
+}
+
+fn literals(r: u32, size: u32) -> u32 {
+    let size = size + 0x21 * (r / 0x20);
+    let r = r % 0x20;
+    if r != 0 {
+        size + r + 1
+    } else {
+        size
+    }
+}
+
+fn cmp(input: &[u8], p: u32, q: u32, r: u32) -> u32 {
+    let mut l = 0;
+    let mut r = r - q;
+    while l < r {
+        if input[(p + l) as usize] != input[(q + l) as usize] {
+            r = 0;
+        }
+        l += 1;
+    }
+    l
+}
+
+fn flz_match(l: u32, size: u32) -> u32 {
+    let l = l - 1;
+    let size = size + (3 * (l / 262));
+    if l % 262 >= 6 {
+        size + 3
+    } else {
+        size + 2
+    }
+}
+
+fn set_next_hash(htab: &mut [u32; 8192], input: &[u8], idx: u32) -> u32 {
+    htab[hash(u24(input, idx)) as usize] = idx;
+    idx + 1
+}
+
+fn hash(v: u32) -> u16 {
+    let hash = (v as u64 * 2654435769) >> 19;
+    hash as u16 & 0x1fff
+}
+
+fn u24(input: &[u8], idx: u32) -> u32 {
+    u32::from(input[idx as usize])
+        + (u32::from(input[(idx + 1) as usize]) << 8)
+        + (u32::from(input[(idx + 2) as usize]) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Counts tokens byte by byte, with the costs in gas of EIP-2028.
+    fn reference_tokens(data: &[u8], istanbul: bool) -> u64 {
+        let non_zero_cost = if istanbul { 16 } else { 68 };
+        data.iter()
+            .map(|byte| if *byte == 0 { 4 } else { non_zero_cost })
+            .sum::<u64>()
+            / 4
+    }
+
+    #[test]
+    fn calldata_stats() {
+        let stats = CalldataStats::new(&[0, 1, 0, 0, 255]);
+        assert_eq!(
+            stats,
+            CalldataStats {
+                zero_bytes: 3,
+                non_zero_bytes: 2
+            }
+        );
+        assert_eq!(stats.tokens(SpecId::PETERSBURG), 3 + 2 * 17);
+        assert_eq!(stats.tokens(SpecId::ISTANBUL), 3 + 2 * 4);
+        assert_eq!(calldata_token_count(&[], SpecId::PRAGUE), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn tokens_match_reference(
+            data in proptest::collection::vec(any::<u8>(), 0..512),
+            istanbul in any::<bool>(),
+        ) {
+            let spec = if istanbul { SpecId::ISTANBUL } else { SpecId::PETERSBURG };
+            prop_assert_eq!(calldata_token_count(&data, spec), reference_tokens(&data, istanbul));
+        }
+    }
+}
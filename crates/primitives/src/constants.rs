@@ -5,6 +5,9 @@ use alloy_primitives::{address, Address};
 /// By default the limit is `0x6000` (~25kb)
 pub const MAX_CODE_SIZE: usize = 0x6000;
 
+/// Gas charged per byte of the code deployed by a contract creation.
+pub const CODE_DEPOSIT_GAS_PER_BYTE: u64 = 200;
+
 /// Number of block hashes that EVM can access in the past (pre-Prague).
 pub const BLOCK_HASH_HISTORY: u64 = 256;
 
@@ -26,6 +29,31 @@ pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
 /// This is named `HISTORY_STORAGE_ADDRESS` in the EIP.
 pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("25a219378dad9b3503c8268c9ca836a52427a4fb");
 
+/// Caller of the system calls made by block processing, such as the EIP-7002 and EIP-7251
+/// request dequeues.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Gas limit of the system calls made by block processing.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// EIP-6110: Supply validator deposits on chain
+///
+/// The address of the deposit contract on mainnet.
+pub const MAINNET_DEPOSIT_CONTRACT_ADDRESS: Address =
+    address!("00000000219ab540356cbb839cbe05303d7705fa");
+
+/// EIP-7002: Execution layer triggerable withdrawals
+///
+/// The address of the withdrawal request predeploy.
+pub const WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS: Address =
+    address!("00000961ef480eb55e80d19ad83579a64c007002");
+
+/// EIP-7251: Increase the MAX_EFFECTIVE_BALANCE
+///
+/// The address of the consolidation request predeploy.
+pub const CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS: Address =
+    address!("0000bbddc7ce488642fb579f8b00f3a590007251");
+
 /// EIP-3860: Limit and meter initcode
 ///
 /// Limit of maximum initcode size is `2 * MAX_CODE_SIZE`.
@@ -50,5 +78,10 @@ pub const BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN: u64 = 3338477;
 /// EIP-7691: Blob throughput increase
 pub const BLOB_BASE_FEE_UPDATE_FRACTION_ELECTRA: u64 = 5007716;
 
+/// Maximum number of blobs a single transaction can carry from Osaka on, regardless of the
+/// block blob limit.
+/// EIP-7594: PeerDAS - Peer Data Availability Sampling
+pub const MAX_BLOBS_PER_TX_OSAKA: u8 = 6;
+
 /// First version of the blob.
 pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
@@ -2,9 +2,22 @@ use crate::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
 use auto_impl::auto_impl;
 
 pub mod components;
+pub mod either;
 pub use components::{
     BlockHash, BlockHashRef, DatabaseComponentError, DatabaseComponents, State, StateRef,
 };
+pub use either::{Either, EitherError};
+
+/// Marker trait for database error types.
+///
+/// Database errors implementing it convert into [`EVMError::Database`] with `?`. Implement it for
+/// the error type of a custom database to get the same conversion.
+///
+/// [`EVMError::Database`]: crate::EVMError::Database
+pub trait DBErrorMarker {}
+
+impl DBErrorMarker for core::convert::Infallible {}
+impl DBErrorMarker for std::string::String {}
 
 /// EVM database interface.
 #[auto_impl(&mut, Box)]
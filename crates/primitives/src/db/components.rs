@@ -10,7 +10,7 @@ use crate::{
     Account, AccountInfo, Address, Bytecode, HashMap, B256, U256,
 };
 
-use super::DatabaseCommit;
+use super::{DBErrorMarker, DatabaseCommit};
 
 #[derive(Debug)]
 pub struct DatabaseComponents<S, BH> {
@@ -24,6 +24,8 @@ pub enum DatabaseComponentError<SE, BHE> {
     BlockHash(BHE),
 }
 
+impl<SE: DBErrorMarker, BHE: DBErrorMarker> DBErrorMarker for DatabaseComponentError<SE, BHE> {}
+
 impl<S: State, BH: BlockHash> Database for DatabaseComponents<S, BH> {
     type Error = DatabaseComponentError<S::Error, BH::Error>;
 
@@ -0,0 +1,131 @@
+//! Database that is one of two databases.
+use crate::{
+    db::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef},
+    Account, AccountInfo, Address, Bytecode, HashMap, B256, U256,
+};
+use core::fmt;
+
+/// One of two databases, for example a database chosen at runtime.
+///
+/// Implements [`Database`], [`DatabaseRef`] and [`DatabaseCommit`] when both databases do, with
+/// [`EitherError`] as the error type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Error of an [`Either`] database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EitherError<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<E> EitherError<E, E> {
+    /// Returns the error of the database when both databases have the same error type.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        match self {
+            Self::Left(e) | Self::Right(e) => e,
+        }
+    }
+}
+
+impl<L: fmt::Display, R: fmt::Display> fmt::Display for EitherError<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left(e) => e.fmt(f),
+            Self::Right(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L, R> std::error::Error for EitherError<L, R>
+where
+    L: std::error::Error + 'static,
+    R: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Left(e) => Some(e),
+            Self::Right(e) => Some(e),
+        }
+    }
+}
+
+impl<L: DBErrorMarker, R: DBErrorMarker> DBErrorMarker for EitherError<L, R> {}
+
+impl<L: Database, R: Database> Database for Either<L, R> {
+    type Error = EitherError<L::Error, R::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self {
+            Self::Left(db) => db.basic(address).map_err(EitherError::Left),
+            Self::Right(db) => db.basic(address).map_err(EitherError::Right),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self {
+            Self::Left(db) => db.code_by_hash(code_hash).map_err(EitherError::Left),
+            Self::Right(db) => db.code_by_hash(code_hash).map_err(EitherError::Right),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self {
+            Self::Left(db) => db.storage(address, index).map_err(EitherError::Left),
+            Self::Right(db) => db.storage(address, index).map_err(EitherError::Right),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        match self {
+            Self::Left(db) => db.block_hash(number).map_err(EitherError::Left),
+            Self::Right(db) => db.block_hash(number).map_err(EitherError::Right),
+        }
+    }
+}
+
+impl<L: DatabaseRef, R: DatabaseRef> DatabaseRef for Either<L, R> {
+    type Error = EitherError<L::Error, R::Error>;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self {
+            Self::Left(db) => db.basic_ref(address).map_err(EitherError::Left),
+            Self::Right(db) => db.basic_ref(address).map_err(EitherError::Right),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self {
+            Self::Left(db) => db.code_by_hash_ref(code_hash).map_err(EitherError::Left),
+            Self::Right(db) => db.code_by_hash_ref(code_hash).map_err(EitherError::Right),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self {
+            Self::Left(db) => db.storage_ref(address, index).map_err(EitherError::Left),
+            Self::Right(db) => db.storage_ref(address, index).map_err(EitherError::Right),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self {
+            Self::Left(db) => db.block_hash_ref(number).map_err(EitherError::Left),
+            Self::Right(db) => db.block_hash_ref(number).map_err(EitherError::Right),
+        }
+    }
+}
+
+impl<L: DatabaseCommit, R: DatabaseCommit> DatabaseCommit for Either<L, R> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        match self {
+            Self::Left(db) => db.commit(changes),
+            Self::Right(db) => db.commit(changes),
+        }
+    }
+}
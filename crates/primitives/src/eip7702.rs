@@ -15,3 +15,40 @@ pub const PER_AUTH_BASE_COST: u64 = 12500;
 
 /// Cost of creating authorized account that was previously empty.
 pub const PER_EMPTY_ACCOUNT_COST: u64 = 25000;
+
+/// Refund of an authorization applied to an authority that exists, which is charged
+/// [`PER_EMPTY_ACCOUNT_COST`] in the initial gas as if it did not.
+pub const PER_EXISTING_AUTHORITY_REFUND: u64 = PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST;
+
+/// Outcome of an authorization of an EIP-7702 authorization list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AuthorizationOutcome {
+    /// The authorization is invalid and skipped, the authority is unchanged.
+    Skipped,
+    /// The code of the authority is set, or cleared, and its nonce is bumped.
+    Applied {
+        /// Whether the authority existed, possibly created by an earlier authorization of the
+        /// list.
+        authority_existed: bool,
+    },
+}
+
+impl AuthorizationOutcome {
+    /// Returns the gas refunded for the authorization, [`PER_EXISTING_AUTHORITY_REFUND`] if it
+    /// is applied to an existing authority.
+    #[inline]
+    pub const fn refund(&self) -> u64 {
+        match self {
+            Self::Applied {
+                authority_existed: true,
+            } => PER_EXISTING_AUTHORITY_REFUND,
+            _ => 0,
+        }
+    }
+}
+
+/// Returns the gas refunded for the outcomes of the authorizations of a transaction, added to
+/// its refund counter.
+pub fn eip7702_refund(outcomes: &[AuthorizationOutcome]) -> i64 {
+    outcomes.iter().map(|outcome| outcome.refund() as i64).sum()
+}
@@ -29,9 +29,20 @@ pub struct Eip7702Bytecode {
 }
 
 impl Eip7702Bytecode {
-    /// Creates a new EIP-7702 bytecode or returns None if the raw bytecode is invalid.
+    /// Creates a new EIP-7702 bytecode or returns an error if the raw bytecode is invalid.
     #[inline]
     pub fn new_raw(raw: Bytes) -> Result<Self, Eip7702DecodeError> {
+        Self::validate(&raw)?;
+        Ok(Self {
+            delegated_address: Address::from_slice(&raw[3..]),
+            version: EIP7702_VERSION,
+            raw,
+        })
+    }
+
+    /// Checks that `raw` is a valid delegation designation, `0xEF01 || 0x00 || address`.
+    #[inline]
+    pub fn validate(raw: &[u8]) -> Result<(), Eip7702DecodeError> {
         if raw.len() != 23 {
             return Err(Eip7702DecodeError::InvalidLength);
         }
@@ -43,12 +54,7 @@ impl Eip7702Bytecode {
         if raw[2] != EIP7702_VERSION {
             return Err(Eip7702DecodeError::UnsupportedVersion);
         }
-
-        Ok(Self {
-            delegated_address: Address::new(raw[3..].try_into().unwrap()),
-            version: EIP7702_VERSION,
-            raw,
-        })
+        Ok(())
     }
 
     /// Creates a new EIP-7702 bytecode with the given address.
@@ -74,6 +80,18 @@ impl Eip7702Bytecode {
     pub fn address(&self) -> Address {
         self.delegated_address
     }
+
+    /// Return the address of the delegated contract.
+    #[inline]
+    pub fn delegated_address(&self) -> Address {
+        self.delegated_address
+    }
+}
+
+impl fmt::Display for Eip7702Bytecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EIP-7702 delegation to {}", self.delegated_address)
+    }
 }
 
 /// Bytecode errors.
@@ -105,6 +123,7 @@ impl std::error::Error for Eip7702DecodeError {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::string::ToString;
 
     #[test]
     fn sanity_decode() {
@@ -132,6 +151,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate() {
+        let address = Address::new([0x01; 20]);
+        let raw = Eip7702Bytecode::new(address).raw;
+        assert_eq!(Eip7702Bytecode::validate(&raw), Ok(()));
+        assert_eq!(
+            Eip7702Bytecode::validate(&raw[..22]),
+            Err(Eip7702DecodeError::InvalidLength)
+        );
+        let mut long = raw.to_vec();
+        long.push(0x00);
+        assert_eq!(
+            Eip7702Bytecode::validate(&long),
+            Err(Eip7702DecodeError::InvalidLength)
+        );
+
+        let mut invalid = raw.to_vec();
+        invalid[1] = 0x02;
+        assert_eq!(
+            Eip7702Bytecode::validate(&invalid),
+            Err(Eip7702DecodeError::InvalidMagic)
+        );
+
+        let mut invalid = raw.to_vec();
+        invalid[2] = 0x01;
+        assert_eq!(
+            Eip7702Bytecode::validate(&invalid),
+            Err(Eip7702DecodeError::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let address = Address::new([0x01; 20]);
+        let bytecode = Eip7702Bytecode::new(address);
+        let decoded = Eip7702Bytecode::new_raw(bytecode.raw().clone()).unwrap();
+        assert_eq!(decoded, bytecode);
+        assert_eq!(decoded.delegated_address(), address);
+        assert_eq!(
+            decoded.to_string(),
+            "EIP-7702 delegation to 0x0101010101010101010101010101010101010101"
+        );
+    }
+
     #[test]
     fn create_eip7702_bytecode_from_address() {
         let address = Address::new([0x01; 20]);
@@ -1,11 +1,38 @@
+pub mod cache_key;
+pub mod fees;
 pub mod handler_cfg;
-
+#[cfg(feature = "optimism")]
+pub mod interop;
+pub mod keccak;
+pub mod parent;
+#[cfg(feature = "secp256k1-recover")]
+pub mod signature;
+#[cfg(feature = "txpool-utils")]
+pub mod txpool;
+
+pub use fees::{
+    effective_balance_requirement, gas_fee, gas_to_u256, refund_to_gas, tx_fee_charge, tx_max_cost,
+    u256_to_gas,
+};
 pub use handler_cfg::{CfgEnvWithHandlerCfg, EnvWithHandlerCfg, HandlerCfg};
+#[cfg(feature = "optimism")]
+pub use interop::{EnvInteropValidator, InteropValidator};
+pub use keccak::{EnvKeccak, Keccak};
+pub use parent::{
+    blob_fee_after, simulate_blob_fees, target_blob_gas_per_block, BaseFeeParams, ParentBlockInfo,
+    INITIAL_BASE_FEE,
+};
+#[cfg(feature = "secp256k1-recover")]
+pub use signature::TxSignature;
+#[cfg(feature = "txpool-utils")]
+pub use txpool::{ByEffectiveTip, DEFAULT_PRICE_BUMP};
 
+#[cfg(feature = "optional_eip3607")]
+use crate::HashSet;
 use crate::{
-    calc_blob_gasprice, calc_excess_blob_gas, AccessListItem, Account, Address, AuthorizationList,
-    Bytes, InvalidHeader, InvalidTransaction, Spec, SpecId, B256, GAS_PER_BLOB, MAX_CODE_SIZE,
-    MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
+    calc_blob_gasprice, calc_excess_blob_gas, AccessListItem, Account, AccountInfo, Address,
+    AuthorizationList, Bytes, InvalidHeader, InvalidTransaction, Spec, SpecId, B256, GAS_PER_BLOB,
+    MAX_BLOBS_PER_TX_OSAKA, MAX_CODE_SIZE, MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use alloy_primitives::TxKind;
 use core::cmp::{min, Ordering};
@@ -48,6 +75,15 @@ impl Env {
         }
     }
 
+    /// Returns the address receiving the priority fees of the transaction,
+    /// [`CfgEnv::fee_recipient_override`] if set, otherwise the block coinbase.
+    #[inline]
+    pub fn fee_recipient(&self) -> Address {
+        self.cfg
+            .fee_recipient_override
+            .unwrap_or(self.block.coinbase)
+    }
+
     /// Calculates the [EIP-4844] `data_fee` of the transaction.
     ///
     /// Returns `None` if `Cancun` is not enabled. This is enforced in [`Env::validate_block_env`].
@@ -77,9 +113,16 @@ impl Env {
     #[inline]
     pub fn validate_block_env<SPEC: Spec>(&self) -> Result<(), InvalidHeader> {
         // `prevrandao` is required for the merge
-        if SPEC::enabled(SpecId::MERGE) && self.block.prevrandao.is_none() {
+        if self.block.randomness(SPEC::SPEC_ID).is_none() {
             return Err(InvalidHeader::PrevrandaoNotSet);
         }
+        // `difficulty` is ignored after the merge
+        if SPEC::enabled(SpecId::MERGE)
+            && self.cfg.strict_randomness
+            && self.block.difficulty != U256::ZERO
+        {
+            return Err(InvalidHeader::DifficultyAfterMerge);
+        }
         // `excess_blob_gas` is required for Cancun
         if SPEC::enabled(SpecId::CANCUN) && self.block.blob_excess_gas_and_price.is_none() {
             return Err(InvalidHeader::ExcessBlobGasNotSet);
@@ -94,7 +137,7 @@ impl Env {
     pub fn validate_tx<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
         // Check if the transaction's chain id is correct
         if let Some(tx_chain_id) = self.tx.chain_id {
-            if tx_chain_id != self.cfg.chain_id {
+            if !self.cfg.is_chain_id_check_disabled() && tx_chain_id != self.cfg.chain_id {
                 return Err(InvalidTransaction::InvalidChainId);
             }
         }
@@ -129,22 +172,21 @@ impl Env {
         }
 
         // EIP-3860: Limit and meter initcode
-        if SPEC::enabled(SpecId::SHANGHAI) && self.tx.transact_to.is_create() {
-            let max_initcode_size = self
-                .cfg
-                .limit_contract_code_size
-                .map(|limit| limit.saturating_mul(2))
-                .unwrap_or(MAX_INITCODE_SIZE);
-            if self.tx.data.len() > max_initcode_size {
-                return Err(InvalidTransaction::CreateInitCodeSizeLimit);
-            }
+        if SPEC::enabled(SpecId::SHANGHAI)
+            && self.tx.transact_to.is_create()
+            && self.tx.data.len() > self.cfg.max_initcode_size()
+        {
+            return Err(InvalidTransaction::CreateInitCodeSizeLimit);
         }
 
         // - For before CANCUN, check that `blob_hashes` and `max_fee_per_blob_gas` are empty / not set
-        if !SPEC::enabled(SpecId::CANCUN)
-            && (self.tx.max_fee_per_blob_gas.is_some() || !self.tx.blob_hashes.is_empty())
-        {
-            return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+        if !SPEC::enabled(SpecId::CANCUN) {
+            if self.tx.max_fee_per_blob_gas.is_some() {
+                return Err(InvalidTransaction::MaxFeePerBlobGasNotSupported);
+            }
+            if !self.tx.blob_hashes.is_empty() {
+                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+            }
         }
 
         // Presence of max_fee_per_blob_gas means that this is blob transaction.
@@ -177,10 +219,14 @@ impl Env {
 
             // ensure the total blob gas spent is at most equal to the limit
             // assert blob_gas_used <= MAX_BLOB_GAS_PER_BLOCK
-            if SPEC::enabled(SpecId::CANCUN) {
+            if SPEC::enabled(SpecId::CANCUN) && !self.cfg.is_blob_count_check_disabled() {
                 let num_blobs = self.tx.blob_hashes.len();
-                if num_blobs > self.cfg.blob_max_count(SPEC::SPEC_ID) as usize {
-                    return Err(InvalidTransaction::TooManyBlobs { have: num_blobs });
+                let max_blobs = self.cfg.blob_max_count_per_tx(SPEC::SPEC_ID) as usize;
+                if num_blobs > max_blobs {
+                    return Err(InvalidTransaction::TooManyBlobs {
+                        have: num_blobs,
+                        max: max_blobs,
+                    });
                 }
             }
         } else {
@@ -212,19 +258,37 @@ impl Env {
 
     /// Validate transaction against state.
     ///
+    /// With [`BaseFeeCheckMode::Cap`] the gas price is lowered to what the caller can pay, see
+    /// [`Env::cap_gas_price_to_balance`].
+    ///
     /// # Panics
     ///
     /// If account code is not loaded.
     #[inline]
     pub fn validate_tx_against_state<SPEC: Spec>(
-        &self,
+        &mut self,
         account: &mut Account,
+    ) -> Result<(), InvalidTransaction> {
+        self.validate_account_nonce_and_code(&account.info)?;
+        self.cap_gas_price_to_balance::<SPEC>(account.info.balance, U256::ZERO);
+        self.validate_account_balance::<SPEC>(account, U256::ZERO)
+    }
+
+    /// Validates the code and nonce of the caller account.
+    ///
+    /// # Panics
+    ///
+    /// If account code is not loaded.
+    #[inline]
+    pub fn validate_account_nonce_and_code(
+        &self,
+        info: &AccountInfo,
     ) -> Result<(), InvalidTransaction> {
         // EIP-3607: Reject transactions from senders with deployed code
         // This EIP is introduced after london but there was no collision in past
         // so we can leave it enabled always
-        if !self.cfg.is_eip3607_disabled() {
-            let bytecode = &account.info.code.as_ref().unwrap();
+        if !self.cfg.is_eip3607_disabled_for(self.tx.caller) {
+            let bytecode = &info.code.as_ref().unwrap();
             // allow EOAs whose code is a valid delegation designation,
             // i.e. 0xef0100 || address, to continue to originate transactions.
             if !bytecode.is_empty() && !bytecode.is_eip7702() {
@@ -234,7 +298,7 @@ impl Env {
 
         // Check that the transaction's nonce is correct
         if let Some(tx) = self.tx.nonce {
-            let state = account.info.nonce;
+            let state = info.nonce;
             match tx.cmp(&state) {
                 Ordering::Greater => {
                     return Err(InvalidTransaction::NonceTooHigh { tx, state });
@@ -246,21 +310,55 @@ impl Env {
             }
         }
 
-        let mut balance_check = U256::from(self.tx.gas_limit)
-            .checked_mul(self.tx.gas_price)
-            .and_then(|gas_cost| gas_cost.checked_add(self.tx.value))
-            .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
-
-        if SPEC::enabled(SpecId::CANCUN) {
-            // if the tx is not a blob tx, this will be None, so we add zero
-            let data_fee = self.calc_max_data_fee().unwrap_or_default();
-            balance_check = balance_check
-                .checked_add(U256::from(data_fee))
-                .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
+        Ok(())
+    }
+
+    /// Lowers the gas price of the transaction to the price the caller can pay with `balance` if
+    /// the basefee check is [`BaseFeeCheckMode::Cap`], after the value transfer and
+    /// `additional_fees`.
+    ///
+    /// The gas price is never raised. This is called before [`Env::validate_account_balance`].
+    #[inline]
+    pub fn cap_gas_price_to_balance<SPEC: Spec>(&mut self, balance: U256, additional_fees: U256) {
+        if self.cfg.base_fee_check_mode() != BaseFeeCheckMode::Cap || self.tx.gas_limit == 0 {
+            return;
         }
+        // Overflows are reported by the balance check.
+        let Some(other_fees) =
+            effective_balance_requirement(&self.tx, U256::ZERO, additional_fees, SPEC::SPEC_ID)
+        else {
+            return;
+        };
+        let affordable = balance.saturating_sub(other_fees) / U256::from(self.tx.gas_limit);
+        if affordable < self.tx.gas_price {
+            self.tx.gas_price = affordable;
+            if let Some(priority_fee) = &mut self.tx.gas_priority_fee {
+                *priority_fee = min(*priority_fee, affordable);
+            }
+        }
+    }
 
-        // Check if account has enough balance for gas_limit*gas_price and value transfer.
-        // Transfer will be done inside `*_inner` functions.
+    /// Validates that the caller account can pay for the transaction, `additional_fees` on top of
+    /// the gas limit and the value transfer.
+    ///
+    /// If the balance check is disabled, the balance of the account is raised to the cost of the
+    /// transaction instead.
+    #[inline]
+    pub fn validate_account_balance<SPEC: Spec>(
+        &self,
+        account: &mut Account,
+        additional_fees: U256,
+    ) -> Result<(), InvalidTransaction> {
+        let balance_check = effective_balance_requirement(
+            &self.tx,
+            self.tx.gas_price,
+            additional_fees,
+            SPEC::SPEC_ID,
+        )
+        .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
+
+        // Check if account has enough balance for gas_limit*gas_price, value transfer and the
+        // additional fees. Transfer will be done inside `*_inner` functions.
         if balance_check > account.info.balance {
             if self.cfg.is_balance_check_disabled() {
                 // Add transaction cost to balance to ensure execution doesn't fail.
@@ -297,10 +395,70 @@ pub struct CfgEnv {
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
+    /// If some it will effects EIP-3860: Limit and meter initcode. Initcode is still charged
+    /// per word, for its full size.
+    /// By default it is double of the contract code size limit, 0xC000 (~49kb).
+    pub limit_contract_initcode_size: Option<usize>,
     /// Blob target count. EIP-7840 Add blob schedule to EL config files.
     ///
     /// Note : Items must be sorted by `SpecId`.
     pub blob_target_and_max_count: Vec<(SpecId, u8, u8)>,
+    /// Record where execution halted in [`ExecutionResult::Halt`](crate::ExecutionResult::Halt).
+    /// Adds a small cost to every halting frame, so it is disabled by default.
+    ///
+    /// By default, it is set to `false`.
+    pub capture_halt_context: bool,
+    /// Warm the precompile addresses at the start of the transaction, as specified by EIP-2929.
+    /// Chains that keep precompiles cold can disable it.
+    ///
+    /// By default, it is set to `true`.
+    pub warm_precompiles: bool,
+    /// Max refund quotient, refunds are limited to `1 / refund_quotient` of the gas spent by the
+    /// transaction. A quotient of zero disables refunds.
+    ///
+    /// By default, it is set to `None`, which uses the quotient of the spec, 5 from London
+    /// (EIP-3529) and 2 before.
+    pub refund_quotient: Option<u64>,
+    /// Address receiving the priority fees instead of the block coinbase, used by MEV simulations
+    /// and chains paying fees elsewhere. It doesn't change the `COINBASE` opcode nor which
+    /// address is warm.
+    ///
+    /// By default, it is set to `None`, fees are paid to the block coinbase.
+    pub fee_recipient_override: Option<Address>,
+    /// Keccak-256 implementation used by the `KECCAK256` instruction, CREATE2 and EOFCREATE
+    /// addresses and code hashes of deployed code.
+    ///
+    /// By default, it is set to [`EnvKeccak::Native`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub keccak: EnvKeccak,
+    /// Maximum number of words on the stack of every frame, for non-standard VMs.
+    ///
+    /// By default, it is set to `None`, which uses the limit of 1024 words of Ethereum.
+    pub stack_limit: Option<usize>,
+    /// Maximum size in bytes of the output of a call frame, and of the revert data of a create
+    /// frame, beyond which [`Self::return_data_limit`] applies. Bounds the return data a
+    /// malicious child can hand to its parent, which is otherwise only bounded by memory gas. The
+    /// output of the transaction itself is not limited.
+    ///
+    /// By default, it is set to `None`, output isn't limited.
+    pub max_return_data_size: Option<usize>,
+    /// How output larger than [`Self::max_return_data_size`] is handled.
+    ///
+    /// By default, it is set to [`ReturnDataLimit::Halt`].
+    pub return_data_limit: ReturnDataLimit,
+    /// Discard the output of the transaction, the [`ExecutionResult`](crate::ExecutionResult)
+    /// has an empty output or revert data. Logs, state, gas and the created address are
+    /// unchanged. Useful for simulations only checking the status and gas of many calls, so their
+    /// results don't keep the output alive.
+    ///
+    /// By default, it is set to `false`.
+    pub discard_output: bool,
+    /// Reject blocks of the Merge and later with a non-zero `difficulty`, which is ignored once
+    /// `prevrandao` replaces it. Catches environments of pre-merge blocks executed with a
+    /// post-merge spec, see [`BlockEnv::set_randomness`].
+    ///
+    /// By default, it is set to `false`.
+    pub strict_randomness: bool,
     /// A hard memory limit in bytes beyond which [crate::result::OutOfGasError::Memory] cannot be resized.
     ///
     /// In cases where the gas limit may be extraordinarily high, it is recommended to set this to
@@ -321,6 +479,29 @@ pub struct CfgEnv {
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_eip3607")]
     pub disable_eip3607: bool,
+    /// Senders exempt from EIP-3607, the check is still applied to all other senders.
+    /// Has no effect if [`Self::disable_eip3607`] is set.
+    /// By default, it is empty.
+    #[cfg(feature = "optional_eip3607")]
+    pub eip3607_allowlist: HashSet<Address>,
+    /// Skips the transaction chain ID check. Useful for simulating transactions signed for
+    /// another chain.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optional_chain_id_check")]
+    pub disable_chain_id_check: bool,
+    /// Skips the EIP-4844 max blobs per transaction check.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optional_blob_count_check")]
+    pub disable_blob_count_check: bool,
+    /// Skips the KZG proof verification of the point evaluation precompile, for simulating
+    /// EIP-4844 transactions with only their versioned hashes and no blobs or proofs.
+    ///
+    /// Transactions are validated the same, including the versioned hash version and the blob
+    /// fee, the point evaluation precompile still checks its input length and that the
+    /// commitment matches the versioned hash. This is for simulation only, any proof is accepted.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optional_blob_validation")]
+    pub skip_blob_validation: bool,
     /// Disables all gas refunds. This is useful when using chains that have gas refunds disabled e.g. Avalanche.
     /// Reasoning behind removing gas refunds can be found in EIP-3298.
     /// By default, it is set to `false`.
@@ -328,13 +509,28 @@ pub struct CfgEnv {
     pub disable_gas_refund: bool,
     /// Disables base fee checks for EIP-1559 transactions.
     /// This is useful for testing method calls with zero gas price.
+    /// Same as setting `base_fee_check_mode` to [`BaseFeeCheckMode::Disabled`].
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_no_base_fee")]
     pub disable_base_fee: bool,
+    /// How transactions with a gas price below the basefee are handled.
+    /// By default, it is set to [`BaseFeeCheckMode::Strict`].
+    #[cfg(feature = "optional_no_base_fee")]
+    pub base_fee_check_mode: BaseFeeCheckMode,
     /// Disables the payout of the reward to the beneficiary.
     /// By default, it is set to `false`.
     #[cfg(feature = "optional_beneficiary_reward")]
     pub disable_beneficiary_reward: bool,
+    /// Validator of OP interop executing messages emitted by the transaction.
+    /// By default, all executing messages are accepted.
+    #[cfg(feature = "optimism")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub interop_validator: EnvInteropValidator,
+    /// Build a synthetic enveloped transaction for non-deposit transactions without one,
+    /// instead of rejecting them. Useful for simulating transactions that aren't signed.
+    /// By default, it is set to `false`.
+    #[cfg(feature = "optimism")]
+    pub synthesize_missing_envelope: bool,
 }
 
 impl CfgEnv {
@@ -344,6 +540,18 @@ impl CfgEnv {
         self.limit_contract_code_size.unwrap_or(MAX_CODE_SIZE)
     }
 
+    /// Returns max initcode size from [`Self::limit_contract_initcode_size`] if set,
+    /// otherwise double of [`Self::limit_contract_code_size`] if set,
+    /// or default [`MAX_INITCODE_SIZE`] value.
+    pub fn max_initcode_size(&self) -> usize {
+        self.limit_contract_initcode_size
+            .or_else(|| {
+                self.limit_contract_code_size
+                    .map(|limit| limit.saturating_mul(2))
+            })
+            .unwrap_or(MAX_INITCODE_SIZE)
+    }
+
     pub fn with_chain_id(mut self, chain_id: u64) -> Self {
         self.chain_id = chain_id;
         self
@@ -370,6 +578,20 @@ impl CfgEnv {
             .unwrap_or(6)
     }
 
+    /// Returns the maximum number of blobs a single transaction can carry in the given spec.
+    ///
+    /// This is the block limit of [`CfgEnv::blob_max_count`], capped from Osaka on at
+    /// [`MAX_BLOBS_PER_TX_OSAKA`].
+    #[inline]
+    pub fn blob_max_count_per_tx(&self, spec_id: SpecId) -> u8 {
+        let max = self.blob_max_count(spec_id);
+        if spec_id.is_enabled_in(SpecId::OSAKA) {
+            max.min(MAX_BLOBS_PER_TX_OSAKA)
+        } else {
+            max
+        }
+    }
+
     #[cfg(feature = "optional_eip3607")]
     pub fn is_eip3607_disabled(&self) -> bool {
         self.disable_eip3607
@@ -380,6 +602,47 @@ impl CfgEnv {
         false
     }
 
+    /// Returns `true` if EIP-3607 is disabled, or if `caller` is in [`Self::eip3607_allowlist`].
+    #[cfg(feature = "optional_eip3607")]
+    pub fn is_eip3607_disabled_for(&self, caller: Address) -> bool {
+        self.disable_eip3607 || self.eip3607_allowlist.contains(&caller)
+    }
+
+    #[cfg(not(feature = "optional_eip3607"))]
+    pub fn is_eip3607_disabled_for(&self, _caller: Address) -> bool {
+        false
+    }
+
+    #[cfg(feature = "optional_chain_id_check")]
+    pub fn is_chain_id_check_disabled(&self) -> bool {
+        self.disable_chain_id_check
+    }
+
+    #[cfg(not(feature = "optional_chain_id_check"))]
+    pub fn is_chain_id_check_disabled(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "optional_blob_count_check")]
+    pub fn is_blob_count_check_disabled(&self) -> bool {
+        self.disable_blob_count_check
+    }
+
+    #[cfg(not(feature = "optional_blob_count_check"))]
+    pub fn is_blob_count_check_disabled(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "optional_blob_validation")]
+    pub fn is_blob_validation_skipped(&self) -> bool {
+        self.skip_blob_validation
+    }
+
+    #[cfg(not(feature = "optional_blob_validation"))]
+    pub fn is_blob_validation_skipped(&self) -> bool {
+        false
+    }
+
     #[cfg(feature = "optional_balance_check")]
     pub fn is_balance_check_disabled(&self) -> bool {
         self.disable_balance_check
@@ -400,14 +663,24 @@ impl CfgEnv {
         false
     }
 
+    /// Returns the configured `base_fee_check_mode`, or [`BaseFeeCheckMode::Disabled`] if
+    /// `disable_base_fee` is set.
     #[cfg(feature = "optional_no_base_fee")]
-    pub fn is_base_fee_check_disabled(&self) -> bool {
-        self.disable_base_fee
+    pub fn base_fee_check_mode(&self) -> BaseFeeCheckMode {
+        if self.disable_base_fee {
+            BaseFeeCheckMode::Disabled
+        } else {
+            self.base_fee_check_mode
+        }
     }
 
     #[cfg(not(feature = "optional_no_base_fee"))]
+    pub fn base_fee_check_mode(&self) -> BaseFeeCheckMode {
+        BaseFeeCheckMode::Strict
+    }
+
     pub fn is_base_fee_check_disabled(&self) -> bool {
-        false
+        self.base_fee_check_mode() != BaseFeeCheckMode::Strict
     }
 
     #[cfg(feature = "optional_block_gas_limit")]
@@ -437,7 +710,18 @@ impl Default for CfgEnv {
             chain_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
+            limit_contract_initcode_size: None,
             blob_target_and_max_count: vec![(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)],
+            capture_halt_context: false,
+            warm_precompiles: true,
+            refund_quotient: None,
+            fee_recipient_override: None,
+            keccak: EnvKeccak::Native,
+            stack_limit: None,
+            max_return_data_size: None,
+            return_data_limit: ReturnDataLimit::Halt,
+            discard_output: false,
+            strict_randomness: false,
             #[cfg(any(feature = "c-kzg", feature = "kzg-rs"))]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
             #[cfg(feature = "memory_limit")]
@@ -448,12 +732,26 @@ impl Default for CfgEnv {
             disable_block_gas_limit: false,
             #[cfg(feature = "optional_eip3607")]
             disable_eip3607: false,
+            #[cfg(feature = "optional_eip3607")]
+            eip3607_allowlist: HashSet::default(),
+            #[cfg(feature = "optional_chain_id_check")]
+            disable_chain_id_check: false,
+            #[cfg(feature = "optional_blob_count_check")]
+            disable_blob_count_check: false,
+            #[cfg(feature = "optional_blob_validation")]
+            skip_blob_validation: false,
             #[cfg(feature = "optional_gas_refund")]
             disable_gas_refund: false,
             #[cfg(feature = "optional_no_base_fee")]
             disable_base_fee: false,
+            #[cfg(feature = "optional_no_base_fee")]
+            base_fee_check_mode: BaseFeeCheckMode::Strict,
             #[cfg(feature = "optional_beneficiary_reward")]
             disable_beneficiary_reward: false,
+            #[cfg(feature = "optimism")]
+            interop_validator: EnvInteropValidator::Permissive,
+            #[cfg(feature = "optimism")]
+            synthesize_missing_envelope: false,
         }
     }
 }
@@ -479,7 +777,8 @@ pub struct BlockEnv {
     pub basefee: U256,
     /// The difficulty of the block.
     ///
-    /// Unused after the Paris (AKA the merge) upgrade, and replaced by `prevrandao`.
+    /// Unused after the Paris (AKA the merge) upgrade, and replaced by `prevrandao`, see
+    /// [`BlockEnv::set_randomness`].
     pub difficulty: U256,
     /// The output of the randomness beacon provided by the beacon chain.
     ///
@@ -507,6 +806,37 @@ impl BlockEnv {
             Some(BlobExcessGasAndPrice::new(excess_blob_gas, is_prague));
     }
 
+    /// Sets the randomness of the block at the given spec, the `prevrandao` from the Merge
+    /// ([EIP-4399]) and the `difficulty` before.
+    ///
+    /// The other field is cleared, `difficulty` is zero after the Merge and `prevrandao` is
+    /// unset before.
+    ///
+    /// [EIP-4399]: https://eips.ethereum.org/EIPS/eip-4399
+    pub fn set_randomness(&mut self, spec_id: SpecId, value: B256) {
+        if spec_id.is_enabled_in(SpecId::MERGE) {
+            self.prevrandao = Some(value);
+            self.difficulty = U256::ZERO;
+        } else {
+            self.difficulty = U256::from_be_bytes(value.0);
+            self.prevrandao = None;
+        }
+    }
+
+    /// Returns the randomness of the block at the given spec, pushed by the `DIFFICULTY`
+    /// instruction, renamed `PREVRANDAO` from the Merge.
+    ///
+    /// Returns `None` if the Merge is enabled and `prevrandao` is not set. This is enforced in
+    /// [`Env::validate_block_env`].
+    #[inline]
+    pub fn randomness(&self, spec_id: SpecId) -> Option<B256> {
+        if spec_id.is_enabled_in(SpecId::MERGE) {
+            self.prevrandao
+        } else {
+            Some(B256::from(self.difficulty.to_be_bytes()))
+        }
+    }
+
     /// See [EIP-4844] and [`crate::calc_blob_gasprice`].
     ///
     /// Returns `None` if `Cancun` is not enabled. This is enforced in [`Env::validate_block_env`].
@@ -619,6 +949,12 @@ pub struct TxEnv {
     /// [EIP-Set EOA account code for one transaction](https://eips.ethereum.org/EIPS/eip-7702)
     pub authorization_list: Option<AuthorizationList>,
 
+    /// The signature of the transaction.
+    ///
+    /// If set, [`Self::caller`] is recovered from it before the transaction is validated.
+    #[cfg(feature = "secp256k1-recover")]
+    pub signature: Option<TxSignature>,
+
     #[cfg_attr(feature = "serde", serde(flatten))]
     #[cfg(feature = "optimism")]
     /// Optimism fields.
@@ -664,6 +1000,8 @@ impl Default for TxEnv {
             blob_hashes: Vec::new(),
             max_fee_per_blob_gas: None,
             authorization_list: None,
+            #[cfg(feature = "secp256k1-recover")]
+            signature: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -765,6 +1103,35 @@ pub enum CreateScheme {
     },
 }
 
+/// How transactions with a gas price below the basefee are handled.
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BaseFeeCheckMode {
+    /// Reject the transaction with [`InvalidTransaction::GasPriceLessThanBasefee`].
+    #[default]
+    Strict,
+    /// Accept the transaction and charge at most its gas price.
+    ///
+    /// The gas price is lowered to what the caller balance allows, see
+    /// [`Env::cap_gas_price_to_balance`], so the caller is never charged more than it signed
+    /// for nor more than its balance.
+    Cap,
+    /// Accept the transaction and charge its gas price.
+    Disabled,
+}
+
+/// How output larger than [`CfgEnv::max_return_data_size`] is handled.
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReturnDataLimit {
+    /// Halt the frame with [`HaltReason::ReturnDataSizeLimit`](crate::HaltReason::ReturnDataSizeLimit), reverting its state changes and
+    /// consuming its gas.
+    #[default]
+    Halt,
+    /// Truncate the output to the limit, the frame otherwise completes as it did.
+    Truncate,
+}
+
 /// What bytecode analysis to perform.
 #[derive(Clone, Default, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -780,6 +1147,50 @@ pub enum AnalysisKind {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_block_randomness() {
+        let randomness = B256::with_last_byte(7);
+        let mut block = BlockEnv::default();
+
+        block.set_randomness(SpecId::LONDON, randomness);
+        assert_eq!(block.difficulty, U256::from(7));
+        assert_eq!(block.prevrandao, None);
+        assert_eq!(block.randomness(SpecId::LONDON), Some(randomness));
+        assert_eq!(block.randomness(SpecId::MERGE), None);
+
+        block.set_randomness(SpecId::MERGE, randomness);
+        assert_eq!(block.difficulty, U256::ZERO);
+        assert_eq!(block.prevrandao, Some(randomness));
+        assert_eq!(block.randomness(SpecId::CANCUN), Some(randomness));
+        assert_eq!(block.randomness(SpecId::LONDON), Some(B256::ZERO));
+    }
+
+    #[test]
+    fn test_validate_block_env_randomness() {
+        let mut env = Env::default();
+        env.block
+            .set_randomness(SpecId::LONDON, B256::with_last_byte(7));
+        assert_eq!(env.validate_block_env::<crate::LondonSpec>(), Ok(()));
+        assert_eq!(
+            env.validate_block_env::<crate::MergeSpec>(),
+            Err(InvalidHeader::PrevrandaoNotSet)
+        );
+
+        // A difficulty set after the merge is only rejected with the strict flag.
+        env.block.prevrandao = Some(B256::ZERO);
+        assert_eq!(env.validate_block_env::<crate::MergeSpec>(), Ok(()));
+        env.cfg.strict_randomness = true;
+        assert_eq!(
+            env.validate_block_env::<crate::MergeSpec>(),
+            Err(InvalidHeader::DifficultyAfterMerge)
+        );
+        assert_eq!(env.validate_block_env::<crate::LondonSpec>(), Ok(()));
+
+        env.block
+            .set_randomness(SpecId::MERGE, B256::with_last_byte(7));
+        assert_eq!(env.validate_block_env::<crate::MergeSpec>(), Ok(()));
+    }
+
     #[test]
     fn test_validate_tx_chain_id() {
         let mut env = Env::default();
@@ -804,6 +1215,244 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_blob_tx() {
+        let mut blob_hash = B256::ZERO;
+        blob_hash[0] = VERSIONED_HASH_VERSION_KZG;
+        let blob_env = |blobs: usize| {
+            let mut env = Env::default();
+            env.tx.transact_to = TxKind::Call(Address::ZERO);
+            env.tx.blob_hashes = vec![blob_hash; blobs];
+            env.tx.max_fee_per_blob_gas = Some(U256::from(1));
+            env
+        };
+        assert_eq!(blob_env(1).validate_tx::<crate::CancunSpec>(), Ok(()));
+
+        // Blob fields before Cancun.
+        let env = blob_env(1);
+        assert_eq!(
+            env.validate_tx::<crate::ShanghaiSpec>(),
+            Err(InvalidTransaction::MaxFeePerBlobGasNotSupported)
+        );
+        let mut env = blob_env(1);
+        env.tx.max_fee_per_blob_gas = None;
+        assert_eq!(
+            env.validate_tx::<crate::ShanghaiSpec>(),
+            Err(InvalidTransaction::BlobVersionedHashesNotSupported)
+        );
+
+        assert_eq!(
+            blob_env(0).validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::EmptyBlobs)
+        );
+
+        let mut env = blob_env(2);
+        env.tx.blob_hashes[1][0] = 0x02;
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobVersionNotSupported)
+        );
+
+        let mut env = blob_env(1);
+        env.block.set_blob_excess_gas_and_price(10_000_000, false);
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::BlobGasPriceGreaterThanMax)
+        );
+
+        // The max blob count depends on the spec.
+        assert_eq!(
+            blob_env(7).validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::TooManyBlobs { have: 7, max: 6 })
+        );
+        assert_eq!(blob_env(7).validate_tx::<crate::PragueSpec>(), Ok(()));
+        assert_eq!(
+            blob_env(10).validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::TooManyBlobs { have: 10, max: 9 })
+        );
+
+        // Osaka caps the blobs of a single transaction below the block limit.
+        assert_eq!(blob_env(6).validate_tx::<crate::OsakaSpec>(), Ok(()));
+        assert_eq!(
+            blob_env(7).validate_tx::<crate::OsakaSpec>(),
+            Err(InvalidTransaction::TooManyBlobs { have: 7, max: 6 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "optional_balance_check",
+        feature = "optional_block_gas_limit",
+        feature = "optional_eip3607",
+        feature = "optional_no_base_fee",
+        feature = "optional_chain_id_check",
+        feature = "optional_blob_count_check",
+    ))]
+    fn optional_checks_are_bypassed_individually() {
+        use crate::{Bytecode, CancunSpec};
+
+        const CALLER: Address = Address::with_last_byte(1);
+
+        type Violate = fn(&mut Env, &mut Account);
+        type Disable = fn(&mut CfgEnv);
+        let cases: [(&str, Violate, Disable); 6] = [
+            (
+                "chain id",
+                |env, _| env.tx.chain_id = Some(2),
+                |cfg| cfg.disable_chain_id_check = true,
+            ),
+            (
+                "block gas limit",
+                |env, _| env.block.gas_limit = U256::from(20_000),
+                |cfg| cfg.disable_block_gas_limit = true,
+            ),
+            (
+                "base fee",
+                |env, _| env.block.basefee = U256::from(1),
+                |cfg| cfg.disable_base_fee = true,
+            ),
+            (
+                "blob count",
+                |env, _| {
+                    let mut hash = B256::ZERO;
+                    hash[0] = VERSIONED_HASH_VERSION_KZG;
+                    env.tx.blob_hashes = vec![hash; 7];
+                    env.tx.max_fee_per_blob_gas = Some(U256::from(1));
+                },
+                |cfg| cfg.disable_blob_count_check = true,
+            ),
+            (
+                "eip-3607",
+                |_, account| account.info.code = Some(Bytecode::new_raw(Bytes::from_static(&[0]))),
+                |cfg| {
+                    cfg.eip3607_allowlist.insert(CALLER);
+                },
+            ),
+            (
+                "balance",
+                |env, _| env.tx.value = U256::from(u64::MAX),
+                |cfg| cfg.disable_balance_check = true,
+            ),
+        ];
+
+        let validate = |violate: Violate, disable: Option<Disable>| {
+            let mut env = Env::default();
+            env.tx.caller = CALLER;
+            env.tx.gas_limit = 21_000;
+            let mut account = Account::default();
+            account.info.balance = U256::from(1_000_000_000);
+            violate(&mut env, &mut account);
+            if let Some(disable) = disable {
+                disable(&mut env.cfg);
+            }
+            env.validate_tx::<CancunSpec>()
+                .and_then(|()| env.validate_tx_against_state::<CancunSpec>(&mut account))
+        };
+
+        for (name, violate, _) in cases {
+            assert!(validate(violate, None).is_err(), "{name}");
+            for (other, _, disable) in cases {
+                assert_eq!(
+                    validate(violate, Some(disable)).is_ok(),
+                    name == other,
+                    "{name} with {other} disabled"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "optional_no_base_fee")]
+    fn base_fee_check_modes() {
+        let env_with = |mode, gas_price: u64, priority_fee: Option<u64>| {
+            let mut env = Env::default();
+            env.cfg.base_fee_check_mode = mode;
+            env.block.basefee = U256::from(10);
+            env.tx.gas_limit = 21_000;
+            env.tx.gas_price = U256::from(gas_price);
+            env.tx.gas_priority_fee = priority_fee.map(U256::from);
+            env
+        };
+
+        // Below basefee, with and without a priority fee.
+        for priority_fee in [None, Some(2)] {
+            let env = env_with(BaseFeeCheckMode::Strict, 5, priority_fee);
+            assert_eq!(
+                env.validate_tx::<crate::LatestSpec>(),
+                Err(InvalidTransaction::GasPriceLessThanBasefee)
+            );
+
+            let env = env_with(BaseFeeCheckMode::Cap, 5, priority_fee);
+            assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+            assert_eq!(env.effective_gas_price(), U256::from(5));
+
+            let env = env_with(BaseFeeCheckMode::Disabled, 5, priority_fee);
+            assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+            assert_eq!(env.effective_gas_price(), U256::from(5));
+        }
+
+        // Above basefee the priority fee applies in every mode.
+        for mode in [
+            BaseFeeCheckMode::Strict,
+            BaseFeeCheckMode::Cap,
+            BaseFeeCheckMode::Disabled,
+        ] {
+            let env = env_with(mode, 20, Some(2));
+            assert_eq!(env.validate_tx::<crate::LatestSpec>(), Ok(()));
+            assert_eq!(env.effective_gas_price(), U256::from(12));
+        }
+
+        // The legacy flag takes precedence.
+        let mut env = env_with(BaseFeeCheckMode::Cap, 5, None);
+        env.cfg.disable_base_fee = true;
+        assert_eq!(env.cfg.base_fee_check_mode(), BaseFeeCheckMode::Disabled);
+        assert_eq!(env.effective_gas_price(), U256::from(5));
+
+        // A capped price is lowered to what the balance allows, never raised.
+        let mut env = env_with(BaseFeeCheckMode::Cap, 5, Some(2));
+        let mut account = Account::default();
+        account.info.balance = U256::from(5 * 21_000);
+        assert_eq!(
+            env.validate_tx_against_state::<crate::LatestSpec>(&mut account),
+            Ok(())
+        );
+        assert_eq!(env.effective_gas_price(), U256::from(5));
+        env.tx.value = U256::from(21_000);
+        assert_eq!(
+            env.validate_tx_against_state::<crate::LatestSpec>(&mut account),
+            Ok(())
+        );
+        assert_eq!(env.effective_gas_price(), U256::from(4));
+        assert_eq!(env.tx.gas_priority_fee, Some(U256::from(2)));
+        account.info.balance = U256::from(21_000);
+        assert_eq!(
+            env.validate_tx_against_state::<crate::LatestSpec>(&mut account),
+            Ok(())
+        );
+        assert_eq!(env.effective_gas_price(), U256::ZERO);
+        assert_eq!(env.tx.gas_priority_fee, Some(U256::ZERO));
+
+        // Strict mode keeps rejecting unaffordable transactions.
+        let mut env = env_with(BaseFeeCheckMode::Strict, 20, None);
+        assert!(matches!(
+            env.validate_tx_against_state::<crate::LatestSpec>(&mut account),
+            Err(InvalidTransaction::LackOfFundForMaxFee { .. })
+        ));
+        assert_eq!(env.effective_gas_price(), U256::from(20));
+    }
+
+    #[test]
+    #[cfg(feature = "optional_eip3607")]
+    fn eip3607_allowlist_only_exempts_listed_senders() {
+        let mut cfg = CfgEnv::default();
+        cfg.eip3607_allowlist.insert(Address::with_last_byte(1));
+        assert!(cfg.is_eip3607_disabled_for(Address::with_last_byte(1)));
+        assert!(!cfg.is_eip3607_disabled_for(Address::with_last_byte(2)));
+
+        cfg.disable_eip3607 = true;
+        assert!(cfg.is_eip3607_disabled_for(Address::with_last_byte(2)));
+    }
+
     #[test]
     fn blob_max_and_target_count() {
         let cfg = CfgEnv::default();
@@ -812,4 +1461,18 @@ mod tests {
         assert_eq!(cfg.blob_max_count(SpecId::PRAGUE), (9));
         assert_eq!(cfg.blob_max_count(SpecId::OSAKA), (9));
     }
+
+    #[test]
+    fn blob_max_count_per_tx() {
+        let mut cfg = CfgEnv::default();
+        cfg.set_blob_max_and_target_count(vec![
+            (SpecId::CANCUN, 3, 6),
+            (SpecId::PRAGUE, 6, 9),
+            (SpecId::OSAKA, 10, 15),
+        ]);
+        assert_eq!(cfg.blob_max_count_per_tx(SpecId::CANCUN), 6);
+        assert_eq!(cfg.blob_max_count_per_tx(SpecId::PRAGUE), 9);
+        assert_eq!(cfg.blob_max_count(SpecId::OSAKA), 15);
+        assert_eq!(cfg.blob_max_count_per_tx(SpecId::OSAKA), 6);
+    }
 }
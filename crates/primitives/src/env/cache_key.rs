@@ -0,0 +1,377 @@
+//! Cache keys of the environment, see [`Env::cache_key`].
+
+use crate::{keccak256, AccessListItem, Address, BlockEnv, CfgEnv, Env, TxEnv, TxKind, B256, U256};
+use std::vec::Vec;
+
+/// Version of the cache key encoding, changed only when the encoding changes.
+const CACHE_KEY_VERSION: u8 = 1;
+
+impl Env {
+    /// Returns a key identifying the environment, for caching execution results.
+    ///
+    /// The key is the keccak-256 hash of a canonical encoding of the configuration, block and
+    /// transaction. Options are encoded with an explicit presence byte and the access list is
+    /// sorted, so equal environments have equal keys regardless of how they were built. The
+    /// encoding is stable across patch releases and for the same set of enabled features.
+    ///
+    /// The spec id is not part of the environment and has to be keyed separately. Configuration
+    /// that is not plain data, the KZG settings, the keccak implementation and the interop
+    /// validator, is not part of the key, nor is the bytecode analysis kind.
+    pub fn cache_key(&self) -> B256 {
+        let mut encoder = CacheKeyEncoder::new(b"env");
+        encoder.cfg(&self.cfg);
+        encoder.b256(self.block.cache_key());
+        encoder.b256(self.tx.cache_key());
+        encoder.finish()
+    }
+}
+
+impl BlockEnv {
+    /// Returns the block component of [`Env::cache_key`].
+    pub fn cache_key(&self) -> B256 {
+        let mut encoder = CacheKeyEncoder::new(b"block");
+        encoder.u256(self.number);
+        encoder.address(self.coinbase);
+        encoder.u256(self.timestamp);
+        encoder.u256(self.gas_limit);
+        encoder.u256(self.basefee);
+        encoder.u256(self.difficulty);
+        encoder.option(self.prevrandao, CacheKeyEncoder::b256);
+        encoder.option(self.blob_excess_gas_and_price.as_ref(), |encoder, blob| {
+            encoder.u64(blob.excess_blob_gas);
+            encoder.u128(blob.blob_gasprice);
+        });
+        encoder.finish()
+    }
+}
+
+impl TxEnv {
+    /// Returns the transaction component of [`Env::cache_key`].
+    ///
+    /// Signed and recovered authorizations are keyed by their recovered authority, an
+    /// authorization list is keyed the same whether it was recovered or not.
+    pub fn cache_key(&self) -> B256 {
+        let mut encoder = CacheKeyEncoder::new(b"tx");
+        encoder.address(self.caller);
+        encoder.u64(self.gas_limit);
+        encoder.u256(self.gas_price);
+        match self.transact_to {
+            TxKind::Create => encoder.u8(0),
+            TxKind::Call(address) => {
+                encoder.u8(1);
+                encoder.address(address);
+            }
+        }
+        encoder.u256(self.value);
+        encoder.bytes(&self.data);
+        encoder.option(self.nonce, CacheKeyEncoder::u64);
+        encoder.option(self.chain_id, CacheKeyEncoder::u64);
+
+        let mut access_list: Vec<AccessListItem> = self.access_list.clone();
+        for item in &mut access_list {
+            item.storage_keys.sort_unstable();
+        }
+        access_list.sort_unstable_by(|a, b| {
+            (a.address, &a.storage_keys).cmp(&(b.address, &b.storage_keys))
+        });
+        encoder.usize(access_list.len());
+        for item in &access_list {
+            encoder.address(item.address);
+            encoder.usize(item.storage_keys.len());
+            for key in &item.storage_keys {
+                encoder.b256(*key);
+            }
+        }
+
+        encoder.option(self.gas_priority_fee, CacheKeyEncoder::u256);
+        encoder.usize(self.blob_hashes.len());
+        for hash in &self.blob_hashes {
+            encoder.b256(*hash);
+        }
+        encoder.option(self.max_fee_per_blob_gas, CacheKeyEncoder::u256);
+        encoder.option(self.authorization_list.as_ref(), |encoder, list| {
+            encoder.usize(list.len());
+            for authorization in list.recovered_iter() {
+                encoder.u256(*authorization.chain_id());
+                encoder.address(*authorization.address());
+                encoder.u64(authorization.nonce());
+                encoder.option(authorization.authority(), CacheKeyEncoder::address);
+            }
+        });
+        #[cfg(feature = "secp256k1-recover")]
+        encoder.option(self.signature, |encoder, signature| {
+            encoder.u256(signature.r);
+            encoder.u256(signature.s);
+            encoder.u64(signature.v);
+            encoder.b256(signature.signing_hash);
+        });
+
+        #[cfg(feature = "optimism")]
+        {
+            let optimism = &self.optimism;
+            encoder.option(optimism.source_hash, CacheKeyEncoder::b256);
+            encoder.option(optimism.mint, CacheKeyEncoder::u128);
+            encoder.option(optimism.is_system_transaction, CacheKeyEncoder::bool);
+            encoder.option(optimism.enveloped_tx.as_ref(), |encoder, tx| {
+                encoder.bytes(tx)
+            });
+        }
+        encoder.finish()
+    }
+}
+
+/// Canonical encoding hashed into a cache key.
+///
+/// Integers are big-endian with a fixed width and variable length data is prefixed with its
+/// length, so no two different values share an encoding.
+struct CacheKeyEncoder {
+    buffer: Vec<u8>,
+}
+
+impl CacheKeyEncoder {
+    fn new(domain: &[u8]) -> Self {
+        let mut encoder = Self { buffer: Vec::new() };
+        encoder.u8(CACHE_KEY_VERSION);
+        encoder.bytes(domain);
+        encoder
+    }
+
+    fn finish(self) -> B256 {
+        keccak256(&self.buffer)
+    }
+
+    fn cfg(&mut self, cfg: &CfgEnv) {
+        self.u64(cfg.chain_id);
+        self.option(cfg.limit_contract_code_size, Self::usize);
+        self.option(cfg.limit_contract_initcode_size, Self::usize);
+        self.usize(cfg.blob_target_and_max_count.len());
+        for (spec_id, target, max) in &cfg.blob_target_and_max_count {
+            self.u8(*spec_id as u8);
+            self.u8(*target);
+            self.u8(*max);
+        }
+        self.bool(cfg.capture_halt_context);
+        self.bool(cfg.warm_precompiles);
+        self.option(cfg.refund_quotient, Self::u64);
+        self.option(cfg.fee_recipient_override, Self::address);
+        self.option(cfg.stack_limit, Self::usize);
+        self.option(cfg.max_return_data_size, Self::usize);
+        self.u8(cfg.return_data_limit as u8);
+        self.bool(cfg.discard_output);
+        self.bool(cfg.strict_randomness);
+        #[cfg(feature = "memory_limit")]
+        self.u64(cfg.memory_limit);
+        #[cfg(feature = "optional_balance_check")]
+        self.bool(cfg.disable_balance_check);
+        #[cfg(feature = "optional_block_gas_limit")]
+        self.bool(cfg.disable_block_gas_limit);
+        #[cfg(feature = "optional_eip3607")]
+        {
+            self.bool(cfg.disable_eip3607);
+            let mut allowlist: Vec<Address> = cfg.eip3607_allowlist.iter().copied().collect();
+            allowlist.sort_unstable();
+            self.usize(allowlist.len());
+            for address in allowlist {
+                self.address(address);
+            }
+        }
+        #[cfg(feature = "optional_chain_id_check")]
+        self.bool(cfg.disable_chain_id_check);
+        #[cfg(feature = "optional_blob_count_check")]
+        self.bool(cfg.disable_blob_count_check);
+        #[cfg(feature = "optional_blob_validation")]
+        self.bool(cfg.skip_blob_validation);
+        #[cfg(feature = "optional_gas_refund")]
+        self.bool(cfg.disable_gas_refund);
+        #[cfg(feature = "optional_no_base_fee")]
+        {
+            self.bool(cfg.disable_base_fee);
+            self.u8(cfg.base_fee_check_mode as u8);
+        }
+        #[cfg(feature = "optional_beneficiary_reward")]
+        self.bool(cfg.disable_beneficiary_reward);
+        #[cfg(feature = "optimism")]
+        self.bool(cfg.synthesize_missing_envelope);
+    }
+
+    fn option<T>(&mut self, value: Option<T>, encode: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                encode(self, value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn u128(&mut self, value: u128) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn u256(&mut self, value: U256) {
+        self.buffer.extend_from_slice(&value.to_be_bytes::<32>());
+    }
+
+    fn address(&mut self, value: Address) {
+        self.buffer.extend_from_slice(value.as_slice());
+    }
+
+    fn b256(&mut self, value: B256) {
+        self.buffer.extend_from_slice(value.as_slice());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.usize(value.len());
+        self.buffer.extend_from_slice(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Authorization, AuthorizationList, BlobExcessGasAndPrice, Bytes, RecoveredAuthority,
+        RecoveredAuthorization, ReturnDataLimit, SpecId,
+    };
+    use std::{boxed::Box, vec};
+
+    #[test]
+    fn equal_envs_have_equal_keys() {
+        let env = |storage_keys: Vec<B256>| {
+            let mut env = Env::default();
+            env.tx.access_list = vec![
+                AccessListItem {
+                    address: Address::with_last_byte(2),
+                    storage_keys,
+                },
+                AccessListItem {
+                    address: Address::with_last_byte(1),
+                    storage_keys: Vec::new(),
+                },
+            ];
+            env
+        };
+        let keys = vec![B256::with_last_byte(1), B256::with_last_byte(2)];
+        let key = env(keys.clone()).cache_key();
+        assert_eq!(env(keys.clone()).cache_key(), key);
+
+        // The order of the access list doesn't matter.
+        let mut reversed = keys.clone();
+        reversed.reverse();
+        assert_eq!(env(reversed).cache_key(), key);
+        let mut reordered = env(keys);
+        reordered.tx.access_list.reverse();
+        assert_eq!(reordered.cache_key(), key);
+    }
+
+    #[test]
+    fn field_changes_change_key() {
+        type Change = Box<dyn Fn(&mut Env)>;
+
+        #[allow(unused_mut)]
+        let mut changes: Vec<Change> = vec![
+            Box::new(|env| env.cfg.chain_id = 10),
+            Box::new(|env| env.cfg.limit_contract_code_size = Some(0)),
+            Box::new(|env| env.cfg.limit_contract_initcode_size = Some(0)),
+            Box::new(|env| {
+                env.cfg
+                    .blob_target_and_max_count
+                    .push((SpecId::OSAKA, 6, 9))
+            }),
+            Box::new(|env| env.cfg.capture_halt_context = true),
+            Box::new(|env| env.cfg.warm_precompiles = false),
+            Box::new(|env| env.cfg.refund_quotient = Some(0)),
+            Box::new(|env| env.cfg.fee_recipient_override = Some(Address::ZERO)),
+            Box::new(|env| env.cfg.stack_limit = Some(1024)),
+            Box::new(|env| env.cfg.max_return_data_size = Some(0)),
+            Box::new(|env| env.cfg.return_data_limit = ReturnDataLimit::Truncate),
+            Box::new(|env| env.cfg.discard_output = true),
+            Box::new(|env| env.cfg.strict_randomness = true),
+            Box::new(|env| env.block.number = U256::from(1)),
+            Box::new(|env| env.block.coinbase = Address::with_last_byte(1)),
+            Box::new(|env| env.block.timestamp = U256::from(2)),
+            Box::new(|env| env.block.gas_limit = U256::from(3)),
+            Box::new(|env| env.block.basefee = U256::from(4)),
+            Box::new(|env| env.block.difficulty = U256::from(5)),
+            Box::new(|env| env.block.prevrandao = None),
+            Box::new(|env| env.block.blob_excess_gas_and_price = None),
+            Box::new(|env| {
+                env.block.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                    excess_blob_gas: 0,
+                    blob_gasprice: 2,
+                })
+            }),
+            Box::new(|env| env.tx.caller = Address::with_last_byte(1)),
+            Box::new(|env| env.tx.gas_limit = 1),
+            Box::new(|env| env.tx.gas_price = U256::from(1)),
+            Box::new(|env| env.tx.transact_to = TxKind::Create),
+            Box::new(|env| env.tx.value = U256::from(1)),
+            Box::new(|env| env.tx.data = Bytes::from_static(&[0])),
+            Box::new(|env| env.tx.nonce = Some(0)),
+            Box::new(|env| env.tx.chain_id = Some(1)),
+            Box::new(|env| env.tx.access_list.push(AccessListItem::default())),
+            Box::new(|env| env.tx.gas_priority_fee = Some(U256::ZERO)),
+            Box::new(|env| env.tx.blob_hashes.push(B256::ZERO)),
+            Box::new(|env| env.tx.max_fee_per_blob_gas = Some(U256::ZERO)),
+            Box::new(|env| env.tx.authorization_list = Some(AuthorizationList::empty())),
+            Box::new(|env| {
+                env.tx.authorization_list = Some(AuthorizationList::Recovered(vec![
+                    RecoveredAuthorization::new_unchecked(
+                        Authorization {
+                            chain_id: U256::from(1),
+                            address: Address::ZERO,
+                            nonce: 0,
+                        },
+                        RecoveredAuthority::Invalid,
+                    ),
+                ]))
+            }),
+        ];
+        #[cfg(feature = "secp256k1-recover")]
+        changes.push(Box::new(|env| {
+            env.tx.signature = Some(crate::TxSignature {
+                r: U256::ZERO,
+                s: U256::ZERO,
+                v: 0,
+                signing_hash: B256::ZERO,
+            })
+        }));
+        #[cfg(feature = "optimism")]
+        changes.extend::<[Box<dyn Fn(&mut Env)>; 5]>([
+            Box::new(|env| env.cfg.synthesize_missing_envelope = true),
+            Box::new(|env| env.tx.optimism.source_hash = Some(B256::ZERO)),
+            Box::new(|env| env.tx.optimism.mint = Some(0)),
+            Box::new(|env| env.tx.optimism.is_system_transaction = Some(false)),
+            Box::new(|env| env.tx.optimism.enveloped_tx = Some(Bytes::new())),
+        ]);
+
+        let base = Env::default();
+        let mut keys = vec![base.cache_key()];
+        for change in &changes {
+            let mut env = base.clone();
+            change(&mut env);
+            assert_ne!(env, base);
+            keys.push(env.cache_key());
+        }
+        let len = keys.len();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), len, "a field change did not change the key");
+    }
+}
@@ -0,0 +1,264 @@
+//! Transaction cost arithmetic shared by the mainnet and Optimism handlers.
+//!
+//! Validation uses the checked helpers, which return `None` instead of overflowing, so an
+//! unpayable transaction is rejected with
+//! [`OverflowPaymentInTransaction`](crate::InvalidTransaction::OverflowPaymentInTransaction).
+//! The charge of a validated transaction can't overflow, [`tx_fee_charge`] saturates so it can't
+//! panic either when the balance check is disabled. None of the helpers panic.
+//!
+//! Gas is converted to and from [`U256`] fee operands with [`gas_to_u256`] and [`u256_to_gas`],
+//! the fees charged after execution are computed with [`gas_fee`], which fail with an
+//! [`ArithmeticError`] instead of truncating or wrapping.
+
+use crate::{ArithmeticError, SpecId, TxEnv, U256};
+
+/// Converts an amount of gas to a [`U256`] fee operand, losslessly.
+#[inline]
+pub const fn gas_to_u256(gas: u64) -> U256 {
+    U256::from_limbs([gas, 0, 0, 0])
+}
+
+/// Converts a [`U256`] to an amount of gas.
+///
+/// Returns [`ArithmeticError::GasOverflow`] if the value doesn't fit in a `u64`.
+#[inline]
+pub fn u256_to_gas(value: U256) -> Result<u64, ArithmeticError> {
+    u64::try_from(value).map_err(|_| ArithmeticError::GasOverflow { value })
+}
+
+/// Converts a gas refund to an amount of gas.
+///
+/// Returns [`ArithmeticError::NegativeGas`] if the refund is negative.
+#[inline]
+pub fn refund_to_gas(refund: i64) -> Result<u64, ArithmeticError> {
+    u64::try_from(refund).map_err(|_| ArithmeticError::NegativeGas { value: refund })
+}
+
+/// Returns the fee of `gas` at `price` per gas.
+///
+/// Returns [`ArithmeticError::FeeOverflow`] if the fee overflows [`U256`].
+#[inline]
+pub fn gas_fee(price: U256, gas: u64) -> Result<U256, ArithmeticError> {
+    price
+        .checked_mul(gas_to_u256(gas))
+        .ok_or(ArithmeticError::FeeOverflow { price, gas })
+}
+
+/// Returns the maximum cost of the transaction,
+/// `gas_limit * gas_price + value + max_fee_per_blob_gas * total_blob_gas`.
+///
+/// The blob fee is only added from Cancun. Returns `None` if the cost overflows [`U256`].
+#[inline]
+pub fn tx_max_cost(tx: &TxEnv, spec: SpecId) -> Option<U256> {
+    effective_balance_requirement(tx, tx.gas_price, U256::ZERO, spec)
+}
+
+/// Returns the balance the caller needs to send the transaction, the [`tx_max_cost`] with
+/// `gas_price` charged per gas instead of the price of the transaction, plus `additional_fees`
+/// such as the Optimism L1 and operator fees.
+///
+/// Returns `None` if the requirement overflows [`U256`].
+#[inline]
+pub fn effective_balance_requirement(
+    tx: &TxEnv,
+    gas_price: U256,
+    additional_fees: U256,
+    spec: SpecId,
+) -> Option<U256> {
+    let mut requirement = gas_to_u256(tx.gas_limit)
+        .checked_mul(gas_price)?
+        .checked_add(tx.value)?
+        .checked_add(additional_fees)?;
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        // Zero if the transaction is not a blob transaction.
+        let max_data_fee = tx
+            .max_fee_per_blob_gas
+            .unwrap_or_default()
+            .checked_mul(U256::from(tx.get_total_blob_gas()))?;
+        requirement = requirement.checked_add(max_data_fee)?;
+    }
+    Some(requirement)
+}
+
+/// Returns the fees charged to the caller before execution,
+/// `gas_limit * effective_gas_price + data_fee`.
+///
+/// Saturates at [`U256::MAX`], which only happens for transactions that passed validation with
+/// the balance check disabled.
+#[inline]
+pub fn tx_fee_charge(gas_limit: u64, effective_gas_price: U256, data_fee: U256) -> U256 {
+    gas_to_u256(gas_limit)
+        .saturating_mul(effective_gas_price)
+        .saturating_add(data_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{B256, GAS_PER_BLOB};
+    use alloy_primitives::U512;
+    use proptest::prelude::*;
+    use std::vec;
+
+    fn u256() -> impl Strategy<Value = U256> {
+        prop_oneof![
+            any::<[u8; 32]>().prop_map(U256::from_be_bytes),
+            any::<u64>().prop_map(U256::from),
+            Just(U256::MAX),
+            Just(U256::ZERO),
+        ]
+    }
+
+    /// Gas amounts biased towards `u64::MAX`.
+    fn gas() -> impl Strategy<Value = u64> {
+        prop_oneof![any::<u64>(), u64::MAX - 1_000_000..=u64::MAX]
+    }
+
+    fn wide(value: U256) -> U512 {
+        U512::from(value)
+    }
+
+    /// Reference requirement computed in 512 bits, which can't overflow.
+    fn reference(tx: &TxEnv, gas_price: U256, additional_fees: U256, spec: SpecId) -> U512 {
+        let mut requirement = wide(U256::from(tx.gas_limit)) * wide(gas_price)
+            + wide(tx.value)
+            + wide(additional_fees);
+        if spec.is_enabled_in(SpecId::CANCUN) {
+            requirement += wide(tx.max_fee_per_blob_gas.unwrap_or_default())
+                * wide(U256::from(tx.get_total_blob_gas()));
+        }
+        requirement
+    }
+
+    fn tx(gas_limit: u64, gas_price: U256, value: U256, blobs: usize, blob_fee: U256) -> TxEnv {
+        TxEnv {
+            gas_limit,
+            gas_price,
+            value,
+            blob_hashes: vec![B256::ZERO; blobs],
+            max_fee_per_blob_gas: Some(blob_fee),
+            ..Default::default()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn requirement_matches_reference(
+            gas_limit in any::<u64>(),
+            gas_price in u256(),
+            value in u256(),
+            additional_fees in u256(),
+            blobs in 0usize..8,
+            blob_fee in u256(),
+            cancun in any::<bool>(),
+        ) {
+            let tx = tx(gas_limit, gas_price, value, blobs, blob_fee);
+            let spec = if cancun { SpecId::CANCUN } else { SpecId::SHANGHAI };
+
+            let expected = reference(&tx, gas_price, additional_fees, spec);
+            let expected = (expected <= wide(U256::MAX)).then(|| U256::from(expected));
+            prop_assert_eq!(
+                effective_balance_requirement(&tx, gas_price, additional_fees, spec),
+                expected
+            );
+            prop_assert_eq!(
+                tx_max_cost(&tx, spec),
+                effective_balance_requirement(&tx, tx.gas_price, U256::ZERO, spec)
+            );
+        }
+
+        #[test]
+        fn fee_charge_saturates(
+            gas_limit in any::<u64>(),
+            gas_price in u256(),
+            data_fee in u256(),
+        ) {
+            let expected = wide(U256::from(gas_limit)) * wide(gas_price) + wide(data_fee);
+            let expected = if expected > wide(U256::MAX) {
+                U256::MAX
+            } else {
+                U256::from(expected)
+            };
+            prop_assert_eq!(tx_fee_charge(gas_limit, gas_price, data_fee), expected);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn gas_conversions_round_trip(gas in gas(), high in 1..=u64::MAX) {
+            prop_assert_eq!(u256_to_gas(gas_to_u256(gas)), Ok(gas));
+            prop_assert_eq!(gas_to_u256(gas), U256::from(gas));
+
+            // Any value with a bit above the 64th is rejected instead of truncated.
+            let value = gas_to_u256(gas) + (U256::from(high) << 64);
+            prop_assert_eq!(u256_to_gas(value), Err(ArithmeticError::GasOverflow { value }));
+        }
+
+        #[test]
+        fn gas_fee_matches_reference(price in u256(), gas in gas()) {
+            let expected = wide(price) * wide(U256::from(gas));
+            let expected = if expected > wide(U256::MAX) {
+                Err(ArithmeticError::FeeOverflow { price, gas })
+            } else {
+                Ok(U256::from(expected))
+            };
+            prop_assert_eq!(gas_fee(price, gas), expected);
+        }
+
+        #[test]
+        fn refund_to_gas_rejects_negative(refund in any::<i64>()) {
+            let expected = if refund < 0 {
+                Err(ArithmeticError::NegativeGas { value: refund })
+            } else {
+                Ok(refund as u64)
+            };
+            prop_assert_eq!(refund_to_gas(refund), expected);
+        }
+    }
+
+    #[test]
+    fn gas_fee_boundaries() {
+        let max_gas = U256::from(u64::MAX);
+        assert_eq!(gas_fee(U256::MAX, 0), Ok(U256::ZERO));
+        assert_eq!(gas_fee(U256::MAX, 1), Ok(U256::MAX));
+        assert_eq!(
+            gas_fee(U256::MAX / max_gas, u64::MAX),
+            Ok(U256::MAX / max_gas * max_gas)
+        );
+        let price = U256::MAX / max_gas + U256::from(1);
+        assert_eq!(
+            gas_fee(price, u64::MAX),
+            Err(ArithmeticError::FeeOverflow {
+                price,
+                gas: u64::MAX
+            })
+        );
+        assert_eq!(
+            u256_to_gas(max_gas + U256::from(1)),
+            Err(ArithmeticError::GasOverflow {
+                value: max_gas + U256::from(1)
+            })
+        );
+    }
+
+    #[test]
+    fn blob_fee_from_cancun() {
+        let tx = tx(10, U256::from(2), U256::from(3), 2, U256::from(1));
+        let blob_gas = U256::from(2 * GAS_PER_BLOB);
+        assert_eq!(tx_max_cost(&tx, SpecId::SHANGHAI), Some(U256::from(23)));
+        assert_eq!(
+            tx_max_cost(&tx, SpecId::CANCUN),
+            Some(U256::from(23) + blob_gas)
+        );
+
+        let overflow = TxEnv {
+            max_fee_per_blob_gas: Some(U256::MAX),
+            ..tx
+        };
+        assert_eq!(tx_max_cost(&overflow, SpecId::CANCUN), None);
+        assert_eq!(
+            tx_max_cost(&overflow, SpecId::SHANGHAI),
+            Some(U256::from(23))
+        );
+    }
+}
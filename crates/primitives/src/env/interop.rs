@@ -0,0 +1,43 @@
+use crate::Log;
+use core::fmt;
+use std::sync::Arc;
+
+/// Validates OP interop executing messages against the set of initiating messages.
+pub trait InteropValidator: fmt::Debug + Send + Sync {
+    /// Returns `true` if the executing message emitted as `log` references a valid
+    /// initiating message.
+    fn validate_executing_message(&self, log: &Log) -> bool;
+}
+
+/// Interop validator carried in the [`CfgEnv`](crate::CfgEnv).
+#[derive(Clone, Debug, Default)]
+pub enum EnvInteropValidator {
+    /// Accepts all executing messages.
+    #[default]
+    Permissive,
+    /// Custom validator.
+    Custom(Arc<dyn InteropValidator>),
+}
+
+impl EnvInteropValidator {
+    /// Returns `true` if the executing message emitted as `log` is valid.
+    #[inline]
+    pub fn validate_executing_message(&self, log: &Log) -> bool {
+        match self {
+            Self::Permissive => true,
+            Self::Custom(validator) => validator.validate_executing_message(log),
+        }
+    }
+}
+
+impl PartialEq for EnvInteropValidator {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Permissive, Self::Permissive) => true,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for EnvInteropValidator {}
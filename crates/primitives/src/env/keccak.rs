@@ -0,0 +1,55 @@
+use crate::{keccak256, Bytecode, B256, KECCAK_EMPTY};
+use core::fmt;
+use std::sync::Arc;
+
+/// Keccak-256 implementation, e.g. backed by hardware acceleration or precomputed hashes.
+pub trait Keccak: fmt::Debug + Send + Sync {
+    /// Returns the Keccak-256 hash of `input`.
+    fn keccak256(&self, input: &[u8]) -> B256;
+}
+
+/// Keccak-256 implementation carried in the [`CfgEnv`](crate::CfgEnv).
+///
+/// Used by the `KECCAK256` instruction, CREATE2 and EOFCREATE address computation and the code
+/// hash of deployed and delegated code.
+#[derive(Clone, Debug, Default)]
+pub enum EnvKeccak {
+    /// [`keccak256`] of `alloy-primitives`.
+    #[default]
+    Native,
+    /// Custom implementation.
+    Custom(Arc<dyn Keccak>),
+}
+
+impl EnvKeccak {
+    /// Returns the Keccak-256 hash of `input`.
+    #[inline]
+    pub fn hash(&self, input: &[u8]) -> B256 {
+        match self {
+            Self::Native => keccak256(input),
+            Self::Custom(keccak) => keccak.keccak256(input),
+        }
+    }
+
+    /// Returns the hash of `bytecode`, same as [`Bytecode::hash_slow`].
+    #[inline]
+    pub fn code_hash(&self, bytecode: &Bytecode) -> B256 {
+        if bytecode.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            self.hash(bytecode.original_byte_slice())
+        }
+    }
+}
+
+impl PartialEq for EnvKeccak {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Native, Self::Native) => true,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for EnvKeccak {}
@@ -0,0 +1,510 @@
+//! Validation and derivation of a [`BlockEnv`] from its parent block.
+
+use crate::{
+    calc_excess_blob_gas, BlobExcessGasAndPrice, BlockEnv, InvalidHeader, SpecId, GAS_PER_BLOB,
+    U256,
+};
+
+/// Base fee of the first London block, see [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+pub const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Parameters of the [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) base fee update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BaseFeeParams {
+    /// Bounds the change of the base fee between blocks to `1 / max_change_denominator`.
+    pub max_change_denominator: u64,
+    /// Ratio of the gas limit to the gas target.
+    pub elasticity_multiplier: u64,
+}
+
+impl Default for BaseFeeParams {
+    fn default() -> Self {
+        Self::ETHEREUM
+    }
+}
+
+impl BaseFeeParams {
+    /// Parameters of Ethereum mainnet.
+    pub const ETHEREUM: Self = Self::new(8, 2);
+
+    /// Parameters of OP mainnet before Canyon.
+    #[cfg(feature = "optimism")]
+    pub const OPTIMISM: Self = Self::new(50, 6);
+
+    /// Parameters of OP mainnet from Canyon.
+    #[cfg(feature = "optimism")]
+    pub const OPTIMISM_CANYON: Self = Self::new(250, 6);
+
+    /// Creates the parameters.
+    pub const fn new(max_change_denominator: u64, elasticity_multiplier: u64) -> Self {
+        Self {
+            max_change_denominator,
+            elasticity_multiplier,
+        }
+    }
+
+    /// Returns the parameters of OP mainnet at the spec.
+    ///
+    /// From Holocene the parameters are set by the parent block, see
+    /// [`Self::from_holocene_extra_data`].
+    #[cfg(feature = "optimism")]
+    pub fn optimism(spec: SpecId) -> Self {
+        if spec.is_enabled_in(SpecId::CANYON) {
+            Self::OPTIMISM_CANYON
+        } else {
+            Self::OPTIMISM
+        }
+    }
+
+    /// Decodes the parameters of the Holocene `extra_data` of a block, a zero version byte
+    /// followed by the big endian `u32` denominator and elasticity.
+    ///
+    /// Zero parameters select `default`, the parameters before Holocene. Returns `None` if the
+    /// extra data is malformed.
+    #[cfg(feature = "optimism")]
+    pub fn from_holocene_extra_data(extra_data: &[u8], default: Self) -> Option<Self> {
+        let [0, params @ ..] = extra_data else {
+            return None;
+        };
+        let params: [u8; 8] = params.try_into().ok()?;
+        let denominator = u32::from_be_bytes(params[..4].try_into().unwrap());
+        let elasticity = u32::from_be_bytes(params[4..].try_into().unwrap());
+        match (denominator, elasticity) {
+            (0, 0) => Some(default),
+            (0, _) => None,
+            _ => Some(Self::new(denominator as u64, elasticity as u64)),
+        }
+    }
+
+    /// Returns the base fee of the block following a block with the given gas usage and base
+    /// fee.
+    pub fn next_block_base_fee(&self, gas_used: u64, gas_limit: u64, base_fee: U256) -> U256 {
+        let gas_target = gas_limit / self.elasticity_multiplier.max(1);
+        if gas_target == 0 || gas_used == gas_target {
+            return base_fee;
+        }
+        let denominator = U256::from(gas_target) * U256::from(self.max_change_denominator.max(1));
+        if gas_used > gas_target {
+            let delta = base_fee * U256::from(gas_used - gas_target) / denominator;
+            base_fee.saturating_add(delta.max(U256::from(1)))
+        } else {
+            let delta = base_fee * U256::from(gas_target - gas_used) / denominator;
+            base_fee.saturating_sub(delta)
+        }
+    }
+}
+
+/// Returns the EIP-4844 blob gas target of a block at the spec.
+pub const fn target_blob_gas_per_block(spec: SpecId) -> u64 {
+    // EIP-7691: Blob throughput increase
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        6 * GAS_PER_BLOB
+    } else {
+        3 * GAS_PER_BLOB
+    }
+}
+
+/// Returns the excess blob gas and blob gas price of the child of a block at the spec, from the
+/// excess blob gas of the block and the number of blobs it included.
+pub fn blob_fee_after(
+    parent_excess_blob_gas: u64,
+    blobs_used: u64,
+    spec: SpecId,
+) -> BlobExcessGasAndPrice {
+    BlobExcessGasAndPrice::from_parent_and_target(
+        parent_excess_blob_gas,
+        blobs_used * GAS_PER_BLOB,
+        target_blob_gas_per_block(spec),
+        spec.is_enabled_in(SpecId::PRAGUE),
+    )
+}
+
+/// Simulates the blob fee market over consecutive blocks at the spec.
+///
+/// Starting from a block with `initial_excess_blob_gas`, yields for every number of blobs of
+/// `blobs_per_block` the excess blob gas and blob gas price of the block following the one
+/// including them, see [`blob_fee_after`].
+pub fn simulate_blob_fees(
+    initial_excess_blob_gas: u64,
+    blobs_per_block: impl IntoIterator<Item = u64>,
+    spec: SpecId,
+) -> impl Iterator<Item = BlobExcessGasAndPrice> {
+    blobs_per_block
+        .into_iter()
+        .scan(initial_excess_blob_gas, move |excess_blob_gas, blobs| {
+            let next = blob_fee_after(*excess_blob_gas, blobs, spec);
+            *excess_blob_gas = next.excess_blob_gas;
+            Some(next)
+        })
+}
+
+/// Header fields of the parent of a block needed to validate or derive its [`BlockEnv`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParentBlockInfo {
+    /// Number of the parent block.
+    pub number: U256,
+    /// Timestamp of the parent block.
+    pub timestamp: U256,
+    /// Gas limit of the parent block.
+    pub gas_limit: u64,
+    /// Gas used by the parent block.
+    pub gas_used: u64,
+    /// Base fee of the parent block, `None` if it is before London.
+    pub basefee: Option<U256>,
+    /// Excess blob gas of the parent block, `None` if it is before Cancun.
+    pub excess_blob_gas: Option<u64>,
+    /// Blob gas used by the parent block.
+    pub blob_gas_used: u64,
+    /// Blob gas target of the parent block, `None` uses the [`target_blob_gas_per_block`] of
+    /// the spec of the block.
+    pub target_blob_gas_per_block: Option<u64>,
+    /// Base fee parameters of the chain for the block.
+    pub base_fee_params: BaseFeeParams,
+}
+
+impl ParentBlockInfo {
+    /// Returns the base fee of the child block at the spec, `None` before London.
+    pub fn next_block_base_fee(&self, spec: SpecId) -> Option<U256> {
+        if !spec.is_enabled_in(SpecId::LONDON) {
+            return None;
+        }
+        Some(match self.basefee {
+            Some(basefee) => {
+                self.base_fee_params
+                    .next_block_base_fee(self.gas_used, self.gas_limit, basefee)
+            }
+            None => U256::from(INITIAL_BASE_FEE),
+        })
+    }
+
+    /// Returns the excess blob gas of the child block at the spec, `None` before Cancun.
+    pub fn next_block_excess_blob_gas(&self, spec: SpecId) -> Option<u64> {
+        if !spec.is_enabled_in(SpecId::CANCUN) {
+            return None;
+        }
+        let Some(excess_blob_gas) = self.excess_blob_gas else {
+            // First Cancun block.
+            return Some(0);
+        };
+        let target = self
+            .target_blob_gas_per_block
+            .unwrap_or(target_blob_gas_per_block(spec));
+        Some(calc_excess_blob_gas(
+            excess_blob_gas,
+            self.blob_gas_used,
+            target,
+        ))
+    }
+}
+
+impl BlockEnv {
+    /// Returns the environment of the child of `parent` at the spec, with the number, base fee
+    /// and excess blob gas derived from the parent.
+    ///
+    /// The timestamp is one second after the parent and the gas limit is the one of the parent,
+    /// other fields are the defaults.
+    pub fn next_from_parent(parent: &ParentBlockInfo, spec: SpecId) -> Self {
+        Self {
+            number: parent.number.saturating_add(U256::from(1)),
+            timestamp: parent.timestamp.saturating_add(U256::from(1)),
+            gas_limit: U256::from(parent.gas_limit),
+            basefee: parent.next_block_base_fee(spec).unwrap_or_default(),
+            blob_excess_gas_and_price: parent.next_block_excess_blob_gas(spec).map(
+                |excess_blob_gas| {
+                    BlobExcessGasAndPrice::new(excess_blob_gas, spec.is_enabled_in(SpecId::PRAGUE))
+                },
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Validates the environment against its parent block at the spec: the number follows the
+    /// parent, the timestamp increases, and the base fee and excess blob gas are the ones
+    /// derived from the parent.
+    ///
+    /// [`Env::validate_block_env`](crate::Env::validate_block_env) checks the consistency of
+    /// the environment on its own.
+    pub fn validate_against_parent(
+        &self,
+        parent: &ParentBlockInfo,
+        spec: SpecId,
+    ) -> Result<(), InvalidHeader> {
+        if Some(self.number) != parent.number.checked_add(U256::from(1)) {
+            return Err(InvalidHeader::NumberNotParentSuccessor);
+        }
+        if self.timestamp <= parent.timestamp {
+            return Err(InvalidHeader::TimestampNotIncreasing);
+        }
+        if let Some(expected) = parent.next_block_base_fee(spec) {
+            if self.basefee != expected {
+                return Err(InvalidHeader::BaseFeeMismatch {
+                    expected,
+                    got: self.basefee,
+                });
+            }
+        }
+        if let Some(expected) = parent.next_block_excess_blob_gas(spec) {
+            let got = self
+                .get_blob_excess_gas()
+                .ok_or(InvalidHeader::ExcessBlobGasNotSet)?;
+            if got != expected {
+                return Err(InvalidHeader::ExcessBlobGasMismatch { expected, got });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN, BLOB_BASE_FEE_UPDATE_FRACTION_ELECTRA};
+    use std::vec::Vec;
+
+    fn parent(gas_used: u64) -> ParentBlockInfo {
+        ParentBlockInfo {
+            number: U256::from(100),
+            timestamp: U256::from(1_000),
+            gas_limit: 30_000_000,
+            gas_used,
+            basefee: Some(U256::from(INITIAL_BASE_FEE)),
+            excess_blob_gas: Some(0),
+            blob_gas_used: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mainnet_base_fee_across_target() {
+        // At the target of 15M gas the base fee is unchanged.
+        let at_target = parent(15_000_000);
+        assert_eq!(
+            at_target.next_block_base_fee(SpecId::CANCUN),
+            Some(U256::from(INITIAL_BASE_FEE))
+        );
+        // A full block raises it by 1/8, an empty one lowers it by 1/8.
+        assert_eq!(
+            parent(30_000_000).next_block_base_fee(SpecId::CANCUN),
+            Some(U256::from(1_125_000_000))
+        );
+        assert_eq!(
+            parent(0).next_block_base_fee(SpecId::CANCUN),
+            Some(U256::from(875_000_000))
+        );
+        // Just above the target it increases by at least one.
+        let mut tiny = parent(15_000_001);
+        tiny.basefee = Some(U256::from(7));
+        assert_eq!(
+            tiny.next_block_base_fee(SpecId::CANCUN),
+            Some(U256::from(8))
+        );
+        let mut tiny = parent(14_999_999);
+        tiny.basefee = Some(U256::from(7));
+        assert_eq!(
+            tiny.next_block_base_fee(SpecId::CANCUN),
+            Some(U256::from(7))
+        );
+
+        // First London block, and before London.
+        let mut pre_london = parent(0);
+        pre_london.basefee = None;
+        assert_eq!(
+            pre_london.next_block_base_fee(SpecId::LONDON),
+            Some(U256::from(INITIAL_BASE_FEE))
+        );
+        assert_eq!(pre_london.next_block_base_fee(SpecId::BERLIN), None);
+    }
+
+    #[test]
+    fn next_block_validates_against_parent() {
+        let mut parent = parent(20_000_000);
+        parent.excess_blob_gas = Some(1_000_000);
+        parent.blob_gas_used = 3 * GAS_PER_BLOB;
+
+        let block = BlockEnv::next_from_parent(&parent, SpecId::PRAGUE);
+        assert_eq!(block.number, U256::from(101));
+        assert_eq!(block.basefee, U256::from(1_041_666_666));
+        // Below the Prague target of 6 blobs.
+        assert_eq!(
+            block.get_blob_excess_gas(),
+            Some(1_000_000 + 3 * GAS_PER_BLOB - 6 * GAS_PER_BLOB)
+        );
+        assert_eq!(
+            block.validate_against_parent(&parent, SpecId::PRAGUE),
+            Ok(())
+        );
+
+        // The Cancun target is 3 blobs.
+        assert_eq!(
+            BlockEnv::next_from_parent(&parent, SpecId::CANCUN).get_blob_excess_gas(),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            block.validate_against_parent(&parent, SpecId::CANCUN),
+            Err(InvalidHeader::ExcessBlobGasMismatch {
+                expected: 1_000_000,
+                got: 1_000_000 - 3 * GAS_PER_BLOB,
+            })
+        );
+
+        let mut wrong = block.clone();
+        wrong.basefee += U256::from(1);
+        assert_eq!(
+            wrong.validate_against_parent(&parent, SpecId::PRAGUE),
+            Err(InvalidHeader::BaseFeeMismatch {
+                expected: block.basefee,
+                got: wrong.basefee,
+            })
+        );
+
+        let mut wrong = block.clone();
+        wrong.timestamp = parent.timestamp;
+        assert_eq!(
+            wrong.validate_against_parent(&parent, SpecId::PRAGUE),
+            Err(InvalidHeader::TimestampNotIncreasing)
+        );
+
+        let mut wrong = block.clone();
+        wrong.number = parent.number;
+        assert_eq!(
+            wrong.validate_against_parent(&parent, SpecId::PRAGUE),
+            Err(InvalidHeader::NumberNotParentSuccessor)
+        );
+
+        let mut wrong = block;
+        wrong.blob_excess_gas_and_price = None;
+        assert_eq!(
+            wrong.validate_against_parent(&parent, SpecId::PRAGUE),
+            Err(InvalidHeader::ExcessBlobGasNotSet)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn optimism_base_fee_params() {
+        let mut parent = parent(30_000_000);
+        parent.base_fee_params = BaseFeeParams::optimism(SpecId::CANYON);
+        // Target of 5M gas, the block is 6 times over it: 1 gwei * 25M / 5M / 250.
+        assert_eq!(
+            parent.next_block_base_fee(SpecId::CANYON),
+            Some(U256::from(1_020_000_000))
+        );
+        parent.base_fee_params = BaseFeeParams::optimism(SpecId::BEDROCK);
+        assert_eq!(
+            parent.next_block_base_fee(SpecId::BEDROCK),
+            Some(U256::from(1_100_000_000))
+        );
+
+        // Holocene parameters of the parent extra data.
+        let extra_data = [0, 0, 0, 0, 100, 0, 0, 0, 3];
+        parent.base_fee_params =
+            BaseFeeParams::from_holocene_extra_data(&extra_data, BaseFeeParams::OPTIMISM_CANYON)
+                .unwrap();
+        assert_eq!(parent.base_fee_params, BaseFeeParams::new(100, 3));
+        // Target of 10M gas, 20M over it: 1 gwei * 20M / 10M / 100.
+        let block = BlockEnv::next_from_parent(&parent, SpecId::HOLOCENE);
+        assert_eq!(block.basefee, U256::from(1_020_000_000));
+        assert_eq!(
+            block.validate_against_parent(&parent, SpecId::HOLOCENE),
+            Ok(())
+        );
+
+        assert_eq!(
+            BaseFeeParams::from_holocene_extra_data(&[0; 9], BaseFeeParams::OPTIMISM_CANYON),
+            Some(BaseFeeParams::OPTIMISM_CANYON)
+        );
+        assert_eq!(
+            BaseFeeParams::from_holocene_extra_data(&[1; 9], BaseFeeParams::OPTIMISM_CANYON),
+            None
+        );
+        assert_eq!(
+            BaseFeeParams::from_holocene_extra_data(&[0; 8], BaseFeeParams::OPTIMISM_CANYON),
+            None
+        );
+    }
+
+    #[test]
+    fn blob_fee_after_follows_excess_blob_gas() {
+        let parent = ParentBlockInfo {
+            excess_blob_gas: Some(10 * GAS_PER_BLOB),
+            blob_gas_used: 5 * GAS_PER_BLOB,
+            ..Default::default()
+        };
+        for spec in [SpecId::CANCUN, SpecId::PRAGUE] {
+            let next = blob_fee_after(10 * GAS_PER_BLOB, 5, spec);
+            assert_eq!(
+                Some(next.excess_blob_gas),
+                parent.next_block_excess_blob_gas(spec)
+            );
+            assert_eq!(
+                next,
+                BlobExcessGasAndPrice::new(
+                    next.excess_blob_gas,
+                    spec.is_enabled_in(SpecId::PRAGUE)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn simulate_blob_fees_from_zero_excess() {
+        // Full Cancun blocks raise the excess by three blobs, the blob gas price leaves its
+        // minimum once the excess reaches 2314058.
+        let fees: Vec<_> = simulate_blob_fees(0, [6; 7], SpecId::CANCUN)
+            .map(|fee| (fee.excess_blob_gas, fee.blob_gasprice))
+            .collect();
+        assert_eq!(
+            fees,
+            [
+                (393216, 1),
+                (786432, 1),
+                (1179648, 1),
+                (1572864, 1),
+                (1966080, 1),
+                (2359296, 2),
+                (2752512, 2),
+            ]
+        );
+
+        // Blocks at or below the target keep the excess at zero.
+        assert!(simulate_blob_fees(0, [0, 3, 6, 0], SpecId::PRAGUE)
+            .all(|fee| fee == BlobExcessGasAndPrice::new(0, true)));
+        assert_eq!(simulate_blob_fees(0, [], SpecId::CANCUN).count(), 0);
+    }
+
+    #[test]
+    fn simulate_blob_fees_max_update() {
+        // EIP-4844: a full block raises the blob gas price by 12.5%, an empty block lowers it
+        // by the same factor. EIP-7691: from Prague a full block raises it by 8.2%, an empty
+        // one lowers it by 14.5%.
+        for (spec, fraction, max_blobs, target_blobs, up, down) in [
+            (
+                SpecId::CANCUN,
+                BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN,
+                6,
+                3,
+                1125,
+                888,
+            ),
+            (
+                SpecId::PRAGUE,
+                BLOB_BASE_FEE_UPDATE_FRACTION_ELECTRA,
+                9,
+                6,
+                1081,
+                854,
+            ),
+        ] {
+            let excess_blob_gas = 60 * fraction;
+            let initial = BlobExcessGasAndPrice::new(excess_blob_gas, spec == SpecId::PRAGUE);
+            let fees: Vec<_> =
+                simulate_blob_fees(excess_blob_gas, [max_blobs, 0, target_blobs], spec).collect();
+            assert_eq!(fees[0].blob_gasprice * 1000 / initial.blob_gasprice, up);
+            assert_eq!(fees[1].blob_gasprice * 1000 / fees[0].blob_gasprice, down);
+            // A block at the target keeps the price.
+            assert_eq!(fees[2], fees[1]);
+        }
+    }
+}
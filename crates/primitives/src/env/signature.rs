@@ -0,0 +1,147 @@
+//! Transaction signatures, used to recover the caller of a transaction.
+use crate::{alloy_primitives::B512, InvalidTransaction, SpecId, B256, U256};
+
+/// Order of the secp256k1 curve.
+pub const SECP256K1N: U256 = U256::from_limbs([
+    0xBFD2_5E8C_D036_4141,
+    0xBAAE_DCE6_AF48_A03B,
+    0xFFFF_FFFF_FFFF_FFFE,
+    0xFFFF_FFFF_FFFF_FFFF,
+]);
+
+/// Half the order of the secp256k1 curve, the largest `s` of a signature from Homestead.
+///
+/// See [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+pub const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xDFE9_2F46_681B_20A0,
+    0x5D57_6E73_57A4_501D,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0x7FFF_FFFF_FFFF_FFFF,
+]);
+
+/// Signature of a transaction and the hash it signs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxSignature {
+    /// The `r` value of the signature.
+    pub r: U256,
+    /// The `s` value of the signature.
+    pub s: U256,
+    /// The `v` value of the signature.
+    ///
+    /// Either 27 or 28, `chain_id * 2 + 35` or `chain_id * 2 + 36` for legacy transactions
+    /// with [EIP-155](https://eips.ethereum.org/EIPS/eip-155), or the y parity, 0 or 1, for
+    /// typed transactions.
+    pub v: u64,
+    /// The signing hash of the transaction.
+    pub signing_hash: B256,
+}
+
+impl TxSignature {
+    /// Checks the signature and returns its y parity.
+    ///
+    /// `r` and `s` must be in `[1, n)`, and from Homestead `s` must be at most `n / 2`. A `v`
+    /// with a chain id is only valid from Spurious Dragon and must encode `chain_id`.
+    pub fn y_parity(&self, chain_id: u64, spec_id: SpecId) -> Result<bool, InvalidTransaction> {
+        let in_range = |value: U256| !value.is_zero() && value < SECP256K1N;
+        if !in_range(self.r) || !in_range(self.s) {
+            return Err(InvalidTransaction::InvalidSignature);
+        }
+        if spec_id.is_enabled_in(SpecId::HOMESTEAD) && self.s > SECP256K1N_HALF {
+            return Err(InvalidTransaction::SignatureHighS);
+        }
+        match self.v {
+            0 | 1 => Ok(self.v == 1),
+            27 | 28 => Ok(self.v == 28),
+            v if v >= 35 && spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) => {
+                if (v - 35) / 2 != chain_id {
+                    return Err(InvalidTransaction::InvalidChainId);
+                }
+                Ok((v - 35) % 2 == 1)
+            }
+            _ => Err(InvalidTransaction::InvalidSignature),
+        }
+    }
+
+    /// Returns `r` and `s` as a 64 byte compact signature.
+    pub fn to_compact(&self) -> B512 {
+        let mut compact = B512::ZERO;
+        compact[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        compact[32..].copy_from_slice(&self.s.to_be_bytes::<32>());
+        compact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(s: U256, v: u64) -> TxSignature {
+        TxSignature {
+            r: U256::from(1),
+            s,
+            v,
+            signing_hash: B256::ZERO,
+        }
+    }
+
+    #[test]
+    fn curve_order_constants() {
+        assert_eq!(SECP256K1N_HALF, SECP256K1N >> 1);
+    }
+
+    #[test]
+    fn y_parity() {
+        let low_s = SECP256K1N_HALF;
+        let cases = [
+            (signature(low_s, 27), SpecId::FRONTIER, Ok(false)),
+            (signature(low_s, 28), SpecId::CANCUN, Ok(true)),
+            (signature(low_s, 0), SpecId::CANCUN, Ok(false)),
+            (signature(low_s, 1), SpecId::CANCUN, Ok(true)),
+            (signature(low_s, 37), SpecId::CANCUN, Ok(false)),
+            (signature(low_s, 38), SpecId::CANCUN, Ok(true)),
+            (
+                signature(low_s, 39),
+                SpecId::CANCUN,
+                Err(InvalidTransaction::InvalidChainId),
+            ),
+            (
+                signature(low_s, 37),
+                SpecId::HOMESTEAD,
+                Err(InvalidTransaction::InvalidSignature),
+            ),
+            (
+                signature(low_s, 29),
+                SpecId::CANCUN,
+                Err(InvalidTransaction::InvalidSignature),
+            ),
+            (
+                signature(low_s + U256::from(1), 27),
+                SpecId::FRONTIER,
+                Ok(false),
+            ),
+            (
+                signature(low_s + U256::from(1), 27),
+                SpecId::HOMESTEAD,
+                Err(InvalidTransaction::SignatureHighS),
+            ),
+            (
+                signature(U256::ZERO, 27),
+                SpecId::CANCUN,
+                Err(InvalidTransaction::InvalidSignature),
+            ),
+            (
+                signature(SECP256K1N, 27),
+                SpecId::FRONTIER,
+                Err(InvalidTransaction::InvalidSignature),
+            ),
+        ];
+        for (signature, spec_id, expected) in cases {
+            assert_eq!(
+                signature.y_parity(1, spec_id),
+                expected,
+                "{signature:?} {spec_id:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Ordering and replacement of transactions, for builders keeping a pool of [`TxEnv`]s.
+//!
+//! A legacy transaction pays its gas price both as max fee and as priority fee, an
+//! [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction pays its max fee
+//! [`TxEnv::gas_price`] and its priority fee [`TxEnv::gas_priority_fee`].
+
+use crate::{TxEnv, U256};
+use core::cmp::Ordering;
+
+/// Percentage by which a replacement transaction has to bump the fees of the transaction it
+/// replaces, see [`TxEnv::can_replace`].
+pub const DEFAULT_PRICE_BUMP: u64 = 10;
+
+impl TxEnv {
+    /// Returns the priority fee per gas paid to the beneficiary at `basefee`.
+    ///
+    /// Returns `None` if the max fee is below `basefee`, the transaction can't be included.
+    pub fn effective_tip(&self, basefee: U256) -> Option<U256> {
+        let max_tip = self.gas_price.checked_sub(basefee)?;
+        Some(match self.gas_priority_fee {
+            Some(priority_fee) => priority_fee.min(max_tip),
+            None => max_tip,
+        })
+    }
+
+    /// Returns whether the transaction can replace `other`, bumping both its max fee and its
+    /// priority fee by at least `bump_percent`, [`DEFAULT_PRICE_BUMP`] by default.
+    ///
+    /// The fees have to increase even with a zero bump. The transactions are expected to have
+    /// the same caller and nonce, which is not checked.
+    pub fn can_replace(&self, other: &TxEnv, bump_percent: u64) -> bool {
+        let bumped = |new: U256, old: U256| {
+            new > old && bump(old, bump_percent).is_some_and(|threshold| new >= threshold)
+        };
+        bumped(self.gas_price, other.gas_price)
+            && bumped(self.max_priority_fee(), other.max_priority_fee())
+    }
+
+    /// Returns the priority fee of the transaction, its gas price if it is a legacy transaction.
+    fn max_priority_fee(&self) -> U256 {
+        self.gas_priority_fee.unwrap_or(self.gas_price)
+    }
+}
+
+/// Returns `fee` increased by `percent`, rounded down, or `None` if it overflows.
+fn bump(fee: U256, percent: u64) -> Option<U256> {
+    // `fee * (100 + percent) / 100` without overflowing the intermediate product.
+    let hundred = U256::from(100);
+    let percent = U256::from(percent);
+    let increase = (fee / hundred)
+        .checked_mul(percent)?
+        .checked_add(fee % hundred * percent / hundred)?;
+    fee.checked_add(increase)
+}
+
+/// Orders transactions by their [effective tip](TxEnv::effective_tip) at a basefee, the
+/// transactions that can't be included at the basefee first.
+///
+/// Transactions with the same effective tip are equal, whatever their other fields are.
+#[derive(Clone, Copy, Debug)]
+pub struct ByEffectiveTip<'a>(pub &'a TxEnv, pub U256);
+
+impl ByEffectiveTip<'_> {
+    /// Returns the effective tip of the transaction at the basefee.
+    #[inline]
+    pub fn effective_tip(&self) -> Option<U256> {
+        self.0.effective_tip(self.1)
+    }
+}
+
+impl PartialEq for ByEffectiveTip<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ByEffectiveTip<'_> {}
+
+impl PartialOrd for ByEffectiveTip<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByEffectiveTip<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.effective_tip().cmp(&other.effective_tip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn legacy(gas_price: u64) -> TxEnv {
+        TxEnv {
+            gas_price: U256::from(gas_price),
+            ..Default::default()
+        }
+    }
+
+    fn eip1559(max_fee: u64, priority_fee: u64) -> TxEnv {
+        TxEnv {
+            gas_price: U256::from(max_fee),
+            gas_priority_fee: Some(U256::from(priority_fee)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn effective_tip() {
+        let basefee = U256::from(10);
+        assert_eq!(legacy(15).effective_tip(basefee), Some(U256::from(5)));
+        assert_eq!(eip1559(15, 2).effective_tip(basefee), Some(U256::from(2)));
+        // The priority fee is capped by the max fee.
+        assert_eq!(eip1559(15, 8).effective_tip(basefee), Some(U256::from(5)));
+        assert_eq!(eip1559(10, 8).effective_tip(basefee), Some(U256::ZERO));
+
+        // The max fee is below the basefee.
+        assert_eq!(legacy(9).effective_tip(basefee), None);
+        assert_eq!(eip1559(9, 8).effective_tip(basefee), None);
+        assert_eq!(eip1559(0, 0).effective_tip(U256::MAX), None);
+    }
+
+    #[test]
+    fn sort_by_effective_tip() {
+        let basefee = U256::from(10);
+        let txs = [
+            eip1559(20, 3),
+            legacy(12),
+            eip1559(9, 5),
+            legacy(14),
+            eip1559(13, 10),
+        ];
+        let mut sorted: Vec<_> = txs.iter().map(|tx| ByEffectiveTip(tx, basefee)).collect();
+        sorted.sort();
+        let tips: Vec<_> = sorted.iter().map(ByEffectiveTip::effective_tip).collect();
+        assert_eq!(
+            tips,
+            [None, Some(2u64), Some(3), Some(3), Some(4)].map(|tip| tip.map(U256::from))
+        );
+
+        // The order depends on the basefee.
+        assert!(ByEffectiveTip(&legacy(14), basefee) > ByEffectiveTip(&eip1559(20, 3), basefee));
+        let basefee = U256::from(13);
+        assert!(ByEffectiveTip(&legacy(14), basefee) < ByEffectiveTip(&eip1559(20, 3), basefee));
+        assert_eq!(
+            ByEffectiveTip(&legacy(16), basefee),
+            ByEffectiveTip(&eip1559(20, 3), basefee)
+        );
+    }
+
+    #[test]
+    fn replacement() {
+        let old = eip1559(100, 10);
+        assert!(eip1559(110, 11).can_replace(&old, DEFAULT_PRICE_BUMP));
+        assert!(eip1559(200, 20).can_replace(&old, DEFAULT_PRICE_BUMP));
+        // Both fees have to be bumped.
+        assert!(!eip1559(109, 20).can_replace(&old, DEFAULT_PRICE_BUMP));
+        assert!(!eip1559(200, 10).can_replace(&old, DEFAULT_PRICE_BUMP));
+        // The fees have to increase with a zero bump.
+        assert!(eip1559(101, 11).can_replace(&old, 0));
+        assert!(!eip1559(100, 11).can_replace(&old, 0));
+
+        // The bump is rounded down.
+        assert!(eip1559(16, 16).can_replace(&eip1559(15, 15), DEFAULT_PRICE_BUMP));
+
+        // A legacy transaction pays its gas price as priority fee.
+        assert!(legacy(110).can_replace(&old, DEFAULT_PRICE_BUMP));
+        assert!(eip1559(110, 110).can_replace(&legacy(100), DEFAULT_PRICE_BUMP));
+        assert!(!eip1559(110, 11).can_replace(&legacy(100), DEFAULT_PRICE_BUMP));
+    }
+
+    #[test]
+    fn bump_near_max() {
+        assert_eq!(bump(U256::from(15), 10), Some(U256::from(16)));
+        assert_eq!(bump(U256::from(1_000), 125), Some(U256::from(2_250)));
+        assert_eq!(bump(U256::MAX, 0), Some(U256::MAX));
+        assert_eq!(bump(U256::MAX, 1), None);
+
+        // A max fee can't be replaced once the bumped fee overflows.
+        let old = TxEnv {
+            gas_price: U256::MAX - U256::from(1),
+            ..Default::default()
+        };
+        let new = TxEnv {
+            gas_price: U256::MAX,
+            ..Default::default()
+        };
+        assert!(new.can_replace(&old, 0));
+        assert!(!new.can_replace(&old, DEFAULT_PRICE_BUMP));
+    }
+}
@@ -7,7 +7,10 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
 mod bytecode;
+pub mod calldata;
 mod constants;
 pub mod db;
 pub mod eip7702;
@@ -15,7 +18,9 @@ pub mod env;
 
 #[cfg(any(feature = "c-kzg", feature = "kzg-rs"))]
 pub mod kzg;
+pub mod logs;
 pub mod precompile;
+pub mod requests;
 pub mod result;
 pub mod specification;
 pub mod state;
@@ -24,21 +29,28 @@ pub use alloy_eip2930::{AccessList, AccessListItem};
 pub use alloy_primitives::{
     self, address, b256, bytes, fixed_bytes, hex, hex_literal,
     map::{self, hash_map, hash_set, HashMap, HashSet},
-    ruint, uint, Address, Bytes, FixedBytes, Log, LogData, TxKind, B256, I256, U256,
+    ruint, uint, Address, Bloom, Bytes, FixedBytes, Log, LogData, TxKind, B256, I256, U256,
 };
+#[cfg(feature = "arbitrary")]
+pub use arbitrary;
 pub use bitvec;
 pub use bytecode::*;
+pub use calldata::{calldata_token_count, flz_compress_len, CalldataStats};
 pub use constants::*;
 pub use eip7702::{
-    Authorization, AuthorizationList, Eip7702Bytecode, Eip7702DecodeError, PrimitiveSignature,
-    RecoveredAuthority, RecoveredAuthorization, SignedAuthorization, EIP7702_MAGIC,
-    EIP7702_MAGIC_BYTES, EIP7702_MAGIC_HASH,
+    eip7702_refund, Authorization, AuthorizationList, AuthorizationOutcome, Eip7702Bytecode,
+    Eip7702DecodeError, PrimitiveSignature, RecoveredAuthority, RecoveredAuthorization,
+    SignedAuthorization, EIP7702_MAGIC, EIP7702_MAGIC_BYTES, EIP7702_MAGIC_HASH,
 };
 pub use env::*;
 
 #[cfg(any(feature = "c-kzg", feature = "kzg-rs"))]
 pub use kzg::{EnvKzgSettings, KzgSettings};
+pub use logs::{filter_logs, log_matches, LogsBloom};
 pub use precompile::*;
+pub use requests::{
+    extract_requests, extract_requests_with_deposit_contract, DepositLogError, Request,
+};
 pub use result::*;
 pub use specification::*;
 pub use state::*;
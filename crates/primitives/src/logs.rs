@@ -0,0 +1,150 @@
+//! Log filtering and bloom utilities.
+
+use crate::{Address, Bloom, Log, B256};
+
+/// Returns `true` if `log` matches the filter, with the semantics of `eth_getLogs`.
+///
+/// `None` is a wildcard: a `None` address matches any emitter, a `None` topic matches any topic
+/// at its position. A log must have at least as many topics as the position of the last `Some`
+/// topic, so `[None, Some(b), None, None]` matches logs with two or more topics, whose second
+/// topic is `b`.
+pub fn log_matches(log: &Log, address: Option<Address>, topics: &[Option<B256>; 4]) -> bool {
+    if address.is_some_and(|address| address != log.address) {
+        return false;
+    }
+    let log_topics = log.topics();
+    topics.iter().enumerate().all(|(i, topic)| match topic {
+        Some(topic) => log_topics.get(i) == Some(topic),
+        None => true,
+    })
+}
+
+/// Returns the logs matching the filter, see [`log_matches`].
+pub fn filter_logs<'a>(
+    logs: &'a [Log],
+    address: Option<Address>,
+    topics: &[Option<B256>; 4],
+) -> impl Iterator<Item = &'a Log> + 'a {
+    let topics = *topics;
+    logs.iter()
+        .filter(move |log| log_matches(log, address, &topics))
+}
+
+/// Builds the bloom filter of a list of logs, as found in receipts and block headers.
+pub trait LogsBloom {
+    /// Returns the bloom filter of the addresses and topics of `logs`.
+    fn from_logs(logs: &[Log]) -> Self;
+}
+
+impl LogsBloom for Bloom {
+    fn from_logs(logs: &[Log]) -> Self {
+        let mut bloom = Bloom::ZERO;
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        address, alloy_primitives::BloomInput, b256, Bytes, ExecutionResult, Output,
+        ResultAndState, SuccessReason,
+    };
+    use std::{vec, vec::Vec};
+
+    const A: B256 = b256!("000000000000000000000000000000000000000000000000000000000000000a");
+    const B: B256 = b256!("000000000000000000000000000000000000000000000000000000000000000b");
+    const C: B256 = b256!("000000000000000000000000000000000000000000000000000000000000000c");
+
+    fn log(address: Address, topics: &[B256]) -> Log {
+        Log::new_unchecked(address, topics.to_vec(), Bytes::new())
+    }
+
+    fn logs() -> Vec<Log> {
+        let first = address!("0000000000000000000000000000000000000001");
+        let second = address!("0000000000000000000000000000000000000002");
+        vec![
+            log(first, &[]),
+            log(first, &[A]),
+            log(first, &[A, B]),
+            log(second, &[B, A]),
+            log(second, &[A, B, C]),
+        ]
+    }
+
+    /// Indices of the logs matching the filter.
+    fn matching(address: Option<Address>, topics: [Option<B256>; 4]) -> Vec<usize> {
+        let logs = logs();
+        filter_logs(&logs, address, &topics)
+            .map(|log| logs.iter().position(|l| l == log).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn topic_filters() {
+        // Empty filter matches everything, including logs without topics.
+        assert_eq!(matching(None, [None; 4]), [0, 1, 2, 3, 4]);
+        // {{A}} matches topic A in the first position.
+        assert_eq!(matching(None, [Some(A), None, None, None]), [1, 2, 4]);
+        // {{}, {B}} matches anything in the first position and B in the second.
+        assert_eq!(matching(None, [None, Some(B), None, None]), [2, 4]);
+        // {{A}, {B}} matches A in the first position and B in the second.
+        assert_eq!(matching(None, [Some(A), Some(B), None, None]), [2, 4]);
+        // {{}, {}, {C}} requires at least three topics.
+        assert_eq!(matching(None, [None, None, Some(C), None]), [4]);
+        // Positions are not interchangeable.
+        assert_eq!(matching(None, [Some(B), Some(A), None, None]), [3]);
+        // More topics than any log has.
+        assert!(matching(None, [None, None, None, Some(A)]).is_empty());
+    }
+
+    #[test]
+    fn address_filter() {
+        let second = address!("0000000000000000000000000000000000000002");
+        assert_eq!(matching(Some(second), [None; 4]), [3, 4]);
+        assert_eq!(matching(Some(second), [Some(A), None, None, None]), [4]);
+        assert!(matching(Some(Address::ZERO), [None; 4]).is_empty());
+    }
+
+    #[test]
+    fn result_logs_filtered() {
+        let mut result = ResultAndState {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used: 0,
+                gas_refunded: 0,
+                logs: logs(),
+                output: Output::Call(Bytes::new()),
+            },
+            state: Default::default(),
+        };
+        assert_eq!(result.logs(), logs());
+        let filtered: Vec<_> = result
+            .logs_filtered(None, &[Some(B), Some(A), None, None])
+            .collect();
+        assert_eq!(filtered, [&logs()[3]]);
+
+        // Reverted transactions have no logs.
+        result.result = ExecutionResult::Revert {
+            gas_used: 0,
+            output: Bytes::new(),
+        };
+        assert_eq!(result.logs_filtered(None, &[None; 4]).count(), 0);
+    }
+
+    #[test]
+    fn bloom_contains_addresses_and_topics() {
+        let logs = logs();
+        let bloom = Bloom::from_logs(&logs);
+        for log in &logs {
+            assert!(bloom.contains_input(BloomInput::Raw(log.address.as_slice())));
+            for topic in log.topics() {
+                assert!(bloom.contains_input(BloomInput::Raw(topic.as_slice())));
+            }
+        }
+        assert_eq!(Bloom::from_logs(&[]), Bloom::ZERO);
+    }
+}
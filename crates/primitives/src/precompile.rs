@@ -108,6 +108,21 @@ impl Precompile {
         Self::StatefulMut(Box::new(p))
     }
 
+    /// Returns `true` if both are the same implementation: the same function, or the same
+    /// stateful precompile instance.
+    ///
+    /// Mutable stateful precompiles are cloned with the sets holding them, they are only equal
+    /// to themselves.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Standard(a), Self::Standard(b)) => core::ptr::fn_addr_eq(*a, *b),
+            (Self::Env(a), Self::Env(b)) => core::ptr::fn_addr_eq(*a, *b),
+            (Self::Stateful(a), Self::Stateful(b)) => Arc::ptr_eq(a, b),
+            (Self::StatefulMut(a), Self::StatefulMut(b)) => core::ptr::addr_eq(&**a, &**b),
+            _ => false,
+        }
+    }
+
     /// Call the precompile with the given input and gas limit and return the result.
     pub fn call(&mut self, bytes: &Bytes, gas_limit: u64, env: &Env) -> PrecompileResult {
         match *self {
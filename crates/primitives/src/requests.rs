@@ -0,0 +1,266 @@
+//! EIP-7685 execution layer requests.
+
+use crate::{
+    b256, Address, Bytes, Log, ResultAndState, SpecId, B256, MAINNET_DEPOSIT_CONTRACT_ADDRESS, U256,
+};
+use core::fmt;
+use std::vec::Vec;
+
+/// EIP-6110: Topic of the `DepositEvent(bytes,bytes,bytes,bytes,bytes)` event of the deposit
+/// contract.
+pub const DEPOSIT_EVENT_SIGNATURE_HASH: B256 =
+    b256!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5");
+
+/// Fields of the deposit event with their sizes, in the order of the event.
+const DEPOSIT_EVENT_FIELDS: [(&str, usize); 5] = [
+    ("pubkey", 48),
+    ("withdrawal_credentials", 32),
+    ("amount", 8),
+    ("signature", 96),
+    ("index", 8),
+];
+
+/// Size of the ABI encoded data of a deposit event.
+const DEPOSIT_EVENT_DATA_SIZE: usize = 576;
+
+/// Size of a deposit request, the concatenation of the fields of the deposit event.
+pub const DEPOSIT_REQUEST_SIZE: usize = 192;
+
+/// An execution layer request, see EIP-7685.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Request {
+    /// EIP-6110 deposit requests, concatenated.
+    Deposit(Bytes),
+    /// EIP-7002 withdrawal requests, as returned by the withdrawal request predeploy.
+    Withdrawal(Bytes),
+    /// EIP-7251 consolidation requests, as returned by the consolidation request predeploy.
+    Consolidation(Bytes),
+}
+
+impl Request {
+    /// Returns the request type.
+    pub const fn request_type(&self) -> u8 {
+        match self {
+            Self::Deposit(_) => 0x00,
+            Self::Withdrawal(_) => 0x01,
+            Self::Consolidation(_) => 0x02,
+        }
+    }
+
+    /// Returns the request data, without the request type.
+    pub fn data(&self) -> &Bytes {
+        match self {
+            Self::Deposit(data) | Self::Withdrawal(data) | Self::Consolidation(data) => data,
+        }
+    }
+
+    /// Returns the request type followed by the request data, as committed to in the
+    /// requests hash.
+    pub fn encode(&self) -> Bytes {
+        let mut encoded = Vec::with_capacity(1 + self.data().len());
+        encoded.push(self.request_type());
+        encoded.extend_from_slice(self.data());
+        encoded.into()
+    }
+}
+
+/// Error of a malformed deposit event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DepositLogError {
+    /// The event data is not [`DEPOSIT_EVENT_DATA_SIZE`] bytes long.
+    InvalidDataLength(usize),
+    /// The offset of a field is not the one of the canonical encoding.
+    InvalidOffset(&'static str),
+    /// The length of a field is not the size of the field.
+    InvalidFieldLength(&'static str),
+}
+
+impl fmt::Display for DepositLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDataLength(len) => write!(f, "invalid deposit event data length {len}"),
+            Self::InvalidOffset(field) => write!(f, "invalid offset of deposit event {field}"),
+            Self::InvalidFieldLength(field) => {
+                write!(f, "invalid length of deposit event {field}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DepositLogError {}
+
+/// Parses the ABI encoded data of a deposit event into a deposit request.
+///
+/// The layout is validated as done by the deposit contract encoding it, offsets and lengths of
+/// the fields must match the canonical encoding exactly.
+pub fn parse_deposit_log(data: &[u8]) -> Result<[u8; DEPOSIT_REQUEST_SIZE], DepositLogError> {
+    if data.len() != DEPOSIT_EVENT_DATA_SIZE {
+        return Err(DepositLogError::InvalidDataLength(data.len()));
+    }
+    let word = |offset: usize| U256::from_be_slice(&data[offset..offset + 32]);
+
+    let mut request = [0; DEPOSIT_REQUEST_SIZE];
+    let mut written = 0;
+    // Fields are encoded after the head of one offset per field.
+    let mut offset = DEPOSIT_EVENT_FIELDS.len() * 32;
+    for (i, (field, size)) in DEPOSIT_EVENT_FIELDS.into_iter().enumerate() {
+        if word(i * 32) != U256::from(offset) {
+            return Err(DepositLogError::InvalidOffset(field));
+        }
+        if word(offset) != U256::from(size) {
+            return Err(DepositLogError::InvalidFieldLength(field));
+        }
+        request[written..written + size].copy_from_slice(&data[offset + 32..offset + 32 + size]);
+        written += size;
+        offset += 32 + size.div_ceil(32) * 32;
+    }
+    Ok(request)
+}
+
+/// Returns the deposit requests of the deposit events emitted by `deposit_contract` in `logs`.
+///
+/// Returns `None` if there are no deposits.
+pub fn extract_deposit_requests<'a>(
+    logs: impl IntoIterator<Item = &'a Log>,
+    deposit_contract: Address,
+) -> Result<Option<Request>, DepositLogError> {
+    let mut deposits = Vec::new();
+    for log in logs {
+        if log.address != deposit_contract
+            || log.topics().first() != Some(&DEPOSIT_EVENT_SIGNATURE_HASH)
+        {
+            continue;
+        }
+        deposits.extend_from_slice(&parse_deposit_log(&log.data.data)?);
+    }
+    Ok((!deposits.is_empty()).then(|| Request::Deposit(deposits.into())))
+}
+
+/// Returns the requests derived from the logs of the executed transactions of a block, that is
+/// the EIP-6110 deposits of the mainnet deposit contract.
+///
+/// Requests are only derived from Prague. Withdrawal and consolidation requests are returned by
+/// the system calls made after the transactions, see `Evm::transact_withdrawal_requests` and
+/// `Evm::transact_consolidation_requests` in `revm`.
+pub fn extract_requests(
+    results: &[ResultAndState],
+    spec_id: SpecId,
+) -> Result<Vec<Request>, DepositLogError> {
+    extract_requests_with_deposit_contract(results, spec_id, MAINNET_DEPOSIT_CONTRACT_ADDRESS)
+}
+
+/// Same as [`extract_requests`] with the deposit contract of the chain.
+pub fn extract_requests_with_deposit_contract(
+    results: &[ResultAndState],
+    spec_id: SpecId,
+    deposit_contract: Address,
+) -> Result<Vec<Request>, DepositLogError> {
+    if !spec_id.is_enabled_in(SpecId::PRAGUE) {
+        return Ok(Vec::new());
+    }
+    let logs = results.iter().flat_map(|result| result.result.logs());
+    Ok(extract_deposit_requests(logs, deposit_contract)?
+        .into_iter()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EvmState, ExecutionResult, LogData, Output, SuccessReason};
+    use std::vec;
+
+    /// Encodes a deposit event with fields filled with their index.
+    fn deposit_data(index: u8) -> Vec<u8> {
+        let mut data = vec![0; DEPOSIT_EVENT_DATA_SIZE];
+        let mut offset = 160;
+        for (i, (_, size)) in DEPOSIT_EVENT_FIELDS.into_iter().enumerate() {
+            data[i * 32 + 30..i * 32 + 32].copy_from_slice(&(offset as u16).to_be_bytes());
+            data[offset + 31] = size as u8;
+            data[offset + 32..offset + 32 + size].fill(index);
+            offset += 32 + size.div_ceil(32) * 32;
+        }
+        data
+    }
+
+    fn deposit_log(address: Address, data: Vec<u8>) -> Log {
+        Log {
+            address,
+            data: LogData::new_unchecked(vec![DEPOSIT_EVENT_SIGNATURE_HASH], data.into()),
+        }
+    }
+
+    fn success(logs: Vec<Log>) -> ResultAndState {
+        ResultAndState {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used: 0,
+                gas_refunded: 0,
+                logs,
+                output: Output::Call(Bytes::new()),
+            },
+            state: EvmState::default(),
+        }
+    }
+
+    #[test]
+    fn parse_valid_deposit() {
+        let request = parse_deposit_log(&deposit_data(7)).unwrap();
+        assert_eq!(request, [7; DEPOSIT_REQUEST_SIZE]);
+    }
+
+    #[test]
+    fn reject_malformed_deposits() {
+        let data = deposit_data(1);
+        assert_eq!(
+            parse_deposit_log(&data[..544]),
+            Err(DepositLogError::InvalidDataLength(544))
+        );
+
+        let mut bad_offset = data.clone();
+        bad_offset[32 + 31] = 0xa0;
+        assert_eq!(
+            parse_deposit_log(&bad_offset),
+            Err(DepositLogError::InvalidOffset("withdrawal_credentials"))
+        );
+
+        let mut bad_length = data;
+        bad_length[384 + 31] = 95;
+        assert_eq!(
+            parse_deposit_log(&bad_length),
+            Err(DepositLogError::InvalidFieldLength("signature"))
+        );
+    }
+
+    #[test]
+    fn extract_deposits_of_block() {
+        let contract = MAINNET_DEPOSIT_CONTRACT_ADDRESS;
+        let other = Address::with_last_byte(1);
+        let results = [
+            success(vec![deposit_log(contract, deposit_data(1))]),
+            // Not from the deposit contract.
+            success(vec![deposit_log(other, vec![0; 3])]),
+            success(vec![
+                deposit_log(contract, deposit_data(2)),
+                Log::new_unchecked(contract, vec![B256::ZERO], Bytes::new()),
+            ]),
+        ];
+
+        let requests = extract_requests(&results, SpecId::PRAGUE).unwrap();
+        let mut expected = vec![1; DEPOSIT_REQUEST_SIZE];
+        expected.extend([2; DEPOSIT_REQUEST_SIZE]);
+        assert_eq!(requests, vec![Request::Deposit(expected.into())]);
+        assert_eq!(requests[0].encode()[0], 0x00);
+
+        assert_eq!(extract_requests(&results, SpecId::CANCUN), Ok(vec![]));
+        assert_eq!(extract_requests(&results[1..2], SpecId::PRAGUE), Ok(vec![]));
+
+        let malformed = [success(vec![deposit_log(contract, vec![0; 576])])];
+        assert_eq!(
+            extract_requests(&malformed, SpecId::PRAGUE),
+            Err(DepositLogError::InvalidOffset("pubkey"))
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{Address, Bytes, EvmState, Log, U256};
+use crate::{
+    db::DBErrorMarker, filter_logs, Address, Bytes, EvmState, Log, B256, CODE_DEPOSIT_GAS_PER_BYTE,
+    U256,
+};
 use core::fmt;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -8,18 +11,89 @@ pub type EVMResult<DBError> = EVMResultGeneric<ResultAndState, DBError>;
 /// Generic result of EVM execution. Used to represent error and generic output.
 pub type EVMResultGeneric<T, DBError> = core::result::Result<T, EVMError<DBError>>;
 
+/// Result of a transaction execution together with the state it changed.
+///
+/// With the `serde` feature enabled this type, and everything it contains, has a stable
+/// JSON representation:
+///
+/// - bytes, hashes and addresses are `0x`-prefixed lowercase hex strings,
+/// - `U256` values are minimal `0x`-prefixed hex strings (`"0x0"` for zero),
+/// - enum variants are tagged with their `snake_case` name, e.g. `{"halt": {"reason": "stack_underflow", ...}}`,
+/// - state and storage maps are serialized with their keys in ascending order,
+/// - [`AccountStatus`](crate::AccountStatus) is a list of the set flag names, e.g. `["touched"]`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResultAndState {
     /// Status of execution
     pub result: ExecutionResult,
     /// State that got updated
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::state::serialize_sorted")
+    )]
     pub state: EvmState,
 }
 
+impl ResultAndState {
+    /// Returns the logs if execution is successful, or an empty list otherwise.
+    pub fn logs(&self) -> &[Log] {
+        self.result.logs()
+    }
+
+    /// Returns the logs matching the filter, see [`log_matches`](crate::log_matches).
+    pub fn logs_filtered<'a>(
+        &'a self,
+        address: Option<Address>,
+        topics: &[Option<B256>; 4],
+    ) -> impl Iterator<Item = &'a Log> + 'a {
+        self.result.logs_filtered(address, topics)
+    }
+}
+
+/// Fees paid by a transaction, used for supply tracking and receipts.
+///
+/// Recorded by the handler when the beneficiary is rewarded. On Optimism deposit transactions
+/// all values are zero, deposits don't pay for gas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeSummary {
+    /// Gas price paid by the caller.
+    pub effective_gas_price: U256,
+    /// Part of the gas price paid to the coinbase.
+    pub tip_per_gas: U256,
+    /// Base fee of the used gas, burned since London.
+    ///
+    /// Zero on Optimism, where it is paid to the base fee vault instead.
+    pub burned: U256,
+    /// Tip of the used gas, paid to the coinbase.
+    pub paid_to_coinbase: U256,
+    /// EIP-4844 blob fee, it is always burned.
+    pub blob_fee_burned: U256,
+    /// EIP-4844 blob gas price paid by the caller, `None` if the transaction has no blobs.
+    pub blob_gas_price: Option<u128>,
+    /// Gas spent by the transaction, before the refund.
+    pub gas_used_pre_refund: u64,
+    /// Refund accumulated during execution, before it is capped to a part of the gas spent.
+    pub refund_accumulated: u64,
+    /// Refund applied to the gas spent, after the cap.
+    pub refund_applied: u64,
+    /// Gas used by the transaction, after the refund.
+    pub gas_used_final: u64,
+    /// L1 data fee, paid to the L1 fee vault.
+    #[cfg(feature = "optimism")]
+    pub l1_fee: U256,
+    /// Base fee of the used gas, paid to the base fee vault.
+    #[cfg(feature = "optimism")]
+    pub base_fee_vault: U256,
+    /// Operator fee, paid to the operator fee vault.
+    #[cfg(feature = "optimism")]
+    pub operator_fee: U256,
+}
+
 /// Result of a transaction execution.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ExecutionResult {
     /// Returned successfully
     Success {
@@ -36,6 +110,13 @@ pub enum ExecutionResult {
         reason: HaltReason,
         /// Halting will spend all the gas, and will be equal to gas_limit.
         gas_used: u64,
+        /// Where execution halted, captured only if
+        /// [`CfgEnv::capture_halt_context`](crate::CfgEnv::capture_halt_context) is set.
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        context: Option<HaltContext>,
     },
 }
 
@@ -82,6 +163,15 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns the logs matching the filter, see [`log_matches`](crate::log_matches).
+    pub fn logs_filtered<'a>(
+        &'a self,
+        address: Option<Address>,
+        topics: &[Option<B256>; 4],
+    ) -> impl Iterator<Item = &'a Log> + 'a {
+        filter_logs(self.logs(), address, topics)
+    }
+
     /// Consumes `self` and returns the logs if execution is successful, or an empty list otherwise.
     pub fn into_logs(self) -> Vec<Log> {
         match self {
@@ -90,6 +180,28 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns true if the created address of a contract creation transaction already had
+    /// code or a nonce.
+    ///
+    /// Collisions of nested creations are not reported, the creating instruction returns zero.
+    pub fn is_create_collision(&self) -> bool {
+        matches!(
+            self,
+            Self::Halt {
+                reason: HaltReason::CreateCollision,
+                ..
+            }
+        )
+    }
+
+    /// Returns the halt context if execution halted and it was captured.
+    pub fn halt_context(&self) -> Option<&HaltContext> {
+        match self {
+            Self::Halt { context, .. } => context.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Returns the gas used.
     pub fn gas_used(&self) -> u64 {
         match *self {
@@ -100,9 +212,24 @@ impl ExecutionResult {
     }
 }
 
+/// Location of the instruction that halted execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HaltContext {
+    /// Address of the contract whose frame halted.
+    pub address: Address,
+    /// Program counter of the halting instruction.
+    ///
+    /// For EOF it is relative to the start of the executing code section.
+    pub pc: usize,
+    /// Opcode of the halting instruction.
+    pub opcode: u8,
+}
+
 /// Output of a transaction execution.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Output {
     Call(Bytes),
     Create(Bytes, Option<Address>),
@@ -132,28 +259,61 @@ impl Output {
             Output::Create(_, address) => address.as_ref(),
         }
     }
-}
 
-/// Main EVM error.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum EVMError<DBError> {
-    /// Transaction validation error.
-    Transaction(InvalidTransaction),
-    /// Header validation error.
-    Header(InvalidHeader),
-    /// Database error.
-    Database(DBError),
-    /// Custom error.
+    /// Returns the size of the code deployed by a successful contract creation.
     ///
-    /// Useful for handler registers where custom logic would want to return their own custom error.
-    Custom(String),
-    /// Precompile error.
-    Precompile(String),
+    /// The output of a creation is the deployed code, so this is its length.
+    pub fn deployed_code_size(&self) -> Option<usize> {
+        match self {
+            Output::Create(code, Some(_)) => Some(code.len()),
+            _ => None,
+        }
+    }
+
+    /// Returns the code deposit gas charged for the code deployed by a successful contract
+    /// creation, [`CODE_DEPOSIT_GAS_PER_BYTE`] per byte.
+    ///
+    /// It is included in the gas used of the transaction.
+    pub fn code_deposit_gas(&self) -> Option<u64> {
+        self.deployed_code_size()
+            .map(|size| size as u64 * CODE_DEPOSIT_GAS_PER_BYTE)
+    }
+}
+
+pub use evm_error::EVMError;
+
+// The serde derives of the enum don't inherit an `allow` of the deprecated variant.
+#[allow(deprecated)]
+mod evm_error {
+    use super::{ArithmeticError, InvalidHeader, InvalidTransaction, String};
+
+    /// Main EVM error.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum EVMError<DBError> {
+        /// Transaction validation error.
+        Transaction(InvalidTransaction),
+        /// Header validation error.
+        Header(InvalidHeader),
+        /// Database error.
+        Database(DBError),
+        /// Custom error.
+        ///
+        /// Useful for handler registers where custom logic would want to return their own custom error.
+        #[deprecated(
+            note = "return a typed error, such as an `InvalidTransaction` variant, instead"
+        )]
+        Custom(String),
+        /// Precompile error.
+        Precompile(String),
+        /// Gas or fee arithmetic overflowed while settling the transaction.
+        Arithmetic(ArithmeticError),
+    }
 }
 
 impl<DBError> EVMError<DBError> {
     /// Maps a `DBError` to a new error type using the provided closure, leaving other variants unchanged.
+    #[allow(deprecated)]
     pub fn map_db_err<F, E>(self, op: F) -> EVMError<E>
     where
         F: FnOnce(DBError) -> E,
@@ -164,17 +324,20 @@ impl<DBError> EVMError<DBError> {
             Self::Database(e) => EVMError::Database(op(e)),
             Self::Precompile(e) => EVMError::Precompile(e),
             Self::Custom(e) => EVMError::Custom(e),
+            Self::Arithmetic(e) => EVMError::Arithmetic(e),
         }
     }
 }
 
 #[cfg(feature = "std")]
 impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBError> {
+    #[allow(deprecated)]
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Transaction(e) => Some(e),
             Self::Header(e) => Some(e),
             Self::Database(e) => Some(e),
+            Self::Arithmetic(e) => Some(e),
             Self::Precompile(_) | Self::Custom(_) => None,
         }
     }
@@ -186,11 +349,19 @@ impl<DBError: fmt::Display> fmt::Display for EVMError<DBError> {
             Self::Transaction(e) => write!(f, "transaction validation error: {e}"),
             Self::Header(e) => write!(f, "header validation error: {e}"),
             Self::Database(e) => write!(f, "database error: {e}"),
+            Self::Arithmetic(e) => write!(f, "arithmetic error: {e}"),
+            #[allow(deprecated)]
             Self::Precompile(e) | Self::Custom(e) => f.write_str(e),
         }
     }
 }
 
+impl<DBError: DBErrorMarker> From<DBError> for EVMError<DBError> {
+    fn from(value: DBError) -> Self {
+        Self::Database(value)
+    }
+}
+
 impl<DBError> From<InvalidTransaction> for EVMError<DBError> {
     fn from(value: InvalidTransaction) -> Self {
         Self::Transaction(value)
@@ -203,6 +374,12 @@ impl<DBError> From<InvalidHeader> for EVMError<DBError> {
     }
 }
 
+impl<DBError> From<ArithmeticError> for EVMError<DBError> {
+    fn from(value: ArithmeticError) -> Self {
+        Self::Arithmetic(value)
+    }
+}
+
 /// Transaction validation error for Optimism.
 #[cfg(feature = "optimism")]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -238,8 +415,49 @@ pub enum OptimismInvalidTransaction {
     /// special gas accounting rules are applied. Normally on L1, [EVMError::Transaction] errors
     /// are cause for non-inclusion, so a special [HaltReason] variant was introduced to handle this
     /// case for failed deposit transactions.
+    ///
+    /// `reason` is the halt reason of the execution, it is reported in the
+    /// [FailedDepositCause] of the failed deposit.
+    #[cfg(feature = "optimism")]
+    HaltedDepositPostRegolith { reason: HaltReason },
+    /// Deposit transaction carries an enveloped transaction that is not a deposit.
+    ///
+    /// Deposits are pre-paid on L1 and are not charged an L1 data fee, an envelope of any
+    /// other transaction type would imply L1 cost accounting that never happens.
     #[cfg(feature = "optimism")]
-    HaltedDepositPostRegolith,
+    DepositWithNonDepositEnvelope,
+    /// Deposit transaction has a non-zero `gas_priority_fee`.
+    ///
+    /// Deposits don't pay a priority fee to the sequencer.
+    #[cfg(feature = "optimism")]
+    DepositWithPriorityFee,
+    /// Deposit transaction carries blob versioned hashes.
+    ///
+    /// Deposits can't carry blobs.
+    #[cfg(feature = "optimism")]
+    DepositWithBlobHashes,
+    /// Blob transactions are not supported on Optimism.
+    #[cfg(feature = "optimism")]
+    BlobTransactionNotSupported,
+    /// Non-deposit transaction is missing the enveloped transaction.
+    ///
+    /// The enveloped transaction is required to compute the L1 data fee and the operator fee.
+    #[cfg(feature = "optimism")]
+    MissingEnvelope,
+    /// Transaction emitted an interop executing message that was rejected by the
+    /// [`InteropValidator`](crate::InteropValidator).
+    #[cfg(feature = "optimism")]
+    InvalidExecutingMessage,
+    /// L1 block info was not loaded before it was needed.
+    ///
+    /// It is loaded by `load_precompiles` of the Optimism handler, custom handlers that replace
+    /// it have to load it as well.
+    #[cfg(feature = "optimism")]
+    MissingL1BlockInfo,
+    /// L1 block info of a spec with operator fees is missing the operator fee scalar or
+    /// constant.
+    #[cfg(feature = "optimism")]
+    MissingOperatorFeeParams,
 }
 
 /// Transaction validation error.
@@ -303,9 +521,13 @@ pub enum InvalidTransaction {
     /// Blob transaction can't be a create transaction.
     /// `to` must be present
     BlobCreateTransaction,
-    /// Transaction has more then `max_blob_num_per_block` blobs.
+    /// Transaction has more blobs than allowed by the spec, see
+    /// [`CfgEnv::blob_max_count_per_tx`].
+    ///
+    /// [`CfgEnv::blob_max_count_per_tx`]: crate::CfgEnv::blob_max_count_per_tx
     TooManyBlobs {
         have: usize,
+        max: usize,
     },
     /// Blob transaction contains a versioned hash with an incorrect version
     BlobVersionNotSupported,
@@ -317,6 +539,12 @@ pub enum InvalidTransaction {
     AuthorizationListInvalidFields,
     /// Empty Authorization List is not allowed.
     EmptyAuthorizationList,
+    /// The signature of the transaction is not valid or the caller can't be recovered from it.
+    #[cfg(feature = "secp256k1-recover")]
+    InvalidSignature,
+    /// EIP-2: the `s` value of the signature is greater than half the curve order.
+    #[cfg(feature = "secp256k1-recover")]
+    SignatureHighS,
     /// Optimism-specific transaction validation error.
     #[cfg(feature = "optimism")]
     OptimismError(OptimismInvalidTransaction),
@@ -333,10 +561,33 @@ impl fmt::Display for OptimismInvalidTransaction {
                 f,
                 "deposit system transactions post regolith hardfork are not supported"
             ),
-            Self::HaltedDepositPostRegolith => write!(
+            Self::HaltedDepositPostRegolith { reason } => write!(
+                f,
+                "deposit transaction halted post-regolith with {reason:?}; error will be bubbled up to main return handler"
+            ),
+            Self::DepositWithNonDepositEnvelope => write!(
                 f,
-                "deposit transaction halted post-regolith; error will be bubbled up to main return handler"
+                "deposit transaction carries a non-deposit enveloped transaction"
             ),
+            Self::DepositWithPriorityFee => {
+                write!(f, "deposit transaction has a non-zero priority fee")
+            }
+            Self::DepositWithBlobHashes => {
+                write!(f, "deposit transaction carries blob versioned hashes")
+            }
+            Self::BlobTransactionNotSupported => {
+                write!(f, "blob transactions are not supported")
+            }
+            Self::MissingEnvelope => {
+                write!(f, "non-deposit transaction is missing the enveloped transaction")
+            }
+            Self::InvalidExecutingMessage => {
+                write!(f, "interop executing message is not valid")
+            }
+            Self::MissingL1BlockInfo => write!(f, "L1 block info is not loaded"),
+            Self::MissingOperatorFeeParams => {
+                write!(f, "operator fee scalar or constant is not loaded")
+            }
         }
     }
 }
@@ -393,8 +644,8 @@ impl fmt::Display for InvalidTransaction {
             }
             Self::EmptyBlobs => write!(f, "empty blobs"),
             Self::BlobCreateTransaction => write!(f, "blob create transaction"),
-            Self::TooManyBlobs { have } => {
-                write!(f, "too many blobs, have {have}")
+            Self::TooManyBlobs { have, max } => {
+                write!(f, "too many blobs, have {have}, max {max}")
             }
             Self::BlobVersionNotSupported => write!(f, "blob version not supported"),
             Self::EofCrateShouldHaveToAddress => write!(f, "EOF crate should have `to` address"),
@@ -403,6 +654,10 @@ impl fmt::Display for InvalidTransaction {
                 write!(f, "authorization list tx has invalid fields")
             }
             Self::EmptyAuthorizationList => write!(f, "empty authorization list"),
+            #[cfg(feature = "secp256k1-recover")]
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            #[cfg(feature = "secp256k1-recover")]
+            Self::SignatureHighS => write!(f, "signature s value is too high"),
             #[cfg(feature = "optimism")]
             Self::OptimismError(op_error) => op_error.fmt(f),
         }
@@ -415,8 +670,20 @@ impl fmt::Display for InvalidTransaction {
 pub enum InvalidHeader {
     /// `prevrandao` is not set for Merge and above.
     PrevrandaoNotSet,
+    /// `difficulty` is set for Merge and above, with [`CfgEnv::strict_randomness`].
+    ///
+    /// [`CfgEnv::strict_randomness`]: crate::CfgEnv::strict_randomness
+    DifficultyAfterMerge,
     /// `excess_blob_gas` is not set for Cancun and above.
     ExcessBlobGasNotSet,
+    /// The block number is not the one following the parent block.
+    NumberNotParentSuccessor,
+    /// The timestamp is not greater than the one of the parent block.
+    TimestampNotIncreasing,
+    /// The base fee is not the one derived from the parent block.
+    BaseFeeMismatch { expected: U256, got: U256 },
+    /// The excess blob gas is not the one derived from the parent block.
+    ExcessBlobGasMismatch { expected: u64, got: u64 },
 }
 
 #[cfg(feature = "std")]
@@ -426,7 +693,50 @@ impl fmt::Display for InvalidHeader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::PrevrandaoNotSet => write!(f, "`prevrandao` not set"),
+            Self::DifficultyAfterMerge => write!(f, "`difficulty` set after the merge"),
             Self::ExcessBlobGasNotSet => write!(f, "`excess_blob_gas` not set"),
+            Self::NumberNotParentSuccessor => {
+                write!(f, "block number does not follow the parent block")
+            }
+            Self::TimestampNotIncreasing => {
+                write!(f, "timestamp is not greater than the parent timestamp")
+            }
+            Self::BaseFeeMismatch { expected, got } => {
+                write!(f, "base fee mismatch; expected {expected}, got {got}")
+            }
+            Self::ExcessBlobGasMismatch { expected, got } => {
+                write!(
+                    f,
+                    "excess blob gas mismatch; expected {expected}, got {got}"
+                )
+            }
+        }
+    }
+}
+
+/// Overflow of the conversions between gas and [`U256`] fees, see [`crate::u256_to_gas`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArithmeticError {
+    /// The value doesn't fit in the 64 bits of an amount of gas.
+    GasOverflow { value: U256 },
+    /// A gas refund is negative.
+    NegativeGas { value: i64 },
+    /// The fee of `gas` at `price` per gas overflows [`U256`].
+    FeeOverflow { price: U256, gas: u64 },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArithmeticError {}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GasOverflow { value } => write!(f, "gas overflow; {value} exceeds u64::MAX"),
+            Self::NegativeGas { value } => write!(f, "negative gas {value}"),
+            Self::FeeOverflow { price, gas } => {
+                write!(f, "fee overflow; {gas} gas at {price} per gas")
+            }
         }
     }
 }
@@ -434,6 +744,7 @@ impl fmt::Display for InvalidHeader {
 /// Reason a transaction successfully completed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum SuccessReason {
     Stop,
     Return,
@@ -445,9 +756,11 @@ pub enum SuccessReason {
 /// immediately end with all gas being consumed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum HaltReason {
     OutOfGas(OutOfGasError),
     OpcodeNotFound,
+    #[cfg_attr(feature = "serde", serde(rename = "invalid_fe_opcode"))]
     InvalidFEOpcode,
     InvalidJump,
     NotActivated,
@@ -460,6 +773,7 @@ pub enum HaltReason {
     /// Create init code size exceeds limit (runtime).
     CreateContractSizeLimit,
     /// Error on created contract that begins with EF
+    #[cfg_attr(feature = "serde", serde(rename = "create_contract_starting_with_ef"))]
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitCodeSizeLimit,
@@ -476,17 +790,70 @@ pub enum HaltReason {
     /// Aud data is smaller then already present data size.
     EofAuxDataTooSmall,
     /// EOF Subroutine stack overflow
+    #[cfg_attr(feature = "serde", serde(rename = "eof_function_stack_overflow"))]
     EOFFunctionStackOverflow,
     /// Check for target address validity is only done inside subcall.
+    #[cfg_attr(feature = "serde", serde(rename = "invalid_extcall_target"))]
     InvalidEXTCALLTarget,
+    /// Execution was cancelled from outside the EVM before it completed.
+    ///
+    /// The result doesn't reflect the transaction and should be discarded.
+    Cancelled,
+    /// Output exceeds the [configured return data limit](crate::CfgEnv::max_return_data_size).
+    ReturnDataSizeLimit,
+    /// Called code starts with the EOF magic but is not a valid EOF container.
+    #[cfg_attr(feature = "serde", serde(rename = "invalid_eof_code"))]
+    InvalidEOFCode,
 
     /* Optimism errors */
+    /// Deposit transaction failed, see [OptimismInvalidTransaction::HaltedDepositPostRegolith].
+    ///
+    /// The cause is diagnostic only, the failed deposit is included with the same state
+    /// changes and gas used whatever the cause is.
     #[cfg(feature = "optimism")]
-    FailedDeposit,
+    FailedDeposit(FailedDepositCause),
+}
+
+/// Why a deposit transaction failed, carried by [HaltReason::FailedDeposit].
+#[cfg(feature = "optimism")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FailedDepositCause {
+    /// Execution ran out of gas.
+    OutOfGas(OutOfGasError),
+    /// Execution halted for a reason other than running out of gas.
+    Halt,
+    /// Execution emitted an interop executing message that was rejected.
+    InvalidExecutingMessage,
+    /// Transaction failed validation before execution.
+    InvalidTransaction,
+}
+
+#[cfg(feature = "optimism")]
+impl FailedDepositCause {
+    /// Returns the cause of a deposit that failed with the given transaction error.
+    pub fn from_invalid_transaction(error: &InvalidTransaction) -> Self {
+        match error {
+            InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::HaltedDepositPostRegolith {
+                    reason: HaltReason::OutOfGas(error),
+                },
+            ) => Self::OutOfGas(*error),
+            InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::HaltedDepositPostRegolith { .. },
+            ) => Self::Halt,
+            InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::InvalidExecutingMessage,
+            ) => Self::InvalidExecutingMessage,
+            _ => Self::InvalidTransaction,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum OutOfGasError {
     // Basic OOG error
     Basic,
@@ -500,3 +867,212 @@ pub enum OutOfGasError {
     // i.e. in `as_usize_or_fail`
     InvalidOperand,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{
+        address, b256, bytes, Account, AccountInfo, AccountStatus, EvmStorageSlot, HashMap,
+        LogData, KECCAK_EMPTY,
+    };
+    use std::vec;
+
+    const GOLDEN: &str = include_str!("../testdata/result_and_state.json");
+
+    fn result_and_state() -> ResultAndState {
+        let address = address!("1000000000000000000000000000000000000001");
+        let log = Log {
+            address,
+            data: LogData::new_unchecked(
+                vec![b256!(
+                    "0000000000000000000000000000000000000000000000000000000000000001"
+                )],
+                bytes!("0102"),
+            ),
+        };
+        let account = Account {
+            info: AccountInfo {
+                balance: U256::from(1_000_000_000_000_000_000u64),
+                nonce: 1,
+                code_hash: KECCAK_EMPTY,
+                code: None,
+            },
+            storage: HashMap::from_iter([(
+                U256::from(1),
+                EvmStorageSlot::new_changed(U256::ZERO, U256::from(42)),
+            )]),
+            status: AccountStatus::Created | AccountStatus::Touched,
+        };
+        ResultAndState {
+            result: ExecutionResult::Success {
+                reason: SuccessReason::Return,
+                gas_used: 53000,
+                gas_refunded: 4800,
+                logs: vec![log],
+                output: Output::Create(bytes!("6000"), Some(address)),
+            },
+            state: HashMap::from_iter([(address, account)]),
+        }
+    }
+
+    #[test]
+    fn result_and_state_golden() {
+        let expected = result_and_state();
+        let golden: serde_json::Value = serde_json::from_str(GOLDEN).unwrap();
+        assert_eq!(serde_json::to_value(&expected).unwrap(), golden);
+
+        let decoded: ResultAndState = serde_json::from_str(GOLDEN).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn execution_result_round_trip() {
+        let results = [
+            ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                gas_used: 21000,
+                gas_refunded: 0,
+                logs: vec![],
+                output: Output::Call(Bytes::new()),
+            },
+            ExecutionResult::Revert {
+                gas_used: 30000,
+                output: bytes!("08c379a0"),
+            },
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Memory),
+                gas_used: 100000,
+                context: None,
+            },
+            ExecutionResult::Halt {
+                reason: HaltReason::InvalidFEOpcode,
+                gas_used: 100000,
+                context: Some(HaltContext {
+                    address: address!("1000000000000000000000000000000000000001"),
+                    pc: 3,
+                    opcode: 0xfe,
+                }),
+            },
+        ];
+        for result in results {
+            let json = serde_json::to_string(&result).unwrap();
+            assert_eq!(
+                serde_json::from_str::<ExecutionResult>(&json).unwrap(),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn enum_tags() {
+        let cases = [
+            (
+                serde_json::to_string(&ExecutionResult::Halt {
+                    reason: HaltReason::OutOfGas(OutOfGasError::InvalidOperand),
+                    gas_used: 1,
+                    context: None,
+                }),
+                r#"{"halt":{"reason":{"out_of_gas":"invalid_operand"},"gas_used":1}}"#,
+            ),
+            (
+                serde_json::to_string(&ExecutionResult::Halt {
+                    reason: HaltReason::StackUnderflow,
+                    gas_used: 1,
+                    context: Some(HaltContext {
+                        address: Address::ZERO,
+                        pc: 2,
+                        opcode: 0x01,
+                    }),
+                }),
+                r#"{"halt":{"reason":"stack_underflow","gas_used":1,"context":{"address":"0x0000000000000000000000000000000000000000","pc":2,"opcode":1}}}"#,
+            ),
+            (
+                serde_json::to_string(&ExecutionResult::Revert {
+                    gas_used: 1,
+                    output: bytes!("ff"),
+                }),
+                r#"{"revert":{"gas_used":1,"output":"0xff"}}"#,
+            ),
+            (
+                serde_json::to_string(&Output::Call(bytes!("00"))),
+                r#"{"call":"0x00"}"#,
+            ),
+            (
+                serde_json::to_string(&Output::Create(Bytes::new(), None)),
+                r#"{"create":["0x",null]}"#,
+            ),
+            (
+                serde_json::to_string(&SuccessReason::EofReturnContract),
+                r#""eof_return_contract""#,
+            ),
+            (
+                serde_json::to_string(&HaltReason::InvalidFEOpcode),
+                r#""invalid_fe_opcode""#,
+            ),
+            (
+                serde_json::to_string(&HaltReason::CreateContractStartingWithEF),
+                r#""create_contract_starting_with_ef""#,
+            ),
+            (
+                serde_json::to_string(&HaltReason::EOFFunctionStackOverflow),
+                r#""eof_function_stack_overflow""#,
+            ),
+            (
+                serde_json::to_string(&HaltReason::InvalidEXTCALLTarget),
+                r#""invalid_extcall_target""#,
+            ),
+        ];
+        for (json, expected) in cases {
+            assert_eq!(json.unwrap(), expected);
+        }
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn failed_deposit_cause_tags() {
+        let cases = [
+            (
+                HaltReason::FailedDeposit(FailedDepositCause::OutOfGas(OutOfGasError::Basic)),
+                r#"{"failed_deposit":{"out_of_gas":"basic"}}"#,
+            ),
+            (
+                HaltReason::FailedDeposit(FailedDepositCause::InvalidExecutingMessage),
+                r#"{"failed_deposit":"invalid_executing_message"}"#,
+            ),
+        ];
+        for (reason, expected) in cases {
+            assert_eq!(serde_json::to_string(&reason).unwrap(), expected);
+            assert_eq!(
+                serde_json::from_str::<HaltReason>(expected).unwrap(),
+                reason
+            );
+        }
+    }
+
+    #[test]
+    fn state_is_serialized_in_key_order() {
+        let mut account = Account::default();
+        for slot in [3u64, 1, 2] {
+            account
+                .storage
+                .insert(U256::from(slot), EvmStorageSlot::new(U256::ZERO));
+        }
+        let state: EvmState = HashMap::from_iter(
+            [3u8, 1, 2].map(|byte| (Address::with_last_byte(byte), account.clone())),
+        );
+        let json = serde_json::to_string(&ResultAndState {
+            result: ExecutionResult::Revert {
+                gas_used: 0,
+                output: Bytes::new(),
+            },
+            state,
+        })
+        .unwrap();
+
+        let position = |needle: &str| json.find(needle).unwrap();
+        assert!(position("01\":") < position("02\":"));
+        assert!(position("02\":") < position("03\":"));
+        assert!(position("\"0x1\"") < position("\"0x2\""));
+        assert!(position("\"0x2\"") < position("\"0x3\""));
+    }
+}
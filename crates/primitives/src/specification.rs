@@ -629,4 +629,17 @@ mod optimism_tests {
         }
         assert!(!SpecId::enabled(SpecId::HOLOCENE, SpecId::LATEST));
     }
+
+    #[test]
+    fn test_osaka_post_isthmus_hardforks() {
+        // from MERGE to ISTHMUS
+        for i in 15..=26 {
+            if let Some(spec) = SpecId::try_from_u8(i) {
+                assert!(OsakaSpec::enabled(spec));
+            }
+        }
+        assert!(OsakaSpec::enabled(SpecId::PRAGUE));
+        assert!(!IsthmusSpec::enabled(SpecId::OSAKA));
+        assert!(!OsakaSpec::enabled(SpecId::LATEST));
+    }
 }
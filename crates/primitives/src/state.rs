@@ -17,6 +17,7 @@ pub struct Account {
     /// Balance, nonce, and code.
     pub info: AccountInfo,
     /// Storage cache
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sorted"))]
     pub storage: EvmStorage,
     /// Account status flags.
     pub status: AccountStatus,
@@ -24,9 +25,16 @@ pub struct Account {
 
 // The `bitflags!` macro generates `struct`s that manage a set of flags.
 bitflags! {
+    /// Serialized as the list of set flag names, see [`AccountStatus::FLAG_NAMES`].
+    ///
+    /// The accounts of a finalized state always satisfy:
+    ///
+    /// - a created account is touched,
+    /// - a self destructed account is touched, and from Cancun it is also created, see
+    ///   [EIP-6780](https://eips.ethereum.org/EIPS/eip-6780),
+    /// - an account that is not touched has no changed storage slot, and is empty if it was loaded
+    ///   as not existing.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-    #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct AccountStatus: u8 {
         /// When account is loaded but not touched or interacted with.
         /// This is the default state.
@@ -52,6 +60,65 @@ impl Default for AccountStatus {
     }
 }
 
+impl AccountStatus {
+    /// Names of the flags as used in the serialized representation.
+    ///
+    /// [`AccountStatus::Loaded`] has no bits set and is represented by an empty list.
+    pub const FLAG_NAMES: [(Self, &'static str); 5] = [
+        (Self::Created, "created"),
+        (Self::SelfDestructed, "self_destructed"),
+        (Self::Touched, "touched"),
+        (Self::LoadedAsNotExisting, "loaded_as_not_existing"),
+        (Self::Cold, "cold"),
+    ];
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AccountStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            Self::FLAG_NAMES
+                .iter()
+                .filter(|(flag, _)| self.contains(*flag))
+                .map(|(_, name)| name),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AccountStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = <std::vec::Vec<std::string::String>>::deserialize(deserializer)?;
+        names.iter().try_fold(Self::Loaded, |status, name| {
+            Self::FLAG_NAMES
+                .iter()
+                .find(|(_, flag_name)| flag_name == name)
+                .map(|(flag, _)| status | *flag)
+                .ok_or_else(|| {
+                    serde::de::Error::custom(format_args!("unknown account status `{name}`"))
+                })
+        })
+    }
+}
+
+/// Serializes a map with its entries sorted by key, so the output does not depend on the
+/// iteration order of the [`HashMap`].
+///
+/// Meant to be used with `#[serde(serialize_with = "...")]` on map fields.
+#[cfg(feature = "serde")]
+pub fn serialize_sorted<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Ord + serde::Serialize + 'a,
+    V: serde::Serialize + 'a,
+    S: serde::Serializer,
+{
+    serializer.collect_map(
+        map.into_iter()
+            .collect::<std::collections::BTreeMap<_, _>>(),
+    )
+}
+
 impl Account {
     /// Create new account and mark it as non existing.
     pub fn new_not_existing() -> Self {
@@ -85,10 +152,23 @@ impl Account {
     }
 
     /// Is account marked for self destruct.
+    ///
+    /// Same as [`Account::was_destroyed`].
     pub fn is_selfdestructed(&self) -> bool {
         self.status.contains(AccountStatus::SelfDestructed)
     }
 
+    /// Is account destroyed by a `SELFDESTRUCT` of this transaction that was not reverted.
+    ///
+    /// From Cancun only accounts created in the same transaction are destroyed, see
+    /// [EIP-6780](https://eips.ethereum.org/EIPS/eip-6780). A destroyed account is touched, its
+    /// account and storage are removed when the state is committed, even if it received balance
+    /// after the `SELFDESTRUCT`.
+    #[inline]
+    pub fn was_destroyed(&self) -> bool {
+        self.status.contains(AccountStatus::SelfDestructed)
+    }
+
     /// Mark account as touched
     pub fn mark_touch(&mut self) {
         self.status |= AccountStatus::Touched;
@@ -132,20 +212,47 @@ impl Account {
     /// Is account loaded as not existing from database
     /// This is needed for pre spurious dragon hardforks where
     /// existing and empty were two separate states.
+    ///
+    /// The flag is kept when the account is created or receives balance in the transaction, it
+    /// always describes the account in the database. An account loaded as not existing that is
+    /// not touched is empty.
     pub fn is_loaded_as_not_existing(&self) -> bool {
         self.status.contains(AccountStatus::LoadedAsNotExisting)
     }
 
     /// Is account newly created in this transaction.
+    ///
+    /// Same as [`Account::is_created_this_tx`].
     pub fn is_created(&self) -> bool {
         self.status.contains(AccountStatus::Created)
     }
 
+    /// Is account created in this transaction, by a create transaction or a `CREATE`, `CREATE2`
+    /// or `EOFCREATE` that was not reverted.
+    ///
+    /// The storage of a created account is not read from the database, and the account is
+    /// touched. An account created again after being destroyed in an earlier transaction is
+    /// created in this one only.
+    #[inline]
+    pub fn is_created_this_tx(&self) -> bool {
+        self.status.contains(AccountStatus::Created)
+    }
+
     /// Is account empty, check if nonce and balance are zero and code is empty.
     pub fn is_empty(&self) -> bool {
         self.info.is_empty()
     }
 
+    /// Is account touched and empty at the spec.
+    ///
+    /// From Spurious Dragon such accounts are deleted when the state is committed, see
+    /// [EIP-161](https://eips.ethereum.org/EIPS/eip-161). Before it touched empty accounts are
+    /// kept, only the accounts that were not existing and stay untouched are not created.
+    #[inline]
+    pub fn is_touched_empty(&self, spec: SpecId) -> bool {
+        SpecId::enabled(spec, SpecId::SPURIOUS_DRAGON) && self.is_touched() && self.is_empty()
+    }
+
     /// Returns an iterator over the storage slots that have been changed.
     ///
     /// See also [EvmStorageSlot::is_changed]
@@ -168,7 +275,9 @@ impl From<AccountInfo> for Account {
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EvmStorageSlot {
-    /// Original value of the storage slot.
+    /// Original value of the storage slot, as it was at the start of the transaction.
+    ///
+    /// Zero for the storage of accounts created in the transaction.
     pub original_value: U256,
     /// Present value of the storage slot.
     pub present_value: U256,
@@ -194,7 +303,9 @@ impl EvmStorageSlot {
             is_cold: false,
         }
     }
-    /// Returns true if the present value differs from the original value
+    /// Returns true if the present value differs from the original value.
+    ///
+    /// A slot written and then restored to its original value is unchanged.
     pub fn is_changed(&self) -> bool {
         self.original_value != self.present_value
     }
@@ -304,6 +415,17 @@ impl AccountInfo {
         self
     }
 
+    /// Sets the [`Bytecode`] of this account and its code hash.
+    ///
+    /// Accounts returned from [`Database::basic`](crate::db::Database::basic)
+    /// with the code set are never followed by a `code_by_hash` call for
+    /// their code, which saves a round trip for remote databases.
+    pub fn with_code(mut self, code: Bytecode) -> Self {
+        self.code_hash = code.hash_slow();
+        self.code = Some(code);
+        self
+    }
+
     /// Returns if an account is empty.
     ///
     /// An account is empty if the following conditions are met.
@@ -363,7 +485,7 @@ impl AccountInfo {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Account, KECCAK_EMPTY, U256};
+    use crate::{Account, SpecId, KECCAK_EMPTY, U256};
 
     #[test]
     fn account_is_empty_balance() {
@@ -389,6 +511,19 @@ mod tests {
         assert!(account.is_empty());
     }
 
+    #[test]
+    fn account_is_touched_empty() {
+        let mut account = Account::new_not_existing();
+        assert!(!account.is_touched_empty(SpecId::CANCUN));
+
+        account.mark_touch();
+        assert!(account.is_touched_empty(SpecId::SPURIOUS_DRAGON));
+        assert!(!account.is_touched_empty(SpecId::TANGERINE));
+
+        account.info.nonce = 1;
+        assert!(!account.is_touched_empty(SpecId::CANCUN));
+    }
+
     #[test]
     fn account_is_empty_code_hash() {
         let mut account = Account::default();
@@ -443,4 +578,30 @@ mod tests {
         // When marking cold account as warm, it should return true
         assert!(account.mark_warm());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn account_status_serde() {
+        use crate::AccountStatus;
+
+        let cases = [
+            (AccountStatus::Loaded, "[]"),
+            (AccountStatus::Touched, r#"["touched"]"#),
+            (
+                AccountStatus::Created | AccountStatus::SelfDestructed | AccountStatus::Cold,
+                r#"["created","self_destructed","cold"]"#,
+            ),
+            (
+                AccountStatus::all(),
+                r#"["created","self_destructed","touched","loaded_as_not_existing","cold"]"#,
+            ),
+        ];
+        for (status, json) in cases {
+            assert_eq!(serde_json::to_string(&status).unwrap(), json);
+            assert_eq!(serde_json::from_str::<AccountStatus>(json).unwrap(), status);
+        }
+
+        assert!(serde_json::from_str::<AccountStatus>(r#"["warm"]"#).is_err());
+        assert!(serde_json::from_str::<AccountStatus>("4").is_err());
+    }
 }
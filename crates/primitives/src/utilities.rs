@@ -1,6 +1,6 @@
 use crate::{
-    b256, B256, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN, BLOB_BASE_FEE_UPDATE_FRACTION_ELECTRA,
-    MIN_BLOB_GASPRICE,
+    b256, Address, B256, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN,
+    BLOB_BASE_FEE_UPDATE_FRACTION_ELECTRA, MIN_BLOB_GASPRICE, U256,
 };
 pub use alloy_primitives::keccak256;
 
@@ -8,6 +8,31 @@ pub use alloy_primitives::keccak256;
 pub const KECCAK_EMPTY: B256 =
     b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
 
+/// Returns the address of a contract created with `CREATE` or a create transaction.
+///
+/// `keccak256(rlp([caller, nonce]))[12:]`, where `nonce` is the caller nonce before the creation.
+#[inline]
+pub fn create_address(caller: Address, nonce: u64) -> Address {
+    caller.create(nonce)
+}
+
+/// Returns the address of a contract created with `CREATE2`.
+///
+/// `keccak256(0xff ++ caller ++ salt ++ init_code_hash)[12:]`, see
+/// [EIP-1014](https://eips.ethereum.org/EIPS/eip-1014).
+#[inline]
+pub fn create2_address(caller: Address, salt: U256, init_code_hash: B256) -> Address {
+    caller.create2(salt.to_be_bytes(), init_code_hash)
+}
+
+/// Returns the address of a contract created with `EOFCREATE`.
+///
+/// Derived the same way as [`create2_address`], with the hash of the initcontainer.
+#[inline]
+pub fn eofcreate_address(caller: Address, salt: U256, initcontainer: &[u8]) -> Address {
+    create2_address(caller, salt, keccak256(initcontainer))
+}
+
 /// Calculates the `excess_blob_gas` from the parent header's `blob_gas_used` and `excess_blob_gas`.
 ///
 /// See also [the EIP-4844 helpers]<https://eips.ethereum.org/EIPS/eip-4844#helpers>
@@ -48,36 +73,148 @@ pub fn calc_blob_gasprice(excess_blob_gas: u64, is_prague: bool) -> u128 {
 /// See also [the EIP-4844 helpers](https://eips.ethereum.org/EIPS/eip-4844#helpers)
 /// (`fake_exponential`).
 ///
+/// The intermediate values are computed in 256 bits and the result saturates at `u128::MAX`,
+/// so it never overflows whatever the inputs.
+///
 /// # Panics
 ///
 /// This function panics if `denominator` is zero.
 #[inline]
 pub fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> u128 {
     assert_ne!(denominator, 0, "attempt to divide by zero");
-    let factor = factor as u128;
-    let numerator = numerator as u128;
-    let denominator = denominator as u128;
+    let factor = U256::from(factor);
+    let numerator = U256::from(numerator);
+    let denominator = U256::from(denominator);
+    // The output is at most `limit` as long as the result fits in a `u128`. The accumulator is
+    // then below 2^192 and its product with the 64-bit numerator fits in 256 bits.
+    let limit = U256::from(u128::MAX) * denominator + (denominator - U256::from(1));
 
-    let mut i = 1;
-    let mut output = 0;
+    let mut i = U256::from(1);
+    let mut output = U256::ZERO;
     let mut numerator_accum = factor * denominator;
-    while numerator_accum > 0 {
-        output += numerator_accum;
+    while numerator_accum > U256::ZERO {
+        output = output.saturating_add(numerator_accum);
+        if output > limit {
+            return u128::MAX;
+        }
 
         // Denominator is asserted as not zero at the start of the function.
         numerator_accum = (numerator_accum * numerator) / (denominator * i);
-        i += 1;
+        i += U256::from(1);
     }
-    output / denominator
+    (output / denominator).to()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::GAS_PER_BLOB;
+    use alloy_primitives::U512;
+    use core::str::FromStr;
+    use proptest::prelude::*;
 
     const TARGET_BLOB_GAS_PER_BLOCK: u64 = 3 * GAS_PER_BLOB;
 
+    #[test]
+    fn test_create_address() {
+        let caller = crate::address!("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        for (nonce, expected) in [
+            (
+                0,
+                crate::address!("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"),
+            ),
+            (
+                1,
+                crate::address!("343c43a37d37dff08ae8c4a11544c718abb4fcf8"),
+            ),
+            (
+                2,
+                crate::address!("f778b86fa74e846c4f0a1fbd1335fe81c00a0c91"),
+            ),
+            (
+                3,
+                crate::address!("fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c"),
+            ),
+        ] {
+            assert_eq!(create_address(caller, nonce), expected, "nonce: {nonce}");
+        }
+    }
+
+    // https://eips.ethereum.org/EIPS/eip-1014#examples
+    #[test]
+    fn test_create2_address() {
+        for (caller, salt, init_code, expected) in [
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38",
+            ),
+            (
+                "deadbeef00000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "B928f69Bb1D91Cd65274e3c79d8986362984fDA3",
+            ),
+            (
+                "deadbeef00000000000000000000000000000000",
+                "000000000000000000000000feed000000000000000000000000000000000000",
+                "00",
+                "D04116cDd17beBE565EB2422F2497E06cC1C9833",
+            ),
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "deadbeef",
+                "70f2b2914A2a4b783FaEFb75f459A580616Fcb5e",
+            ),
+            (
+                "00000000000000000000000000000000deadbeef",
+                "00000000000000000000000000000000000000000000000000000000cafebabe",
+                "deadbeef",
+                "60f3f640a8508fC6a86d45DF051962668E1e8AC7",
+            ),
+            (
+                "00000000000000000000000000000000deadbeef",
+                "00000000000000000000000000000000000000000000000000000000cafebabe",
+                "deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+                "1d8bfDC5D46DC4f61D6b6115972536eBE6A8854C",
+            ),
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "",
+                "E33C0C7F7df4809055C3ebA6c09CFe4BaF1BD9e0",
+            ),
+        ] {
+            let caller: Address = caller.parse().unwrap();
+            let salt = U256::from_be_bytes(B256::from_str(salt).unwrap().0);
+            let init_code = crate::hex::decode(init_code).unwrap();
+            let expected: Address = expected.parse().unwrap();
+            assert_eq!(
+                create2_address(caller, salt, keccak256(&init_code)),
+                expected
+            );
+            assert_eq!(eofcreate_address(caller, salt, &init_code), expected);
+        }
+    }
+
+    #[test]
+    fn test_create2_address_uniswap_v2_pair() {
+        // USDC/WETH pair deployed by the Uniswap V2 factory,
+        // salt is `keccak256(abi.encodePacked(token0, token1))`.
+        let factory = crate::address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f");
+        let init_code_hash =
+            b256!("96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f");
+        let usdc = crate::address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let weth = crate::address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let salt = U256::from_be_bytes(keccak256([usdc.as_slice(), weth.as_slice()].concat()).0);
+        assert_eq!(
+            create2_address(factory, salt, init_code_hash),
+            crate::address!("B4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc")
+        );
+    }
+
     // https://github.com/ethereum/go-ethereum/blob/28857080d732857030eda80c69b9ba2c8926f221/consensus/misc/eip4844/eip4844_test.go#L27
     #[test]
     fn test_calc_excess_blob_gas() {
@@ -182,4 +319,65 @@ mod tests {
             assert_eq!(actual, expected, "test: {t:?}");
         }
     }
+
+    #[test]
+    fn fake_exp_saturates() {
+        for t @ &(factor, numerator, denominator, expected) in &[
+            (u64::MAX, 0, u64::MAX, u64::MAX as u128),
+            (1, u64::MAX, u64::MAX, 2),
+            (u64::MAX, u64::MAX, u64::MAX, 50143449209799256680),
+            (u64::MAX, u64::MAX, 1, u128::MAX),
+            (1, u64::MAX, 1, u128::MAX),
+        ] {
+            let actual = fake_exponential(factor, numerator, denominator);
+            assert_eq!(actual, expected, "test: {t:?}");
+        }
+        assert_eq!(calc_blob_gasprice(u64::MAX, false), u128::MAX);
+        assert_eq!(calc_blob_gasprice(u64::MAX, true), u128::MAX);
+    }
+
+    /// The EIP-4844 `fake_exponential` computed in 512 bits.
+    fn reference_fake_exponential(factor: u64, numerator: u64, denominator: u64) -> U512 {
+        let numerator = U512::from(numerator);
+        let denominator = U512::from(denominator);
+        let mut i = U512::from(1);
+        let mut output = U512::ZERO;
+        let mut numerator_accum = U512::from(factor) * denominator;
+        while numerator_accum > U512::ZERO {
+            output += numerator_accum;
+            numerator_accum = (numerator_accum * numerator) / (denominator * i);
+            i += U512::from(1);
+        }
+        output / denominator
+    }
+
+    proptest! {
+        #[test]
+        fn fake_exp_matches_reference(
+            factor in any::<u64>(),
+            numerator in any::<u64>(),
+            denominator in 1..=u64::MAX,
+        ) {
+            // Bounds the result below 2^249 so the reference can't overflow.
+            prop_assume!(numerator / denominator < 128);
+            let expected = reference_fake_exponential(factor, numerator, denominator);
+            let expected = if expected > U512::from(u128::MAX) {
+                u128::MAX
+            } else {
+                expected.to()
+            };
+            prop_assert_eq!(fake_exponential(factor, numerator, denominator), expected);
+        }
+
+        #[test]
+        fn fake_exp_is_monotonic(
+            factor in any::<u64>(),
+            numerator in any::<u64>(),
+            denominator in 1..=u64::MAX,
+        ) {
+            let actual = fake_exponential(factor, numerator, denominator);
+            prop_assert!(actual >= factor as u128);
+            prop_assert!(actual >= fake_exponential(factor, numerator / 2, denominator));
+        }
+    }
 }
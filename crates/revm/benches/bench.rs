@@ -3,12 +3,17 @@ use criterion::{
 };
 use revm::{
     db::BenchmarkDB,
-    interpreter::{analysis::to_analysed, Contract, DummyHost, Interpreter},
+    interpreter::{
+        analysis::to_analysed, gas::GasPricingOverride, Contract, DummyHost, Interpreter,
+    },
     primitives::{address, bytes, hex, BerlinSpec, Bytecode, Bytes, TxKind, U256},
     Evm,
 };
 use revm_interpreter::{opcode::make_instruction_table, SharedMemory, EMPTY_SHARED_MEMORY};
-use std::time::Duration;
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
 fn analysis(c: &mut Criterion) {
     let evm = Evm::builder()
@@ -65,6 +70,81 @@ fn snailtracer(c: &mut Criterion) {
     g.finish();
 }
 
+fn gas_pricing(c: &mut Criterion) {
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode(SNAILTRACER)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("1000000000000000000000000000000000000000");
+            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.data = bytes!("30627b7c");
+        })
+        .build();
+
+    let mut g = c.benchmark_group("gas_pricing");
+    g.noise_threshold(0.03)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+    g.bench_function("transact/none", |b| b.iter(|| evm.transact().unwrap()));
+
+    // An override without changes only adds the cost of consulting it.
+    let mut evm = evm
+        .modify()
+        .with_gas_pricing(Arc::new(GasPricingOverride::new()))
+        .build();
+    g.bench_function("transact/unchanged", |b| b.iter(|| evm.transact().unwrap()));
+    g.finish();
+}
+
+fn cancellation(c: &mut Criterion) {
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode(SNAILTRACER)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("1000000000000000000000000000000000000000");
+            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.data = bytes!("30627b7c");
+        })
+        .build();
+
+    let mut g = c.benchmark_group("cancellation");
+    g.noise_threshold(0.03)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+    g.bench_function("transact/none", |b| b.iter(|| evm.transact().unwrap()));
+
+    // A token that is never set only adds the cost of checking it every 1024 instructions.
+    let mut evm = evm
+        .modify()
+        .with_cancellation(Arc::new(AtomicBool::new(false)))
+        .build();
+    g.bench_function("transact/1024", |b| b.iter(|| evm.transact().unwrap()));
+    g.finish();
+}
+
+fn stack_limit(c: &mut Criterion) {
+    let mut evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode(SNAILTRACER)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("1000000000000000000000000000000000000000");
+            tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+            tx.data = bytes!("30627b7c");
+        })
+        .build();
+
+    let mut g = c.benchmark_group("stack_limit");
+    g.noise_threshold(0.03)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+    g.bench_function("transact/default", |b| b.iter(|| evm.transact().unwrap()));
+
+    // The default path must not be slower than an explicitly configured limit.
+    evm.cfg_mut().stack_limit = Some(2048);
+    g.bench_function("transact/2048", |b| b.iter(|| evm.transact().unwrap()));
+    g.finish();
+}
+
 fn transfer(c: &mut Criterion) {
     let mut evm = Evm::builder()
         .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
@@ -124,6 +204,9 @@ criterion_group!(
     benches,
     analysis,
     snailtracer,
+    gas_pricing,
+    cancellation,
+    stack_limit,
     transfer,
 );
 criterion_main!(benches);
@@ -0,0 +1,192 @@
+//! Workload setup shared by the benchmarks.
+//!
+//! Every workload is a contract deployed at [`CONTRACT`] and a set of funded [`caller`]s, so a new
+//! benchmark only needs its bytecode and a transaction.
+//!
+//! Each benchmark uses only some of the workloads.
+#![allow(dead_code)]
+
+use criterion::{measurement::WallTime, BenchmarkGroup};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    interpreter::opcode::*,
+    primitives::{AccountInfo, Address, Bytecode, Bytes, SpecId, TxKind, U256},
+    Evm,
+};
+use std::time::Duration;
+
+/// Address the workload contract is deployed at.
+pub(crate) const CONTRACT: Address = Address::new([0xc0; 20]);
+
+/// Balance of every caller and token holder.
+pub(crate) const INITIAL_BALANCE: u64 = 1_000_000_000_000_000_000;
+
+/// Returns the address of the `i`-th caller.
+pub(crate) fn caller(i: usize) -> Address {
+    let mut address = [0u8; 20];
+    address[0] = 0x10;
+    address[12..].copy_from_slice(&(i as u64).to_be_bytes());
+    Address::new(address)
+}
+
+/// Returns a database with `code` deployed at [`CONTRACT`] and `callers` funded callers.
+///
+/// Callers also hold [`INITIAL_BALANCE`] tokens of the [`token`] contract, their balance is
+/// stored in the slot of their address.
+pub(crate) fn db_with_contract(code: Bytes, callers: usize) -> CacheDB<EmptyDB> {
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(
+        CONTRACT,
+        AccountInfo::from_bytecode(Bytecode::new_raw(code)),
+    );
+    for i in 0..callers {
+        let caller = caller(i);
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(INITIAL_BALANCE)),
+        );
+        db.insert_account_storage(
+            CONTRACT,
+            U256::from_be_bytes(caller.into_word().0),
+            U256::from(INITIAL_BALANCE),
+        )
+        .unwrap();
+    }
+    db
+}
+
+/// Returns a Cancun [`Evm`] calling [`CONTRACT`] from the first caller.
+pub(crate) fn evm<DB: revm::Database>(db: DB) -> Evm<'static, (), DB> {
+    Evm::builder()
+        .with_db(db)
+        .with_spec_id(SpecId::CANCUN)
+        .modify_tx_env(|tx| {
+            tx.caller = caller(0);
+            tx.transact_to = TxKind::Call(CONTRACT);
+            tx.gas_limit = 30_000_000;
+        })
+        .build()
+}
+
+/// Configures a group for workloads that take milliseconds per iteration.
+pub(crate) fn configure_group(g: &mut BenchmarkGroup<'_, WallTime>) {
+    g.noise_threshold(0.03)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+}
+
+/// ERC-20 style token, transfers the amount in the second calldata word to the address in the
+/// first one. Reverts if the caller balance is too low.
+#[rustfmt::skip]
+pub(crate) fn token() -> Bytes {
+    Bytes::from(vec![
+        CALLER, SLOAD, // from balance
+        PUSH1, 0x20, CALLDATALOAD, // amount
+        DUP2, DUP2, GT, PUSH1, 0x1a, JUMPI, // revert if amount > from balance
+        DUP1, SWAP2, SUB, CALLER, SSTORE, // from balance -= amount
+        PUSH1, 0x00, CALLDATALOAD, // to
+        DUP1, SLOAD, DUP3, ADD, SWAP1, SSTORE, // to balance += amount
+        STOP, //
+        JUMPDEST, PUSH1, 0x00, DUP1, REVERT,
+    ])
+}
+
+/// Calldata of a [`token`] transfer.
+pub(crate) fn transfer_calldata(to: Address, amount: U256) -> Bytes {
+    let mut data = to.into_word().to_vec();
+    data.extend_from_slice(&amount.to_be_bytes::<32>());
+    data.into()
+}
+
+/// Returns the [`token`] balance of the address in the first calldata word, a view call.
+#[rustfmt::skip]
+pub(crate) fn balance_of() -> Bytes {
+    Bytes::from(vec![
+        PUSH0, CALLDATALOAD, SLOAD, PUSH0, MSTORE, PUSH1, 0x20, PUSH0, RETURN,
+    ])
+}
+
+/// Calls itself with all the available gas until it runs out of gas or hits the depth limit.
+pub(crate) fn recursion() -> Bytes {
+    Bytes::from(vec![
+        PUSH1, 0x00, DUP1, DUP1, DUP1, DUP1, ADDRESS, GAS, CALL, STOP,
+    ])
+}
+
+/// Calls itself with the number in the first calldata word decremented, until it reaches zero.
+#[rustfmt::skip]
+pub(crate) fn call_chain() -> Bytes {
+    Bytes::from(vec![
+        PUSH0, CALLDATALOAD, DUP1, ISZERO, PUSH1, 0x16, JUMPI,
+        // Store `depth - 1` as calldata of the next call.
+        PUSH1, 0x01, SWAP1, SUB, PUSH0, MSTORE,
+        PUSH0, PUSH0, PUSH1, 0x20, PUSH0, PUSH0, ADDRESS, GAS, CALL,
+        JUMPDEST, STOP,
+    ])
+}
+
+/// Hashes the first 1 MiB of memory.
+pub(crate) fn keccak_memory() -> Bytes {
+    Bytes::from(vec![PUSH3, 0x10, 0x00, 0x00, PUSH0, KECCAK256, STOP])
+}
+
+/// Copies the calldata to memory.
+pub(crate) fn calldata_copy() -> Bytes {
+    Bytes::from(vec![CALLDATASIZE, PUSH0, PUSH0, CALLDATACOPY, STOP])
+}
+
+/// Copies as many bytes of the code as the calldata size to memory, zero filling all but the
+/// few bytes of code.
+pub(crate) fn code_copy() -> Bytes {
+    Bytes::from(vec![CALLDATASIZE, PUSH0, PUSH0, CODECOPY, STOP])
+}
+
+/// Copies as many bytes of memory as the calldata size one word forward, overlapping itself.
+pub(crate) fn memory_copy() -> Bytes {
+    Bytes::from(vec![CALLDATASIZE, PUSH0, PUSH1, 0x20, MCOPY, STOP])
+}
+
+/// Calls the identity precompile without input.
+pub(crate) fn identity_call() -> Bytes {
+    Bytes::from(vec![
+        PUSH1, 0x00, DUP1, DUP1, DUP1, PUSH1, 0x04, GAS, STATICCALL, STOP,
+    ])
+}
+
+/// Calls itself with one byte of calldata, the inner call returning `size` zero bytes, and copies
+/// the last word of the return data to memory.
+#[rustfmt::skip]
+pub(crate) fn return_data(size: u32) -> Bytes {
+    let size = size.to_be_bytes();
+    assert_eq!(size[0], 0, "size must fit in three bytes");
+    Bytes::from(vec![
+        CALLDATASIZE, PUSH1, 0x17, JUMPI, // the inner call returns
+        PUSH0, PUSH0, PUSH1, 0x01, PUSH0, PUSH0, ADDRESS, GAS, CALL, POP,
+        PUSH1, 0x20, PUSH1, 0x20, RETURNDATASIZE, SUB, PUSH0, RETURNDATACOPY, STOP,
+        JUMPDEST, PUSH3, size[1], size[2], size[3], PUSH0, RETURN,
+    ])
+}
+
+/// Writes `slot + 1` to each of the slots below the number in the first calldata word.
+#[rustfmt::skip]
+pub(crate) fn sstore_loop() -> Bytes {
+    Bytes::from(vec![
+        PUSH1, 0x00, CALLDATALOAD, // counter
+        JUMPDEST, DUP1, ISZERO, PUSH1, 0x16, JUMPI, // stop at zero
+        PUSH1, 0x01, SWAP1, SUB, // counter -= 1
+        DUP1, PUSH1, 0x01, ADD, DUP2, SSTORE, // storage[counter] = counter + 1
+        PUSH1, 0x03, JUMP, //
+        JUMPDEST, STOP,
+    ])
+}
+
+/// Returns initcode that deploys `runtime`.
+pub(crate) fn initcode(runtime: &[u8]) -> Bytes {
+    let len = u16::try_from(runtime.len()).unwrap().to_be_bytes();
+    let mut code = vec![
+        PUSH2, len[0], len[1], DUP1, PUSH1, 0x0c, PUSH1, 0x00, CODECOPY, PUSH1, 0x00, RETURN,
+    ];
+    code.extend_from_slice(runtime);
+    code.into()
+}
@@ -0,0 +1,84 @@
+//! Benchmarks of deep call chains, with frames reused from the frame pool or allocated for every
+//! call.
+//!
+//! A counting allocator reports the allocations of a transaction with an empty pool and with the
+//! pool filled by a previous transaction. The pool is large enough to hold every frame of the
+//! chain.
+
+mod bench_utils;
+
+use bench_utils::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::U256,
+    Evm, FramePool,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const DEPTH: u64 = 200;
+
+/// System allocator counting the allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Executes the transaction and returns the number of allocations it made.
+fn count_allocations(evm: &mut Evm<'_, (), CacheDB<EmptyDB>>) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = evm.transact().unwrap();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert!(result.result.is_success());
+    allocations
+}
+
+fn call_chain_bench(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(call_chain(), 1));
+    evm.tx_mut().data = U256::from(DEPTH).to_be_bytes_vec().into();
+    // Pools every frame of the chain.
+    evm.context.evm.frame_pool = FramePool::with_max_len(DEPTH as usize + 1);
+
+    let empty_pool = count_allocations(&mut evm);
+    assert_eq!(evm.context.evm.frame_pool.len(), DEPTH as usize + 1);
+    let filled_pool = count_allocations(&mut evm);
+    println!("call_chain/{DEPTH}: {empty_pool} allocations with an empty frame pool, {filled_pool} with a filled one");
+    assert!(filled_pool < empty_pool);
+
+    let mut g = c.benchmark_group("call_chain");
+    configure_group(&mut g);
+    g.bench_function(format!("transact/{DEPTH}_depth_reused"), |b| {
+        b.iter(|| evm.transact().unwrap())
+    });
+    g.bench_function(format!("transact/{DEPTH}_depth_allocated"), |b| {
+        b.iter(|| {
+            evm.context.evm.frame_pool.clear();
+            evm.transact().unwrap()
+        })
+    });
+    g.finish();
+}
+
+criterion_group!(benches, call_chain_bench);
+criterion_main!(benches);
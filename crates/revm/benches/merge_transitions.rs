@@ -0,0 +1,91 @@
+//! Benchmarks of merging the transitions of a block into the bundle state, serially and on the
+//! rayon thread pool.
+//!
+//! Every account is changed in a first block already merged into the bundle, and changed again
+//! with a few storage slots in the benchmarked block.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use revm::{
+    db::{
+        states::{bundle_state::BundleRetention, StorageSlot},
+        AccountStatus, BundleState, TransitionAccount, TransitionState,
+    },
+    primitives::{AccountInfo, Address, HashMap, U256},
+};
+use std::time::Duration;
+
+const ACCOUNTS: u64 = 100_000;
+const SLOTS: u64 = 4;
+
+/// Returns the transitions changing the nonce of every account from `nonce - 1` to `nonce`.
+fn transitions(nonce: u64) -> TransitionState {
+    let info = |nonce| AccountInfo {
+        nonce,
+        ..Default::default()
+    };
+    let mut transitions = TransitionState::default();
+    transitions.add_transitions(
+        (0..ACCOUNTS)
+            .map(|i| {
+                let storage = (0..SLOTS)
+                    .map(|slot| {
+                        let value = U256::from(nonce);
+                        (
+                            U256::from(slot),
+                            StorageSlot::new_changed(value - U256::from(1), value),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>();
+                let transition = TransitionAccount {
+                    info: Some(info(nonce)),
+                    status: AccountStatus::Changed,
+                    previous_info: Some(info(nonce - 1)),
+                    previous_status: AccountStatus::Changed,
+                    storage,
+                    storage_was_destroyed: false,
+                };
+                (Address::from_word(U256::from(i).into()), transition)
+            })
+            .collect(),
+    );
+    transitions
+}
+
+fn merge_transitions_bench(c: &mut Criterion) {
+    let mut bundle = BundleState::default();
+    bundle.apply_transitions_and_create_reverts(transitions(1), BundleRetention::Reverts);
+    let transitions = transitions(2);
+
+    let mut g = c.benchmark_group("merge_transitions");
+    g.warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+    g.throughput(Throughput::Elements(ACCOUNTS));
+    g.bench_function(format!("serial/{ACCOUNTS}_accounts"), |b| {
+        b.iter_batched(
+            || (bundle.clone(), transitions.clone()),
+            |(mut bundle, transitions)| {
+                bundle.apply_transitions_and_create_reverts(transitions, BundleRetention::Reverts);
+                bundle
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    g.bench_function(format!("parallel/{ACCOUNTS}_accounts"), |b| {
+        b.iter_batched(
+            || (bundle.clone(), transitions.clone()),
+            |(mut bundle, transitions)| {
+                bundle.apply_transitions_and_create_reverts_parallel(
+                    transitions,
+                    BundleRetention::Reverts,
+                );
+                bundle
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    g.finish();
+}
+
+criterion_group!(benches, merge_transitions_bench);
+criterion_main!(benches);
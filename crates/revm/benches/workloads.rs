@@ -0,0 +1,320 @@
+//! Benchmarks of common transaction workloads going through all handler stages.
+//!
+//! The size of each workload is part of the benchmark name, so results are only compared against
+//! baselines of the same size.
+
+mod bench_utils;
+
+use bench_utils::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use revm::primitives::{create_address, Bytes, ReturnDataLimit, SpecId, TxKind, U256};
+
+const TRANSFERS: usize = 10_000;
+const HOLDERS: usize = 100;
+const SSTORES: u64 = 1_000;
+const RUNTIME_SIZE: usize = 8 * 1024;
+const PRECOMPILE_TXS: usize = 10_000;
+const LARGE_RUNTIME_SIZE: usize = 24 * 1024;
+const COPY_SIZES: [usize; 3] = [1024, 32 * 1024, 1024 * 1024];
+const RETURN_DATA_SIZE: u32 = 10 * 1024 * 1024;
+const VIEW_CALLS: usize = 10_000;
+
+fn erc20_transfers(c: &mut Criterion) {
+    let mut g = c.benchmark_group("erc20");
+    configure_group(&mut g);
+    g.throughput(Throughput::Elements(TRANSFERS as u64));
+    g.bench_function(format!("transact_commit/{TRANSFERS}_transfers"), |b| {
+        b.iter_batched(
+            || evm(db_with_contract(token(), HOLDERS)),
+            |mut evm| {
+                for i in 0..TRANSFERS {
+                    let tx = evm.tx_mut();
+                    tx.caller = caller(i % HOLDERS);
+                    tx.data = transfer_calldata(caller((i + 1) % HOLDERS), U256::from(1));
+                    assert!(evm.transact_commit().unwrap().is_success());
+                }
+                evm
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    g.finish();
+}
+
+fn call_recursion(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(recursion(), 1));
+
+    let mut g = c.benchmark_group("recursion");
+    configure_group(&mut g);
+    g.bench_function("transact/self_call_30m_gas", |b| {
+        b.iter(|| evm.transact().unwrap())
+    });
+    g.finish();
+}
+
+fn sstore_loop_bench(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(sstore_loop(), 1));
+    evm.tx_mut().data = U256::from(SSTORES).to_be_bytes_vec().into();
+
+    let mut g = c.benchmark_group("sstore");
+    configure_group(&mut g);
+    g.throughput(Throughput::Elements(SSTORES));
+    g.bench_function(format!("transact/{SSTORES}_slots"), |b| {
+        b.iter(|| {
+            let result = evm.transact().unwrap();
+            assert!(result.result.is_success());
+            result
+        })
+    });
+    g.finish();
+}
+
+fn deploy_and_call(c: &mut Criterion) {
+    // The token followed by unreachable code, to make the contract mid-size.
+    let mut runtime = token().to_vec();
+    runtime.resize(RUNTIME_SIZE, revm::interpreter::opcode::JUMPDEST);
+    let initcode = initcode(&runtime);
+    let deployed = create_address(caller(0), 0);
+
+    let mut g = c.benchmark_group("deploy");
+    configure_group(&mut g);
+    g.bench_function(format!("transact_commit/{RUNTIME_SIZE}_bytes"), |b| {
+        b.iter_batched(
+            || evm(db_with_contract(Bytes::new(), 2)),
+            |mut evm| {
+                let tx = evm.tx_mut();
+                tx.transact_to = TxKind::Create;
+                tx.data = initcode.clone();
+                assert!(evm.transact_commit().unwrap().is_success());
+
+                let tx = evm.tx_mut();
+                tx.transact_to = TxKind::Call(deployed);
+                tx.data = transfer_calldata(caller(1), U256::ZERO);
+                assert!(evm.transact_commit().unwrap().is_success());
+                evm
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    g.finish();
+}
+
+/// Short transactions calling a precompile, dominated by the per-transaction setup that warms
+/// the Prague precompile set unless `warm_precompiles` is disabled.
+fn precompile_calls(c: &mut Criterion) {
+    let mut g = c.benchmark_group("precompiles");
+    configure_group(&mut g);
+    g.throughput(Throughput::Elements(PRECOMPILE_TXS as u64));
+    for (name, warm_precompiles) in [("warm", true), ("cold", false)] {
+        let mut evm = evm(db_with_contract(identity_call(), 1));
+        evm.modify_spec_id(SpecId::PRAGUE);
+        evm.cfg_mut().warm_precompiles = warm_precompiles;
+        g.bench_function(format!("transact/{PRECOMPILE_TXS}_txs_{name}"), |b| {
+            b.iter(|| {
+                for _ in 0..PRECOMPILE_TXS {
+                    assert!(evm.transact().unwrap().result.is_success());
+                }
+            })
+        });
+    }
+    g.finish();
+}
+
+/// KECCAK256 over 1 MiB of memory, including its expansion.
+fn keccak_memory_bench(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(keccak_memory(), 1));
+
+    let mut g = c.benchmark_group("keccak");
+    configure_group(&mut g);
+    g.throughput(Throughput::Bytes(1 << 20));
+    g.bench_function("transact/1_mib", |b| {
+        b.iter(|| {
+            let result = evm.transact().unwrap();
+            assert!(result.result.is_success());
+            result
+        })
+    });
+    g.finish();
+}
+
+/// CALLDATACOPY, CODECOPY and MCOPY of 1 KiB to 1 MiB, including memory expansion.
+fn copy_bench(c: &mut Criterion) {
+    let mut g = c.benchmark_group("copy");
+    configure_group(&mut g);
+    for (name, code) in [
+        ("calldatacopy", calldata_copy()),
+        ("codecopy", code_copy()),
+        ("mcopy", memory_copy()),
+    ] {
+        let mut evm = evm(db_with_contract(code, 1));
+        for size in COPY_SIZES {
+            evm.tx_mut().data = vec![1; size].into();
+            g.throughput(Throughput::Bytes(size as u64));
+            g.bench_function(format!("transact/{name}/{}_kib", size / 1024), |b| {
+                b.iter(|| {
+                    let result = evm.transact().unwrap();
+                    assert!(result.result.is_success());
+                    result
+                })
+            });
+        }
+    }
+    g.finish();
+}
+
+/// Child call returning 10 MiB, with the return data shared with the caller or truncated to
+/// 1 MiB by the configured limit.
+fn return_data_bench(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(return_data(RETURN_DATA_SIZE), 1));
+    // Memory expansion of the child costs about 210M gas.
+    evm.tx_mut().gas_limit = 500_000_000;
+
+    let mut g = c.benchmark_group("return_data");
+    configure_group(&mut g);
+    g.throughput(Throughput::Bytes(RETURN_DATA_SIZE as u64));
+    for limit in [None, Some(1024 * 1024)] {
+        evm.cfg_mut().max_return_data_size = limit;
+        evm.cfg_mut().return_data_limit = ReturnDataLimit::Truncate;
+        let name = match limit {
+            None => "unlimited",
+            Some(_) => "truncated_1_mib",
+        };
+        g.bench_function(format!("transact/{name}/10_mib"), |b| {
+            b.iter(|| {
+                let result = evm.transact().unwrap();
+                assert!(result.result.is_success());
+                result
+            })
+        });
+    }
+    g.finish();
+}
+
+/// Balance queries of the token holders, with the output kept in the results or discarded.
+fn view_calls(c: &mut Criterion) {
+    let mut evm = evm(db_with_contract(balance_of(), HOLDERS));
+    let calldata: Vec<Bytes> = (0..HOLDERS)
+        .map(|i| caller(i).into_word().to_vec().into())
+        .collect();
+
+    let mut g = c.benchmark_group("view_calls");
+    configure_group(&mut g);
+    g.throughput(Throughput::Elements(VIEW_CALLS as u64));
+    for discard_output in [false, true] {
+        evm.cfg_mut().discard_output = discard_output;
+        let name = if discard_output { "discarded" } else { "kept" };
+        g.bench_function(format!("transact/{VIEW_CALLS}_balance_of/{name}"), |b| {
+            b.iter(|| {
+                let mut gas_used = 0;
+                for i in 0..VIEW_CALLS {
+                    evm.tx_mut().data = calldata[i % HOLDERS].clone();
+                    let result = evm.transact().unwrap().result;
+                    assert!(result.is_success());
+                    gas_used += result.gas_used();
+                }
+                gas_used
+            })
+        });
+    }
+    g.finish();
+}
+
+/// Transfer on a large contract loaded into a fresh database, analysed on every run unless the
+/// jump table is reused from a shared `AnalysisCache`.
+fn analysis_cache(c: &mut Criterion) {
+    use revm::AnalysisCache;
+    use std::sync::Arc;
+
+    // The token followed by unreachable code, so analysis dominates the transaction.
+    let mut runtime = token().to_vec();
+    runtime.resize(LARGE_RUNTIME_SIZE, revm::interpreter::opcode::JUMPDEST);
+    let runtime = Bytes::from(runtime);
+
+    let mut g = c.benchmark_group("analysis_cache");
+    configure_group(&mut g);
+    for (name, cache) in [
+        ("uncached", None),
+        ("cached", Some(Arc::new(AnalysisCache::new(16)))),
+    ] {
+        g.bench_function(format!("transact/{LARGE_RUNTIME_SIZE}_bytes_{name}"), |b| {
+            b.iter_batched(
+                || {
+                    let mut evm = evm(db_with_contract(runtime.clone(), 2));
+                    evm.context.evm.analysis_cache = cache.clone();
+                    evm.tx_mut().data = transfer_calldata(caller(1), U256::from(1));
+                    evm
+                },
+                |mut evm| {
+                    assert!(evm.transact().unwrap().result.is_success());
+                    evm
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    g.finish();
+}
+
+/// Non-deposit transaction charged the L1 data fee in `deduct_caller` and paying the fee
+/// recipients in `reward_beneficiary`.
+#[cfg(feature = "optimism")]
+fn optimism_transfer(c: &mut Criterion) {
+    use revm::{
+        optimism::L1_BLOCK_CONTRACT,
+        primitives::{AccountInfo, SpecId},
+        Evm,
+    };
+
+    let mut db = db_with_contract(token(), 2);
+    db.insert_account_info(L1_BLOCK_CONTRACT, AccountInfo::default());
+    // L1 base fee, fee overhead and fee scalar slots of the L1 block contract.
+    for (slot, value) in [(1, 1_000_000_000), (5, 2_100), (6, 1_000_000)] {
+        db.insert_account_storage(L1_BLOCK_CONTRACT, U256::from(slot), U256::from(value))
+            .unwrap();
+    }
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .optimism()
+        .with_spec_id(SpecId::REGOLITH)
+        .modify_block_env(|block| block.basefee = U256::from(10))
+        .modify_tx_env(|tx| {
+            tx.caller = caller(0);
+            tx.transact_to = TxKind::Call(CONTRACT);
+            tx.gas_price = U256::from(20);
+            tx.gas_limit = 100_000;
+            tx.data = transfer_calldata(caller(1), U256::from(1));
+            tx.optimism.enveloped_tx = Some(Bytes::from(vec![0x02; 150]));
+        })
+        .build();
+
+    let mut g = c.benchmark_group("optimism");
+    configure_group(&mut g);
+    g.bench_function("transact/l1_fee_transfer", |b| {
+        b.iter(|| {
+            let result = evm.transact().unwrap();
+            assert!(result.result.is_success());
+            result
+        })
+    });
+    g.finish();
+}
+
+#[cfg(not(feature = "optimism"))]
+fn optimism_transfer(_: &mut Criterion) {}
+
+#[rustfmt::skip]
+criterion_group!(
+    benches,
+    erc20_transfers,
+    call_recursion,
+    sstore_loop_bench,
+    deploy_and_call,
+    precompile_calls,
+    keccak_memory_bench,
+    copy_bench,
+    return_data_bench,
+    view_calls,
+    analysis_cache,
+    optimism_transfer,
+);
+criterion_main!(benches);
@@ -0,0 +1,244 @@
+use crate::{
+    interpreter::analysis::to_analysed_with_jump_table,
+    primitives::{legacy::JumpTable, Bytecode, HashMap, B256},
+};
+use std::{
+    collections::VecDeque,
+    sync::{PoisonError, RwLock},
+};
+
+/// A thread safe cache of legacy bytecode jump tables, keyed by code hash.
+///
+/// Jump destination analysis of legacy bytecode is repeated every time the code is loaded into a
+/// new [`Evm`](crate::Evm) or database. Wrap the cache in an [`Arc`](std::sync::Arc) and pass a
+/// clone to each [`EvmBuilder::with_analysis_cache`](crate::EvmBuilder::with_analysis_cache) to
+/// analyse each contract only once.
+///
+/// The cache holds at most `max_entries` jump tables, when full the oldest entry is evicted.
+///
+/// # Example
+///
+/// ```
+/// use revm::{db::{CacheDB, EmptyDB}, AnalysisCache, Evm};
+/// use std::sync::Arc;
+///
+/// let cache = Arc::new(AnalysisCache::new(1024));
+///
+/// for _ in 0..4 {
+///     let mut evm = Evm::builder()
+///         .with_db(CacheDB::new(EmptyDB::default()))
+///         .with_analysis_cache(cache.clone())
+///         .build();
+///     evm.transact().unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AnalysisCache {
+    max_entries: usize,
+    inner: RwLock<Entries>,
+}
+
+/// Cached jump tables and their insertion order.
+#[derive(Debug, Default)]
+struct Entries {
+    jump_tables: HashMap<B256, JumpTable>,
+    order: VecDeque<B256>,
+}
+
+impl AnalysisCache {
+    /// Creates a new cache holding at most `max_entries` jump tables.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            inner: RwLock::default(),
+        }
+    }
+
+    /// Returns the maximum number of cached jump tables.
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Returns the number of cached jump tables.
+    pub fn len(&self) -> usize {
+        self.read(|entries| entries.jump_tables.len())
+    }
+
+    /// Returns `true` if no jump table is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the cached jump table of the code with the given hash.
+    pub fn get(&self, code_hash: &B256) -> Option<JumpTable> {
+        self.read(|entries| entries.jump_tables.get(code_hash).cloned())
+    }
+
+    /// Caches the jump table of the code with the given hash, evicting the oldest entries if the
+    /// cache is full.
+    pub fn insert(&self, code_hash: B256, jump_table: JumpTable) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        if entries.jump_tables.insert(code_hash, jump_table).is_some() {
+            return;
+        }
+        entries.order.push_back(code_hash);
+        while entries.order.len() > self.max_entries {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.jump_tables.remove(&oldest);
+            }
+        }
+    }
+
+    /// Clears the cache.
+    pub fn clear(&self) {
+        let mut entries = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        entries.jump_tables.clear();
+        entries.order.clear();
+    }
+
+    /// Analyses legacy raw bytecode, reusing the cached jump table of `code_hash` if there is one
+    /// and caching it otherwise.
+    ///
+    /// `code_hash` must be the hash of `bytecode`. Bytecode that is not legacy raw is returned
+    /// as-is.
+    pub fn analyse(&self, code_hash: B256, bytecode: Bytecode) -> Bytecode {
+        if !matches!(bytecode, Bytecode::LegacyRaw(_)) {
+            return bytecode;
+        }
+        if let Some(jump_table) = self.get(&code_hash) {
+            return to_analysed_with_jump_table(bytecode, Some(jump_table));
+        }
+        let bytecode = to_analysed_with_jump_table(bytecode, None);
+        if let Some(jump_table) = bytecode.legacy_jump_table() {
+            self.insert(code_hash, jump_table.clone());
+        }
+        bytecode
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&Entries) -> R) -> R {
+        f(&self.inner.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, keccak256, AccountInfo, Address, Bytes, TxKind, U256},
+        Evm,
+    };
+    use std::{sync::Arc, vec, vec::Vec};
+
+    const CONTRACT: Address = address!("00000000000000000000000000000000000c0de0");
+
+    fn bytecode(value: u8) -> Bytecode {
+        // PUSH1 0x07 JUMP INVALID INVALID INVALID INVALID JUMPDEST
+        // PUSH1 value PUSH1 0 SSTORE STOP
+        Bytecode::new_raw(Bytes::from(vec![
+            opcode::PUSH1,
+            0x07,
+            opcode::JUMP,
+            opcode::INVALID,
+            opcode::INVALID,
+            opcode::INVALID,
+            opcode::INVALID,
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            value,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]))
+    }
+
+    fn transact(cache: Option<Arc<AnalysisCache>>) -> crate::primitives::ResultAndState {
+        let code = bytecode(0x2a);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        let builder = Evm::builder().with_db(db).modify_tx_env(|tx| {
+            tx.transact_to = TxKind::Call(CONTRACT);
+            tx.gas_limit = 100_000;
+        });
+        let mut evm = match cache {
+            Some(cache) => builder.with_analysis_cache(cache).build(),
+            None => builder.build(),
+        };
+        evm.transact().unwrap()
+    }
+
+    #[test]
+    fn same_execution_with_and_without_cache() {
+        let cache = Arc::new(AnalysisCache::new(16));
+        let expected = transact(None);
+        assert!(expected.result.is_success());
+        assert_eq!(
+            expected.state[&CONTRACT].storage[&U256::ZERO].present_value,
+            U256::from(0x2a)
+        );
+
+        // First run populates the cache, the second one uses it.
+        for _ in 0..2 {
+            assert_eq!(transact(Some(cache.clone())), expected);
+        }
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&bytecode(0x2a).hash_slow()).is_some());
+    }
+
+    #[test]
+    fn analyse_reuses_cached_jump_table() {
+        let cache = AnalysisCache::new(16);
+        let code = bytecode(1);
+        let hash = code.hash_slow();
+
+        let first = cache.analyse(hash, code.clone());
+        let second = cache.analyse(hash, code);
+        assert_eq!(first, second);
+        let (first, second) = (
+            first.legacy_jump_table().unwrap(),
+            second.legacy_jump_table().unwrap(),
+        );
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+    }
+
+    #[test]
+    fn evicts_oldest_entries() {
+        let cache = AnalysisCache::new(2);
+        let hashes: Vec<B256> = (0..3u8)
+            .map(|i| {
+                let code = bytecode(i);
+                let hash = code.hash_slow();
+                cache.analyse(hash, code);
+                hash
+            })
+            .collect();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&hashes[0]).is_none());
+        assert!(cache.get(&hashes[1]).is_some());
+        assert!(cache.get(&hashes[2]).is_some());
+
+        // Re-inserting an entry does not evict others.
+        cache.insert(hashes[2], cache.get(&hashes[2]).unwrap());
+        assert_eq!(cache.len(), 2);
+
+        let disabled = AnalysisCache::new(0);
+        disabled.analyse(keccak256([0]), bytecode(0));
+        assert!(disabled.is_empty());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,13 +1,19 @@
 use crate::{
     db::{Database, DatabaseRef, EmptyDB, WrapDatabaseRef},
     handler::register,
+    interpreter::{
+        gas::{reprice, GasPricingOverride},
+        opcode::static_gas,
+        Cancellation, InstructionResult, DEFAULT_CANCELLATION_STRIDE,
+    },
+    precompile::Precompiles,
     primitives::{
         BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv,
     },
-    Context, ContextWithHandlerCfg, Evm, Handler,
+    ChainSpec, Context, ContextPrecompiles, ContextWithHandlerCfg, Evm, Handler,
 };
-use core::marker::PhantomData;
-use std::boxed::Box;
+use core::{marker::PhantomData, sync::atomic::AtomicBool};
+use std::{boxed::Box, sync::Arc};
 
 /// Evm Builder allows building or modifying EVM.
 /// Note that some of the methods that changes underlying structures
@@ -71,6 +77,9 @@ impl<'a, EXT, DB: Database> EvmBuilder<'a, SetGenericStage, EXT, DB> {
         }
     }
     /// Sets the [`DatabaseRef`] that will be used by [`Evm`].
+    ///
+    /// To run multiple [`Evm`]s over the same state, pass each a clone of an
+    /// `Arc<`[`SharedCacheDb`](crate::db::SharedCacheDb)`>`.
     pub fn with_ref_db<ODB: DatabaseRef>(
         self,
         db: ODB,
@@ -155,6 +164,30 @@ impl<'a, EXT, DB: Database> EvmBuilder<'a, SetGenericStage, EXT, DB> {
         }
     }
 
+    /// Configures the EVM for the given block of the chain.
+    ///
+    /// The spec is resolved from the hardfork schedule of the chain, the block environment is
+    /// set and the [`CfgEnv`] is configured by [`ChainSpec::configure`]. The handler is reset,
+    /// handle registers are appended afterwards.
+    pub fn with_chain_spec(
+        mut self,
+        chain_spec: &ChainSpec,
+        block_env: BlockEnv,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        let handler_cfg = HandlerCfg {
+            spec_id: chain_spec.spec_id(&block_env),
+            #[cfg(feature = "optimism")]
+            is_optimism: chain_spec.is_optimism,
+        };
+        self.context.evm.env.block = block_env;
+        chain_spec.configure(&mut self.context.evm.env.cfg);
+        EvmBuilder {
+            context: self.context,
+            handler: EvmBuilder::<'a, HandlerStage, EXT, DB>::handler(handler_cfg),
+            phantom: PhantomData,
+        }
+    }
+
     /// Sets the Optimism handler with latest spec.
     ///
     /// If `optimism-default-handler` feature is enabled this is not needed.
@@ -341,6 +374,111 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         }
     }
 
+    /// Installs a static precompile set, such as a custom Optimism set built with
+    /// `OpPrecompilesBuilder`, in place of the precompiles of the spec.
+    ///
+    /// The set is installed by a handle register, so it is kept when the spec changes.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_static_precompiles(
+        self,
+        precompiles: &'static Precompiles,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.append_handler_register_box(Box::new(move |handler| {
+            handler.pre_execution.load_precompiles =
+                Arc::new(move || ContextPrecompiles::from_static_precompiles(precompiles));
+        }))
+    }
+
+    /// Installs a gas pricing that reprices the opcodes of the spec, see [`GasPricingOverride`].
+    ///
+    /// Static gas overrides wrap the instructions of the table, dynamic costs are priced by the
+    /// interpreters of the frames. Without a pricing instructions run unchanged.
+    ///
+    /// The pricing is installed by a handle register, so it is kept when the spec changes.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_gas_pricing(
+        self,
+        pricing: Arc<GasPricingOverride>,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.append_handler_register_box(Box::new(move |handler| {
+            let spec_id = handler.cfg.spec_id;
+            for opcode in 0..=u8::MAX {
+                let (Some(gas), Some(priced)) = (
+                    static_gas(opcode, spec_id),
+                    pricing.static_gas(opcode, spec_id),
+                ) else {
+                    continue;
+                };
+                if gas == priced {
+                    continue;
+                }
+                let (gas, priced) = (gas as u64, priced as u64);
+                handler.instruction_table.update_boxed(
+                    opcode,
+                    move |instruction, interpreter, host| {
+                        // The instruction charges the static gas of the spec.
+                        if !reprice(&mut interpreter.gas, gas, priced) {
+                            interpreter.instruction_result = InstructionResult::OutOfGas;
+                            return;
+                        }
+                        instruction(interpreter, host)
+                    },
+                );
+            }
+
+            let pricing = pricing.clone();
+            let execute_frame = handler.execution.execute_frame.clone();
+            handler.execution.execute_frame =
+                Arc::new(move |frame, shared_memory, tables, context| {
+                    frame.interpreter_mut().gas_pricing = Some(pricing.clone());
+                    execute_frame(frame, shared_memory, tables, context)
+                });
+        }))
+    }
+
+    /// Installs a cancellation token checked every [`DEFAULT_CANCELLATION_STRIDE`] instructions,
+    /// see [`Self::with_cancellation_stride`].
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_cancellation(
+        self,
+        token: Arc<AtomicBool>,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.with_cancellation_stride(token, DEFAULT_CANCELLATION_STRIDE)
+    }
+
+    /// Installs a cancellation token checked by the interpreters every `stride` instructions.
+    ///
+    /// Setting the token from another thread halts the running frames with
+    /// [`HaltReason::Cancelled`](crate::primitives::HaltReason::Cancelled). The state changes of
+    /// the halted frames are reverted as for any halt, and the result of the transaction should
+    /// be discarded. The token is not reset by the EVM.
+    ///
+    /// The cancellation is installed by a handle register, so it is kept when the spec changes.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn with_cancellation_stride(
+        self,
+        token: Arc<AtomicBool>,
+        stride: u32,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        let cancellation = Cancellation::new(token, stride);
+        self.append_handler_register_box(Box::new(move |handler| {
+            let cancellation = cancellation.clone();
+            let execute_frame = handler.execution.execute_frame.clone();
+            handler.execution.execute_frame =
+                Arc::new(move |frame, shared_memory, tables, context| {
+                    let interpreter = frame.interpreter_mut();
+                    if interpreter.cancellation.is_none() {
+                        interpreter.cancellation = Some(cancellation.clone());
+                    }
+                    execute_frame(frame, shared_memory, tables, context)
+                });
+        }))
+    }
+
     /// Sets specification Id , that will mark the version of EVM.
     /// It represent the hard fork of ethereum.
     ///
@@ -429,6 +567,23 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         self
     }
 
+    /// Sets the [`AnalysisCache`](crate::AnalysisCache) used to reuse jump tables of legacy
+    /// bytecode across EVMs and transactions.
+    ///
+    /// Code hashes returned by the database must match the bytecode, otherwise jump tables of
+    /// different bytecode would be mixed up.
+    #[cfg(feature = "std")]
+    pub fn with_analysis_cache(mut self, cache: std::sync::Arc<crate::AnalysisCache>) -> Self {
+        self.context.evm.analysis_cache = Some(cache);
+        self
+    }
+
+    /// Sets the [`StateOverrides`](crate::StateOverrides) applied before each transaction.
+    pub fn with_state_overrides(mut self, overrides: crate::StateOverrides) -> Self {
+        self.context.evm.state_overrides = overrides;
+        self
+    }
+
     /// Resets [`Handler`] to default mainnet.
     pub fn reset_handler(mut self) -> Self {
         self.handler = Self::handler(self.handler.cfg());
@@ -440,11 +595,12 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
 mod test {
     use super::SpecId;
     use crate::{
-        db::EmptyDB,
+        db::{Database, Either, EmptyDB},
         inspector::inspector_handle_register,
         inspectors::NoOpInspector,
         primitives::{
-            address, AccountInfo, Address, Bytecode, Bytes, PrecompileResult, TxKind, U256,
+            address, AccountInfo, Address, Bytecode, Bytes, EVMError, PrecompileResult, TxKind,
+            U256,
         },
         Context, ContextPrecompile, ContextStatefulPrecompile, Evm, InMemoryDB, InnerEvmContext,
     };
@@ -637,4 +793,215 @@ mod test {
 
         evm.transact().unwrap();
     }
+
+    #[test]
+    fn gas_pricing_override() {
+        use crate::interpreter::{
+            gas::GasPricingOverride,
+            opcode::{ADD, BALANCE, MSTORE, POP, PUSH1, PUSH20, SLOAD, SSTORE, STOP},
+        };
+
+        let caller = address!("0000000000000000000000000000000000000001");
+        let target = address!("0000000000000000000000000000000000001000");
+        let other = address!("0000000000000000000000000000000000001001");
+        let mut code = vec![PUSH1, 1, PUSH1, 2, ADD, POP];
+        // Memory expansion, then a cold SLOAD and a cold SSTORE.
+        code.extend([PUSH1, 0x2a, PUSH1, 0, MSTORE]);
+        code.extend([PUSH1, 0, SLOAD, POP, PUSH1, 1, PUSH1, 1, SSTORE]);
+        // Cold account access.
+        code.push(PUSH20);
+        code.extend_from_slice(other.as_slice());
+        code.extend([BALANCE, POP, STOP]);
+
+        let gas_used = |pricing: Option<Arc<GasPricingOverride>>| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+            let code = Bytecode::new_legacy(code.clone().into());
+            db.insert_account_info(target, AccountInfo::from_bytecode(code));
+            let builder = Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(target);
+                    tx.gas_limit = 100_000;
+                });
+            let mut evm = match pricing {
+                Some(pricing) => builder.with_gas_pricing(pricing).build(),
+                None => builder.build(),
+            };
+            let result = evm.transact().unwrap().result;
+            assert!(result.is_success());
+            result.gas_used()
+        };
+
+        let pricing = GasPricingOverride::new()
+            .with_static_gas(ADD, 10)
+            .with_memory_expansion_percent(200)
+            .with_cold_access_percent(50)
+            .with_storage_write_percent(150);
+        assert_eq!(
+            pricing.static_gas_table(SpecId::CANCUN)[ADD as usize],
+            Some(10)
+        );
+
+        let base = gas_used(None);
+        // A pricing without changes keeps every cost.
+        assert_eq!(gas_used(Some(Arc::new(GasPricingOverride::new()))), base);
+
+        let priced = gas_used(Some(Arc::new(pricing)));
+        let add = 10 - 3;
+        let memory = 3;
+        let sload = 2_000 / 2;
+        let sstore = 20_000 / 2 - 2_100 / 2;
+        let balance = 2_500 / 2;
+        assert_eq!(priced, base + add + memory - sload + sstore - balance);
+    }
+
+    #[test]
+    fn cancellation() {
+        use crate::{
+            interpreter::{
+                opcode::{CALL, GAS, JUMP, JUMPDEST, POP, PUSH0, PUSH20, STOP},
+                DEFAULT_CANCELLATION_STRIDE,
+            },
+            primitives::{ExecutionResult, HaltReason},
+        };
+        use core::sync::atomic::{AtomicBool, Ordering};
+        use std::{
+            thread,
+            time::{Duration, Instant},
+        };
+
+        let caller = address!("0000000000000000000000000000000000000001");
+        let looping = address!("0000000000000000000000000000000000001000");
+        let parent = address!("0000000000000000000000000000000000001001");
+        let stop = address!("0000000000000000000000000000000000001002");
+        // Calls the looping contract with all of its gas.
+        let mut parent_code = vec![PUSH0, PUSH0, PUSH0, PUSH0, PUSH0, PUSH20];
+        parent_code.extend_from_slice(looping.as_slice());
+        parent_code.extend([GAS, CALL, POP, STOP]);
+
+        let mut db = InMemoryDB::default();
+        for (address, code) in [
+            (looping, vec![JUMPDEST, PUSH0, JUMP]),
+            (parent, parent_code),
+            (stop, vec![STOP]),
+        ] {
+            let code = Bytecode::new_legacy(code.into());
+            db.insert_account_info(address, AccountInfo::from_bytecode(code));
+        }
+        let transact = |target: Address, token: Arc<AtomicBool>, stride: u32| {
+            Evm::builder()
+                .with_db(db.clone())
+                .with_spec_id(SpecId::CANCUN)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(target);
+                    tx.gas_limit = u64::MAX;
+                })
+                .with_cancellation_stride(token, stride)
+                .build()
+                .transact()
+                .unwrap()
+                .result
+        };
+
+        // The loop runs until the token is set from another thread, also in a nested frame.
+        for target in [looping, parent] {
+            let token = Arc::new(AtomicBool::new(false));
+            let cancel = thread::spawn({
+                let token = token.clone();
+                move || {
+                    thread::sleep(Duration::from_millis(50));
+                    token.store(true, Ordering::Relaxed);
+                }
+            });
+            let start = Instant::now();
+            let result = transact(target, token, DEFAULT_CANCELLATION_STRIDE);
+            assert!(start.elapsed() < Duration::from_secs(10));
+            cancel.join().unwrap();
+            assert!(
+                matches!(
+                    result,
+                    ExecutionResult::Halt {
+                        reason: HaltReason::Cancelled,
+                        ..
+                    }
+                ),
+                "{result:?}"
+            );
+        }
+
+        // A set token halts before the first instruction, an unset one changes nothing.
+        let result = transact(stop, Arc::new(AtomicBool::new(true)), 1);
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::Cancelled,
+                ..
+            }
+        ));
+        assert!(transact(stop, Arc::new(AtomicBool::new(false)), 1).is_success());
+    }
+
+    #[test]
+    fn with_db_wrappers() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let to = address!("2000000000000000000000000000000000000000");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000u64)));
+        let transfer = |tx: &mut crate::primitives::TxEnv| {
+            tx.caller = caller;
+            tx.transact_to = TxKind::Call(to);
+            tx.value = U256::from(1);
+        };
+
+        // A mutable reference to a database is a database.
+        Evm::builder()
+            .with_db(&mut db)
+            .modify_tx_env(transfer)
+            .build()
+            .transact_commit()
+            .unwrap();
+        assert_eq!(db.basic(to).unwrap().unwrap().balance, U256::from(1));
+
+        // An `Arc` of a database is a `DatabaseRef`.
+        let shared = Arc::new(db.clone());
+        let result = Evm::builder()
+            .with_ref_db(Arc::clone(&shared))
+            .modify_tx_env(transfer)
+            .build()
+            .transact()
+            .unwrap();
+        assert!(result.result.is_success());
+
+        // One of two databases, chosen at runtime.
+        for db in [Either::Left(db.clone()), Either::Right(EmptyDB::default())] {
+            let funded = matches!(db, Either::Left(_));
+            let result = Evm::builder()
+                .with_db(db)
+                .modify_tx_env(transfer)
+                .build()
+                .transact();
+            assert_eq!(result.is_ok(), funded);
+        }
+    }
+
+    #[test]
+    fn database_error_conversion() {
+        fn balance<DB: Database>(db: &mut DB, address: Address) -> Result<U256, EVMError<DB::Error>>
+        where
+            DB::Error: crate::db::DBErrorMarker,
+        {
+            Ok(db.basic(address)?.unwrap_or_default().balance)
+        }
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(Address::ZERO, AccountInfo::from_balance(U256::from(2)));
+        assert_eq!(balance(&mut db, Address::ZERO), Ok(U256::from(2)));
+
+        let mut db = Either::<InMemoryDB, EmptyDB>::Right(EmptyDB::default());
+        assert_eq!(balance(&mut db, Address::ZERO), Ok(U256::ZERO));
+    }
 }
@@ -0,0 +1,219 @@
+use crate::primitives::{BlockEnv, CfgEnv, SpecId, U256};
+use std::borrow::Cow;
+
+/// Condition under which a hardfork is active.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ForkCondition {
+    /// Active from the given block number.
+    Block(u64),
+    /// Active from the given block timestamp.
+    Timestamp(u64),
+}
+
+impl ForkCondition {
+    /// Returns `true` if the fork is active in the given block.
+    pub fn is_active(&self, block: &BlockEnv) -> bool {
+        match *self {
+            Self::Block(number) => block.number >= U256::from(number),
+            Self::Timestamp(timestamp) => block.timestamp >= U256::from(timestamp),
+        }
+    }
+}
+
+/// Configuration of a chain: its hardfork schedule, the changes it makes to [`CfgEnv`] and the
+/// handler it runs with.
+///
+/// [`EvmBuilder::with_chain_spec`](crate::EvmBuilder::with_chain_spec) resolves the [`SpecId`]
+/// of a block from the schedule and configures the EVM consistently with it.
+#[derive(Clone, Debug)]
+pub struct ChainSpec {
+    /// Name of the chain.
+    pub name: &'static str,
+    /// Chain ID, set in [`CfgEnv::chain_id`].
+    pub chain_id: u64,
+    /// Hardforks in the order of their activation.
+    ///
+    /// A hardfork is only active if all hardforks before it are, blocks before the first one
+    /// resolve to its spec.
+    pub hardforks: Cow<'static, [(SpecId, ForkCondition)]>,
+    /// Changes made to the [`CfgEnv`] of the chain after its chain ID is set.
+    pub modify_cfg: fn(&mut CfgEnv),
+    /// Whether the Optimism handler is installed.
+    #[cfg(feature = "optimism")]
+    pub is_optimism: bool,
+}
+
+impl ChainSpec {
+    /// Ethereum mainnet.
+    pub const MAINNET: Self = Self {
+        name: "mainnet",
+        chain_id: 1,
+        hardforks: Cow::Borrowed(&[
+            (SpecId::FRONTIER, ForkCondition::Block(0)),
+            (SpecId::FRONTIER_THAWING, ForkCondition::Block(200_000)),
+            (SpecId::HOMESTEAD, ForkCondition::Block(1_150_000)),
+            (SpecId::DAO_FORK, ForkCondition::Block(1_920_000)),
+            (SpecId::TANGERINE, ForkCondition::Block(2_463_000)),
+            (SpecId::SPURIOUS_DRAGON, ForkCondition::Block(2_675_000)),
+            (SpecId::BYZANTIUM, ForkCondition::Block(4_370_000)),
+            (SpecId::CONSTANTINOPLE, ForkCondition::Block(7_280_000)),
+            (SpecId::PETERSBURG, ForkCondition::Block(7_280_000)),
+            (SpecId::ISTANBUL, ForkCondition::Block(9_069_000)),
+            (SpecId::MUIR_GLACIER, ForkCondition::Block(9_200_000)),
+            (SpecId::BERLIN, ForkCondition::Block(12_244_000)),
+            (SpecId::LONDON, ForkCondition::Block(12_965_000)),
+            (SpecId::ARROW_GLACIER, ForkCondition::Block(13_773_000)),
+            (SpecId::GRAY_GLACIER, ForkCondition::Block(15_050_000)),
+            (SpecId::MERGE, ForkCondition::Block(15_537_394)),
+            (SpecId::SHANGHAI, ForkCondition::Timestamp(1_681_338_455)),
+            (SpecId::CANCUN, ForkCondition::Timestamp(1_710_338_135)),
+            (SpecId::PRAGUE, ForkCondition::Timestamp(1_746_612_311)),
+        ]),
+        modify_cfg: keep_cfg,
+        #[cfg(feature = "optimism")]
+        is_optimism: false,
+    };
+
+    /// OP mainnet, from Bedrock.
+    #[cfg(feature = "optimism")]
+    pub const OP_MAINNET: Self = Self {
+        name: "optimism",
+        chain_id: 10,
+        hardforks: Cow::Borrowed(&[
+            (SpecId::BEDROCK, ForkCondition::Block(105_235_063)),
+            (SpecId::REGOLITH, ForkCondition::Timestamp(0)),
+            (SpecId::CANYON, ForkCondition::Timestamp(1_704_992_401)),
+            (SpecId::ECOTONE, ForkCondition::Timestamp(1_710_374_401)),
+            (SpecId::FJORD, ForkCondition::Timestamp(1_720_627_201)),
+            (SpecId::GRANITE, ForkCondition::Timestamp(1_726_070_401)),
+            (SpecId::HOLOCENE, ForkCondition::Timestamp(1_736_445_601)),
+            (SpecId::ISTHMUS, ForkCondition::Timestamp(1_746_806_401)),
+        ]),
+        modify_cfg: keep_cfg,
+        is_optimism: true,
+    };
+
+    /// Returns the spec of the given block.
+    pub fn spec_id(&self, block: &BlockEnv) -> SpecId {
+        let mut hardforks = self.hardforks.iter();
+        let Some(&(mut spec_id, first)) = hardforks.next() else {
+            return SpecId::LATEST;
+        };
+        if !first.is_active(block) {
+            return spec_id;
+        }
+        for (fork_spec_id, condition) in hardforks {
+            if !condition.is_active(block) {
+                break;
+            }
+            spec_id = *fork_spec_id;
+        }
+        spec_id
+    }
+
+    /// Applies the chain ID and the changes of the chain to the given [`CfgEnv`].
+    pub fn configure(&self, cfg: &mut CfgEnv) {
+        cfg.chain_id = self.chain_id;
+        (self.modify_cfg)(cfg);
+    }
+}
+
+fn keep_cfg(_: &mut CfgEnv) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        interpreter::opcode::{PUSH0, STOP},
+        primitives::{address, AccountInfo, Bytecode, ExecutionResult, HaltReason, TxKind},
+        Evm,
+    };
+
+    fn block(number: u64, timestamp: u64) -> BlockEnv {
+        BlockEnv {
+            number: U256::from(number),
+            timestamp: U256::from(timestamp),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mainnet_spec_id() {
+        let spec = ChainSpec::MAINNET;
+        assert_eq!(spec.spec_id(&block(0, 0)), SpecId::FRONTIER);
+        assert_eq!(spec.spec_id(&block(7_280_000, 0)), SpecId::PETERSBURG);
+        assert_eq!(
+            spec.spec_id(&block(17_034_869, 1_681_338_443)),
+            SpecId::MERGE
+        );
+        assert_eq!(
+            spec.spec_id(&block(17_034_870, 1_681_338_455)),
+            SpecId::SHANGHAI
+        );
+        assert_eq!(
+            spec.spec_id(&block(22_431_084, 1_746_612_311)),
+            SpecId::PRAGUE
+        );
+        // Timestamp forks are not active before the block forks.
+        assert_eq!(
+            spec.spec_id(&block(1_000_000, 1_800_000_000)),
+            SpecId::FRONTIER_THAWING
+        );
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn op_mainnet_spec_id() {
+        let spec = ChainSpec::OP_MAINNET;
+        assert_eq!(spec.spec_id(&block(0, 1_700_000_000)), SpecId::BEDROCK);
+        assert_eq!(
+            spec.spec_id(&block(105_235_063, 1_686_068_903)),
+            SpecId::REGOLITH
+        );
+        assert_eq!(
+            spec.spec_id(&block(120_000_000, 1_710_374_401)),
+            SpecId::ECOTONE
+        );
+    }
+
+    /// Executes `PUSH0` with the mainnet spec of the given block.
+    fn push0(number: u64, timestamp: u64) -> (SpecId, ExecutionResult) {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let target = address!("0000000000000000000000000000000000001000");
+        let mut db = InMemoryDB::default();
+        let code = Bytecode::new_legacy([PUSH0, STOP].into());
+        db.insert_account_info(target, AccountInfo::from_bytecode(code));
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_chain_spec(&ChainSpec::MAINNET, block(number, timestamp))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(target);
+                tx.gas_limit = 100_000;
+                tx.chain_id = Some(1);
+            })
+            .build();
+        assert_eq!(evm.cfg().chain_id, 1);
+        let result = evm.transact().unwrap().result;
+        (evm.spec_id(), result)
+    }
+
+    #[test]
+    fn builder_resolves_spec_of_block() {
+        let (spec_id, result) = push0(17_034_869, 1_681_338_443);
+        assert_eq!(spec_id, SpecId::MERGE);
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::NotActivated,
+                ..
+            }
+        ));
+
+        let (spec_id, result) = push0(17_034_870, 1_681_338_455);
+        assert_eq!(spec_id, SpecId::SHANGHAI);
+        assert!(result.is_success());
+    }
+}
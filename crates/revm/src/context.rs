@@ -1,11 +1,13 @@
 mod context_precompiles;
+mod context_view;
 pub(crate) mod evm_context;
 mod inner_evm_context;
 
 pub use context_precompiles::{
     ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile, ContextStatefulPrecompileArc,
-    ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
+    ContextStatefulPrecompileBox, ContextStatefulPrecompileMut, PrecompileAddresses,
 };
+pub use context_view::ContextView;
 pub use evm_context::EvmContext;
 pub use inner_evm_context::InnerEvmContext;
 use revm_interpreter::{as_u64_saturated, StateLoad};
@@ -15,7 +17,7 @@ use crate::{
     interpreter::{AccountLoad, Host, SStoreResult, SelfDestructResult},
     primitives::{Address, Bytes, Env, HandlerCfg, Log, B256, BLOCK_HASH_HISTORY, U256},
 };
-use std::boxed::Box;
+use std::{boxed::Box, vec::Vec};
 
 /// Main Context structure that contains both EvmContext and External context.
 pub struct Context<EXT, DB: Database> {
@@ -178,6 +180,13 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
             .ok()
     }
 
+    fn sload_many(&mut self, address: Address, indices: &[U256]) -> Option<Vec<StateLoad<U256>>> {
+        self.evm
+            .sload_many(address, indices)
+            .map_err(|e| self.evm.error = Err(e))
+            .ok()
+    }
+
     fn tload(&mut self, address: Address, index: U256) -> U256 {
         self.evm.tload(address, index)
     }
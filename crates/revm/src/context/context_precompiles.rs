@@ -1,7 +1,7 @@
 use super::InnerEvmContext;
 use crate::{
     precompile::{Precompile, PrecompileResult},
-    primitives::{db::Database, Address, Bytes, HashMap, HashSet},
+    primitives::{db::Database, hash_map, Address, Bytes, HashMap, HashSet},
 };
 use dyn_clone::DynClone;
 use revm_precompile::{PrecompileSpecId, PrecompileWithAddress, Precompiles};
@@ -94,12 +94,14 @@ impl<DB: Database> ContextPrecompiles<DB> {
     }
 
     /// Returns precompiles addresses.
+    ///
+    /// Does not allocate, unlike [`Self::addresses_set`].
     #[inline]
-    pub fn addresses<'a>(&'a self) -> Box<dyn ExactSizeIterator<Item = &'a Address> + 'a> {
-        match self.inner {
-            PrecompilesCow::StaticRef(inner) => Box::new(inner.addresses()),
-            PrecompilesCow::Owned(ref inner) => Box::new(inner.keys()),
-        }
+    pub fn addresses(&self) -> PrecompileAddresses<'_, DB> {
+        PrecompileAddresses(match self.inner {
+            PrecompilesCow::StaticRef(inner) => AddressesInner::StaticRef(inner.inner().keys()),
+            PrecompilesCow::Owned(ref inner) => AddressesInner::Owned(inner.keys()),
+        })
     }
 
     /// Returns `true` if the precompiles contains the given address.
@@ -164,6 +166,36 @@ impl<DB: Database> ContextPrecompiles<DB> {
     }
 }
 
+/// Iterator over the addresses of [`ContextPrecompiles`].
+pub struct PrecompileAddresses<'a, DB: Database>(AddressesInner<'a, DB>);
+
+enum AddressesInner<'a, DB: Database> {
+    StaticRef(hash_map::Keys<'a, Address, Precompile>),
+    Owned(hash_map::Keys<'a, Address, ContextPrecompile<DB>>),
+}
+
+impl<'a, DB: Database> Iterator for PrecompileAddresses<'a, DB> {
+    type Item = &'a Address;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            AddressesInner::StaticRef(keys) => keys.next(),
+            AddressesInner::Owned(keys) => keys.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            AddressesInner::StaticRef(keys) => keys.size_hint(),
+            AddressesInner::Owned(keys) => keys.size_hint(),
+        }
+    }
+}
+
+impl<DB: Database> ExactSizeIterator for PrecompileAddresses<'_, DB> {}
+
 impl<DB: Database> Extend<(Address, ContextPrecompile<DB>)> for ContextPrecompiles<DB> {
     fn extend<T: IntoIterator<Item = (Address, ContextPrecompile<DB>)>>(&mut self, iter: T) {
         self.to_mut().extend(iter.into_iter().map(Into::into))
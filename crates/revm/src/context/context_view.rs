@@ -0,0 +1,208 @@
+use super::{EvmContext, PrecompileAddresses};
+use crate::{
+    db::Database,
+    primitives::{Env, SpecId},
+    JournaledState,
+};
+
+/// Read-only view into the [`EvmContext`].
+///
+/// Gives external tooling a stable way to inspect the context without depending on its internal
+/// layout. It only borrows the context, nothing is copied.
+///
+/// Obtained from [`Evm::context_view`](crate::Evm::context_view) or, inside of an
+/// [`Inspector`](crate::Inspector), from [`EvmContext::view`].
+///
+/// # Example
+///
+/// ```
+/// use revm::{
+///     db::Database, interpreter::Interpreter, primitives::SpecId, EvmContext, Inspector,
+/// };
+///
+/// #[derive(Default)]
+/// struct MaxDepth(u64);
+///
+/// impl<DB: Database> Inspector<DB> for MaxDepth {
+///     fn step(&mut self, _interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+///         self.0 = self.0.max(context.view().depth());
+///     }
+/// }
+///
+/// let evm = revm::Evm::builder().with_spec_id(SpecId::CANCUN).build();
+/// let view = evm.context_view();
+/// assert_eq!(view.spec_id(), SpecId::CANCUN);
+/// assert_eq!(view.depth(), 0);
+/// assert_eq!(view.env().cfg.chain_id, 1);
+/// ```
+pub struct ContextView<'a, DB: Database> {
+    context: &'a EvmContext<DB>,
+}
+
+impl<DB: Database> Clone for ContextView<'_, DB> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<DB: Database> Copy for ContextView<'_, DB> {}
+
+impl<'a, DB: Database> ContextView<'a, DB> {
+    /// Creates a new view of the given context.
+    #[inline]
+    pub fn new(context: &'a EvmContext<DB>) -> Self {
+        Self { context }
+    }
+
+    /// Returns the environment of the current transaction.
+    #[inline]
+    pub fn env(&self) -> &'a Env {
+        &self.context.inner.env
+    }
+
+    /// Returns the spec the context is configured with.
+    #[inline]
+    pub fn spec_id(&self) -> SpecId {
+        self.context.inner.spec_id()
+    }
+
+    /// Returns the journaled state.
+    #[inline]
+    pub fn journaled_state(&self) -> &'a JournaledState {
+        &self.context.inner.journaled_state
+    }
+
+    /// Returns the current call depth.
+    #[inline]
+    pub fn depth(&self) -> u64 {
+        self.context.inner.journaled_state.depth()
+    }
+
+    /// Returns the L1 block info of the current transaction, if it was fetched.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn l1_block_info(&self) -> Option<&'a crate::optimism::L1BlockInfo> {
+        self.context.inner.l1_block_info.as_ref()
+    }
+
+    /// Returns the addresses of the precompiles loaded for the current transaction.
+    ///
+    /// Precompiles are loaded at the start of the transaction execution.
+    #[inline]
+    pub fn loaded_precompile_addresses(&self) -> PrecompileAddresses<'a, DB> {
+        self.context.precompiles.addresses()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::{opcode, CallInputs, CallOutcome, Interpreter},
+        precompile::Precompiles,
+        primitives::{address, Bytecode, Bytes, TxKind},
+        Evm, Inspector,
+    };
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct ViewInspector {
+        depths: Vec<u64>,
+        spec_id: Option<SpecId>,
+    }
+
+    impl<DB: Database> Inspector<DB> for ViewInspector {
+        fn step(&mut self, _interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+            let view = context.view();
+            assert_eq!(view.depth(), view.journaled_state().depth());
+            self.spec_id = Some(view.spec_id());
+        }
+
+        fn call(
+            &mut self,
+            context: &mut EvmContext<DB>,
+            _inputs: &mut CallInputs,
+        ) -> Option<CallOutcome> {
+            self.depths.push(context.view().depth());
+            None
+        }
+    }
+
+    #[test]
+    fn view_matches_context() {
+        let mut evm = Evm::builder().with_spec_id(SpecId::BERLIN).build();
+        evm.context.evm.inner.env.cfg.chain_id = 5;
+        evm.context.evm.inner.journaled_state.depth = 3;
+
+        let view = evm.context_view();
+        assert_eq!(view.spec_id(), SpecId::BERLIN);
+        assert_eq!(view.env().cfg.chain_id, 5);
+        assert_eq!(view.depth(), 3);
+        assert!(core::ptr::eq(
+            view.journaled_state(),
+            &evm.context.evm.inner.journaled_state
+        ));
+        #[cfg(feature = "optimism")]
+        assert!(view.l1_block_info().is_none());
+    }
+
+    #[test]
+    fn view_lists_loaded_precompiles() {
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        assert_eq!(evm.context_view().loaded_precompile_addresses().len(), 0);
+
+        evm.transact().unwrap();
+        let view = evm.context_view();
+        assert_eq!(
+            view.loaded_precompile_addresses().len(),
+            Precompiles::cancun().len()
+        );
+        assert!(view
+            .loaded_precompile_addresses()
+            .any(|a| *a == address!("0000000000000000000000000000000000000001")));
+    }
+
+    #[test]
+    fn inspector_view() {
+        // Calls itself once, the nested call returns immediately as it is out of gas.
+        let code = Bytes::from(vec![
+            opcode::PUSH1,
+            0x0,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::ADDRESS,
+            opcode::PUSH1,
+            0x0,
+            opcode::CALL,
+            opcode::STOP,
+        ]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .with_external_context(ViewInspector::default())
+            .with_spec_id(SpecId::SHANGHAI)
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+
+        let inspector = evm.into_context().external;
+        assert_eq!(inspector.depths, [0, 1]);
+        assert_eq!(inspector.spec_id, Some(SpecId::SHANGHAI));
+    }
+}
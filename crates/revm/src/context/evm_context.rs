@@ -5,15 +5,17 @@ use super::inner_evm_context::InnerEvmContext;
 use crate::{
     db::Database,
     interpreter::{
-        analysis::validate_eof, CallInputs, Contract, CreateInputs, EOFCreateInputs, EOFCreateKind,
-        Gas, InstructionResult, Interpreter, InterpreterResult,
+        analysis::{validate_eof, ValidationKind},
+        CallInputs, Contract, CreateInputs, EOFCreateInputs, EOFCreateKind, Gas, InstructionResult,
+        InterpreterResult, STACK_LIMIT,
     },
     primitives::{
-        keccak256, Address, Bytecode, Bytes, CreateScheme, EVMError, Env, Eof,
+        create2_address, create_address, Address, Bytecode, Bytes, CreateScheme, EVMError, Env,
+        Eof,
         SpecId::{self, *},
         B256, EOF_MAGIC_BYTES,
     },
-    ContextPrecompiles, FrameOrResult, CALL_STACK_LIMIT,
+    ContextPrecompiles, ContextView, Frame, FrameOrResult, FramePool, CALL_STACK_LIMIT,
 };
 use core::{
     fmt,
@@ -27,6 +29,8 @@ pub struct EvmContext<DB: Database> {
     pub inner: InnerEvmContext<DB>,
     /// Precompiles that are available for evm.
     pub precompiles: ContextPrecompiles<DB>,
+    /// Frames of finished calls, reused by following calls.
+    pub frame_pool: FramePool,
 }
 
 impl<DB: Database + Clone> Clone for EvmContext<DB>
@@ -37,6 +41,7 @@ where
         Self {
             inner: self.inner.clone(),
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 }
@@ -74,6 +79,7 @@ impl<DB: Database> EvmContext<DB> {
         Self {
             inner: InnerEvmContext::new(db),
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 
@@ -83,6 +89,7 @@ impl<DB: Database> EvmContext<DB> {
         Self {
             inner: InnerEvmContext::new_with_env(db, env),
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 
@@ -94,16 +101,34 @@ impl<DB: Database> EvmContext<DB> {
         EvmContext {
             inner: self.inner.with_db(db),
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 
+    /// Returns a read-only view of the context.
+    #[inline]
+    pub fn view(&self) -> ContextView<'_, DB> {
+        ContextView::new(self)
+    }
+
     /// Sets precompiles
+    ///
+    /// Their addresses are warmed unless [`CfgEnv::warm_precompiles`] is disabled.
+    ///
+    /// [`CfgEnv::warm_precompiles`]: crate::primitives::CfgEnv::warm_precompiles
     #[inline]
     pub fn set_precompiles(&mut self, precompiles: ContextPrecompiles<DB>) {
         // set warm loaded addresses.
-        self.journaled_state
-            .warm_preloaded_addresses
-            .extend(precompiles.addresses_set());
+        if self.env.cfg.warm_precompiles {
+            self.journaled_state
+                .warm_preloaded_addresses
+                .extend(precompiles.addresses().copied());
+            #[cfg(feature = "access-debug")]
+            for address in precompiles.addresses() {
+                self.journaled_state
+                    .record_warm_address(*address, crate::WarmingReason::Precompile);
+            }
+        }
         self.precompiles = precompiles;
     }
 
@@ -115,6 +140,10 @@ impl<DB: Database> EvmContext<DB> {
         input_data: &Bytes,
         gas: Gas,
     ) -> Result<Option<InterpreterResult>, EVMError<DB::Error>> {
+        if !self.precompiles.contains(address) {
+            return Ok(None);
+        }
+        instrument_span!(TRACE, "precompile", %address, gas_limit = gas.limit());
         let Some(outcome) =
             self.precompiles
                 .call(address, input_data, gas.limit(), &mut self.inner)
@@ -126,6 +155,7 @@ impl<DB: Database> EvmContext<DB> {
             result: InstructionResult::Return,
             gas,
             output: Bytes::new(),
+            halt_context: None,
         };
 
         match outcome {
@@ -149,6 +179,18 @@ impl<DB: Database> EvmContext<DB> {
         Ok(Some(result))
     }
 
+    /// Applies the [configured stack limit](crate::primitives::CfgEnv::stack_limit) to the stack
+    /// of a new frame, which may come from the pool with the limit of another configuration.
+    #[inline]
+    fn with_stack_limit(&self, mut frame: Frame) -> Frame {
+        let limit = self.env.cfg.stack_limit.unwrap_or(STACK_LIMIT);
+        let stack = &mut frame.interpreter_mut().stack;
+        if stack.limit() != limit {
+            stack.set_limit(limit);
+        }
+        frame
+    }
+
     /// Make call frame
     #[inline]
     pub fn make_call_frame(
@@ -163,6 +205,7 @@ impl<DB: Database> EvmContext<DB> {
                     result: instruction_result,
                     gas,
                     output: Bytes::new(),
+                    halt_context: None,
                 },
                 inputs.return_memory_offset.clone(),
             ))
@@ -241,25 +284,59 @@ impl<DB: Database> EvmContext<DB> {
             return return_result(InstructionResult::Stop);
         }
 
+        #[cfg_attr(not(feature = "std"), allow(unused_variables, unused_assignments))]
+        let mut bytecode_hash = code_hash;
         if let Bytecode::Eip7702(eip7702_bytecode) = bytecode {
-            bytecode = self
+            let info = &self
                 .inner
                 .journaled_state
                 .load_code(eip7702_bytecode.delegated_address, &mut self.inner.db)?
-                .info
-                .code
-                .clone()
-                .unwrap_or_default();
+                .info;
+            bytecode_hash = info.code_hash();
+            bytecode = info.code.clone().unwrap_or_default();
+        }
+
+        // Code starting with the EOF magic is executed as EOF only once EOF is enabled, whatever
+        // the database decoded it to. Before, it is legacy code halting on the undefined 0xEF
+        // opcode, as it could not be deployed since EIP-3541. After, it has to be a valid
+        // runtime container.
+        if bytecode.bytes_slice().starts_with(&EOF_MAGIC_BYTES) {
+            if !self.spec_id().is_enabled_in(OSAKA) {
+                if let Bytecode::Eof(eof) = &bytecode {
+                    bytecode = Bytecode::new_legacy(eof.raw.clone());
+                }
+            } else {
+                let eof = match &bytecode {
+                    Bytecode::Eof(eof) => Ok(eof.clone()),
+                    _ => Eof::decode(bytecode.original_bytes()).map(Arc::new),
+                };
+                let Some(eof) = eof
+                    .ok()
+                    .filter(|eof| validate_eof(eof, ValidationKind::Runtime).is_ok())
+                else {
+                    self.journaled_state.checkpoint_revert(checkpoint);
+                    return return_result(InstructionResult::InvalidEOFCode);
+                };
+                bytecode = Bytecode::Eof(eof);
+            }
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(cache) = &self.inner.analysis_cache {
+            bytecode = cache.analyse(bytecode_hash, bytecode);
         }
 
         let contract =
             Contract::new_with_context(inputs.input.clone(), bytecode, Some(code_hash), inputs);
         // Create interpreter and executes call and push new CallStackFrame.
-        Ok(FrameOrResult::new_call_frame(
+        let frame = self.frame_pool.new_call(
             inputs.return_memory_offset.clone(),
             checkpoint,
-            Interpreter::new(contract, gas.limit(), inputs.is_static),
-        ))
+            contract,
+            gas.limit(),
+            inputs.is_static,
+        );
+        Ok(FrameOrResult::Frame(self.with_stack_limit(frame)))
     }
 
     /// Make create frame.
@@ -275,6 +352,7 @@ impl<DB: Database> EvmContext<DB> {
                     result: e,
                     gas: Gas::new(inputs.gas_limit),
                     output: Bytes::new(),
+                    halt_context: None,
                 },
                 None,
             ))
@@ -309,10 +387,10 @@ impl<DB: Database> EvmContext<DB> {
         // Create address
         let mut init_code_hash = B256::ZERO;
         let created_address = match inputs.scheme {
-            CreateScheme::Create => inputs.caller.create(old_nonce),
+            CreateScheme::Create => create_address(inputs.caller, old_nonce),
             CreateScheme::Create2 { salt } => {
-                init_code_hash = keccak256(&inputs.init_code);
-                inputs.caller.create2(salt.to_be_bytes(), init_code_hash)
+                init_code_hash = self.env.cfg.keccak.hash(&inputs.init_code);
+                create2_address(inputs.caller, salt, init_code_hash)
             }
         };
 
@@ -349,11 +427,10 @@ impl<DB: Database> EvmContext<DB> {
             inputs.value,
         );
 
-        Ok(FrameOrResult::new_create_frame(
-            created_address,
-            checkpoint,
-            Interpreter::new(contract, inputs.gas_limit, false),
-        ))
+        let frame =
+            self.frame_pool
+                .new_create(created_address, checkpoint, contract, inputs.gas_limit);
+        Ok(FrameOrResult::Frame(self.with_stack_limit(frame)))
     }
 
     /// Make create frame.
@@ -369,6 +446,7 @@ impl<DB: Database> EvmContext<DB> {
                     result: e,
                     gas: Gas::new(inputs.gas_limit),
                     output: Bytes::new(),
+                    halt_context: None,
                 },
                 None,
             ))
@@ -388,7 +466,7 @@ impl<DB: Database> EvmContext<DB> {
                     return return_error(InstructionResult::InvalidEOFInitCode);
                 };
 
-                if validate_eof(&eof).is_err() {
+                if validate_eof(&eof, ValidationKind::Initcode).is_err() {
                     // TODO (EOF) new error type.
                     self.journaled_state.inc_nonce(inputs.caller);
                     return return_error(InstructionResult::InvalidEOFInitCode);
@@ -400,7 +478,7 @@ impl<DB: Database> EvmContext<DB> {
                     .env
                     .tx
                     .nonce
-                    .map(|nonce| self.env.tx.caller.create(nonce));
+                    .map(|nonce| create_address(self.env.tx.caller, nonce));
 
                 (input, eof, nonce)
             }
@@ -426,7 +504,8 @@ impl<DB: Database> EvmContext<DB> {
         };
         let old_nonce = nonce - 1;
 
-        let created_address = created_address.unwrap_or_else(|| inputs.caller.create(old_nonce));
+        let created_address =
+            created_address.unwrap_or_else(|| create_address(inputs.caller, old_nonce));
 
         // created address is not allowed to be a precompile.
         if self.precompiles.contains(&created_address) {
@@ -460,15 +539,10 @@ impl<DB: Database> EvmContext<DB> {
             inputs.value,
         );
 
-        let mut interpreter = Interpreter::new(contract, inputs.gas_limit, false);
-        // EOF init will enable RETURNCONTRACT opcode.
-        interpreter.set_is_eof_init();
-
-        Ok(FrameOrResult::new_eofcreate_frame(
-            created_address,
-            checkpoint,
-            interpreter,
-        ))
+        let frame =
+            self.frame_pool
+                .new_eofcreate(created_address, checkpoint, contract, inputs.gas_limit);
+        Ok(FrameOrResult::Frame(self.with_stack_limit(frame)))
     }
 }
 
@@ -535,8 +609,15 @@ pub(crate) mod test_utils {
                 error: Ok(()),
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
+                #[cfg(feature = "optimism")]
+                op_tx_fees: None,
+                fee_summary: None,
+                state_overrides: Default::default(),
+                #[cfg(feature = "std")]
+                analysis_cache: None,
             },
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 
@@ -550,8 +631,15 @@ pub(crate) mod test_utils {
                 error: Ok(()),
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
+                #[cfg(feature = "optimism")]
+                op_tx_fees: None,
+                fee_summary: None,
+                state_overrides: Default::default(),
+                #[cfg(feature = "std")]
+                analysis_cache: None,
             },
             precompiles: ContextPrecompiles::default(),
+            frame_pool: FramePool::default(),
         }
     }
 }
@@ -562,10 +650,11 @@ mod tests {
     use crate::primitives::U256;
     use crate::{
         db::{CacheDB, EmptyDB},
+        interpreter::{Host, StateLoad},
         primitives::{address, Bytecode},
-        Frame, JournalEntry,
+        Context, Frame, JournalEntry,
     };
-    use std::boxed::Box;
+    use std::{boxed::Box, vec::Vec};
     use test_utils::*;
 
     // Tests that the `EVMContext::make_call_frame` function returns an error if the
@@ -651,4 +740,110 @@ mod tests {
         };
         assert_eq!(call_frame.return_memory_range, 0..0,);
     }
+
+    #[test]
+    fn test_batched_storage_access_matches_scalar() {
+        let contract = address!("dead10000000000000000000000000000001dead");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_storage(contract, U256::from(1), U256::from(10))
+            .unwrap();
+        db.insert_account_storage(contract, U256::from(2), U256::from(20))
+            .unwrap();
+        let mut context = create_cache_db_evm_context(Box::default(), db);
+        context
+            .inner
+            .journaled_state
+            .load_account(contract, &mut context.inner.db)
+            .unwrap();
+        // warm slot 2 before the batch.
+        context.sload(contract, U256::from(2)).unwrap();
+
+        let mut scalar = Context {
+            evm: context.clone(),
+            external: (),
+        };
+        let mut batched = Context {
+            evm: context,
+            external: (),
+        };
+
+        let indices = [1, 2, 3, 1].map(U256::from);
+        let expected: Vec<_> = indices
+            .iter()
+            .map(|index| scalar.sload(contract, *index).unwrap())
+            .collect();
+        assert_eq!(
+            expected.iter().map(|load| load.is_cold).collect::<Vec<_>>(),
+            [true, false, true, false]
+        );
+        assert_eq!(batched.sload_many(contract, &indices), Some(expected));
+        // all slots are warm now.
+        assert_eq!(
+            batched.sload_many(contract, &indices).unwrap(),
+            [10, 20, 0, 10].map(|value| StateLoad::new(U256::from(value), false))
+        );
+        scalar.sload_many(contract, &indices).unwrap();
+
+        let entries =
+            [(3, 30), (4, 40), (3, 31), (1, 10)].map(|(i, v)| (U256::from(i), U256::from(v)));
+        let expected: Vec<_> = entries
+            .iter()
+            .map(|(index, value)| scalar.sstore(contract, *index, *value).unwrap())
+            .collect();
+        assert_eq!(batched.sstore_many(contract, &entries), Some(expected));
+
+        assert_eq!(
+            batched.evm.journaled_state.journal,
+            scalar.evm.journaled_state.journal
+        );
+        assert_eq!(
+            batched.evm.journaled_state.state,
+            scalar.evm.journaled_state.state
+        );
+    }
+
+    #[test]
+    fn test_block_hash_window_and_history() {
+        use crate::primitives::{B256, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS};
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for number in 0..=300u64 {
+            db.block_hashes
+                .insert(U256::from(number), B256::from(U256::from(number + 1)));
+        }
+        let slot = U256::from(299 % BLOCKHASH_SERVE_WINDOW as u64);
+        db.insert_account_storage(BLOCKHASH_STORAGE_ADDRESS, slot, U256::from(42))
+            .unwrap();
+        let mut env = Box::<Env>::default();
+        env.block.number = U256::from(300);
+        let mut context = Context {
+            evm: create_cache_db_evm_context(env, db),
+            external: (),
+        };
+
+        // The host returns zero out of the `BLOCKHASH` window.
+        let known = B256::from(U256::from(300 - 256 + 1));
+        for (number, expected) in [
+            (299, B256::from(U256::from(300))),
+            (300 - 256, known),
+            (300 - 257, B256::ZERO),
+            (300, B256::ZERO),
+            (301, B256::ZERO),
+        ] {
+            assert_eq!(context.block_hash(number), Some(expected), "{number}");
+        }
+
+        // The history contract is read from the database, then from the journal once loaded.
+        let history = |context: &mut Context<(), CacheDB<EmptyDB>>, number| {
+            context.evm.history_block_hash(number).unwrap()
+        };
+        assert_eq!(history(&mut context, 300), None);
+        assert_eq!(history(&mut context, 299), Some(B256::from(U256::from(42))));
+        assert!(context.evm.journaled_state.state.is_empty());
+        context.evm.load_account(BLOCKHASH_STORAGE_ADDRESS).unwrap();
+        context
+            .sstore(BLOCKHASH_STORAGE_ADDRESS, slot, U256::from(43))
+            .unwrap();
+        assert_eq!(history(&mut context, 299), Some(B256::from(U256::from(43))));
+    }
 }
@@ -1,19 +1,20 @@
 use crate::{
     db::Database,
     interpreter::{
-        analysis::to_analysed, gas, return_ok, AccountLoad, InstructionResult, InterpreterResult,
-        SStoreResult, SelfDestructResult, StateLoad,
+        analysis::to_analysed, as_u64_saturated, gas, return_ok, AccountLoad, InstructionResult,
+        InterpreterResult, SStoreResult, SelfDestructResult, StateLoad,
     },
     journaled_state::JournaledState,
     primitives::{
         AccessListItem, Account, Address, AnalysisKind, Bytecode, Bytes, CfgEnv, EVMError, Env,
-        Eof, HashSet, Spec,
+        Eof, FeeSummary, HashSet, ReturnDataLimit, Spec,
         SpecId::{self, *},
-        B256, EOF_MAGIC_BYTES, EOF_MAGIC_HASH, U256,
+        B256, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, EOF_MAGIC_BYTES, EOF_MAGIC_HASH,
+        U256,
     },
-    JournalCheckpoint,
+    JournalCheckpoint, StateOverrides,
 };
-use std::{boxed::Box, sync::Arc};
+use std::{boxed::Box, sync::Arc, vec::Vec};
 
 /// EVM contexts contains data that EVM needs for execution.
 #[derive(Debug)]
@@ -30,6 +31,16 @@ pub struct InnerEvmContext<DB: Database> {
     /// Used as temporary value holder to store L1 block info.
     #[cfg(feature = "optimism")]
     pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    /// Fees charged to the last executed Optimism transaction.
+    #[cfg(feature = "optimism")]
+    pub op_tx_fees: Option<crate::optimism::OpTxFees>,
+    /// Fees paid by the last executed transaction.
+    pub fee_summary: Option<FeeSummary>,
+    /// Overrides of the code and storage of accounts, applied before each transaction.
+    pub state_overrides: StateOverrides,
+    /// Cache of legacy bytecode jump tables, shared between EVMs.
+    #[cfg(feature = "std")]
+    pub analysis_cache: Option<Arc<crate::AnalysisCache>>,
 }
 
 impl<DB: Database + Clone> Clone for InnerEvmContext<DB>
@@ -44,6 +55,12 @@ where
             error: self.error.clone(),
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info.clone(),
+            #[cfg(feature = "optimism")]
+            op_tx_fees: self.op_tx_fees,
+            fee_summary: self.fee_summary,
+            state_overrides: self.state_overrides.clone(),
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache.clone(),
         }
     }
 }
@@ -57,6 +74,12 @@ impl<DB: Database> InnerEvmContext<DB> {
             error: Ok(()),
             #[cfg(feature = "optimism")]
             l1_block_info: None,
+            #[cfg(feature = "optimism")]
+            op_tx_fees: None,
+            fee_summary: None,
+            state_overrides: StateOverrides::default(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
         }
     }
 
@@ -70,6 +93,12 @@ impl<DB: Database> InnerEvmContext<DB> {
             error: Ok(()),
             #[cfg(feature = "optimism")]
             l1_block_info: None,
+            #[cfg(feature = "optimism")]
+            op_tx_fees: None,
+            fee_summary: None,
+            state_overrides: StateOverrides::default(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
         }
     }
 
@@ -85,6 +114,12 @@ impl<DB: Database> InnerEvmContext<DB> {
             error: Ok(()),
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info,
+            #[cfg(feature = "optimism")]
+            op_tx_fees: self.op_tx_fees,
+            fee_summary: self.fee_summary,
+            state_overrides: self.state_overrides,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache,
         }
     }
 
@@ -109,6 +144,30 @@ impl<DB: Database> InnerEvmContext<DB> {
                 storage_keys.iter().map(|i| U256::from_be_bytes(i.0)),
                 &mut self.db,
             )?;
+            #[cfg(feature = "access-debug")]
+            {
+                use crate::WarmingReason::AccessList;
+                self.journaled_state
+                    .record_warm_address(*address, AccessList);
+                for key in storage_keys {
+                    let key = U256::from_be_bytes(key.0);
+                    self.journaled_state
+                        .record_warm_slot(*address, key, AccessList);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the [state overrides](StateOverrides) to the journaled state.
+    pub fn apply_state_overrides(&mut self) -> Result<(), EVMError<DB::Error>> {
+        for (address, account) in &self.state_overrides.accounts {
+            self.journaled_state.override_account(
+                *address,
+                account.code.clone(),
+                account.storage.iter().map(|(key, value)| (*key, *value)),
+                &mut self.db,
+            )?;
         }
         Ok(())
     }
@@ -136,6 +195,37 @@ impl<DB: Database> InnerEvmContext<DB> {
         self.db.block_hash(number).map_err(EVMError::Database)
     }
 
+    /// Fetch block hash from the storage of the EIP-2935 history contract.
+    ///
+    /// Returns `None` if the block is not one of the [`BLOCKHASH_SERVE_WINDOW`] most recent
+    /// complete blocks. The history contract is deployed from Prague, `BLOCKHASH` doesn't read it.
+    ///
+    /// A slot loaded by the transaction is read from the journal, other slots from the database.
+    /// The account and the slot are not warmed.
+    #[inline]
+    pub fn history_block_hash(&mut self, number: u64) -> Result<Option<B256>, EVMError<DB::Error>> {
+        let block_number = as_u64_saturated!(self.env.block.number);
+        let window = BLOCKHASH_SERVE_WINDOW as u64;
+        match block_number.checked_sub(number) {
+            Some(diff) if diff > 0 && diff <= window => {}
+            _ => return Ok(None),
+        }
+        let slot = U256::from(number % window);
+        if let Some(slot) = self
+            .journaled_state
+            .state
+            .get(&BLOCKHASH_STORAGE_ADDRESS)
+            .and_then(|account| account.storage.get(&slot))
+        {
+            return Ok(Some(B256::from(slot.present_value.to_be_bytes::<32>())));
+        }
+        let value = self
+            .db
+            .storage(BLOCKHASH_STORAGE_ADDRESS, slot)
+            .map_err(EVMError::Database)?;
+        Ok(Some(B256::from(value.to_be_bytes::<32>())))
+    }
+
     /// Mark account as touched as only touched accounts will be added to state.
     #[inline]
     pub fn touch(&mut self, address: &Address) {
@@ -222,6 +312,17 @@ impl<DB: Database> InnerEvmContext<DB> {
         self.journaled_state.sload(address, index, &mut self.db)
     }
 
+    /// Load multiple storage slots of the same account, see [`InnerEvmContext::sload`].
+    #[inline]
+    pub fn sload_many(
+        &mut self,
+        address: Address,
+        indices: &[U256],
+    ) -> Result<Vec<StateLoad<U256>>, EVMError<DB::Error>> {
+        self.journaled_state
+            .sload_many(address, indices, &mut self.db)
+    }
+
     /// Storage change of storage slot, before storing `sload` will be called for that slot.
     #[inline]
     pub fn sstore(
@@ -272,6 +373,7 @@ impl<DB: Database> InnerEvmContext<DB> {
         //
         // Bytes of RETURN will drained in `insert_eofcreate_outcome`.
         if interpreter_result.result != InstructionResult::ReturnContract {
+            self.limit_return_data(interpreter_result);
             self.journaled_state.checkpoint_revert(journal_checkpoint);
             return;
         }
@@ -297,18 +399,46 @@ impl<DB: Database> InnerEvmContext<DB> {
         let bytecode =
             Eof::decode(interpreter_result.output.clone()).expect("Eof is already verified");
 
-        // eof bytecode is going to be hashed.
+        let bytecode = Bytecode::Eof(Arc::new(bytecode));
+        let hash = self.env.cfg.keccak.code_hash(&bytecode);
         self.journaled_state
-            .set_code(address, Bytecode::Eof(Arc::new(bytecode)));
+            .set_code_with_hash(address, bytecode, hash);
+    }
+
+    /// Applies the [configured return data limit](CfgEnv::max_return_data_size) to the output of
+    /// a frame that is handed to its parent as return data.
+    ///
+    /// The output of the first frame is the output of the transaction and is not limited.
+    /// Truncating shares the buffer of the output, halting drops it and consumes the gas of the
+    /// frame.
+    #[inline]
+    fn limit_return_data(&self, interpreter_result: &mut InterpreterResult) {
+        let Some(limit) = self.env.cfg.max_return_data_size else {
+            return;
+        };
+        // The checkpoint of the frame is not committed or reverted yet, the first frame is at
+        // depth 1.
+        if self.journaled_state.depth() <= 1 || interpreter_result.output.len() <= limit {
+            return;
+        }
+        match self.env.cfg.return_data_limit {
+            ReturnDataLimit::Halt => {
+                interpreter_result.result = InstructionResult::ReturnDataSizeLimit;
+                interpreter_result.output = Bytes::new();
+                interpreter_result.gas.spend_all();
+            }
+            ReturnDataLimit::Truncate => interpreter_result.output.truncate(limit),
+        }
     }
 
     /// Handles call return.
     #[inline]
     pub fn call_return(
         &mut self,
-        interpreter_result: &InterpreterResult,
+        interpreter_result: &mut InterpreterResult,
         journal_checkpoint: JournalCheckpoint,
     ) {
+        self.limit_return_data(interpreter_result);
         // revert changes or not.
         if matches!(interpreter_result.result, return_ok!()) {
             self.journaled_state.checkpoint_commit();
@@ -327,6 +457,7 @@ impl<DB: Database> InnerEvmContext<DB> {
     ) {
         // if return is not ok revert and return.
         if !matches!(interpreter_result.result, return_ok!()) {
+            self.limit_return_data(interpreter_result);
             self.journaled_state.checkpoint_revert(journal_checkpoint);
             return;
         }
@@ -375,7 +506,9 @@ impl<DB: Database> InnerEvmContext<DB> {
         };
 
         // set code
-        self.journaled_state.set_code(address, bytecode);
+        let hash = self.env.cfg.keccak.code_hash(&bytecode);
+        self.journaled_state
+            .set_code_with_hash(address, bytecode, hash);
 
         interpreter_result.result = InstructionResult::Return;
     }
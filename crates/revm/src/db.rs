@@ -9,6 +9,11 @@ pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 mod ethersdb;
 pub mod in_memory_db;
+mod overlay_db;
+#[cfg(feature = "std")]
+mod shared_cache_db;
+#[cfg(feature = "snapshot-db")]
+mod snapshot_db;
 pub mod states;
 
 pub use crate::primitives::db::*;
@@ -18,6 +23,11 @@ pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
 pub use in_memory_db::*;
+pub use overlay_db::OverlayDb;
+#[cfg(feature = "std")]
+pub use shared_cache_db::SharedCacheDb;
+#[cfg(feature = "snapshot-db")]
+pub use snapshot_db::{SnapshotDb, SnapshotError};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
@@ -7,7 +7,7 @@ use alloy_provider::{
     network::{primitives::HeaderResponse, BlockResponse},
     Network, Provider,
 };
-use alloy_transport::{Transport, TransportError};
+use alloy_transport::{Transport, TransportError, TransportErrorKind};
 use std::future::IntoFuture;
 use tokio::runtime::{Handle, Runtime};
 
@@ -118,7 +118,7 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> DatabaseRef for AlloyD
         let (nonce, balance, code) = self.block_on(f);
 
         let balance = balance?;
-        let code = Bytecode::new_raw(code?.0.into());
+        let code = Bytecode::new_raw_checked(code?.0.into()).map_err(TransportErrorKind::custom)?;
         let code_hash = code.hash_slow();
         let nonce = nonce?;
 
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use ethers_core::types::{Block, BlockId, TxHash, H160 as eH160, H256, U64 as eU64};
-use ethers_providers::Middleware;
+use ethers_providers::{Middleware, MiddlewareError, ProviderError};
 use tokio::runtime::{Handle, Runtime};
 
 use crate::primitives::{AccountInfo, Address, Bytecode, B256, U256};
@@ -129,7 +129,8 @@ impl<M: Middleware> DatabaseRef for EthersDB<M> {
 
         let balance = U256::from_limbs(balance?.0);
         let nonce = nonce?.as_u64();
-        let bytecode = Bytecode::new_raw(code?.0.into());
+        let bytecode = Bytecode::new_raw_checked(code?.0.into())
+            .map_err(|e| M::Error::from_provider_err(ProviderError::CustomError(e.to_string())))?;
         let code_hash = bytecode.hash_slow();
         Ok(Some(AccountInfo::new(balance, nonce, code_hash, bytecode)))
     }
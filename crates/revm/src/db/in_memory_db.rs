@@ -1,7 +1,7 @@
 use super::{DatabaseCommit, DatabaseRef, EmptyDB};
 use crate::primitives::{
-    hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, Log, B256, KECCAK_EMPTY,
-    U256,
+    hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, Log, B256, I256,
+    KECCAK_EMPTY, U256,
 };
 use crate::Database;
 use core::convert::Infallible;
@@ -22,12 +22,24 @@ pub type InMemoryDB = CacheDB<EmptyDB>;
 pub struct CacheDB<ExtDB> {
     /// Account info where None means it is not existing. Not existing state is needed for Pre TANGERINE forks.
     /// `code` is always `None`, and bytecode can be found in `contracts`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub accounts: HashMap<Address, DbAccount>,
     /// Tracks all contracts by their code hash.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub contracts: HashMap<B256, Bytecode>,
     /// All logs that were committed via [DatabaseCommit::commit].
     pub logs: Vec<Log>,
     /// All cached block hashes from the [DatabaseRef].
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub block_hashes: HashMap<U256, B256>,
     /// The underlying database ([DatabaseRef]) that is used to load data.
     ///
@@ -81,6 +93,19 @@ impl<ExtDB> CacheDB<ExtDB> {
         self.insert_contract(&mut info);
         self.accounts.entry(address).or_default().info = info;
     }
+
+    /// Insert account info with its code but not override storage.
+    ///
+    /// The code hash of `info` is set from `code` and the code is registered in the
+    /// contracts, so executing the account never calls `code_by_hash`.
+    pub fn insert_account_with_code(
+        &mut self,
+        address: Address,
+        info: AccountInfo,
+        code: Bytecode,
+    ) {
+        self.insert_account_info(address, info.with_code(code));
+    }
 }
 
 impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
@@ -127,23 +152,73 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     }
 }
 
-impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
-    fn commit(&mut self, changes: HashMap<Address, Account>) {
+impl<ExtDB> CacheDB<ExtDB> {
+    /// Commits the changes like [`DatabaseCommit::commit`], returning the change applied to
+    /// every account, sorted by address.
+    ///
+    /// Meant to keep external indexes in sync with the cache, see [`AccountChange`].
+    pub fn commit_with_changes(
+        &mut self,
+        changes: HashMap<Address, Account>,
+    ) -> Vec<(Address, AccountChange)> {
+        let mut applied = Vec::new();
+        self.commit_inner(
+            changes,
+            Some(&mut |address: Address, change: AccountChange| applied.push((address, change))),
+        );
+        applied.sort_unstable_by_key(|(address, _)| *address);
+        applied
+    }
+
+    fn commit_inner(
+        &mut self,
+        changes: HashMap<Address, Account>,
+        mut on_change: Option<&mut dyn FnMut(Address, AccountChange)>,
+    ) {
         for (address, mut account) in changes {
             if !account.is_touched() {
                 continue;
             }
             if account.is_selfdestructed() {
                 let db_account = self.accounts.entry(address).or_default();
+                let previous = core::mem::take(&mut db_account.info);
                 db_account.storage.clear();
                 db_account.account_state = AccountState::NotExisting;
-                db_account.info = AccountInfo::default();
+                if let Some(on_change) = on_change.as_mut() {
+                    on_change(
+                        address,
+                        AccountChange {
+                            previous_balance: previous.balance,
+                            previous_nonce: previous.nonce,
+                            destroyed: true,
+                            ..Default::default()
+                        },
+                    );
+                }
                 continue;
             }
             let is_newly_created = account.is_created();
             self.insert_contract(&mut account.info);
 
             let db_account = self.accounts.entry(address).or_default();
+            let change = on_change.is_some().then(|| {
+                let mut storage: Vec<_> = account
+                    .changed_storage_slots()
+                    .map(|(key, slot)| (*key, slot.present_value()))
+                    .collect();
+                storage.sort_unstable_by_key(|(key, _)| *key);
+                AccountChange {
+                    previous_balance: db_account.info.balance,
+                    balance: account.info.balance,
+                    previous_nonce: db_account.info.nonce,
+                    nonce: account.info.nonce,
+                    code_hash: (db_account.info.code_hash != account.info.code_hash)
+                        .then_some(account.info.code_hash),
+                    storage,
+                    created: is_newly_created,
+                    destroyed: false,
+                }
+            });
             db_account.info = account.info;
 
             db_account.account_state = if is_newly_created {
@@ -161,14 +236,25 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
                     .into_iter()
                     .map(|(key, value)| (key, value.present_value())),
             );
+
+            if let (Some(on_change), Some(change)) = (on_change.as_mut(), change) {
+                on_change(address, change);
+            }
         }
     }
 }
 
+impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.commit_inner(changes, None)
+    }
+}
+
 impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        instrument_event!(TRACE, %address, "basic");
         let basic = match self.accounts.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => entry.insert(
@@ -185,6 +271,7 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     }
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        instrument_event!(TRACE, %code_hash, "code_by_hash");
         match self.contracts.entry(code_hash) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
@@ -198,6 +285,7 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     ///
     /// It is assumed that account is already loaded.
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        instrument_event!(TRACE, %address, %index, "storage");
         match self.accounts.entry(address) {
             Entry::Occupied(mut acc_entry) => {
                 let acc_entry = acc_entry.get_mut();
@@ -235,6 +323,7 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     }
 
     fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        instrument_event!(TRACE, number, "block_hash");
         match self.block_hashes.entry(U256::from(number)) {
             Entry::Occupied(entry) => Ok(*entry.get()),
             Entry::Vacant(entry) => {
@@ -297,6 +386,10 @@ pub struct DbAccount {
     /// If account is selfdestructed or newly created, storage will be cleared.
     pub account_state: AccountState,
     /// storage slots
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub storage: HashMap<U256, U256>,
 }
 
@@ -333,6 +426,36 @@ impl From<AccountInfo> for DbAccount {
     }
 }
 
+/// Change applied to an account by [`CacheDB::commit_with_changes`].
+///
+/// New values are the ones held by the cache once the commit is done.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountChange {
+    /// Balance before the commit.
+    pub previous_balance: U256,
+    /// Balance after the commit.
+    pub balance: U256,
+    /// Nonce before the commit.
+    pub previous_nonce: u64,
+    /// Nonce after the commit.
+    pub nonce: u64,
+    /// The new code hash, if the code of the account was set.
+    pub code_hash: Option<B256>,
+    /// Storage slots whose value changed in the transaction with their new value, sorted by slot.
+    pub storage: Vec<(U256, U256)>,
+    /// The account was created, its previous storage is cleared.
+    pub created: bool,
+    /// The account was destroyed, its info and storage are cleared.
+    pub destroyed: bool,
+}
+
+impl AccountChange {
+    /// Returns the balance difference applied by the commit.
+    pub fn balance_delta(&self) -> I256 {
+        I256::from_raw(self.balance.wrapping_sub(self.previous_balance))
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccountState {
@@ -431,8 +554,17 @@ impl Database for BenchmarkDB {
 
 #[cfg(test)]
 mod tests {
-    use super::{CacheDB, EmptyDB};
-    use crate::primitives::{db::Database, AccountInfo, Address, HashMap, U256};
+    use super::{AccountChange, CacheDB, EmptyDB, InMemoryDB};
+    use crate::{
+        interpreter::opcode::{
+            CALL, CREATE, GAS, MSTORE, POP, PUSH1, PUSH20, PUSH6, SELFDESTRUCT, SSTORE, STOP,
+        },
+        primitives::{
+            create_address, db::Database, AccountInfo, Address, Bytecode, HashMap, SpecId, TxKind,
+            I256, U256,
+        },
+        Evm,
+    };
 
     #[test]
     fn test_insert_account_storage() {
@@ -486,6 +618,110 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_commit_with_changes() {
+        let caller = Address::with_last_byte(0x01);
+        let beneficiary = Address::with_last_byte(0xb0);
+        let contract = Address::with_last_byte(0xc0);
+        let victim = Address::with_last_byte(0xd0);
+        let created = create_address(contract, 1);
+
+        // Creates an account with SSTORE(1, 5) as initcode, destroys the victim and writes a slot.
+        let mut code = vec![PUSH6, PUSH1, 5, PUSH1, 1, SSTORE, STOP, PUSH1, 0, MSTORE];
+        code.extend([PUSH1, 6, PUSH1, 26, PUSH1, 10, CREATE, POP]);
+        code.extend([PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH20]);
+        code.extend_from_slice(victim.as_slice());
+        code.extend([GAS, CALL, POP, PUSH1, 1, PUSH1, 0, SSTORE, STOP]);
+        let mut victim_code = vec![PUSH20];
+        victim_code.extend_from_slice(beneficiary.as_slice());
+        victim_code.push(SELFDESTRUCT);
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(1_000_000_000u64)),
+        );
+        for (address, code, balance) in [(contract, code, 100), (victim, victim_code, 50)] {
+            let mut info = AccountInfo::from_bytecode(Bytecode::new_legacy(code.into()));
+            info.balance = U256::from(balance);
+            db.insert_account_info(address, info);
+        }
+        db.insert_account_storage(victim, U256::from(3), U256::from(4))
+            .unwrap();
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::SHANGHAI)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(contract);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        let state = evm.transact().unwrap().state;
+        let changes = evm.db_mut().commit_with_changes(state);
+        assert!(changes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        let changes: HashMap<_, _> = changes.into_iter().collect();
+
+        assert_eq!(
+            changes[&caller],
+            AccountChange {
+                previous_balance: U256::from(1_000_000_000u64),
+                balance: U256::from(1_000_000_000u64),
+                nonce: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            changes[&contract],
+            AccountChange {
+                previous_balance: U256::from(100),
+                balance: U256::from(90),
+                previous_nonce: 1,
+                nonce: 2,
+                storage: vec![(U256::ZERO, U256::from(1))],
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            changes[&contract].balance_delta(),
+            I256::try_from(-10i64).unwrap()
+        );
+        assert_eq!(
+            changes[&created],
+            AccountChange {
+                balance: U256::from(10),
+                nonce: 1,
+                storage: vec![(U256::from(1), U256::from(5))],
+                created: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            changes[&victim],
+            AccountChange {
+                previous_balance: U256::from(50),
+                previous_nonce: 1,
+                destroyed: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            changes[&beneficiary],
+            AccountChange {
+                balance: U256::from(50),
+                ..Default::default()
+            }
+        );
+
+        // The changes match the cache contents.
+        let db = evm.db_mut();
+        assert_eq!(db.basic(contract).unwrap().unwrap().balance, U256::from(90));
+        assert_eq!(db.storage(created, U256::from(1)), Ok(U256::from(5)));
+        assert_eq!(db.basic(victim).unwrap(), None);
+        assert_eq!(db.storage(victim, U256::from(3)), Ok(U256::ZERO));
+    }
+
     #[cfg(feature = "serde-json")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
@@ -0,0 +1,262 @@
+use super::{BundleState, DatabaseRef};
+use crate::primitives::{AccountInfo, Address, Bytecode, B256, U256};
+use std::vec::Vec;
+
+/// A read-only view of a [DatabaseRef] with [BundleState] layers stacked on top of it.
+///
+/// Every layer holds the changes of one or more blocks built on top of the layers below it, so
+/// the state at any block can be reached from a historical base without copying it. Reads are
+/// resolved from the topmost layer that knows the value, accounts destroyed in a layer shadow the
+/// storage of all the layers below it and of the base database.
+///
+/// # Example
+///
+/// ```
+/// use revm::{
+///     db::{states::bundle_state::BundleRetention, EmptyDB, OverlayDb, State},
+///     Evm,
+/// };
+///
+/// let mut overlay = OverlayDb::new(EmptyDB::default());
+///
+/// // Execute a block over the overlay and push its changes as a new layer.
+/// let mut state = State::builder()
+///     .with_database_ref(&overlay)
+///     .with_bundle_update()
+///     .build();
+/// Evm::builder().with_db(&mut state).build().transact_commit().unwrap();
+/// state.merge_transitions(BundleRetention::PlainState);
+/// let bundle = state.take_bundle();
+/// overlay.push_layer(bundle);
+///
+/// // Drop the block again.
+/// overlay.pop_layer();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OverlayDb<ExtDB> {
+    /// State layers, from the oldest to the newest.
+    layers: Vec<BundleState>,
+    /// The base database the layers are built on.
+    db: ExtDB,
+}
+
+impl<ExtDB> OverlayDb<ExtDB> {
+    /// Creates a new overlay without layers over the given database.
+    pub fn new(db: ExtDB) -> Self {
+        Self {
+            layers: Vec::new(),
+            db,
+        }
+    }
+
+    /// Pushes a layer on top of the others.
+    pub fn push_layer(&mut self, layer: BundleState) {
+        self.layers.push(layer);
+    }
+
+    /// Removes the topmost layer, returning it if there was one.
+    pub fn pop_layer(&mut self) -> Option<BundleState> {
+        self.layers.pop()
+    }
+
+    /// Merges all layers into a single one, keeping the resolved state unchanged.
+    pub fn flatten(&mut self) {
+        let mut layers = core::mem::take(&mut self.layers).into_iter();
+        if let Some(mut flat) = layers.next() {
+            for layer in layers {
+                flat.extend(layer);
+            }
+            self.layers.push(flat);
+        }
+    }
+
+    /// Returns the layers, from the oldest to the newest.
+    pub fn layers(&self) -> &[BundleState] {
+        &self.layers
+    }
+
+    /// Returns the base database.
+    pub fn db(&self) -> &ExtDB {
+        &self.db
+    }
+
+    /// Consumes the overlay and returns the base database.
+    pub fn into_db(self) -> ExtDB {
+        self.db
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for OverlayDb<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self
+            .layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.account(&address))
+        {
+            Some(account) => Ok(account.account_info()),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self
+            .layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.bytecode(&code_hash))
+        {
+            Some(code) => Ok(code),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        // The slot of a created or destroyed account is known even if it is not in the layer.
+        match self
+            .layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.account(&address)?.storage_slot(index))
+        {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{states::bundle_state::BundleRetention, InMemoryDB, State},
+        interpreter::opcode::{ADD, PUSH1, PUSH20, SELFDESTRUCT, SLOAD, SSTORE, STOP},
+        primitives::{SpecId, TxKind},
+        Evm,
+    };
+
+    const CALLER: Address = Address::with_last_byte(0x01);
+    const BENEFICIARY: Address = Address::with_last_byte(0xb0);
+    const COUNTER: Address = Address::with_last_byte(0xc0);
+    const VICTIM: Address = Address::with_last_byte(0xd0);
+
+    /// Base state with a counter in slot 0 of `COUNTER` and a slot 1 in `VICTIM`.
+    fn base() -> OverlayDb<InMemoryDB> {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SSTORE(0, SLOAD(0) + 1)
+        let counter = [PUSH1, 0, SLOAD, PUSH1, 1, ADD, PUSH1, 0, SSTORE, STOP];
+        let mut victim = vec![PUSH20];
+        victim.extend_from_slice(BENEFICIARY.as_slice());
+        victim.push(SELFDESTRUCT);
+        for (address, code) in [(COUNTER, counter.to_vec()), (VICTIM, victim)] {
+            let code = Bytecode::new_legacy(code.into());
+            db.insert_account_info(address, AccountInfo::from_bytecode(code));
+        }
+        db.insert_account_storage(COUNTER, U256::ZERO, U256::from(10))
+            .unwrap();
+        db.insert_account_storage(VICTIM, U256::from(1), U256::from(7))
+            .unwrap();
+        OverlayDb::new(db)
+    }
+
+    /// Executes a block calling `to` over the overlay and returns its changes.
+    fn execute_block(overlay: &OverlayDb<InMemoryDB>, to: Address) -> BundleState {
+        let mut state = State::builder()
+            .with_database_ref(overlay)
+            .with_bundle_update()
+            .build();
+        let result = Evm::builder()
+            .with_db(&mut state)
+            .with_spec_id(SpecId::SHANGHAI)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TxKind::Call(to);
+                tx.gas_limit = 100_000;
+            })
+            .build()
+            .transact_commit()
+            .unwrap();
+        assert!(result.is_success());
+        state.merge_transitions(BundleRetention::PlainState);
+        state.take_bundle()
+    }
+
+    fn counter(overlay: &OverlayDb<InMemoryDB>) -> U256 {
+        overlay.storage_ref(COUNTER, U256::ZERO).unwrap()
+    }
+
+    fn caller_nonce(overlay: &OverlayDb<InMemoryDB>) -> u64 {
+        overlay.basic_ref(CALLER).unwrap().unwrap().nonce
+    }
+
+    #[test]
+    fn test_push_and_pop_layers() {
+        let mut overlay = base();
+
+        let block = execute_block(&overlay, COUNTER);
+        overlay.push_layer(block);
+        assert_eq!(counter(&overlay), U256::from(11));
+        assert_eq!(caller_nonce(&overlay), 1);
+
+        // The next block executes on top of the previous one.
+        let block = execute_block(&overlay, COUNTER);
+        overlay.push_layer(block);
+        assert_eq!(counter(&overlay), U256::from(12));
+        assert_eq!(caller_nonce(&overlay), 2);
+
+        overlay.pop_layer();
+        assert_eq!(counter(&overlay), U256::from(11));
+        assert_eq!(caller_nonce(&overlay), 1);
+
+        overlay.pop_layer();
+        assert_eq!(counter(&overlay), U256::from(10));
+        assert_eq!(caller_nonce(&overlay), 0);
+        assert!(overlay.pop_layer().is_none());
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut overlay = base();
+        for _ in 0..3 {
+            let block = execute_block(&overlay, COUNTER);
+            overlay.push_layer(block);
+        }
+
+        overlay.flatten();
+        assert_eq!(overlay.layers().len(), 1);
+        assert_eq!(counter(&overlay), U256::from(13));
+        assert_eq!(caller_nonce(&overlay), 3);
+
+        overlay.pop_layer();
+        assert_eq!(counter(&overlay), U256::from(10));
+    }
+
+    #[test]
+    fn test_destroyed_account_shadows_storage() {
+        let mut overlay = base();
+        let block = execute_block(&overlay, VICTIM);
+        overlay.push_layer(block);
+        assert_eq!(overlay.basic_ref(VICTIM).unwrap(), None);
+        assert_eq!(overlay.storage_ref(VICTIM, U256::from(1)), Ok(U256::ZERO));
+
+        // A layer that does not touch the account keeps it destroyed.
+        let block = execute_block(&overlay, COUNTER);
+        overlay.push_layer(block);
+        assert_eq!(overlay.storage_ref(VICTIM, U256::from(1)), Ok(U256::ZERO));
+
+        overlay.pop_layer();
+        overlay.pop_layer();
+        assert!(overlay.basic_ref(VICTIM).unwrap().is_some());
+        assert_eq!(
+            overlay.storage_ref(VICTIM, U256::from(1)),
+            Ok(U256::from(7))
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use super::DatabaseRef;
+use crate::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, U256};
+use core::hash::Hash;
+use std::sync::{PoisonError, RwLock, RwLockWriteGuard};
+
+/// A thread safe, read-only cache over a [DatabaseRef].
+///
+/// Every value loaded from the underlying database is cached, so it is fetched only once no matter
+/// how many [`Evm`](crate::Evm)s read it. Wrap it in an [`Arc`](std::sync::Arc) and pass a clone
+/// to each [`EvmBuilder::with_ref_db`](crate::EvmBuilder::with_ref_db) to run many simulations
+/// in parallel over the same state.
+///
+/// It does not implement [DatabaseCommit](super::DatabaseCommit), so changes can't be committed
+/// to the shared state. To commit changes, wrap it in a per [`Evm`](crate::Evm)
+/// [CacheDB](super::CacheDB) overlay instead.
+///
+/// # Example
+///
+/// ```
+/// use revm::{db::{CacheDB, EmptyDB, SharedCacheDb}, Evm};
+/// use std::sync::Arc;
+///
+/// let shared = Arc::new(SharedCacheDb::new(CacheDB::new(EmptyDB::default())));
+///
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         let shared = shared.clone();
+///         scope.spawn(move || {
+///             let mut evm = Evm::builder().with_ref_db(shared).build();
+///             evm.transact().unwrap();
+///         });
+///     }
+/// });
+///
+/// // Committing goes through a per-Evm overlay.
+/// let mut evm = Evm::builder().with_db(CacheDB::new(shared)).build();
+/// evm.transact_commit().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedCacheDb<ExtDB> {
+    accounts: RwLock<HashMap<Address, Option<AccountInfo>>>,
+    storage: RwLock<HashMap<(Address, U256), U256>>,
+    contracts: RwLock<HashMap<B256, Bytecode>>,
+    block_hashes: RwLock<HashMap<u64, B256>>,
+    /// The underlying database ([DatabaseRef]) that is used to load data.
+    db: ExtDB,
+}
+
+impl<ExtDB> SharedCacheDb<ExtDB> {
+    /// Creates a new shared cache over the given database.
+    pub fn new(db: ExtDB) -> Self {
+        Self {
+            accounts: RwLock::default(),
+            storage: RwLock::default(),
+            contracts: RwLock::default(),
+            block_hashes: RwLock::default(),
+            db,
+        }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &ExtDB {
+        &self.db
+    }
+
+    /// Clears the cache, following reads will be loaded from the underlying database again.
+    pub fn clear(&self) {
+        write(&self.accounts).clear();
+        write(&self.storage).clear();
+        write(&self.contracts).clear();
+        write(&self.block_hashes).clear();
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for SharedCacheDb<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        get_or_load(&self.accounts, address, || self.db.basic_ref(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        get_or_load(&self.contracts, code_hash, || {
+            self.db.code_by_hash_ref(code_hash)
+        })
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        get_or_load(&self.storage, (address, index), || {
+            self.db.storage_ref(address, index)
+        })
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        get_or_load(&self.block_hashes, number, || {
+            self.db.block_hash_ref(number)
+        })
+    }
+}
+
+/// Returns the cached value, or loads it and caches it.
+///
+/// The lock is not held while loading. If the value was cached in the meantime by another
+/// thread, the value cached first is kept and returned.
+fn get_or_load<K: Eq + Hash, V: Clone, E>(
+    map: &RwLock<HashMap<K, V>>,
+    key: K,
+    load: impl FnOnce() -> Result<V, E>,
+) -> Result<V, E> {
+    if let Some(value) = map.read().unwrap_or_else(PoisonError::into_inner).get(&key) {
+        return Ok(value.clone());
+    }
+    let value = load()?;
+    Ok(write(map).entry(key).or_insert(value).clone())
+}
+
+/// Locks the map for writing. Entries are only ever inserted whole, so a poisoned map is
+/// still consistent.
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, Bytes, ExecutionResult, TxKind, KECCAK_EMPTY},
+        Evm,
+    };
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::{sync::Arc, vec, vec::Vec};
+
+    const CONTRACT: Address = address!("00000000000000000000000000000000000c0de0");
+
+    /// Counts the storage reads that reach the database.
+    struct CountingDb {
+        db: CacheDB<EmptyDB>,
+        storage_reads: AtomicUsize,
+    }
+
+    impl DatabaseRef for CountingDb {
+        type Error = <CacheDB<EmptyDB> as DatabaseRef>::Error;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.db.basic_ref(address)
+        }
+
+        fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.db.code_by_hash_ref(code_hash)
+        }
+
+        fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.storage_reads.fetch_add(1, Ordering::Relaxed);
+            self.db.storage_ref(address, index)
+        }
+
+        fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash_ref(number)
+        }
+    }
+
+    /// Returns a fork-like state with a contract that returns the caller balance plus slot 0,
+    /// and the callers of each simulation.
+    fn fork() -> (CountingDb, Vec<Address>) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // BALANCE(CALLER) + SLOAD(0)
+        let code = Bytes::from(vec![
+            opcode::CALLER,
+            opcode::BALANCE,
+            opcode::PUSH1,
+            0x0,
+            opcode::SLOAD,
+            opcode::ADD,
+            opcode::PUSH1,
+            0x0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH1,
+            0x0,
+            opcode::RETURN,
+        ]);
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code)),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(CONTRACT, U256::ZERO, U256::from(1000))
+            .unwrap();
+
+        let callers = (1..=8u8)
+            .map(|i| {
+                let caller = Address::with_last_byte(i);
+                db.insert_account_info(
+                    caller,
+                    AccountInfo::from_balance(U256::from(i) * U256::from(1_000_000)),
+                );
+                caller
+            })
+            .collect();
+
+        let db = CountingDb {
+            db,
+            storage_reads: AtomicUsize::new(0),
+        };
+        (db, callers)
+    }
+
+    fn simulate<DB: DatabaseRef>(db: DB, caller: Address) -> ExecutionResult
+    where
+        DB::Error: core::fmt::Debug,
+    {
+        let mut evm = Evm::builder()
+            .with_ref_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(CONTRACT);
+                tx.value = U256::from(7);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        evm.transact().unwrap().result
+    }
+
+    #[test]
+    fn concurrent_simulations_match_sequential() {
+        let (db, callers) = fork();
+        let sequential: Vec<_> = callers
+            .iter()
+            .map(|caller| simulate(&db, *caller))
+            .collect();
+
+        let shared = Arc::new(SharedCacheDb::new(db));
+        let concurrent: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = callers
+                .iter()
+                .map(|caller| {
+                    let shared = shared.clone();
+                    scope.spawn(move || simulate(shared, *caller))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(concurrent, sequential);
+        assert_eq!(
+            concurrent[0].output(),
+            Some(&Bytes::from(U256::from(1_000_993).to_be_bytes_vec()))
+        );
+
+        // Once cached, slot 0 is no longer read from the underlying database.
+        let reads = shared.db().storage_reads.load(Ordering::Relaxed);
+        simulate(shared.clone(), callers[0]);
+        assert_eq!(shared.db().storage_reads.load(Ordering::Relaxed), reads);
+    }
+
+    #[test]
+    fn commits_go_to_overlay() {
+        let (db, callers) = fork();
+        let shared = Arc::new(SharedCacheDb::new(db));
+
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(shared.clone()))
+            .modify_tx_env(|tx| {
+                tx.caller = callers[0];
+                tx.transact_to = TxKind::Call(CONTRACT);
+                tx.value = U256::from(7);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        evm.transact_commit().unwrap();
+
+        let overlay = evm.into_context().evm.inner.db;
+        assert_eq!(
+            overlay.basic_ref(CONTRACT).unwrap().unwrap().balance,
+            U256::from(7)
+        );
+        let contract = shared.basic_ref(CONTRACT).unwrap().unwrap();
+        assert_eq!(contract.balance, U256::ZERO);
+        assert_ne!(contract.code_hash, KECCAK_EMPTY);
+    }
+
+    #[test]
+    fn clear() {
+        let (db, _) = fork();
+        let shared = SharedCacheDb::new(db);
+        assert_eq!(
+            shared.storage_ref(CONTRACT, U256::ZERO),
+            Ok(U256::from(1000))
+        );
+        assert_eq!(
+            shared.storage_ref(CONTRACT, U256::ZERO),
+            Ok(U256::from(1000))
+        );
+        assert_eq!(shared.db().storage_reads.load(Ordering::Relaxed), 1);
+
+        shared.clear();
+        assert_eq!(
+            shared.storage_ref(CONTRACT, U256::ZERO),
+            Ok(U256::from(1000))
+        );
+        assert_eq!(shared.db().storage_reads.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,639 @@
+use super::{BundleState, CacheDB, DatabaseRef, EmptyDB};
+use crate::primitives::{AccountInfo, Address, Bytecode, Bytes, B256, KECCAK_EMPTY, U256};
+use core::{convert::Infallible, fmt};
+use memmap2::Mmap;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Magic bytes at the start of a snapshot file.
+const MAGIC: &[u8; 8] = b"REVMSNAP";
+/// Version of the snapshot file format.
+const VERSION: u32 = 1;
+/// Length of the header.
+const HEADER_LEN: usize = 64;
+
+/// Address, balance, nonce and code hash.
+const ACCOUNT_LEN: usize = 20 + 32 + 8 + 32;
+/// Address, slot and value.
+const STORAGE_LEN: usize = 20 + 32 + 32;
+/// Code hash, offset and length of the code in the code section.
+const CODE_LEN: usize = 32 + 8 + 8;
+/// Block number and hash.
+const BLOCK_HASH_LEN: usize = 8 + 32;
+
+/// A read-only [DatabaseRef] over a memory-mapped snapshot file.
+///
+/// The file holds sorted tables of accounts, storage slots, contracts and block hashes that are
+/// binary searched in place, so opening a snapshot doesn't load the state into memory. It is
+/// written by [`SnapshotDb::create`], [`SnapshotDb::from_cache_db`] or
+/// [`SnapshotDb::from_bundle_state`].
+///
+/// Reads take `&self` and don't lock, so the snapshot can be shared by many [`Evm`](crate::Evm)s
+/// through an [`Arc`](std::sync::Arc) and
+/// [`EvmBuilder::with_ref_db`](crate::EvmBuilder::with_ref_db).
+///
+/// Data missing from the snapshot reads like an [`EmptyDB`], so a snapshot of an
+/// [`InMemoryDB`](super::InMemoryDB) reads the same as the database itself.
+///
+/// # File format
+///
+/// All integers are big-endian. A 64 byte header holds the magic `REVMSNAP`, the format version
+/// as a `u32`, four reserved bytes and the number of accounts, storage slots, contracts and block
+/// hashes and the length of the code section as `u64`s. It is followed by:
+///
+/// - the accounts sorted by address: address, balance, nonce and code hash,
+/// - the storage slots sorted by address and slot: address, slot and value,
+/// - the contracts sorted by code hash: code hash, offset and length in the code section,
+/// - the block hashes sorted by number: number and hash,
+/// - the code section, the concatenated original bytes of the contracts.
+///
+/// # Safety
+///
+/// The file must not be modified while it is open, the mapped memory would change under the
+/// readers.
+#[derive(Debug)]
+pub struct SnapshotDb {
+    mmap: Mmap,
+    accounts: (usize, usize),
+    storage: (usize, usize),
+    codes: (usize, usize),
+    block_hashes: (usize, usize),
+    code_section: (usize, usize),
+}
+
+impl SnapshotDb {
+    /// Opens the snapshot at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        let file = File::open(path)?;
+        // SAFETY: the file is only read, see the safety section of the type.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_mmap(mmap)
+    }
+
+    /// Writes a snapshot of `accounts` to `path` and opens it.
+    ///
+    /// Each account comes with its storage slots. The code of the accounts must be set in
+    /// [`AccountInfo::code`], and if an account or a slot is given more than once the last value
+    /// wins. Slots with a value of zero are not written.
+    pub fn create<A, S>(path: impl AsRef<Path>, accounts: A) -> Result<Self, SnapshotError>
+    where
+        A: IntoIterator<Item = (Address, AccountInfo, S)>,
+        S: IntoIterator<Item = (U256, U256)>,
+    {
+        let mut writer = SnapshotWriter::default();
+        for (address, info, storage) in accounts {
+            writer.account(address, info);
+            for (slot, value) in storage {
+                writer.storage.insert((address, slot), value);
+            }
+        }
+        writer.write(path.as_ref())?;
+        Self::open(path)
+    }
+
+    /// Writes a snapshot of the accounts, contracts and block hashes of `db` to `path` and opens
+    /// it.
+    ///
+    /// Only the data cached in `db` is written, not the one of its underlying database.
+    pub fn from_cache_db<ExtDB>(
+        path: impl AsRef<Path>,
+        db: &CacheDB<ExtDB>,
+    ) -> Result<Self, SnapshotError> {
+        let mut writer = SnapshotWriter::default();
+        for (address, account) in &db.accounts {
+            let Some(info) = account.info() else {
+                continue;
+            };
+            writer.account(*address, info);
+            for (slot, value) in &account.storage {
+                writer.storage.insert((*address, *slot), *value);
+            }
+        }
+        for (code_hash, code) in &db.contracts {
+            writer.code(*code_hash, code);
+        }
+        for (number, hash) in &db.block_hashes {
+            writer.block_hashes.insert(number.saturating_to(), *hash);
+        }
+        writer.write(path.as_ref())?;
+        Self::open(path)
+    }
+
+    /// Writes a snapshot of the present state of the accounts and the contracts of `bundle` to
+    /// `path` and opens it.
+    ///
+    /// Only the accounts and slots in the bundle are written, so it should hold the whole state.
+    pub fn from_bundle_state(
+        path: impl AsRef<Path>,
+        bundle: &BundleState,
+    ) -> Result<Self, SnapshotError> {
+        let mut writer = SnapshotWriter::default();
+        for (address, account) in &bundle.state {
+            let Some(info) = &account.info else {
+                continue;
+            };
+            writer.account(*address, info.clone());
+            for (slot, value) in &account.storage {
+                writer
+                    .storage
+                    .insert((*address, *slot), value.present_value());
+            }
+        }
+        for (code_hash, code) in &bundle.contracts {
+            writer.code(*code_hash, code);
+        }
+        writer.write(path.as_ref())?;
+        Self::open(path)
+    }
+
+    /// Returns the number of accounts in the snapshot.
+    pub fn accounts_len(&self) -> usize {
+        self.accounts.1 / ACCOUNT_LEN
+    }
+
+    /// Returns the number of storage slots in the snapshot.
+    pub fn storage_len(&self) -> usize {
+        self.storage.1 / STORAGE_LEN
+    }
+
+    fn from_mmap(mmap: Mmap) -> Result<Self, SnapshotError> {
+        let header = mmap.get(..HEADER_LEN).ok_or(SnapshotError::InvalidHeader)?;
+        if &header[..8] != MAGIC {
+            return Err(SnapshotError::InvalidHeader);
+        }
+        let version = u32::from_be_bytes(header[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let count = |index: usize| {
+            let offset = 16 + index * 8;
+            u64::from_be_bytes(header[offset..offset + 8].try_into().unwrap())
+        };
+
+        let mut offset = HEADER_LEN;
+        let mut section = |len: u64, record_len: usize| -> Result<_, SnapshotError> {
+            let len = usize::try_from(len)
+                .ok()
+                .and_then(|len| len.checked_mul(record_len))
+                .ok_or(SnapshotError::Truncated)?;
+            let start = offset;
+            offset = offset.checked_add(len).ok_or(SnapshotError::Truncated)?;
+            Ok((start, len))
+        };
+        let accounts = section(count(0), ACCOUNT_LEN)?;
+        let storage = section(count(1), STORAGE_LEN)?;
+        let codes = section(count(2), CODE_LEN)?;
+        let block_hashes = section(count(3), BLOCK_HASH_LEN)?;
+        let code_section = section(count(4), 1)?;
+        if offset != mmap.len() {
+            return Err(SnapshotError::Truncated);
+        }
+        let codes_in_section =
+            mmap[codes.0..codes.0 + codes.1]
+                .chunks_exact(CODE_LEN)
+                .all(|record| {
+                    let offset = u64::from_be_bytes(record[32..40].try_into().unwrap());
+                    let len = u64::from_be_bytes(record[40..48].try_into().unwrap());
+                    offset
+                        .checked_add(len)
+                        .is_some_and(|end| end <= code_section.1 as u64)
+                });
+        if !codes_in_section {
+            return Err(SnapshotError::Truncated);
+        }
+
+        Ok(Self {
+            mmap,
+            accounts,
+            storage,
+            codes,
+            block_hashes,
+            code_section,
+        })
+    }
+
+    fn table(&self, (start, len): (usize, usize)) -> &[u8] {
+        &self.mmap[start..start + len]
+    }
+}
+
+impl DatabaseRef for SnapshotDb {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let Some(record) = find(self.table(self.accounts), ACCOUNT_LEN, address.as_slice()) else {
+            return EmptyDB::new().basic_ref(address);
+        };
+        Ok(Some(AccountInfo {
+            balance: U256::from_be_slice(&record[20..52]),
+            nonce: u64::from_be_bytes(record[52..60].try_into().unwrap()),
+            code_hash: B256::from_slice(&record[60..92]),
+            code: None,
+        }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let Some(record) = find(self.table(self.codes), CODE_LEN, code_hash.as_slice()) else {
+            return EmptyDB::new().code_by_hash_ref(code_hash);
+        };
+        let offset = u64::from_be_bytes(record[32..40].try_into().unwrap()) as usize;
+        let len = u64::from_be_bytes(record[40..48].try_into().unwrap()) as usize;
+        let code = &self.table(self.code_section)[offset..offset + len];
+        Ok(Bytecode::new_raw(Bytes::copy_from_slice(code)))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let mut key = [0; 52];
+        key[..20].copy_from_slice(address.as_slice());
+        key[20..].copy_from_slice(&index.to_be_bytes::<32>());
+        let Some(record) = find(self.table(self.storage), STORAGE_LEN, &key) else {
+            return EmptyDB::new().storage_ref(address, index);
+        };
+        Ok(U256::from_be_slice(&record[52..84]))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let key = number.to_be_bytes();
+        let Some(record) = find(self.table(self.block_hashes), BLOCK_HASH_LEN, &key) else {
+            return EmptyDB::new().block_hash_ref(number);
+        };
+        Ok(B256::from_slice(&record[8..40]))
+    }
+}
+
+/// Binary searches the table of records of `record_len` bytes sorted by their first bytes for
+/// the record starting with `key`.
+fn find<'a>(table: &'a [u8], record_len: usize, key: &[u8]) -> Option<&'a [u8]> {
+    let record = |index: usize| &table[index * record_len..(index + 1) * record_len];
+    let (mut low, mut high) = (0, table.len() / record_len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match record(mid)[..key.len()].cmp(key) {
+            core::cmp::Ordering::Less => low = mid + 1,
+            core::cmp::Ordering::Greater => high = mid,
+            core::cmp::Ordering::Equal => return Some(record(mid)),
+        }
+    }
+    None
+}
+
+/// Sorted contents of a snapshot file.
+#[derive(Default)]
+struct SnapshotWriter {
+    accounts: BTreeMap<Address, AccountInfo>,
+    storage: BTreeMap<(Address, U256), U256>,
+    codes: BTreeMap<B256, Bytes>,
+    block_hashes: BTreeMap<u64, B256>,
+}
+
+impl SnapshotWriter {
+    fn account(&mut self, address: Address, mut info: AccountInfo) {
+        if let Some(code) = info.code.take() {
+            self.code(info.code_hash, &code);
+        }
+        self.accounts.insert(address, info);
+    }
+
+    fn code(&mut self, code_hash: B256, code: &Bytecode) {
+        if code_hash != KECCAK_EMPTY && !code_hash.is_zero() && !code.is_empty() {
+            self.codes.insert(code_hash, code.original_bytes());
+        }
+    }
+
+    fn write(mut self, path: &Path) -> io::Result<()> {
+        self.storage.retain(|_, value| !value.is_zero());
+        let code_section_len: usize = self.codes.values().map(|c| c.len()).sum();
+
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut header = [0; HEADER_LEN];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..12].copy_from_slice(&VERSION.to_be_bytes());
+        let counts = [
+            self.accounts.len(),
+            self.storage.len(),
+            self.codes.len(),
+            self.block_hashes.len(),
+            code_section_len,
+        ];
+        for (index, count) in counts.into_iter().enumerate() {
+            let offset = 16 + index * 8;
+            header[offset..offset + 8].copy_from_slice(&(count as u64).to_be_bytes());
+        }
+        file.write_all(&header)?;
+
+        for (address, info) in &self.accounts {
+            file.write_all(address.as_slice())?;
+            file.write_all(&info.balance.to_be_bytes::<32>())?;
+            file.write_all(&info.nonce.to_be_bytes())?;
+            file.write_all(info.code_hash.as_slice())?;
+        }
+        for ((address, slot), value) in &self.storage {
+            file.write_all(address.as_slice())?;
+            file.write_all(&slot.to_be_bytes::<32>())?;
+            file.write_all(&value.to_be_bytes::<32>())?;
+        }
+        let mut offset = 0u64;
+        for (code_hash, code) in &self.codes {
+            file.write_all(code_hash.as_slice())?;
+            file.write_all(&offset.to_be_bytes())?;
+            file.write_all(&(code.len() as u64).to_be_bytes())?;
+            offset += code.len() as u64;
+        }
+        for (number, hash) in &self.block_hashes {
+            file.write_all(&number.to_be_bytes())?;
+            file.write_all(hash.as_slice())?;
+        }
+        for code in self.codes.values() {
+            file.write_all(code)?;
+        }
+        file.into_inner()?.sync_all()
+    }
+}
+
+/// Error of opening or writing a [`SnapshotDb`].
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading or writing the file failed.
+    Io(io::Error),
+    /// The file is not a snapshot.
+    InvalidHeader,
+    /// The version of the snapshot format is not supported.
+    UnsupportedVersion(u32),
+    /// The length of the file doesn't match its header.
+    Truncated,
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "snapshot io error: {error}"),
+            Self::InvalidHeader => write!(f, "not a snapshot file"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {version}")
+            }
+            Self::Truncated => write!(f, "snapshot file length doesn't match its header"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{BundleState, InMemoryDB},
+        primitives::{keccak256, TxKind},
+        Evm,
+    };
+    use std::{path::PathBuf, sync::Arc, vec};
+
+    /// Temporary snapshot file, removed on drop.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let file = format!("revm-snapshot-{}-{name}", std::process::id());
+            Self(std::env::temp_dir().join(file))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn address(index: u64) -> Address {
+        Address::from_word(keccak256(index.to_be_bytes()))
+    }
+
+    /// A database with `count` accounts, some with code and storage, and a few block hashes.
+    fn populated_db(count: u64) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        for index in 0..count {
+            let code = (index % 7 == 0)
+                .then(|| Bytecode::new_raw(Bytes::from(vec![0x60, index as u8, 0x60, 0x00, 0x55])));
+            let info = AccountInfo {
+                balance: U256::from(index) << 100,
+                nonce: index,
+                code_hash: code.as_ref().map_or(KECCAK_EMPTY, Bytecode::hash_slow),
+                code,
+            };
+            db.insert_account_info(address(index), info);
+            for slot in 0..index % 5 {
+                db.insert_account_storage(address(index), U256::from(slot), U256::from(index))
+                    .unwrap();
+            }
+        }
+        for number in [0, 1, 255, 256] {
+            db.block_hashes
+                .insert(U256::from(number), keccak256([number as u8, 1]));
+        }
+        db
+    }
+
+    fn assert_same_reads(snapshot: &SnapshotDb, db: &InMemoryDB, count: u64) {
+        // Include addresses that are not in the databases.
+        for index in 0..count + 10 {
+            let address = address(index);
+            let info = db.basic_ref(address).unwrap();
+            assert_eq!(
+                snapshot.basic_ref(address).unwrap(),
+                info.clone().map(|info| info.without_code())
+            );
+            if let Some(info) = info {
+                assert_eq!(
+                    snapshot
+                        .code_by_hash_ref(info.code_hash)
+                        .unwrap()
+                        .original_bytes(),
+                    db.code_by_hash_ref(info.code_hash)
+                        .unwrap()
+                        .original_bytes()
+                );
+            }
+            for slot in 0..6 {
+                let slot = U256::from(slot);
+                assert_eq!(
+                    snapshot.storage_ref(address, slot).unwrap(),
+                    db.storage_ref(address, slot).unwrap()
+                );
+            }
+        }
+        for number in 0..300 {
+            assert_eq!(
+                snapshot.block_hash_ref(number).unwrap(),
+                db.block_hash_ref(number).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn cache_db_round_trip() {
+        let count = 3_000;
+        let db = populated_db(count);
+        let path = TempPath::new("cache-db");
+        let snapshot = SnapshotDb::from_cache_db(&path.0, &db).unwrap();
+        assert_eq!(snapshot.accounts_len(), count as usize);
+        assert_same_reads(&snapshot, &db, count);
+
+        // Reopening reads the same.
+        drop(snapshot);
+        let snapshot = SnapshotDb::open(&path.0).unwrap();
+        assert_same_reads(&snapshot, &db, count);
+    }
+
+    #[test]
+    fn create_from_accounts() {
+        let count = 2_000;
+        let db = populated_db(count);
+        let accounts = db.accounts.iter().map(|(address, account)| {
+            let mut info = account.info.clone();
+            info.code = Some(db.contracts[&info.code_hash].clone());
+            (*address, info, account.storage.clone())
+        });
+        let path = TempPath::new("accounts");
+        let snapshot = SnapshotDb::create(&path.0, accounts).unwrap();
+        assert_eq!(
+            snapshot.storage_len(),
+            db.accounts
+                .values()
+                .flat_map(|account| account.storage.values())
+                .filter(|value| !value.is_zero())
+                .count()
+        );
+        for index in 0..count {
+            let address = address(index);
+            assert_eq!(
+                snapshot.basic_ref(address).unwrap(),
+                db.basic_ref(address)
+                    .unwrap()
+                    .map(|info| info.without_code())
+            );
+        }
+    }
+
+    #[test]
+    fn bundle_state_round_trip() {
+        let db = populated_db(100);
+        let bundle = BundleState::new(
+            db.accounts.iter().map(|(address, account)| {
+                let storage = account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (*slot, (U256::ZERO, *value)))
+                    .collect();
+                (*address, None, Some(account.info.clone()), storage)
+            }),
+            Vec::<Vec<(Address, Option<Option<AccountInfo>>, Vec<(U256, U256)>)>>::new(),
+            db.contracts.clone(),
+        );
+        let path = TempPath::new("bundle");
+        let snapshot = SnapshotDb::from_bundle_state(&path.0, &bundle).unwrap();
+        assert_eq!(snapshot.accounts_len(), 100);
+        for index in 0..100 {
+            let address = address(index);
+            let info = db.basic_ref(address).unwrap().unwrap();
+            assert_eq!(
+                snapshot.basic_ref(address).unwrap(),
+                Some(info.clone().without_code())
+            );
+            assert_eq!(
+                snapshot
+                    .code_by_hash_ref(info.code_hash)
+                    .unwrap()
+                    .hash_slow(),
+                info.code_hash
+            );
+            for slot in 0..6 {
+                let slot = U256::from(slot);
+                assert_eq!(
+                    snapshot.storage_ref(address, slot).unwrap(),
+                    db.storage_ref(address, slot).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_readers_execute() {
+        let db = populated_db(50);
+        let path = TempPath::new("concurrent");
+        let snapshot = Arc::new(SnapshotDb::from_cache_db(&path.0, &db).unwrap());
+        let caller = address(3);
+        let contract = address(14);
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let snapshot = snapshot.clone();
+                scope.spawn(move || {
+                    let mut evm = Evm::builder()
+                        .with_ref_db(snapshot)
+                        .modify_tx_env(|tx| {
+                            tx.caller = caller;
+                            tx.transact_to = TxKind::Call(contract);
+                        })
+                        .build();
+                    let result = evm.transact().unwrap();
+                    assert!(result.result.is_success());
+                    assert_eq!(
+                        result.state[&contract].storage[&U256::ZERO].present_value,
+                        U256::from(14)
+                    );
+                });
+            }
+        });
+
+        // The same transaction over the in-memory database.
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(contract);
+            })
+            .build();
+        let result = evm.transact().unwrap();
+        assert_eq!(
+            result.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from(14)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let path = TempPath::new("invalid");
+        std::fs::write(&path.0, b"not a snapshot").unwrap();
+        assert!(matches!(
+            SnapshotDb::open(&path.0),
+            Err(SnapshotError::InvalidHeader)
+        ));
+
+        let snapshot = SnapshotDb::from_cache_db(&path.0, &populated_db(3)).unwrap();
+        drop(snapshot);
+        let mut bytes = std::fs::read(&path.0).unwrap();
+        bytes.pop();
+        std::fs::write(&path.0, bytes).unwrap();
+        assert!(matches!(
+            SnapshotDb::open(&path.0),
+            Err(SnapshotError::Truncated)
+        ));
+    }
+}
@@ -24,6 +24,10 @@ pub struct BundleAccount {
     /// If it is different we add it to changeset.
     ///
     /// If Account was destroyed we ignore original value and compare present state with U256::ZERO.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub storage: StorageWithOriginalValues,
     /// Account status.
     pub status: AccountStatus,
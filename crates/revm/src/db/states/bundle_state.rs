@@ -4,7 +4,10 @@ use super::{
     AccountRevert, AccountStatus, BundleAccount, PlainStateReverts, RevertToSlot, StorageSlot,
     TransitionState,
 };
-use core::{mem, ops::RangeInclusive};
+use core::{
+    mem,
+    ops::{RangeFrom, RangeInclusive},
+};
 use revm_interpreter::primitives::{
     hash_map::{self, Entry},
     AccountInfo, Address, Bytecode, HashMap, HashSet, B256, KECCAK_EMPTY, U256,
@@ -396,8 +399,16 @@ impl BundleRetention {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BundleState {
     /// Account state.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub state: HashMap<Address, BundleAccount>,
     /// All created contracts in this block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub contracts: HashMap<B256, Bytecode>,
     /// Changes to revert.
     ///
@@ -583,6 +594,74 @@ impl BundleState {
         self.reverts.push(reverts);
     }
 
+    /// Parallel version of [`Self::apply_transitions_and_create_reverts`] that updates the
+    /// accounts on the rayon thread pool.
+    ///
+    /// The resulting bundle, including the order of the reverts, is the same as the one of the
+    /// serial version.
+    #[cfg(feature = "rayon")]
+    pub fn apply_transitions_and_create_reverts_parallel(
+        &mut self,
+        transitions: TransitionState,
+        retention: BundleRetention,
+    ) {
+        use rayon::prelude::*;
+
+        let include_reverts = retention.includes_reverts();
+        // take the accounts out of the bundle so they can be updated independently.
+        let accounts = transitions
+            .transitions
+            .into_iter()
+            .map(|(address, transition)| {
+                let account = self.state.remove(&address);
+                if let Some(account) = &account {
+                    self.state_size -= account.size_hint();
+                }
+                (address, transition, account)
+            })
+            .collect::<Vec<_>>();
+
+        // indexed parallel iterators collect in the order of the transitions.
+        let updated = accounts
+            .into_par_iter()
+            .map(|(address, transition, account)| {
+                let new_contract = transition
+                    .has_new_contract()
+                    .map(|(hash, bytecode)| (hash, bytecode.clone()));
+                let (account, revert) = match account {
+                    Some(mut account) => {
+                        let revert = account.update_and_create_revert(transition);
+                        (Some(account), revert)
+                    }
+                    None => {
+                        let present_bundle = transition.present_bundle_account();
+                        let revert = transition.create_revert();
+                        (revert.is_some().then_some(present_bundle), revert)
+                    }
+                };
+                (address, account, revert, new_contract)
+            })
+            .collect::<Vec<_>>();
+
+        let reverts_capacity = if include_reverts { updated.len() } else { 0 };
+        let mut reverts = Vec::with_capacity(reverts_capacity);
+        for (address, account, revert, new_contract) in updated {
+            if let Some((hash, new_bytecode)) = new_contract {
+                self.contracts.insert(hash, new_bytecode);
+            }
+            if let Some(account) = account {
+                self.state_size += account.size_hint();
+                self.state.insert(address, account);
+            }
+            if let Some(revert) = revert.filter(|_| include_reverts) {
+                self.reverts_size += revert.size_hint();
+                reverts.push((address, revert));
+            }
+        }
+
+        self.reverts.push(reverts);
+    }
+
     /// Generate a [`StateChangeset`] from the bundle state without consuming
     /// it.
     pub fn to_plain_state(&self, is_value_known: OriginalValuesKnown) -> StateChangeset {
@@ -836,6 +915,104 @@ impl BundleState {
         }
     }
 
+    /// Unwinds the blocks in `range` and returns their reverts as plain state reverts, oldest
+    /// block first.
+    ///
+    /// Blocks are counted from zero by their position in [`Self::reverts`], the range always ends
+    /// with the latest block. The reverts of a block hold the account info and storage values
+    /// from before the block.
+    pub fn take_reverts_for_unwind(&mut self, range: RangeFrom<usize>) -> PlainStateReverts {
+        let start = range.start.min(self.reverts.len());
+        let reverts = Reverts::new(self.reverts[start..].to_vec()).to_plain_state_reverts();
+        self.revert(self.reverts.len() - start);
+        reverts
+    }
+
+    /// Unwinds the bundle to the state after its first `num_blocks` blocks.
+    ///
+    /// Returns the [`StateChangeset`] that brings a database holding the plain state of the
+    /// bundle back to the unwound state.
+    ///
+    /// Note: Storage of an account destroyed in the unwound blocks is only restored for the slots
+    /// known to the bundle. Other slots are covered by the `wiped` flag of the plain state
+    /// reverts.
+    pub fn revert_to(&mut self, num_blocks: usize) -> StateChangeset {
+        let start = num_blocks.min(self.reverts.len());
+        let mut addresses = self.reverts[start..]
+            .iter()
+            .flatten()
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        addresses.sort_unstable();
+        addresses.dedup();
+        let unwound = addresses
+            .into_iter()
+            .map(|address| (address, self.state.get(&address).cloned()))
+            .collect::<Vec<_>>();
+
+        self.take_reverts_for_unwind(start..);
+
+        let mut accounts = Vec::with_capacity(unwound.len());
+        let mut storage = Vec::with_capacity(unwound.len());
+        for (address, present) in unwound {
+            let Some(account) = self.state.get(&address) else {
+                // account did not exist before the unwound blocks.
+                accounts.push((address, None));
+                storage.push(PlainStorageChangeset {
+                    address,
+                    wipe_storage: true,
+                    storage: Vec::new(),
+                });
+                continue;
+            };
+
+            if present
+                .as_ref()
+                .is_none_or(|present| present.info != account.info)
+            {
+                let info = account.info.as_ref().map(AccountInfo::copy_without_code);
+                accounts.push((address, info));
+            }
+
+            // If either state was destroyed, the storage is wiped and written again.
+            let wipe_storage =
+                account.was_destroyed() || present.as_ref().is_some_and(|p| p.was_destroyed());
+            let present_storage = present.map(|present| present.storage).unwrap_or_default();
+            let mut account_storage = Vec::new();
+            for (key, slot) in account.storage.iter() {
+                let changed = present_storage
+                    .get(key)
+                    .is_none_or(|present| present.present_value != slot.present_value);
+                if (wipe_storage && !slot.present_value.is_zero()) || (!wipe_storage && changed) {
+                    account_storage.push((*key, slot.present_value));
+                }
+            }
+            if !wipe_storage {
+                // slots created in the unwound blocks go back to their original value.
+                account_storage.extend(
+                    present_storage
+                        .iter()
+                        .filter(|(key, _)| !account.storage.contains_key(*key))
+                        .map(|(key, slot)| (*key, slot.original_value())),
+                );
+            }
+
+            if !account_storage.is_empty() || wipe_storage {
+                storage.push(PlainStorageChangeset {
+                    address,
+                    wipe_storage,
+                    storage: account_storage,
+                });
+            }
+        }
+
+        StateChangeset {
+            accounts,
+            storage,
+            contracts: Vec::new(),
+        }
+    }
+
     /// Prepends present the state with the given BundleState.
     /// It adds changes from the given state but does not override any existing changes.
     ///
@@ -855,7 +1032,11 @@ impl BundleState {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{db::StorageWithOriginalValues, TransitionAccount};
+    use crate::{
+        db::{Database, DatabaseCommit, InMemoryDB, State, StorageWithOriginalValues},
+        primitives::{Account, EvmStorageSlot},
+        TransitionAccount,
+    };
 
     #[test]
     fn transition_states() {
@@ -1324,4 +1505,189 @@ mod tests {
             .insert(B256::default(), Bytecode::default());
         assert!(builder.get_contracts_mut().contains_key(&B256::default()));
     }
+
+    /// Executes `blocks` on top of a database holding `account1` with two storage slots.
+    fn execute_blocks(blocks: &[Vec<(Address, Account)>]) -> BundleState {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(account1(), account1_info());
+        db.insert_account_storage(account1(), slot1(), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(account1(), slot2(), U256::from(2))
+            .unwrap();
+        let mut state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        for block in blocks {
+            for (address, _) in block {
+                state.basic(*address).unwrap();
+            }
+            state.commit(block.iter().cloned().collect());
+            state.merge_transitions(BundleRetention::Reverts);
+        }
+        state.take_bundle()
+    }
+
+    fn account1_info() -> AccountInfo {
+        AccountInfo {
+            nonce: 1,
+            balance: U256::from(1),
+            ..Default::default()
+        }
+    }
+
+    fn touched(info: AccountInfo, storage: &[(U256, U256, U256)]) -> Account {
+        let mut account = Account::from(info);
+        account.storage = storage
+            .iter()
+            .map(|(key, original, present)| {
+                (*key, EvmStorageSlot::new_changed(*original, *present))
+            })
+            .collect();
+        account.mark_touch();
+        account
+    }
+
+    fn created(balance: u64, storage: &[(U256, U256, U256)]) -> Account {
+        let info = AccountInfo {
+            nonce: 1,
+            balance: U256::from(balance),
+            ..Default::default()
+        };
+        let mut account = touched(info, storage);
+        account.mark_created();
+        account
+    }
+
+    fn selfdestructed() -> Account {
+        let mut account = touched(AccountInfo::default(), &[]);
+        account.mark_selfdestruct();
+        account
+    }
+
+    /// Plain state of a database, without the accounts of the test bundles.
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct PlainDb {
+        accounts: HashMap<Address, AccountInfo>,
+        storage: HashMap<Address, HashMap<U256, U256>>,
+    }
+
+    impl PlainDb {
+        fn with_account1() -> Self {
+            let mut db = Self::default();
+            db.accounts.insert(account1(), account1_info());
+            db.storage.insert(
+                account1(),
+                HashMap::from_iter([(slot1(), U256::from(1)), (slot2(), U256::from(2))]),
+            );
+            db
+        }
+
+        fn apply(&mut self, changeset: StateChangeset) {
+            for (address, info) in changeset.accounts {
+                match info {
+                    Some(info) => self.accounts.insert(address, info),
+                    None => self.accounts.remove(&address),
+                };
+            }
+            for changes in changeset.storage {
+                let storage = self.storage.entry(changes.address).or_default();
+                if changes.wipe_storage {
+                    storage.clear();
+                }
+                for (key, value) in changes.storage {
+                    if value.is_zero() {
+                        storage.remove(&key);
+                    } else {
+                        storage.insert(key, value);
+                    }
+                }
+            }
+            self.storage.retain(|_, storage| !storage.is_empty());
+        }
+    }
+
+    #[test]
+    fn revert_to_unwinds_blocks() {
+        let account3 = Address::new([0x62; 20]);
+        let (zero, one, two) = (U256::ZERO, U256::from(1), U256::from(2));
+        let blocks = [
+            // account1 changes both slots and account2 is created.
+            vec![
+                (
+                    account1(),
+                    touched(
+                        account1_info(),
+                        &[
+                            (slot1(), one, U256::from(10)),
+                            (slot2(), two, U256::from(20)),
+                        ],
+                    ),
+                ),
+                (account2(), created(5, &[])),
+            ],
+            // account1 is destroyed, wiping its storage, and account3 is created.
+            vec![
+                (account1(), selfdestructed()),
+                (account3, created(3, &[(slot1(), zero, U256::from(5))])),
+            ],
+            // account3 is destroyed and account2 is changed.
+            vec![
+                (account3, selfdestructed()),
+                (
+                    account2(),
+                    touched(
+                        AccountInfo {
+                            nonce: 1,
+                            balance: U256::from(7),
+                            ..Default::default()
+                        },
+                        &[],
+                    ),
+                ),
+            ],
+        ];
+        let expected = execute_blocks(&blocks[..1]);
+        let mut bundle = execute_blocks(&blocks);
+
+        let mut db = PlainDb::with_account1();
+        db.apply(bundle.to_plain_state(OriginalValuesKnown::Yes));
+        assert_eq!(db.accounts.get(&account1()), None);
+        assert_eq!(db.storage.get(&account1()), None);
+
+        let sorted = |mut reverts: Reverts| {
+            reverts.sort();
+            reverts
+        };
+        let mut unwound = bundle.clone();
+        let reverts = unwound.take_reverts_for_unwind(1..);
+        assert_eq!(reverts.accounts.len(), 2);
+        // account3 did not exist before the second block.
+        assert!(reverts.accounts[0].contains(&(account3, None)));
+        assert_eq!(sorted(unwound.reverts), sorted(expected.reverts.clone()));
+
+        db.apply(bundle.revert_to(1));
+        let mut expected_db = PlainDb::with_account1();
+        expected_db.apply(expected.to_plain_state(OriginalValuesKnown::Yes));
+        assert_eq!(db, expected_db);
+
+        assert_eq!(
+            sorted(bundle.reverts.clone()),
+            sorted(expected.reverts.clone())
+        );
+        assert_eq!(bundle.reverts_size, expected.reverts_size);
+        assert_eq!(
+            bundle.state.keys().collect::<HashSet<_>>(),
+            expected.state.keys().collect::<HashSet<_>>()
+        );
+        for (address, account) in &expected.state {
+            assert_eq!(bundle.state[address].info, account.info);
+            assert_eq!(bundle.state[address].status, account.status);
+        }
+
+        // Unwinding the first block restores the database.
+        db.apply(bundle.revert_to(0));
+        assert_eq!(db, PlainDb::with_account1());
+        assert!(bundle.reverts.is_empty());
+    }
 }
@@ -9,7 +9,24 @@ use std::vec::Vec;
 /// Contains reverts of multiple account in multiple transitions (Transitions as a block).
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Reverts(Vec<Vec<(Address, AccountRevert)>>);
+pub struct Reverts(
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sorted_reverts"))]
+    Vec<Vec<(Address, AccountRevert)>>,
+);
+
+/// Serializes reverts with the accounts of each transition sorted by address, as their order
+/// in memory follows the iteration order of the transition map.
+#[cfg(feature = "serde")]
+fn serialize_sorted_reverts<S: serde::Serializer>(
+    reverts: &[Vec<(Address, AccountRevert)>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(reverts.iter().map(|transition| {
+        let mut accounts: Vec<_> = transition.iter().collect();
+        accounts.sort_unstable_by_key(|(address, _)| *address);
+        accounts
+    }))
+}
 
 impl Deref for Reverts {
     type Target = Vec<Vec<(Address, AccountRevert)>>;
@@ -100,6 +117,10 @@ impl Reverts {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AccountRevert {
     pub account: AccountInfoRevert,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub storage: HashMap<U256, RevertToSlot>,
     pub previous_status: AccountStatus,
     pub wipe_storage: bool,
@@ -171,6 +171,16 @@ impl<DB: Database> State<DB> {
         }
     }
 
+    /// Same as [`Self::merge_transitions`], but the accounts are merged in parallel, see
+    /// [`BundleState::apply_transitions_and_create_reverts_parallel`].
+    #[cfg(feature = "rayon")]
+    pub fn merge_transitions_parallel(&mut self, retention: BundleRetention) {
+        if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
+            self.bundle_state
+                .apply_transitions_and_create_reverts_parallel(transition_state, retention);
+        }
+    }
+
     /// Get a mutable reference to the [`CacheAccount`] for the given address.
     /// If the account is not found in the cache, it will be loaded from the
     /// database and inserted into the cache.
@@ -792,4 +802,76 @@ mod tests {
             )])])
         )
     }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn merge_transitions_parallel_matches_serial() {
+        let mut serial = State::builder().with_bundle_update().build();
+        let mut parallel = State::builder().with_bundle_update().build();
+        let address = |i: u64| Address::from_word(B256::from(U256::from(i)));
+        let info = |nonce: u64| AccountInfo {
+            nonce,
+            ..Default::default()
+        };
+
+        // Accounts are created in the first block and changed or destroyed in the second one.
+        let blocks = [
+            (0..1_000)
+                .map(|i| {
+                    let transition = TransitionAccount {
+                        info: Some(info(1)),
+                        status: AccountStatus::InMemoryChange,
+                        previous_info: None,
+                        previous_status: AccountStatus::LoadedNotExisting,
+                        storage: HashMap::from_iter([(
+                            U256::from(i),
+                            StorageSlot::new_changed(U256::ZERO, U256::from(1)),
+                        )]),
+                        storage_was_destroyed: false,
+                    };
+                    (address(i), transition)
+                })
+                .collect::<Vec<_>>(),
+            (0..2_000)
+                .map(|i| {
+                    let transition = if i < 500 {
+                        TransitionAccount {
+                            info: None,
+                            status: AccountStatus::Destroyed,
+                            previous_info: Some(info(1)),
+                            previous_status: AccountStatus::InMemoryChange,
+                            storage: HashMap::default(),
+                            storage_was_destroyed: true,
+                        }
+                    } else {
+                        let existed = i < 1_000;
+                        TransitionAccount {
+                            info: Some(info(2)),
+                            status: AccountStatus::InMemoryChange,
+                            previous_info: existed.then(|| info(1)),
+                            previous_status: if existed {
+                                AccountStatus::InMemoryChange
+                            } else {
+                                AccountStatus::LoadedNotExisting
+                            },
+                            storage: HashMap::default(),
+                            storage_was_destroyed: false,
+                        }
+                    };
+                    (address(i), transition)
+                })
+                .collect::<Vec<_>>(),
+        ];
+
+        for block in blocks {
+            serial.apply_transition(block);
+            // the same transitions are merged, in the same iteration order.
+            parallel.transition_state = serial.transition_state.clone();
+            serial.merge_transitions(BundleRetention::Reverts);
+            parallel.merge_transitions_parallel(BundleRetention::Reverts);
+        }
+
+        assert_eq!(serial.bundle_state.state.len(), 2_000);
+        assert_eq!(serial.take_bundle(), parallel.take_bundle());
+    }
 }
@@ -2,23 +2,38 @@ use revm_interpreter::gas::InitialAndFloorGas;
 
 use crate::{
     builder::{EvmBuilder, HandlerStage, SetGenericStage},
-    db::{Database, DatabaseCommit, EmptyDB},
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef, DbAccount, EmptyDB},
     handler::Handler,
     interpreter::{
         CallInputs, CreateInputs, EOFCreateInputs, Host, InterpreterAction, SharedMemory,
     },
     primitives::{
-        specification::SpecId, BlockEnv, CfgEnv, EVMError, EVMResult, EnvWithHandlerCfg,
-        ExecutionResult, HandlerCfg, ResultAndState, TxEnv, TxKind, EOF_MAGIC_BYTES,
+        specification::SpecId, Address, BlockEnv, Bytes, CfgEnv, EVMError, EVMResult,
+        EnvWithHandlerCfg, EvmState, ExecutionResult, FeeSummary, HandlerCfg, HashMap,
+        ResultAndState, TxEnv, TxKind, CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS, EOF_MAGIC_BYTES,
+        SYSTEM_ADDRESS, SYSTEM_CALL_GAS_LIMIT, U256, WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
     },
-    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
+    BundleControl, BundleSimulationReport, BundleTxResult, Context, ContextView,
+    ContextWithHandlerCfg, FirstFrameInputs, Frame, FrameOrResult, FrameResult, PreparedTx,
+    PreverifiedTx, SuspendOutcome, SuspendedExecution,
 };
 use core::fmt;
-use std::{boxed::Box, vec::Vec};
+use std::{boxed::Box, format, vec::Vec};
 
 /// EVM call stack limit.
 pub const CALL_STACK_LIMIT: u64 = 1024;
 
+/// Outcome of the main call loop.
+enum LoopOutcome {
+    /// All frames returned.
+    Done(FrameResult),
+    /// The last frame of the call stack is about to start executing.
+    Suspended {
+        call_stack: Vec<Frame>,
+        shared_memory: SharedMemory,
+    },
+}
+
 /// EVM instance containing both internal EVM context and external context
 /// and the handler that dictates the logic of EVM (or hardfork specification).
 pub struct Evm<'a, EXT, DB: Database> {
@@ -27,6 +42,9 @@ pub struct Evm<'a, EXT, DB: Database> {
     /// Handler is a component of the of EVM that contains all the logic. Handler contains specification id
     /// and it different depending on the specified fork.
     pub handler: Handler<'a, Context<EXT, DB>, EXT, DB>,
+    /// Time spent in the handler stages, see [`Self::metrics`].
+    #[cfg(feature = "metrics")]
+    metrics: crate::HandlerMetrics,
 }
 
 impl<EXT, DB> fmt::Debug for Evm<'_, EXT, DB>
@@ -51,6 +69,94 @@ impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
     }
 }
 
+impl<EXT, ExtDB: DatabaseRef> Evm<'_, EXT, CacheDB<ExtDB>> {
+    /// Simulates the transactions of a bundle in order, each one `control` returns
+    /// [`BundleControl::Continue`] for is committed to the [`CacheDB`] for the following
+    /// transactions.
+    ///
+    /// `control` is called with the index of the transaction in the bundle, its result and state,
+    /// and a view of the context. A skipped transaction is not committed, the following
+    /// transactions are validated and executed against the state before it. The simulation stops
+    /// at the first aborted transaction or at the first error of a transaction.
+    ///
+    /// The committed transactions are discarded once the simulation ends, the accounts of the
+    /// database and its logs are restored. The transaction environment is restored too.
+    pub fn simulate_bundle<I, F>(
+        &mut self,
+        txs: I,
+        mut control: F,
+    ) -> Result<BundleSimulationReport, EVMError<ExtDB::Error>>
+    where
+        I: IntoIterator<Item = TxEnv>,
+        F: FnMut(usize, &ResultAndState, ContextView<'_, CacheDB<ExtDB>>) -> BundleControl,
+    {
+        let coinbase = self.context.evm.env.block.coinbase;
+        let coinbase_balance = self
+            .context
+            .evm
+            .db
+            .basic(coinbase)
+            .map_err(EVMError::Database)?
+            .map(|info| info.balance)
+            .unwrap_or_default();
+        let mut report = BundleSimulationReport {
+            coinbase_balance_before: coinbase_balance,
+            coinbase_balance_after: coinbase_balance,
+            ..Default::default()
+        };
+
+        // Accounts of the database before they are first committed to.
+        let mut committed_accounts: HashMap<Address, Option<DbAccount>> = HashMap::default();
+        let logs_len = self.context.evm.db.logs.len();
+        let original_tx = self.context.evm.env.tx.clone();
+        let mut output = Ok(());
+        for (index, tx) in txs.into_iter().enumerate() {
+            self.context.evm.env.tx = tx;
+            let result_and_state = match self.transact() {
+                Ok(result_and_state) => result_and_state,
+                Err(e) => {
+                    output = Err(e);
+                    break;
+                }
+            };
+            let tx_control = control(index, &result_and_state, self.context_view());
+            let ResultAndState { result, state } = result_and_state;
+            if tx_control == BundleControl::Continue {
+                report.cumulative_gas_used += result.gas_used();
+                if let Some(account) = state.get(&coinbase) {
+                    report.coinbase_balance_after = account.info.balance;
+                }
+                let db = &mut self.context.evm.db;
+                for address in state.keys() {
+                    committed_accounts
+                        .entry(*address)
+                        .or_insert_with(|| db.accounts.get(address).cloned());
+                }
+                db.commit(state);
+            }
+            report.results.push(BundleTxResult {
+                result,
+                control: tx_control,
+            });
+            if tx_control == BundleControl::Abort {
+                break;
+            }
+        }
+        self.context.evm.env.tx = original_tx;
+
+        let db = &mut self.context.evm.db;
+        db.logs.truncate(logs_len);
+        for (address, account) in committed_accounts {
+            match account {
+                Some(account) => db.accounts.insert(address, account),
+                None => db.accounts.remove(&address),
+            };
+        }
+
+        output.map(|()| report)
+    }
+}
+
 impl<'a> Evm<'a, (), EmptyDB> {
     /// Returns evm builder with empty database and empty external context.
     pub fn builder() -> EvmBuilder<'a, SetGenericStage, (), EmptyDB> {
@@ -65,7 +171,12 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
         handler: Handler<'a, Context<EXT, DB>, EXT, DB>,
     ) -> Evm<'a, EXT, DB> {
         context.evm.journaled_state.set_spec_id(handler.cfg.spec_id);
-        Evm { context, handler }
+        Evm {
+            context,
+            handler,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+        }
     }
 
     /// Allow for evm setting to be modified by feeding current evm
@@ -77,6 +188,15 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
     /// Runs main call loop.
     #[inline]
     pub fn run_the_loop(&mut self, first_frame: Frame) -> Result<FrameResult, EVMError<DB::Error>> {
+        let (call_stack, shared_memory) = self.first_call_stack(first_frame);
+        match self.run_frames(call_stack, shared_memory, None)? {
+            LoopOutcome::Done(result) => Ok(result),
+            LoopOutcome::Suspended { .. } => unreachable!("suspension is disabled"),
+        }
+    }
+
+    /// Creates the call stack and shared memory of the main call loop.
+    fn first_call_stack(&self, first_frame: Frame) -> (Vec<Frame>, SharedMemory) {
         let mut call_stack: Vec<Frame> = Vec::with_capacity(1025);
         call_stack.push(first_frame);
 
@@ -87,15 +207,61 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
         let mut shared_memory = SharedMemory::new();
 
         shared_memory.new_context();
+        (call_stack, shared_memory)
+    }
+
+    /// Runs the frames of the call stack, suspending before the frame at `suspend_at` depth
+    /// starts executing.
+    fn run_frames(
+        &mut self,
+        call_stack: Vec<Frame>,
+        shared_memory: SharedMemory,
+        suspend_at: Option<usize>,
+    ) -> Result<LoopOutcome, EVMError<DB::Error>> {
+        metrics_stage!(
+            self,
+            execution,
+            self.execution_stage(call_stack, shared_memory, suspend_at)
+        )
+    }
+
+    /// Runs the frames of the call stack, see [`Self::run_frames`].
+    fn execution_stage(
+        &mut self,
+        mut call_stack: Vec<Frame>,
+        mut shared_memory: SharedMemory,
+        suspend_at: Option<usize>,
+    ) -> Result<LoopOutcome, EVMError<DB::Error>> {
+        if suspend_at == Some(call_stack.len()) {
+            return Ok(LoopOutcome::Suspended {
+                call_stack,
+                shared_memory,
+            });
+        }
+
+        instrument_span!(DEBUG, "execution");
+        #[cfg(feature = "instrument")]
+        let mut frame_spans = crate::instrument::FrameSpans::new(&call_stack);
 
         // Peek the last stack frame.
         let mut stack_frame = call_stack.last_mut().unwrap();
 
         loop {
+            #[cfg(feature = "instrument")]
+            let _frame_span = frame_spans.enter_top();
+
             // Execute the frame.
+            #[cfg(feature = "metrics")]
+            let start = std::time::Instant::now();
             let next_action =
                 self.handler
                     .execute_frame(stack_frame, &mut shared_memory, &mut self.context)?;
+            #[cfg(feature = "metrics")]
+            self.metrics.record_frame(
+                call_stack.len(),
+                start.elapsed(),
+                matches!(next_action, InterpreterAction::Return { .. }),
+            );
 
             // Take error and break the loop, if any.
             // This error can be set in the Interpreter when it interacts with the context.
@@ -116,6 +282,14 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
                     let returned_frame = call_stack
                         .pop()
                         .expect("We just returned from Interpreter frame");
+                    #[cfg(feature = "instrument")]
+                    frame_spans.pop();
+                    instrument_event!(
+                        TRACE,
+                        result = ?result.result,
+                        gas_spent = result.gas.spent(),
+                        "frame returned"
+                    );
 
                     let ctx = &mut self.context;
                     FrameOrResult::Result(match returned_frame {
@@ -139,13 +313,21 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
             match frame_or_result {
                 FrameOrResult::Frame(frame) => {
                     shared_memory.new_context();
+                    #[cfg(feature = "instrument")]
+                    frame_spans.push(&frame);
                     call_stack.push(frame);
+                    if suspend_at == Some(call_stack.len()) {
+                        return Ok(LoopOutcome::Suspended {
+                            call_stack,
+                            shared_memory,
+                        });
+                    }
                     stack_frame = call_stack.last_mut().unwrap();
                 }
                 FrameOrResult::Result(result) => {
                     let Some(top_frame) = call_stack.last_mut() else {
                         // Break the loop if there are no more frames.
-                        return Ok(result);
+                        return Ok(LoopOutcome::Done(result));
                     };
                     stack_frame = top_frame;
                     let ctx = &mut self.context;
@@ -178,39 +360,104 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         self.handler.cfg.spec_id
     }
 
-    /// Pre verify transaction by checking Environment, initial gas spend and if caller
-    /// has enough balance to pay for the gas.
+    /// Returns a read-only view of the EVM context.
     #[inline]
-    pub fn preverify_transaction(&mut self) -> Result<(), EVMError<DB::Error>> {
-        let output = self.preverify_transaction_inner().map(|_| ());
-        self.clear();
-        output
+    pub fn context_view(&self) -> ContextView<'_, DB> {
+        self.context.evm.view()
     }
 
     /// Calls clear handle of post execution to clear the state for next execution.
+    ///
+    /// Transient storage is cleared even if the handle doesn't, so it never leaks into the next
+    /// transaction.
     fn clear(&mut self) {
         self.handler.post_execution().clear(&mut self.context);
+        self.context
+            .evm
+            .inner
+            .journaled_state
+            .clear_transient_storage();
     }
 
-    /// Transact pre-verified transaction
+    /// Validates `tx` against the environment and the state of its caller without executing it.
     ///
-    /// This function will not validate the transaction.
-    #[inline]
-    pub fn transact_preverified(&mut self) -> EVMResult<DB::Error> {
-        let init_and_floor_gas = self
-            .handler
-            .validation()
-            .initial_tx_gas(&self.context.evm.env)
+    /// The returned token is executed with [`Self::transact_preverified`], which only
+    /// rechecks the nonce and balance of the caller. See [`PreverifiedTx`] for the staleness
+    /// hazard.
+    pub fn preverify_transaction(
+        &mut self,
+        tx: &TxEnv,
+    ) -> Result<PreverifiedTx, EVMError<DB::Error>> {
+        self.context.evm.env.tx = tx.clone();
+        let output = self.preverify_transaction_inner().and_then(|initial_gas| {
+            // The caller may have been recovered from the signature.
+            let tx = self.context.evm.env.tx.clone();
+            let caller = self.context.evm.inner.load_account(tx.caller)?.data;
+            Ok(PreverifiedTx {
+                tx,
+                initial_gas,
+                caller_nonce: caller.info.nonce,
+                caller_balance: caller.info.balance,
+            })
+        });
+        self.clear();
+        output
+    }
+
+    /// Executes a transaction validated by [`Self::preverify_transaction`].
+    ///
+    /// If the nonce or balance of the caller changed since, the transaction is validated against
+    /// the state again and fails like [`Self::transact`] would if it is no longer valid.
+    pub fn transact_preverified(&mut self, preverified: PreverifiedTx) -> EVMResult<DB::Error> {
+        let PreverifiedTx {
+            tx,
+            initial_gas,
+            caller_nonce,
+            caller_balance,
+        } = preverified;
+        self.context.evm.env.tx = tx;
+        #[cfg(feature = "instrument")]
+        let _span = crate::instrument::transact_span(&self.context.evm.env.tx).entered();
+        self.recheck_caller(caller_nonce, caller_balance)
             .inspect_err(|_e| self.clear())?;
-        let output = self.transact_preverified_inner(init_and_floor_gas);
+
+        let output = self.transact_preverified_inner(initial_gas);
         let output = self.handler.post_execution().end(&mut self.context, output);
         self.clear();
         output
     }
 
+    /// Validates the transaction against the state again if the nonce or balance of the caller
+    /// changed since it was verified.
+    ///
+    /// Validation is always repeated with the balance check disabled, as it tops up the balance
+    /// of the caller.
+    fn recheck_caller(&mut self, nonce: u64, balance: U256) -> Result<(), EVMError<DB::Error>> {
+        let caller = self.context.evm.env.tx.caller;
+        let account = self.context.evm.inner.load_account(caller)?.data;
+        let unchanged = account.info.nonce == nonce && account.info.balance == balance;
+        if !unchanged || self.context.evm.env.cfg.is_balance_check_disabled() {
+            self.handler
+                .validation()
+                .tx_against_state(&mut self.context)?;
+        }
+        Ok(())
+    }
+
     /// Pre verify transaction inner.
     #[inline]
     fn preverify_transaction_inner(&mut self) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
+        metrics_stage!(self, validation, self.validation_stage())
+    }
+
+    /// Validates the transaction, see [`Self::preverify_transaction_inner`].
+    #[inline]
+    fn validation_stage(&mut self) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
+        instrument_span!(DEBUG, "validation");
+        #[cfg(feature = "secp256k1-recover")]
+        self.handler
+            .validation()
+            .recover_caller(&mut self.context.evm.env)?;
         self.handler.validation().env(&self.context.evm.env)?;
         let initial_gas_spend = self
             .handler
@@ -227,6 +474,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// This function will validate the transaction.
     #[inline]
     pub fn transact(&mut self) -> EVMResult<DB::Error> {
+        #[cfg(feature = "instrument")]
+        let _span = crate::instrument::transact_span(&self.context.evm.env.tx).entered();
         let init_and_floor_gas = self
             .preverify_transaction_inner()
             .inspect_err(|_e| self.clear())?;
@@ -237,6 +486,164 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         output
     }
 
+    /// Executes the transaction like [`Self::transact`], but suspends it at the frame boundary
+    /// where the call stack first reaches `depth` frames, before that frame starts executing.
+    ///
+    /// The suspended execution is continued with [`Self::resume`].
+    #[inline]
+    pub fn suspend_at_depth(
+        &mut self,
+        depth: usize,
+    ) -> Result<SuspendOutcome, EVMError<DB::Error>> {
+        let init_and_floor_gas = self
+            .preverify_transaction_inner()
+            .inspect_err(|_e| self.clear())?;
+
+        let output = match self.suspend_at_depth_inner(init_and_floor_gas, depth) {
+            Ok(SuspendOutcome::Suspended(suspended)) => {
+                self.clear();
+                return Ok(SuspendOutcome::Suspended(suspended));
+            }
+            Ok(SuspendOutcome::Completed(result)) => Ok(result),
+            Err(e) => Err(e),
+        };
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output.map(SuspendOutcome::Completed)
+    }
+
+    /// Continues an execution suspended by [`Self::suspend_at_depth`] until the transaction
+    /// finishes.
+    #[inline]
+    pub fn resume(&mut self, suspended: SuspendedExecution) -> EVMResult<DB::Error> {
+        let output = self.resume_inner(suspended);
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output
+    }
+
+    /// Validates the transaction and runs pre-execution, stopping before the first frame is
+    /// created.
+    ///
+    /// The context is left with the accounts loaded, the caller charged and the EIP-7702
+    /// authorizations applied. The frames are executed by the caller, for example with
+    /// [`Self::create_first_frame`] and [`Self::run_the_loop`], and the transaction must then be
+    /// finished with [`Self::finish_transaction`].
+    ///
+    /// On error the state is cleared. The error is returned as is, without the `end` stage of
+    /// post-execution that turns failed Optimism deposits into a halt.
+    pub fn prepare_transaction(&mut self) -> Result<PreparedTx, EVMError<DB::Error>> {
+        let output = self.preverify_transaction_inner().and_then(|initial_gas| {
+            let (inputs, eip7702_gas_refund) = self.pre_execution(&initial_gas)?;
+            Ok(PreparedTx {
+                initial_gas,
+                eip7702_gas_refund,
+                inputs,
+            })
+        });
+        output.inspect_err(|_e| self.clear())
+    }
+
+    /// Creates the first frame of a transaction prepared with [`Self::prepare_transaction`].
+    ///
+    /// Returns the result directly if the frame ends without executing code, for example a call
+    /// to a precompile.
+    pub fn create_first_frame(
+        &mut self,
+        inputs: FirstFrameInputs,
+    ) -> Result<FrameOrResult, EVMError<DB::Error>> {
+        let ctx = &mut self.context;
+        let exec = self.handler.execution();
+        match inputs {
+            FirstFrameInputs::Call(inputs) => exec.call(ctx, inputs),
+            FirstFrameInputs::Create(inputs) => exec.create(ctx, inputs),
+            FirstFrameInputs::EOFCreate(inputs) => exec.eofcreate(ctx, inputs),
+        }
+    }
+
+    /// Runs the post-execution stages of a transaction prepared with
+    /// [`Self::prepare_transaction`] on the result of its first frame, or on the error that
+    /// stopped its execution, and clears the state for the next transaction.
+    pub fn finish_transaction(
+        &mut self,
+        prepared: PreparedTx,
+        result: Result<FrameResult, EVMError<DB::Error>>,
+    ) -> EVMResult<DB::Error> {
+        let output = result.and_then(|result| {
+            self.finish_transaction_inner(prepared.initial_gas, prepared.eip7702_gas_refund, result)
+        });
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output
+    }
+
+    /// Calls `contract` with `data` from [`SYSTEM_ADDRESS`], as block processing does around the
+    /// transactions of a block.
+    ///
+    /// The call has a gas limit of [`SYSTEM_CALL_GAS_LIMIT`], is not charged and does not count
+    /// against the block gas limit. The environment is restored afterwards and the system address
+    /// and the coinbase are left out of the returned state.
+    pub fn transact_system_call(&mut self, contract: Address, data: Bytes) -> EVMResult<DB::Error> {
+        let system_tx = TxEnv {
+            caller: SYSTEM_ADDRESS,
+            transact_to: TxKind::Call(contract),
+            data,
+            gas_limit: SYSTEM_CALL_GAS_LIMIT,
+            ..Default::default()
+        };
+        let tx = core::mem::replace(&mut self.context.evm.env.tx, system_tx);
+        let block = self.context.evm.env.block.clone();
+        let coinbase = block.coinbase;
+        self.context.evm.env.block.basefee = U256::ZERO;
+        self.context.evm.env.block.gas_limit =
+            block.gas_limit.max(U256::from(SYSTEM_CALL_GAS_LIMIT));
+
+        let output = self.transact();
+        self.context.evm.env.tx = tx;
+        self.context.evm.env.block = block;
+
+        let mut output = output?;
+        if contract != SYSTEM_ADDRESS {
+            output.state.remove(&SYSTEM_ADDRESS);
+        }
+        if contract != coinbase {
+            output.state.remove(&coinbase);
+        }
+        Ok(output)
+    }
+
+    /// Dequeues the EIP-7002 withdrawal requests of the block from the withdrawal request
+    /// predeploy, returning the raw request bytes and the changed state.
+    pub fn transact_withdrawal_requests(
+        &mut self,
+    ) -> Result<(Bytes, EvmState), EVMError<DB::Error>> {
+        self.transact_request_system_call(WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS, "withdrawal")
+    }
+
+    /// Dequeues the EIP-7251 consolidation requests of the block from the consolidation request
+    /// predeploy, returning the raw request bytes and the changed state.
+    pub fn transact_consolidation_requests(
+        &mut self,
+    ) -> Result<(Bytes, EvmState), EVMError<DB::Error>> {
+        self.transact_request_system_call(CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS, "consolidation")
+    }
+
+    /// Calls a request predeploy without input, failing if the call does not succeed.
+    #[allow(deprecated)]
+    fn transact_request_system_call(
+        &mut self,
+        contract: Address,
+        name: &str,
+    ) -> Result<(Bytes, EvmState), EVMError<DB::Error>> {
+        let ResultAndState { result, state } = self.transact_system_call(contract, Bytes::new())?;
+        match result {
+            ExecutionResult::Success { output, .. } => Ok((output.into_data(), state)),
+            result => Err(EVMError::Custom(format!(
+                "{name} request system call failed: {result:?}"
+            ))),
+        }
+    }
+
     /// Returns the reference of handler configuration
     #[inline]
     pub fn handler_cfg(&self) -> &HandlerCfg {
@@ -255,6 +662,23 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         &mut self.context.evm.env.cfg
     }
 
+    /// Returns the time spent in the handler stages since the EVM was built or the metrics were
+    /// last taken.
+    ///
+    /// The metrics are not kept by [`Self::modify`].
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> &crate::HandlerMetrics {
+        &self.metrics
+    }
+
+    /// Takes the metrics, see [`Self::metrics`], and resets them.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn take_metrics(&mut self) -> crate::HandlerMetrics {
+        core::mem::take(&mut self.metrics)
+    }
+
     /// Returns the reference of transaction
     #[inline]
     pub fn tx(&self) -> &TxEnv {
@@ -291,6 +715,25 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         &mut self.context.evm.env.block
     }
 
+    /// Returns the fees charged to the last executed Optimism transaction.
+    ///
+    /// Values are available after [`Self::transact`] returns and are reset when the next
+    /// transaction is executed.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn op_tx_fees(&self) -> Option<&crate::optimism::OpTxFees> {
+        self.context.evm.inner.op_tx_fees.as_ref()
+    }
+
+    /// Returns how the fees of the last executed transaction were distributed.
+    ///
+    /// Values are available after [`Self::transact`] returns and are reset when the next
+    /// transaction is executed.
+    #[inline]
+    pub fn fee_summary(&self) -> Option<&FeeSummary> {
+        self.context.evm.inner.fee_summary.as_ref()
+    }
+
     /// Modify spec id, this will create new EVM that matches this spec id.
     pub fn modify_spec_id(&mut self, spec_id: SpecId) {
         self.handler.modify_spec_id(spec_id);
@@ -322,10 +765,132 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
     /// Transact pre-verified transaction.
     fn transact_preverified_inner(&mut self, gas: InitialAndFloorGas) -> EVMResult<DB::Error> {
+        let (first_frame_or_result, eip7702_gas_refund) = self.start_transaction(&gas)?;
+
+        // Starts the main running loop.
+        let result = match first_frame_or_result {
+            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
+            FrameOrResult::Result(result) => result,
+        };
+
+        self.finish_transaction_inner(gas, eip7702_gas_refund, result)
+    }
+
+    /// Transact pre-verified transaction until the call stack reaches `depth` frames.
+    fn suspend_at_depth_inner(
+        &mut self,
+        gas: InitialAndFloorGas,
+        depth: usize,
+    ) -> Result<SuspendOutcome, EVMError<DB::Error>> {
+        let (first_frame_or_result, eip7702_gas_refund) = self.start_transaction(&gas)?;
+
+        let outcome = match first_frame_or_result {
+            FrameOrResult::Frame(first_frame) => {
+                let (call_stack, shared_memory) = self.first_call_stack(first_frame);
+                self.run_frames(call_stack, shared_memory, Some(depth))?
+            }
+            FrameOrResult::Result(result) => LoopOutcome::Done(result),
+        };
+
+        match outcome {
+            LoopOutcome::Done(result) => self
+                .finish_transaction_inner(gas, eip7702_gas_refund, result)
+                .map(SuspendOutcome::Completed),
+            LoopOutcome::Suspended {
+                call_stack,
+                shared_memory,
+            } => {
+                let inner = &self.context.evm.inner;
+                Ok(SuspendOutcome::Suspended(Box::new(SuspendedExecution {
+                    env: inner.env.clone(),
+                    journaled_state: inner.journaled_state.clone(),
+                    call_stack,
+                    shared_memory,
+                    initial_gas: gas,
+                    eip7702_gas_refund,
+                    fee_summary: inner.fee_summary,
+                    #[cfg(feature = "optimism")]
+                    l1_block_info: inner.l1_block_info.clone(),
+                    #[cfg(feature = "optimism")]
+                    op_tx_fees: inner.op_tx_fees,
+                })))
+            }
+        }
+    }
+
+    /// Restores the suspended execution and runs it to the end.
+    fn resume_inner(&mut self, suspended: SuspendedExecution) -> EVMResult<DB::Error> {
+        let SuspendedExecution {
+            env,
+            journaled_state,
+            call_stack,
+            shared_memory,
+            initial_gas,
+            eip7702_gas_refund,
+            fee_summary,
+            #[cfg(feature = "optimism")]
+            l1_block_info,
+            #[cfg(feature = "optimism")]
+            op_tx_fees,
+        } = suspended;
+
+        // Precompiles are not part of the snapshot, their addresses are already warm in the
+        // restored journaled state.
+        let precompiles = self.handler.pre_execution().load_precompiles();
+        self.context.evm.precompiles = precompiles;
+
+        let inner = &mut self.context.evm.inner;
+        inner.env = env;
+        inner.journaled_state = journaled_state;
+        inner.fee_summary = fee_summary;
+        #[cfg(feature = "optimism")]
+        {
+            inner.l1_block_info = l1_block_info;
+            inner.op_tx_fees = op_tx_fees;
+        }
+
+        let result = match self.run_frames(call_stack, shared_memory, None)? {
+            LoopOutcome::Done(result) => result,
+            LoopOutcome::Suspended { .. } => unreachable!("suspension is disabled"),
+        };
+        self.finish_transaction_inner(initial_gas, eip7702_gas_refund, result)
+    }
+
+    /// Runs pre execution and creates the first frame.
+    ///
+    /// Returns the first frame or its result and the EIP-7702 gas refund.
+    fn start_transaction(
+        &mut self,
+        gas: &InitialAndFloorGas,
+    ) -> Result<(FrameOrResult, i64), EVMError<DB::Error>> {
+        let (inputs, eip7702_gas_refund) = self.pre_execution(gas)?;
+        Ok((self.create_first_frame(inputs)?, eip7702_gas_refund))
+    }
+
+    /// Runs pre execution and returns the inputs of the first frame and the EIP-7702 gas refund.
+    fn pre_execution(
+        &mut self,
+        gas: &InitialAndFloorGas,
+    ) -> Result<(FirstFrameInputs, i64), EVMError<DB::Error>> {
+        metrics_stage!(self, pre_execution, self.pre_execution_stage(gas))
+    }
+
+    /// Runs pre execution, see [`Self::pre_execution`].
+    fn pre_execution_stage(
+        &mut self,
+        gas: &InitialAndFloorGas,
+    ) -> Result<(FirstFrameInputs, i64), EVMError<DB::Error>> {
+        instrument_span!(DEBUG, "pre_execution");
         let spec_id = self.spec_id();
         let ctx = &mut self.context;
         let pre_exec = self.handler.pre_execution();
 
+        debug_assert_eq!(
+            ctx.evm.journaled_state.transient_storage_len(),
+            0,
+            "transient storage leaked from the previous transaction"
+        );
+
         // load access list and beneficiary if needed.
         pre_exec.load_accounts(ctx)?;
 
@@ -333,6 +898,9 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         let precompiles = pre_exec.load_precompiles();
         ctx.evm.set_precompiles(precompiles);
 
+        // apply state overrides once the warm addresses are known.
+        ctx.evm.apply_state_overrides()?;
+
         // deduce caller balance with its limit.
         pre_exec.deduct_caller(ctx)?;
 
@@ -341,38 +909,49 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         // apply EIP-7702 auth list.
         let eip7702_gas_refund = pre_exec.apply_eip7702_auth_list(ctx)? as i64;
 
-        let exec = self.handler.execution();
-        // call inner handling of call/create
-        let first_frame_or_result = match ctx.evm.env.tx.transact_to {
-            TxKind::Call(_) => exec.call(
-                ctx,
-                CallInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-            )?,
-            TxKind::Create => {
-                // if first byte of data is magic 0xEF00, then it is EOFCreate.
+        let tx = &ctx.evm.env.tx;
+        let inputs = match tx.transact_to {
+            TxKind::Call(_) => {
+                FirstFrameInputs::Call(CallInputs::new_boxed(tx, gas_limit).unwrap())
+            }
+            // if first byte of data is magic 0xEF00, then it is EOFCreate.
+            TxKind::Create
                 if spec_id.is_enabled_in(SpecId::OSAKA)
-                    && ctx.env().tx.data.starts_with(&EOF_MAGIC_BYTES)
-                {
-                    exec.eofcreate(
-                        ctx,
-                        Box::new(EOFCreateInputs::new_tx(&ctx.evm.env.tx, gas_limit)),
-                    )?
-                } else {
-                    // Safe to unwrap because we are sure that it is create tx.
-                    exec.create(
-                        ctx,
-                        CreateInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-                    )?
-                }
+                    && tx.data.starts_with(&EOF_MAGIC_BYTES) =>
+            {
+                FirstFrameInputs::EOFCreate(Box::new(EOFCreateInputs::new_tx(tx, gas_limit)))
+            }
+            // Safe to unwrap because we are sure that it is create tx.
+            TxKind::Create => {
+                FirstFrameInputs::Create(CreateInputs::new_boxed(tx, gas_limit).unwrap())
             }
         };
 
-        // Starts the main running loop.
-        let mut result = match first_frame_or_result {
-            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
-            FrameOrResult::Result(result) => result,
-        };
+        Ok((inputs, eip7702_gas_refund))
+    }
+
+    /// Runs post execution on the result of the first frame.
+    fn finish_transaction_inner(
+        &mut self,
+        gas: InitialAndFloorGas,
+        eip7702_gas_refund: i64,
+        result: FrameResult,
+    ) -> EVMResult<DB::Error> {
+        metrics_stage!(
+            self,
+            post_execution,
+            self.post_execution_stage(gas, eip7702_gas_refund, result)
+        )
+    }
 
+    /// Runs post execution, see [`Self::finish_transaction_inner`].
+    fn post_execution_stage(
+        &mut self,
+        gas: InitialAndFloorGas,
+        eip7702_gas_refund: i64,
+        mut result: FrameResult,
+    ) -> EVMResult<DB::Error> {
+        instrument_span!(DEBUG, "post_execution", gas_spent = result.gas().spent());
         let ctx = &mut self.context;
 
         // handle output of call/create calls.
@@ -389,8 +968,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         // spend at least a gas_floor amount of gas.
         if result.gas().spent_sub_refunded() < gas.floor_gas {
             result.gas_mut().set_spent(gas.floor_gas);
-            // clear refund
-            result.gas_mut().set_refund(0);
+            // no refund is applied, the accumulated refund is kept.
+            result.gas_mut().set_final_refund_with_quotient(0);
         }
 
         // Reimburse the caller
@@ -407,11 +986,25 @@ mod tests {
 
     use super::*;
     use crate::{
-        db::BenchmarkDB,
-        interpreter::opcode::{PUSH1, SSTORE},
+        db::{BenchmarkDB, InMemoryDB},
+        interpreter::{
+            gas,
+            opcode::{
+                ADD, ADDRESS, BALANCE, CALL, CALLER, CALLVALUE, COINBASE, CREATE, CREATE2,
+                DELEGATECALL, DIFFICULTY, EOFCREATE, EXTCALL, EXTCODESIZE, EXTDELEGATECALL, GAS,
+                INVALID, KECCAK256, MLOAD, MSTORE, MSTORE8, POP, PUSH0, PUSH1, PUSH2, PUSH20,
+                PUSH3, PUSH32, PUSH4, RETURN, RETURNCONTRACT, SLOAD, SSTORE, STATICCALL, STOP,
+                TLOAD, TSTORE,
+            },
+        },
         primitives::{
-            address, Authorization, Bytecode, RecoveredAuthority, RecoveredAuthorization, U256,
+            address, create2_address, create_address,
+            eof::{EofBody, TypesSection},
+            keccak256, AccountInfo, Address, Authorization, Bytecode, Bytes, Env, EvmState,
+            HaltContext, HaltReason, InvalidHeader, InvalidTransaction, Output, RecoveredAuthority,
+            RecoveredAuthorization, B256, I256, MAX_CODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
         },
+        JournaledState,
     };
 
     #[test]
@@ -452,4 +1045,2838 @@ mod tests {
             U256::from(1)
         );
     }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn handler_metrics_time_stages_and_frames() {
+        use crate::HandlerMetrics;
+        use core::time::Duration;
+
+        let caller = address!("0000000000000000000000000000000000000001");
+        let outer = address!("00000000000000000000000000000000000000c0");
+        let inner = address!("00000000000000000000000000000000000000c1");
+        let mut db = funded_db(caller);
+        let mut code = call_code(CALL, inner, 100_000, Some(0));
+        code.push(STOP);
+        insert_code(&mut db, outer, code, 0);
+        insert_code(&mut db, inner, vec![PUSH1, 1, PUSH1, 1, SSTORE, STOP], 0);
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(outer);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        for nonce in 0..3 {
+            evm.tx_mut().nonce = Some(nonce);
+            assert!(evm.transact_commit().unwrap().is_success());
+        }
+        // A transaction failing validation is not recorded.
+        evm.tx_mut().nonce = Some(0);
+        assert!(evm.transact_commit().is_err());
+
+        let metrics = evm.take_metrics();
+        for (name, timing) in metrics.stages() {
+            assert_eq!(timing.count, 3, "{name}");
+            assert!(timing.total > Duration::ZERO, "{name}");
+        }
+        assert_eq!(metrics.frames.len(), 2);
+        for timing in &metrics.frames {
+            assert_eq!(timing.count, 3);
+            assert!(timing.total > Duration::ZERO);
+        }
+        assert_eq!(*evm.metrics(), HandlerMetrics::default());
+    }
+
+    #[test]
+    fn fee_summary_reconciles_balances() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let target = address!("0000000000000000000000000000000000000002");
+        let coinbase = address!("00000000000000000000000000000000000000c0");
+        let initial_balance = U256::from(1_000_000_000_000u64);
+        let mut blob_hash = B256::ZERO;
+        blob_hash[0] = VERSIONED_HASH_VERSION_KZG;
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: initial_balance,
+                ..Default::default()
+            },
+        );
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_block_env(|block| {
+                block.coinbase = coinbase;
+                block.basefee = U256::from(7);
+                block.set_blob_excess_gas_and_price(0, false);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(target);
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(10);
+                tx.gas_priority_fee = Some(U256::from(2));
+                tx.blob_hashes = vec![blob_hash];
+                tx.max_fee_per_blob_gas = Some(U256::from(1));
+            })
+            .build();
+
+        let ResultAndState { state, .. } = evm.transact().unwrap();
+        let summary = *evm.fee_summary().unwrap();
+
+        let gas_used = U256::from(21_000);
+        assert_eq!(summary.effective_gas_price, U256::from(9));
+        assert_eq!(summary.tip_per_gas, U256::from(2));
+        assert_eq!(summary.burned, U256::from(7) * gas_used);
+        assert_eq!(summary.paid_to_coinbase, U256::from(2) * gas_used);
+        assert_eq!(summary.blob_fee_burned, U256::from(131_072));
+        assert_eq!(summary.blob_gas_price, Some(1));
+
+        let caller_paid = initial_balance - state[&caller].info.balance;
+        assert_eq!(
+            caller_paid,
+            summary.burned + summary.paid_to_coinbase + summary.blob_fee_burned
+        );
+        assert_eq!(state[&coinbase].info.balance, summary.paid_to_coinbase);
+    }
+
+    #[test]
+    fn fee_summary_gas_prices() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let summary = |gas_price: u64, gas_priority_fee: Option<u64>| {
+            let mut evm = Evm::builder()
+                .with_db(funded_db(caller))
+                .with_spec_id(SpecId::CANCUN)
+                .modify_block_env(|block| block.basefee = U256::from(7))
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(Address::with_last_byte(2));
+                    tx.gas_limit = 21_000;
+                    tx.gas_price = U256::from(gas_price);
+                    tx.gas_priority_fee = gas_priority_fee.map(U256::from);
+                })
+                .build();
+            evm.transact().unwrap();
+            *evm.fee_summary().unwrap()
+        };
+
+        // The priority fee is capped by the max fee.
+        let capped = summary(10, Some(5));
+        assert_eq!(capped.effective_gas_price, U256::from(10));
+        assert_eq!(capped.tip_per_gas, U256::from(3));
+        assert_eq!(capped.blob_gas_price, None);
+
+        let uncapped = summary(10, Some(1));
+        assert_eq!(uncapped.effective_gas_price, U256::from(8));
+
+        let legacy = summary(9, None);
+        assert_eq!(legacy.effective_gas_price, U256::from(9));
+        assert_eq!(legacy.blob_gas_price, None);
+    }
+
+    /// Transacts a call clearing the first `slots` storage slots of a contract and returns the
+    /// gas used and the fee summary.
+    fn transact_sstore_clears(slots: u8) -> (u64, FeeSummary) {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let target = address!("0000000000000000000000000000000000001000");
+        let mut db = funded_db(caller);
+        let mut code = Vec::new();
+        for slot in 0..slots {
+            code.extend([PUSH1, 0, PUSH1, slot, SSTORE]);
+        }
+        code.push(STOP);
+        insert_code(&mut db, target, code, 0);
+        for slot in 0..slots {
+            db.insert_account_storage(target, U256::from(slot), U256::from(1))
+                .unwrap();
+        }
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(target);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        let ResultAndState { result, .. } = transact_checked(&mut evm).unwrap();
+        assert!(result.is_success());
+        (result.gas_used(), *evm.fee_summary().unwrap())
+    }
+
+    #[test]
+    fn fee_summary_sstore_clear_refund_under_cap() {
+        let (gas_used, summary) = transact_sstore_clears(1);
+        // 21_000 + 2 * PUSH1 + cold SSTORE reset.
+        assert_eq!(summary.gas_used_pre_refund, 26_006);
+        assert_eq!(summary.refund_accumulated, 4_800);
+        assert_eq!(summary.refund_applied, summary.refund_accumulated);
+        assert_eq!(summary.gas_used_final, 26_006 - 4_800);
+        assert_eq!(summary.gas_used_final, gas_used);
+    }
+
+    #[test]
+    fn fee_summary_sstore_clear_refund_over_cap() {
+        let (gas_used, summary) = transact_sstore_clears(2);
+        assert_eq!(summary.gas_used_pre_refund, 31_012);
+        assert_eq!(summary.refund_accumulated, 2 * 4_800);
+        // Capped to a fifth of the gas spent.
+        assert_eq!(summary.refund_applied, 31_012 / 5);
+        assert_eq!(summary.gas_used_final, 31_012 - 31_012 / 5);
+        assert_eq!(summary.gas_used_final, gas_used);
+    }
+
+    /// Transacts a transfer paying a tip of 2 per gas with the given block coinbase and fee
+    /// recipient override.
+    fn transact_tip(
+        db: InMemoryDB,
+        caller: Address,
+        coinbase: Address,
+        fee_recipient_override: Option<Address>,
+    ) -> (EvmState, FeeSummary) {
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.fee_recipient_override = fee_recipient_override)
+            .modify_block_env(|block| {
+                block.coinbase = coinbase;
+                block.basefee = U256::from(7);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000002"));
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(10);
+                tx.gas_priority_fee = Some(U256::from(2));
+            })
+            .build();
+        let ResultAndState { state, .. } = transact_checked(&mut evm).unwrap();
+        (state, *evm.fee_summary().unwrap())
+    }
+
+    /// Transacts with `evm`, asserting that the finalized state satisfies the invariants of the
+    /// status flags.
+    fn transact_checked<EXT, DB: Database>(evm: &mut Evm<'_, EXT, DB>) -> EVMResult<DB::Error> {
+        let output = evm.transact();
+        if let Ok(ResultAndState { state, .. }) = &output {
+            crate::test_utils::assert_state_invariants(state, evm.spec_id());
+        }
+        output
+    }
+
+    fn funded_db(caller: Address) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    /// Every spec, from Frontier to the latest.
+    fn all_specs() -> impl Iterator<Item = SpecId> {
+        (0..=u8::MAX).filter_map(SpecId::n)
+    }
+
+    /// Legacy code calling `target` with `opcode`, forwarding `gas` and `value` if any.
+    fn call_code(opcode: u8, target: Address, gas: u32, value: Option<u8>) -> Vec<u8> {
+        // out len, out offset, in len, in offset.
+        let mut code = vec![PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0];
+        if let Some(value) = value {
+            code.extend([PUSH1, value]);
+        }
+        code.push(PUSH20);
+        code.extend_from_slice(target.as_slice());
+        code.push(PUSH3);
+        code.extend_from_slice(&gas.to_be_bytes()[1..]);
+        code.push(opcode);
+        code
+    }
+
+    fn insert_code(db: &mut InMemoryDB, address: Address, code: Vec<u8>, balance: u64) {
+        let code = Bytecode::new_legacy(code.into());
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                balance: U256::from(balance),
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn transact_call(spec: SpecId, db: InMemoryDB, caller: Address, to: Address) -> ResultAndState {
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(spec)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(to);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        transact_checked(&mut evm).unwrap()
+    }
+
+    #[test]
+    fn delegatecall_chain_context() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let a = address!("00000000000000000000000000000000000000a0");
+        let b = address!("00000000000000000000000000000000000000b0");
+        let c = address!("00000000000000000000000000000000000000c0");
+        let d = address!("00000000000000000000000000000000000000d0");
+
+        for spec in all_specs().filter(|spec| spec.is_enabled_in(SpecId::HOMESTEAD)) {
+            let mut db = funded_db(caller);
+            // A calls B with a value of 5, B delegates to C which delegates to D.
+            insert_code(&mut db, a, call_code(CALL, b, 600_000, Some(5)), 5);
+            insert_code(&mut db, b, call_code(DELEGATECALL, c, 500_000, None), 0);
+            insert_code(&mut db, c, call_code(DELEGATECALL, d, 400_000, None), 0);
+            // SSTORE(0, CALLER), SSTORE(1, ADDRESS), SSTORE(2, CALLVALUE)
+            insert_code(
+                &mut db,
+                d,
+                vec![
+                    CALLER, PUSH1, 0, SSTORE, ADDRESS, PUSH1, 1, SSTORE, CALLVALUE, PUSH1, 2,
+                    SSTORE,
+                ],
+                0,
+            );
+
+            let ResultAndState { result, state } = transact_call(spec, db, caller, a);
+            assert!(result.is_success(), "{spec:?}");
+            // D runs in the context of the call from A to B.
+            let storage = &state[&b].storage;
+            let slot = |i: u64| storage[&U256::from(i)].present_value;
+            assert_eq!(slot(0), U256::from_be_bytes(a.into_word().0), "{spec:?}");
+            assert_eq!(slot(1), U256::from_be_bytes(b.into_word().0), "{spec:?}");
+            assert_eq!(slot(2), U256::from(5), "{spec:?}");
+            assert!(state.get(&c).is_none_or(|c| c.storage.is_empty()));
+            assert!(state.get(&d).is_none_or(|d| d.storage.is_empty()));
+            assert_eq!(state[&b].info.balance, U256::from(5), "{spec:?}");
+        }
+    }
+
+    #[test]
+    fn call_new_account_cost() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("00000000000000000000000000000000000000a0");
+        let existing = address!("00000000000000000000000000000000000000e0");
+        let empty = address!("00000000000000000000000000000000000000e1");
+
+        for spec in all_specs() {
+            for value in [0, 1] {
+                let gas_used = |target: Address| {
+                    let mut db = funded_db(caller);
+                    insert_code(
+                        &mut db,
+                        contract,
+                        call_code(CALL, target, 0, Some(value)),
+                        1,
+                    );
+                    db.insert_account_info(
+                        existing,
+                        AccountInfo {
+                            balance: U256::from(1),
+                            ..Default::default()
+                        },
+                    );
+                    let ResultAndState { result, .. } = transact_call(spec, db, caller, contract);
+                    assert!(result.is_success(), "{spec:?}");
+                    result.gas_used()
+                };
+                // EIP-161: only value transfers create accounts from Spurious Dragon, before
+                // any call to a non-existing account did.
+                let creates_account = value != 0 || !spec.is_enabled_in(SpecId::SPURIOUS_DRAGON);
+                let new_account_cost = if creates_account { gas::NEWACCOUNT } else { 0 };
+                assert_eq!(
+                    gas_used(empty) - gas_used(existing),
+                    new_account_cost,
+                    "{spec:?} value {value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn coinbase_first_touch_cost() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("00000000000000000000000000000000000000a0");
+
+        // Cost of the first BALANCE of the coinbase, warm from Shanghai (EIP-3651).
+        let cases = [
+            (SpecId::FRONTIER, 20),
+            (SpecId::TANGERINE, 400),
+            (SpecId::ISTANBUL, 700),
+            (SpecId::BERLIN, gas::COLD_ACCOUNT_ACCESS_COST),
+            (SpecId::MERGE, gas::COLD_ACCOUNT_ACCESS_COST),
+            (SpecId::SHANGHAI, gas::WARM_STORAGE_READ_COST),
+            (SpecId::CANCUN, gas::WARM_STORAGE_READ_COST),
+            (SpecId::PRAGUE, gas::WARM_STORAGE_READ_COST),
+        ];
+        for (spec, cost) in cases {
+            let gas_used = |code: Vec<u8>| {
+                let mut db = funded_db(caller);
+                insert_code(&mut db, contract, code, 0);
+                let ResultAndState { result, .. } = transact_call(spec, db, caller, contract);
+                assert!(result.is_success(), "{spec:?}");
+                result.gas_used()
+            };
+            let balance = gas_used(vec![COINBASE, BALANCE, POP, STOP]);
+            let no_balance = gas_used(vec![COINBASE, POP, STOP]);
+            assert_eq!(balance - no_balance, cost, "{spec:?}");
+
+            let mut env = Env::default();
+            env.tx.caller = caller;
+            env.tx.transact_to = TxKind::Call(contract);
+            assert_eq!(
+                crate::handler::mainnet::warm_addresses_for_tx(&env, spec)
+                    .any(|address| address == env.block.coinbase),
+                spec.is_enabled_in(SpecId::SHANGHAI),
+                "{spec:?}"
+            );
+        }
+    }
+
+    /// Signatures by the key `0x4646..46` of `0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f`: the
+    /// EIP-155 example transaction, the same transaction without EIP-155 and as an EIP-1559
+    /// transaction.
+    #[cfg(feature = "secp256k1-recover")]
+    fn signed_txs() -> [crate::primitives::TxSignature; 3] {
+        use crate::primitives::{b256, TxSignature};
+        let word = |word: B256| U256::from_be_bytes(word.0);
+        [
+            TxSignature {
+                r: word(b256!(
+                    "28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276"
+                )),
+                s: word(b256!(
+                    "67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+                )),
+                v: 37,
+                signing_hash: b256!(
+                    "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+                ),
+            },
+            TxSignature {
+                r: word(b256!(
+                    "f973a0b87062c389d125d8199e803b832b6ac6bf7867a4f6cd87506060fc4c58"
+                )),
+                s: word(b256!(
+                    "53271217f374b54b0fce070a1589ffcaf2bf49da14eca2cae8dcf11c301bf420"
+                )),
+                v: 28,
+                signing_hash: b256!(
+                    "f9e36c28c8cb35adba138005c02ab7aa7fbcd891f3139cb2eeed052a51cd2713"
+                ),
+            },
+            TxSignature {
+                r: word(b256!(
+                    "0fd109be4a9e07d64737926c7e2d8d7afa63ec5991d126b8abe7919d3c62a7be"
+                )),
+                s: word(b256!(
+                    "11b28c8daacf9738009879135c6e68b55d2b1ed0d15626a052206eae98080c06"
+                )),
+                v: 0,
+                signing_hash: b256!(
+                    "577f072b4be21dbe73cdd90f32675d67d2fdfefdecfbc579f52025caf096400a"
+                ),
+            },
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1-recover")]
+    fn recover_caller_from_signature() {
+        let sender = address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        for signature in signed_txs() {
+            let mut evm = Evm::builder()
+                .with_db(funded_db(sender))
+                .with_spec_id(SpecId::CANCUN)
+                .build();
+            let mut tx = transfer(Address::ZERO, 0, 1);
+            tx.signature = Some(signature);
+
+            let preverified = evm.preverify_transaction(&tx).unwrap();
+            assert_eq!(
+                preverified.recovered_caller(),
+                Some(sender),
+                "{signature:?}"
+            );
+            assert_eq!(preverified.tx().caller, sender);
+
+            let ResultAndState { result, state } = evm.transact_preverified(preverified).unwrap();
+            assert!(result.is_success());
+            assert_eq!(state[&sender].info.nonce, 1);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1-recover")]
+    fn reject_malleable_signature() {
+        use crate::primitives::env::signature::SECP256K1N;
+
+        let sender = address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        let transact = |spec: SpecId, chain_id: u64, signature| {
+            let mut tx = transfer(Address::ZERO, 0, 1);
+            tx.signature = Some(signature);
+            Evm::builder()
+                .with_db(funded_db(sender))
+                .with_spec_id(spec)
+                .modify_cfg_env(|cfg| cfg.chain_id = chain_id)
+                .with_tx_env(tx)
+                .build()
+                .transact()
+                .map(|output| output.state[&sender].info.nonce)
+        };
+
+        // The high `s` twin of a pre EIP-155 signature recovers the same caller.
+        let [_, mut signature, _] = signed_txs();
+        signature.s = SECP256K1N - signature.s;
+        signature.v = 27;
+        assert_eq!(transact(SpecId::FRONTIER, 1, signature), Ok(1));
+        assert_eq!(
+            transact(SpecId::HOMESTEAD, 1, signature),
+            Err(EVMError::Transaction(InvalidTransaction::SignatureHighS))
+        );
+
+        // The EIP-155 signature is only valid on its chain.
+        let [signature, ..] = signed_txs();
+        assert_eq!(transact(SpecId::CANCUN, 1, signature), Ok(1));
+        assert_eq!(
+            transact(SpecId::CANCUN, 5, signature),
+            Err(EVMError::Transaction(InvalidTransaction::InvalidChainId))
+        );
+    }
+
+    /// Stores GAS before and after clearing slot 0 and returns both.
+    const GAS_AROUND_SSTORE_CLEAR: [u8; 18] = [
+        GAS, PUSH1, 0, MSTORE, PUSH1, 0, PUSH1, 0, SSTORE, GAS, PUSH1, 0x20, MSTORE, PUSH1, 0x40,
+        PUSH1, 0, RETURN,
+    ];
+
+    #[test]
+    fn gas_opcode_ignores_refunds() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("00000000000000000000000000000000000000a0");
+        // 100k gas limit minus the intrinsic gas and the first GAS.
+        let before = 100_000 - 21_000 - gas::BASE;
+        // PUSH1, MSTORE with one word of memory, two PUSH1, SSTORE resetting a slot and GAS. The
+        // SSTORE costs 5000 on every spec, cold from Berlin.
+        let after = before - (3 + 6 + 3 + 3 + 5_000 + gas::BASE);
+        // Gas spent before refunds.
+        let spent: u64 = 21_000 + 5_034;
+
+        for (spec, refund, quotient) in [
+            (SpecId::FRONTIER, 15_000, 2),
+            (SpecId::HOMESTEAD, 15_000, 2),
+            (SpecId::BYZANTIUM, 15_000, 2),
+            (SpecId::CONSTANTINOPLE, 15_000, 2),
+            (SpecId::PETERSBURG, 15_000, 2),
+            (SpecId::ISTANBUL, 15_000, 2),
+            (SpecId::BERLIN, 15_000, 2),
+            (SpecId::LONDON, 4_800, 5),
+            (SpecId::SHANGHAI, 4_800, 5),
+            (SpecId::CANCUN, 4_800, 5),
+            (SpecId::PRAGUE, 4_800, 5),
+        ] {
+            for refund_quotient in [None, Some(1), Some(0)] {
+                let mut db = funded_db(caller);
+                insert_code(&mut db, contract, GAS_AROUND_SSTORE_CLEAR.to_vec(), 0);
+                db.insert_account_storage(contract, U256::ZERO, U256::from(1))
+                    .unwrap();
+                let result = Evm::builder()
+                    .with_db(db)
+                    .with_spec_id(spec)
+                    .modify_cfg_env(|cfg| cfg.refund_quotient = refund_quotient)
+                    .modify_tx_env(|tx| {
+                        tx.caller = caller;
+                        tx.transact_to = TxKind::Call(contract);
+                        tx.gas_limit = 100_000;
+                    })
+                    .build()
+                    .transact()
+                    .unwrap()
+                    .result;
+
+                let output = result.output().unwrap();
+                let gas_at = |offset: usize| U256::from_be_slice(&output[offset..offset + 32]);
+                assert_eq!(gas_at(0), U256::from(before), "{spec:?}");
+                assert_eq!(gas_at(32), U256::from(after), "{spec:?}");
+
+                let quotient = refund_quotient.unwrap_or(quotient);
+                let refunded = spent.checked_div(quotient).map_or(0, |max| max.min(refund));
+                assert_eq!(
+                    result.gas_used(),
+                    spent - refunded,
+                    "{spec:?} {refund_quotient:?}"
+                );
+            }
+        }
+    }
+
+    fn transfer(caller: Address, nonce: u64, value: u64) -> TxEnv {
+        TxEnv {
+            caller,
+            transact_to: TxKind::Call(address!("0000000000000000000000000000000000001000")),
+            value: U256::from(value),
+            gas_limit: 21_000,
+            gas_price: U256::from(1),
+            nonce: Some(nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn preverified_tx_executes_like_transact() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let mut evm = Evm::builder().with_db(funded_db(caller)).build();
+
+        let tx = transfer(caller, 0, 1_000);
+        let preverified = evm.preverify_transaction(&tx).unwrap();
+        assert_eq!(preverified.tx(), &tx);
+        assert_eq!(preverified.initial_gas(), 21_000);
+        assert_eq!(preverified.caller_nonce(), 0);
+        assert_eq!(preverified.caller_balance(), U256::from(1_000_000_000u64));
+
+        let result = evm.transact_preverified(preverified).unwrap();
+        *evm.tx_mut() = tx;
+        assert_eq!(result, evm.transact().unwrap());
+
+        assert!(matches!(
+            evm.preverify_transaction(&transfer(caller, 0, 1_000_000_000)),
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+    }
+
+    /// Transacts with [`Evm::prepare_transaction`] and [`Evm::finish_transaction`], running the
+    /// frames in between.
+    pub(crate) fn transact_prepared<EXT, DB: Database>(
+        evm: &mut Evm<'_, EXT, DB>,
+    ) -> EVMResult<DB::Error> {
+        let prepared = evm.prepare_transaction()?;
+        let result =
+            evm.create_first_frame(prepared.inputs().clone())
+                .and_then(|first_frame_or_result| match first_frame_or_result {
+                    FrameOrResult::Frame(first_frame) => evm.run_the_loop(first_frame),
+                    FrameOrResult::Result(result) => Ok(result),
+                });
+        evm.finish_transaction(prepared, result)
+    }
+
+    #[test]
+    fn prepared_tx_executes_like_transact() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("0000000000000000000000000000000000000100");
+        let mut db = funded_db(caller);
+        insert_code(&mut db, contract, vec![PUSH1, 0x01, PUSH1, 0x00, SSTORE], 0);
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(contract);
+                tx.data = Bytes::from_static(&[0; 4]);
+                tx.gas_limit = 100_000;
+                tx.gas_price = U256::from(1);
+            })
+            .build();
+
+        let prepared = evm.prepare_transaction().unwrap();
+        assert_eq!(prepared.initial_gas(), 21_016);
+        assert_eq!(prepared.eip7702_gas_refund(), 0);
+        let FirstFrameInputs::Call(inputs) = prepared.inputs() else {
+            panic!("expected call inputs");
+        };
+        assert_eq!(inputs.target_address, contract);
+        assert_eq!(inputs.gas_limit, 100_000 - 21_016);
+        // The caller is charged for the gas limit before the first frame is created.
+        let state = &evm.context.evm.inner.journaled_state.state;
+        assert_eq!(
+            state[&caller].info.balance,
+            U256::from(1_000_000_000u64 - 100_000)
+        );
+
+        let first_frame = match evm.create_first_frame(prepared.inputs().clone()) {
+            Ok(FrameOrResult::Frame(first_frame)) => first_frame,
+            _ => panic!("expected a frame"),
+        };
+        let result = evm.run_the_loop(first_frame);
+        let output = evm.finish_transaction(prepared, result).unwrap();
+        assert!(evm.context.evm.inner.journaled_state.state.is_empty());
+        assert_eq!(output, evm.transact().unwrap());
+
+        evm.tx_mut().transact_to = TxKind::Create;
+        let output = transact_prepared(&mut evm).unwrap();
+        assert_eq!(output, evm.transact().unwrap());
+
+        evm.tx_mut().nonce = Some(5);
+        assert!(matches!(
+            transact_prepared(&mut evm),
+            Err(EVMError::Transaction(
+                InvalidTransaction::NonceTooHigh { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn preverified_tx_rechecks_caller() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let mut evm = Evm::builder().with_db(funded_db(caller)).build();
+
+        // Both transactions use nonce 0, executing one makes the other stale.
+        let first = evm
+            .preverify_transaction(&transfer(caller, 0, 1_000))
+            .unwrap();
+        let second = evm
+            .preverify_transaction(&transfer(caller, 0, 2_000))
+            .unwrap();
+        let ResultAndState { state, .. } = evm.transact_preverified(first).unwrap();
+        evm.db_mut().commit(state);
+        assert!(matches!(
+            evm.transact_preverified(second),
+            Err(EVMError::Transaction(InvalidTransaction::NonceTooLow {
+                tx: 0,
+                state: 1
+            }))
+        ));
+
+        // The balance of the caller dropped below the cost of the transaction.
+        let spend = evm
+            .preverify_transaction(&transfer(caller, 1, 500_000))
+            .unwrap();
+        let retry = spend.clone();
+        evm.db_mut().insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 1,
+                balance: U256::from(100_000),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            evm.transact_preverified(spend),
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+
+        // A changed state that still covers the transaction is valid.
+        evm.db_mut().insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 1,
+                balance: U256::from(2_000_000),
+                ..Default::default()
+            },
+        );
+        let result = evm.transact_preverified(retry).unwrap();
+        assert!(result.result.is_success());
+    }
+
+    /// Database recording the state every commit receives.
+    #[derive(Default)]
+    struct CommitRecorder {
+        db: InMemoryDB,
+        commits: Vec<EvmState>,
+    }
+
+    impl Database for CommitRecorder {
+        type Error = core::convert::Infallible;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.db.basic(address)
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.db.code_by_hash(code_hash)
+        }
+
+        fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.db.storage(address, index)
+        }
+
+        fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash(number)
+        }
+    }
+
+    impl DatabaseCommit for CommitRecorder {
+        fn commit(&mut self, changes: EvmState) {
+            self.commits.push(changes.clone());
+            self.db.commit(changes);
+        }
+    }
+
+    /// Commits a transaction from `caller` to `to` and returns the state the database received.
+    fn committed_state(db: InMemoryDB, caller: Address, to: TxKind, data: Vec<u8>) -> EvmState {
+        let mut evm = Evm::builder()
+            .with_db(CommitRecorder {
+                db,
+                ..Default::default()
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = to;
+                tx.data = data.into();
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        assert!(evm.transact_commit().unwrap().is_success());
+        let mut commits = core::mem::take(&mut evm.db_mut().commits);
+        assert_eq!(commits.len(), 1);
+        commits.pop().unwrap()
+    }
+
+    #[test]
+    fn committed_storage_original_values() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("00000000000000000000000000000000000000c0");
+        let slot = U256::from(1);
+        let sstores = |values: &[u8]| {
+            let mut code: Vec<u8> = values
+                .iter()
+                .flat_map(|value| [PUSH1, *value, PUSH1, 1, SSTORE])
+                .collect();
+            code.push(STOP);
+            code
+        };
+        let committed_slot = |values: &[u8]| {
+            let mut db = funded_db(caller);
+            insert_code(&mut db, contract, sstores(values), 0);
+            db.insert_account_storage(contract, slot, U256::from(7))
+                .unwrap();
+            let state = committed_state(db, caller, TxKind::Call(contract), Vec::new());
+            state[&contract].storage[&slot].clone()
+        };
+
+        // Writing a slot and restoring it reports it unchanged.
+        let restored = committed_slot(&[9, 7]);
+        assert_eq!(restored.original_value(), U256::from(7));
+        assert_eq!(restored.present_value(), U256::from(7));
+        assert!(!restored.is_changed());
+
+        // Several writes keep the value from the start of the transaction.
+        let written = committed_slot(&[8, 0, 9]);
+        assert_eq!(written.original_value(), U256::from(7));
+        assert_eq!(written.present_value(), U256::from(9));
+        assert!(written.is_changed());
+    }
+
+    #[test]
+    fn committed_storage_of_created_account() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let created = create_address(caller, 0);
+        // SSTORE(1, 5), SSTORE(2, 6), SSTORE(2, 0)
+        let initcode = vec![
+            PUSH1, 5, PUSH1, 1, SSTORE, PUSH1, 6, PUSH1, 2, SSTORE, PUSH1, 0, PUSH1, 2, SSTORE,
+            STOP,
+        ];
+
+        let state = committed_state(funded_db(caller), caller, TxKind::Create, initcode);
+        let account = &state[&created];
+        assert!(account.is_created());
+        let written = &account.storage[&U256::from(1)];
+        assert_eq!(written.original_value(), U256::ZERO);
+        assert_eq!(written.present_value(), U256::from(5));
+        assert!(written.is_changed());
+        let restored = &account.storage[&U256::from(2)];
+        assert_eq!(restored.original_value(), U256::ZERO);
+        assert!(!restored.is_changed());
+        assert_eq!(
+            account
+                .changed_storage_slots()
+                .map(|(key, _)| *key)
+                .collect::<Vec<_>>(),
+            [U256::from(1)]
+        );
+    }
+
+    #[test]
+    fn transient_storage_cleared_between_transactions() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("0000000000000000000000000000000000000c0d");
+        // SSTORE(0, TLOAD(0)), TSTORE(0, 1), SSTORE(1, TLOAD(0))
+        let code = Bytecode::new_legacy(
+            [
+                PUSH0, TLOAD, PUSH0, SSTORE, PUSH1, 0x01, PUSH0, TSTORE, PUSH0, TLOAD, PUSH1, 0x01,
+                SSTORE, STOP,
+            ]
+            .into(),
+        );
+        let mut db = funded_db(caller);
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        for nonce in 0..2 {
+            evm.tx_mut().nonce = Some(nonce);
+            let ResultAndState { result, state } = evm.transact().unwrap();
+            assert!(result.is_success());
+            let storage = &state[&contract].storage;
+            assert_eq!(storage[&U256::ZERO].present_value, U256::ZERO);
+            assert_eq!(storage[&U256::from(1)].present_value, U256::from(1));
+            assert_eq!(evm.context.evm.journaled_state.transient_storage_len(), 0);
+            evm.context.evm.db.commit(state);
+        }
+    }
+
+    #[test]
+    fn fee_recipient_override() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let coinbase = address!("00000000000000000000000000000000000000c0");
+        let recipient = address!("00000000000000000000000000000000000000fe");
+        let tip = U256::from(2 * 21_000);
+
+        let (state, summary) = transact_tip(funded_db(caller), caller, coinbase, None);
+        assert_eq!(summary.paid_to_coinbase, tip);
+        assert_eq!(state[&coinbase].info.balance, tip);
+        assert!(!state.contains_key(&recipient));
+
+        let (state, summary) = transact_tip(funded_db(caller), caller, coinbase, Some(recipient));
+        assert_eq!(summary.paid_to_coinbase, tip);
+        assert_eq!(state[&recipient].info.balance, tip);
+        // The coinbase is warm but not touched.
+        assert!(state
+            .get(&coinbase)
+            .is_none_or(|account| !account.is_touched()));
+    }
+
+    #[test]
+    fn fee_recipient_is_caller() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let (state, summary) = transact_tip(funded_db(caller), caller, caller, None);
+        // The caller only pays the burned base fee.
+        assert_eq!(
+            state[&caller].info.balance,
+            U256::from(1_000_000_000u64) - summary.burned
+        );
+    }
+
+    #[test]
+    fn fee_recipient_is_precompile_or_delegated() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let tip = U256::from(2 * 21_000);
+
+        let identity = address!("0000000000000000000000000000000000000004");
+        let (state, _) = transact_tip(funded_db(caller), caller, identity, None);
+        assert_eq!(state[&identity].info.balance, tip);
+
+        // The delegated coinbase is paid, its code is kept and its delegate is not loaded.
+        let coinbase = address!("00000000000000000000000000000000000000c0");
+        let delegate = address!("00000000000000000000000000000000000000de");
+        let mut db = funded_db(caller);
+        db.insert_account_info(
+            coinbase,
+            AccountInfo {
+                code: Some(Bytecode::new_eip7702(delegate)),
+                code_hash: Bytecode::new_eip7702(delegate).hash_slow(),
+                ..Default::default()
+            },
+        );
+        let (state, _) = transact_tip(db, caller, coinbase, None);
+        assert_eq!(state[&coinbase].info.balance, tip);
+        assert_eq!(
+            state[&coinbase].info.code_hash,
+            Bytecode::new_eip7702(delegate).hash_slow()
+        );
+        assert!(!state.contains_key(&delegate));
+    }
+
+    /// Database counting the `code_by_hash` calls.
+    #[derive(Default)]
+    struct CodeByHashCounter {
+        db: InMemoryDB,
+        code_by_hash_calls: usize,
+    }
+
+    impl Database for CodeByHashCounter {
+        type Error = core::convert::Infallible;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.db.basic(address)
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.code_by_hash_calls += 1;
+            self.db.code_by_hash(code_hash)
+        }
+
+        fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.db.storage(address, index)
+        }
+
+        fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+            self.db.block_hash(number)
+        }
+    }
+
+    #[test]
+    fn pre_populated_code_skips_code_by_hash() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let authority = address!("0000000000000000000000000000000000000100");
+        let delegate = address!("00000000000000000000000000000000000000de");
+        let callee = address!("00000000000000000000000000000000000000c0");
+        let callee_code = Bytecode::new_legacy([PUSH1, 1, PUSH1, 1, SSTORE, STOP].into());
+
+        // The delegate stores the code size of the callee and calls it.
+        let mut delegate_code = vec![PUSH20];
+        delegate_code.extend_from_slice(callee.as_slice());
+        delegate_code.extend([EXTCODESIZE, PUSH1, 0, SSTORE]);
+        delegate_code.extend(call_code(CALL, callee, 50_000, Some(0)));
+        delegate_code.push(STOP);
+
+        let code_by_hash_calls = |db: InMemoryDB| {
+            let mut evm = Evm::builder()
+                .with_spec_id(SpecId::PRAGUE)
+                .with_db(CodeByHashCounter {
+                    db,
+                    ..Default::default()
+                })
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(authority);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build();
+            let result = evm.transact().unwrap();
+            assert!(result.result.is_success());
+            assert_eq!(
+                result.state[&authority].storage[&U256::ZERO].present_value(),
+                U256::from(callee_code.len())
+            );
+            assert_eq!(
+                result.state[&callee].storage[&U256::from(1)].present_value(),
+                U256::from(1)
+            );
+            evm.db().code_by_hash_calls
+        };
+
+        let mut db = funded_db(caller);
+        db.insert_account_with_code(
+            authority,
+            AccountInfo::default(),
+            Bytecode::new_eip7702(delegate),
+        );
+        db.insert_account_with_code(
+            delegate,
+            AccountInfo::default(),
+            Bytecode::new_legacy(delegate_code.into()),
+        );
+        db.insert_account_with_code(callee, AccountInfo::default(), callee_code.clone());
+        assert_eq!(code_by_hash_calls(db.clone()), 0);
+
+        // Without the code in the account info, it is fetched by hash once.
+        let info = db.accounts[&callee].info.copy_without_code();
+        db.insert_account_info(callee, info);
+        assert_eq!(code_by_hash_calls(db), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "optional_no_base_fee")]
+    fn fee_summary_reports_capped_gas_price() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let target = address!("0000000000000000000000000000000000001000");
+        let transact = |balance: u64| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(
+                caller,
+                AccountInfo {
+                    balance: U256::from(balance),
+                    ..Default::default()
+                },
+            );
+            let mut evm = Evm::builder()
+                .with_db(db)
+                .modify_cfg_env(|cfg| {
+                    cfg.base_fee_check_mode = crate::primitives::BaseFeeCheckMode::Cap
+                })
+                .modify_block_env(|block| block.basefee = U256::from(10))
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(target);
+                    tx.gas_limit = 21_000;
+                    tx.gas_price = U256::from(5);
+                    tx.gas_priority_fee = Some(U256::from(2));
+                })
+                .build();
+            let ResultAndState { state, .. } = evm.transact().unwrap();
+            (*evm.fee_summary().unwrap(), state[&caller].info.balance)
+        };
+
+        // The signed price below the basefee is charged.
+        let (summary, balance) = transact(1_000_000);
+        assert_eq!(summary.effective_gas_price, U256::from(5));
+        assert_eq!(summary.tip_per_gas, U256::ZERO);
+        assert_eq!(summary.burned, U256::from(5 * 21_000));
+        assert_eq!(balance, U256::from(1_000_000 - 5 * 21_000));
+
+        // The price is lowered to what the caller can pay.
+        let (summary, balance) = transact(3 * 21_000 + 1);
+        assert_eq!(summary.effective_gas_price, U256::from(3));
+        assert_eq!(summary.burned, U256::from(3 * 21_000));
+        assert_eq!(balance, U256::from(1));
+    }
+
+    /// Returns an evm whose transaction calls a contract that writes memory, calls another
+    /// contract and stores the results.
+    fn suspend_evm() -> Evm<'static, (), InMemoryDB> {
+        let caller = Address::with_last_byte(1);
+        let outer = address!("0000000000000000000000000000000000001000");
+        let inner = address!("0000000000000000000000000000000000001001");
+
+        let mut outer_code = vec![PUSH1, 0x42, PUSH1, 0, MSTORE];
+        outer_code.extend([PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH20]);
+        outer_code.extend(inner.as_slice());
+        outer_code.extend([GAS, CALL, PUSH1, 1, SSTORE]);
+        outer_code.extend([PUSH1, 0, MLOAD, PUSH1, 2, SSTORE, STOP]);
+        let inner_code = vec![PUSH1, 0x2a, PUSH1, 0, SSTORE, STOP];
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000_000),
+                ..Default::default()
+            },
+        );
+        db.insert_account_info(
+            outer,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(outer_code.into())),
+        );
+        db.insert_account_info(
+            inner,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(inner_code.into())),
+        );
+
+        Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(outer);
+                tx.gas_limit = 200_000;
+            })
+            .build()
+    }
+
+    #[test]
+    fn suspend_and_resume() {
+        let expected = suspend_evm().transact().unwrap();
+        assert!(expected.result.is_success());
+
+        for depth in [1, 2] {
+            let mut evm = suspend_evm();
+            let SuspendOutcome::Suspended(suspended) = evm.suspend_at_depth(depth).unwrap() else {
+                panic!("depth {depth} not reached");
+            };
+            assert_eq!(suspended.call_stack.len(), depth);
+            assert_eq!(evm.resume(*suspended).unwrap(), expected, "depth {depth}");
+        }
+
+        // Depth is never reached.
+        let SuspendOutcome::Completed(result) = suspend_evm().suspend_at_depth(3).unwrap() else {
+            panic!("suspended");
+        };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn frame_pool_reuses_frames() {
+        use crate::FramePool;
+
+        let expected = suspend_evm().transact().unwrap();
+
+        let mut evm = suspend_evm();
+        for _ in 0..3 {
+            assert_eq!(evm.transact().unwrap(), expected);
+            // Outer and inner call frames, reused by every following transaction.
+            assert_eq!(evm.context.evm.frame_pool.len(), 2);
+        }
+
+        // Create frames are pooled separately.
+        evm.tx_mut().transact_to = TxKind::Create;
+        evm.tx_mut().data = Bytes::from_static(&[STOP]);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.context.evm.frame_pool.len(), 3);
+
+        evm.context.evm.frame_pool.clear();
+        evm.tx_mut().transact_to =
+            TxKind::Call(address!("0000000000000000000000000000000000001000"));
+        evm.tx_mut().data = Bytes::new();
+        assert_eq!(evm.transact().unwrap(), expected);
+
+        // Frames returned to a full pool are dropped.
+        evm.context.evm.frame_pool = FramePool::with_max_len(1);
+        for _ in 0..2 {
+            assert_eq!(evm.transact().unwrap(), expected);
+            assert_eq!(evm.context.evm.frame_pool.len(), 1);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde-state", feature = "serde-json"))]
+    fn suspended_execution_serde() {
+        let expected = suspend_evm().transact().unwrap();
+
+        let SuspendOutcome::Suspended(suspended) = suspend_evm().suspend_at_depth(2).unwrap()
+        else {
+            panic!("depth not reached");
+        };
+        let serialized = serde_json::to_string(&suspended).unwrap();
+        let suspended: SuspendedExecution = serde_json::from_str(&serialized).unwrap();
+
+        // Resume in a fresh evm.
+        assert_eq!(suspend_evm().resume(suspended).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "serde-json")]
+    fn execution_serializes_deterministically() {
+        use crate::db::{states::bundle_state::BundleRetention, State};
+
+        let caller = address!("0000000000000000000000000000000000000001");
+        let contract = address!("00000000000000000000000000000000000000c0");
+        // Touch enough accounts and slots that any map ordering would show up.
+        let mut code = Vec::new();
+        for slot in 1..=16 {
+            code.extend([PUSH1, slot, PUSH1, slot, SSTORE]);
+        }
+        for target in 0x10..0x18 {
+            code.extend(call_code(CALL, Address::with_last_byte(target), 0, Some(1)));
+            code.push(POP);
+        }
+
+        let serialize = || {
+            let mut db = funded_db(caller);
+            insert_code(&mut db, contract, code.clone(), 100);
+            let mut state = State::builder()
+                .with_database(db)
+                .with_bundle_update()
+                .build();
+            let result = Evm::builder()
+                .with_db(&mut state)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(contract);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap();
+            let result_json = serde_json::to_string(&result).unwrap();
+
+            state.commit(result.state);
+            state.merge_transitions(BundleRetention::Reverts);
+            let bundle_json = serde_json::to_string(&state.take_bundle()).unwrap();
+            (result_json, bundle_json)
+        };
+
+        // Every execution starts from freshly seeded maps.
+        assert_eq!(serialize(), serialize());
+    }
+
+    #[test]
+    fn cold_precompiles() {
+        let contract = Address::with_last_byte(0xc0);
+        // Call the identity precompile twice, only the first access can be cold.
+        let call_identity = [
+            PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 4, GAS, STATICCALL, POP,
+        ];
+        let code = [&call_identity[..], &call_identity, &[STOP]].concat();
+
+        let gas_used = |warm_precompiles| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(
+                contract,
+                AccountInfo::from_bytecode(Bytecode::new_legacy(code.clone().into())),
+            );
+            let mut evm = Evm::builder()
+                .with_db(db)
+                .modify_cfg_env(|cfg| cfg.warm_precompiles = warm_precompiles)
+                .modify_tx_env(|tx| tx.transact_to = TxKind::Call(contract))
+                .build();
+            let result = evm.transact().unwrap().result;
+            assert!(result.is_success());
+            result.gas_used()
+        };
+
+        assert_eq!(
+            gas_used(false) - gas_used(true),
+            gas::COLD_ACCOUNT_ACCESS_COST - gas::WARM_STORAGE_READ_COST
+        );
+    }
+
+    /// Encodes an EOF container with a single code section.
+    fn eof(code: &[u8], max_stack_size: u16, containers: Vec<Bytes>) -> Bytes {
+        EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, max_stack_size)],
+            code_section: vec![Bytes::copy_from_slice(code)],
+            container_section: containers,
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof()
+        .raw
+    }
+
+    /// Runtime container that stores 1 at slot 1.
+    fn eof_runtime() -> Bytes {
+        eof(&[PUSH1, 0x01, PUSH1, 0x01, SSTORE, STOP], 2, vec![])
+    }
+
+    /// Initcontainer deploying `runtime` with `aux_data_size` zero bytes appended to its data.
+    fn eof_initcode(runtime: Bytes, aux_data_size: u16) -> Bytes {
+        let [hi, lo] = aux_data_size.to_be_bytes();
+        eof(
+            &[PUSH2, hi, lo, PUSH0, RETURNCONTRACT, 0x00],
+            2,
+            vec![runtime],
+        )
+    }
+
+    fn eof_create_tx(
+        spec_id: SpecId,
+        initcode: Bytes,
+    ) -> (Evm<'static, (), InMemoryDB>, ExecutionResult) {
+        let mut evm = Evm::builder()
+            .with_spec_id(spec_id)
+            .with_db(InMemoryDB::default())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Create;
+                tx.data = initcode;
+            })
+            .build();
+        let result = evm.transact_commit().unwrap();
+        (evm, result)
+    }
+
+    fn call(evm: &mut Evm<'_, (), InMemoryDB>, address: Address) -> U256 {
+        evm.tx_mut().transact_to = TxKind::Call(address);
+        evm.tx_mut().data = Bytes::new();
+        assert!(evm.transact_commit().unwrap().is_success());
+        evm.db().accounts[&address].storage[&U256::from(1)]
+    }
+
+    #[test]
+    fn eof_create_tx_deploys_and_calls_contract() {
+        let (mut evm, result) = eof_create_tx(SpecId::OSAKA, eof_initcode(eof_runtime(), 0));
+        let ExecutionResult::Success {
+            output: Output::Create(code, Some(address)),
+            ..
+        } = result
+        else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(code, eof_runtime());
+        assert_eq!(call(&mut evm, address), U256::from(1));
+    }
+
+    #[test]
+    fn eofcreate_deploys_nested_containers() {
+        // Factory creating a child from the nested initcontainer when called.
+        let child_initcode = eof_initcode(eof_runtime(), 0);
+        let factory = eof(
+            &[PUSH0, PUSH0, PUSH0, PUSH0, EOFCREATE, 0x00, STOP],
+            4,
+            vec![child_initcode.clone()],
+        );
+        let (mut evm, result) = eof_create_tx(SpecId::OSAKA, eof_initcode(factory, 0));
+        let ExecutionResult::Success {
+            output: Output::Create(_, Some(factory_address)),
+            ..
+        } = result
+        else {
+            panic!("unexpected result: {result:?}");
+        };
+
+        evm.tx_mut().transact_to = TxKind::Call(factory_address);
+        evm.tx_mut().data = Bytes::new();
+        assert!(evm.transact_commit().unwrap().is_success());
+
+        let child = factory_address.create2(B256::ZERO, keccak256(&child_initcode));
+        let child_code = evm.db().accounts[&child].info.code.clone().unwrap();
+        assert_eq!(child_code.original_bytes(), eof_runtime());
+        assert_eq!(call(&mut evm, child), U256::from(1));
+    }
+
+    #[test]
+    fn eof_create_tx_invalid_initcontainer() {
+        // Runtime code can't be used as initcode.
+        let (evm, result) = eof_create_tx(SpecId::OSAKA, eof_runtime());
+        assert!(
+            matches!(result, ExecutionResult::Revert { .. }),
+            "{result:?}"
+        );
+        // The nonce is still bumped.
+        assert_eq!(evm.db().accounts[&evm.tx().caller].info.nonce, 1);
+    }
+
+    #[test]
+    fn eof_create_tx_code_size_limit() {
+        // Runtime code and the aux data exceed the code size limit.
+        let (_, result) = eof_create_tx(
+            SpecId::OSAKA,
+            eof_initcode(eof_runtime(), MAX_CODE_SIZE as u16),
+        );
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CreateContractSizeLimit,
+                gas_used: result.gas_used(),
+                context: None,
+            }
+        );
+
+        // Deploying exactly at the limit succeeds.
+        let aux_data_size = MAX_CODE_SIZE - eof_runtime().len();
+        let (_, result) = eof_create_tx(
+            SpecId::OSAKA,
+            eof_initcode(eof_runtime(), aux_data_size as u16),
+        );
+        assert!(result.is_success(), "{result:?}");
+    }
+
+    #[test]
+    fn eof_opcodes_are_invalid_before_osaka() {
+        // EOF initcode is executed as legacy code starting with the invalid 0xEF opcode.
+        let (_, result) = eof_create_tx(SpecId::PRAGUE, eof_initcode(eof_runtime(), 0));
+        assert!(matches!(result, ExecutionResult::Halt { .. }), "{result:?}");
+
+        // EOFCREATE is disabled in legacy code.
+        let (_, result) = eof_create_tx(
+            SpecId::OSAKA,
+            Bytes::from_static(&[PUSH0, PUSH0, PUSH0, PUSH0, EOFCREATE, 0x00]),
+        );
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OpcodeNotFound,
+                gas_used: result.gas_used(),
+                context: None,
+            }
+        );
+    }
+
+    #[test]
+    fn eof_calls_legacy_contract() {
+        let legacy = address!("0000000000000000000000000000000000001000");
+        let caller = address!("0000000000000000000000000000000000002000");
+
+        // EXTCALL the legacy contract and store the status at slot 0, then EXTDELEGATECALL it
+        // and store the status at slot 2.
+        let mut code = vec![PUSH0, PUSH0, PUSH0, PUSH20];
+        code.extend_from_slice(legacy.as_slice());
+        code.extend([EXTCALL, PUSH0, SSTORE, PUSH0, PUSH0, PUSH20]);
+        code.extend_from_slice(legacy.as_slice());
+        code.extend([EXTDELEGATECALL, PUSH1, 0x02, SSTORE, STOP]);
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            legacy,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(
+                [PUSH1, 0x01, PUSH1, 0x01, SSTORE, STOP].into(),
+            )),
+        );
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_bytecode(Bytecode::new_raw(eof(&code, 4, vec![]))),
+        );
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::OSAKA)
+            .with_db(db)
+            .modify_tx_env(|tx| tx.transact_to = TxKind::Call(caller))
+            .build();
+        assert!(evm.transact_commit().unwrap().is_success());
+
+        let caller_storage = &evm.db().accounts[&caller].storage;
+        // EXTCALL to legacy code succeeds.
+        assert_eq!(
+            caller_storage.get(&U256::ZERO).copied().unwrap_or_default(),
+            U256::ZERO
+        );
+        assert_eq!(
+            evm.db().accounts[&legacy].storage[&U256::from(1)],
+            U256::from(1)
+        );
+        // EXTDELEGATECALL to legacy code is a light failure.
+        assert_eq!(caller_storage[&U256::from(2)], U256::from(1));
+    }
+
+    #[test]
+    fn difficulty_pushes_randomness_of_spec() {
+        let caller = address!("0000000000000000000000000000000000002000");
+        let contract = address!("0000000000000000000000000000000000001000");
+        let randomness = B256::with_last_byte(0x2a);
+        let transact = |spec: SpecId, modify_block: &dyn Fn(&mut BlockEnv), strict: bool| {
+            let mut db = funded_db(caller);
+            insert_code(&mut db, contract, vec![DIFFICULTY, PUSH1, 0, SSTORE], 0);
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(spec)
+                .modify_cfg_env(|cfg| cfg.strict_randomness = strict)
+                .modify_block_env(modify_block)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(contract);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .map(|ResultAndState { state, .. }| {
+                    state[&contract].storage[&U256::ZERO].present_value
+                })
+        };
+
+        // The randomness is pushed from the field of each era.
+        for spec in [
+            SpecId::BERLIN,
+            SpecId::LONDON,
+            SpecId::MERGE,
+            SpecId::CANCUN,
+        ] {
+            assert_eq!(
+                transact(spec, &|block| block.set_randomness(spec, randomness), true),
+                Ok(U256::from(0x2a)),
+                "{spec:?}"
+            );
+        }
+
+        // A pre-merge block is rejected after the merge, its difficulty is ignored if a
+        // `prevrandao` is set too, unless the strict flag is set.
+        let pre_merge_block =
+            |block: &mut BlockEnv| block.set_randomness(SpecId::LONDON, randomness);
+        let mixed_block = |block: &mut BlockEnv| {
+            pre_merge_block(block);
+            block.prevrandao = Some(B256::ZERO);
+        };
+        assert_eq!(
+            transact(SpecId::MERGE, &pre_merge_block, false),
+            Err(EVMError::Header(InvalidHeader::PrevrandaoNotSet))
+        );
+        assert_eq!(transact(SpecId::MERGE, &mixed_block, false), Ok(U256::ZERO));
+        assert_eq!(
+            transact(SpecId::MERGE, &mixed_block, true),
+            Err(EVMError::Header(InvalidHeader::DifficultyAfterMerge))
+        );
+    }
+
+    fn halt_reason(result: &ExecutionResult) -> Option<HaltReason> {
+        match result {
+            ExecutionResult::Halt { reason, .. } => Some(*reason),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn eof_code_is_legacy_before_osaka() {
+        let caller = address!("0000000000000000000000000000000000002000");
+        let decoded = address!("0000000000000000000000000000000000001000");
+        let raw = address!("0000000000000000000000000000000000001001");
+        let mut db = funded_db(caller);
+        db.insert_account_info(
+            decoded,
+            AccountInfo::from_bytecode(Bytecode::new_raw(eof_runtime())),
+        );
+        insert_code(&mut db, raw, eof_runtime().to_vec(), 0);
+
+        for spec in all_specs().filter(|spec| !spec.is_enabled_in(SpecId::OSAKA)) {
+            // The container halts on the undefined 0xEF opcode, whether it is decoded or not.
+            let decoded_result = transact_call(spec, db.clone(), caller, decoded).result;
+            let raw_result = transact_call(spec, db.clone(), caller, raw).result;
+            assert_eq!(
+                halt_reason(&decoded_result),
+                Some(HaltReason::OpcodeNotFound),
+                "{spec:?}"
+            );
+            assert_eq!(decoded_result, raw_result, "{spec:?}");
+        }
+
+        // From Osaka the raw container is executed as EOF.
+        let ResultAndState { result, state } = transact_call(SpecId::OSAKA, db, caller, raw);
+        assert!(result.is_success(), "{result:?}");
+        assert_eq!(
+            state[&raw].storage[&U256::from(1)].present_value,
+            U256::from(1)
+        );
+    }
+
+    #[test]
+    fn invalid_eof_code_halts_from_osaka() {
+        let caller = address!("0000000000000000000000000000000000002000");
+        let outer = address!("0000000000000000000000000000000000003000");
+        let invalid = [
+            // Truncated header.
+            Bytes::from_static(&[0xEF, 0x00, 0x01]),
+            // Code section without a terminating instruction.
+            eof(&[PUSH1, 0x01], 1, vec![]),
+            // Initcontainer, RETURNCONTRACT is not allowed in runtime code.
+            eof_initcode(eof_runtime(), 0),
+        ];
+        for code in invalid {
+            let target = address!("0000000000000000000000000000000000001000");
+            let mut db = funded_db(caller);
+            insert_code(&mut db, target, code.to_vec(), 0);
+            // Call the target and store the status at slot 0.
+            let mut outer_code = call_code(CALL, target, 100_000, Some(0));
+            outer_code.extend([PUSH1, 0x00, SSTORE, STOP]);
+            insert_code(&mut db, outer, outer_code, 0);
+
+            let result = transact_call(SpecId::PRAGUE, db.clone(), caller, target).result;
+            assert_eq!(halt_reason(&result), Some(HaltReason::OpcodeNotFound));
+
+            let result = transact_call(SpecId::OSAKA, db.clone(), caller, target).result;
+            assert_eq!(halt_reason(&result), Some(HaltReason::InvalidEOFCode));
+            assert_eq!(result.gas_used(), 1_000_000);
+
+            // The failed call consumes the forwarded gas and the caller continues.
+            let ResultAndState { result, state } = transact_call(SpecId::OSAKA, db, caller, outer);
+            assert!(result.is_success(), "{result:?}");
+            assert!(result.gas_used() > 100_000);
+            assert_eq!(state[&outer].storage[&U256::ZERO].present_value, U256::ZERO);
+        }
+    }
+
+    #[test]
+    fn create_of_ef_code() {
+        let caller = address!("0000000000000000000000000000000000002000");
+        // Initcode returning the EOF magic.
+        let initcode = Bytes::from_static(&[
+            PUSH2, 0xEF, 0x00, PUSH1, 0x00, MSTORE, PUSH1, 0x02, PUSH1, 0x1E, RETURN,
+        ]);
+        let create = |spec: SpecId| {
+            let mut evm = Evm::builder()
+                .with_spec_id(spec)
+                .with_db(funded_db(caller))
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Create;
+                    tx.data = initcode.clone();
+                })
+                .build();
+            let result = evm.transact_commit().unwrap();
+            (evm.db().clone(), result)
+        };
+
+        // EIP-3541 rejects the code from London.
+        for spec in [SpecId::LONDON, SpecId::PRAGUE, SpecId::OSAKA] {
+            let (_, result) = create(spec);
+            assert_eq!(
+                halt_reason(&result),
+                Some(HaltReason::CreateContractStartingWithEF),
+                "{spec:?}"
+            );
+        }
+
+        // Before London it is deployed, calling it halts on the undefined opcode as legacy code
+        // and on the invalid container from Osaka.
+        let (db, result) = create(SpecId::BERLIN);
+        let ExecutionResult::Success {
+            output: Output::Create(code, Some(address)),
+            ..
+        } = result
+        else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(code, Bytes::from_static(&[0xEF, 0x00]));
+        for (spec, reason) in [
+            (SpecId::BERLIN, HaltReason::OpcodeNotFound),
+            (SpecId::PRAGUE, HaltReason::OpcodeNotFound),
+            (SpecId::OSAKA, HaltReason::InvalidEOFCode),
+        ] {
+            let result = transact_call(spec, db.clone(), caller, address).result;
+            assert_eq!(halt_reason(&result), Some(reason), "{spec:?}");
+        }
+
+        // From Osaka the CREATE opcode rejects initcode starting with the EOF magic.
+        let factory = address!("0000000000000000000000000000000000003000");
+        let mut db = funded_db(caller);
+        // Store the runtime container in memory, create from it and store the address at slot 0.
+        let runtime = eof_runtime();
+        let mut code = vec![];
+        for (offset, byte) in runtime.iter().enumerate() {
+            code.extend([PUSH1, *byte, PUSH1, offset as u8, MSTORE8]);
+        }
+        code.extend([PUSH1, runtime.len() as u8, PUSH1, 0x00, PUSH1, 0x00, CREATE]);
+        code.extend([PUSH1, 0x00, SSTORE, STOP]);
+        insert_code(&mut db, factory, code, 0);
+        let ResultAndState { result, state } = transact_call(SpecId::OSAKA, db, caller, factory);
+        assert!(result.is_success(), "{result:?}");
+        assert!(state[&factory].storage[&U256::ZERO].present_value.is_zero());
+    }
+
+    fn transact_halting(
+        contracts: &[(Address, &[u8])],
+        capture_halt_context: bool,
+    ) -> ExecutionResult {
+        let mut db = InMemoryDB::default();
+        for (address, code) in contracts {
+            db.insert_account_info(
+                *address,
+                AccountInfo::from_bytecode(Bytecode::new_legacy(code.to_vec().into())),
+            );
+        }
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.capture_halt_context = capture_halt_context)
+            .modify_tx_env(|tx| tx.transact_to = TxKind::Call(contracts[0].0))
+            .build();
+        transact_checked(&mut evm).unwrap().result
+    }
+
+    #[test]
+    fn halt_context_invalid_opcode() {
+        let contract = address!("0000000000000000000000000000000000001000");
+        let code = [PUSH1, 0x00, PUSH1, 0x00, INVALID];
+
+        let result = transact_halting(&[(contract, &code[..])], true);
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::InvalidFEOpcode,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+        assert_eq!(
+            result.halt_context(),
+            Some(&HaltContext {
+                address: contract,
+                pc: 4,
+                opcode: INVALID,
+            })
+        );
+
+        // Nothing is captured unless enabled.
+        let result = transact_halting(&[(contract, &code[..])], false);
+        assert!(result.is_halt());
+        assert_eq!(result.halt_context(), None);
+    }
+
+    #[test]
+    fn halt_context_stack_underflow() {
+        let contract = address!("0000000000000000000000000000000000001000");
+        let result = transact_halting(&[(contract, &[PUSH1, 0x01, ADD][..])], true);
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::StackUnderflow,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+        assert_eq!(
+            result.halt_context(),
+            Some(&HaltContext {
+                address: contract,
+                pc: 2,
+                opcode: ADD,
+            })
+        );
+    }
+
+    #[test]
+    fn halt_context_is_from_halting_frame() {
+        let caller = address!("0000000000000000000000000000000000001000");
+        let callee = address!("0000000000000000000000000000000000002000");
+
+        // The callee halts, which the caller observes as a failed call. The caller then halts
+        // itself on a stack underflow at pc 29.
+        let mut code = vec![PUSH0, PUSH0, PUSH0, PUSH0, PUSH0, PUSH20];
+        code.extend_from_slice(callee.as_slice());
+        code.extend([GAS, CALL, POP, ADD]);
+
+        let result = transact_halting(&[(caller, &code[..]), (callee, &[INVALID][..])], true);
+        assert_eq!(
+            result.halt_context(),
+            Some(&HaltContext {
+                address: caller,
+                pc: 29,
+                opcode: ADD,
+            })
+        );
+    }
+
+    fn transact_with_cfg(
+        modify_cfg: impl FnOnce(&mut CfgEnv),
+        modify_tx: impl FnOnce(&mut TxEnv),
+        db: InMemoryDB,
+    ) -> Result<ExecutionResult, EVMError<core::convert::Infallible>> {
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_cfg_env(modify_cfg)
+            .modify_tx_env(modify_tx)
+            .build();
+        transact_checked(&mut evm).map(|result| result.result)
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "optional_blob_validation",
+        any(feature = "c-kzg", feature = "kzg-rs")
+    ))]
+    fn skip_blob_validation() {
+        use crate::precompile::kzg_point_evaluation::{
+            kzg_to_versioned_hash, ADDRESS, RETURN_VALUE,
+        };
+
+        let caller = Address::with_last_byte(1);
+        // A blob tx that only has the commitment of its blob, the proof is zeroed.
+        let commitment = crate::primitives::hex!("8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7");
+        let versioned_hash = B256::from(kzg_to_versioned_hash(&commitment));
+        let input =
+            Bytes::from([versioned_hash.as_slice(), &[0; 64], &commitment, &[0; 48]].concat());
+        let blob_tx = |tx: &mut TxEnv| {
+            tx.caller = caller;
+            tx.transact_to = TxKind::Call(ADDRESS);
+            tx.data = input.clone();
+            tx.gas_limit = 100_000;
+            tx.blob_hashes = vec![versioned_hash];
+            tx.max_fee_per_blob_gas = Some(U256::from(1));
+        };
+        let skip = |cfg: &mut CfgEnv| cfg.skip_blob_validation = true;
+
+        let result = transact_with_cfg(|_| {}, blob_tx, funded_db(caller)).unwrap();
+        assert!(!result.is_success());
+        let result = transact_with_cfg(skip, blob_tx, funded_db(caller)).unwrap();
+        assert_eq!(result.output(), Some(&Bytes::from_static(RETURN_VALUE)));
+
+        // The versioned hash format and the blob fee are still validated.
+        let unversioned = |tx: &mut TxEnv| {
+            blob_tx(tx);
+            tx.blob_hashes[0][0] = 0;
+        };
+        assert_eq!(
+            transact_with_cfg(skip, unversioned, funded_db(caller)),
+            Err(EVMError::Transaction(
+                InvalidTransaction::BlobVersionNotSupported
+            ))
+        );
+        let underpriced = |tx: &mut TxEnv| {
+            blob_tx(tx);
+            tx.max_fee_per_blob_gas = Some(U256::ZERO);
+        };
+        assert_eq!(
+            transact_with_cfg(skip, underpriced, funded_db(caller)),
+            Err(EVMError::Transaction(
+                InvalidTransaction::BlobGasPriceGreaterThanMax
+            ))
+        );
+    }
+
+    #[test]
+    fn contract_code_size_limit() {
+        // Initcode returning 30KB of zeroed memory as runtime code.
+        let initcode = Bytes::from_static(&[PUSH2, 0x78, 0x00, PUSH0, RETURN]);
+        let create = |tx: &mut TxEnv| {
+            tx.transact_to = TxKind::Create;
+            tx.data = initcode.clone();
+        };
+
+        let result = transact_with_cfg(|_| {}, create, InMemoryDB::default()).unwrap();
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::CreateContractSizeLimit,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+
+        let result = transact_with_cfg(
+            |cfg| cfg.limit_contract_code_size = Some(0x8000),
+            create,
+            InMemoryDB::default(),
+        )
+        .unwrap();
+        let ExecutionResult::Success {
+            output: Output::Create(code, Some(_)),
+            ..
+        } = result
+        else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(code.len(), 30 * 1024);
+    }
+
+    #[test]
+    fn contract_initcode_size_limit() {
+        // 60KB of initcode that stops immediately.
+        let initcode = Bytes::from(vec![STOP; 60 * 1024]);
+        let create = |tx: &mut TxEnv| {
+            tx.transact_to = TxKind::Create;
+            tx.data = initcode.clone();
+        };
+
+        assert_eq!(
+            transact_with_cfg(|_| {}, create, InMemoryDB::default()),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CreateInitCodeSizeLimit
+            ))
+        );
+
+        for modify_cfg in [
+            (|cfg: &mut CfgEnv| cfg.limit_contract_initcode_size = Some(0x10000)) as fn(&mut _),
+            // Raising the code size limit doubles the initcode limit.
+            |cfg: &mut CfgEnv| cfg.limit_contract_code_size = Some(0x8000),
+        ] {
+            let result = transact_with_cfg(modify_cfg, create, InMemoryDB::default()).unwrap();
+            assert!(result.is_success(), "{result:?}");
+            // Every word of the initcode is charged.
+            assert_eq!(
+                result.gas_used(),
+                53_000
+                    + initcode.len() as u64 * gas::STANDARD_TOKEN_COST
+                    + gas::initcode_cost(initcode.len() as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn create_opcode_initcode_size_limit() {
+        let factory = address!("0000000000000000000000000000000000001000");
+        // CREATE with 60KB of zeroed memory as initcode.
+        let code = [PUSH3, 0x00, 0xF0, 0x00, PUSH0, PUSH0, CREATE, STOP];
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            factory,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+        let call = |tx: &mut TxEnv| tx.transact_to = TxKind::Call(factory);
+
+        let result = transact_with_cfg(|_| {}, call, db.clone()).unwrap();
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::CreateInitCodeSizeLimit,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+
+        let result = transact_with_cfg(
+            |cfg| cfg.limit_contract_initcode_size = Some(0x10000),
+            call,
+            db,
+        )
+        .unwrap();
+        assert!(result.is_success(), "{result:?}");
+    }
+
+    #[test]
+    fn created_addresses_match_precomputed() {
+        let caller = address!("0000000000000000000000000000000000000100");
+        let factory = address!("0000000000000000000000000000000000001000");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+        // Creates a contract with CREATE and CREATE2, the initcode is a single STOP, and
+        // returns both addresses.
+        #[rustfmt::skip]
+        let code = [
+            PUSH1, 0x01, PUSH1, 0x40, PUSH0, CREATE, PUSH0, MSTORE,
+            PUSH1, 0x42, PUSH1, 0x01, PUSH1, 0x40, PUSH0, CREATE2, PUSH1, 0x20, MSTORE,
+            PUSH1, 0x40, PUSH0, RETURN,
+        ];
+        db.insert_account_info(
+            factory,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+
+        let result = transact_with_cfg(
+            |_| {},
+            |tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Create;
+                tx.data = Bytes::from_static(&[STOP]);
+            },
+            db.clone(),
+        )
+        .unwrap();
+        let ExecutionResult::Success { output, .. } = result else {
+            panic!("{result:?}");
+        };
+        assert_eq!(output.address(), Some(&create_address(caller, 5)));
+
+        let result = transact_with_cfg(
+            |_| {},
+            |tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(factory);
+            },
+            db,
+        )
+        .unwrap();
+        let output = result.output().unwrap();
+        assert_eq!(
+            Address::from_word(B256::from_slice(&output[..32])),
+            create_address(factory, 1)
+        );
+        assert_eq!(
+            Address::from_word(B256::from_slice(&output[32..])),
+            create2_address(factory, U256::from(0x42), keccak256([STOP]))
+        );
+    }
+
+    #[test]
+    fn create_output_deployed_code() {
+        for size in [0u16, 1, 100, MAX_CODE_SIZE as u16] {
+            // Initcode returning `size` bytes of zeroed memory as runtime code.
+            let [hi, lo] = size.to_be_bytes();
+            let initcode = Bytes::from(vec![PUSH2, hi, lo, PUSH0, RETURN]);
+            let result = transact_with_cfg(
+                |_| {},
+                |tx| {
+                    tx.transact_to = TxKind::Create;
+                    tx.data = initcode;
+                },
+                InMemoryDB::default(),
+            )
+            .unwrap();
+            let ExecutionResult::Success { output, .. } = &result else {
+                panic!("{result:?}");
+            };
+            assert_eq!(output.deployed_code_size(), Some(size as usize));
+            assert_eq!(
+                output.code_deposit_gas(),
+                Some(size as u64 * gas::CODEDEPOSIT)
+            );
+            assert!(!result.is_create_collision());
+        }
+
+        let output = Output::Call(Bytes::from_static(&[STOP]));
+        assert_eq!(output.deployed_code_size(), None);
+        assert_eq!(output.code_deposit_gas(), None);
+    }
+
+    #[test]
+    fn create_collision() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let factory = address!("00000000000000000000000000000000000000f0");
+        // Initcode deploying a single zero byte.
+        let initcode = [PUSH1, 0x01, PUSH0, RETURN];
+        let mut code = vec![PUSH4];
+        code.extend(initcode);
+        code.extend([PUSH0, MSTORE]);
+        // CREATE2 with salt zero twice, storing the created addresses in slots 0 and 1.
+        for slot in [0, 1] {
+            code.extend([
+                PUSH0, PUSH1, 4, PUSH1, 28, PUSH0, CREATE2, PUSH1, slot, SSTORE,
+            ]);
+        }
+        let mut db = funded_db(caller);
+        insert_code(&mut db, factory, code, 0);
+
+        let ResultAndState { result, state } = transact_call(SpecId::CANCUN, db, caller, factory);
+        assert!(result.is_success(), "{result:?}");
+        // The second creation collides with the first and returns zero.
+        let storage = &state[&factory].storage;
+        let created = create2_address(factory, U256::ZERO, keccak256(initcode));
+        assert_eq!(
+            storage[&U256::ZERO].present_value,
+            U256::from_be_bytes(created.into_word().0)
+        );
+        assert_eq!(storage[&U256::from(1)].present_value, U256::ZERO);
+        assert!(!result.is_create_collision());
+
+        // A creation transaction colliding with existing code halts.
+        let mut db = funded_db(caller);
+        insert_code(&mut db, create_address(caller, 0), vec![STOP], 0);
+        let result = transact_with_cfg(
+            |_| {},
+            |tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Create;
+                tx.data = Bytes::copy_from_slice(&initcode);
+            },
+            db,
+        )
+        .unwrap();
+        assert!(result.is_create_collision(), "{result:?}");
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CreateCollision,
+                gas_used: result.gas_used(),
+                context: None,
+            }
+        );
+    }
+
+    #[test]
+    fn custom_keccak() {
+        use crate::primitives::{EnvKeccak, Keccak};
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Returns the input length as hash.
+        #[derive(Debug, Default)]
+        struct LengthKeccak {
+            calls: AtomicUsize,
+        }
+
+        impl Keccak for LengthKeccak {
+            fn keccak256(&self, input: &[u8]) -> B256 {
+                self.calls.fetch_add(1, Ordering::Relaxed);
+                B256::with_last_byte(input.len() as u8)
+            }
+        }
+
+        let factory = address!("0000000000000000000000000000000000001000");
+        // Stores the hash of a zero word, then creates a contract with CREATE2 from 4 bytes of
+        // initcode deploying a single STOP, and stores its address.
+        #[rustfmt::skip]
+        let code = [
+            PUSH1, 0x20, PUSH0, KECCAK256, PUSH0, SSTORE,
+            PUSH4, PUSH1, 0x01, PUSH0, RETURN, PUSH0, MSTORE,
+            PUSH1, 0x42, PUSH1, 0x04, PUSH1, 0x1c, PUSH0, CREATE2, PUSH1, 0x01, SSTORE,
+        ];
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            factory,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+
+        let keccak = Arc::new(LengthKeccak::default());
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.keccak = EnvKeccak::Custom(keccak.clone()))
+            .modify_tx_env(|tx| tx.transact_to = TxKind::Call(factory))
+            .build();
+        let ResultAndState { result, state } = evm.transact().unwrap();
+        assert!(result.is_success(), "{result:?}");
+
+        let storage = &state[&factory].storage;
+        assert_eq!(storage[&U256::ZERO].present_value, U256::from(32));
+        let created = create2_address(factory, U256::from(0x42), B256::with_last_byte(4));
+        assert_eq!(
+            storage[&U256::from(1)].present_value,
+            U256::from_be_bytes(created.into_word().0)
+        );
+        assert_eq!(state[&created].info.code_hash, B256::with_last_byte(1));
+        assert_eq!(keccak.calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[cfg(feature = "access-debug")]
+    #[test]
+    fn warming_report_reasons() {
+        use crate::{
+            inspector_handle_register,
+            interpreter::{
+                opcode::{REVERT, SLOAD},
+                CallOutcome,
+            },
+            primitives::AccessListItem,
+            EvmContext, Inspector, WarmingReason, WarmingRecord, WarmingReport,
+        };
+
+        /// Keeps the report at the end of the last call frame.
+        #[derive(Default)]
+        struct Reporter(WarmingReport);
+
+        impl<DB: Database> Inspector<DB> for Reporter {
+            fn call_end(
+                &mut self,
+                context: &mut EvmContext<DB>,
+                _inputs: &CallInputs,
+                outcome: CallOutcome,
+            ) -> CallOutcome {
+                self.0 = context.journaled_state.warming_report();
+                outcome
+            }
+        }
+
+        let caller = address!("0000000000000000000000000000000000000001");
+        let identity = address!("0000000000000000000000000000000000000004");
+        let target = address!("00000000000000000000000000000000000000a0");
+        let callee = address!("00000000000000000000000000000000000000b0");
+        let reverter = address!("00000000000000000000000000000000000000c0");
+        let listed = address!("00000000000000000000000000000000000000d0");
+        let coinbase = address!("00000000000000000000000000000000000000e0");
+
+        let mut db = funded_db(caller);
+        // SLOAD(5), then calls the callee, the reverter and the identity precompile.
+        let mut code = vec![PUSH1, 5, SLOAD, POP];
+        for address in [callee, reverter, identity] {
+            code.extend(call_code(CALL, address, 0xffff, Some(0)));
+            code.push(POP);
+        }
+        insert_code(&mut db, target, code, 0);
+        insert_code(&mut db, callee, vec![STOP], 0);
+        // SLOAD(7), then reverts.
+        let code = vec![PUSH1, 7, SLOAD, PUSH1, 0, PUSH1, 0, REVERT];
+        insert_code(&mut db, reverter, code, 0);
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .with_external_context(Reporter::default())
+            .append_handler_register(inspector_handle_register)
+            .modify_block_env(|block| block.coinbase = coinbase)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(target);
+                tx.gas_limit = 1_000_000;
+                tx.access_list = vec![AccessListItem {
+                    address: listed,
+                    storage_keys: vec![B256::with_last_byte(1)],
+                }];
+            })
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        let report = &evm.context.external.0;
+
+        let record = |reason, depth| Some(WarmingRecord { reason, depth });
+        assert_eq!(report.address(caller), record(WarmingReason::TxCaller, 0));
+        assert_eq!(report.address(target), record(WarmingReason::TxTarget, 0));
+        assert_eq!(report.address(coinbase), record(WarmingReason::Coinbase, 0));
+        assert_eq!(
+            report.address(identity),
+            record(WarmingReason::Precompile, 0)
+        );
+        assert_eq!(report.address(listed), record(WarmingReason::AccessList, 0));
+        assert_eq!(
+            report.slot(listed, U256::from(1)),
+            record(WarmingReason::AccessList, 0)
+        );
+
+        // Accessed from the frame of the target.
+        assert_eq!(
+            report.slot(target, U256::from(5)),
+            record(WarmingReason::Access, 1)
+        );
+        assert_eq!(report.address(callee), record(WarmingReason::Access, 1));
+        // The reverter is warmed by the call, its own access is reverted.
+        assert_eq!(report.address(reverter), record(WarmingReason::Access, 1));
+        assert_eq!(report.slot(reverter, U256::from(7)), None);
+        assert_eq!(report.slot(target, U256::from(6)), None);
+
+        // Cleared with the transaction.
+        assert_eq!(
+            evm.context.evm.journaled_state.warming_report(),
+            WarmingReport::default()
+        );
+    }
+
+    #[test]
+    fn configured_stack_limit() {
+        let caller = address!("0000000000000000000000000000000000000100");
+        let contract = address!("0000000000000000000000000000000000000200");
+        let transact = |items: usize, stack_limit: Option<usize>| {
+            let mut code = [PUSH1, 0].repeat(items);
+            code.push(STOP);
+            let mut db = funded_db(caller);
+            insert_code(&mut db, contract, code, 0);
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_cfg_env(|cfg| cfg.stack_limit = stack_limit)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(contract);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap()
+                .result
+        };
+        let overflowed = |result: ExecutionResult| {
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::StackOverflow,
+                    ..
+                }
+            )
+        };
+
+        assert!(transact(16, Some(16)).is_success());
+        assert!(overflowed(transact(17, Some(16))));
+        assert!(transact(1024, None).is_success());
+        assert!(overflowed(transact(1025, None)));
+        assert!(transact(2048, Some(2048)).is_success());
+    }
+
+    #[test]
+    fn return_data_limit() {
+        use crate::{
+            interpreter::opcode::{MSTORE8, RETURNDATACOPY, RETURNDATASIZE},
+            primitives::ReturnDataLimit,
+        };
+
+        let caller = address!("0000000000000000000000000000000000000100");
+        let parent = address!("0000000000000000000000000000000000000200");
+        let child = address!("0000000000000000000000000000000000000300");
+        // Returns 64 bytes, ending with 0xff and 0xee.
+        let child_code = vec![
+            PUSH1, 0xff, PUSH1, 31, MSTORE8, PUSH1, 0xee, PUSH1, 63, MSTORE8, PUSH1, 64, PUSH1, 0,
+            RETURN,
+        ];
+        // Calls the child and returns the call status, RETURNDATASIZE and `len` bytes of return
+        // data at `offset`, in three words.
+        let transact = |offset: u8, len: u8, limit: Option<(usize, ReturnDataLimit)>| {
+            let mut parent_code = vec![PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH20];
+            parent_code.extend_from_slice(child.as_slice());
+            parent_code.extend([
+                GAS,
+                CALL,
+                PUSH1,
+                0,
+                MSTORE,
+                RETURNDATASIZE,
+                PUSH1,
+                32,
+                MSTORE,
+                PUSH1,
+                len,
+                PUSH1,
+                offset,
+                PUSH1,
+                64,
+                RETURNDATACOPY,
+                PUSH1,
+                96,
+                PUSH1,
+                0,
+                RETURN,
+            ]);
+            let mut db = funded_db(caller);
+            insert_code(&mut db, parent, parent_code, 0);
+            insert_code(&mut db, child, child_code.clone(), 0);
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_cfg_env(|cfg| {
+                    if let Some((size, policy)) = limit {
+                        cfg.max_return_data_size = Some(size);
+                        cfg.return_data_limit = policy;
+                    }
+                })
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(parent);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap()
+                .result
+        };
+        let output = |status: u8, size: u8, last: u8| {
+            let mut output = [0; 96];
+            output[31] = status;
+            output[63] = size;
+            output[95] = last;
+            Bytes::copy_from_slice(&output)
+        };
+        let out_of_offset = |result: ExecutionResult| {
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::OutOfOffset,
+                    ..
+                }
+            )
+        };
+
+        // Without a limit, or within it, return data is the output of the child.
+        for limit in [
+            None,
+            Some((64, ReturnDataLimit::Halt)),
+            Some((64, ReturnDataLimit::Truncate)),
+        ] {
+            assert_eq!(transact(0, 32, limit).output(), Some(&output(1, 64, 0xff)));
+            assert_eq!(transact(32, 32, limit).output(), Some(&output(1, 64, 0xee)));
+            assert!(out_of_offset(transact(33, 32, limit)));
+            assert!(out_of_offset(transact(65, 0, limit)));
+        }
+
+        // Truncated output is seen as shorter return data.
+        let truncate = Some((32, ReturnDataLimit::Truncate));
+        assert_eq!(
+            transact(0, 32, truncate).output(),
+            Some(&output(1, 32, 0xff))
+        );
+        assert!(out_of_offset(transact(1, 32, truncate)));
+
+        // A halted child fails the call and leaves no return data.
+        let halt = Some((32, ReturnDataLimit::Halt));
+        assert_eq!(transact(0, 0, halt).output(), Some(&output(0, 0, 0)));
+        assert!(out_of_offset(transact(0, 1, halt)));
+
+        // The output of the transaction itself is not limited.
+        for policy in [ReturnDataLimit::Halt, ReturnDataLimit::Truncate] {
+            let mut db = funded_db(caller);
+            insert_code(&mut db, child, child_code.clone(), 0);
+            let result = Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_cfg_env(|cfg| {
+                    cfg.max_return_data_size = Some(32);
+                    cfg.return_data_limit = policy;
+                })
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(child);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap()
+                .result;
+            assert_eq!(
+                result.output().map(|output| output.len()),
+                Some(64),
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn discard_output_keeps_status_and_gas() {
+        use crate::interpreter::opcode::{LOG0, REVERT};
+
+        let caller = address!("0000000000000000000000000000000000000100");
+        let contract = address!("0000000000000000000000000000000000000200");
+        // Stores a word in memory, then logs and returns it after writing slot 1.
+        let returning = vec![
+            PUSH1, 0xaa, PUSH1, 0, MSTORE, PUSH1, 1, PUSH1, 1, SSTORE, PUSH1, 32, PUSH1, 0, LOG0,
+            PUSH1, 32, PUSH1, 0, RETURN,
+        ];
+        let reverting = vec![PUSH1, 0xaa, PUSH1, 0, MSTORE, PUSH1, 32, PUSH1, 0, REVERT];
+        // Deploys PUSH0 STOP.
+        let initcode = vec![
+            PUSH2, PUSH0, STOP, PUSH1, 0, MSTORE, PUSH1, 2, PUSH1, 30, RETURN,
+        ];
+
+        let transact = |code: Option<Vec<u8>>, discard_output: bool| {
+            let mut db = funded_db(caller);
+            let tx_kind = match &code {
+                Some(code) => {
+                    insert_code(&mut db, contract, code.clone(), 0);
+                    TxKind::Call(contract)
+                }
+                None => TxKind::Create,
+            };
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .modify_cfg_env(|cfg| cfg.discard_output = discard_output)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = tx_kind;
+                    tx.data = initcode.clone().into();
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap()
+        };
+        let discarded = |result: ExecutionResult| match result {
+            ExecutionResult::Success {
+                reason,
+                gas_used,
+                gas_refunded,
+                logs,
+                output,
+            } => ExecutionResult::Success {
+                reason,
+                gas_used,
+                gas_refunded,
+                logs,
+                output: match output {
+                    Output::Call(_) => Output::Call(Bytes::new()),
+                    Output::Create(_, address) => Output::Create(Bytes::new(), address),
+                },
+            },
+            ExecutionResult::Revert { gas_used, .. } => ExecutionResult::Revert {
+                gas_used,
+                output: Bytes::new(),
+            },
+            halt => halt,
+        };
+
+        for code in [Some(returning), Some(reverting), None, Some(vec![INVALID])] {
+            let kept = transact(code.clone(), false);
+            let discarded_result = transact(code, true);
+            assert_eq!(discarded_result.state, kept.state);
+            if !kept.result.is_halt() {
+                assert!(!kept.result.output().unwrap().is_empty());
+            }
+            assert_eq!(discarded_result.result, discarded(kept.result));
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn request_system_calls() {
+        use crate::interpreter::opcode::SLOAD;
+
+        let caller = address!("0000000000000000000000000000000000000100");
+        let coinbase = address!("0000000000000000000000000000000000000200");
+        let mut db = funded_db(caller);
+        // Returns slot 1 and clears it, a queue of a single request.
+        let queue = vec![
+            PUSH1, 1, SLOAD, PUSH1, 0, MSTORE, PUSH1, 0, PUSH1, 1, SSTORE, PUSH1, 32, PUSH1, 0,
+            RETURN,
+        ];
+        insert_code(&mut db, WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS, queue, 0);
+        db.insert_account_storage(
+            WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
+            U256::from(1),
+            U256::from(0xabcd),
+        )
+        .unwrap();
+        insert_code(
+            &mut db,
+            CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+            vec![INVALID],
+            0,
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_block_env(|block| {
+                block.coinbase = coinbase;
+                block.basefee = U256::from(7);
+                block.gas_limit = U256::from(1_000_000);
+            })
+            .modify_tx_env(|tx| tx.caller = caller)
+            .build();
+
+        let (requests, state) = evm.transact_withdrawal_requests().unwrap();
+        assert_eq!(requests, Bytes::from(U256::from(0xabcd).to_be_bytes_vec()));
+        let predeploy = &state[&WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS];
+        assert_eq!(predeploy.storage[&U256::from(1)].present_value, U256::ZERO);
+        assert!(!state.contains_key(&SYSTEM_ADDRESS));
+        assert!(!state.contains_key(&coinbase));
+
+        // The environment of the block and the transaction is restored.
+        assert_eq!(evm.tx().caller, caller);
+        assert_eq!(evm.block().basefee, U256::from(7));
+        assert_eq!(evm.block().gas_limit, U256::from(1_000_000));
+
+        assert!(matches!(
+            evm.transact_consolidation_requests(),
+            Err(EVMError::Custom(_))
+        ));
+    }
+
+    /// Evm calling a counter contract from a funded caller, paying a gas price of one to the
+    /// coinbase, and the transaction incrementing the counter with `nonce`.
+    fn bundle_evm() -> (Evm<'static, (), InMemoryDB>, impl Fn(u64) -> TxEnv) {
+        let caller = address!("0000000000000000000000000000000000002000");
+        let counter = address!("0000000000000000000000000000000000001000");
+        let mut db = funded_db(caller);
+        let code = vec![PUSH1, 0, SLOAD, PUSH1, 1, ADD, PUSH1, 0, SSTORE];
+        insert_code(&mut db, counter, code, 0);
+        let evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_block_env(|block| {
+                block.coinbase = address!("0000000000000000000000000000000000003000")
+            })
+            .build();
+        let tx = move |nonce| TxEnv {
+            caller,
+            transact_to: TxKind::Call(counter),
+            gas_limit: 100_000,
+            gas_price: U256::from(1),
+            nonce: Some(nonce),
+            ..Default::default()
+        };
+        (evm, tx)
+    }
+
+    #[test]
+    fn simulate_bundle_skips_transaction() {
+        let (mut evm, tx) = bundle_evm();
+        let counter = address!("0000000000000000000000000000000000001000");
+
+        // The third transaction reuses the nonce of the skipped one.
+        let report = evm
+            .simulate_bundle([tx(0), tx(1), tx(1)], |index, result_and_state, ctx| {
+                assert!(result_and_state.result.is_success());
+                assert_eq!(ctx.env().tx.nonce, Some(index.min(1) as u64));
+                // The counter is incremented from the committed state.
+                let expected = if index == 0 { 1 } else { 2 };
+                assert_eq!(
+                    result_and_state.state[&counter].storage[&U256::ZERO].present_value,
+                    U256::from(expected)
+                );
+                if index == 1 {
+                    BundleControl::Skip
+                } else {
+                    BundleControl::Continue
+                }
+            })
+            .unwrap();
+
+        let controls: Vec<_> = report.results.iter().map(|tx| tx.control).collect();
+        assert_eq!(
+            controls,
+            [
+                BundleControl::Continue,
+                BundleControl::Skip,
+                BundleControl::Continue
+            ]
+        );
+        assert!(!report.is_aborted());
+        let gas_used = report.results[0].result.gas_used() + report.results[2].result.gas_used();
+        assert_eq!(report.cumulative_gas_used, gas_used);
+        assert_eq!(report.coinbase_balance_before, U256::ZERO);
+        assert_eq!(report.coinbase_balance_after, U256::from(gas_used));
+        assert_eq!(
+            report.coinbase_balance_delta(),
+            I256::from_raw(U256::from(gas_used))
+        );
+
+        // The database and the transaction environment are restored.
+        let caller = address!("0000000000000000000000000000000000002000");
+        assert_eq!(evm.db().accounts[&caller].info.nonce, 0);
+        assert_eq!(evm.db().storage_ref(counter, U256::ZERO), Ok(U256::ZERO));
+        assert_eq!(evm.tx(), &TxEnv::default());
+    }
+
+    #[test]
+    fn simulate_bundle_aborts() {
+        let (mut evm, tx) = bundle_evm();
+        let caller = address!("0000000000000000000000000000000000002000");
+        let counter = address!("0000000000000000000000000000000000001000");
+        let coinbase = address!("0000000000000000000000000000000000003000");
+        let caller_info = evm.db().accounts[&caller].info.clone();
+
+        let report = evm
+            .simulate_bundle([tx(0), tx(1), tx(2)], |index, _, _| {
+                if index == 1 {
+                    BundleControl::Abort
+                } else {
+                    BundleControl::Continue
+                }
+            })
+            .unwrap();
+        assert_eq!(report.results.len(), 2);
+        assert!(report.is_aborted());
+        assert_eq!(
+            report.cumulative_gas_used,
+            report.results[0].result.gas_used()
+        );
+        // The committed transaction is discarded.
+        let unchanged = |evm: &Evm<'static, (), InMemoryDB>| {
+            assert_eq!(evm.db().accounts[&caller].info, caller_info);
+            assert_eq!(evm.db().storage_ref(counter, U256::ZERO), Ok(U256::ZERO));
+            assert_eq!(evm.db().basic_ref(coinbase), Ok(None));
+        };
+        unchanged(&evm);
+
+        // An invalid transaction stops the simulation, the committed transactions are discarded
+        // too.
+        assert_eq!(
+            evm.simulate_bundle([tx(0), tx(0)], |_, _, _| BundleControl::Continue),
+            Err(EVMError::Transaction(InvalidTransaction::NonceTooLow {
+                tx: 0,
+                state: 1
+            }))
+        );
+        unchanged(&evm);
+    }
+
+    #[test]
+    fn state_clear_touched_empty_accounts() {
+        let caller = address!("0000000000000000000000000000000000000100");
+        let contract = address!("0000000000000000000000000000000000000200");
+        let empty = address!("0000000000000000000000000000000000000300");
+        let ripemd = Address::with_last_byte(3);
+        let identity = Address::with_last_byte(4);
+
+        // Zero value calls to an empty account, and to precompiles without gas.
+        let mut code = call_code(CALL, empty, 0, Some(0));
+        code.extend(call_code(CALL, ripemd, 0, Some(0)));
+        code.extend(call_code(CALL, identity, 0, Some(0)));
+        for spec in [SpecId::TANGERINE, SpecId::SPURIOUS_DRAGON, SpecId::CANCUN] {
+            let mut db = funded_db(caller);
+            insert_code(&mut db, contract, code.clone(), 0);
+            let ResultAndState { result, state } = transact_call(spec, db, caller, contract);
+            assert!(result.is_success());
+
+            let is_state_clear = spec.is_enabled_in(SpecId::SPURIOUS_DRAGON);
+            assert!(state[&empty].is_touched());
+            assert_eq!(state[&empty].is_touched_empty(spec), is_state_clear);
+            // The touch of RIPEMD-160 survives the revert of the failed call, see EIP-716.
+            assert_eq!(state[&ripemd].is_touched(), is_state_clear);
+            assert!(!state[&identity].is_touched());
+
+            let mut cleared: Vec<_> =
+                JournaledState::touched_empty_accounts(&state, spec).collect();
+            cleared.sort();
+            // The zero coinbase of the default block is touched by the fee payment of zero.
+            let expected = if is_state_clear {
+                vec![Address::ZERO, ripemd, empty]
+            } else {
+                vec![]
+            };
+            assert_eq!(cleared, expected);
+        }
+
+        // The caller is emptied by the fee payment but its nonce is bumped, while the coinbase
+        // is touched without being paid.
+        let coinbase = address!("0000000000000000000000000000000000000400");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(21_000),
+                ..Default::default()
+            },
+        );
+        let ResultAndState { result, state } = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_block_env(|block| {
+                block.coinbase = coinbase;
+                block.basefee = U256::from(1);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(empty);
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(1);
+            })
+            .build()
+            .transact()
+            .unwrap();
+        assert!(result.is_success());
+        assert_eq!(state[&caller].info.balance, U256::ZERO);
+        assert!(!state[&caller].is_touched_empty(SpecId::CANCUN));
+        let mut cleared: Vec<_> =
+            JournaledState::touched_empty_accounts(&state, SpecId::CANCUN).collect();
+        cleared.sort();
+        assert_eq!(cleared, vec![empty, coinbase]);
+    }
+
+    #[test]
+    fn state_overrides_proxy_implementation() {
+        use crate::{StateOverrides, EIP1967_IMPLEMENTATION_SLOT};
+
+        let caller = address!("0000000000000000000000000000000000001000");
+        let proxy = address!("0000000000000000000000000000000000002000");
+        let implementation = address!("0000000000000000000000000000000000003000");
+        // Delegates to the implementation in the EIP-1967 slot and returns its output.
+        let mut proxy_code = vec![PUSH1, 32, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH32];
+        proxy_code.extend(EIP1967_IMPLEMENTATION_SLOT.to_be_bytes::<32>());
+        proxy_code.extend([SLOAD, GAS, DELEGATECALL, POP, PUSH1, 32, PUSH1, 0, RETURN]);
+        // Stores `value` at slot 1 and returns it.
+        let implementation_code = |value: u8| {
+            let mut code = vec![
+                PUSH1, value, PUSH1, 1, SSTORE, PUSH1, value, PUSH1, 0, MSTORE,
+            ];
+            code.extend([PUSH1, 32, PUSH1, 0, RETURN]);
+            Bytecode::new_legacy(code.into())
+        };
+        let slot_of = |address: Address| U256::from_be_bytes(address.into_word().0);
+        let overridden = StateOverrides::implementation_address(&implementation_code(42));
+
+        let mut db = funded_db(caller);
+        insert_code(&mut db, proxy, proxy_code, 0);
+        db.insert_account_storage(proxy, EIP1967_IMPLEMENTATION_SLOT, slot_of(implementation))
+            .unwrap();
+        db.insert_account_info(
+            implementation,
+            AccountInfo::from_bytecode(implementation_code(1)),
+        );
+        let transact = |db: InMemoryDB, overrides: StateOverrides| {
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .with_state_overrides(overrides)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(proxy);
+                    tx.gas_limit = 1_000_000;
+                })
+                .build()
+                .transact()
+                .unwrap()
+        };
+
+        // The same upgrade written to the database.
+        let mut upgraded = db.clone();
+        upgraded
+            .insert_account_storage(proxy, EIP1967_IMPLEMENTATION_SLOT, slot_of(overridden))
+            .unwrap();
+        upgraded.insert_account_info(
+            overridden,
+            AccountInfo::from_bytecode(implementation_code(42)),
+        );
+        let expected = transact(upgraded, StateOverrides::new()).result;
+        assert_eq!(
+            expected.output(),
+            Some(&Bytes::from(U256::from(42).to_be_bytes::<32>()))
+        );
+
+        // The override resolves to the injected implementation and is charged the same gas.
+        let overrides = StateOverrides::new().proxy_implementation(proxy, implementation_code(42));
+        let ResultAndState { result, state } = transact(db.clone(), overrides);
+        assert_eq!(result, expected);
+        assert_eq!(
+            state[&proxy].storage[&EIP1967_IMPLEMENTATION_SLOT].present_value(),
+            slot_of(overridden)
+        );
+        assert_eq!(
+            state[&proxy].storage[&U256::from(1)].present_value(),
+            U256::from(42)
+        );
+
+        // Without overrides the proxy keeps its implementation.
+        let output = transact(db, StateOverrides::new()).result;
+        assert_eq!(
+            output.output(),
+            Some(&Bytes::from(U256::from(1).to_be_bytes::<32>()))
+        );
+    }
 }
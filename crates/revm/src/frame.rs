@@ -1,11 +1,11 @@
 use crate::{
-    interpreter::Interpreter,
+    interpreter::{Contract, Interpreter},
     primitives::{Address, Output},
     JournalCheckpoint,
 };
 use core::ops::Range;
 use revm_interpreter::{CallOutcome, CreateOutcome, Gas, InstructionResult, InterpreterResult};
-use std::boxed::Box;
+use std::{boxed::Box, vec::Vec};
 
 /// Call CallStackFrame.
 #[derive(Debug)]
@@ -227,6 +227,180 @@ impl Frame {
     }
 }
 
+/// Default maximum number of frames of a [`FramePool`].
+pub const FRAME_POOL_MAX_LEN: usize = 32;
+
+/// Frames of finished calls, kept to be reused by following calls of the same kind.
+///
+/// A reused frame saves the allocation of the frame and of its interpreter stack. Frames are
+/// returned to the pool by the mainnet `*_return` handles, their inputs, bytecode and output are
+/// dropped and only the allocations are kept. Frames returned to a full pool are dropped, see
+/// [`FramePool::with_max_len`].
+#[derive(Debug)]
+pub struct FramePool {
+    /// Pooled [`Frame::Call`]s.
+    calls: Vec<Frame>,
+    /// Pooled [`Frame::Create`]s.
+    creates: Vec<Frame>,
+    /// Pooled [`Frame::EOFCreate`]s.
+    eofcreates: Vec<Frame>,
+    max_len: usize,
+}
+
+impl Default for FramePool {
+    fn default() -> Self {
+        Self::with_max_len(FRAME_POOL_MAX_LEN)
+    }
+}
+
+impl FramePool {
+    /// Creates an empty pool holding at most `max_len` frames.
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            calls: Vec::new(),
+            creates: Vec::new(),
+            eofcreates: Vec::new(),
+            max_len,
+        }
+    }
+
+    /// Returns the maximum number of pooled frames.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Returns the number of pooled frames.
+    pub fn len(&self) -> usize {
+        self.calls.len() + self.creates.len() + self.eofcreates.len()
+    }
+
+    /// Returns `true` if there are no pooled frames.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all pooled frames.
+    pub fn clear(&mut self) {
+        self.calls.clear();
+        self.creates.clear();
+        self.eofcreates.clear();
+    }
+
+    /// Creates a call frame, reusing a pooled one if there is one.
+    pub fn new_call(
+        &mut self,
+        return_memory_range: Range<usize>,
+        checkpoint: JournalCheckpoint,
+        contract: Contract,
+        gas_limit: u64,
+        is_static: bool,
+    ) -> Frame {
+        let Some(Frame::Call(mut frame)) = self.calls.pop() else {
+            return Frame::new_call(
+                return_memory_range,
+                checkpoint,
+                Interpreter::new(contract, gas_limit, is_static),
+            );
+        };
+        frame.return_memory_range = return_memory_range;
+        frame
+            .frame_data
+            .reset(checkpoint, contract, gas_limit, is_static);
+        Frame::Call(frame)
+    }
+
+    /// Creates a create frame, reusing a pooled one if there is one.
+    pub fn new_create(
+        &mut self,
+        created_address: Address,
+        checkpoint: JournalCheckpoint,
+        contract: Contract,
+        gas_limit: u64,
+    ) -> Frame {
+        let Some(Frame::Create(mut frame)) = self.creates.pop() else {
+            return Frame::new_create(
+                created_address,
+                checkpoint,
+                Interpreter::new(contract, gas_limit, false),
+            );
+        };
+        frame.created_address = created_address;
+        frame
+            .frame_data
+            .reset(checkpoint, contract, gas_limit, false);
+        Frame::Create(frame)
+    }
+
+    /// Creates an EOF create frame, reusing a pooled one if there is one.
+    ///
+    /// The interpreter is set to EOF init mode.
+    pub fn new_eofcreate(
+        &mut self,
+        created_address: Address,
+        checkpoint: JournalCheckpoint,
+        contract: Contract,
+        gas_limit: u64,
+    ) -> Frame {
+        let mut frame = match self.eofcreates.pop() {
+            Some(Frame::EOFCreate(mut frame)) => {
+                frame.created_address = created_address;
+                frame
+                    .frame_data
+                    .reset(checkpoint, contract, gas_limit, false);
+                frame
+            }
+            _ => Box::new(EOFCreateFrame {
+                created_address,
+                frame_data: FrameData {
+                    checkpoint,
+                    interpreter: Interpreter::new(contract, gas_limit, false),
+                },
+            }),
+        };
+        // EOF init will enable RETURNCONTRACT opcode.
+        frame.frame_data.interpreter.set_is_eof_init();
+        Frame::EOFCreate(frame)
+    }
+
+    /// Returns a finished call frame to the pool.
+    pub fn recycle_call(&mut self, mut frame: Box<CallFrame>) {
+        if self.len() < self.max_len {
+            frame.frame_data.interpreter.clear();
+            self.calls.push(Frame::Call(frame));
+        }
+    }
+
+    /// Returns a finished create frame to the pool.
+    pub fn recycle_create(&mut self, mut frame: Box<CreateFrame>) {
+        if self.len() < self.max_len {
+            frame.frame_data.interpreter.clear();
+            self.creates.push(Frame::Create(frame));
+        }
+    }
+
+    /// Returns a finished EOF create frame to the pool.
+    pub fn recycle_eofcreate(&mut self, mut frame: Box<EOFCreateFrame>) {
+        if self.len() < self.max_len {
+            frame.frame_data.interpreter.clear();
+            self.eofcreates.push(Frame::EOFCreate(frame));
+        }
+    }
+}
+
+impl FrameData {
+    /// Resets the frame data to run `contract`, keeping the interpreter allocations.
+    fn reset(
+        &mut self,
+        checkpoint: JournalCheckpoint,
+        contract: Contract,
+        gas_limit: u64,
+        is_static: bool,
+    ) {
+        self.checkpoint = checkpoint;
+        self.interpreter.reset(contract, gas_limit, is_static);
+    }
+}
+
 impl FrameOrResult {
     /// Creates new create frame.
     pub fn new_create_frame(
@@ -0,0 +1,89 @@
+//! Fuzzing entry points.
+//!
+//! Used by the `cargo-fuzz` targets in the `fuzz` directory.
+
+use crate::{
+    db::{CacheDB, EmptyDB},
+    primitives::{
+        arbitrary::{self, Arbitrary, Unstructured},
+        AccountInfo, Bytecode, EVMError, Env, ExecutionResult, SpecId, TxKind, U256,
+    },
+    Evm,
+};
+use std::boxed::Box;
+
+/// Builds and executes a transaction from arbitrary data.
+///
+/// The caller is funded and the arbitrary bytecode is either the code of the called account or
+/// the initcode of the created one. Data that doesn't decode into a transaction is ignored.
+///
+/// # Panics
+///
+/// Panics if execution panics, fails with an error other than an invalid transaction or header,
+/// or uses more gas than the transaction gas limit.
+pub fn run_arbitrary_tx(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    let Ok((mut env, bytecode, spec_id)) = arbitrary_input(&mut u) else {
+        return;
+    };
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(env.tx.caller, AccountInfo::from_balance(U256::MAX >> 1));
+    match env.tx.transact_to {
+        TxKind::Call(address) if address != env.tx.caller => {
+            db.insert_account_info(address, AccountInfo::from_bytecode(bytecode));
+        }
+        TxKind::Call(_) => {}
+        TxKind::Create => env.tx.data = bytecode.original_bytes(),
+    }
+
+    let gas_limit = env.tx.gas_limit;
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .with_env(Box::new(env))
+        .with_spec_id(spec_id)
+        .build();
+    match evm.transact() {
+        Ok(result) => {
+            let gas_used = result.result.gas_used();
+            assert!(gas_used <= gas_limit, "gas used {gas_used} > {gas_limit}");
+            if let ExecutionResult::Success { gas_refunded, .. } = result.result {
+                assert!(
+                    gas_refunded <= gas_limit,
+                    "gas refunded {gas_refunded} > {gas_limit}"
+                );
+            }
+        }
+        Err(EVMError::Transaction(_) | EVMError::Header(_)) => {}
+        Err(error) => panic!("unexpected error: {error:?}"),
+    }
+}
+
+fn arbitrary_input(u: &mut Unstructured<'_>) -> arbitrary::Result<(Env, Bytecode, SpecId)> {
+    let spec_id = SpecId::try_from_u8(u.int_in_range(0..=SpecId::OSAKA as u8)?)
+        .ok_or(arbitrary::Error::IncorrectFormat)?;
+    Ok((Env::arbitrary(u)?, Bytecode::arbitrary(u)?, spec_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn run_arbitrary_inputs() {
+        let mut state = 0x2545f4914f6cdd1du64;
+        for _ in 0..256 {
+            let data: Vec<u8> = (0..512)
+                .map(|_| {
+                    // xorshift64
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect();
+            run_arbitrary_tx(&data);
+        }
+    }
+}
@@ -19,6 +19,8 @@ pub use post_execution::{
 pub use pre_execution::{
     DeductCallerHandle, LoadAccountsHandle, LoadPrecompilesHandle, PreExecutionHandler,
 };
+#[cfg(feature = "secp256k1-recover")]
+pub use validation::RecoverCallerHandle;
 pub use validation::{
     ValidateEnvHandle, ValidateInitialTxGasHandle, ValidateTxEnvAgainstState, ValidationHandler,
 };
@@ -1,5 +1,7 @@
 use revm_interpreter::gas::InitialAndFloorGas;
 
+#[cfg(feature = "secp256k1-recover")]
+use crate::primitives::Address;
 use crate::{
     handler::mainnet,
     primitives::{db::Database, EVMError, Env, Spec},
@@ -7,6 +9,11 @@ use crate::{
 };
 use std::sync::Arc;
 
+/// Handle that recovers the caller of the transaction from its signature.
+#[cfg(feature = "secp256k1-recover")]
+pub type RecoverCallerHandle<'a, DB> =
+    Arc<dyn Fn(&mut Env) -> Result<Option<Address>, EVMError<<DB as Database>::Error>> + 'a>;
+
 /// Handle that validates env.
 pub type ValidateEnvHandle<'a, DB> =
     Arc<dyn Fn(&Env) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
@@ -22,6 +29,9 @@ pub type ValidateInitialTxGasHandle<'a, DB> =
 
 /// Handles related to validation.
 pub struct ValidationHandler<'a, EXT, DB: Database> {
+    /// Recover the caller from the signature of the transaction, before it is validated.
+    #[cfg(feature = "secp256k1-recover")]
+    pub recover_caller: RecoverCallerHandle<'a, DB>,
     /// Validate and calculate initial transaction gas.
     pub initial_tx_gas: ValidateInitialTxGasHandle<'a, DB>,
     /// Validate transactions against state data.
@@ -34,6 +44,8 @@ impl<'a, EXT: 'a, DB: Database + 'a> ValidationHandler<'a, EXT, DB> {
     /// Create new ValidationHandles
     pub fn new<SPEC: Spec + 'a>() -> Self {
         Self {
+            #[cfg(feature = "secp256k1-recover")]
+            recover_caller: Arc::new(mainnet::recover_caller::<SPEC, DB>),
             initial_tx_gas: Arc::new(mainnet::validate_initial_tx_gas::<SPEC, DB>),
             env: Arc::new(mainnet::validate_env::<SPEC, DB>),
             tx_against_state: Arc::new(mainnet::validate_tx_against_state::<SPEC, EXT, DB>),
@@ -42,6 +54,12 @@ impl<'a, EXT: 'a, DB: Database + 'a> ValidationHandler<'a, EXT, DB> {
 }
 
 impl<EXT, DB: Database> ValidationHandler<'_, EXT, DB> {
+    /// Recover the caller, returning it if the transaction is signed.
+    #[cfg(feature = "secp256k1-recover")]
+    pub fn recover_caller(&self, env: &mut Env) -> Result<Option<Address>, EVMError<DB::Error>> {
+        (self.recover_caller)(env)
+    }
+
     /// Validate env.
     pub fn env(&self, env: &Env) -> Result<(), EVMError<DB::Error>> {
         (self.env)(env)
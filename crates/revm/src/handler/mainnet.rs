@@ -11,8 +11,19 @@ pub use execution::{
     call, call_return, create, create_return, eofcreate, eofcreate_return, execute_frame,
     insert_call_outcome, insert_create_outcome, insert_eofcreate_outcome, last_frame_return,
 };
-pub use post_execution::{clear, end, output, refund, reimburse_caller, reward_beneficiary};
+pub use post_execution::{
+    clear, end, output, refund, refund_quotient, reimburse_caller, reward_beneficiary,
+    set_fee_summary_gas,
+};
+#[cfg(feature = "optimism")]
+pub(crate) use pre_execution::warm_addresses_with_precompiles;
 pub use pre_execution::{
     apply_eip7702_auth_list, deduct_caller, deduct_caller_inner, load_accounts, load_precompiles,
+    warm_addresses_for_tx,
+};
+#[cfg(feature = "secp256k1-recover")]
+pub use validation::recover_caller;
+pub use validation::{
+    calculate_intrinsic_gas, validate_env, validate_initial_tx_gas, validate_tx_against_state,
+    IntrinsicGas,
 };
-pub use validation::{validate_env, validate_initial_tx_gas, validate_tx_against_state};
@@ -75,15 +75,14 @@ pub fn call<SPEC: Spec, EXT, DB: Database>(
 pub fn call_return<EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     frame: Box<CallFrame>,
-    interpreter_result: InterpreterResult,
+    mut interpreter_result: InterpreterResult,
 ) -> Result<CallOutcome, EVMError<DB::Error>> {
     context
         .evm
-        .call_return(&interpreter_result, frame.frame_data.checkpoint);
-    Ok(CallOutcome::new(
-        interpreter_result,
-        frame.return_memory_range,
-    ))
+        .call_return(&mut interpreter_result, frame.frame_data.checkpoint);
+    let return_memory_range = frame.return_memory_range.clone();
+    context.evm.frame_pool.recycle_call(frame);
+    Ok(CallOutcome::new(interpreter_result, return_memory_range))
 }
 
 #[inline]
@@ -121,9 +120,11 @@ pub fn create_return<SPEC: Spec, EXT, DB: Database>(
         frame.created_address,
         frame.frame_data.checkpoint,
     );
+    let created_address = frame.created_address;
+    context.evm.frame_pool.recycle_create(frame);
     Ok(CreateOutcome::new(
         interpreter_result,
-        Some(frame.created_address),
+        Some(created_address),
     ))
 }
 
@@ -161,9 +162,11 @@ pub fn eofcreate_return<SPEC: Spec, EXT, DB: Database>(
         frame.created_address,
         frame.frame_data.checkpoint,
     );
+    let created_address = frame.created_address;
+    context.evm.frame_pool.recycle_eofcreate(frame);
     Ok(CreateOutcome::new(
         interpreter_result,
-        Some(frame.created_address),
+        Some(created_address),
     ))
 }
 
@@ -190,8 +193,18 @@ mod tests {
 
     /// Creates frame result.
     fn call_last_frame_return(instruction_result: InstructionResult, gas: Gas) -> Gas {
+        call_last_frame_return_with_quotient(instruction_result, gas, None)
+    }
+
+    /// Creates frame result with the given refund quotient.
+    fn call_last_frame_return_with_quotient(
+        instruction_result: InstructionResult,
+        gas: Gas,
+        refund_quotient: Option<u64>,
+    ) -> Gas {
         let mut env = Env::default();
         env.tx.gas_limit = 100;
+        env.cfg.refund_quotient = refund_quotient;
 
         let mut ctx = Context::new_empty();
         ctx.evm.inner.env = Box::new(env);
@@ -200,6 +213,7 @@ mod tests {
                 result: instruction_result,
                 output: Bytes::new(),
                 gas,
+                halt_context: None,
             },
             0..0,
         ));
@@ -224,7 +238,8 @@ mod tests {
         let gas = call_last_frame_return(InstructionResult::Stop, return_gas);
         assert_eq!(gas.remaining(), 90);
         assert_eq!(gas.spent(), 10);
-        assert_eq!(gas.refunded(), 2);
+        assert_eq!(gas.refunded(), 30);
+        assert_eq!(gas.final_refund(), 2);
 
         let gas = call_last_frame_return(InstructionResult::Revert, return_gas);
         assert_eq!(gas.remaining(), 90);
@@ -232,6 +247,44 @@ mod tests {
         assert_eq!(gas.refunded(), 0);
     }
 
+    #[test]
+    fn test_consume_gas_with_refund_quotient() {
+        let mut return_gas = Gas::new(50);
+        return_gas.record_refund(30);
+
+        // min(30, 50/10)
+        let gas =
+            call_last_frame_return_with_quotient(InstructionResult::Stop, return_gas, Some(10));
+        assert_eq!(gas.spent(), 50);
+        assert_eq!(gas.refunded(), 30);
+        assert_eq!(gas.final_refund(), 5);
+
+        // Refunds are disabled, the accumulated refund is kept.
+        let gas =
+            call_last_frame_return_with_quotient(InstructionResult::Stop, return_gas, Some(0));
+        assert_eq!(gas.spent(), 50);
+        assert_eq!(gas.refunded(), 30);
+        assert_eq!(gas.final_refund(), 0);
+
+        for quotient in [Some(10), Some(0)] {
+            let gas = call_last_frame_return_with_quotient(
+                InstructionResult::Revert,
+                return_gas,
+                quotient,
+            );
+            assert_eq!(gas.spent(), 50);
+            assert_eq!(gas.final_refund(), 0);
+        }
+
+        let mut gas = Gas::new(100);
+        assert!(gas.record_cost(60));
+        gas.record_refund(30);
+        assert_eq!(gas.final_refund_with_quotient(0), 0);
+        assert_eq!(gas.final_refund_with_quotient(1), 30);
+        assert_eq!(gas.final_refund_with_quotient(2), 30);
+        assert_eq!(gas.final_refund_with_quotient(5), 12);
+    }
+
     #[test]
     fn test_revert_gas() {
         let gas = call_last_frame_return(InstructionResult::Revert, Gas::new(90));
@@ -1,7 +1,8 @@
 use crate::{
-    interpreter::{Gas, SuccessOrHalt},
+    interpreter::{gas, Gas, SuccessOrHalt},
     primitives::{
-        db::Database, EVMError, ExecutionResult, ResultAndState, Spec, SpecId, SpecId::LONDON, U256,
+        db::Database, gas_fee, gas_to_u256, refund_to_gas, u256_to_gas, Bytes, CfgEnv, EVMError,
+        ExecutionResult, FeeSummary, Output, ResultAndState, Spec, SpecId::LONDON,
     },
     Context, FrameResult,
 };
@@ -29,7 +30,7 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     gas: &Gas,
 ) -> Result<(), EVMError<DB::Error>> {
-    let beneficiary = context.evm.env.block.coinbase;
+    let beneficiary = context.evm.env.fee_recipient();
     let effective_gas_price = context.evm.env.effective_gas_price();
 
     // transfer fee to coinbase/beneficiary.
@@ -40,6 +41,12 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
         effective_gas_price
     };
 
+    let gas_used = gas.spent_sub_refunded();
+    let paid_to_coinbase = gas_fee(coinbase_gas_price, gas_used)?;
+    let burned = gas_fee(effective_gas_price - coinbase_gas_price, gas_used)?;
+
+    // Only the account is loaded, a beneficiary that is a contract or an EIP-7702 delegated
+    // account is paid without loading its code or following its delegation.
     let coinbase_account = context
         .evm
         .inner
@@ -51,13 +58,37 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
         .data
         .info
         .balance
-        .saturating_add(coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64));
+        .saturating_add(paid_to_coinbase);
+
+    let mut summary = FeeSummary {
+        effective_gas_price,
+        tip_per_gas: coinbase_gas_price,
+        burned,
+        paid_to_coinbase,
+        blob_fee_burned: context.evm.env.calc_data_fee().unwrap_or_default(),
+        blob_gas_price: (!context.evm.env.tx.blob_hashes.is_empty())
+            .then(|| context.evm.env.block.get_blob_gasprice())
+            .flatten(),
+        ..Default::default()
+    };
+    set_fee_summary_gas(&mut summary, gas);
+    context.evm.inner.fee_summary = Some(summary);
 
     Ok(())
 }
 
+/// Records the gas used by the transaction and its refund, before and after the cap, in the fee
+/// summary.
+#[inline]
+pub fn set_fee_summary_gas(summary: &mut FeeSummary, gas: &Gas) {
+    summary.gas_used_pre_refund = gas.spent();
+    summary.refund_accumulated = refund_to_gas(gas.refunded()).unwrap_or_default();
+    summary.refund_applied = refund_to_gas(gas.final_refund()).unwrap_or_default();
+    summary.gas_used_final = gas.spent_sub_refunded();
+}
+
 pub fn refund<SPEC: Spec, EXT, DB: Database>(
-    _context: &mut Context<EXT, DB>,
+    context: &mut Context<EXT, DB>,
     gas: &mut Gas,
     eip7702_refund: i64,
 ) {
@@ -66,7 +97,14 @@ pub fn refund<SPEC: Spec, EXT, DB: Database>(
     // Calculate gas refund for transaction.
     // If spec is set to london, it will decrease the maximum refund amount to 5th part of
     // gas spend. (Before london it was 2th part of gas spend)
-    gas.set_final_refund(SPEC::SPEC_ID.is_enabled_in(SpecId::LONDON));
+    gas.set_final_refund_with_quotient(refund_quotient::<SPEC>(&context.evm.inner.env.cfg));
+}
+
+/// Returns the max refund quotient, the configured one or the one of the spec.
+#[inline]
+pub fn refund_quotient<SPEC: Spec>(cfg: &CfgEnv) -> u64 {
+    cfg.refund_quotient
+        .unwrap_or(gas::max_refund_quotient(SPEC::SPEC_ID))
 }
 
 #[inline]
@@ -76,6 +114,11 @@ pub fn reimburse_caller<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<(), EVMError<DB::Error>> {
     let caller = context.evm.env.tx.caller;
     let effective_gas_price = context.evm.env.effective_gas_price();
+    // The sum can't overflow 256 bits, it only fails to convert back if it exceeds `u64::MAX`.
+    let reimbursed_gas = u256_to_gas(
+        gas_to_u256(gas.remaining()) + gas_to_u256(refund_to_gas(gas.final_refund())?),
+    )?;
+    let reimbursed = gas_fee(effective_gas_price, reimbursed_gas)?;
 
     // return balance of not spend gas.
     let caller_account = context
@@ -84,10 +127,7 @@ pub fn reimburse_caller<SPEC: Spec, EXT, DB: Database>(
         .journaled_state
         .load_account(caller, &mut context.evm.inner.db)?;
 
-    caller_account.data.info.balance =
-        caller_account.data.info.balance.saturating_add(
-            effective_gas_price * U256::from(gas.remaining() + gas.refunded() as u64),
-        );
+    caller_account.data.info.balance = caller_account.data.info.balance.saturating_add(reimbursed);
 
     Ok(())
 }
@@ -100,9 +140,15 @@ pub fn output<EXT, DB: Database>(
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
     context.evm.take_error()?;
     // used gas with refund calculated.
-    let gas_refunded = result.gas().refunded() as u64;
+    let gas_refunded = refund_to_gas(result.gas().final_refund())?;
     let final_gas_used = result.gas().spent() - gas_refunded;
-    let output = result.output();
+    let mut output = result.output();
+    if context.evm.env.cfg.discard_output {
+        output = match output {
+            Output::Call(_) => Output::Call(Bytes::new()),
+            Output::Create(_, address) => Output::Create(Bytes::new(), address),
+        };
+    }
     let instruction_result = result.into_interpreter_result();
 
     // reset journal and return present state.
@@ -123,6 +169,7 @@ pub fn output<EXT, DB: Database>(
         SuccessOrHalt::Halt(reason) => ExecutionResult::Halt {
             reason,
             gas_used: final_gas_used,
+            context: instruction_result.halt_context,
         },
         // Only two internal return flags.
         flag @ (SuccessOrHalt::FatalExternalError | SuccessOrHalt::Internal(_)) => {
@@ -135,3 +182,86 @@ pub fn output<EXT, DB: Database>(
 
     Ok(ResultAndState { result, state })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::EmptyDB,
+        primitives::{address, Address, ArithmeticError, CancunSpec, U256},
+    };
+
+    const CALLER: Address = address!("0000000000000000000000000000000000000001");
+    const COINBASE: Address = address!("0000000000000000000000000000000000000002");
+
+    fn context(gas_price: U256) -> Context<(), EmptyDB> {
+        let mut context = Context::new_empty();
+        context.evm.inner.env.tx.caller = CALLER;
+        context.evm.inner.env.tx.gas_price = gas_price;
+        context.evm.inner.env.block.coinbase = COINBASE;
+        context
+    }
+
+    fn balance(context: &Context<(), EmptyDB>, address: Address) -> U256 {
+        context.evm.inner.journaled_state.state[&address]
+            .info
+            .balance
+    }
+
+    #[test]
+    fn settles_near_max_gas_limit() {
+        let mut context = context(U256::from(3));
+        let mut gas = Gas::new_spent(u64::MAX);
+        gas.erase_cost(u64::MAX - 50_000);
+        gas.record_refund(20_000);
+        refund::<CancunSpec, _, _>(&mut context, &mut gas, 0);
+        assert_eq!(gas.final_refund(), 10_000);
+
+        reimburse_caller::<CancunSpec, _, _>(&mut context, &gas).unwrap();
+        reward_beneficiary::<CancunSpec, _, _>(&mut context, &gas).unwrap();
+        assert_eq!(
+            balance(&context, CALLER),
+            U256::from(u64::MAX - 40_000) * U256::from(3)
+        );
+        assert_eq!(balance(&context, COINBASE), U256::from(40_000 * 3));
+    }
+
+    #[test]
+    fn fee_overflow_is_an_arithmetic_error() {
+        let mut context = context(U256::MAX);
+        let mut gas = Gas::new_spent(u64::MAX);
+        gas.erase_cost(1);
+
+        // The unused gas is reimbursed in full, the fee of the used gas doesn't fit in 256 bits.
+        reimburse_caller::<CancunSpec, _, _>(&mut context, &gas).unwrap();
+        assert_eq!(balance(&context, CALLER), U256::MAX);
+        assert_eq!(
+            reward_beneficiary::<CancunSpec, _, _>(&mut context, &gas),
+            Err(EVMError::Arithmetic(ArithmeticError::FeeOverflow {
+                price: U256::MAX,
+                gas: u64::MAX - 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn negative_refund_is_an_arithmetic_error() {
+        let mut context = context(U256::from(1));
+        let mut gas = Gas::new_spent(100);
+        gas.erase_cost(50);
+        gas.set_refund(-5);
+
+        assert_eq!(
+            reimburse_caller::<CancunSpec, _, _>(&mut context, &gas),
+            Err(EVMError::Arithmetic(ArithmeticError::NegativeGas {
+                value: -5
+            }))
+        );
+
+        // The fee summary reports no refund instead.
+        let mut summary = FeeSummary::default();
+        set_fee_summary_gas(&mut summary, &gas);
+        assert_eq!(summary.refund_accumulated, 0);
+        assert_eq!(summary.refund_applied, 0);
+    }
+}
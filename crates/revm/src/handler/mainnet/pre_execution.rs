@@ -3,14 +3,15 @@
 //! They handle initial setup of the EVM, call loop and the final return of the EVM
 
 use crate::{
-    precompile::PrecompileSpecId,
+    precompile::{PrecompileSpecId, Precompiles},
     primitives::{
         db::Database,
-        eip7702, Account, Bytecode, EVMError, Env, Spec,
+        tx_fee_charge, Account, Address, AuthorizationOutcome, Bytecode, EVMError, Env,
+        RecoveredAuthorization, Spec, SpecId,
         SpecId::{CANCUN, PRAGUE, SHANGHAI},
         TxKind, BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256,
     },
-    Context, ContextPrecompiles,
+    Context, ContextPrecompiles, JournaledState,
 };
 
 /// Main precompile load
@@ -27,25 +28,35 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     // set journaling state flag.
     context.evm.journaled_state.set_spec_id(SPEC::SPEC_ID);
 
-    // load coinbase
-    // EIP-3651: Warm COINBASE. Starts the `COINBASE` address warm
-    if SPEC::enabled(SHANGHAI) {
-        let coinbase = context.evm.inner.env.block.coinbase;
+    // EIP-3651 and EIP-2935, the coinbase and the historical block hashes contract are warm.
+    let block = &context.evm.inner.env.block;
+    for address in preloaded_warm_addresses(block.coinbase, SPEC::SPEC_ID) {
         context
             .evm
             .journaled_state
             .warm_preloaded_addresses
-            .insert(coinbase);
+            .insert(address);
+        #[cfg(feature = "access-debug")]
+        context.evm.journaled_state.record_warm_address(
+            address,
+            if address == BLOCKHASH_STORAGE_ADDRESS {
+                crate::WarmingReason::BlockHashHistory
+            } else {
+                crate::WarmingReason::Coinbase
+            },
+        );
     }
 
-    // Load blockhash storage address
-    // EIP-2935: Serve historical block hashes from state
-    if SPEC::enabled(PRAGUE) {
-        context
-            .evm
-            .journaled_state
-            .warm_preloaded_addresses
-            .insert(BLOCKHASH_STORAGE_ADDRESS);
+    // EIP-2929: the sender and the recipient of the transaction are warm.
+    #[cfg(feature = "access-debug")]
+    {
+        let tx = &context.evm.inner.env.tx;
+        let (caller, target) = (tx.caller, tx.transact_to.to().copied());
+        let journaled_state = &mut context.evm.journaled_state;
+        journaled_state.record_warm_address(caller, crate::WarmingReason::TxCaller);
+        if let Some(target) = target {
+            journaled_state.record_warm_address(target, crate::WarmingReason::TxTarget);
+        }
     }
 
     // Load access list
@@ -53,18 +64,87 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     Ok(())
 }
 
+/// Returns the addresses that are warm at the start of a transaction of the spec, see
+/// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929).
+///
+/// These are, in order:
+/// - the caller and the target of a call transaction,
+/// - the coinbase from Shanghai, see [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651),
+/// - the historical block hashes contract from Prague, see
+///   [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935),
+/// - the addresses of the access list,
+/// - the authorities of the EIP-7702 authorizations that pass the chain id and nonce checks and
+///   are recoverable, from Prague, even if the authorization is not applied,
+/// - the precompiles of the spec, unless [`CfgEnv::warm_precompiles`] is disabled.
+///
+/// Addresses can repeat. The precompiles are the standard ones, an EVM with custom precompiles
+/// warms the addresses of [`ContextPrecompiles::addresses`] instead. See
+/// [`optimism::warm_addresses_for_tx`](crate::optimism::warm_addresses_for_tx) for OP Stack
+/// chains.
+///
+/// [`CfgEnv::warm_precompiles`]: crate::primitives::CfgEnv::warm_precompiles
+pub fn warm_addresses_for_tx(env: &Env, spec: SpecId) -> impl Iterator<Item = Address> + '_ {
+    let precompiles = Precompiles::new(PrecompileSpecId::from_spec_id(spec));
+    warm_addresses_with_precompiles(env, spec, precompiles)
+}
+
+/// Same as [`warm_addresses_for_tx`] with the given precompiles.
+#[inline]
+pub(crate) fn warm_addresses_with_precompiles<'a>(
+    env: &'a Env,
+    spec: SpecId,
+    precompiles: &'static Precompiles,
+) -> impl Iterator<Item = Address> + 'a {
+    let tx = &env.tx;
+    let authorities = tx
+        .authorization_list
+        .as_ref()
+        .filter(|_| spec.is_enabled_in(PRAGUE))
+        .into_iter()
+        .flat_map(|list| list.recovered_iter())
+        .filter(|authorization| {
+            let chain_id = *authorization.chain_id();
+            (chain_id.is_zero() || chain_id == U256::from(env.cfg.chain_id))
+                && authorization.nonce() != u64::MAX
+        })
+        .filter_map(|authorization| authorization.authority());
+    let precompiles = env
+        .cfg
+        .warm_precompiles
+        .then(|| precompiles.addresses().copied())
+        .into_iter()
+        .flatten();
+
+    core::iter::once(tx.caller)
+        .chain(tx.transact_to.to().copied())
+        .chain(preloaded_warm_addresses(env.block.coinbase, spec))
+        .chain(tx.access_list.iter().map(|item| item.address))
+        .chain(authorities)
+        .chain(precompiles)
+}
+
+/// Addresses that are warm at the start of the transaction without being loaded.
+#[inline]
+fn preloaded_warm_addresses(coinbase: Address, spec: SpecId) -> impl Iterator<Item = Address> {
+    let coinbase = spec.is_enabled_in(SHANGHAI).then_some(coinbase);
+    let block_hashes = spec
+        .is_enabled_in(PRAGUE)
+        .then_some(BLOCKHASH_STORAGE_ADDRESS);
+    coinbase.into_iter().chain(block_hashes)
+}
+
 /// Helper function that deducts the caller balance.
 #[inline]
 pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env) {
+    // EIP-4844
+    let data_fee = if SPEC::enabled(CANCUN) {
+        env.calc_data_fee().expect("already checked")
+    } else {
+        U256::ZERO
+    };
     // Subtract gas costs from the caller's account.
     // We need to saturate the gas cost to prevent underflow in case that `disable_balance_check` is enabled.
-    let mut gas_cost = U256::from(env.tx.gas_limit).saturating_mul(env.effective_gas_price());
-
-    // EIP-4844
-    if SPEC::enabled(CANCUN) {
-        let data_fee = env.calc_data_fee().expect("already checked");
-        gas_cost = gas_cost.saturating_add(data_fee);
-    }
+    let gas_cost = tx_fee_charge(env.tx.gas_limit, env.effective_gas_price(), data_fee);
 
     // set new caller account balance.
     caller_account.info.balance = caller_account.info.balance.saturating_sub(gas_cost);
@@ -84,6 +164,9 @@ pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env)
 pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
+    // Fees are recorded in `reward_beneficiary`.
+    context.evm.inner.fee_summary = None;
+
     // load caller's account.
     let caller_account = context
         .evm
@@ -108,74 +191,313 @@ pub fn apply_eip7702_auth_list<SPEC: Spec, EXT, DB: Database>(
     }
 
     // return if there is no auth list.
-    let Some(authorization_list) = context.evm.inner.env.tx.authorization_list.as_ref() else {
+    let inner = &mut context.evm.inner;
+    let Some(authorization_list) = inner.env.tx.authorization_list.as_ref() else {
         return Ok(0);
     };
 
-    let mut refunded_accounts = 0;
+    let mut refunded_gas = 0;
     for authorization in authorization_list.recovered_iter() {
-        // 1. Verify the chain id is either 0 or the chain's current ID.
-        let chain_id = *authorization.chain_id();
-        if !chain_id.is_zero() && chain_id != U256::from(context.evm.inner.env.cfg.chain_id) {
-            continue;
-        }
+        refunded_gas += apply_eip7702_authorization(
+            &mut inner.journaled_state,
+            &mut inner.db,
+            &inner.env,
+            &authorization,
+        )?
+        .refund();
+    }
+
+    Ok(refunded_gas)
+}
+
+/// Applies an authorization of the EIP-7702 authorization list to its authority, following the
+/// steps of the EIP.
+fn apply_eip7702_authorization<DB: Database>(
+    journaled_state: &mut JournaledState,
+    db: &mut DB,
+    env: &Env,
+    authorization: &RecoveredAuthorization,
+) -> Result<AuthorizationOutcome, EVMError<DB::Error>> {
+    // 1. Verify the chain id is either 0 or the chain's current ID.
+    let chain_id = *authorization.chain_id();
+    if !chain_id.is_zero() && chain_id != U256::from(env.cfg.chain_id) {
+        return Ok(AuthorizationOutcome::Skipped);
+    }
+
+    // 2. Verify the `nonce` is less than `2**64 - 1`.
+    if authorization.nonce() == u64::MAX {
+        return Ok(AuthorizationOutcome::Skipped);
+    }
+
+    // recover authority and authorized addresses.
+    // 3. `authority = ecrecover(keccak(MAGIC || rlp([chain_id, address, nonce])), y_parity, r, s]`
+    let Some(authority) = authorization.authority() else {
+        return Ok(AuthorizationOutcome::Skipped);
+    };
+
+    // warm authority account and check nonce.
+    // 4. Add `authority` to `accessed_addresses` (as defined in [EIP-2929](./eip-2929.md).)
+    let mut authority_acc = journaled_state.load_code(authority, db)?;
 
-        // 2. Verify the `nonce` is less than `2**64 - 1`.
-        if authorization.nonce() == u64::MAX {
-            continue;
+    // 5. Verify the code of `authority` is either empty or already delegated.
+    if let Some(bytecode) = &authority_acc.info.code {
+        // if it is not empty and it is not eip7702
+        if !bytecode.is_empty() && !bytecode.is_eip7702() {
+            return Ok(AuthorizationOutcome::Skipped);
         }
+    }
+
+    // 6. Verify the nonce of `authority` is equal to `nonce`. In case `authority` does not exist in the trie, verify that `nonce` is equal to `0`.
+    if authorization.nonce() != authority_acc.info.nonce {
+        return Ok(AuthorizationOutcome::Skipped);
+    }
+
+    // 7. Add `PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST` gas to the global refund counter if `authority` exists in the trie.
+    let authority_existed = !authority_acc.is_empty();
+
+    // 8. Set the code of `authority` to be `0xef0100 || address`. This is a delegation designation.
+    //  * As a special case, if `address` is `0x0000000000000000000000000000000000000000` do not write the designation. Clear the accounts code and reset the account's code hash to the empty hash `0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470`.
+    let (bytecode, hash) = if authorization.address.is_zero() {
+        (Bytecode::default(), KECCAK_EMPTY)
+    } else {
+        let bytecode = Bytecode::new_eip7702(authorization.address);
+        let hash = env.cfg.keccak.code_hash(&bytecode);
+        (bytecode, hash)
+    };
+    authority_acc.info.code_hash = hash;
+    authority_acc.info.code = Some(bytecode);
+
+    // 9. Increase the nonce of `authority` by one.
+    authority_acc.info.nonce = authority_acc.info.nonce.saturating_add(1);
+    authority_acc.mark_touch();
+
+    Ok(AuthorizationOutcome::Applied { authority_existed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{
+            address, eip7702::PER_EXISTING_AUTHORITY_REFUND, eip7702_refund, AccessListItem,
+            AccountInfo, Authorization, EvmState, PragueSpec, RecoveredAuthority,
+        },
+    };
+    use std::{vec, vec::Vec};
+
+    #[test]
+    fn warm_addresses() {
+        let caller = address!("00000000000000000000000000000000000000c0");
+        let target = address!("00000000000000000000000000000000000000d0");
+        let coinbase = address!("00000000000000000000000000000000000000e0");
+        let listed = address!("00000000000000000000000000000000000000f0");
+        let authority = address!("0000000000000000000000000000000000000100");
+        let other_chain = address!("0000000000000000000000000000000000000101");
 
-        // recover authority and authorized addresses.
-        // 3. `authority = ecrecover(keccak(MAGIC || rlp([chain_id, address, nonce])), y_parity, r, s]`
-        let Some(authority) = authorization.authority() else {
-            continue;
+        let authorization = |chain_id: u64, authority: Address| {
+            RecoveredAuthorization::new_unchecked(
+                Authorization {
+                    chain_id: U256::from(chain_id),
+                    address: Address::ZERO,
+                    nonce: 0,
+                },
+                RecoveredAuthority::Valid(authority),
+            )
         };
+        let mut env = Env::default();
+        env.cfg.warm_precompiles = false;
+        env.tx.caller = caller;
+        env.tx.transact_to = TxKind::Call(target);
+        env.block.coinbase = coinbase;
+        env.tx.access_list = vec![AccessListItem {
+            address: listed,
+            storage_keys: Vec::new(),
+        }];
+        env.tx.authorization_list = Some(
+            vec![
+                authorization(env.cfg.chain_id, authority),
+                authorization(env.cfg.chain_id + 1, other_chain),
+            ]
+            .into(),
+        );
 
-        // warm authority account and check nonce.
-        // 4. Add `authority` to `accessed_addresses` (as defined in [EIP-2929](./eip-2929.md).)
-        let mut authority_acc = context
-            .evm
-            .inner
-            .journaled_state
-            .load_code(authority, &mut context.evm.inner.db)?;
-
-        // 5. Verify the code of `authority` is either empty or already delegated.
-        if let Some(bytecode) = &authority_acc.info.code {
-            // if it is not empty and it is not eip7702
-            if !bytecode.is_empty() && !bytecode.is_eip7702() {
-                continue;
-            }
+        let cases: [(SpecId, &[Address]); 4] = [
+            (SpecId::BERLIN, &[caller, target, listed]),
+            (SpecId::SHANGHAI, &[caller, target, coinbase, listed]),
+            (SpecId::CANCUN, &[caller, target, coinbase, listed]),
+            (
+                SpecId::PRAGUE,
+                &[
+                    caller,
+                    target,
+                    coinbase,
+                    BLOCKHASH_STORAGE_ADDRESS,
+                    listed,
+                    authority,
+                ],
+            ),
+        ];
+        for (spec, expected) in cases {
+            let warm: Vec<_> = warm_addresses_for_tx(&env, spec).collect();
+            assert_eq!(warm, expected, "{spec:?}");
         }
 
-        // 6. Verify the nonce of `authority` is equal to `nonce`. In case `authority` does not exist in the trie, verify that `nonce` is equal to `0`.
-        if authorization.nonce() != authority_acc.info.nonce {
-            continue;
-        }
+        // The precompiles of the spec are warm unless disabled.
+        env.cfg.warm_precompiles = true;
+        let precompiles = Precompiles::new(PrecompileSpecId::CANCUN);
+        let warm: Vec<_> = warm_addresses_for_tx(&env, SpecId::CANCUN).collect();
+        assert_eq!(warm.len(), 4 + precompiles.len());
+        assert!(precompiles
+            .addresses()
+            .all(|address| warm.contains(address)));
+    }
 
-        // 7. Add `PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST` gas to the global refund counter if `authority` exists in the trie.
-        if !authority_acc.is_empty() {
-            refunded_accounts += 1;
+    const CALLER: Address = address!("00000000000000000000000000000000000000c0");
+    const AUTHORITY: Address = address!("0000000000000000000000000000000000000100");
+    const DELEGATE: Address = address!("0000000000000000000000000000000000000200");
+
+    /// Applies the authorizations, of `(authority, nonce, address)`, of a Prague call from
+    /// [`CALLER`], returning the refund and the state.
+    fn apply_authorizations(
+        db: InMemoryDB,
+        authorizations: &[(Address, u64, Address)],
+    ) -> (u64, EvmState) {
+        let mut context = Context::new_with_db(db);
+        let env = &mut context.evm.inner.env;
+        env.tx.caller = CALLER;
+        env.tx.transact_to = TxKind::Call(DELEGATE);
+        env.tx.authorization_list = Some(
+            authorizations
+                .iter()
+                .map(|&(authority, nonce, address)| {
+                    RecoveredAuthorization::new_unchecked(
+                        Authorization {
+                            chain_id: U256::ZERO,
+                            address,
+                            nonce,
+                        },
+                        RecoveredAuthority::Valid(authority),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        load_accounts::<PragueSpec, _, _>(&mut context).unwrap();
+        deduct_caller::<PragueSpec, _, _>(&mut context).unwrap();
+        let refund = apply_eip7702_auth_list::<PragueSpec, _, _>(&mut context).unwrap();
+        (refund, context.evm.journaled_state.finalize().0)
+    }
+
+    fn db_with(accounts: &[(Address, AccountInfo)]) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1)));
+        for (address, info) in accounts {
+            db.insert_account_info(*address, info.clone());
         }
+        db
+    }
 
-        // 8. Set the code of `authority` to be `0xef0100 || address`. This is a delegation designation.
-        //  * As a special case, if `address` is `0x0000000000000000000000000000000000000000` do not write the designation. Clear the accounts code and reset the account's code hash to the empty hash `0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470`.
-        let (bytecode, hash) = if authorization.address.is_zero() {
-            (Bytecode::default(), KECCAK_EMPTY)
-        } else {
-            let bytecode = Bytecode::new_eip7702(authorization.address);
-            let hash = bytecode.hash_slow();
-            (bytecode, hash)
+    #[test]
+    fn eip7702_refund_of_outcomes() {
+        let outcomes = [
+            AuthorizationOutcome::Skipped,
+            AuthorizationOutcome::Applied {
+                authority_existed: false,
+            },
+            AuthorizationOutcome::Applied {
+                authority_existed: true,
+            },
+            AuthorizationOutcome::Applied {
+                authority_existed: true,
+            },
+        ];
+        assert_eq!(PER_EXISTING_AUTHORITY_REFUND, 12_500);
+        assert_eq!(
+            eip7702_refund(&outcomes),
+            2 * PER_EXISTING_AUTHORITY_REFUND as i64
+        );
+        assert_eq!(eip7702_refund(&[]), 0);
+    }
+
+    #[test]
+    fn eip7702_refund_fresh_authority() {
+        let (refund, state) = apply_authorizations(db_with(&[]), &[(AUTHORITY, 0, DELEGATE)]);
+        assert_eq!(refund, 0);
+        let authority = &state[&AUTHORITY].info;
+        assert_eq!(authority.code, Some(Bytecode::new_eip7702(DELEGATE)));
+        assert_eq!(authority.nonce, 1);
+
+        // A funded authority without code exists.
+        let funded = AccountInfo::from_balance(U256::from(1));
+        let (refund, _) =
+            apply_authorizations(db_with(&[(AUTHORITY, funded)]), &[(AUTHORITY, 0, DELEGATE)]);
+        assert_eq!(refund, PER_EXISTING_AUTHORITY_REFUND);
+    }
+
+    #[test]
+    fn eip7702_refund_existing_delegation() {
+        let delegated = AccountInfo {
+            nonce: 1,
+            ..AccountInfo::from_bytecode(Bytecode::new_eip7702(CALLER))
         };
-        authority_acc.info.code_hash = hash;
-        authority_acc.info.code = Some(bytecode);
+        let db = db_with(&[(AUTHORITY, delegated)]);
+
+        // Overwriting the delegation.
+        let (refund, state) = apply_authorizations(db.clone(), &[(AUTHORITY, 1, DELEGATE)]);
+        assert_eq!(refund, PER_EXISTING_AUTHORITY_REFUND);
+        assert_eq!(
+            state[&AUTHORITY].info.code,
+            Some(Bytecode::new_eip7702(DELEGATE))
+        );
 
-        // 9. Increase the nonce of `authority` by one.
-        authority_acc.info.nonce = authority_acc.info.nonce.saturating_add(1);
-        authority_acc.mark_touch();
+        // Clearing the delegation.
+        let (refund, state) = apply_authorizations(db.clone(), &[(AUTHORITY, 1, Address::ZERO)]);
+        assert_eq!(refund, PER_EXISTING_AUTHORITY_REFUND);
+        let authority = &state[&AUTHORITY].info;
+        assert_eq!(authority.code_hash, KECCAK_EMPTY);
+        assert!(authority.code.as_ref().unwrap().is_empty());
+        assert_eq!(authority.nonce, 2);
+
+        // An authorization with a stale nonce is skipped.
+        let (refund, state) = apply_authorizations(db, &[(AUTHORITY, 0, DELEGATE)]);
+        assert_eq!(refund, 0);
+        assert_eq!(
+            state[&AUTHORITY].info.code,
+            Some(Bytecode::new_eip7702(CALLER))
+        );
     }
 
-    let refunded_gas =
-        refunded_accounts * (eip7702::PER_EMPTY_ACCOUNT_COST - eip7702::PER_AUTH_BASE_COST);
+    #[test]
+    fn eip7702_refund_repeated_authority() {
+        // The first authorization creates the authority, the second is applied to an existing
+        // account and refunded. The last one has a stale nonce.
+        let (refund, state) = apply_authorizations(
+            db_with(&[]),
+            &[
+                (AUTHORITY, 0, DELEGATE),
+                (AUTHORITY, 1, Address::ZERO),
+                (AUTHORITY, 1, DELEGATE),
+            ],
+        );
+        assert_eq!(refund, PER_EXISTING_AUTHORITY_REFUND);
+        let authority = &state[&AUTHORITY].info;
+        assert_eq!(authority.code_hash, KECCAK_EMPTY);
+        assert_eq!(authority.nonce, 2);
+    }
 
-    Ok(refunded_gas)
+    #[test]
+    fn eip7702_refund_authority_is_caller() {
+        // The nonce of the caller is bumped before the authorizations are applied.
+        let (refund, state) = apply_authorizations(db_with(&[]), &[(CALLER, 0, DELEGATE)]);
+        assert_eq!(refund, 0);
+        assert_eq!(state[&CALLER].info.nonce, 1);
+        assert_eq!(state[&CALLER].info.code_hash, KECCAK_EMPTY);
+
+        let (refund, state) = apply_authorizations(db_with(&[]), &[(CALLER, 1, DELEGATE)]);
+        assert_eq!(refund, PER_EXISTING_AUTHORITY_REFUND);
+        let caller = &state[&CALLER].info;
+        assert_eq!(caller.code, Some(Bytecode::new_eip7702(DELEGATE)));
+        assert_eq!(caller.nonce, 2);
+    }
 }
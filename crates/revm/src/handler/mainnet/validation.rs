@@ -1,11 +1,67 @@
-use revm_interpreter::gas::{self, InitialAndFloorGas};
+use revm_interpreter::gas::{self, InitialAndFloorGas, IntrinsicGasComponents};
 
+#[cfg(feature = "secp256k1-recover")]
+use crate::primitives::Address;
 use crate::{
     handler::SpecId,
-    primitives::{db::Database, EVMError, Env, InvalidTransaction, Spec},
+    primitives::{db::Database, EVMError, Env, InvalidTransaction, Spec, TxEnv},
     Context,
 };
 
+/// Intrinsic gas of a transaction, see [`calculate_intrinsic_gas`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntrinsicGas {
+    /// Gas charged before execution starts.
+    pub total: u64,
+    /// EIP-7623 floor of the gas used, zero before Prague.
+    pub floor: u64,
+    /// Itemization of [`Self::total`].
+    pub components: IntrinsicGasComponents,
+}
+
+impl From<IntrinsicGas> for InitialAndFloorGas {
+    fn from(gas: IntrinsicGas) -> Self {
+        Self {
+            initial_gas: gas.total,
+            floor_gas: gas.floor,
+        }
+    }
+}
+
+/// Calculates the intrinsic gas of the transaction.
+///
+/// On Optimism, deposit transactions are charged the same intrinsic gas on all forks, Regolith
+/// only changes how their gas used is reported.
+pub fn calculate_intrinsic_gas(tx: &TxEnv, spec: SpecId) -> IntrinsicGas {
+    let authorization_list_num = tx
+        .authorization_list
+        .as_ref()
+        .map(|l| l.len() as u64)
+        .unwrap_or_default();
+    let is_create = tx.transact_to.is_create();
+
+    let components = gas::calculate_initial_tx_gas_components(
+        spec,
+        &tx.data,
+        is_create,
+        &tx.access_list,
+        authorization_list_num,
+    );
+
+    // EIP-7623
+    let floor = if spec.is_enabled_in(SpecId::PRAGUE) {
+        gas::calc_tx_floor_cost(gas::get_tokens_in_calldata(&tx.data, true))
+    } else {
+        0
+    };
+
+    IntrinsicGas {
+        total: components.total(),
+        floor,
+        components,
+    }
+}
+
 /// Validate environment for the mainnet.
 pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
     // Important: validate block before tx.
@@ -14,6 +70,28 @@ pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<
     Ok(())
 }
 
+/// Recovers the caller of the transaction from [`TxEnv::signature`] if it is set.
+///
+/// The recovered caller replaces [`TxEnv::caller`] and is returned.
+#[cfg(feature = "secp256k1-recover")]
+pub fn recover_caller<SPEC: Spec, DB: Database>(
+    env: &mut Env,
+) -> Result<Option<Address>, EVMError<DB::Error>> {
+    let Some(signature) = env.tx.signature else {
+        return Ok(None);
+    };
+    let y_parity = signature.y_parity(env.cfg.chain_id, SPEC::SPEC_ID)?;
+    let caller = revm_precompile::secp256k1::ecrecover(
+        &signature.to_compact(),
+        y_parity as u8,
+        &signature.signing_hash,
+    )
+    .map_err(|_| InvalidTransaction::InvalidSignature)?;
+    let caller = Address::from_word(caller);
+    env.tx.caller = caller;
+    Ok(Some(caller))
+}
+
 /// Validates transaction against the state.
 pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
@@ -40,23 +118,7 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
 pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
     env: &Env,
 ) -> Result<InitialAndFloorGas, EVMError<DB::Error>> {
-    let input = &env.tx.data;
-    let is_create = env.tx.transact_to.is_create();
-    let access_list = &env.tx.access_list;
-    let authorization_list_num = env
-        .tx
-        .authorization_list
-        .as_ref()
-        .map(|l| l.len() as u64)
-        .unwrap_or_default();
-
-    let gas = gas::calculate_initial_tx_gas(
-        SPEC::SPEC_ID,
-        input,
-        is_create,
-        access_list,
-        authorization_list_num,
-    );
+    let gas: InitialAndFloorGas = calculate_intrinsic_gas(&env.tx, SPEC::SPEC_ID).into();
 
     // Additional check to see if limit is big enough to cover initial gas.
     if gas.initial_gas > env.tx.gas_limit {
@@ -70,3 +132,136 @@ pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
 
     Ok(gas)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{
+        AccessListItem, Address, Authorization, Bytes, RecoveredAuthority, RecoveredAuthorization,
+        TxKind, B256, U256,
+    };
+
+    fn call(data: &'static [u8]) -> TxEnv {
+        TxEnv {
+            transact_to: TxKind::Call(Address::ZERO),
+            data: Bytes::from_static(data),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn intrinsic_gas_per_fork() {
+        let tx = call(&[0, 1]);
+        let frontier = calculate_intrinsic_gas(&tx, SpecId::FRONTIER);
+        assert_eq!(
+            frontier.components,
+            IntrinsicGasComponents {
+                base: 21000,
+                calldata_zero_bytes: 4,
+                calldata_non_zero_bytes: 68,
+                ..Default::default()
+            }
+        );
+        assert_eq!(frontier.total, 21072);
+        assert_eq!(frontier.floor, 0);
+
+        // EIP-2028
+        let istanbul = calculate_intrinsic_gas(&tx, SpecId::ISTANBUL);
+        assert_eq!(istanbul.components.calldata_non_zero_bytes, 16);
+        assert_eq!(istanbul.total, 21020);
+
+        // EIP-2930
+        let mut tx = call(&[]);
+        tx.access_list = vec![AccessListItem {
+            address: Address::ZERO,
+            storage_keys: vec![B256::ZERO, B256::with_last_byte(1)],
+        }];
+        assert_eq!(calculate_intrinsic_gas(&tx, SpecId::ISTANBUL).total, 21000);
+        let berlin = calculate_intrinsic_gas(&tx, SpecId::BERLIN);
+        assert_eq!(berlin.components.access_list_accounts, 2400);
+        assert_eq!(berlin.components.access_list_slots, 3800);
+        assert_eq!(berlin.total, 27200);
+
+        // EIP-2 and EIP-3860
+        let create = TxEnv {
+            transact_to: TxKind::Create,
+            data: Bytes::from_static(&[1; 33]),
+            ..Default::default()
+        };
+        assert_eq!(
+            calculate_intrinsic_gas(&create, SpecId::FRONTIER)
+                .components
+                .base,
+            21000
+        );
+        assert_eq!(
+            calculate_intrinsic_gas(&create, SpecId::HOMESTEAD)
+                .components
+                .base,
+            53000
+        );
+        assert_eq!(
+            calculate_intrinsic_gas(&create, SpecId::MERGE).total,
+            53000 + 33 * 16
+        );
+        let shanghai = calculate_intrinsic_gas(&create, SpecId::SHANGHAI);
+        assert_eq!(shanghai.components.initcode_words, 4);
+        assert_eq!(shanghai.total, 53000 + 33 * 16 + 4);
+    }
+
+    #[test]
+    fn intrinsic_gas_prague() {
+        let mut tx = call(&[1; 10]);
+        let authorization = RecoveredAuthorization::new_unchecked(
+            Authorization {
+                chain_id: U256::from(1),
+                address: Address::ZERO,
+                nonce: 0,
+            },
+            RecoveredAuthority::Valid(Address::ZERO),
+        );
+        tx.authorization_list = Some(vec![authorization.clone(), authorization].into());
+
+        let cancun = calculate_intrinsic_gas(&tx, SpecId::CANCUN);
+        assert_eq!(cancun.total, 21160);
+        assert_eq!(cancun.floor, 0);
+
+        // EIP-7702 and EIP-7623
+        let prague = calculate_intrinsic_gas(&tx, SpecId::PRAGUE);
+        assert_eq!(prague.components.authorization_list, 50000);
+        assert_eq!(prague.total, 71160);
+        assert_eq!(prague.floor, 21000 + 40 * 10);
+    }
+
+    #[test]
+    fn validation_uses_intrinsic_gas() {
+        let env = Env {
+            tx: TxEnv {
+                gas_limit: 30_000,
+                ..call(&[0, 1, 2])
+            },
+            ..Default::default()
+        };
+        let gas =
+            validate_initial_tx_gas::<crate::primitives::PragueSpec, crate::db::EmptyDB>(&env)
+                .unwrap();
+        let intrinsic = calculate_intrinsic_gas(&env.tx, SpecId::PRAGUE);
+        assert_eq!(gas.initial_gas, intrinsic.total);
+        assert_eq!(gas.floor_gas, intrinsic.floor);
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn intrinsic_gas_deposit() {
+        let tx = call(&[0, 1]);
+        let mut deposit = tx.clone();
+        deposit.optimism.source_hash = Some(B256::ZERO);
+        deposit.optimism.is_system_transaction = Some(true);
+        for spec in [SpecId::BEDROCK, SpecId::REGOLITH] {
+            assert_eq!(
+                calculate_intrinsic_gas(&deposit, spec),
+                calculate_intrinsic_gas(&tx, spec)
+            );
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub use handler_register::{inspector_handle_register, GetInspector};
 use crate::{
     interpreter::{
         CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+        SelfDestructOutcome,
     },
     primitives::{db::Database, Address, Log, U256},
     EvmContext,
@@ -161,10 +162,19 @@ pub trait Inspector<DB: Database> {
     }
 
     /// Called when a contract has been self-destructed with funds transferred to target.
+    ///
+    /// The `outcome` tells if the contract is deleted or, since Cancun, only its balance is swept.
     #[inline]
-    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+        outcome: SelfDestructOutcome,
+    ) {
         let _ = contract;
         let _ = target;
         let _ = value;
+        let _ = outcome;
     }
 }
@@ -7,7 +7,7 @@ use revm_interpreter::OpCode;
 
 use crate::{
     inspectors::GasInspector,
-    interpreter::{CallInputs, CreateInputs, Interpreter},
+    interpreter::{CallInputs, CreateInputs, Interpreter, SelfDestructOutcome},
     primitives::{Address, U256},
     Database, EvmContext, Inspector,
 };
@@ -103,10 +103,16 @@ impl<DB: Database> Inspector<DB> for CustomPrintTracer {
         None
     }
 
-    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+    fn selfdestruct(
+        &mut self,
+        contract: Address,
+        target: Address,
+        value: U256,
+        outcome: SelfDestructOutcome,
+    ) {
         println!(
-            "SELFDESTRUCT: contract: {:?}, refund target: {:?}, value {:?}",
-            contract, target, value
+            "SELFDESTRUCT: contract: {:?}, refund target: {:?}, value {:?}, outcome {:?}",
+            contract, target, value, outcome
         );
     }
 }
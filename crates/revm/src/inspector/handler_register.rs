@@ -1,7 +1,7 @@
 use crate::{
     db::Database,
     handler::register::EvmHandler,
-    interpreter::{opcode, InstructionResult, Interpreter},
+    interpreter::{opcode, InstructionResult, Interpreter, SelfDestructOutcome},
     primitives::EVMError,
     Context, FrameOrResult, FrameResult, Inspector, JournalEntry,
 };
@@ -64,26 +64,51 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
 
     // Register selfdestruct function.
     table.update_boxed(opcode::SELFDESTRUCT, |prev, interpreter, host| {
+        let journal_len = host.evm.journaled_state.journal.last().unwrap().len();
         // execute selfdestruct
         prev(interpreter, host);
         // check if selfdestruct was successful and if journal entry is made.
-        match host.evm.journaled_state.journal.last().unwrap().last() {
+        let journal = host.evm.journaled_state.journal.last().unwrap();
+        match journal[journal_len..].last() {
             Some(JournalEntry::AccountDestroyed {
                 address,
                 target,
                 had_balance,
                 ..
             }) => {
-                host.external
-                    .get_inspector()
-                    .selfdestruct(*address, *target, *had_balance);
+                let outcome = if address == target {
+                    SelfDestructOutcome::Burned
+                } else {
+                    SelfDestructOutcome::Deleted
+                };
+                host.external.get_inspector().selfdestruct(
+                    *address,
+                    *target,
+                    *had_balance,
+                    outcome,
+                );
             }
             Some(JournalEntry::BalanceTransfer {
                 from, to, balance, ..
             }) => {
-                host.external
-                    .get_inspector()
-                    .selfdestruct(*from, *to, *balance);
+                host.external.get_inspector().selfdestruct(
+                    *from,
+                    *to,
+                    *balance,
+                    SelfDestructOutcome::Swept,
+                );
+            }
+            // EIP-6780: account that is not created in this transaction selfdestructs to itself,
+            // nothing changes.
+            None if interpreter.instruction_result == InstructionResult::SelfDestruct => {
+                let address = interpreter.contract.target_address;
+                let balance = host.evm.journaled_state.state[&address].info.balance;
+                host.external.get_inspector().selfdestruct(
+                    address,
+                    address,
+                    balance,
+                    SelfDestructOutcome::Swept,
+                );
             }
             _ => {}
         }
@@ -264,6 +289,7 @@ mod tests {
     use crate::{
         inspectors::NoOpInspector,
         interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome},
+        primitives::{Address, U256},
         Evm, EvmContext,
     };
 
@@ -397,4 +423,126 @@ mod tests {
             .append_handler_register(inspector_handle_register)
             .build();
     }
+
+    #[derive(Default)]
+    struct SelfDestructInspector(Vec<(Address, Address, U256, SelfDestructOutcome)>);
+
+    impl<DB: Database> Inspector<DB> for SelfDestructInspector {
+        fn selfdestruct(
+            &mut self,
+            contract: Address,
+            target: Address,
+            value: U256,
+            outcome: SelfDestructOutcome,
+        ) {
+            self.0.push((contract, target, value, outcome));
+        }
+    }
+
+    #[test]
+    fn test_selfdestruct_outcomes() {
+        use crate::{
+            db::{CacheDB, EmptyDB},
+            primitives::{
+                address, create_address, AccountInfo, Bytecode, Bytes, SpecId, TxKind, KECCAK_EMPTY,
+            },
+            DatabaseRef,
+        };
+
+        const CALLER: Address = address!("1000000000000000000000000000000000000000");
+        const TARGET: Address = address!("2000000000000000000000000000000000000000");
+        const CONTRACT: Address = address!("3000000000000000000000000000000000000000");
+        const SELF_TARGET: Address = address!("4000000000000000000000000000000000000000");
+
+        // Selfdestructs to `target`, or to itself if `None`.
+        let selfdestruct = |target: Option<Address>| -> Bytes {
+            let mut code = match target {
+                Some(target) => [&[opcode::PUSH20][..], target.as_slice()].concat(),
+                None => vec![opcode::ADDRESS],
+            };
+            code.push(opcode::SELFDESTRUCT);
+            code.into()
+        };
+        let contract = |code: Bytes| AccountInfo {
+            balance: U256::from(100),
+            ..AccountInfo::from_bytecode(Bytecode::new_raw(code))
+        };
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1000)));
+        db.insert_account_info(CONTRACT, contract(selfdestruct(Some(TARGET))));
+        db.insert_account_storage(CONTRACT, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_info(SELF_TARGET, contract(selfdestruct(None)));
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(SelfDestructInspector::default())
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.value = U256::from(50);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let mut transact = |transact_to, data| {
+            let nonce = evm.db().basic_ref(CALLER).unwrap().unwrap().nonce;
+            evm.tx_mut().transact_to = transact_to;
+            evm.tx_mut().data = data;
+            assert!(evm.transact_commit().unwrap().is_success());
+            create_address(CALLER, nonce)
+        };
+
+        // Pre-existing contract keeps its code and storage, only its balance is swept.
+        transact(TxKind::Call(CONTRACT), Bytes::new());
+        // Pre-existing contract selfdestructing to itself keeps its balance.
+        transact(TxKind::Call(SELF_TARGET), Bytes::new());
+        // Contract created in the same transaction is removed.
+        let created = transact(TxKind::Create, selfdestruct(Some(TARGET)));
+        // Contract created in the same transaction selfdestructing to itself burns its balance.
+        let burned = transact(TxKind::Create, selfdestruct(None));
+
+        let context = evm.into_context();
+        let db = context.evm.inner.db;
+        let contract = db.basic_ref(CONTRACT).unwrap().unwrap();
+        assert_eq!(contract.balance, U256::ZERO);
+        assert_ne!(contract.code_hash, KECCAK_EMPTY);
+        assert_eq!(db.storage_ref(CONTRACT, U256::from(1)), Ok(U256::from(1)));
+        assert_eq!(
+            db.basic_ref(SELF_TARGET).unwrap().unwrap().balance,
+            U256::from(150)
+        );
+        assert_eq!(db.basic_ref(created), Ok(None));
+        assert_eq!(db.basic_ref(burned), Ok(None));
+        assert_eq!(
+            db.basic_ref(TARGET).unwrap().unwrap().balance,
+            U256::from(150 + 50)
+        );
+
+        assert_eq!(
+            context.external.0,
+            [
+                (
+                    CONTRACT,
+                    TARGET,
+                    U256::from(150),
+                    SelfDestructOutcome::Swept
+                ),
+                (
+                    SELF_TARGET,
+                    SELF_TARGET,
+                    U256::from(150),
+                    SelfDestructOutcome::Swept
+                ),
+                (
+                    created,
+                    TARGET,
+                    U256::from(50),
+                    SelfDestructOutcome::Deleted
+                ),
+                (burned, burned, U256::from(50), SelfDestructOutcome::Burned),
+            ]
+        );
+    }
 }
@@ -0,0 +1,222 @@
+//! Instrumentation with the `tracing` crate, enabled by the `instrument` feature.
+//!
+//! Spans are entered around the handler stages of a transaction and around every frame, the span
+//! of a frame being a child of the span of the frame that created it. Without the feature the
+//! macros expand to nothing.
+
+/// Enters a span of the given level until the end of the enclosing block.
+macro_rules! instrument_span {
+    ($level:ident, $($args:tt)+) => {
+        #[cfg(feature = "instrument")]
+        let _span = tracing::span!(tracing::Level::$level, $($args)+).entered();
+    };
+}
+
+/// Emits an event of the given level.
+macro_rules! instrument_event {
+    ($level:ident, $($args:tt)+) => {
+        #[cfg(feature = "instrument")]
+        tracing::event!(tracing::Level::$level, $($args)+);
+    };
+}
+
+#[cfg(feature = "instrument")]
+pub(crate) use spans::*;
+
+#[cfg(feature = "instrument")]
+mod spans {
+    use crate::{primitives::TxEnv, Frame};
+    use std::vec::Vec;
+    use tracing::{debug_span, trace_span, Span};
+
+    /// Returns the span of a transaction, with the signing hash of the transaction if it is
+    /// signed.
+    pub(crate) fn transact_span(tx: &TxEnv) -> Span {
+        let span = debug_span!(
+            "transact",
+            caller = %tx.caller,
+            gas_limit = tx.gas_limit,
+            tx_hash = tracing::field::Empty,
+        );
+        #[cfg(feature = "secp256k1-recover")]
+        if let Some(signature) = &tx.signature {
+            span.record("tx_hash", tracing::field::display(signature.signing_hash));
+        }
+        span
+    }
+
+    /// Spans of the frames of a call stack.
+    #[derive(Debug, Default)]
+    pub(crate) struct FrameSpans(Vec<Span>);
+
+    impl FrameSpans {
+        /// Creates the spans of the frames of `call_stack`.
+        pub(crate) fn new(call_stack: &[Frame]) -> Self {
+            let mut spans = Self(Vec::with_capacity(call_stack.len()));
+            for frame in call_stack {
+                spans.push(frame);
+            }
+            spans
+        }
+
+        /// Creates the span of a frame pushed on the call stack, as a child of the span of the
+        /// top frame.
+        pub(crate) fn push(&mut self, frame: &Frame) {
+            let depth = self.0.len() + 1;
+            let kind = match frame {
+                Frame::Call(_) => "call",
+                Frame::Create(_) => "create",
+                Frame::EOFCreate(_) => "eofcreate",
+            };
+            let interpreter = frame.interpreter();
+            let address = interpreter.contract.target_address;
+            let gas_limit = interpreter.gas.limit();
+            let span = match self.0.last() {
+                Some(parent) => trace_span!(
+                    parent: parent,
+                    "frame",
+                    depth,
+                    kind,
+                    %address,
+                    gas_limit
+                ),
+                None => trace_span!("frame", depth, kind, %address, gas_limit),
+            };
+            self.0.push(span);
+        }
+
+        /// Removes the span of the frame popped from the call stack.
+        pub(crate) fn pop(&mut self) {
+            self.0.pop();
+        }
+
+        /// Enters the span of the top frame.
+        pub(crate) fn enter_top(&self) -> Option<tracing::span::EnteredSpan> {
+            self.0.last().cloned().map(Span::entered)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "instrument"))]
+mod tests {
+    use crate::{
+        db::InMemoryDB,
+        interpreter::opcode::{CALL, GAS, PUSH1, PUSH20, SSTORE},
+        primitives::{address, AccountInfo, Bytecode, TxKind},
+        Evm,
+    };
+    use std::{string::String, sync::Mutex, vec::Vec};
+    use tracing::{
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    /// Records the name and the parent of every span.
+    #[derive(Default)]
+    struct SpanCollector {
+        /// Spans by id minus one.
+        spans: Mutex<Vec<(&'static str, Option<u64>)>>,
+        /// Entered spans.
+        entered: Mutex<Vec<u64>>,
+    }
+
+    impl SpanCollector {
+        /// Returns the path of every span from the root span, in creation order.
+        fn paths(&self) -> Vec<String> {
+            let spans = self.spans.lock().unwrap();
+            let path = |mut id: u64| {
+                let mut names = Vec::new();
+                loop {
+                    let (name, parent) = spans[id as usize - 1];
+                    names.push(name);
+                    match parent {
+                        Some(parent) => id = parent,
+                        None => break,
+                    }
+                }
+                names.reverse();
+                names.join("/")
+            };
+            (1..=spans.len() as u64).map(path).collect()
+        }
+    }
+
+    impl Subscriber for &'static SpanCollector {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let parent = if let Some(parent) = attrs.parent() {
+                Some(parent.into_u64())
+            } else if attrs.is_contextual() {
+                self.entered.lock().unwrap().last().copied()
+            } else {
+                None
+            };
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((attrs.metadata().name(), parent));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, span: &Id) {
+            let mut entered = self.entered.lock().unwrap();
+            if let Some(position) = entered.iter().rposition(|id| *id == span.into_u64()) {
+                entered.remove(position);
+            }
+        }
+    }
+
+    #[test]
+    fn span_hierarchy_of_nested_call() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let outer = address!("0000000000000000000000000000000000001000");
+        let inner = address!("0000000000000000000000000000000000002000");
+
+        // The outer contract calls the inner one, which writes a storage slot.
+        let mut code = vec![PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH1, 0, PUSH20];
+        code.extend_from_slice(inner.as_slice());
+        code.extend([GAS, CALL]);
+        let mut db = InMemoryDB::default();
+        for (address, code) in [(outer, code), (inner, vec![PUSH1, 1, PUSH1, 0, SSTORE])] {
+            db.insert_account_info(
+                address,
+                AccountInfo::from_bytecode(Bytecode::new_raw(code.into())),
+            );
+        }
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(outer);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        let collector: &'static SpanCollector = Box::leak(Box::default());
+        let result = tracing::subscriber::with_default(collector, || evm.transact().unwrap());
+        assert!(result.result.is_success());
+        assert_eq!(
+            collector.paths(),
+            [
+                "transact",
+                "transact/validation",
+                "transact/pre_execution",
+                "transact/execution",
+                "transact/execution/frame",
+                "transact/execution/frame/frame",
+                "transact/post_execution",
+            ]
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use revm_interpreter::Eip7702CodeLoad;
 
 use crate::{
-    interpreter::{AccountLoad, InstructionResult, SStoreResult, SelfDestructResult, StateLoad},
+    interpreter::{
+        AccountLoad, InstructionResult, SStoreResult, SelfDestructOutcome, SelfDestructResult,
+        StateLoad,
+    },
     primitives::{
-        db::Database, hash_map::Entry, Account, Address, Bytecode, EVMError, EvmState,
-        EvmStorageSlot, HashMap, HashSet, Log, SpecId, SpecId::*, TransientStorage, B256,
+        db::Database, hash_map::Entry, Account, AccountStatus, Address, Bytecode, EVMError,
+        EvmState, EvmStorageSlot, HashMap, HashSet, Log, SpecId, SpecId::*, TransientStorage, B256,
         KECCAK_EMPTY, PRECOMPILE3, U256,
     },
 };
@@ -18,10 +21,18 @@ use std::vec::Vec;
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JournaledState {
     /// The current state.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub state: EvmState,
     /// Transient storage that is discarded after every transaction.
     ///
     /// See [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153).
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::primitives::serialize_sorted")
+    )]
     pub transient_storage: TransientStorage,
     /// Emitted logs.
     pub logs: Vec<Log>,
@@ -46,6 +57,12 @@ pub struct JournaledState {
     /// Note that this not include newly loaded accounts, account and storage
     /// is considered warm if it is found in the `State`.
     pub warm_preloaded_addresses: HashSet<Address>,
+    /// Why the addresses and storage slots of the transaction became warm.
+    ///
+    /// See [`JournaledState::warming_report`].
+    #[cfg(feature = "access-debug")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    warming: WarmingLog,
 }
 
 impl JournaledState {
@@ -67,6 +84,8 @@ impl JournaledState {
             depth: 0,
             spec,
             warm_preloaded_addresses,
+            #[cfg(feature = "access-debug")]
+            warming: WarmingLog::default(),
         }
     }
 
@@ -109,29 +128,119 @@ impl JournaledState {
 
     /// Does cleanup and returns modified state.
     ///
+    /// The returned state contains every account loaded during the transaction. Only touched
+    /// accounts are changed, and touched accounts that are empty must be deleted when committed
+    /// from Spurious Dragon (EIP-161), see [Self::touched_empty_accounts].
+    ///
     /// This resets the [JournaledState] to its initial state in [Self::new]
     #[inline]
     pub fn finalize(&mut self) -> (EvmState, Vec<Log>) {
+        self.clear_transient_storage();
         let Self {
             state,
-            transient_storage,
+            // cleared above
+            transient_storage: _,
             logs,
             depth,
             journal,
             // kept, see [Self::new]
             spec: _,
             warm_preloaded_addresses: _,
+            #[cfg(feature = "access-debug")]
+            warming,
         } = self;
 
-        *transient_storage = TransientStorage::default();
         *journal = vec![vec![]];
         *depth = 0;
+        #[cfg(feature = "access-debug")]
+        {
+            *warming = WarmingLog::default();
+        }
         let state = mem::take(state);
         let logs = mem::take(logs);
 
         (state, logs)
     }
 
+    /// Returns the addresses of the accounts of the finalized `state` that are deleted by the
+    /// EIP-161 state clear at the spec, see [Account::is_touched_empty].
+    #[inline]
+    pub fn touched_empty_accounts(
+        state: &EvmState,
+        spec: SpecId,
+    ) -> impl Iterator<Item = Address> + '_ {
+        state
+            .iter()
+            .filter(move |(_, account)| account.is_touched_empty(spec))
+            .map(|(address, _)| *address)
+    }
+
+    /// Records why the address became warm at the current depth.
+    ///
+    /// Cold accesses are recorded as [`WarmingReason::Access`] when they are journaled, addresses
+    /// warmed in other ways are recorded by their callers.
+    #[cfg(feature = "access-debug")]
+    #[inline]
+    pub fn record_warm_address(&mut self, address: Address, reason: WarmingReason) {
+        self.warming.addresses.record(address, reason, self.depth);
+    }
+
+    /// Records why the storage slot became warm at the current depth.
+    ///
+    /// See [`JournaledState::record_warm_address`].
+    #[cfg(feature = "access-debug")]
+    #[inline]
+    pub fn record_warm_slot(&mut self, address: Address, key: U256, reason: WarmingReason) {
+        self.warming
+            .slots
+            .record((address, key), reason, self.depth);
+    }
+
+    /// Returns why each address and storage slot of the transaction is warm, useful to debug
+    /// [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929) gas costs.
+    ///
+    /// Accesses that were reverted together with their call frame are cold again and are not
+    /// reported. The records are cleared by [`JournaledState::finalize`], so the report is only
+    /// complete during execution, e.g. from an [`Inspector`](crate::Inspector).
+    #[cfg(feature = "access-debug")]
+    pub fn warming_report(&self) -> WarmingReport {
+        let is_warm_account = |address: &Address| {
+            self.state
+                .get(address)
+                .is_some_and(|account| !account.status.contains(AccountStatus::Cold))
+        };
+        let mut addresses: Vec<_> = self
+            .warming
+            .addresses
+            .0
+            .iter()
+            .filter(|(address, record)| {
+                record.reason != WarmingReason::Access || is_warm_account(address)
+            })
+            .map(|(address, record)| (*address, *record))
+            .collect();
+        addresses.sort_unstable_by_key(|(address, _)| *address);
+
+        let mut slots: Vec<_> = self
+            .warming
+            .slots
+            .0
+            .iter()
+            .filter(|((address, key), record)| {
+                record.reason != WarmingReason::Access
+                    || is_warm_account(address)
+                        && self.state[address]
+                            .storage
+                            .get(key)
+                            .is_some_and(|slot| !slot.is_cold)
+            })
+            .map(|((address, key), record)| (*address, *key, *record))
+            .collect();
+        slots.sort_unstable_by_key(|(address, key, _)| (*address, *key));
+
+        WarmingReport { addresses, slots }
+    }
+
     /// Returns the _loaded_ [Account] for the given address.
     ///
     /// This assumes that the account has already been loaded.
@@ -195,6 +304,29 @@ impl JournaledState {
         Some(account.info.nonce)
     }
 
+    /// Increases the balance of the account and touches it, saturating at [U256::MAX].
+    ///
+    /// Used for balance that is not transferred from another account, as the mint of deposits.
+    #[inline]
+    pub fn balance_incr<DB: Database>(
+        &mut self,
+        address: Address,
+        balance: U256,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        self.load_account(address, db)?;
+        let account = self.state.get_mut(&address).unwrap();
+        let old_balance = account.info.balance;
+        account.info.balance = old_balance.saturating_add(balance);
+        let journal = self.journal.last_mut().unwrap();
+        Self::touch_account(journal, &address, account);
+        journal.push(JournalEntry::BalanceChange {
+            address,
+            old_balance,
+        });
+        Ok(())
+    }
+
     /// Transfers balance from two accounts. Returns error if sender balance is not enough.
     #[inline]
     pub fn transfer<DB: Database>(
@@ -421,6 +553,12 @@ impl JournaledState {
                     acc.info.code_hash = KECCAK_EMPTY;
                     acc.info.code = None;
                 }
+                JournalEntry::BalanceChange {
+                    address,
+                    old_balance,
+                } => {
+                    state.get_mut(&address).unwrap().info.balance = old_balance;
+                }
             }
         }
     }
@@ -508,28 +646,35 @@ impl JournaledState {
         let is_cancun_enabled = SpecId::enabled(self.spec, CANCUN);
 
         // EIP-6780 (Cancun hard-fork): selfdestruct only if contract is created in the same tx
-        let journal_entry = if acc.is_created() || !is_cancun_enabled {
+        let (journal_entry, outcome) = if acc.is_created() || !is_cancun_enabled {
             acc.mark_selfdestruct();
             acc.info.balance = U256::ZERO;
-            Some(JournalEntry::AccountDestroyed {
+            let entry = JournalEntry::AccountDestroyed {
                 address,
                 target,
                 was_destroyed: previously_destroyed,
                 had_balance: balance,
-            })
+            };
+            let outcome = if address == target {
+                SelfDestructOutcome::Burned
+            } else {
+                SelfDestructOutcome::Deleted
+            };
+            (Some(entry), outcome)
         } else if address != target {
             acc.info.balance = U256::ZERO;
-            Some(JournalEntry::BalanceTransfer {
+            let entry = JournalEntry::BalanceTransfer {
                 from: address,
                 to: target,
                 balance,
-            })
+            };
+            (Some(entry), SelfDestructOutcome::Swept)
         } else {
             // State is not changed:
             // * if we are after Cancun upgrade and
-            // * Selfdestruct account that is created in the same transaction and
+            // * Selfdestruct account that is not created in the same transaction and
             // * Specify the target is same as selfdestructed account. The balance stays unchanged.
-            None
+            (None, SelfDestructOutcome::Swept)
         };
 
         if let Some(entry) = journal_entry {
@@ -541,6 +686,7 @@ impl JournaledState {
                 had_value: !balance.is_zero(),
                 target_exists: !is_empty,
                 previously_destroyed,
+                outcome,
             },
             is_cold,
         })
@@ -576,6 +722,49 @@ impl JournaledState {
         Ok(account)
     }
 
+    /// Overrides the code and storage slots of an account before the transaction, as if the
+    /// database held them.
+    ///
+    /// The code and the slots are applied in one step: the account is loaded without being
+    /// warmed and the slots are set as original values, so an access to them is charged as it
+    /// would be without the override. Accounts and slots that are already loaded keep their
+    /// warmth. Like [`JournaledState::initial_account_load`], the change is not tracked inside
+    /// the journal.
+    pub fn override_account<DB: Database>(
+        &mut self,
+        address: Address,
+        code: Option<Bytecode>,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let is_cold =
+            !self.state.contains_key(&address) && !self.warm_preloaded_addresses.contains(&address);
+        let account = self.initial_account_load(address, [], db)?;
+        if is_cold {
+            account.mark_cold();
+        }
+        if let Some(code) = code {
+            account.info.code_hash = code.hash_slow();
+            account.info.code = Some(code);
+            account.status -= AccountStatus::LoadedAsNotExisting;
+        }
+        for (key, value) in storage {
+            match account.storage.entry(key) {
+                Entry::Occupied(entry) => {
+                    let slot = entry.into_mut();
+                    slot.original_value = value;
+                    slot.present_value = value;
+                }
+                Entry::Vacant(entry) => {
+                    let mut slot = EvmStorageSlot::new(value);
+                    slot.mark_cold();
+                    entry.insert(slot);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// load account into memory. return if it is cold or warm accessed
     #[inline]
     pub fn load_account<DB: Database>(
@@ -616,6 +805,10 @@ impl JournaledState {
                 .last_mut()
                 .unwrap()
                 .push(JournalEntry::AccountWarmed { address });
+            #[cfg(feature = "access-debug")]
+            self.warming
+                .addresses
+                .record(address, WarmingReason::Access, self.depth);
         }
 
         Ok(load)
@@ -648,6 +841,10 @@ impl JournaledState {
     }
 
     /// Loads code.
+    ///
+    /// [`Database::code_by_hash`] is only called if the account was loaded without code and
+    /// its code hash is not [`KECCAK_EMPTY`], the code returned by [`Database::basic`] is
+    /// used as is.
     #[inline]
     pub fn load_code<DB: Database>(
         &mut self,
@@ -682,37 +879,43 @@ impl JournaledState {
     ) -> Result<StateLoad<U256>, EVMError<DB::Error>> {
         // assume acc is warm
         let account = self.state.get_mut(&address).unwrap();
-        // only if account is created in this tx we can assume that storage is empty.
-        let is_newly_created = account.is_created();
-        let (value, is_cold) = match account.storage.entry(key) {
-            Entry::Occupied(occ) => {
-                let slot = occ.into_mut();
-                let is_cold = slot.mark_warm();
-                (slot.present_value, is_cold)
-            }
-            Entry::Vacant(vac) => {
-                // if storage was cleared, we don't need to ping db.
-                let value = if is_newly_created {
-                    U256::ZERO
-                } else {
-                    db.storage(address, key).map_err(EVMError::Database)?
-                };
-
-                vac.insert(EvmStorageSlot::new(value));
+        let journal = self.journal.last_mut().unwrap();
+        let load = load_slot(account, journal, address, key, db)?;
+        #[cfg(feature = "access-debug")]
+        if load.is_cold {
+            self.warming
+                .slots
+                .record((address, key), WarmingReason::Access, self.depth);
+        }
+        Ok(load)
+    }
 
-                (value, true)
+    /// Loads multiple storage slots of the same account, see [`JournaledState::sload`].
+    ///
+    /// The account and the journal are looked up once for all slots.
+    #[inline]
+    pub fn sload_many<DB: Database>(
+        &mut self,
+        address: Address,
+        keys: &[U256],
+        db: &mut DB,
+    ) -> Result<Vec<StateLoad<U256>>, EVMError<DB::Error>> {
+        // assume acc is warm
+        let account = self.state.get_mut(&address).unwrap();
+        let journal = self.journal.last_mut().unwrap();
+        let loads = keys
+            .iter()
+            .map(|key| load_slot(account, journal, address, *key, db))
+            .collect::<Result<Vec<_>, _>>()?;
+        #[cfg(feature = "access-debug")]
+        for (key, load) in keys.iter().zip(&loads) {
+            if load.is_cold {
+                self.warming
+                    .slots
+                    .record((address, *key), WarmingReason::Access, self.depth);
             }
-        };
-
-        if is_cold {
-            // add it to journal as cold loaded.
-            self.journal
-                .last_mut()
-                .unwrap()
-                .push(JournalEntry::StorageWarmed { address, key });
         }
-
-        Ok(StateLoad::new(value, is_cold))
+        Ok(loads)
     }
 
     /// Stores storage slot.
@@ -768,6 +971,23 @@ impl JournaledState {
         ))
     }
 
+    /// Returns the number of non-zero transient storage slots.
+    ///
+    /// It is zero outside of a transaction.
+    #[inline]
+    pub fn transient_storage_len(&self) -> usize {
+        self.transient_storage.len()
+    }
+
+    /// Clears the transient storage, as required by EIP-1153 at the end of every transaction.
+    ///
+    /// Called by [`Self::finalize`], and after every transaction by [`Evm`](crate::Evm) whatever
+    /// its outcome.
+    #[inline]
+    pub fn clear_transient_storage(&mut self) {
+        self.transient_storage = TransientStorage::default();
+    }
+
     /// Read transient storage tied to the account.
     ///
     /// EIP-1153: Transient storage opcodes
@@ -892,6 +1112,10 @@ pub enum JournalEntry {
     /// Action: Account code changed
     /// Revert: Revert to previous bytecode.
     CodeChange { address: Address },
+    /// Balance increased outside of a transfer
+    /// Action: Balance changed
+    /// Revert: Revert to previous balance
+    BalanceChange { address: Address, old_balance: U256 },
 }
 
 /// SubRoutine checkpoint that will help us to go back from this
@@ -901,3 +1125,150 @@ pub struct JournalCheckpoint {
     log_i: usize,
     journal_i: usize,
 }
+
+/// Loads the storage slot of the account and journals it if it was cold.
+#[inline]
+fn load_slot<DB: Database>(
+    account: &mut Account,
+    journal: &mut Vec<JournalEntry>,
+    address: Address,
+    key: U256,
+    db: &mut DB,
+) -> Result<StateLoad<U256>, EVMError<DB::Error>> {
+    // only if account is created in this tx we can assume that storage is empty.
+    let is_newly_created = account.is_created();
+    let (value, is_cold) = match account.storage.entry(key) {
+        Entry::Occupied(occ) => {
+            let slot = occ.into_mut();
+            let is_cold = slot.mark_warm();
+            (slot.present_value, is_cold)
+        }
+        Entry::Vacant(vac) => {
+            // if storage was cleared, we don't need to ping db.
+            let value = if is_newly_created {
+                U256::ZERO
+            } else {
+                db.storage(address, key).map_err(EVMError::Database)?
+            };
+
+            vac.insert(EvmStorageSlot::new(value));
+
+            (value, true)
+        }
+    };
+
+    if is_cold {
+        // add it to journal as cold loaded.
+        journal.push(JournalEntry::StorageWarmed { address, key });
+    }
+
+    Ok(StateLoad::new(value, is_cold))
+}
+
+/// Why an address or a storage slot became warm, see [`JournaledState::warming_report`].
+#[cfg(feature = "access-debug")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarmingReason {
+    /// Listed in the access list of the transaction.
+    ///
+    /// See [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930).
+    AccessList,
+    /// Sender of the transaction.
+    TxCaller,
+    /// Recipient of a call transaction.
+    TxTarget,
+    /// Coinbase of the block, warm since Shanghai.
+    ///
+    /// See [EIP-3651](https://eips.ethereum.org/EIPS/eip-3651).
+    Coinbase,
+    /// A precompile of the active spec.
+    Precompile,
+    /// The historical block hashes contract, warm since Prague.
+    ///
+    /// See [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935).
+    BlockHashHistory,
+    /// Accessed during execution, this first access was charged as cold.
+    Access,
+}
+
+/// How an address or a storage slot became warm.
+#[cfg(feature = "access-debug")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarmingRecord {
+    /// Why it became warm.
+    pub reason: WarmingReason,
+    /// Journal depth at which it became warm, `0` before the first call frame.
+    pub depth: usize,
+}
+
+/// Warm addresses and storage slots of the transaction, sorted by address and key.
+#[cfg(feature = "access-debug")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarmingReport {
+    /// Warm addresses.
+    pub addresses: Vec<(Address, WarmingRecord)>,
+    /// Warm storage slots.
+    pub slots: Vec<(Address, U256, WarmingRecord)>,
+}
+
+#[cfg(feature = "access-debug")]
+impl WarmingReport {
+    /// Returns how the address became warm, if it is warm.
+    pub fn address(&self, address: Address) -> Option<WarmingRecord> {
+        self.addresses
+            .binary_search_by_key(&address, |(address, _)| *address)
+            .ok()
+            .map(|i| self.addresses[i].1)
+    }
+
+    /// Returns how the storage slot became warm, if it is warm.
+    pub fn slot(&self, address: Address, key: U256) -> Option<WarmingRecord> {
+        self.slots
+            .binary_search_by_key(&(address, key), |(address, key, _)| (*address, *key))
+            .ok()
+            .map(|i| self.slots[i].2)
+    }
+}
+
+/// Warming records of the addresses and the storage slots of the transaction.
+#[cfg(feature = "access-debug")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct WarmingLog {
+    addresses: WarmingRecords<Address>,
+    slots: WarmingRecords<(Address, U256)>,
+}
+
+#[cfg(feature = "access-debug")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WarmingRecords<K: Eq + core::hash::Hash>(HashMap<K, WarmingRecord>);
+
+#[cfg(feature = "access-debug")]
+impl<K: Eq + core::hash::Hash> Default for WarmingRecords<K> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
+#[cfg(feature = "access-debug")]
+impl<K: Eq + core::hash::Hash> WarmingRecords<K> {
+    /// Records the reason, the first one given explicitly is kept.
+    ///
+    /// [`WarmingReason::Access`] is replaced by any later reason as a cold access can be
+    /// reverted and repeated, and as the caller is loaded before the transaction warms it.
+    fn record(&mut self, key: K, reason: WarmingReason, depth: usize) {
+        let record = WarmingRecord { reason, depth };
+        match self.0.entry(key) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().reason == WarmingReason::Access {
+                    entry.insert(record);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(record);
+            }
+        }
+    }
+}
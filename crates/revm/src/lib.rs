@@ -8,7 +8,15 @@ extern crate alloc as std;
 
 // Define modules.
 
+#[macro_use]
+mod instrument;
+#[macro_use]
+mod metrics;
+
+#[cfg(feature = "std")]
+mod analysis_cache;
 mod builder;
+mod chain_spec;
 mod context;
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -17,29 +25,53 @@ pub mod test_utils;
 pub mod db;
 mod evm;
 mod frame;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
 pub mod handler;
 mod inspector;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+mod prepared;
+mod preverified;
+pub mod replay;
+mod simulation;
+mod state_overrides;
+mod suspend;
+pub mod witness;
 
 // Export items.
 
+#[cfg(feature = "std")]
+pub use analysis_cache::AnalysisCache;
 pub use builder::EvmBuilder;
+pub use chain_spec::{ChainSpec, ForkCondition};
 pub use context::{
     Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
     ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
-    ContextWithHandlerCfg, EvmContext, InnerEvmContext,
+    ContextView, ContextWithHandlerCfg, EvmContext, InnerEvmContext, PrecompileAddresses,
 };
 pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
 pub use evm::{Evm, CALL_STACK_LIMIT};
-pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
+pub use frame::{
+    CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FramePool, FrameResult,
+    FRAME_POOL_MAX_LEN,
+};
 pub use handler::Handler;
 pub use inspector::{inspector_handle_register, inspectors, GetInspector, Inspector};
 pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+#[cfg(feature = "access-debug")]
+pub use journaled_state::{WarmingReason, WarmingRecord, WarmingReport};
+#[cfg(feature = "metrics")]
+pub use metrics::{HandlerMetrics, StageTiming};
+pub use prepared::{FirstFrameInputs, PreparedTx};
+pub use preverified::PreverifiedTx;
+pub use simulation::{BundleControl, BundleSimulationReport, BundleTxResult};
+pub use state_overrides::{AccountOverride, StateOverrides, EIP1967_IMPLEMENTATION_SLOT};
+pub use suspend::{SuspendOutcome, SuspendedExecution};
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
 pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
@@ -0,0 +1,154 @@
+//! Timing of the handler stages, enabled by the `metrics` feature.
+//!
+//! The time spent in every stage of a transaction and in the frames at every call depth is
+//! accumulated in the [`HandlerMetrics`] of the [`Evm`](crate::Evm). Without the feature the
+//! macros expand to the timed expression only.
+
+/// Evaluates the `Result` expression and, if it is `Ok`, adds the time it took to the stage of
+/// the metrics of `$evm`.
+macro_rules! metrics_stage {
+    ($evm:expr, $stage:ident, $body:expr) => {{
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let output = $body;
+        #[cfg(feature = "metrics")]
+        if output.is_ok() {
+            $evm.metrics.$stage.record(start.elapsed());
+        }
+        output
+    }};
+}
+
+#[cfg(feature = "metrics")]
+pub use handler_metrics::*;
+
+#[cfg(feature = "metrics")]
+mod handler_metrics {
+    use core::{fmt, time::Duration};
+    use std::vec::Vec;
+
+    /// Number of times a stage ran and the total time it took.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct StageTiming {
+        /// Number of times the stage ran.
+        pub count: u64,
+        /// Total time of all runs.
+        pub total: Duration,
+    }
+
+    impl StageTiming {
+        /// Records a run of the stage that took `elapsed`.
+        #[inline]
+        pub fn record(&mut self, elapsed: Duration) {
+            self.count += 1;
+            self.total += elapsed;
+        }
+
+        /// Returns the mean time of a run, zero if the stage never ran.
+        pub fn mean(&self) -> Duration {
+            match self.total.as_nanos().checked_div(self.count.into()) {
+                Some(mean) => Duration::from_nanos(mean as u64),
+                None => Duration::ZERO,
+            }
+        }
+    }
+
+    /// Time spent in the handler stages of the transactions executed by an
+    /// [`Evm`](crate::Evm), see [`Evm::metrics`](crate::Evm::metrics).
+    ///
+    /// Failed stages are not recorded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use revm::{
+    ///     primitives::{address, AccountInfo, TxKind, U256},
+    ///     Evm, InMemoryDB,
+    /// };
+    ///
+    /// let caller = address!("0000000000000000000000000000000000000001");
+    /// let mut db = InMemoryDB::default();
+    /// db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000_000)));
+    /// let mut evm = Evm::builder()
+    ///     .with_db(db)
+    ///     .modify_tx_env(|tx| {
+    ///         tx.caller = caller;
+    ///         tx.transact_to = TxKind::Call(address!("0000000000000000000000000000000000000002"));
+    ///     })
+    ///     .build();
+    ///
+    /// for nonce in 0..10 {
+    ///     evm.tx_mut().nonce = Some(nonce);
+    ///     evm.transact_commit().unwrap();
+    /// }
+    ///
+    /// let metrics = evm.take_metrics();
+    /// assert_eq!(metrics.validation.count, 10);
+    /// println!("{metrics}");
+    /// ```
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct HandlerMetrics {
+        /// Validation of the environment, the initial gas and the caller.
+        pub validation: StageTiming,
+        /// Loading of the accounts, deduction of the caller and EIP-7702 authorizations.
+        pub pre_execution: StageTiming,
+        /// Execution of the frames, from the first frame until it returns. A suspended
+        /// execution is recorded again when it is resumed.
+        pub execution: StageTiming,
+        /// Refunds, reimbursement of the caller, reward of the beneficiary and output.
+        pub post_execution: StageTiming,
+        /// Frames by call depth, the first entry being the frames of depth one.
+        ///
+        /// A frame is counted when it returns, its time is the time spent running its
+        /// interpreter, excluding the frames it called.
+        pub frames: Vec<StageTiming>,
+    }
+
+    impl HandlerMetrics {
+        /// Records a run of the interpreter of a frame at `depth` that took `elapsed`, counting
+        /// the frame if it returned.
+        #[inline]
+        pub(crate) fn record_frame(&mut self, depth: usize, elapsed: Duration, returned: bool) {
+            if self.frames.len() < depth {
+                self.frames.resize(depth, StageTiming::default());
+            }
+            let timing = &mut self.frames[depth - 1];
+            timing.total += elapsed;
+            timing.count += returned as u64;
+        }
+
+        /// Returns the stages with their names, in execution order.
+        pub fn stages(&self) -> [(&'static str, &StageTiming); 4] {
+            [
+                ("validation", &self.validation),
+                ("pre_execution", &self.pre_execution),
+                ("execution", &self.execution),
+                ("post_execution", &self.post_execution),
+            ]
+        }
+    }
+
+    impl fmt::Display for HandlerMetrics {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for (name, timing) in self.stages() {
+                writeln!(
+                    f,
+                    "{name}: {} runs, {:?} total, {:?} mean",
+                    timing.count,
+                    timing.total,
+                    timing.mean()
+                )?;
+            }
+            for (depth, timing) in self.frames.iter().enumerate() {
+                writeln!(
+                    f,
+                    "depth {}: {} frames, {:?} total",
+                    depth + 1,
+                    timing.count,
+                    timing.total
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
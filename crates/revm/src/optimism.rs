@@ -1,15 +1,28 @@
 //! Optimism-specific constants, types, and helpers.
 
 mod bn128;
+mod envelope;
+#[cfg(test)]
 mod fast_lz;
+mod features;
 mod handler_register;
+mod interop;
 mod l1block;
-mod precompile;
+pub mod precompile;
 
+pub use bn128::pair::GRANITE_MAX_INPUT_SIZE as BN128_PAIR_GRANITE_MAX_INPUT_SIZE;
+pub use envelope::{estimate_envelope_size, synthetic_envelope};
+pub use features::{features, OpFeatures};
 pub use handler_register::{
     clear, deduct_caller, end, last_frame_return, load_precompiles, optimism_handle_register,
     output, refund, reimburse_caller, reward_beneficiary, validate_env, validate_tx_against_state,
+    warm_addresses_for_tx,
+};
+pub use interop::{
+    is_executing_message, validate_executing_messages, CROSS_L2_INBOX, EXECUTING_MESSAGE_TOPIC,
 };
 pub use l1block::{
-    L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT,
+    L1BlockInfo, OpTxFees, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT,
+    OPERATOR_FEE_RECIPIENT,
 };
+pub use precompile::OpPrecompilesBuilder;
@@ -4,7 +4,10 @@ use revm_precompile::{Error, Precompile, PrecompileResult, PrecompileWithAddress
 pub(crate) mod pair {
     use super::*;
 
-    const GRANITE_MAX_INPUT_SIZE: usize = 112687;
+    /// Maximum input size of the bn128 pairing precompile, active from Granite onward.
+    ///
+    /// Inputs exceeding it fail with [`Error::Bn128PairLength`] and consume all gas.
+    pub const GRANITE_MAX_INPUT_SIZE: usize = 112_687;
     pub(crate) const GRANITE: PrecompileWithAddress = PrecompileWithAddress(
         bn128::pair::ADDRESS,
         Precompile::Standard(|input, gas_limit| run_pair(input, gas_limit)),
@@ -82,4 +85,20 @@ mod tests {
             Err(PrecompileErrors::Error(Error::Bn128PairLength))
         ));
     }
+
+    #[test]
+    fn test_bn128_pair_granite_limit_boundary() {
+        // Input at the limit passes the size check and fails on the gas check instead.
+        let input = vec![1u8; pair::GRANITE_MAX_INPUT_SIZE];
+        let res = pair::run_pair(&input, 260_000);
+        assert!(matches!(res, Err(PrecompileErrors::Error(Error::OutOfGas))));
+
+        // Input one byte over the limit is rejected before any gas is computed.
+        let input = vec![1u8; pair::GRANITE_MAX_INPUT_SIZE + 1];
+        let res = pair::run_pair(&input, u64::MAX);
+        assert!(matches!(
+            res,
+            Err(PrecompileErrors::Error(Error::Bn128PairLength))
+        ));
+    }
 }
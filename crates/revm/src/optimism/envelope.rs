@@ -0,0 +1,313 @@
+//! Synthetic enveloped transactions for L1 cost estimation.
+//!
+//! The L1 cost of a non-deposit transaction is computed from its signed RLP envelope. Hypothetical
+//! transactions, e.g. in `eth_call` or gas estimation, don't have one, so a synthetic envelope is
+//! built from the [`TxEnv`] fields with a placeholder signature.
+
+use crate::primitives::{
+    keccak256, Authorization, AuthorizationList, Bytes, TxEnv, TxKind, B256, U256,
+};
+use std::vec::Vec;
+
+/// [EIP-2930] access list transaction type.
+///
+/// [EIP-2930]: https://eips.ethereum.org/EIPS/eip-2930
+const EIP2930_TX_TYPE: u8 = 0x01;
+/// [EIP-1559] dynamic fee transaction type.
+///
+/// [EIP-1559]: https://eips.ethereum.org/EIPS/eip-1559
+const EIP1559_TX_TYPE: u8 = 0x02;
+/// [EIP-4844] blob transaction type.
+///
+/// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
+const EIP4844_TX_TYPE: u8 = 0x03;
+/// [EIP-7702] set code transaction type.
+///
+/// [EIP-7702]: https://eips.ethereum.org/EIPS/eip-7702
+const EIP7702_TX_TYPE: u8 = 0x04;
+
+/// Returns the size in bytes of the [`synthetic_envelope`] of the transaction.
+pub fn estimate_envelope_size(tx: &TxEnv) -> usize {
+    synthetic_envelope(tx).len()
+}
+
+/// Builds a synthetic enveloped transaction from the [`TxEnv`].
+///
+/// The transaction type is derived from the set fields, the same way the transaction would be
+/// typed when signed. All fields are encoded as in the signed transaction, except the signature
+/// which is replaced by a full-size placeholder with a random-like byte distribution, so that
+/// both the zero/non-zero byte count and the FastLZ compressed size match a real signature.
+pub fn synthetic_envelope(tx: &TxEnv) -> Bytes {
+    let (r, s) = placeholder_signature(tx);
+    let chain_id = tx.chain_id.unwrap_or_default();
+    let nonce = tx.nonce.unwrap_or_default();
+
+    let mut fields = Vec::new();
+    let tx_type = if let Some(authorization_list) = &tx.authorization_list {
+        encode_typed_prefix(&mut fields, tx, chain_id, nonce);
+        encode_access_list(&mut fields, tx);
+        encode_authorization_list(&mut fields, authorization_list, &r, &s);
+        Some(EIP7702_TX_TYPE)
+    } else if !tx.blob_hashes.is_empty() {
+        encode_typed_prefix(&mut fields, tx, chain_id, nonce);
+        encode_access_list(&mut fields, tx);
+        encode_u256(&mut fields, tx.max_fee_per_blob_gas.unwrap_or_default());
+        let mut hashes = Vec::new();
+        for hash in &tx.blob_hashes {
+            encode_bytes(&mut hashes, hash.as_slice());
+        }
+        encode_list(&mut fields, &hashes);
+        Some(EIP4844_TX_TYPE)
+    } else if tx.gas_priority_fee.is_some() {
+        encode_typed_prefix(&mut fields, tx, chain_id, nonce);
+        encode_access_list(&mut fields, tx);
+        Some(EIP1559_TX_TYPE)
+    } else if !tx.access_list.is_empty() {
+        encode_u64(&mut fields, chain_id);
+        encode_u64(&mut fields, nonce);
+        encode_u256(&mut fields, tx.gas_price);
+        encode_call_fields(&mut fields, tx);
+        encode_access_list(&mut fields, tx);
+        Some(EIP2930_TX_TYPE)
+    } else {
+        encode_u64(&mut fields, nonce);
+        encode_u256(&mut fields, tx.gas_price);
+        encode_call_fields(&mut fields, tx);
+        None
+    };
+
+    let v = match (tx_type, tx.chain_id) {
+        (Some(_), _) => 1,
+        // EIP-155 `v` for the transaction chain id.
+        (None, Some(chain_id)) => chain_id.saturating_mul(2).saturating_add(36),
+        (None, None) => 28,
+    };
+    encode_u64(&mut fields, v);
+    encode_bytes(&mut fields, r.as_slice());
+    encode_bytes(&mut fields, s.as_slice());
+
+    let mut envelope = Vec::with_capacity(fields.len() + 10);
+    envelope.extend(tx_type);
+    encode_list(&mut envelope, &fields);
+    envelope.into()
+}
+
+/// Returns placeholder `r` and `s` signature values derived from the caller.
+///
+/// The values have no leading zero bytes so they are encoded with their full 32 bytes.
+fn placeholder_signature(tx: &TxEnv) -> (B256, B256) {
+    let mut r = keccak256(tx.caller);
+    let mut s = keccak256(r);
+    r[0] |= 0x01;
+    s[0] |= 0x01;
+    (r, s)
+}
+
+/// Encodes the leading fields shared by the EIP-1559, EIP-4844 and EIP-7702 transactions.
+fn encode_typed_prefix(out: &mut Vec<u8>, tx: &TxEnv, chain_id: u64, nonce: u64) {
+    encode_u64(out, chain_id);
+    encode_u64(out, nonce);
+    encode_u256(out, tx.gas_priority_fee.unwrap_or_default());
+    encode_u256(out, tx.gas_price);
+    encode_call_fields(out, tx);
+}
+
+/// Encodes the gas limit, destination, value and input of the transaction.
+fn encode_call_fields(out: &mut Vec<u8>, tx: &TxEnv) {
+    encode_u64(out, tx.gas_limit);
+    match tx.transact_to {
+        TxKind::Call(address) => encode_bytes(out, address.as_slice()),
+        TxKind::Create => encode_bytes(out, &[]),
+    }
+    encode_u256(out, tx.value);
+    encode_bytes(out, &tx.data);
+}
+
+fn encode_access_list(out: &mut Vec<u8>, tx: &TxEnv) {
+    let mut items = Vec::new();
+    for item in &tx.access_list {
+        let mut keys = Vec::new();
+        for key in &item.storage_keys {
+            encode_bytes(&mut keys, key.as_slice());
+        }
+        let mut fields = Vec::new();
+        encode_bytes(&mut fields, item.address.as_slice());
+        encode_list(&mut fields, &keys);
+        encode_list(&mut items, &fields);
+    }
+    encode_list(out, &items);
+}
+
+fn encode_authorization_list(
+    out: &mut Vec<u8>,
+    authorization_list: &AuthorizationList,
+    r: &B256,
+    s: &B256,
+) {
+    let mut items = Vec::new();
+    match authorization_list {
+        AuthorizationList::Signed(signed) => {
+            for authorization in signed {
+                encode_authorization(&mut items, authorization.inner(), r, s);
+            }
+        }
+        AuthorizationList::Recovered(recovered) => {
+            for authorization in recovered {
+                encode_authorization(&mut items, authorization, r, s);
+            }
+        }
+    }
+    encode_list(out, &items);
+}
+
+fn encode_authorization(out: &mut Vec<u8>, authorization: &Authorization, r: &B256, s: &B256) {
+    let mut fields = Vec::new();
+    encode_u256(&mut fields, *authorization.chain_id());
+    encode_bytes(&mut fields, authorization.address().as_slice());
+    encode_u64(&mut fields, authorization.nonce());
+    encode_u64(&mut fields, 1);
+    encode_bytes(&mut fields, r.as_slice());
+    encode_bytes(&mut fields, s.as_slice());
+    encode_list(out, &fields);
+}
+
+fn encode_u64(out: &mut Vec<u8>, value: u64) {
+    encode_u256(out, U256::from(value));
+}
+
+fn encode_u256(out: &mut Vec<u8>, value: U256) {
+    let bytes = value.to_be_bytes::<32>();
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    encode_bytes(out, &bytes[leading_zeros..]);
+}
+
+fn encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        out.push(bytes[0]);
+    } else {
+        encode_header(out, 0x80, bytes.len());
+        out.extend_from_slice(bytes);
+    }
+}
+
+fn encode_list(out: &mut Vec<u8>, payload: &[u8]) {
+    encode_header(out, 0xc0, payload.len());
+    out.extend_from_slice(payload);
+}
+
+fn encode_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len <= 55 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = (len as u64).to_be_bytes();
+        let leading_zeros = len_bytes.iter().take_while(|byte| **byte == 0).count();
+        out.push(offset + 55 + (len_bytes.len() - leading_zeros) as u8);
+        out.extend_from_slice(&len_bytes[leading_zeros..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        optimism::L1BlockInfo,
+        primitives::{address, hex, SpecId},
+    };
+    use std::vec;
+
+    // Second tx in OP mainnet Fjord block 124665056, also used in the `L1BlockInfo` tests.
+    // <https://optimistic.etherscan.io/tx/0x1059e8004daff32caa1f1b1ef97fe3a07a8cf40508f5b835b66d9420d87c4a4a>
+    const TX: &[u8] = &hex!("02f904940a8303fba78401d6d2798401db2b6d830493e0943e6f4f7866654c18f536170780344aa8772950b680b904246a761202000000000000000000000000087000a300de7200382b55d40045000000e5d60e0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000014000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000003a0000000000000000000000000000000000000000000000000000000000000022482ad56cb0000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000120000000000000000000000000dc6ff44d5d932cbd77b52e5612ba0529dc6226f1000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000044095ea7b300000000000000000000000021c4928109acb0659a88ae5329b5374a3024694c0000000000000000000000000000000000000000000000049b9ca9a6943400000000000000000000000000000000000000000000000000000000000000000000000000000000000021c4928109acb0659a88ae5329b5374a3024694c000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000024b6b55f250000000000000000000000000000000000000000000000049b9ca9a694340000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000415ec214a3950bea839a7e6fbb0ba1540ac2076acd50820e2d5ef83d0902cdffb24a47aff7de5190290769c4f0a9c6fabf63012986a0d590b1b571547a8c7050ea1b00000000000000000000000000000000000000000000000000000000000000c080a06db770e6e25a617fe9652f0958bd9bd6e49281a53036906386ed39ec48eadf63a07f47cf51a4a40b4494cf26efc686709a9b03939e20ee27e59682f5faa536667e");
+
+    /// The [`TxEnv`] of [`TX`].
+    fn tx_env() -> TxEnv {
+        TxEnv {
+            gas_limit: 300_000,
+            gas_price: U256::from(31_140_717),
+            gas_priority_fee: Some(U256::from(30_855_801)),
+            transact_to: TxKind::Call(address!("3e6f4f7866654c18f536170780344aa8772950b6")),
+            // The 0x424 byte input follows the 48 byte envelope prefix.
+            data: Bytes::copy_from_slice(&TX[48..48 + 0x424]),
+            nonce: Some(261_031),
+            chain_id: Some(10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_synthetic_envelope_matches_signed_layout() {
+        let envelope = synthetic_envelope(&tx_env());
+
+        assert_eq!(envelope.len(), TX.len());
+        assert_eq!(estimate_envelope_size(&tx_env()), TX.len());
+        // Everything but the signature is encoded identically.
+        let signature_len = 1 + 2 * 33;
+        assert_eq!(
+            envelope[..envelope.len() - signature_len],
+            TX[..TX.len() - signature_len]
+        );
+    }
+
+    #[test]
+    fn test_synthetic_envelope_l1_fee_band() {
+        // L1 block info for OP mainnet fjord block 124665056
+        let mut l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1055991687),
+            l1_base_fee_scalar: U256::from(5227),
+            l1_blob_base_fee_scalar: Some(U256::from(1014213)),
+            l1_blob_base_fee: Some(U256::from(1)),
+            ..Default::default()
+        };
+
+        let signed_fee = l1_block_info.calculate_tx_l1_cost(TX, SpecId::FJORD);
+        l1_block_info.clear_tx_l1_cost();
+        let synthetic_fee =
+            l1_block_info.calculate_tx_l1_cost(&synthetic_envelope(&tx_env()), SpecId::FJORD);
+
+        // The synthetic envelope must be within 1% of the signed one.
+        let diff = signed_fee.abs_diff(synthetic_fee);
+        assert!(
+            diff * U256::from(100) <= signed_fee,
+            "signed: {signed_fee}, synthetic: {synthetic_fee}"
+        );
+    }
+
+    #[test]
+    fn test_synthetic_envelope_types() {
+        // Legacy transaction, EIP-155 `v` = 10 * 2 + 35 + 1.
+        let mut tx = TxEnv {
+            chain_id: Some(10),
+            ..Default::default()
+        };
+        let legacy = synthetic_envelope(&tx);
+        assert!(legacy[0] >= 0xc0);
+        assert_eq!(legacy[legacy.len() - 67], 56);
+
+        tx.access_list = vec![Default::default()];
+        assert_eq!(synthetic_envelope(&tx)[0], EIP2930_TX_TYPE);
+
+        tx.gas_priority_fee = Some(U256::from(1));
+        assert_eq!(synthetic_envelope(&tx)[0], EIP1559_TX_TYPE);
+
+        tx.blob_hashes = vec![B256::ZERO];
+        assert_eq!(synthetic_envelope(&tx)[0], EIP4844_TX_TYPE);
+
+        tx.authorization_list = Some(AuthorizationList::empty());
+        assert_eq!(synthetic_envelope(&tx)[0], EIP7702_TX_TYPE);
+    }
+
+    #[test]
+    fn test_encode_long_input() {
+        let tx = TxEnv {
+            data: Bytes::from(vec![0xff; 1 << 16]),
+            ..Default::default()
+        };
+        let envelope = synthetic_envelope(&tx);
+        // List with a 3 byte length prefix, containing the input with a 3 byte length prefix.
+        assert_eq!(envelope[0], 0xf7 + 3);
+        assert_eq!(envelope.len(), estimate_envelope_size(&tx));
+        assert!(envelope
+            .windows(4)
+            .any(|window| window == [0xb7 + 3, 0x01, 0x00, 0x00]));
+    }
+}
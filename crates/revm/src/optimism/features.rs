@@ -0,0 +1,199 @@
+//! Optimism hardfork feature activation.
+//!
+//! All spec-gated Optimism behaviors are derived from the [`SpecId`] here, so that the
+//! handler and [`L1BlockInfo`](crate::optimism::L1BlockInfo) don't need to know which
+//! hardfork introduced which behavior.
+
+use crate::primitives::SpecId;
+
+/// Set of Optimism behaviors that are active for a given [`SpecId`].
+///
+/// Every flag corresponds to a hardfork and is active from that hardfork onward.
+/// Use [`features`] to obtain the set for a spec.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OpFeatures(u16);
+
+impl OpFeatures {
+    /// No Optimism behavior is active.
+    pub const EMPTY: Self = Self(0);
+    /// Regolith: deposit transactions report the actual gas used and receive refunds,
+    /// system transactions are disabled and the rollup data gas no longer accounts for
+    /// the signature.
+    pub const REGOLITH: Self = Self(1 << 0);
+    /// Canyon: channel and batch derivation rule changes. Execution follows Shanghai.
+    pub const CANYON: Self = Self(1 << 1);
+    /// Ecotone: the L1 cost is computed from the base fee and blob base fee scalars.
+    pub const ECOTONE: Self = Self(1 << 2);
+    /// Fjord: the L1 cost is computed from the FastLZ compressed size and the
+    /// `P256VERIFY` precompile is available.
+    pub const FJORD: Self = Self(1 << 3);
+    /// Granite: the bn128 pairing precompile input size is limited.
+    pub const GRANITE: Self = Self(1 << 4);
+    /// Holocene: EIP-1559 parameters are configurable through the system config.
+    pub const HOLOCENE: Self = Self(1 << 5);
+    /// Isthmus: the operator fee is charged and the BLS12-381 precompiles are available.
+    pub const ISTHMUS: Self = Self(1 << 6);
+
+    /// Returns the raw bits of the set.
+    #[inline]
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Returns `true` if all flags of `other` are contained in `self`.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the union of both sets.
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns `true` if deposit transactions report their actual gas used and
+    /// receive gas refunds.
+    #[inline]
+    pub const fn is_regolith_gas_reporting_enabled(self) -> bool {
+        self.contains(Self::REGOLITH)
+    }
+
+    /// Returns `true` if system transactions are rejected.
+    #[inline]
+    pub const fn is_system_transaction_disabled(self) -> bool {
+        self.contains(Self::REGOLITH)
+    }
+
+    /// Returns `true` if the rollup data gas is padded with 68 non-zero bytes to account
+    /// for the empty signature.
+    #[inline]
+    pub const fn pads_data_gas_for_signature(self) -> bool {
+        !self.contains(Self::REGOLITH)
+    }
+
+    /// Returns `true` if the Canyon channel rules are active.
+    #[inline]
+    pub const fn is_canyon_channel_rules_enabled(self) -> bool {
+        self.contains(Self::CANYON)
+    }
+
+    /// Returns `true` if the L1 cost uses the Ecotone base fee and blob base fee scalars.
+    #[inline]
+    pub const fn uses_ecotone_scalars(self) -> bool {
+        self.contains(Self::ECOTONE)
+    }
+
+    /// Returns `true` if the L1 cost is estimated from the FastLZ compressed size.
+    #[inline]
+    pub const fn uses_fastlz_cost(self) -> bool {
+        self.contains(Self::FJORD)
+    }
+
+    /// Returns `true` if the `P256VERIFY` precompile is available.
+    #[inline]
+    pub const fn has_p256_verify_precompile(self) -> bool {
+        self.contains(Self::FJORD)
+    }
+
+    /// Returns `true` if the bn128 pairing precompile input size is limited.
+    #[inline]
+    pub const fn is_bn128_pair_limit_enabled(self) -> bool {
+        self.contains(Self::GRANITE)
+    }
+
+    /// Returns `true` if EIP-1559 parameters are configurable through the system config.
+    #[inline]
+    pub const fn is_holocene_eip1559_params_enabled(self) -> bool {
+        self.contains(Self::HOLOCENE)
+    }
+
+    /// Returns `true` if the operator fee is charged.
+    #[inline]
+    pub const fn is_operator_fee_enabled(self) -> bool {
+        self.contains(Self::ISTHMUS)
+    }
+
+    /// Returns `true` if the BLS12-381 precompiles are available.
+    #[inline]
+    pub const fn has_bls12_381_precompiles(self) -> bool {
+        self.contains(Self::ISTHMUS)
+    }
+}
+
+/// Returns the Optimism behaviors that are active for the given spec.
+pub const fn features(spec_id: SpecId) -> OpFeatures {
+    let mut features = OpFeatures::EMPTY;
+    if spec_id.is_enabled_in(SpecId::REGOLITH) {
+        features = features.union(OpFeatures::REGOLITH);
+    }
+    if spec_id.is_enabled_in(SpecId::CANYON) {
+        features = features.union(OpFeatures::CANYON);
+    }
+    if spec_id.is_enabled_in(SpecId::ECOTONE) {
+        features = features.union(OpFeatures::ECOTONE);
+    }
+    if spec_id.is_enabled_in(SpecId::FJORD) {
+        features = features.union(OpFeatures::FJORD);
+    }
+    if spec_id.is_enabled_in(SpecId::GRANITE) {
+        features = features.union(OpFeatures::GRANITE);
+    }
+    if spec_id.is_enabled_in(SpecId::HOLOCENE) {
+        features = features.union(OpFeatures::HOLOCENE);
+    }
+    if spec_id.is_enabled_in(SpecId::ISTHMUS) {
+        features = features.union(OpFeatures::ISTHMUS);
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_matrix() {
+        // Bits are ordered from Regolith (lowest) to Isthmus (highest).
+        let matrix = [
+            (SpecId::MERGE, 0b000_0000),
+            (SpecId::BEDROCK, 0b000_0000),
+            (SpecId::REGOLITH, 0b000_0001),
+            (SpecId::SHANGHAI, 0b000_0001),
+            (SpecId::CANYON, 0b000_0011),
+            (SpecId::CANCUN, 0b000_0011),
+            (SpecId::ECOTONE, 0b000_0111),
+            (SpecId::FJORD, 0b000_1111),
+            (SpecId::GRANITE, 0b001_1111),
+            (SpecId::HOLOCENE, 0b011_1111),
+            (SpecId::PRAGUE, 0b011_1111),
+            (SpecId::ISTHMUS, 0b111_1111),
+            (SpecId::LATEST, 0b111_1111),
+        ];
+
+        for (spec_id, bits) in matrix {
+            let features = features(spec_id);
+            let bit = |n: u16| bits & (1 << n) != 0;
+            assert_eq!(features.bits(), bits, "{spec_id:?}");
+
+            assert_eq!(features.is_regolith_gas_reporting_enabled(), bit(0));
+            assert_eq!(features.is_system_transaction_disabled(), bit(0));
+            assert_eq!(features.pads_data_gas_for_signature(), !bit(0));
+            assert_eq!(features.is_canyon_channel_rules_enabled(), bit(1));
+            assert_eq!(features.uses_ecotone_scalars(), bit(2));
+            assert_eq!(features.uses_fastlz_cost(), bit(3));
+            assert_eq!(features.has_p256_verify_precompile(), bit(3));
+            assert_eq!(features.is_bn128_pair_limit_enabled(), bit(4));
+            assert_eq!(features.is_holocene_eip1559_params_enabled(), bit(5));
+            assert_eq!(features.is_operator_fee_enabled(), bit(6));
+            assert_eq!(features.has_bls12_381_precompiles(), bit(6));
+        }
+    }
+
+    #[test]
+    fn test_features_are_cumulative() {
+        assert_eq!(features(SpecId::BEDROCK), OpFeatures::EMPTY);
+        assert!(features(SpecId::ISTHMUS).contains(features(SpecId::GRANITE)));
+        assert_eq!(features(SpecId::LATEST).bits(), 0b111_1111);
+    }
+}
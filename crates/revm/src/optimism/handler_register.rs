@@ -8,17 +8,16 @@ use crate::{
     interpreter::{return_ok, return_revert, Gas, InstructionResult},
     optimism,
     primitives::{
-        db::Database, spec_to_generic, Account, EVMError, Env, ExecutionResult, HaltReason,
-        HashMap, InvalidTransaction, OptimismInvalidTransaction, ResultAndState, Spec, SpecId,
-        SpecId::REGOLITH, U256,
+        db::Database, gas_fee, gas_to_u256, spec_to_generic, Address, Bytes, EVMError, Env,
+        ExecutionResult, FailedDepositCause, FeeSummary, HaltReason, InvalidTransaction,
+        OptimismInvalidTransaction, ResultAndState, Spec, SpecId, U256,
     },
     Context, ContextPrecompiles, FrameResult,
 };
-use core::{cmp::Ordering, ops::Mul};
-use revm_precompile::PrecompileSpecId;
-use std::{boxed::Box, string::ToString, sync::Arc};
+use revm_precompile::{PrecompileSpecId, Precompiles};
+use std::{borrow::Cow, sync::Arc};
 
-use super::l1block::OPERATOR_FEE_RECIPIENT;
+use super::l1block::{L1BlockInfo, OpTxFees, OPERATOR_FEE_RECIPIENT};
 
 pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
     spec_to_generic!(handler.cfg.spec_id, {
@@ -42,28 +41,102 @@ pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_,
     });
 }
 
+/// EIP-2718 type of the deposit transaction.
+const DEPOSIT_TRANSACTION_TYPE: u8 = 0x7E;
+
 /// Validate environment for the Optimism chain.
 pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
-    // Do not perform any extra validation for deposit transactions, they are pre-verified on L1.
+    // Deposit transactions are pre-verified on L1, only reject field combinations that
+    // contradict the deposit fee rules.
     if env.tx.optimism.source_hash.is_some() {
-        return Ok(());
+        return validate_deposit_fields(env).map_err(Into::into);
     }
     // Important: validate block before tx.
     env.validate_block_env::<SPEC>()?;
 
     // Do not allow for a system transaction to be processed if Regolith is enabled.
     let tx = &env.tx.optimism;
-    if tx.is_system_transaction.unwrap_or(false) && SPEC::enabled(SpecId::REGOLITH) {
+    if tx.is_system_transaction.unwrap_or(false)
+        && optimism::features(SPEC::SPEC_ID).is_system_transaction_disabled()
+    {
         return Err(InvalidTransaction::OptimismError(
             OptimismInvalidTransaction::DepositSystemTxPostRegolith,
         )
         .into());
     }
 
+    // OP Stack chains have no blob transactions.
+    if env.tx.max_fee_per_blob_gas.is_some() || !env.tx.blob_hashes.is_empty() {
+        return Err(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::BlobTransactionNotSupported,
+        )
+        .into());
+    }
+
     env.validate_tx::<SPEC>()?;
     Ok(())
 }
 
+/// Validate that a deposit transaction doesn't carry fields that only apply to
+/// transactions paying fees on L2.
+fn validate_deposit_fields(env: &Env) -> Result<(), InvalidTransaction> {
+    let tx = &env.tx;
+    // An empty envelope or a deposit envelope is not charged any L1 cost. `0x7F` is treated
+    // as a deposit by the L1 cost function as well.
+    let is_deposit_envelope = |enveloped_tx: &Bytes| {
+        matches!(
+            enveloped_tx.first(),
+            None | Some(&DEPOSIT_TRANSACTION_TYPE) | Some(&0x7F)
+        )
+    };
+
+    let error = if tx
+        .optimism
+        .enveloped_tx
+        .as_ref()
+        .is_some_and(|enveloped_tx| !is_deposit_envelope(enveloped_tx))
+    {
+        OptimismInvalidTransaction::DepositWithNonDepositEnvelope
+    } else if tx.gas_priority_fee.is_some_and(|fee| !fee.is_zero()) {
+        OptimismInvalidTransaction::DepositWithPriorityFee
+    } else if !tx.blob_hashes.is_empty() {
+        OptimismInvalidTransaction::DepositWithBlobHashes
+    } else {
+        return Ok(());
+    };
+    Err(InvalidTransaction::OptimismError(error))
+}
+
+/// Returns the enveloped transaction of a non-deposit transaction.
+///
+/// If it is missing and [`CfgEnv::synthesize_missing_envelope`] is set, a
+/// [`optimism::synthetic_envelope`] is built instead.
+///
+/// [`CfgEnv::synthesize_missing_envelope`]: crate::primitives::CfgEnv::synthesize_missing_envelope
+fn enveloped_tx(env: &Env) -> Result<Cow<'_, Bytes>, InvalidTransaction> {
+    match &env.tx.optimism.enveloped_tx {
+        Some(enveloped_tx) => Ok(Cow::Borrowed(enveloped_tx)),
+        None if env.cfg.synthesize_missing_envelope => {
+            Ok(Cow::Owned(optimism::synthetic_envelope(&env.tx)))
+        }
+        None => Err(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::MissingEnvelope,
+        )),
+    }
+}
+
+/// Returns the L1 block info loaded by [`validate_tx_against_state`], or
+/// [`OptimismInvalidTransaction::MissingL1BlockInfo`] if it is not loaded.
+fn loaded_l1_block_info(
+    l1_block_info: &mut Option<L1BlockInfo>,
+) -> Result<&mut L1BlockInfo, InvalidTransaction> {
+    l1_block_info
+        .as_mut()
+        .ok_or(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::MissingL1BlockInfo,
+        ))
+}
+
 /// Don not perform any extra validation for deposit transactions, they are pre-verified on L1.
 pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
@@ -76,112 +149,44 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     // storage l1 block info for later use. l1_block_info is cleared after execution.
     if context.evm.inner.l1_block_info.is_none() {
         // the L1-cost fee is only computed for Optimism non-deposit transactions.
-        let l1_block_info =
-            crate::optimism::L1BlockInfo::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID)
-                .map_err(EVMError::Database)?;
+        let l1_block_info = L1BlockInfo::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID)
+            .map_err(EVMError::Database)?;
         context.evm.inner.l1_block_info = Some(l1_block_info);
     }
 
-    let env @ Env { cfg, tx, .. } = context.evm.inner.env.as_ref();
+    let env = context.evm.inner.env.as_mut();
 
     // load acc
-    let tx_caller = tx.caller;
     let account = context
         .evm
         .inner
         .journaled_state
-        .load_code(tx_caller, &mut context.evm.inner.db)?
+        .load_code(env.tx.caller, &mut context.evm.inner.db)?
         .data;
 
-    // EIP-3607: Reject transactions from senders with deployed code
-    // This EIP is introduced after london but there was no collision in past
-    // so we can leave it enabled always
-    if !cfg.is_eip3607_disabled() {
-        let bytecode = &account.info.code.as_ref().unwrap();
-        // allow EOAs whose code is a valid delegation designation,
-        // i.e. 0xef0100 || address, to continue to originate transactions.
-        if !bytecode.is_empty() && !bytecode.is_eip7702() {
-            return Err(EVMError::Transaction(
-                InvalidTransaction::RejectCallerWithCode,
-            ));
-        }
-    }
-
-    // Check that the transaction's nonce is correct
-    if let Some(tx) = tx.nonce {
-        let state = account.info.nonce;
-        match tx.cmp(&state) {
-            Ordering::Greater => {
-                return Err(EVMError::Transaction(InvalidTransaction::NonceTooHigh {
-                    tx,
-                    state,
-                }));
-            }
-            Ordering::Less => {
-                return Err(EVMError::Transaction(InvalidTransaction::NonceTooLow {
-                    tx,
-                    state,
-                }));
-            }
-            _ => {}
-        }
-    }
+    // EIP-3607 and nonce checks are shared with mainnet.
+    env.validate_account_nonce_and_code(&account.info)
+        .map_err(EVMError::Transaction)?;
 
     // get envelope
-    let Some(enveloped_tx) = &tx.optimism.enveloped_tx else {
-        return Err(EVMError::Custom(
-            "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
-        ));
-    };
-
-    // compute L1 cost
-    let tx_l1_cost = context
-        .evm
-        .inner
-        .l1_block_info
-        .as_mut()
-        .expect("L1BlockInfo should be loaded")
-        .calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
-
-    let gas_limit = U256::from(tx.gas_limit);
-    let operator_fee_charge = context
-        .evm
-        .inner
-        .l1_block_info
-        .as_ref()
-        .expect("L1BlockInfo should be loaded")
-        .operator_fee_charge(enveloped_tx, gas_limit, SPEC::SPEC_ID);
-
-    let mut balance_check = gas_limit
-        .checked_mul(tx.gas_price)
-        .and_then(|gas_cost| gas_cost.checked_add(tx.value))
-        .and_then(|total_cost| total_cost.checked_add(tx_l1_cost))
-        .and_then(|total_cost| total_cost.checked_add(operator_fee_charge))
+    let enveloped_tx = enveloped_tx(env)?;
+
+    // compute L1 cost and operator fee
+    let l1_block_info = loaded_l1_block_info(&mut context.evm.inner.l1_block_info)?;
+    let tx_l1_cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, SPEC::SPEC_ID);
+    let operator_fee_charge = l1_block_info
+        .operator_fee_charge(&enveloped_tx, gas_to_u256(env.tx.gas_limit), SPEC::SPEC_ID)
+        .map_err(InvalidTransaction::OptimismError)?;
+    let op_fees = tx_l1_cost
+        .checked_add(operator_fee_charge)
         .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
 
-    if SPEC::enabled(SpecId::CANCUN) {
-        // if the tx is not a blob tx, this will be None, so we add zero
-        let data_fee = env.calc_max_data_fee().unwrap_or_default();
-        balance_check = balance_check
-            .checked_add(U256::from(data_fee))
-            .ok_or(InvalidTransaction::OverflowPaymentInTransaction)?;
-    }
-
-    // Check if account has enough balance for gas_limit*gas_price and value transfer.
-    // Transfer will be done inside `*_inner` functions.
-    if balance_check > account.info.balance {
-        if cfg.is_balance_check_disabled() {
-            // Add transaction cost to balance to ensure execution doesn't fail.
-            account.info.balance = balance_check;
-        } else {
-            return Err(EVMError::Transaction(
-                InvalidTransaction::LackOfFundForMaxFee {
-                    fee: Box::new(balance_check),
-                    balance: Box::new(account.info.balance),
-                },
-            ));
-        }
-    }
+    // Check if account has enough balance for gas_limit*gas_price, value transfer, L1 cost
+    // and operator fee. With the balance check disabled, the caller is left with the value
+    // transfer and the unused part of the gas price after `deduct_caller`.
+    env.cap_gas_price_to_balance::<SPEC>(account.info.balance, op_fees);
+    env.validate_account_balance::<SPEC>(account, op_fees)
+        .map_err(EVMError::Transaction)?;
 
     Ok(())
 }
@@ -196,7 +201,7 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
     let is_deposit = env.tx.optimism.source_hash.is_some();
     let tx_system = env.tx.optimism.is_system_transaction;
     let tx_gas_limit = env.tx.gas_limit;
-    let is_regolith = SPEC::enabled(REGOLITH);
+    let is_regolith = optimism::features(SPEC::SPEC_ID).is_regolith_gas_reporting_enabled();
 
     let instruction_result = frame_result.interpreter_result().result;
     let gas = frame_result.gas_mut();
@@ -264,12 +269,13 @@ pub fn refund<SPEC: Spec, EXT, DB: Database>(
 
     let env = context.evm.inner.env();
     let is_deposit = env.tx.optimism.source_hash.is_some();
-    let is_regolith = SPEC::enabled(REGOLITH);
+    let is_regolith = optimism::features(SPEC::SPEC_ID).is_regolith_gas_reporting_enabled();
 
-    // Prior to Regolith, deposit transactions did not receive gas refunds.
-    let is_gas_refund_disabled = env.cfg.is_gas_refund_disabled() || (is_deposit && !is_regolith);
-    if !is_gas_refund_disabled {
-        gas.set_final_refund(SPEC::SPEC_ID.is_enabled_in(SpecId::LONDON));
+    if is_deposit && !is_regolith {
+        // Prior to Regolith, deposit transactions did not receive gas refunds.
+        gas.set_final_refund_with_quotient(0);
+    } else if !env.cfg.is_gas_refund_disabled() {
+        gas.set_final_refund_with_quotient(mainnet::refund_quotient::<SPEC>(&env.cfg));
     }
 }
 
@@ -287,13 +293,16 @@ pub fn reimburse_caller<SPEC: Spec, EXT, DB: Database>(
             .inner
             .journaled_state
             .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
-        let operator_fee_refund = context
-            .evm
-            .inner
-            .l1_block_info
-            .as_ref()
-            .expect("L1BlockInfo should be loaded")
-            .operator_fee_refund(gas, SPEC::SPEC_ID);
+        // Nothing was charged for an empty envelope, so there is nothing to refund.
+        let is_empty_envelope = enveloped_tx(&context.evm.inner.env)
+            .map_or(true, |enveloped_tx| enveloped_tx.is_empty());
+        let operator_fee_refund = if is_empty_envelope {
+            U256::ZERO
+        } else {
+            loaded_l1_block_info(&mut context.evm.inner.l1_block_info)?
+                .operator_fee_refund(gas, SPEC::SPEC_ID)
+                .map_err(InvalidTransaction::OptimismError)?
+        };
 
         // In additional to the normal transaction fee, additionally refund the caller
         // for the operator fee.
@@ -310,17 +319,33 @@ pub fn reimburse_caller<SPEC: Spec, EXT, DB: Database>(
 /// Load precompiles for Optimism chain.
 #[inline]
 pub fn load_precompiles<SPEC: Spec, EXT, DB: Database>() -> ContextPrecompiles<DB> {
-    if SPEC::enabled(SpecId::ISTHMUS) {
-        ContextPrecompiles::from_static_precompiles(optimism::precompile::isthmus())
-    } else if SPEC::enabled(SpecId::GRANITE) {
-        ContextPrecompiles::from_static_precompiles(optimism::precompile::granite())
-    } else if SPEC::enabled(SpecId::FJORD) {
-        ContextPrecompiles::from_static_precompiles(optimism::precompile::fjord())
+    ContextPrecompiles::from_static_precompiles(precompiles(SPEC::SPEC_ID))
+}
+
+/// Returns the precompiles of the OP Stack spec.
+fn precompiles(spec_id: SpecId) -> &'static Precompiles {
+    let features = optimism::features(spec_id);
+    if spec_id.is_enabled_in(SpecId::OSAKA) {
+        optimism::precompile::osaka()
+    } else if features.has_bls12_381_precompiles() {
+        optimism::precompile::isthmus()
+    } else if features.is_bn128_pair_limit_enabled() {
+        optimism::precompile::granite()
+    } else if features.has_p256_verify_precompile() {
+        optimism::precompile::fjord()
     } else {
-        ContextPrecompiles::new(PrecompileSpecId::from_spec_id(SPEC::SPEC_ID))
+        Precompiles::new(PrecompileSpecId::from_spec_id(spec_id))
     }
 }
 
+/// Returns the addresses that are warm at the start of a transaction of the OP Stack spec.
+///
+/// Same as [`mainnet::warm_addresses_for_tx`] with the OP Stack precompiles. The fee vaults are
+/// not warm.
+pub fn warm_addresses_for_tx(env: &Env, spec_id: SpecId) -> impl Iterator<Item = Address> + '_ {
+    mainnet::warm_addresses_with_precompiles(env, spec_id, precompiles(spec_id))
+}
+
 /// Deduct max balance from caller
 #[inline]
 pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
@@ -333,11 +358,16 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
         .journaled_state
         .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
 
+    // Deposit transactions are not charged any fees, others are recorded in `reward_beneficiary`.
+    let is_deposit = context.evm.inner.env.tx.optimism.source_hash.is_some();
+    context.evm.inner.op_tx_fees = is_deposit.then(OpTxFees::default);
+    context.evm.inner.fee_summary = is_deposit.then(FeeSummary::default);
+
     // If the transaction is a deposit with a `mint` value, add the mint value
     // in wei to the caller's balance. This should be persisted to the database
     // prior to the rest of execution.
     if let Some(mint) = context.evm.inner.env.tx.optimism.mint {
-        caller_account.info.balance += U256::from(mint);
+        caller_account.info.balance = caller_account.info.balance.saturating_add(U256::from(mint));
     }
 
     // We deduct caller max balance after minting and before deducing the
@@ -347,34 +377,33 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
     // If the transaction is not a deposit transaction, subtract the L1 data fee from the
     // caller's balance directly after minting the requested amount of ETH.
     // Additionally deduct the operator fee from the caller's account.
-    if context.evm.inner.env.tx.optimism.source_hash.is_none() {
+    if !is_deposit {
         // get envelope
-        let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
-            ));
-        };
+        let enveloped_tx = enveloped_tx(&context.evm.inner.env)?;
 
-        let l1_block = context
-            .evm
-            .inner
-            .l1_block_info
-            .as_mut()
-            .expect("L1BlockInfo should be loaded");
+        let l1_block = loaded_l1_block_info(&mut context.evm.inner.l1_block_info)?;
 
-        let tx_l1_cost = l1_block.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
-        caller_account.info.balance = caller_account.info.balance.saturating_sub(tx_l1_cost);
+        let tx_l1_cost = l1_block.calculate_tx_l1_cost(&enveloped_tx, SPEC::SPEC_ID);
 
         // Deduct the operator fee from the caller's account.
-        let gas_limit = U256::from(context.evm.inner.env.tx.gas_limit);
+        let gas_limit = gas_to_u256(context.evm.inner.env.tx.gas_limit);
 
-        let operator_fee_charge =
-            l1_block.operator_fee_charge(enveloped_tx, gas_limit, SPEC::SPEC_ID);
+        let operator_fee_charge = l1_block
+            .operator_fee_charge(&enveloped_tx, gas_limit, SPEC::SPEC_ID)
+            .map_err(InvalidTransaction::OptimismError)?;
 
-        caller_account.info.balance = caller_account
-            .info
-            .balance
-            .saturating_sub(operator_fee_charge);
+        let fees = tx_l1_cost.saturating_add(operator_fee_charge);
+
+        // With balance checks disabled, the caller is credited the part of the fees it can't
+        // cover, the same way `validate_tx_against_state` tops up its balance. The fees are
+        // then charged in full, so the fee vaults receive exactly what the caller paid.
+        if context.evm.inner.env.cfg.is_balance_check_disabled()
+            && caller_account.info.balance < fees
+        {
+            caller_account.info.balance = fees;
+        }
+
+        caller_account.info.balance = caller_account.info.balance.saturating_sub(fees);
     }
     Ok(())
 }
@@ -390,29 +419,45 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
     // transfer fee to coinbase/beneficiary.
     if !is_deposit {
         mainnet::reward_beneficiary::<SPEC, EXT, DB>(context, gas)?;
+    } else if let Some(summary) = &mut context.evm.inner.fee_summary {
+        // Deposits pay no fees but still report their gas and refund.
+        mainnet::set_fee_summary_gas(summary, gas);
     }
 
     if !is_deposit {
         // If the transaction is not a deposit transaction, fees are paid out
         // to both the Base Fee Vault as well as the L1 Fee Vault.
-        let Some(l1_block_info) = &mut context.evm.inner.l1_block_info else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load L1 block information.".to_string(),
-            ));
-        };
-
-        let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
-            ));
-        };
-
-        let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
-        let operator_fee_cost = l1_block_info.operator_fee_charge(
-            enveloped_tx,
-            U256::from(gas.spent() - gas.refunded() as u64),
-            SPEC::SPEC_ID,
+        let l1_block_info = loaded_l1_block_info(&mut context.evm.inner.l1_block_info)?;
+
+        let enveloped_tx = enveloped_tx(&context.evm.inner.env)?;
+
+        let l1_cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, SPEC::SPEC_ID);
+        let operator_fee_cost = l1_block_info
+            .operator_fee_charge(
+                &enveloped_tx,
+                gas_to_u256(gas.spent_sub_refunded()),
+                SPEC::SPEC_ID,
+            )
+            .map_err(InvalidTransaction::OptimismError)?;
+        // The operator fee charged in `deduct_caller` minus the one refunded in
+        // `reimburse_caller` must be exactly what the operator fee vault receives.
+        debug_assert!(
+            enveloped_tx.is_empty()
+                || l1_block_info
+                    .operator_fee_charge(&enveloped_tx, gas_to_u256(gas.limit()), SPEC::SPEC_ID)
+                    .and_then(|charge| {
+                        let refund = l1_block_info.operator_fee_refund(gas, SPEC::SPEC_ID)?;
+                        Ok(charge.saturating_sub(refund))
+                    })
+                    == Ok(operator_fee_cost),
+            "operator fee charge and refund do not reconcile"
         );
+        context.evm.inner.op_tx_fees = Some(OpTxFees {
+            l1_cost,
+            l1_data_gas: l1_block_info.data_gas(&enveloped_tx, SPEC::SPEC_ID),
+            l1_gas_price: l1_block_info.l1_base_fee,
+            operator_fee: operator_fee_cost,
+        });
 
         // Send the L1 cost of the transaction to the L1 Fee Vault.
         let mut l1_fee_vault_account = context
@@ -430,13 +475,11 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
             .journaled_state
             .load_account(optimism::BASE_FEE_RECIPIENT, &mut context.evm.inner.db)?;
         base_fee_vault_account.mark_touch();
-        base_fee_vault_account.info.balance += context
-            .evm
-            .inner
-            .env
-            .block
-            .basefee
-            .mul(U256::from(gas.spent() - gas.refunded() as u64));
+        let base_fee = gas_fee(
+            context.evm.inner.env.block.basefee,
+            gas.spent_sub_refunded(),
+        )?;
+        base_fee_vault_account.info.balance += base_fee;
 
         // Send the operator fee of the transaction to the coinbase.
         let mut operator_fee_vault_account = context
@@ -447,6 +490,14 @@ pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
 
         operator_fee_vault_account.mark_touch();
         operator_fee_vault_account.data.info.balance += operator_fee_cost;
+
+        // Base fee is not burned but paid to the Base Fee Vault.
+        if let Some(summary) = &mut context.evm.inner.fee_summary {
+            summary.burned = U256::ZERO;
+            summary.l1_fee = l1_cost;
+            summary.base_fee_vault = base_fee;
+            summary.operator_fee = operator_fee_cost;
+        }
     }
     Ok(())
 }
@@ -459,14 +510,20 @@ pub fn output<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
     let result = mainnet::output::<EXT, DB>(context, frame_result)?;
 
-    if result.result.is_halt() {
+    // Executing messages of the interop CrossL2Inbox must reference valid initiating messages.
+    if let ExecutionResult::Success { logs, .. } = &result.result {
+        optimism::validate_executing_messages(&context.evm.inner.env.cfg.interop_validator, logs)
+            .map_err(|error| EVMError::Transaction(InvalidTransaction::OptimismError(error)))?;
+    }
+
+    if let ExecutionResult::Halt { reason, .. } = result.result {
         // Post-regolith, if the transaction is a deposit transaction and it halts,
         // we bubble up to the global return handler. The mint value will be persisted
         // and the caller nonce will be incremented there.
         let is_deposit = context.evm.inner.env.tx.optimism.source_hash.is_some();
-        if is_deposit && SPEC::enabled(REGOLITH) {
+        if is_deposit && optimism::features(SPEC::SPEC_ID).is_regolith_gas_reporting_enabled() {
             return Err(EVMError::Transaction(InvalidTransaction::OptimismError(
-                OptimismInvalidTransaction::HaltedDepositPostRegolith,
+                OptimismInvalidTransaction::HaltedDepositPostRegolith { reason },
             )));
         }
     }
@@ -479,9 +536,9 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     evm_output: Result<ResultAndState, EVMError<DB::Error>>,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
-    evm_output.or_else(|err| {
-        if matches!(err, EVMError::Transaction(_))
-            && context.evm.inner.env().tx.optimism.source_hash.is_some()
+    evm_output.or_else(|err| match err {
+        EVMError::Transaction(error)
+            if context.evm.inner.env().tx.optimism.source_hash.is_some() =>
         {
             // If the transaction is a deposit transaction and it failed
             // for any reason, the caller nonce must be bumped, and the
@@ -493,23 +550,20 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
 
             // Increment sender nonce and account balance for the mint amount. Deposits
             // always persist the mint amount, even if the transaction fails.
-            let account = {
-                let mut acc = Account::from(
-                    context
-                        .evm
-                        .db
-                        .basic(caller)
-                        .unwrap_or_default()
-                        .unwrap_or_default(),
-                );
-                acc.info.nonce = acc.info.nonce.saturating_add(1);
-                acc.info.balance = acc.info.balance.saturating_add(U256::from(
-                    context.evm.inner.env().tx.optimism.mint.unwrap_or(0),
-                ));
-                acc.mark_touch();
-                acc
-            };
-            let state = HashMap::from_iter([(caller, account)]);
+            //
+            // Changes of the failed transaction are discarded and the caller is loaded again
+            // through the journal, so its status follows the same rules as any other account:
+            // it is touched, never created nor destroyed, and keeps the `LoadedAsNotExisting`
+            // flag if it did not exist.
+            let mint = U256::from(context.evm.inner.env().tx.optimism.mint.unwrap_or(0));
+            let inner = &mut context.evm.inner;
+            inner.journaled_state.clear();
+            inner
+                .journaled_state
+                .balance_incr(caller, mint, &mut inner.db)?;
+            // The nonce saturates instead of failing, the deposit is included regardless.
+            let _ = inner.journaled_state.inc_nonce(caller);
+            let (state, _) = inner.journaled_state.finalize();
 
             // The gas used of a failed deposit post-regolith is the gas
             // limit of the transaction. pre-regolith, it is the gas limit
@@ -522,7 +576,9 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
                 .optimism
                 .is_system_transaction
                 .unwrap_or(false);
-            let gas_used = if SPEC::enabled(REGOLITH) || !is_system_tx {
+            let gas_used = if optimism::features(SPEC::SPEC_ID).is_regolith_gas_reporting_enabled()
+                || !is_system_tx
+            {
                 context.evm.inner.env().tx.gas_limit
             } else {
                 0
@@ -530,14 +586,16 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
 
             Ok(ResultAndState {
                 result: ExecutionResult::Halt {
-                    reason: HaltReason::FailedDeposit,
+                    reason: HaltReason::FailedDeposit(
+                        FailedDepositCause::from_invalid_transaction(&error),
+                    ),
                     gas_used,
+                    context: None,
                 },
                 state,
             })
-        } else {
-            Err(err)
         }
+        err => Err(err),
     })
 }
 
@@ -557,11 +615,15 @@ mod tests {
     use crate::{
         db::{EmptyDB, InMemoryDB},
         primitives::{
-            bytes, state::AccountInfo, Address, BedrockSpec, Bytes, Env, IsthmusSpec, LatestSpec,
-            RegolithSpec, B256,
+            address, bytes, state::AccountInfo, Address, BedrockSpec, Bytecode, Bytes, CancunSpec,
+            EcotoneSpec, Env, EnvInteropValidator, HoloceneSpec, InteropValidator, IsthmusSpec,
+            LatestSpec, Log, LogData, OutOfGasError, RegolithSpec, SpecId, TxKind, B256,
         },
-        L1BlockInfo,
+        test_utils::assert_state_invariants,
+        Evm, L1BlockInfo,
     };
+    use core::convert::Infallible;
+    use std::boxed::Box;
 
     /// Creates frame result.
     fn call_last_frame_return<SPEC: Spec>(
@@ -576,6 +638,7 @@ mod tests {
                 result: instruction_result,
                 output: Bytes::new(),
                 gas,
+                halt_context: None,
             },
             0..0,
         ));
@@ -623,7 +686,8 @@ mod tests {
             call_last_frame_return::<RegolithSpec>(env.clone(), InstructionResult::Stop, ret_gas);
         assert_eq!(gas.remaining(), 90);
         assert_eq!(gas.spent(), 10);
-        assert_eq!(gas.refunded(), 2); // min(20, 10/5)
+        assert_eq!(gas.refunded(), 20);
+        assert_eq!(gas.final_refund(), 2); // min(20, 10/5)
 
         let gas = call_last_frame_return::<RegolithSpec>(env, InstructionResult::Revert, ret_gas);
         assert_eq!(gas.remaining(), 90);
@@ -631,6 +695,35 @@ mod tests {
         assert_eq!(gas.refunded(), 0);
     }
 
+    #[test]
+    fn test_consume_gas_with_refund_quotient() {
+        let mut env = Env::default();
+        env.tx.gas_limit = 100;
+        env.tx.optimism.source_hash = None;
+
+        let mut ret_gas = Gas::new(50);
+        ret_gas.record_refund(20);
+
+        for (quotient, refunded) in [(10, 5), (0, 0)] {
+            env.cfg.refund_quotient = Some(quotient);
+            let gas = call_last_frame_return::<RegolithSpec>(
+                env.clone(),
+                InstructionResult::Stop,
+                ret_gas,
+            );
+            assert_eq!(gas.spent(), 50);
+            assert_eq!(gas.final_refund(), refunded);
+
+            let gas = call_last_frame_return::<RegolithSpec>(
+                env.clone(),
+                InstructionResult::Revert,
+                ret_gas,
+            );
+            assert_eq!(gas.spent(), 50);
+            assert_eq!(gas.refunded(), 0);
+        }
+    }
+
     #[test]
     fn test_consume_gas_sys_deposit_tx() {
         let mut env = Env::default();
@@ -643,6 +736,29 @@ mod tests {
         assert_eq!(gas.refunded(), 0);
     }
 
+    #[test]
+    fn test_deposit_refund_pre_regolith() {
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        context.evm.inner.env.tx.gas_limit = 100;
+        context.evm.inner.env.tx.optimism.source_hash = Some(B256::ZERO);
+        deduct_caller::<BedrockSpec, (), _>(&mut context).unwrap();
+
+        let mut gas = Gas::new(100);
+        assert!(gas.record_cost(60));
+        gas.record_refund(10);
+        refund::<BedrockSpec, (), _>(&mut context, &mut gas, 0);
+        reward_beneficiary::<BedrockSpec, (), _>(&mut context, &gas).unwrap();
+
+        // The refund is accumulated but not applied.
+        assert_eq!(gas.refunded(), 10);
+        assert_eq!(gas.final_refund(), 0);
+        let summary = context.evm.inner.fee_summary.unwrap();
+        assert_eq!(summary.gas_used_pre_refund, 60);
+        assert_eq!(summary.refund_accumulated, 10);
+        assert_eq!(summary.refund_applied, 0);
+        assert_eq!(summary.gas_used_final, 60);
+    }
+
     #[test]
     fn test_commit_mint_value() {
         let caller = Address::ZERO;
@@ -678,6 +794,151 @@ mod tests {
         assert_eq!(account.info.balance, U256::from(1010));
     }
 
+    #[test]
+    fn test_failed_deposit_caller() {
+        let caller = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1000),
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.env.tx.caller = caller;
+        context.evm.inner.env.tx.gas_limit = 100;
+        context.evm.inner.env.tx.optimism.source_hash = Some(B256::ZERO);
+        context.evm.inner.env.tx.optimism.mint = Some(10);
+        // Changes of the failed transaction are discarded.
+        context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(caller, &mut context.evm.inner.db)
+            .unwrap()
+            .data
+            .info
+            .balance = U256::ZERO;
+        let err = EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::HaltedDepositPostRegolith {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+            },
+        ));
+
+        let output = end::<RegolithSpec, (), _>(&mut context, Err(err)).unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::FailedDeposit(FailedDepositCause::OutOfGas(
+                    OutOfGasError::Basic
+                )),
+                gas_used: 100,
+                ..
+            }
+        ));
+        assert_eq!(output.state.len(), 1);
+        assert_state_invariants(&output.state, SpecId::REGOLITH);
+        let account = &output.state[&caller];
+        assert!(account.is_touched() && !account.is_loaded_as_not_existing());
+        assert!(!account.is_created_this_tx() && !account.was_destroyed());
+        assert_eq!(account.info.balance, U256::from(1010));
+        assert_eq!(account.info.nonce, 1);
+        assert!(context.evm.inner.journaled_state.state.is_empty());
+
+        // A caller that does not exist is loaded as such, and is not empty once its nonce is
+        // bumped even without mint.
+        let caller = Address::with_last_byte(2);
+        context.evm.inner.env.tx.caller = caller;
+        context.evm.inner.env.tx.optimism.mint = None;
+        let err = EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::HaltedDepositPostRegolith {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+            },
+        ));
+        let output = end::<RegolithSpec, (), _>(&mut context, Err(err)).unwrap();
+        assert_state_invariants(&output.state, SpecId::REGOLITH);
+        let account = &output.state[&caller];
+        assert!(account.is_touched() && account.is_loaded_as_not_existing());
+        assert_eq!(account.info.nonce, 1);
+        assert!(!account.is_touched_empty(SpecId::REGOLITH));
+    }
+
+    #[test]
+    fn test_failed_deposit_cause() {
+        let caller = Address::with_last_byte(1);
+        let target = address!("0000000000000000000000000000000000001000");
+        let run = |code: Bytes| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(
+                target,
+                AccountInfo {
+                    code: Some(Bytecode::new_raw(code)),
+                    ..Default::default()
+                },
+            );
+            let mut evm = Evm::builder()
+                .with_db(db)
+                .optimism()
+                .with_spec_id(SpecId::REGOLITH)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(target);
+                    tx.gas_limit = 30_000;
+                    tx.optimism.source_hash = Some(B256::ZERO);
+                    tx.optimism.mint = Some(10);
+                })
+                .build();
+            let output = evm.transact().unwrap();
+            assert_state_invariants(&output.state, SpecId::REGOLITH);
+            output
+        };
+
+        // JUMPDEST PUSH1 0 JUMP loops until the gas runs out.
+        let output = run(bytes!("5b600056"));
+        assert_eq!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::FailedDeposit(FailedDepositCause::OutOfGas(
+                    OutOfGasError::Basic
+                )),
+                gas_used: 30_000,
+                context: None,
+            }
+        );
+        assert_eq!(output.state[&caller].info.balance, U256::from(10));
+        assert_eq!(output.state[&caller].info.nonce, 1);
+
+        // INVALID halts without running out of gas, the failed deposit is otherwise the same.
+        let output = run(bytes!("fe"));
+        assert_eq!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::FailedDeposit(FailedDepositCause::Halt),
+                gas_used: 30_000,
+                context: None,
+            }
+        );
+        assert_eq!(output.state[&caller].info.nonce, 1);
+
+        // A reverting deposit is not a failed deposit, its result is the revert.
+        let output = run(bytes!("60006000fd"));
+        assert!(matches!(output.result, ExecutionResult::Revert { .. }));
+
+        assert_eq!(
+            FailedDepositCause::from_invalid_transaction(&InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::InvalidExecutingMessage
+            )),
+            FailedDepositCause::InvalidExecutingMessage
+        );
+        assert_eq!(
+            FailedDepositCause::from_invalid_transaction(&InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::DepositWithPriorityFee
+            )),
+            FailedDepositCause::InvalidTransaction
+        );
+    }
+
     #[test]
     fn test_remove_l1_cost_non_deposit() {
         let caller = Address::ZERO;
@@ -782,6 +1043,258 @@ mod tests {
         assert_eq!(account.info.balance, U256::from(1));
     }
 
+    /// Runs the fee stages of an Isthmus transaction ending with the given frame outcome and
+    /// asserts that the operator fee taken from the caller is the one credited to the vault.
+    fn assert_operator_fee_reconciles(instruction_result: InstructionResult, ret_gas: Gas) {
+        let caller = Address::with_last_byte(1);
+        let initial_balance = U256::from(1_000_000);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: initial_balance,
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        // Only the operator fee is non-zero, L1 cost and gas price are zero.
+        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(1_500_000)),
+            operator_fee_constant: Some(U256::from(7)),
+            ..Default::default()
+        });
+        context.evm.inner.env.tx.caller = caller;
+        context.evm.inner.env.tx.gas_limit = ret_gas.limit();
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        deduct_caller::<IsthmusSpec, (), _>(&mut context).unwrap();
+
+        let mut frame_result = FrameResult::Call(CallOutcome::new(
+            InterpreterResult {
+                result: instruction_result,
+                output: Bytes::new(),
+                gas: ret_gas,
+                halt_context: None,
+            },
+            0..0,
+        ));
+        last_frame_return::<IsthmusSpec, _, _>(&mut context, &mut frame_result).unwrap();
+        refund::<IsthmusSpec, _, _>(&mut context, frame_result.gas_mut(), 0);
+        let gas = *frame_result.gas();
+
+        reimburse_caller::<IsthmusSpec, (), _>(&mut context, &gas).unwrap();
+        reward_beneficiary::<IsthmusSpec, (), _>(&mut context, &gas).unwrap();
+
+        let caller_balance = context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(caller, &mut context.evm.inner.db)
+            .unwrap()
+            .info
+            .balance;
+        let vault_balance = context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(OPERATOR_FEE_RECIPIENT, &mut context.evm.inner.db)
+            .unwrap()
+            .info
+            .balance;
+        let expected = context
+            .evm
+            .inner
+            .l1_block_info
+            .as_ref()
+            .unwrap()
+            .operator_fee_charge(
+                &bytes!("FACADE"),
+                U256::from(gas.spent_sub_refunded()),
+                SpecId::ISTHMUS,
+            )
+            .unwrap();
+
+        assert_eq!(vault_balance, expected);
+        assert_eq!(initial_balance - caller_balance, vault_balance);
+    }
+
+    #[test]
+    fn test_operator_fee_reconciles_success() {
+        let mut ret_gas = Gas::new(101);
+        assert!(ret_gas.record_cost(41));
+        ret_gas.record_refund(10);
+        assert_operator_fee_reconciles(InstructionResult::Stop, ret_gas);
+    }
+
+    #[test]
+    fn test_operator_fee_reconciles_revert() {
+        let mut ret_gas = Gas::new(101);
+        assert!(ret_gas.record_cost(41));
+        ret_gas.record_refund(10);
+        assert_operator_fee_reconciles(InstructionResult::Revert, ret_gas);
+    }
+
+    #[test]
+    fn test_operator_fee_reconciles_out_of_gas() {
+        assert_operator_fee_reconciles(InstructionResult::OutOfGas, Gas::new_spent(101));
+    }
+
+    #[test]
+    fn test_op_tx_fees_match_vault_balances() {
+        let caller = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000),
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            operator_fee_scalar: Some(U256::from(1_500_000)),
+            operator_fee_constant: Some(U256::from(7)),
+            ..Default::default()
+        });
+        context.evm.inner.env.tx.caller = caller;
+        context.evm.inner.env.tx.gas_limit = 100;
+        context.evm.inner.env.tx.gas_price = U256::from(3);
+        context.evm.inner.env.block.basefee = U256::from(2);
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        deduct_caller::<IsthmusSpec, (), _>(&mut context).unwrap();
+        // Fees are only known once the gas used is known.
+        assert_eq!(context.evm.inner.op_tx_fees, None);
+        assert_eq!(context.evm.inner.fee_summary, None);
+
+        let mut gas = Gas::new(100);
+        assert!(gas.record_cost(41));
+        reimburse_caller::<IsthmusSpec, (), _>(&mut context, &gas).unwrap();
+        reward_beneficiary::<IsthmusSpec, (), _>(&mut context, &gas).unwrap();
+
+        let mut balance = |address| {
+            context
+                .evm
+                .inner
+                .journaled_state
+                .load_account(address, &mut context.evm.inner.db)
+                .unwrap()
+                .info
+                .balance
+        };
+        let l1_vault = balance(optimism::L1_FEE_RECIPIENT);
+        let operator_vault = balance(OPERATOR_FEE_RECIPIENT);
+        let base_fee_vault = balance(optimism::BASE_FEE_RECIPIENT);
+        let coinbase = balance(Address::ZERO);
+
+        let fees = context.evm.inner.op_tx_fees.unwrap();
+        // See `test_calculate_tx_l1_cost_fjord`.
+        assert_eq!(fees.l1_cost, U256::from(1700));
+        assert_eq!(fees.l1_data_gas, U256::from(1600));
+        assert_eq!(fees.l1_gas_price, U256::from(1_000));
+        // 41 * 1.5 + 7
+        assert_eq!(fees.operator_fee, U256::from(68));
+        assert_eq!(fees.l1_cost, l1_vault);
+        assert_eq!(fees.operator_fee, operator_vault);
+
+        let summary = context.evm.inner.fee_summary.unwrap();
+        assert_eq!(summary.effective_gas_price, U256::from(3));
+        assert_eq!(summary.tip_per_gas, U256::from(1));
+        // Base fee goes to the Base Fee Vault instead of being burned.
+        assert_eq!(summary.burned, U256::ZERO);
+        assert_eq!(summary.base_fee_vault, U256::from(82));
+        assert_eq!(summary.base_fee_vault, base_fee_vault);
+        assert_eq!(summary.paid_to_coinbase, U256::from(41));
+        assert_eq!(summary.paid_to_coinbase, coinbase);
+        assert_eq!(summary.l1_fee, l1_vault);
+        assert_eq!(summary.operator_fee, operator_vault);
+    }
+
+    #[test]
+    fn test_op_tx_fees_deposit() {
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        context.evm.inner.env.tx.optimism.source_hash = Some(B256::ZERO);
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        context.evm.inner.env.tx.gas_price = U256::from(10);
+
+        deduct_caller::<IsthmusSpec, (), _>(&mut context).unwrap();
+        reward_beneficiary::<IsthmusSpec, (), _>(&mut context, &Gas::new(100)).unwrap();
+
+        assert_eq!(context.evm.inner.op_tx_fees, Some(OpTxFees::default()));
+        // Deposits don't pay for gas, whatever their gas price.
+        let summary = context.evm.inner.fee_summary.unwrap();
+        assert_eq!(summary, FeeSummary::default());
+        assert_eq!(summary.effective_gas_price, U256::ZERO);
+        assert_eq!(summary.blob_gas_price, None);
+    }
+
+    /// Validates and deducts the fees of a transaction from a zero-balance caller with balance
+    /// checks disabled and returns the caller balance.
+    #[cfg(feature = "optional_balance_check")]
+    fn zero_balance_caller_after_deduction<SPEC: Spec>(validate: bool) -> U256 {
+        let caller = Address::with_last_byte(1);
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        context.evm.inner.env.cfg.disable_balance_check = true;
+        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_fee_overhead: Some(U256::from(1_000)),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            operator_fee_scalar: Some(U256::from(10_000_000)),
+            operator_fee_constant: Some(U256::from(50)),
+            ..Default::default()
+        });
+        context.evm.inner.env.tx.caller = caller;
+        context.evm.inner.env.tx.gas_limit = 10;
+        context.evm.inner.env.tx.gas_price = U256::from(2);
+        context.evm.inner.env.tx.value = U256::from(5);
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+
+        if validate {
+            validate_tx_against_state::<SPEC, (), _>(&mut context).unwrap();
+        }
+        deduct_caller::<SPEC, (), _>(&mut context).unwrap();
+
+        context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(caller, &mut context.evm.inner.db)
+            .unwrap()
+            .info
+            .balance
+    }
+
+    #[test]
+    #[cfg(feature = "optional_balance_check")]
+    fn test_balance_check_disabled_covers_fees() {
+        // The caller is topped up with the gas cost, value, L1 cost and operator fee
+        // and is left with the value after the deduction.
+        assert_eq!(
+            zero_balance_caller_after_deduction::<RegolithSpec>(true),
+            U256::from(5)
+        );
+        assert_eq!(
+            zero_balance_caller_after_deduction::<IsthmusSpec>(true),
+            U256::from(5)
+        );
+
+        // Without validation the fees are credited to the caller before they are charged.
+        assert_eq!(
+            zero_balance_caller_after_deduction::<RegolithSpec>(false),
+            U256::ZERO
+        );
+        assert_eq!(
+            zero_balance_caller_after_deduction::<IsthmusSpec>(false),
+            U256::ZERO
+        );
+    }
+
     #[test]
     fn test_remove_l1_cost_lack_of_funds() {
         let caller = Address::ZERO;
@@ -814,6 +1327,191 @@ mod tests {
         );
     }
 
+    /// Validates a transfer of 5 wei with a gas limit of 100 at a gas price of 10 from a caller
+    /// with `info` against the state, with the mainnet and the Optimism handlers.
+    ///
+    /// Returns the results and the balances of the caller afterwards. With `l1_cost` the
+    /// transaction has an L1 cost of 1048.
+    fn validate_against_state_both(
+        info: AccountInfo,
+        l1_cost: bool,
+        modify_env: impl Fn(&mut Env),
+    ) -> [(Result<(), EVMError<Infallible>>, U256); 2] {
+        let caller = Address::with_last_byte(1);
+        [false, true].map(|is_optimism| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(caller, info.clone());
+            let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+            context.evm.inner.l1_block_info = Some(L1BlockInfo {
+                l1_base_fee: U256::from(1_000),
+                l1_fee_overhead: Some(U256::from(1_000)),
+                l1_base_fee_scalar: U256::from(1_000),
+                ..Default::default()
+            });
+            let env = &mut context.evm.inner.env;
+            env.tx.caller = caller;
+            env.tx.gas_limit = 100;
+            env.tx.gas_price = U256::from(10);
+            env.tx.value = U256::from(5);
+            env.tx.optimism.enveloped_tx = Some(if l1_cost {
+                bytes!("FACADE")
+            } else {
+                Bytes::new()
+            });
+            modify_env(env);
+
+            let result = if is_optimism {
+                validate_tx_against_state::<RegolithSpec, (), _>(&mut context)
+            } else {
+                mainnet::validate_tx_against_state::<RegolithSpec, (), _>(&mut context)
+            };
+            let balance = context.evm.inner.journaled_state.state[&caller]
+                .info
+                .balance;
+            (result, balance)
+        })
+    }
+
+    #[test]
+    fn test_validate_tx_against_state_matches_mainnet() {
+        let with_balance = |balance: u64, info: AccountInfo| AccountInfo {
+            balance: U256::from(balance),
+            ..info
+        };
+        let eoa = AccountInfo {
+            nonce: 1,
+            ..Default::default()
+        };
+        let with_code = AccountInfo::from_bytecode(Bytecode::new_legacy(bytes!("00")));
+        let delegated = AccountInfo::from_bytecode(Bytecode::new_eip7702(Address::ZERO));
+
+        let cases = [
+            // Exact balance and one wei short.
+            (with_balance(1005, eoa.clone()), None, Ok(())),
+            (
+                with_balance(1004, eoa.clone()),
+                None,
+                Err(InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(U256::from(1005)),
+                    balance: Box::new(U256::from(1004)),
+                }),
+            ),
+            // EIP-3607, EIP-7702 delegated senders are allowed.
+            (
+                with_balance(1005, with_code),
+                None,
+                Err(InvalidTransaction::RejectCallerWithCode),
+            ),
+            (with_balance(1005, delegated), None, Ok(())),
+            // Nonce checks.
+            (with_balance(1005, eoa.clone()), Some(1), Ok(())),
+            (
+                with_balance(1005, eoa.clone()),
+                Some(2),
+                Err(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 }),
+            ),
+            (
+                with_balance(1005, eoa),
+                Some(0),
+                Err(InvalidTransaction::NonceTooLow { tx: 0, state: 1 }),
+            ),
+        ];
+        for (info, nonce, expected) in cases {
+            let [mainnet, optimism] =
+                validate_against_state_both(info, false, |env| env.tx.nonce = nonce);
+            assert_eq!(mainnet.0, expected.map_err(EVMError::Transaction));
+            assert_eq!(mainnet, optimism);
+        }
+    }
+
+    #[test]
+    fn test_validate_tx_against_state_l1_cost_balance() {
+        // The L1 cost is only required by Optimism.
+        let [mainnet, optimism] =
+            validate_against_state_both(AccountInfo::from_balance(U256::from(1005)), true, |_| {});
+        assert_eq!(mainnet.0, Ok(()));
+        assert_eq!(
+            optimism.0,
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(U256::from(1005 + 1048)),
+                    balance: Box::new(U256::from(1005)),
+                }
+            ))
+        );
+
+        let [mainnet, optimism] = validate_against_state_both(
+            AccountInfo::from_balance(U256::from(1005 + 1048)),
+            true,
+            |_| {},
+        );
+        assert_eq!(mainnet.0, Ok(()));
+        assert_eq!(optimism.0, Ok(()));
+    }
+
+    #[cfg(feature = "optional_balance_check")]
+    #[test]
+    fn test_validate_tx_against_state_balance_check_disabled() {
+        for (l1_cost, op_balance) in [(false, 1005), (true, 1005 + 1048)] {
+            let [mainnet, optimism] =
+                validate_against_state_both(AccountInfo::default(), l1_cost, |env| {
+                    env.cfg.disable_balance_check = true
+                });
+            // The balance is raised to the cost of the transaction.
+            assert_eq!(mainnet, (Ok(()), U256::from(1005)));
+            assert_eq!(optimism, (Ok(()), U256::from(op_balance)));
+        }
+    }
+
+    #[test]
+    fn test_output_rejects_invalid_executing_message() {
+        /// Rejects the executing message with the message hash `2`.
+        #[derive(Debug)]
+        struct RejectSecondMessage;
+
+        impl InteropValidator for RejectSecondMessage {
+            fn validate_executing_message(&self, log: &Log) -> bool {
+                log.topics().get(1) != Some(&B256::with_last_byte(2))
+            }
+        }
+
+        let run = |messages: &[u8]| {
+            let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+            context.evm.inner.env.cfg.interop_validator =
+                EnvInteropValidator::Custom(Arc::new(RejectSecondMessage));
+            for msg_hash in messages {
+                context.evm.inner.journaled_state.logs.push(Log {
+                    address: optimism::CROSS_L2_INBOX,
+                    data: LogData::new_unchecked(
+                        vec![
+                            optimism::EXECUTING_MESSAGE_TOPIC,
+                            B256::with_last_byte(*msg_hash),
+                        ],
+                        Bytes::new(),
+                    ),
+                });
+            }
+            let frame_result = FrameResult::Call(CallOutcome::new(
+                InterpreterResult {
+                    result: InstructionResult::Stop,
+                    output: Bytes::new(),
+                    gas: Gas::new(0),
+                    halt_context: None,
+                },
+                0..0,
+            ));
+            output::<IsthmusSpec, _, _>(&mut context, frame_result)
+        };
+
+        assert!(run(&[1]).is_ok());
+        assert_eq!(
+            run(&[1, 2]).unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::OptimismError(
+                OptimismInvalidTransaction::InvalidExecutingMessage
+            ))
+        );
+    }
+
     #[test]
     fn test_validate_sys_tx() {
         // mark the tx as a system transaction.
@@ -838,6 +1536,189 @@ mod tests {
         assert!(validate_env::<RegolithSpec, EmptyDB>(&env).is_ok());
     }
 
+    #[test]
+    fn test_validate_deposit_tx_contradicting_fields() {
+        let mut env = Env::default();
+        env.tx.optimism.source_hash = Some(B256::ZERO);
+        // Deposit envelopes and a zero priority fee are allowed.
+        env.tx.optimism.enveloped_tx = Some(bytes!("7EFACADE"));
+        env.tx.gas_priority_fee = Some(U256::ZERO);
+        assert!(validate_env::<RegolithSpec, EmptyDB>(&env).is_ok());
+
+        let assert_rejected = |env: &Env, error: OptimismInvalidTransaction| {
+            assert_eq!(
+                validate_env::<RegolithSpec, EmptyDB>(env),
+                Err(EVMError::Transaction(InvalidTransaction::OptimismError(
+                    error
+                )))
+            );
+        };
+
+        let mut invalid = env.clone();
+        invalid.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        assert_rejected(
+            &invalid,
+            OptimismInvalidTransaction::DepositWithNonDepositEnvelope,
+        );
+
+        let mut invalid = env.clone();
+        invalid.tx.gas_priority_fee = Some(U256::from(1));
+        assert_rejected(&invalid, OptimismInvalidTransaction::DepositWithPriorityFee);
+
+        let mut invalid = env;
+        invalid.tx.blob_hashes = vec![B256::ZERO];
+        assert_rejected(&invalid, OptimismInvalidTransaction::DepositWithBlobHashes);
+    }
+
+    #[test]
+    fn test_validate_blob_tx() {
+        let mut blob_hash = B256::ZERO;
+        blob_hash[0] = crate::primitives::VERSIONED_HASH_VERSION_KZG;
+        let mut env = Env::default();
+        env.tx.transact_to = TxKind::Call(Address::ZERO);
+        env.tx.blob_hashes = vec![blob_hash];
+        env.tx.max_fee_per_blob_gas = Some(U256::from(1));
+        // Valid on L1.
+        assert!(env.validate_tx::<CancunSpec>().is_ok());
+
+        let rejected = Err(EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::BlobTransactionNotSupported,
+        )));
+        assert_eq!(validate_env::<EcotoneSpec, EmptyDB>(&env), rejected);
+
+        // Simulating blob txs without proofs doesn't make them valid on OP.
+        #[cfg(feature = "optional_blob_validation")]
+        {
+            let mut env = env.clone();
+            env.cfg.skip_blob_validation = true;
+            assert_eq!(validate_env::<EcotoneSpec, EmptyDB>(&env), rejected);
+        }
+
+        env.tx.blob_hashes.clear();
+        assert_eq!(validate_env::<EcotoneSpec, EmptyDB>(&env), rejected);
+    }
+
+    #[test]
+    fn test_missing_envelope() {
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        let missing_envelope = Err(EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::MissingEnvelope,
+        )));
+
+        assert_eq!(
+            validate_tx_against_state::<RegolithSpec, (), _>(&mut context),
+            missing_envelope
+        );
+        assert_eq!(
+            deduct_caller::<RegolithSpec, (), _>(&mut context),
+            missing_envelope
+        );
+        // L1 block info was loaded by the validation above.
+        assert_eq!(
+            reward_beneficiary::<RegolithSpec, (), _>(&mut context, &Gas::new(0)),
+            missing_envelope
+        );
+    }
+
+    #[test]
+    fn test_missing_l1_block_info() {
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        let missing_l1_block_info = Err(EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::MissingL1BlockInfo,
+        )));
+
+        assert_eq!(
+            deduct_caller::<IsthmusSpec, (), _>(&mut context),
+            missing_l1_block_info
+        );
+        assert_eq!(
+            reimburse_caller::<IsthmusSpec, (), _>(&mut context, &Gas::new(0)),
+            missing_l1_block_info
+        );
+        assert_eq!(
+            reward_beneficiary::<RegolithSpec, (), _>(&mut context, &Gas::new(0)),
+            missing_l1_block_info
+        );
+    }
+
+    #[test]
+    fn test_missing_operator_fee_params() {
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(InMemoryDB::default());
+        context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+        // L1 block info set by hand, without the operator fee parameters of Isthmus.
+        context.evm.inner.l1_block_info = Some(L1BlockInfo::default());
+        let missing_params = Err(EVMError::Transaction(InvalidTransaction::OptimismError(
+            OptimismInvalidTransaction::MissingOperatorFeeParams,
+        )));
+
+        assert_eq!(
+            validate_tx_against_state::<IsthmusSpec, (), _>(&mut context),
+            missing_params
+        );
+        assert_eq!(
+            deduct_caller::<IsthmusSpec, (), _>(&mut context),
+            missing_params
+        );
+        assert_eq!(
+            reimburse_caller::<IsthmusSpec, (), _>(&mut context, &Gas::new(0)),
+            missing_params
+        );
+        assert_eq!(
+            reward_beneficiary::<IsthmusSpec, (), _>(&mut context, &Gas::new(0)),
+            missing_params
+        );
+
+        // The parameters are only needed from Isthmus.
+        assert_eq!(
+            validate_tx_against_state::<HoloceneSpec, (), _>(&mut context),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_synthesize_missing_envelope() {
+        let caller = Address::ZERO;
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000),
+                ..Default::default()
+            },
+        );
+        let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
+        context.evm.l1_block_info = Some(L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_fee_overhead: Some(U256::from(1_000)),
+            l1_base_fee_scalar: U256::from(1_000),
+            ..Default::default()
+        });
+        context.evm.inner.env.cfg.synthesize_missing_envelope = true;
+
+        let envelope = optimism::synthetic_envelope(&context.evm.inner.env.tx);
+        let l1_cost = context
+            .evm
+            .l1_block_info
+            .clone()
+            .unwrap()
+            .calculate_tx_l1_cost(&envelope, SpecId::REGOLITH);
+        assert_ne!(l1_cost, U256::ZERO);
+
+        validate_tx_against_state::<RegolithSpec, (), _>(&mut context).unwrap();
+        deduct_caller::<RegolithSpec, (), _>(&mut context).unwrap();
+
+        // The L1 cost of the synthetic envelope is charged, the envelope isn't stored.
+        let account = context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(caller, &mut context.evm.inner.db)
+            .unwrap();
+        assert_eq!(account.info.balance, U256::from(1_000_000) - l1_cost);
+        assert_eq!(context.evm.inner.env.tx.optimism.enveloped_tx, None);
+    }
+
     #[test]
     fn test_validate_tx_against_state_deposit_tx() {
         // Set source hash.
@@ -847,4 +1728,111 @@ mod tests {
         // Nonce and balance checks should be skipped for deposit transactions.
         assert!(validate_env::<LatestSpec, EmptyDB>(&env).is_ok());
     }
+
+    #[test]
+    fn warm_addresses_include_op_precompiles() {
+        let p256 = revm_precompile::secp256r1::P256VERIFY.0;
+        let env = Env::default();
+        for (spec, warm) in [
+            (SpecId::ECOTONE, false),
+            (SpecId::FJORD, true),
+            (SpecId::GRANITE, true),
+        ] {
+            assert_eq!(
+                warm_addresses_for_tx(&env, spec).any(|address| address == p256),
+                warm,
+                "{spec:?}"
+            );
+        }
+        // Mainnet doesn't have P256VERIFY at the same spec.
+        assert!(!mainnet::warm_addresses_for_tx(&env, SpecId::FJORD).any(|address| address == p256));
+    }
+
+    #[test]
+    fn gas_opcode_in_deposits() {
+        let caller = Address::with_last_byte(1);
+        let target = address!("0000000000000000000000000000000000001000");
+        // GAS before and after clearing slot 0, returned as two words.
+        let code = bytes!("5a60005260006000555a60205260406000f3");
+        let run = |spec: SpecId| {
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(
+                target,
+                AccountInfo {
+                    code: Some(Bytecode::new_raw(code.clone())),
+                    ..Default::default()
+                },
+            );
+            db.insert_account_storage(target, U256::ZERO, U256::from(1))
+                .unwrap();
+            Evm::builder()
+                .with_db(db)
+                .optimism()
+                .with_spec_id(spec)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TxKind::Call(target);
+                    tx.gas_limit = 100_000;
+                    tx.optimism.source_hash = Some(B256::ZERO);
+                    tx.optimism.mint = Some(0);
+                    tx.optimism.is_system_transaction = Some(false);
+                })
+                .build()
+                .transact()
+                .unwrap()
+                .result
+        };
+
+        // `last_frame_return` rewrites the gas of deposits after execution, and only applies
+        // refunds from Regolith, but GAS observes the same values.
+        let bedrock = run(SpecId::BEDROCK);
+        let regolith = run(SpecId::REGOLITH);
+        assert_eq!(bedrock.output(), regolith.output());
+        let output = regolith.output().unwrap();
+        assert_eq!(U256::from_be_slice(&output[..32]), U256::from(78_998));
+        assert_eq!(U256::from_be_slice(&output[32..]), U256::from(73_981));
+        assert_eq!(bedrock.gas_used(), 100_000);
+        assert_eq!(regolith.gas_used(), 21_000 + 5_034 - 4_800);
+    }
+
+    #[test]
+    fn prepared_deposit_executes_like_transact() {
+        let caller = Address::with_last_byte(1);
+        let target = Address::with_last_byte(2);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            target,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(bytes!("6001600055"))),
+                ..Default::default()
+            },
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .optimism()
+            .with_spec_id(SpecId::REGOLITH)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TxKind::Call(target);
+                tx.gas_limit = 100_000;
+                tx.optimism.source_hash = Some(B256::ZERO);
+                tx.optimism.mint = Some(1_000);
+                tx.optimism.is_system_transaction = Some(false);
+            })
+            .build();
+
+        let prepared = evm.prepare_transaction().unwrap();
+        // The mint is applied by `deduct_caller` in pre-execution.
+        let state = &evm.context.evm.inner.journaled_state.state;
+        assert_eq!(state[&caller].info.balance, U256::from(1_000));
+        let result =
+            evm.create_first_frame(prepared.inputs().clone())
+                .and_then(|first_frame_or_result| match first_frame_or_result {
+                    crate::FrameOrResult::Frame(first_frame) => evm.run_the_loop(first_frame),
+                    crate::FrameOrResult::Result(result) => Ok(result),
+                });
+        let output = evm.finish_transaction(prepared, result).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(output, evm.transact().unwrap());
+    }
 }
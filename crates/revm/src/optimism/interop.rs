@@ -0,0 +1,99 @@
+//! OP interop executing message validation.
+
+use crate::primitives::{
+    address, b256, Address, EnvInteropValidator, Log, OptimismInvalidTransaction, B256,
+};
+
+/// The address of the CrossL2Inbox predeploy.
+pub const CROSS_L2_INBOX: Address = address!("4200000000000000000000000000000000000022");
+
+/// Topic of the CrossL2Inbox
+/// `ExecutingMessage(bytes32,(address,uint256,uint256,uint256,uint256))` event.
+pub const EXECUTING_MESSAGE_TOPIC: B256 =
+    b256!("5c37832d2e8d10e346e55ad62071a6a2f9fa5130614ef2ec6617555c6f467ba7");
+
+/// Returns `true` if the log is an executing message emitted by the CrossL2Inbox.
+#[inline]
+pub fn is_executing_message(log: &Log) -> bool {
+    log.address == CROSS_L2_INBOX && log.topics().first() == Some(&EXECUTING_MESSAGE_TOPIC)
+}
+
+/// Validates all executing messages found in `logs` with the given validator.
+///
+/// Logs that are not executing messages are ignored.
+pub fn validate_executing_messages(
+    validator: &EnvInteropValidator,
+    logs: &[Log],
+) -> Result<(), OptimismInvalidTransaction> {
+    if logs
+        .iter()
+        .filter(|log| is_executing_message(log))
+        .all(|log| validator.validate_executing_message(log))
+    {
+        Ok(())
+    } else {
+        Err(OptimismInvalidTransaction::InvalidExecutingMessage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{Bytes, InteropValidator, LogData};
+    use std::{sync::Arc, vec};
+
+    /// Rejects executing messages for the given message hash.
+    #[derive(Debug)]
+    struct RejectMessage(B256);
+
+    impl InteropValidator for RejectMessage {
+        fn validate_executing_message(&self, log: &Log) -> bool {
+            log.topics().get(1) != Some(&self.0)
+        }
+    }
+
+    fn executing_message(msg_hash: B256) -> Log {
+        Log {
+            address: CROSS_L2_INBOX,
+            data: LogData::new_unchecked(vec![EXECUTING_MESSAGE_TOPIC, msg_hash], Bytes::new()),
+        }
+    }
+
+    #[test]
+    fn test_is_executing_message() {
+        assert!(is_executing_message(&executing_message(B256::ZERO)));
+
+        let mut log = executing_message(B256::ZERO);
+        log.address = Address::ZERO;
+        assert!(!is_executing_message(&log));
+
+        let log = Log {
+            address: CROSS_L2_INBOX,
+            data: LogData::new_unchecked(vec![B256::ZERO], Bytes::new()),
+        };
+        assert!(!is_executing_message(&log));
+    }
+
+    #[test]
+    fn test_validate_executing_messages() {
+        let accepted = executing_message(B256::with_last_byte(1));
+        let rejected = executing_message(B256::with_last_byte(2));
+        let validator =
+            EnvInteropValidator::Custom(Arc::new(RejectMessage(B256::with_last_byte(2))));
+
+        assert_eq!(
+            validate_executing_messages(&validator, core::slice::from_ref(&accepted)),
+            Ok(())
+        );
+        assert_eq!(
+            validate_executing_messages(&validator, &[accepted.clone(), rejected.clone()]),
+            Err(OptimismInvalidTransaction::InvalidExecutingMessage)
+        );
+
+        // The default validator accepts everything.
+        assert_eq!(
+            validate_executing_messages(&EnvInteropValidator::default(), &[accepted, rejected]),
+            Ok(())
+        );
+    }
+}
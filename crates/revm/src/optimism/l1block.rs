@@ -1,5 +1,8 @@
-use crate::optimism::fast_lz::flz_compress_len;
-use crate::primitives::{address, db::Database, Address, SpecId, U256};
+use crate::optimism::features;
+use crate::primitives::{
+    address, db::Database, flz_compress_len, gas_to_u256, Address, CalldataStats,
+    OptimismInvalidTransaction, SpecId, U256,
+};
 use core::ops::Mul;
 use revm_interpreter::Gas;
 
@@ -64,6 +67,23 @@ const L1_COST_INTERCEPT: u64 = 42_585_600;
 /// <https://github.com/ethereum-optimism/op-geth/blob/647c346e2bef36219cc7b47d76b1cb87e7ca29e4/core/types/rollup_cost.go#82>
 const MIN_TX_SIZE_SCALED: u64 = 100 * 1_000_000;
 
+/// Fees charged to an Optimism transaction.
+///
+/// All values are zero for deposit transactions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpTxFees {
+    /// L1 data fee charged to the caller and sent to the [`L1_FEE_RECIPIENT`].
+    pub l1_cost: U256,
+    /// L1 data gas of the enveloped transaction.
+    pub l1_data_gas: U256,
+    /// L1 gas price, the base fee of the L1 origin block.
+    pub l1_gas_price: U256,
+    /// Operator fee charged to the caller, after the refund, and sent to the
+    /// [`OPERATOR_FEE_RECIPIENT`].
+    pub operator_fee: U256,
+}
+
 /// L1 block info
 ///
 /// We can extract L1 epoch data from each L2 block, by looking at the `setL1BlockValues`
@@ -76,6 +96,7 @@ const MIN_TX_SIZE_SCALED: u64 = 100 * 1_000_000;
 ///
 /// For now, we only care about the fields necessary for L1 cost calculation.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct L1BlockInfo {
     /// The base fee of the L1 origin block.
     pub l1_base_fee: U256,
@@ -108,7 +129,8 @@ impl L1BlockInfo {
 
         let l1_base_fee = db.storage(L1_BLOCK_CONTRACT, L1_BASE_FEE_SLOT)?;
 
-        if !spec_id.is_enabled_in(SpecId::ECOTONE) {
+        let features = features(spec_id);
+        if !features.uses_ecotone_scalars() {
             let l1_fee_overhead = db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT)?;
             let l1_fee_scalar = db.storage(L1_BLOCK_CONTRACT, L1_SCALAR_SLOT)?;
 
@@ -141,7 +163,7 @@ impl L1BlockInfo {
                 .then(|| db.storage(L1_BLOCK_CONTRACT, L1_OVERHEAD_SLOT))
                 .transpose()?;
 
-            if spec_id.is_enabled_in(SpecId::ISTHMUS) {
+            if features.is_operator_fee_enabled() {
                 let operator_fee_scalars = db
                     .storage(L1_BLOCK_CONTRACT, OPERATOR_FEE_SCALARS_SLOT)?
                     .to_be_bytes::<32>();
@@ -189,45 +211,73 @@ impl L1BlockInfo {
 
     /// Calculate the operator fee for executing this transaction.
     ///
-    /// Introduced in isthmus. Prior to isthmus, the operator fee is always zero.
-    pub fn operator_fee_charge(&self, input: &[u8], gas_limit: U256, spec_id: SpecId) -> U256 {
+    /// `operatorFeeCharge = gasLimit * operatorFeeScalar / 1e6 + operatorFeeConstant`
+    ///
+    /// Introduced in isthmus. Prior to isthmus, the operator fee is always zero. Deposit
+    /// transactions and empty inputs are never charged.
+    ///
+    /// Returns [`OptimismInvalidTransaction::MissingOperatorFeeParams`] if the operator fee
+    /// scalar or constant is not loaded.
+    pub fn operator_fee_charge(
+        &self,
+        input: &[u8],
+        gas_limit: U256,
+        spec_id: SpecId,
+    ) -> Result<U256, OptimismInvalidTransaction> {
         // If the input is a deposit transaction or empty, the default value is zero.
         if input.is_empty() || input.first() == Some(&0x7F) {
-            return U256::ZERO;
+            return Ok(U256::ZERO);
         }
-        if !spec_id.is_enabled_in(SpecId::ISTHMUS) {
-            return U256::ZERO;
+        if !features(spec_id).is_operator_fee_enabled() {
+            return Ok(U256::ZERO);
         }
-        let operator_fee_scalar = self
-            .operator_fee_scalar
-            .expect("Missing operator fee scalar for isthmus L1 Block");
-        let operator_fee_constant = self
-            .operator_fee_constant
-            .expect("Missing operator fee constant for isthmus L1 Block");
-
-        let product = gas_limit.saturating_mul(operator_fee_scalar)
-            / (U256::from(OPERATOR_FEE_SCALAR_DECIMAL));
 
-        product.saturating_add(operator_fee_constant)
+        self.operator_fee_for_gas(gas_limit)
     }
 
-    /// Calculate the operator fee for executing this transaction.
+    /// Calculate the operator fee refund for the unused part of the gas limit.
+    ///
+    /// `operatorFeeRefund = operatorFee(gasLimit) - operatorFee(gasUsed)`
+    ///
+    /// where `gasUsed` is the gas spent minus the gas refunded. The refund is computed as the
+    /// difference of two rounded charges, so that the charge at the gas limit minus this refund
+    /// is exactly the operator fee of the gas used, which is the amount credited to
+    /// [`OPERATOR_FEE_RECIPIENT`].
     ///
     /// Introduced in isthmus. Prior to isthmus, the operator fee is always zero.
-    pub fn operator_fee_refund(&self, gas: &Gas, spec_id: SpecId) -> U256 {
-        if !spec_id.is_enabled_in(SpecId::ISTHMUS) {
-            return U256::ZERO;
+    ///
+    /// Returns [`OptimismInvalidTransaction::MissingOperatorFeeParams`] if the operator fee
+    /// scalar or constant is not loaded.
+    pub fn operator_fee_refund(
+        &self,
+        gas: &Gas,
+        spec_id: SpecId,
+    ) -> Result<U256, OptimismInvalidTransaction> {
+        if !features(spec_id).is_operator_fee_enabled() {
+            return Ok(U256::ZERO);
         }
 
-        let operator_fee_scalar = self
-            .operator_fee_scalar
-            .expect("Missing operator fee scalar for isthmus L1 Block");
+        // The constant cancels out but is kept to preserve the rounding of the charge.
+        let charged = self.operator_fee_for_gas(gas_to_u256(gas.limit()))?;
+        let used = self.operator_fee_for_gas(gas_to_u256(gas.spent_sub_refunded()))?;
+
+        Ok(charged.saturating_sub(used))
+    }
+
+    /// Calculate the operator fee for the given amount of gas.
+    ///
+    /// `operatorFee = gas * operatorFeeScalar / 1e6 + operatorFeeConstant`
+    fn operator_fee_for_gas(&self, gas: U256) -> Result<U256, OptimismInvalidTransaction> {
+        let (Some(operator_fee_scalar), Some(operator_fee_constant)) =
+            (self.operator_fee_scalar, self.operator_fee_constant)
+        else {
+            return Err(OptimismInvalidTransaction::MissingOperatorFeeParams);
+        };
 
-        // We're computing the difference between two operator fees, so no need to include the
-        // constant.
+        let product =
+            gas.saturating_mul(operator_fee_scalar) / (U256::from(OPERATOR_FEE_SCALAR_DECIMAL));
 
-        operator_fee_scalar.saturating_mul(U256::from(gas.remaining() + gas.refunded() as u64))
-            / (U256::from(OPERATOR_FEE_SCALAR_DECIMAL))
+        Ok(product.saturating_add(operator_fee_constant))
     }
 
     /// Calculate the data gas for posting the transaction on L1. Calldata costs 16 gas per byte
@@ -238,7 +288,8 @@ impl L1BlockInfo {
     /// Prior to regolith, an extra 68 non-zero bytes were included in the rollup data costs to
     /// account for the empty signature.
     pub fn data_gas(&self, input: &[u8], spec_id: SpecId) -> U256 {
-        if spec_id.is_enabled_in(SpecId::FJORD) {
+        let features = features(spec_id);
+        if features.uses_fastlz_cost() {
             let estimated_size = self.tx_estimated_size_fjord(input);
 
             return estimated_size
@@ -246,16 +297,12 @@ impl L1BlockInfo {
                 .wrapping_div(U256::from(1_000_000));
         };
 
-        let mut rollup_data_gas_cost = U256::from(input.iter().fold(0, |acc, byte| {
-            acc + if *byte == 0x00 {
-                ZERO_BYTE_COST
-            } else {
-                NON_ZERO_BYTE_COST
-            }
-        }));
+        let stats = CalldataStats::new(input);
+        let mut rollup_data_gas_cost = U256::from(stats.zero_bytes * ZERO_BYTE_COST)
+            + U256::from(stats.non_zero_bytes * NON_ZERO_BYTE_COST);
 
         // Prior to regolith, an extra 68 non zero bytes were included in the rollup data costs.
-        if !spec_id.is_enabled_in(SpecId::REGOLITH) {
+        if features.pads_data_gas_for_signature() {
             rollup_data_gas_cost += U256::from(NON_ZERO_BYTE_COST).mul(U256::from(68));
         }
 
@@ -288,11 +335,12 @@ impl L1BlockInfo {
             return tx_l1_cost;
         }
         // If the input is a deposit transaction or empty, the default value is zero.
+        let features = features(spec_id);
         let tx_l1_cost = if input.is_empty() || input.first() == Some(&0x7F) {
             return U256::ZERO;
-        } else if spec_id.is_enabled_in(SpecId::FJORD) {
+        } else if features.uses_fastlz_cost() {
             self.calculate_tx_l1_cost_fjord(input)
-        } else if spec_id.is_enabled_in(SpecId::ECOTONE) {
+        } else if features.uses_ecotone_scalars() {
             self.calculate_tx_l1_cost_ecotone(input, spec_id)
         } else {
             self.calculate_tx_l1_cost_bedrock(input, spec_id)
@@ -593,8 +641,135 @@ mod tests {
             ..Default::default()
         };
 
-        let refunded = l1_block_info.operator_fee_refund(&gas, SpecId::ISTHMUS);
+        let refunded = l1_block_info
+            .operator_fee_refund(&gas, SpecId::ISTHMUS)
+            .unwrap();
 
         assert_eq!(refunded, U256::from(100))
     }
+
+    #[test]
+    fn test_operator_fee_missing_params() {
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(2000)),
+            ..Default::default()
+        };
+        let input = bytes!("FACADE");
+        let gas = Gas::new(50000);
+
+        assert_eq!(
+            l1_block_info.operator_fee_charge(&input, U256::from(50000), SpecId::ISTHMUS),
+            Err(OptimismInvalidTransaction::MissingOperatorFeeParams)
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_refund(&gas, SpecId::ISTHMUS),
+            Err(OptimismInvalidTransaction::MissingOperatorFeeParams)
+        );
+
+        // The parameters are not needed pre-isthmus or for inputs that are never charged.
+        assert_eq!(
+            l1_block_info.operator_fee_charge(&input, U256::from(50000), SpecId::HOLOCENE),
+            Ok(U256::ZERO)
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_refund(&gas, SpecId::HOLOCENE),
+            Ok(U256::ZERO)
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_charge(&[], U256::from(50000), SpecId::ISTHMUS),
+            Ok(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_operator_fee_refund_reconciles_with_rounding() {
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(1_500_000)),
+            operator_fee_constant: Some(U256::from(7)),
+            ..Default::default()
+        };
+        let input = bytes!("FACADE");
+
+        // 2 gas limit, 1 gas used: charge = 2 * 1.5 + 7 = 10, used fee = floor(1.5) + 7 = 8.
+        // Scaling the unused gas directly would refund floor(1.5) = 1 and leave 9 charged.
+        let mut gas = Gas::new_spent(2);
+        gas.erase_cost(1);
+        let charge = l1_block_info
+            .operator_fee_charge(&input, U256::from(2), SpecId::ISTHMUS)
+            .unwrap();
+        let refund = l1_block_info
+            .operator_fee_refund(&gas, SpecId::ISTHMUS)
+            .unwrap();
+        let used = l1_block_info
+            .operator_fee_charge(&input, U256::from(1), SpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(charge, U256::from(10));
+        assert_eq!(used, U256::from(8));
+        assert_eq!(refund, U256::from(2));
+        assert_eq!(charge - refund, used);
+
+        // Refunded gas lowers the gas used below the spent gas.
+        let mut gas = Gas::new_spent(2);
+        gas.record_refund(1);
+        let refund = l1_block_info
+            .operator_fee_refund(&gas, SpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(charge - refund, used);
+
+        // Pre-isthmus there is nothing to refund.
+        assert_eq!(
+            l1_block_info.operator_fee_refund(&gas, SpecId::HOLOCENE),
+            Ok(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_operator_fee_near_max_gas() {
+        let input = bytes!("FACADE");
+        for gas_limit in [u64::MAX, u64::MAX - 1, 1 << 63] {
+            for (scalar, constant) in [
+                (U256::from(u32::MAX), U256::from(u64::MAX)),
+                (U256::MAX, U256::from(u64::MAX)),
+                (U256::from(u32::MAX), U256::MAX),
+                (U256::MAX, U256::MAX),
+            ] {
+                let l1_block_info = L1BlockInfo {
+                    operator_fee_scalar: Some(scalar),
+                    operator_fee_constant: Some(constant),
+                    ..Default::default()
+                };
+                let mut gas = Gas::new_spent(gas_limit);
+                gas.erase_cost(gas_limit / 3);
+                gas.record_refund(1);
+
+                let charge = l1_block_info
+                    .operator_fee_charge(&input, gas_to_u256(gas.limit()), SpecId::ISTHMUS)
+                    .unwrap();
+                let used = l1_block_info
+                    .operator_fee_charge(
+                        &input,
+                        gas_to_u256(gas.spent_sub_refunded()),
+                        SpecId::ISTHMUS,
+                    )
+                    .unwrap();
+                let refund = l1_block_info
+                    .operator_fee_refund(&gas, SpecId::ISTHMUS)
+                    .unwrap();
+                assert_eq!(charge - refund, used);
+            }
+        }
+
+        // The largest scalar and constant stored in the L1 block contract don't saturate.
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(u32::MAX)),
+            operator_fee_constant: Some(U256::from(u64::MAX)),
+            ..Default::default()
+        };
+        assert_eq!(
+            l1_block_info.operator_fee_charge(&input, gas_to_u256(u64::MAX), SpecId::ISTHMUS),
+            Ok(U256::from(u64::MAX) * U256::from(u32::MAX)
+                / U256::from(OPERATOR_FEE_SCALAR_DECIMAL)
+                + U256::from(u64::MAX))
+        );
+    }
 }
@@ -1,10 +1,90 @@
+use crate::primitives::{Address, Precompile};
 use once_cell::race::OnceBox;
 #[cfg(feature = "blst")]
 use revm_precompile::bls12_381;
-use revm_precompile::{secp256r1, Precompiles};
+use revm_precompile::{modexp, secp256r1, PrecompileWithAddress, Precompiles};
 use std::boxed::Box;
 
-/// Returns precompiles for Fjord spec.
+/// Builder of custom Optimism precompile sets.
+///
+/// Starts from the precompiles of an Optimism hardfork, to which precompiles can be added or from
+/// which they can be removed. The frozen set is installed with
+/// [`EvmBuilder::with_static_precompiles`](crate::EvmBuilder::with_static_precompiles).
+///
+/// # Example
+///
+/// ```
+/// use revm::{
+///     optimism::OpPrecompilesBuilder,
+///     precompile::secp256r1::P256VERIFY,
+///     primitives::{address, SpecId},
+///     Evm,
+/// };
+///
+/// let precompiles = OpPrecompilesBuilder::granite()
+///     .add(address!("0000000000000000000000000000000000001000"), P256VERIFY.1)
+///     .freeze();
+///
+/// let evm = Evm::builder()
+///     .with_empty_db()
+///     .optimism()
+///     .with_spec_id(SpecId::GRANITE)
+///     .with_static_precompiles(precompiles)
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpPrecompilesBuilder {
+    precompiles: Precompiles,
+}
+
+impl OpPrecompilesBuilder {
+    /// Starts from the given precompiles.
+    pub fn new(precompiles: Precompiles) -> Self {
+        Self { precompiles }
+    }
+
+    /// Starts from the Fjord precompiles.
+    pub fn fjord() -> Self {
+        Self::new(fjord().clone())
+    }
+
+    /// Starts from the Granite precompiles.
+    pub fn granite() -> Self {
+        Self::new(granite().clone())
+    }
+
+    /// Starts from the Isthmus precompiles.
+    pub fn isthmus() -> Self {
+        Self::new(isthmus().clone())
+    }
+
+    /// Adds a precompile at the given address, replacing the existing one.
+    pub fn add(mut self, address: Address, precompile: Precompile) -> Self {
+        self.precompiles
+            .extend([PrecompileWithAddress(address, precompile)]);
+        self
+    }
+
+    /// Removes the precompile at the given address, if any.
+    pub fn remove(mut self, address: Address) -> Self {
+        self.precompiles.remove(&address);
+        self
+    }
+
+    /// Returns the built precompiles.
+    pub fn build(self) -> Precompiles {
+        self.precompiles
+    }
+
+    /// Returns the built precompiles with a static lifetime.
+    ///
+    /// The precompiles are leaked, build the set once and share the reference.
+    pub fn freeze(self) -> &'static Precompiles {
+        Box::leak(Box::new(self.precompiles))
+    }
+}
+
+/// Returns precompiles for Fjord spec, the Cancun precompiles with P256VERIFY.
 pub(crate) fn fjord() -> &'static Precompiles {
     static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
     INSTANCE.get_or_init(|| {
@@ -19,7 +99,8 @@ pub(crate) fn fjord() -> &'static Precompiles {
     })
 }
 
-/// Returns precompiles for Granite spec.
+/// Returns precompiles for Granite spec, the Fjord precompiles with the bn256Pairing input size
+/// restricted.
 pub(crate) fn granite() -> &'static Precompiles {
     static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
     INSTANCE.get_or_init(|| {
@@ -34,7 +115,7 @@ pub(crate) fn granite() -> &'static Precompiles {
     })
 }
 
-/// Returns precompiles for isthmus
+/// Returns precompiles for Isthmus spec, the Granite precompiles with the BLS12-381 precompiles.
 pub(crate) fn isthmus() -> &'static Precompiles {
     static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
     INSTANCE.get_or_init(|| {
@@ -51,3 +132,287 @@ pub(crate) fn isthmus() -> &'static Precompiles {
         Box::new(precompiles)
     })
 }
+
+/// Returns precompiles for Osaka, the Isthmus precompiles with the L1 Osaka changes.
+pub(crate) fn osaka() -> &'static Precompiles {
+    static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = isthmus().clone();
+
+        precompiles.extend([
+            // EIP-7883: ModExp Gas Cost Increase.
+            modexp::OSAKA,
+        ]);
+
+        Box::new(precompiles)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        optimism::BN128_PAIR_GRANITE_MAX_INPUT_SIZE,
+        primitives::{
+            address, hex, Bytes, Env, PrecompileErrors, PrecompileResult, SpecId, TxKind, B256,
+        },
+        Evm,
+    };
+    use revm_precompile::{
+        bn128, secp256k1, secp256r1::P256VERIFY, u64_to_address, Error, PrecompileDiff,
+    };
+
+    #[test]
+    fn test_bn128_pair_limit_is_inherited() {
+        // Smallest number of pairing elements over the Granite limit.
+        let elements = BN128_PAIR_GRANITE_MAX_INPUT_SIZE / bn128::PAIR_ELEMENT_LEN + 1;
+        let input = Bytes::from(vec![1u8; elements * bn128::PAIR_ELEMENT_LEN]);
+        let env = Env::default();
+        let call = |precompiles: &Precompiles| -> PrecompileResult {
+            precompiles
+                .get(&bn128::pair::ADDRESS)
+                .unwrap()
+                .call_ref(&input, 260_000, &env)
+        };
+
+        // Fjord runs the unrestricted pairing, which runs out of gas.
+        assert!(matches!(
+            call(fjord()),
+            Err(PrecompileErrors::Error(Error::OutOfGas))
+        ));
+
+        for precompiles in [granite(), isthmus()] {
+            assert!(matches!(
+                call(precompiles),
+                Err(PrecompileErrors::Error(Error::Bn128PairLength))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_builder_add_and_remove() {
+        let address = address!("0000000000000000000000000000000000001000");
+        let identity = u64_to_address(4);
+        let precompiles = OpPrecompilesBuilder::granite()
+            .add(address, P256VERIFY.1)
+            .remove(identity)
+            .freeze();
+
+        let mut evm = Evm::builder()
+            .with_empty_db()
+            .optimism()
+            .with_spec_id(SpecId::GRANITE)
+            .with_static_precompiles(precompiles)
+            .build();
+        let loaded = evm.handler.pre_execution().load_precompiles();
+        evm.context.evm.set_precompiles(loaded);
+
+        let warm = &evm.context.evm.journaled_state.warm_preloaded_addresses;
+        assert!(warm.contains(&address));
+        assert!(warm.contains(P256VERIFY.address()));
+        assert!(warm.contains(secp256k1::ECRECOVER.address()));
+        assert!(!warm.contains(&identity));
+
+        let context = &mut evm.context.evm;
+        let mut call = |address: &Address, input: &Bytes, gas_limit: u64| {
+            context
+                .precompiles
+                .call(address, input, gas_limit, &mut context.inner)
+        };
+
+        // P256 dispatches from both addresses. Invalid signatures return empty output.
+        for address in [&address, P256VERIFY.address()] {
+            let output = call(address, &Bytes::from(vec![0u8; 160]), 10_000)
+                .unwrap()
+                .unwrap();
+            assert_eq!(output.gas_used, 3_450);
+            assert!(output.bytes.is_empty());
+        }
+        let output = call(secp256k1::ECRECOVER.address(), &Bytes::new(), 10_000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(output.gas_used, 3_000);
+
+        // The Granite pairing limit is kept.
+        let elements = BN128_PAIR_GRANITE_MAX_INPUT_SIZE / bn128::PAIR_ELEMENT_LEN + 1;
+        let input = Bytes::from(vec![1u8; elements * bn128::PAIR_ELEMENT_LEN]);
+        assert!(matches!(
+            call(&bn128::pair::ADDRESS, &input, 260_000).unwrap(),
+            Err(PrecompileErrors::Error(Error::Bn128PairLength))
+        ));
+
+        assert!(call(&identity, &Bytes::new(), 10_000).is_none());
+    }
+
+    #[test]
+    fn test_builder_bases() {
+        assert_eq!(OpPrecompilesBuilder::fjord().build().len(), fjord().len());
+        assert_eq!(
+            OpPrecompilesBuilder::isthmus().build().len(),
+            isthmus().len()
+        );
+
+        let precompiles = OpPrecompilesBuilder::granite()
+            .remove(*P256VERIFY.address())
+            .build();
+        assert_eq!(precompiles.len(), granite().len() - 1);
+        assert!(!precompiles.contains(P256VERIFY.address()));
+        assert!(!precompiles.addresses_set().contains(P256VERIFY.address()));
+    }
+
+    #[test]
+    fn test_osaka_modexp_repricing() {
+        let env = Env::default();
+        let modexp_gas = |precompiles: &Precompiles| {
+            precompiles
+                .get(modexp::OSAKA.address())
+                .unwrap()
+                .call_ref(&Bytes::new(), 100_000, &env)
+                .unwrap()
+                .gas_used
+        };
+
+        assert_eq!(modexp_gas(isthmus()), 200);
+        assert_eq!(modexp_gas(osaka()), 500);
+        assert_eq!(osaka().len(), isthmus().len());
+    }
+
+    #[test]
+    fn test_hardfork_differences() {
+        assert_eq!(
+            fjord().difference(Precompiles::cancun()),
+            PrecompileDiff {
+                added: vec![*P256VERIFY.address()],
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            granite().difference(fjord()),
+            PrecompileDiff {
+                changed: vec![bn128::pair::ADDRESS],
+                ..Default::default()
+            }
+        );
+
+        let diff = isthmus().difference(granite());
+        #[cfg(feature = "blst")]
+        assert_eq!(
+            diff,
+            PrecompileDiff {
+                added: bls12_381::precompiles()
+                    .map(|precompile| *precompile.address())
+                    .collect(),
+                ..Default::default()
+            }
+        );
+        #[cfg(not(feature = "blst"))]
+        assert!(diff.is_empty());
+
+        assert_eq!(
+            osaka().difference(isthmus()),
+            PrecompileDiff {
+                changed: vec![*modexp::OSAKA.address()],
+                ..Default::default()
+            }
+        );
+    }
+
+    /// Calls `address` with `input` in a deposit transaction and returns the output.
+    fn call_precompile(spec: SpecId, address: Address, input: Vec<u8>) -> Bytes {
+        let result = Evm::builder()
+            .with_empty_db()
+            .optimism()
+            .with_spec_id(spec)
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = TxKind::Call(address);
+                tx.data = input.into();
+                tx.gas_limit = 1_000_000;
+                tx.optimism.source_hash = Some(B256::ZERO);
+                tx.optimism.mint = Some(0);
+                tx.optimism.is_system_transaction = Some(false);
+            })
+            .build()
+            .transact()
+            .unwrap()
+            .result;
+        assert!(result.is_success(), "{result:?}");
+        result.into_output().unwrap()
+    }
+
+    #[test]
+    fn test_p256_verify_execution() {
+        // RIP-7212 test vector.
+        let input = hex::decode(
+            "4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4d\
+             a73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac\
+             36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d60\
+             4aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff3\
+             7618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e",
+        )
+        .unwrap();
+        let valid = B256::with_last_byte(1);
+
+        for spec in [SpecId::FJORD, SpecId::GRANITE, SpecId::ISTHMUS] {
+            let output = call_precompile(spec, *P256VERIFY.address(), input.clone());
+            assert_eq!(output[..], valid[..], "{spec:?}");
+        }
+        // Before Fjord the address is an empty account.
+        let output = call_precompile(SpecId::ECOTONE, *P256VERIFY.address(), input);
+        assert!(output.is_empty());
+    }
+
+    /// Encodes a BLS12-381 base field element of 48 bytes, left padded to 64 bytes.
+    #[cfg(feature = "blst")]
+    fn fp(hex: &str) -> Vec<u8> {
+        let mut encoded = vec![0; 16];
+        encoded.extend(hex::decode(hex).unwrap());
+        encoded
+    }
+
+    #[test]
+    #[cfg(feature = "blst")]
+    fn test_bls12_381_execution() {
+        // Generators of G1 and G2, see EIP-2537.
+        let g1_x = fp("17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb");
+        let g1_y = fp("08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1");
+        let g1_neg_y = fp("114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca");
+        let g2 = [
+            fp("024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"),
+            fp("13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"),
+            fp("0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801"),
+            fp("0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be"),
+        ]
+        .concat();
+        let g1 = [g1_x.clone(), g1_y].concat();
+        let g1_neg = [g1_x, g1_neg_y].concat();
+        let g1_add = u64_to_address(bls12_381::g1_add::ADDRESS);
+        let pairing = u64_to_address(bls12_381::pairing::ADDRESS);
+
+        // G + G = 2G.
+        let output = call_precompile(SpecId::ISTHMUS, g1_add, [g1.clone(), g1.clone()].concat());
+        let double = [
+            fp("0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e"),
+            fp("166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28"),
+        ]
+        .concat();
+        assert_eq!(output[..], double[..]);
+
+        // G + -G is the point at infinity.
+        let output = call_precompile(
+            SpecId::ISTHMUS,
+            g1_add,
+            [g1.clone(), g1_neg.clone()].concat(),
+        );
+        assert_eq!(output[..], [0; 128]);
+
+        // e(G1, G2) * e(-G1, G2) = 1.
+        let input = [g1.clone(), g2.clone(), g1_neg, g2].concat();
+        let output = call_precompile(SpecId::ISTHMUS, pairing, input);
+        assert_eq!(output[..], B256::with_last_byte(1)[..]);
+
+        // Before Isthmus the addresses are empty accounts.
+        let output = call_precompile(SpecId::GRANITE, g1_add, [g1.clone(), g1].concat());
+        assert!(output.is_empty());
+    }
+}
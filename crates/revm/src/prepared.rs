@@ -0,0 +1,52 @@
+use crate::interpreter::{gas::InitialAndFloorGas, CallInputs, CreateInputs, EOFCreateInputs};
+use std::boxed::Box;
+
+/// Inputs of the first frame of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FirstFrameInputs {
+    /// The transaction calls an account.
+    Call(Box<CallInputs>),
+    /// The transaction creates a contract from legacy initcode.
+    Create(Box<CreateInputs>),
+    /// The transaction creates a contract from an EOF initcode container.
+    EOFCreate(Box<EOFCreateInputs>),
+}
+
+/// A transaction that went through validation and pre-execution, but whose first frame was not
+/// created yet.
+///
+/// Created by [`Evm::prepare_transaction`](crate::Evm::prepare_transaction), which leaves the
+/// context with the accounts loaded, the caller charged and the EIP-7702 authorizations applied.
+/// The frames are executed by the caller, for example with
+/// [`Evm::create_first_frame`](crate::Evm::create_first_frame) and
+/// [`Evm::run_the_loop`](crate::Evm::run_the_loop), and the transaction is finished with
+/// [`Evm::finish_transaction`](crate::Evm::finish_transaction).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreparedTx {
+    pub(crate) initial_gas: InitialAndFloorGas,
+    pub(crate) eip7702_gas_refund: i64,
+    pub(crate) inputs: FirstFrameInputs,
+}
+
+impl PreparedTx {
+    /// Returns the intrinsic gas of the transaction.
+    pub fn initial_gas(&self) -> u64 {
+        self.initial_gas.initial_gas
+    }
+
+    /// Returns the EIP-7623 floor gas of the transaction.
+    pub fn floor_gas(&self) -> u64 {
+        self.initial_gas.floor_gas
+    }
+
+    /// Returns the gas refunded by the EIP-7702 authorization list.
+    pub fn eip7702_gas_refund(&self) -> i64 {
+        self.eip7702_gas_refund
+    }
+
+    /// Returns the inputs of the first frame, its gas limit is the transaction gas limit minus
+    /// the intrinsic gas.
+    pub fn inputs(&self) -> &FirstFrameInputs {
+        &self.inputs
+    }
+}
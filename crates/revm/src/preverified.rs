@@ -0,0 +1,62 @@
+use crate::{
+    interpreter::gas::InitialAndFloorGas,
+    primitives::{TxEnv, U256},
+};
+
+/// A transaction validated against the environment and the state of its caller.
+///
+/// Created by [`Evm::preverify_transaction`](crate::Evm::preverify_transaction) and executed by
+/// [`Evm::transact_preverified`](crate::Evm::transact_preverified), which skips the
+/// environment checks and the intrinsic gas calculation.
+///
+/// # Staleness
+///
+/// The token is only valid for the block and configuration it was verified with. Execution
+/// rechecks the nonce and balance of the caller and validates the transaction against the state
+/// again if they changed, but a change of the block, the configuration or the spec is not
+/// detected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreverifiedTx {
+    pub(crate) tx: TxEnv,
+    pub(crate) initial_gas: InitialAndFloorGas,
+    pub(crate) caller_nonce: u64,
+    pub(crate) caller_balance: U256,
+}
+
+impl PreverifiedTx {
+    /// Returns the verified transaction.
+    pub fn tx(&self) -> &TxEnv {
+        &self.tx
+    }
+
+    /// Returns the verified transaction, consuming the token.
+    pub fn into_tx(self) -> TxEnv {
+        self.tx
+    }
+
+    /// Returns the intrinsic gas of the transaction.
+    pub fn initial_gas(&self) -> u64 {
+        self.initial_gas.initial_gas
+    }
+
+    /// Returns the EIP-7623 floor gas of the transaction.
+    pub fn floor_gas(&self) -> u64 {
+        self.initial_gas.floor_gas
+    }
+
+    /// Returns the caller recovered from [`TxEnv::signature`], if the transaction is signed.
+    #[cfg(feature = "secp256k1-recover")]
+    pub fn recovered_caller(&self) -> Option<crate::primitives::Address> {
+        self.tx.signature.map(|_| self.tx.caller)
+    }
+
+    /// Returns the nonce of the caller when the transaction was verified.
+    pub fn caller_nonce(&self) -> u64 {
+        self.caller_nonce
+    }
+
+    /// Returns the balance of the caller when the transaction was verified.
+    pub fn caller_balance(&self) -> U256 {
+        self.caller_balance
+    }
+}
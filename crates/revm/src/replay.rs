@@ -0,0 +1,427 @@
+//! Deterministic replay of a transaction.
+//!
+//! A [`Replay`] holds everything needed to reproduce the execution of a transaction offline: the
+//! environment, every value read from the database and the expected result. It is recorded with
+//! [`Replay::record`] (or a [`ReplayRecorder`] around the database of an existing [`Evm`]),
+//! serialized with any serde format and verified anywhere with [`run`].
+//!
+//! Handle registers and inspectors are not part of the replay, the transaction is replayed with
+//! the default handler of its [`HandlerCfg`].
+
+use crate::{
+    db::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef},
+    primitives::{
+        Account, AccountInfo, Address, Bytecode, EVMError, Env, EnvWithHandlerCfg, HandlerCfg,
+        HashMap, ResultAndState, SpecId, B256, U256,
+    },
+    Evm,
+};
+use core::fmt;
+use std::{boxed::Box, collections::BTreeMap};
+
+/// Values read from a database, keyed in ascending order so that replays serialize
+/// deterministically.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayReads {
+    /// Results of [`Database::basic`].
+    pub accounts: BTreeMap<Address, Option<AccountInfo>>,
+    /// Results of [`Database::storage`].
+    pub storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// Results of [`Database::code_by_hash`].
+    pub contracts: BTreeMap<B256, Bytecode>,
+    /// Results of [`Database::block_hash`].
+    pub block_hashes: BTreeMap<u64, B256>,
+}
+
+/// Database wrapper recording the values read from the wrapped database.
+///
+/// Only the first read of a value is recorded, later reads see the changes committed to the
+/// wrapped database in between.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayRecorder<DB> {
+    /// The wrapped database.
+    pub db: DB,
+    /// The recorded reads.
+    pub reads: ReplayReads,
+}
+
+impl<DB> ReplayRecorder<DB> {
+    /// Creates a recorder without reads around the given database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            reads: ReplayReads::default(),
+        }
+    }
+
+    /// Consumes the recorder and returns the wrapped database and the recorded reads.
+    pub fn into_parts(self) -> (DB, ReplayReads) {
+        (self.db, self.reads)
+    }
+}
+
+impl<DB: Database> Database for ReplayRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.reads
+            .accounts
+            .entry(address)
+            .or_insert_with(|| info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.reads
+            .contracts
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.reads
+            .storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.reads.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for ReplayRecorder<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Error of a [`ReplayDb`] read that was not recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReplayError {
+    /// The account was not recorded.
+    MissingAccount(Address),
+    /// The storage slot was not recorded.
+    MissingStorage(Address, U256),
+    /// The code was not recorded.
+    MissingCode(B256),
+    /// The block hash was not recorded.
+    MissingBlockHash(u64),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => write!(f, "account {address} was not recorded"),
+            Self::MissingStorage(address, index) => {
+                write!(f, "storage slot {index} of {address} was not recorded")
+            }
+            Self::MissingCode(code_hash) => write!(f, "code {code_hash} was not recorded"),
+            Self::MissingBlockHash(number) => {
+                write!(f, "hash of block {number} was not recorded")
+            }
+        }
+    }
+}
+
+impl DBErrorMarker for ReplayError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+/// Database serving exactly the recorded reads, and failing with a [`ReplayError`] on anything
+/// else.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayDb {
+    /// The recorded reads.
+    pub reads: ReplayReads,
+}
+
+impl ReplayDb {
+    /// Creates a database serving the given reads.
+    pub fn new(reads: ReplayReads) -> Self {
+        Self { reads }
+    }
+}
+
+impl Database for ReplayDb {
+    type Error = ReplayError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseRef for ReplayDb {
+    type Error = ReplayError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.reads
+            .accounts
+            .get(&address)
+            .cloned()
+            .ok_or(ReplayError::MissingAccount(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.reads
+            .contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(ReplayError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.reads
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+            .copied()
+            .ok_or(ReplayError::MissingStorage(address, index))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.reads
+            .block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(ReplayError::MissingBlockHash(number))
+    }
+}
+
+/// A recorded transaction: its environment, the values it read and its result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    /// Environment of the transaction.
+    pub env: Box<Env>,
+    /// Spec the transaction was executed with.
+    pub spec_id: SpecId,
+    /// Whether the transaction was executed with the Optimism handler.
+    #[cfg(feature = "optimism")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_optimism: bool,
+    /// Values read from the database.
+    pub reads: ReplayReads,
+    /// Expected result of the transaction.
+    pub result: ResultAndState,
+}
+
+impl Replay {
+    /// Executes the transaction of `env` over `db` without committing it, and records it.
+    ///
+    /// Returns the replay and the database.
+    pub fn record<DB: Database>(
+        db: DB,
+        env: EnvWithHandlerCfg,
+    ) -> Result<(Self, DB), EVMError<DB::Error>> {
+        let mut evm = Evm::builder()
+            .with_db(ReplayRecorder::new(db))
+            .with_env_with_handler_cfg(env)
+            .build();
+        let result = evm.transact()?;
+        let (recorder, env) = evm.into_db_and_env_with_handler_cfg();
+        let (db, reads) = recorder.into_parts();
+        let replay = Self {
+            env: env.env,
+            spec_id: env.handler_cfg.spec_id,
+            #[cfg(feature = "optimism")]
+            is_optimism: env.handler_cfg.is_optimism,
+            reads,
+            result,
+        };
+        Ok((replay, db))
+    }
+
+    /// Returns the handler configuration of the transaction.
+    pub fn handler_cfg(&self) -> HandlerCfg {
+        HandlerCfg {
+            spec_id: self.spec_id,
+            #[cfg(feature = "optimism")]
+            is_optimism: self.is_optimism,
+        }
+    }
+
+    /// Serializes the replay to JSON.
+    #[cfg(feature = "serde-json")]
+    pub fn to_json(&self) -> serde_json::Result<std::string::String> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserializes a replay from JSON.
+    #[cfg(feature = "serde-json")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Error of [`run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayRunError {
+    /// The replayed transaction failed.
+    Evm(EVMError<ReplayError>),
+    /// The replayed transaction has a different result than the recorded one.
+    Mismatch {
+        /// The recorded result.
+        expected: Box<ResultAndState>,
+        /// The result of the replay.
+        actual: Box<ResultAndState>,
+    },
+}
+
+impl fmt::Display for ReplayRunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Evm(e) => write!(f, "replay failed: {e}"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "replay result mismatch: expected {:?}, got {:?}",
+                expected.result, actual.result
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayRunError {}
+
+impl From<EVMError<ReplayError>> for ReplayRunError {
+    fn from(e: EVMError<ReplayError>) -> Self {
+        Self::Evm(e)
+    }
+}
+
+/// Replays the transaction over its recorded reads and verifies that it has the recorded result.
+///
+/// Returns the result of the replay.
+pub fn run(replay: &Replay) -> Result<ResultAndState, ReplayRunError> {
+    let mut evm = Evm::builder()
+        .with_db(ReplayDb::new(replay.reads.clone()))
+        .with_env_with_handler_cfg(EnvWithHandlerCfg::new(
+            replay.env.clone(),
+            replay.handler_cfg(),
+        ))
+        .build();
+    let actual = evm.transact()?;
+    if actual != replay.result {
+        return Err(ReplayRunError::Mismatch {
+            expected: Box::new(replay.result.clone()),
+            actual: Box::new(actual),
+        });
+    }
+    Ok(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB, InMemoryDB},
+        interpreter::opcode::{ADD, BLOCKHASH, NUMBER, PUSH1, SLOAD, SSTORE, STOP, SUB},
+        primitives::{address, TxKind},
+    };
+    use std::vec;
+
+    const CALLER: Address = address!("0000000000000000000000000000000000000001");
+    const COUNTER: Address = address!("00000000000000000000000000000000000000c0");
+
+    /// Records a call to a counter that also stores the hash of the previous block.
+    fn record() -> (Replay, InMemoryDB) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SSTORE(0, SLOAD(0) + 1), SSTORE(1, BLOCKHASH(NUMBER - 1))
+        let code = vec![
+            PUSH1, 0, SLOAD, PUSH1, 1, ADD, PUSH1, 0, SSTORE, PUSH1, 1, NUMBER, SUB, BLOCKHASH,
+            PUSH1, 1, SSTORE, STOP,
+        ];
+        db.insert_account_info(
+            COUNTER,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+        db.insert_account_storage(COUNTER, U256::ZERO, U256::from(41))
+            .unwrap();
+
+        let mut env = Env::default();
+        env.block.number = U256::from(10);
+        env.tx.caller = CALLER;
+        env.tx.transact_to = TxKind::Call(COUNTER);
+        env.tx.gas_limit = 100_000;
+        let env = EnvWithHandlerCfg::new(Box::new(env), HandlerCfg::new(SpecId::CANCUN));
+        Replay::record(db, env).unwrap()
+    }
+
+    #[test]
+    fn records_reads() {
+        let (replay, _) = record();
+        assert_eq!(replay.reads.storage[&COUNTER][&U256::ZERO], U256::from(41));
+        assert!(replay.reads.block_hashes.contains_key(&9));
+        assert!(replay.reads.accounts.contains_key(&CALLER));
+        assert_eq!(run(&replay), Ok(replay.result.clone()));
+    }
+
+    #[test]
+    fn missing_reads_fail() {
+        let (mut replay, _) = record();
+        replay.reads.storage.clear();
+        assert_eq!(
+            run(&replay),
+            Err(ReplayRunError::Evm(EVMError::Database(
+                ReplayError::MissingStorage(COUNTER, U256::ZERO)
+            )))
+        );
+    }
+
+    #[test]
+    fn changed_reads_mismatch() {
+        let (mut replay, _) = record();
+        replay
+            .reads
+            .storage
+            .get_mut(&COUNTER)
+            .unwrap()
+            .insert(U256::ZERO, U256::from(1));
+        assert!(matches!(run(&replay), Err(ReplayRunError::Mismatch { .. })));
+    }
+
+    #[cfg(all(feature = "std", feature = "serde-json"))]
+    #[test]
+    fn replay_from_file() {
+        let (replay, _) = record();
+        let json = replay.to_json().unwrap();
+        // Serialization is deterministic.
+        assert_eq!(json, record().0.to_json().unwrap());
+
+        let path = std::env::temp_dir().join("revm_replay_from_file.json");
+        std::fs::write(&path, json).unwrap();
+        let replay = Replay::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(run(&replay).unwrap(), record().0.result);
+    }
+}
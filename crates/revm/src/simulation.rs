@@ -0,0 +1,58 @@
+use crate::primitives::{ExecutionResult, I256, U256};
+use std::vec::Vec;
+
+/// Decision on a transaction of a bundle simulated with
+/// [`Evm::simulate_bundle`](crate::Evm::simulate_bundle), once it is executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BundleControl {
+    /// Commit the state of the transaction and execute the next one.
+    Continue,
+    /// Discard the state of the transaction and execute the next one, as if the transaction
+    /// was not part of the bundle.
+    Skip,
+    /// Discard the state of the transaction and stop the simulation.
+    Abort,
+}
+
+/// Result of a transaction of a bundle, with the decision taken on it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleTxResult {
+    /// Result of the transaction, whether its state is committed or not.
+    pub result: ExecutionResult,
+    /// Decision taken on the transaction.
+    pub control: BundleControl,
+}
+
+/// Report of a bundle simulated with [`Evm::simulate_bundle`](crate::Evm::simulate_bundle).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BundleSimulationReport {
+    /// Results of the executed transactions, in bundle order. The transactions after an
+    /// aborted one are not executed.
+    pub results: Vec<BundleTxResult>,
+    /// Gas used by the committed transactions.
+    pub cumulative_gas_used: u64,
+    /// Balance of the block coinbase before the bundle.
+    pub coinbase_balance_before: U256,
+    /// Balance of the block coinbase after the committed transactions.
+    pub coinbase_balance_after: U256,
+}
+
+impl BundleSimulationReport {
+    /// Returns whether the simulation was aborted by a transaction.
+    #[inline]
+    pub fn is_aborted(&self) -> bool {
+        self.results
+            .last()
+            .is_some_and(|tx| tx.control == BundleControl::Abort)
+    }
+
+    /// Returns the change of the balance of the block coinbase over the committed transactions,
+    /// negative if the coinbase paid more than it received.
+    #[inline]
+    pub fn coinbase_balance_delta(&self) -> I256 {
+        I256::from_raw(
+            self.coinbase_balance_after
+                .wrapping_sub(self.coinbase_balance_before),
+        )
+    }
+}
@@ -0,0 +1,86 @@
+use crate::primitives::{uint, Address, Bytecode, HashMap, U256};
+
+/// Storage slot holding the implementation address of an
+/// [EIP-1967](https://eips.ethereum.org/EIPS/eip-1967) proxy,
+/// `keccak256("eip1967.proxy.implementation") - 1`.
+pub const EIP1967_IMPLEMENTATION_SLOT: U256 =
+    uint!(0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc_U256);
+
+/// Override of the code and storage of an account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountOverride {
+    /// Code replacing the code of the account, if any.
+    pub code: Option<Bytecode>,
+    /// Values of the overridden storage slots, the other slots are read from the database.
+    pub storage: HashMap<U256, U256>,
+}
+
+/// Overrides of the code and storage of accounts, applied before each transaction.
+///
+/// The overrides of an account are applied in one step through the journaled state, see
+/// [`JournaledState::override_account`](crate::JournaledState::override_account). They are
+/// part of the state of the transaction as if the database held them, so warm and cold
+/// accesses are charged as without the overrides. The database is not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateOverrides {
+    /// Overrides by account.
+    pub accounts: HashMap<Address, AccountOverride>,
+}
+
+impl StateOverrides {
+    /// Creates empty overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the code of `address` and the given storage slots.
+    ///
+    /// Slots overridden before for the account are kept unless they are given again.
+    pub fn code_and_storage(
+        mut self,
+        address: Address,
+        code: Bytecode,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) -> Self {
+        let account = self.accounts.entry(address).or_default();
+        account.code = Some(code);
+        account.storage.extend(storage);
+        self
+    }
+
+    /// Overrides the given storage slots of `address`, keeping its code.
+    pub fn storage(
+        mut self,
+        address: Address,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) -> Self {
+        self.accounts
+            .entry(address)
+            .or_default()
+            .storage
+            .extend(storage);
+        self
+    }
+
+    /// Points the EIP-1967 `proxy` to `new_impl_code`, injected at the address returned by
+    /// [`StateOverrides::implementation_address`].
+    ///
+    /// See [`EIP1967_IMPLEMENTATION_SLOT`].
+    pub fn proxy_implementation(self, proxy: Address, new_impl_code: Bytecode) -> Self {
+        let implementation = Self::implementation_address(&new_impl_code);
+        let slot = U256::from_be_bytes(implementation.into_word().0);
+        self.storage(proxy, [(EIP1967_IMPLEMENTATION_SLOT, slot)])
+            .code_and_storage(implementation, new_impl_code, [])
+    }
+
+    /// Returns the address at which [`StateOverrides::proxy_implementation`] injects `code`,
+    /// the last 20 bytes of its code hash.
+    pub fn implementation_address(code: &Bytecode) -> Address {
+        Address::from_word(code.hash_slow())
+    }
+
+    /// Returns `true` if no account is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    interpreter::{gas::InitialAndFloorGas, SharedMemory},
+    primitives::{Env, FeeSummary, ResultAndState},
+    Frame, JournaledState,
+};
+use std::{boxed::Box, vec::Vec};
+
+/// Transaction execution suspended at a frame boundary.
+///
+/// Created by [`Evm::suspend_at_depth`](crate::Evm::suspend_at_depth) and continued by
+/// [`Evm::resume`](crate::Evm::resume). The database is not part of the snapshot, the resuming
+/// EVM must use the same handler and a database with the same state as the suspending one.
+///
+/// With the `serde-state` feature it can be serialized to resume in another process.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde-state", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuspendedExecution {
+    /// Environment of the transaction.
+    pub env: Box<Env>,
+    /// State changes and journal of the transaction.
+    pub journaled_state: JournaledState,
+    /// Call stack, the last frame has not started executing.
+    pub call_stack: Vec<Frame>,
+    /// Memory shared by the frames of the call stack.
+    pub shared_memory: SharedMemory,
+    /// Initial and floor gas of the transaction.
+    pub initial_gas: InitialAndFloorGas,
+    /// Gas refunded by the EIP-7702 authorization list.
+    pub eip7702_gas_refund: i64,
+    /// Fees recorded before execution.
+    pub fee_summary: Option<FeeSummary>,
+    /// L1 block info loaded during validation.
+    #[cfg(feature = "optimism")]
+    pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    /// Optimism fees recorded before execution.
+    #[cfg(feature = "optimism")]
+    pub op_tx_fees: Option<crate::optimism::OpTxFees>,
+}
+
+/// Outcome of [`Evm::suspend_at_depth`](crate::Evm::suspend_at_depth).
+#[derive(Debug)]
+pub enum SuspendOutcome {
+    /// Execution reached the requested depth.
+    Suspended(Box<SuspendedExecution>),
+    /// Transaction finished without reaching the requested depth.
+    Completed(ResultAndState),
+}
@@ -1,2 +1,11 @@
+mod differential;
+mod state_invariants;
+
 #[doc(hidden)]
 pub use crate::context::evm_context::test_utils::*;
+pub use differential::{
+    DifferentialError, DifferentialHost, DifferentialInterpreter, Divergence, StepState,
+};
+pub use state_invariants::{
+    assert_state_invariants, check_account_status, check_state_invariants, StatusViolation,
+};
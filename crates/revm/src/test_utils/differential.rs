@@ -0,0 +1,316 @@
+//! Differential execution of a frame with two instruction tables.
+//!
+//! A [`DifferentialInterpreter`] executes the same frame with two instruction tables in lockstep,
+//! each with its own host over a shared database, and compares the stack, gas and memory of the
+//! two interpreters after every instruction. It is meant to check that a new instruction table
+//! behaves like a reference one, for example over the reads of a [`Replay`](crate::replay::Replay)
+//! served by a [`ReplayDb`](crate::replay::ReplayDb).
+//!
+//! Execution stops at the first call or create of the frame, sub-frames are not executed.
+
+use crate::{
+    db::{DatabaseRef, WrapDatabaseRef},
+    interpreter::{
+        opcode::{InstructionTables, OpCode},
+        Contract, Gas, Host, InstructionResult, Interpreter, InterpreterAction, SharedMemory,
+    },
+    primitives::{keccak256, EVMError, Env, SpecId, B256, U256},
+    Context, EvmContext,
+};
+use core::fmt;
+use std::{boxed::Box, vec::Vec};
+
+/// Host of a [`DifferentialInterpreter`], reading from the shared database.
+pub type DifferentialHost<'a, DB> = Context<(), WrapDatabaseRef<&'a DB>>;
+
+/// State of an interpreter after an instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepState {
+    /// Program counter of the next instruction.
+    pub pc: usize,
+    /// Stack, from bottom to top.
+    pub stack: Vec<U256>,
+    /// Gas of the frame.
+    pub gas: Gas,
+    /// Keccak-256 hash of the memory of the frame.
+    pub memory_hash: B256,
+    /// Result of the instruction.
+    pub result: InstructionResult,
+}
+
+impl StepState {
+    fn new(interpreter: &Interpreter) -> Self {
+        Self {
+            pc: interpreter.program_counter(),
+            stack: interpreter.stack.data().clone(),
+            gas: interpreter.gas,
+            memory_hash: keccak256(interpreter.shared_memory.context_memory()),
+            result: interpreter.instruction_result,
+        }
+    }
+
+    /// Returns the names of the fields that differ from `other`.
+    fn diff(&self, other: &Self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.pc != other.pc {
+            fields.push("pc");
+        }
+        if self.stack != other.stack {
+            fields.push("stack");
+        }
+        if self.gas != other.gas {
+            fields.push("gas");
+        }
+        if self.memory_hash != other.memory_hash {
+            fields.push("memory");
+        }
+        if self.result != other.result {
+            fields.push("result");
+        }
+        fields
+    }
+}
+
+/// First instruction after which the two interpreters are in different states.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Number of instructions executed before the diverging one.
+    pub step: usize,
+    /// Program counter of the diverging instruction.
+    pub pc: usize,
+    /// Opcode of the diverging instruction.
+    pub opcode: u8,
+    /// State with the first instruction table.
+    pub a: StepState,
+    /// State with the second instruction table.
+    pub b: StepState,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at pc {} (step {}) diverged in {}",
+            OpCode::name_by_op(self.opcode),
+            self.pc,
+            self.step,
+            self.a.diff(&self.b).join(", ")
+        )
+    }
+}
+
+/// Error of [`DifferentialInterpreter::run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DifferentialError<E> {
+    /// Loading the account of the frame failed.
+    Evm(EVMError<E>),
+    /// The interpreters diverged after an instruction.
+    Divergence(Box<Divergence>),
+    /// The interpreters stopped in the same state with different actions.
+    ActionMismatch {
+        /// Action with the first instruction table.
+        a: Box<InterpreterAction>,
+        /// Action with the second instruction table.
+        b: Box<InterpreterAction>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for DifferentialError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Evm(e) => write!(f, "loading the frame failed: {e}"),
+            Self::Divergence(divergence) => write!(f, "{divergence}"),
+            Self::ActionMismatch { a, b } => write!(f, "actions differ: {a:?} and {b:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for DifferentialError<E> {}
+
+/// Executes a frame with two instruction tables in lockstep, see the [module](self) docs.
+pub struct DifferentialInterpreter<'a, DB: DatabaseRef> {
+    /// Host of the first instruction table.
+    pub host_a: DifferentialHost<'a, DB>,
+    /// Host of the second instruction table.
+    pub host_b: DifferentialHost<'a, DB>,
+}
+
+impl<'a, DB: DatabaseRef> DifferentialInterpreter<'a, DB> {
+    /// Creates two hosts over `db` with the given environment and spec.
+    pub fn new(db: &'a DB, env: Box<Env>, spec_id: SpecId) -> Self {
+        let host = || {
+            let mut evm = EvmContext::new_with_env(WrapDatabaseRef(db), env.clone());
+            evm.journaled_state.set_spec_id(spec_id);
+            Context::new(evm, ())
+        };
+        Self {
+            host_a: host(),
+            host_b: host(),
+        }
+    }
+
+    /// Executes `contract` with both instruction tables until the interpreters stop or diverge.
+    ///
+    /// Returns the action of the frame, identical with both tables.
+    pub fn run(
+        &mut self,
+        contract: Contract,
+        gas_limit: u64,
+        table_a: &InstructionTables<'_, DifferentialHost<'a, DB>>,
+        table_b: &InstructionTables<'_, DifferentialHost<'a, DB>>,
+    ) -> Result<InterpreterAction, DifferentialError<DB::Error>> {
+        for host in [&mut self.host_a, &mut self.host_b] {
+            host.evm.journaled_state.checkpoint();
+            host.evm
+                .load_account(contract.target_address)
+                .map_err(DifferentialError::Evm)?;
+        }
+
+        let mut a = Interpreter::new(contract.clone(), gas_limit, false);
+        let mut b = Interpreter::new(contract, gas_limit, false);
+        a.shared_memory = SharedMemory::new();
+        b.shared_memory = SharedMemory::new();
+        let mut step = 0;
+        loop {
+            let pc = a.program_counter();
+            let opcode = a.current_opcode();
+            let action_a = step_instruction(&mut a, table_a, &mut self.host_a);
+            let action_b = step_instruction(&mut b, table_b, &mut self.host_b);
+
+            let (state_a, state_b) = (StepState::new(&a), StepState::new(&b));
+            if state_a != state_b {
+                return Err(DifferentialError::Divergence(Box::new(Divergence {
+                    step,
+                    pc,
+                    opcode,
+                    a: state_a,
+                    b: state_b,
+                })));
+            }
+            if state_a.result != InstructionResult::Continue {
+                if action_a != action_b {
+                    return Err(DifferentialError::ActionMismatch {
+                        a: Box::new(action_a),
+                        b: Box::new(action_b),
+                    });
+                }
+                return Ok(action_a);
+            }
+            step += 1;
+        }
+    }
+}
+
+/// Executes a single instruction from a plain or boxed table.
+fn step_instruction<H: Host>(
+    interpreter: &mut Interpreter,
+    table: &InstructionTables<'_, H>,
+    host: &mut H,
+) -> InterpreterAction {
+    match table {
+        InstructionTables::Plain(table) => interpreter.step_instruction(table, host),
+        InstructionTables::Boxed(table) => interpreter.step_instruction(table, host),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        interpreter::{
+            instructions::arithmetic,
+            opcode::{make_instruction_table, ADD, PUSH1, SLOAD, SSTORE, STOP},
+        },
+        primitives::{
+            address, AccountInfo, Address, Bytecode, CancunSpec, EnvWithHandlerCfg, HandlerCfg,
+            TxKind,
+        },
+        replay::{Replay, ReplayDb, ReplayError},
+    };
+    use std::vec;
+
+    const CALLER: Address = address!("0000000000000000000000000000000000000001");
+    const CONTRACT: Address = address!("00000000000000000000000000000000000000c0");
+
+    /// ADD charging one gas more than it should.
+    fn overcharging_add<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
+        arithmetic::add(interpreter, host);
+        let _ = interpreter.gas.record_cost(1);
+    }
+
+    /// Records a call storing `SLOAD(0) + 2` in slot 0.
+    fn record() -> Replay {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        let code = vec![PUSH1, 2, PUSH1, 0, SLOAD, ADD, PUSH1, 0, SSTORE, STOP];
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo::from_bytecode(Bytecode::new_legacy(code.into())),
+        );
+        db.insert_account_storage(CONTRACT, U256::ZERO, U256::from(40))
+            .unwrap();
+
+        let mut env = Env::default();
+        env.tx.caller = CALLER;
+        env.tx.transact_to = TxKind::Call(CONTRACT);
+        env.tx.gas_limit = 100_000;
+        let env = EnvWithHandlerCfg::new(Box::new(env), HandlerCfg::new(SpecId::CANCUN));
+        Replay::record(db, env).unwrap().0
+    }
+
+    /// Runs the recorded call with the Cancun table and `table`.
+    fn run_replay<'a>(
+        replay: &Replay,
+        db: &'a ReplayDb,
+        table: InstructionTables<'_, DifferentialHost<'a, ReplayDb>>,
+    ) -> Result<InterpreterAction, DifferentialError<ReplayError>> {
+        let code = db.reads.accounts[&CONTRACT].clone().unwrap().code.unwrap();
+        let contract = Contract::new_env(&replay.env, code, None);
+        let reference = InstructionTables::new_plain::<CancunSpec>();
+        DifferentialInterpreter::new(db, replay.env.clone(), SpecId::CANCUN)
+            .run(contract, 50_000, &reference, &table)
+    }
+
+    #[test]
+    fn identical_tables_agree() {
+        let replay = record();
+        let db = ReplayDb::new(replay.reads.clone());
+        let action = run_replay(&replay, &db, InstructionTables::new_plain::<CancunSpec>());
+        assert!(matches!(
+            action,
+            Ok(InterpreterAction::Return { result }) if result.result == InstructionResult::Stop
+        ));
+
+        // The same instructions boxed agree as well.
+        let mut boxed = InstructionTables::new_plain::<CancunSpec>();
+        boxed.to_boxed();
+        assert!(run_replay(&replay, &db, boxed).is_ok());
+    }
+
+    #[test]
+    fn catches_gas_bug_in_cloned_table() {
+        let replay = record();
+        let db = ReplayDb::new(replay.reads.clone());
+        let mut table = make_instruction_table::<DifferentialHost<'_, ReplayDb>, CancunSpec>();
+        table[ADD as usize] = overcharging_add;
+
+        let Err(DifferentialError::Divergence(divergence)) =
+            run_replay(&replay, &db, InstructionTables::Plain(table))
+        else {
+            panic!("the gas bug is not caught");
+        };
+        assert_eq!((divergence.step, divergence.pc), (3, 5));
+        assert_eq!(divergence.opcode, ADD);
+        assert_eq!(divergence.a.stack, divergence.b.stack);
+        assert_eq!(
+            divergence.a.gas.remaining(),
+            divergence.b.gas.remaining() + 1
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "ADD at pc 5 (step 3) diverged in gas"
+        );
+    }
+}
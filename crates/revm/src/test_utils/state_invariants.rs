@@ -0,0 +1,140 @@
+//! Invariants of the account status flags of a finalized state, see
+//! [`AccountStatus`](crate::primitives::AccountStatus).
+//!
+//! The test helpers of this crate that execute transactions check them on the finalized states.
+
+use crate::primitives::{Account, Address, EvmState, SpecId};
+use core::fmt;
+
+/// Invariant of the account status flags violated by an account of a finalized state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusViolation {
+    /// The account is created but not touched.
+    CreatedNotTouched,
+    /// The account is destroyed but not touched.
+    DestroyedNotTouched,
+    /// The account is destroyed without being created in the transaction, from Cancun.
+    DestroyedNotCreated,
+    /// The account is not touched but has changed storage slots.
+    UntouchedStorageChanged,
+    /// The account is loaded as not existing and not touched, but is not empty.
+    UntouchedNotExistingNotEmpty,
+}
+
+impl fmt::Display for StatusViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CreatedNotTouched => "created account is not touched",
+            Self::DestroyedNotTouched => "destroyed account is not touched",
+            Self::DestroyedNotCreated => "destroyed account is not created in the transaction",
+            Self::UntouchedStorageChanged => "untouched account has changed storage",
+            Self::UntouchedNotExistingNotEmpty => "untouched not existing account is not empty",
+        })
+    }
+}
+
+/// Checks the status flags of an account of a state finalized at `spec`.
+pub fn check_account_status(account: &Account, spec: SpecId) -> Result<(), StatusViolation> {
+    let touched = account.is_touched();
+    if account.is_created_this_tx() && !touched {
+        return Err(StatusViolation::CreatedNotTouched);
+    }
+    if account.was_destroyed() {
+        if !touched {
+            return Err(StatusViolation::DestroyedNotTouched);
+        }
+        if spec.is_enabled_in(SpecId::CANCUN) && !account.is_created_this_tx() {
+            return Err(StatusViolation::DestroyedNotCreated);
+        }
+    }
+    if !touched {
+        if account.changed_storage_slots().next().is_some() {
+            return Err(StatusViolation::UntouchedStorageChanged);
+        }
+        if account.is_loaded_as_not_existing() && !account.is_empty() {
+            return Err(StatusViolation::UntouchedNotExistingNotEmpty);
+        }
+    }
+    Ok(())
+}
+
+/// Checks the status flags of all the accounts of a state finalized at `spec`, returning the
+/// first account violating an invariant.
+pub fn check_state_invariants(
+    state: &EvmState,
+    spec: SpecId,
+) -> Result<(), (Address, StatusViolation)> {
+    state.iter().try_for_each(|(address, account)| {
+        check_account_status(account, spec).map_err(|violation| (*address, violation))
+    })
+}
+
+/// Asserts that all the accounts of a state finalized at `spec` satisfy the invariants of the
+/// status flags.
+///
+/// # Panics
+///
+/// Panics if an account violates an invariant.
+#[track_caller]
+pub fn assert_state_invariants(state: &EvmState, spec: SpecId) {
+    if let Err((address, violation)) = check_state_invariants(state, spec) {
+        panic!(
+            "account {address}: {violation}, status {:?}",
+            state[&address].status
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{AccountInfo, EvmStorageSlot, U256};
+
+    #[test]
+    fn status_violations() {
+        let mut account = Account::from(AccountInfo::from_balance(U256::from(1)));
+        assert_eq!(check_account_status(&account, SpecId::CANCUN), Ok(()));
+
+        account.mark_created();
+        assert_eq!(
+            check_account_status(&account, SpecId::CANCUN),
+            Err(StatusViolation::CreatedNotTouched)
+        );
+        account.mark_touch();
+        account.mark_selfdestruct();
+        assert_eq!(check_account_status(&account, SpecId::CANCUN), Ok(()));
+
+        // Before Cancun any account can be destroyed.
+        account.unmark_created();
+        assert_eq!(
+            check_account_status(&account, SpecId::CANCUN),
+            Err(StatusViolation::DestroyedNotCreated)
+        );
+        assert_eq!(check_account_status(&account, SpecId::SHANGHAI), Ok(()));
+        account.unmark_touch();
+        assert_eq!(
+            check_account_status(&account, SpecId::SHANGHAI),
+            Err(StatusViolation::DestroyedNotTouched)
+        );
+
+        let mut account = Account::from(AccountInfo::default());
+        account.storage.insert(
+            U256::ZERO,
+            EvmStorageSlot::new_changed(U256::ZERO, U256::from(1)),
+        );
+        assert_eq!(
+            check_account_status(&account, SpecId::CANCUN),
+            Err(StatusViolation::UntouchedStorageChanged)
+        );
+
+        let mut account = Account::new_not_existing();
+        assert_eq!(check_account_status(&account, SpecId::CANCUN), Ok(()));
+        account.info.nonce = 1;
+        assert_eq!(
+            check_account_status(&account, SpecId::CANCUN),
+            Err(StatusViolation::UntouchedNotExistingNotEmpty)
+        );
+        account.mark_touch();
+        assert_eq!(check_account_status(&account, SpecId::CANCUN), Ok(()));
+    }
+}
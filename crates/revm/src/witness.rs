@@ -0,0 +1,369 @@
+//! Execution witnesses for stateless verification.
+//!
+//! A [`Witness`] holds the pre-state read by one or more transactions: the accounts, storage
+//! slots, bytecodes and block hashes, each with the value of its first read. It is recorded by a
+//! [`WitnessRecorder`] around the database of an [`Evm`](crate::Evm), and is itself the database
+//! of the stateless re-execution, failing with a [`WitnessError`] on anything it doesn't hold.
+//!
+//! The journal only reads the database on the first access of a transaction to a value, and the
+//! Optimism handler reads the L1 block contract with `L1BlockInfo::try_fetch` directly from the
+//! database, so the recorder sees the whole pre-state of the execution.
+
+use crate::{
+    db::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef},
+    primitives::{Account, AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256},
+};
+use core::fmt;
+use std::collections::BTreeMap;
+
+/// Pre-state values read by an execution, keyed in ascending order so that witnesses serialize
+/// deterministically.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness {
+    /// Accounts without their code, `None` if the account doesn't exist.
+    pub accounts: BTreeMap<Address, Option<AccountInfo>>,
+    /// Storage slots by account.
+    pub storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// Bytecodes by code hash, both the ones returned with an account and by
+    /// [`Database::code_by_hash`].
+    pub codes: BTreeMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: BTreeMap<u64, B256>,
+}
+
+impl Witness {
+    /// Records the first read of the account at `address`, moving its code to the codes.
+    fn record_account(&mut self, address: Address, info: &Option<AccountInfo>) {
+        if self.accounts.contains_key(&address) {
+            return;
+        }
+        let mut info = info.clone();
+        if let Some(info) = &mut info {
+            if let Some(code) = info.code.take() {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.codes.entry(info.code_hash).or_insert(code);
+                }
+            }
+        }
+        self.accounts.insert(address, info);
+    }
+}
+
+/// Database wrapper recording the [`Witness`] of the executions over the wrapped database.
+///
+/// Only the first read of a value is recorded, so the witness of several transactions committed
+/// to the wrapped database holds the state before the first one.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessRecorder<DB> {
+    /// The wrapped database.
+    pub db: DB,
+    /// The recorded witness.
+    pub witness: Witness,
+}
+
+impl<DB> WitnessRecorder<DB> {
+    /// Creates a recorder with an empty witness around the given database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: Witness::default(),
+        }
+    }
+
+    /// Takes the recorded witness, leaving an empty one.
+    pub fn take_witness(&mut self) -> Witness {
+        core::mem::take(&mut self.witness)
+    }
+
+    /// Consumes the recorder and returns the wrapped database and the recorded witness.
+    pub fn into_parts(self) -> (DB, Witness) {
+        (self.db, self.witness)
+    }
+}
+
+impl<DB: Database> Database for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.witness.record_account(address, &info);
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness
+            .codes
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for WitnessRecorder<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Error of a read that is not in the [`Witness`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WitnessError {
+    /// The account is not in the witness.
+    MissingAccount(Address),
+    /// The storage slot is not in the witness.
+    MissingStorage(Address, U256),
+    /// The code is not in the witness.
+    MissingCode(B256),
+    /// The block hash is not in the witness.
+    MissingBlockHash(u64),
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => write!(f, "account {address} is not in the witness"),
+            Self::MissingStorage(address, index) => {
+                write!(f, "storage slot {index} of {address} is not in the witness")
+            }
+            Self::MissingCode(code_hash) => write!(f, "code {code_hash} is not in the witness"),
+            Self::MissingBlockHash(number) => {
+                write!(f, "hash of block {number} is not in the witness")
+            }
+        }
+    }
+}
+
+impl DBErrorMarker for WitnessError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WitnessError {}
+
+impl Database for Witness {
+    type Error = WitnessError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseRef for Witness {
+    type Error = WitnessError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.accounts
+            .get(&address)
+            .cloned()
+            .ok_or(WitnessError::MissingAccount(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.codes
+            .get(&code_hash)
+            .cloned()
+            .ok_or(WitnessError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+            .copied()
+            .ok_or(WitnessError::MissingStorage(address, index))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(WitnessError::MissingBlockHash(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, InMemoryDB},
+        interpreter::opcode::{
+            ADD, BLOCKHASH, CALL, EXTCODESIZE, GAS, NUMBER, PUSH0, PUSH1, PUSH20, SLOAD, SSTORE,
+            STOP, SUB,
+        },
+        primitives::{address, EVMError, ResultAndState, SpecId, TxKind},
+        Evm,
+    };
+    use std::{vec, vec::Vec};
+
+    const CALLER: Address = address!("0000000000000000000000000000000000000001");
+    const COUNTER: Address = address!("00000000000000000000000000000000000000c0");
+    const LIBRARY: Address = address!("00000000000000000000000000000000000000d0");
+
+    /// A counter that also stores the hash of the previous block and the code size of the
+    /// library, then calls the library, which increments its own counter.
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SSTORE(0, SLOAD(0) + 1), SSTORE(1, BLOCKHASH(NUMBER - 1)),
+        // SSTORE(2, EXTCODESIZE(LIBRARY)), CALL(GAS, LIBRARY, 0, 0, 0, 0, 0)
+        let mut code = vec![
+            PUSH1, 0, SLOAD, PUSH1, 1, ADD, PUSH1, 0, SSTORE, PUSH1, 1, NUMBER, SUB, BLOCKHASH,
+            PUSH1, 1, SSTORE, PUSH20,
+        ];
+        code.extend_from_slice(LIBRARY.as_slice());
+        code.extend([EXTCODESIZE, PUSH1, 2, SSTORE]);
+        code.extend([PUSH0, PUSH0, PUSH0, PUSH0, PUSH0, PUSH20]);
+        code.extend_from_slice(LIBRARY.as_slice());
+        code.extend([GAS, CALL, STOP]);
+        let library = [PUSH1, 0, SLOAD, PUSH1, 1, ADD, PUSH1, 0, SSTORE, STOP];
+        for (address, code) in [(COUNTER, code), (LIBRARY, library.to_vec())] {
+            let code = Bytecode::new_legacy(code.into());
+            db.insert_account_info(address, AccountInfo::from_bytecode(code));
+        }
+        db.insert_account_storage(COUNTER, U256::ZERO, U256::from(41))
+            .unwrap();
+        db.insert_account_storage(LIBRARY, U256::ZERO, U256::from(7))
+            .unwrap();
+        db
+    }
+
+    /// Executes and commits a call to the counter for every nonce, returning the results.
+    fn execute<DB: Database + DatabaseCommit>(
+        db: DB,
+        nonces: u64,
+    ) -> Result<(Vec<ResultAndState>, DB), EVMError<DB::Error>> {
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_block_env(|block| block.number = U256::from(10))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TxKind::Call(COUNTER);
+                tx.gas_limit = 200_000;
+            })
+            .build();
+        let mut results = Vec::new();
+        for nonce in 0..nonces {
+            evm.tx_mut().nonce = Some(nonce);
+            let result = evm.transact()?;
+            assert!(result.result.is_success(), "{:?}", result.result);
+            evm.db_mut().commit(result.state.clone());
+            results.push(result);
+        }
+        Ok((results, evm.into_db_and_env_with_handler_cfg().0))
+    }
+
+    #[test]
+    fn reexecutes_from_witness() {
+        let (results, recorder) = execute(WitnessRecorder::new(db()), 2).unwrap();
+        let (_, witness) = recorder.into_parts();
+
+        // The first read values are kept even though the transactions overwrote them.
+        assert_eq!(witness.storage[&COUNTER][&U256::ZERO], U256::from(41));
+        assert_eq!(witness.storage[&LIBRARY][&U256::ZERO], U256::from(7));
+        assert_eq!(witness.accounts[&CALLER].as_ref().unwrap().nonce, 0);
+        assert!(witness.block_hashes.contains_key(&9));
+        // The code returned with the accounts is moved to the codes.
+        for address in [COUNTER, LIBRARY] {
+            let info = witness.accounts[&address].as_ref().unwrap();
+            assert!(info.code.is_none());
+            assert!(witness.codes.contains_key(&info.code_hash));
+        }
+
+        // The witness alone reproduces the transactions.
+        let (replayed, _) = execute(CacheDB::new(witness), 2).unwrap();
+        assert_eq!(replayed, results);
+    }
+
+    #[test]
+    fn missing_witness_fails() {
+        let (_, recorder) = execute(WitnessRecorder::new(db()), 1).unwrap();
+        let (_, mut witness) = recorder.into_parts();
+        witness.storage.remove(&LIBRARY);
+        assert_eq!(
+            execute(CacheDB::new(witness), 1).unwrap_err(),
+            EVMError::Database(WitnessError::MissingStorage(LIBRARY, U256::ZERO))
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn witness_serde_round_trip() {
+        let (results, recorder) = execute(WitnessRecorder::new(db()), 1).unwrap();
+        let json = serde_json::to_string(&recorder.witness).unwrap();
+        let witness: Witness = serde_json::from_str(&json).unwrap();
+        assert_eq!(witness, recorder.witness);
+        assert_eq!(execute(CacheDB::new(witness), 1).unwrap().0, results);
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn records_l1_block_slots() {
+        use crate::{optimism::L1_BLOCK_CONTRACT, primitives::bytes};
+
+        fn execute<DB: Database>(db: DB) -> Result<ResultAndState, EVMError<DB::Error>> {
+            Evm::builder()
+                .with_db(db)
+                .optimism()
+                .with_spec_id(SpecId::ISTHMUS)
+                .modify_tx_env(|tx| {
+                    tx.caller = CALLER;
+                    tx.transact_to = TxKind::Call(COUNTER);
+                    tx.gas_limit = 200_000;
+                    tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+                })
+                .build()
+                .transact()
+        }
+        let mut recorder = WitnessRecorder::new(db());
+        let result = execute(&mut recorder).unwrap();
+        assert!(result.result.is_success(), "{:?}", result.result);
+
+        // Base fee, fee scalars, overhead (as the scalars are empty), blob base fee and operator
+        // fee scalars of the L1 block contract.
+        let witness = recorder.take_witness();
+        assert!(witness.accounts.contains_key(&L1_BLOCK_CONTRACT));
+        assert_eq!(
+            witness.storage[&L1_BLOCK_CONTRACT]
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            [1u64, 3, 5, 7, 8].map(U256::from)
+        );
+
+        assert_eq!(execute(&mut witness.clone()), Ok(result));
+    }
+}
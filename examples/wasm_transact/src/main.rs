@@ -0,0 +1,71 @@
+//! Runs a transaction with revm compiled to WebAssembly.
+//!
+//! Build it for WASI and run it with wasmtime:
+//!
+//! ```sh
+//! rustup target add wasm32-wasip1
+//! cargo build --target wasm32-wasip1 --release
+//! wasmtime target/wasm32-wasip1/release/revm-wasm-transact.wasm
+//! ```
+use revm::{
+    db::{CacheDB, EmptyDB},
+    interpreter::opcode,
+    primitives::{
+        address, AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output, TxKind, U256,
+    },
+    Evm,
+};
+
+const CALLER: Address = address!("1000000000000000000000000000000000000000");
+const CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+
+fn main() {
+    // Stores the call value in slot 0 and returns it.
+    let code = Bytes::from(vec![
+        opcode::CALLVALUE,
+        opcode::DUP1,
+        opcode::PUSH1,
+        0x00,
+        opcode::SSTORE,
+        opcode::PUSH1,
+        0x00,
+        opcode::MSTORE,
+        opcode::PUSH1,
+        0x20,
+        opcode::PUSH1,
+        0x00,
+        opcode::RETURN,
+    ]);
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+    db.insert_account_info(
+        CONTRACT,
+        AccountInfo::from_bytecode(Bytecode::new_raw(code)),
+    );
+
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .modify_tx_env(|tx| {
+            tx.caller = CALLER;
+            tx.transact_to = TxKind::Call(CONTRACT);
+            tx.value = U256::from(42);
+            tx.gas_limit = 100_000;
+        })
+        .build();
+
+    let result = evm.transact_commit().expect("transaction is valid");
+    let ExecutionResult::Success {
+        gas_used,
+        output: Output::Call(output),
+        ..
+    } = &result
+    else {
+        panic!("transaction failed: {result:?}");
+    };
+    assert_eq!(U256::from_be_slice(output), U256::from(42));
+
+    let stored = evm.db().accounts[&CONTRACT].storage[&U256::ZERO];
+    assert_eq!(stored, U256::from(42));
+    println!("stored {stored} using {gas_used} gas");
+}
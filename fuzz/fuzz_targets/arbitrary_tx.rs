@@ -0,0 +1,10 @@
+//! Executes arbitrary transactions and checks execution invariants.
+//!
+//! Run with `cargo +nightly fuzz run arbitrary_tx` from the `fuzz` directory.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    revm::fuzz::run_arbitrary_tx(data);
+});
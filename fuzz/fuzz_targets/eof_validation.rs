@@ -0,0 +1,28 @@
+//! Decodes and validates arbitrary bytes as an EOF container.
+//!
+//! Run with `cargo +nightly fuzz run eof_validation` from the `fuzz` directory.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use revm_interpreter::{
+    analysis::{validate_eof, validate_raw_eof_inner, ValidationKind},
+    primitives::{Bytes, Eof},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let raw = Bytes::copy_from_slice(data);
+    let _ = Eof::decode_dangling(raw.clone());
+    let _ = validate_raw_eof_inner(raw.clone(), None);
+
+    let Ok(eof) = Eof::decode(raw) else {
+        return;
+    };
+    let _ = eof.encode_slow();
+    for kind in [
+        ValidationKind::Initcode,
+        ValidationKind::Runtime,
+        ValidationKind::Any,
+    ] {
+        let _ = validate_eof(&eof, kind);
+    }
+});